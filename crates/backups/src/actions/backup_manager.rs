@@ -17,8 +17,15 @@
 //! # Fields
 //!
 //! * `repository` - The Git repository used for managing backups.
+use crate::data::backup_file_entry::BackupFileEntry;
 use crate::data::backup_item::BackupItem;
 use crate::data::modified_file::ModifiedFile;
+use crate::data::retention_policy::RetentionPolicy;
+#[cfg(feature = "s3")]
+use crate::data::s3_credentials::S3Credentials;
+#[cfg(feature = "scheduler")]
+use crate::data::schedule_policy::{BackupSchedule, SchedulePolicy};
+use crate::data::verify_report::{CorruptObject, VerifyReport};
 use crate::log_stub::*;
 use anyhow::{Result, anyhow};
 use git2::{Oid, Repository, RepositoryInitOptions};
@@ -27,6 +34,26 @@ use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use sevenz_rust2::{ArchiveWriter, encoder_options};
 use std::fs;
 use std::path::Path;
+#[cfg(feature = "scheduler")]
+use std::path::PathBuf;
+#[cfg(feature = "scheduler")]
+use std::sync::Arc;
+#[cfg(feature = "scheduler")]
+use std::time::Duration;
+#[cfg(feature = "scheduler")]
+use obsidian_scheduler::{callback::CallbackTimer, timer_trait::Timer};
+
+/// Notes reference that holds the labels attached to backups, one label per line.
+const LABELS_NOTES_REF: &str = "refs/notes/labels";
+
+/// Compression level of the archives [`BackupManager::push_to_s3`] uploads.
+#[cfg(feature = "s3")]
+const S3_COMPRESSION_LEVEL: u8 = 5;
+
+/// Archives larger than this are uploaded in parts of this size. S3 requires at least 5 MiB
+/// for every part but the last.
+#[cfg(feature = "s3")]
+const S3_PART_SIZE: usize = 8 * 1024 * 1024;
 
 /// `BackupManager` is a struct responsible for managing backup operations.
 ///
@@ -47,6 +74,8 @@ use std::path::Path;
 pub struct BackupManager {
     repository: Repository,
     ignore_matcher: Option<Gitignore>,
+    /// Patterns added so far, kept so later ignore files and patterns can layer on top
+    ignore_builder: Option<GitignoreBuilder>,
 }
 
 impl BackupManager {
@@ -188,9 +217,61 @@ impl BackupManager {
         Ok(Self {
             repository,
             ignore_matcher: None,
+            ignore_builder: None,
         })
     }
 
+    /// Sets the zlib compression level (0-9) of the backup store.
+    ///
+    /// The level is saved in the store's git config as `core.compression`,
+    /// `core.looseCompression`, and `pack.compression`, so it persists across managers. Higher
+    /// levels shrink large worlds at the cost of slower packing.
+    ///
+    /// libgit2, which this crate is built on, doesn't read these settings: the packs written
+    /// after a purge use zlib's default level whatever is configured. The level takes effect
+    /// when the store is maintained with the `git` command line, such as `git gc` or
+    /// `git repack -adf` run in the store directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the level is above 9 or the config can't be written.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use obsidian_backups::BackupManager;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")
+    ///     .expect("Failed to initialize BackupManager");
+    /// manager.set_compression(9).expect("Failed to set compression");
+    /// ```
+    pub fn set_compression(&self, level: u32) -> Result<()> {
+        if level > 9 {
+            return Err(anyhow!(
+                "Compression level must be between 0 and 9, got {}",
+                level
+            ));
+        }
+        info!("Setting store compression level to {}", level);
+
+        let mut config = self.repository.config()?;
+        for key in ["core.compression", "core.looseCompression", "pack.compression"] {
+            config.set_i32(key, level as i32)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the compression level set with [`set_compression`](Self::set_compression), or
+    /// `None` if the store uses git's default.
+    pub fn compression(&self) -> Result<Option<u32>> {
+        let config = self.repository.config()?.snapshot()?;
+        match config.get_i32("pack.compression") {
+            Ok(level) => Ok(Some(level.max(0) as u32)),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     /// Sets up a `.gitignore`-style ignore file for the repository using the provided file path.
     /// This function configures an ignore matcher to exclude specified paths or patterns.
     ///
@@ -229,7 +310,9 @@ impl BackupManager {
     /// ```
     pub fn setup_ignore_file(&mut self, ignore_file: impl AsRef<Path>) -> Result<()> {
         let working_directory = self.repository.workdir().unwrap_or(Path::new("./"));
-        let mut builder = GitignoreBuilder::new(working_directory);
+        let builder = self
+            .ignore_builder
+            .insert(GitignoreBuilder::new(working_directory));
 
         let ignore_file = ignore_file.as_ref();
 
@@ -238,6 +321,68 @@ impl BackupManager {
         {
             warn!("Failed to add ignore file {ignore_file:?}: {e}");
         }
+        self.build_ignore_matcher()
+    }
+
+    /// Adds another `.gitignore`-style ignore file on top of the patterns already configured.
+    ///
+    /// Unlike [`setup_ignore_file`](Self::setup_ignore_file), which replaces any earlier
+    /// patterns, this keeps them, so a global default ignore file can be combined with a
+    /// per-server one. As in git, when patterns disagree the one added last wins, so a later
+    /// `!pattern` can re-include a path an earlier file ignores.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or contains an invalid pattern.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use obsidian_backups::BackupManager;
+    ///
+    /// let mut manager = BackupManager::new("./backup_store", "./my_data")
+    ///     .expect("Failed to initialize BackupManager");
+    /// manager.add_ignore_file("./defaults.ignore").expect("Failed to add ignore file");
+    /// manager.add_ignore_file("./my_data/.backupignore").expect("Failed to add ignore file");
+    /// manager.add_ignore_pattern("*.log").expect("Failed to add ignore pattern");
+    /// ```
+    pub fn add_ignore_file(&mut self, ignore_file: impl AsRef<Path>) -> Result<()> {
+        let ignore_file = ignore_file.as_ref();
+        debug!("Adding ignore file {:?}", ignore_file);
+        if let Some(e) = self.ignore_builder().add(ignore_file) {
+            return Err(anyhow!("Failed to add ignore file {ignore_file:?}: {e}"));
+        }
+        self.build_ignore_matcher()
+    }
+
+    /// Adds a single `.gitignore`-style pattern on top of the patterns already configured.
+    ///
+    /// See [`add_ignore_file`](Self::add_ignore_file) for how patterns are layered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is invalid.
+    pub fn add_ignore_pattern(&mut self, pattern: impl AsRef<str>) -> Result<()> {
+        let pattern = pattern.as_ref();
+        debug!("Adding ignore pattern {:?}", pattern);
+        self.ignore_builder()
+            .add_line(None, pattern)
+            .map_err(|e| anyhow!("Invalid ignore pattern {pattern:?}: {e}"))?;
+        self.build_ignore_matcher()
+    }
+
+    /// Helper method to get the ignore builder, creating an empty one rooted at the working directory
+    fn ignore_builder(&mut self) -> &mut GitignoreBuilder {
+        let working_directory = self.repository.workdir().unwrap_or(Path::new("./"));
+        self.ignore_builder
+            .get_or_insert_with(|| GitignoreBuilder::new(working_directory))
+    }
+
+    /// Helper method to rebuild the ignore matcher from every pattern added so far
+    fn build_ignore_matcher(&mut self) -> Result<()> {
+        let Some(builder) = &self.ignore_builder else {
+            return Ok(());
+        };
         match builder.build() {
             Ok(ignore_matcher) => {
                 self.ignore_matcher = Some(ignore_matcher);
@@ -328,6 +473,7 @@ impl BackupManager {
                             .message()
                             .unwrap_or("No description was provided")
                             .to_string(),
+                        size: None,
                     };
                     trace!(
                         "Created backup item: id={}, timestamp={}, description={:?}",
@@ -346,6 +492,72 @@ impl BackupManager {
         Ok(items)
     }
 
+    /// Lists all backups like [`list`](Self::list), with each item's `size` filled in.
+    ///
+    /// This reads the size of every file in every backup, so it is slower than `list` on stores
+    /// with many backups. See [`backup_size`](Self::backup_size).
+    pub fn list_with_sizes(&self) -> Result<Vec<BackupItem>> {
+        self.list()?
+            .into_iter()
+            .map(|mut item| {
+                item.size = Some(self.backup_size(&item.id)?);
+                Ok(item)
+            })
+            .collect()
+    }
+
+    /// Returns the total uncompressed size, in bytes, of the files in a backup.
+    ///
+    /// This is the size the backup occupies once restored, not the space it takes in the store,
+    /// where unchanged files are shared between backups and objects are compressed. Only object
+    /// headers are read, so file contents aren't loaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backup ID is invalid or the backup's objects can't be read.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use obsidian_backups::BackupManager;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")
+    ///     .expect("Failed to initialize BackupManager");
+    ///
+    /// let last_backup = manager.last().unwrap().expect("No backups found");
+    /// let size = manager.backup_size(&last_backup.id).expect("Failed to get backup size");
+    /// println!("Backup {} holds {} bytes", last_backup.id, size);
+    /// ```
+    pub fn backup_size(&self, backup_id: impl AsRef<str>) -> Result<u64> {
+        let backup_id = backup_id.as_ref();
+        debug!("Calculating size of backup {}", backup_id);
+        let oid = Oid::from_str(backup_id)?;
+        let tree = self.repository.find_commit(oid)?.tree()?;
+        let odb = self.repository.odb()?;
+
+        let mut size = 0u64;
+        let mut error = None;
+        let walked = tree.walk(git2::TreeWalkMode::PreOrder, |_, entry| {
+            if entry.kind() == Some(git2::ObjectType::Blob) {
+                match odb.read_header(entry.id()) {
+                    Ok((length, _)) => size += length as u64,
+                    Err(e) => {
+                        error = Some(e);
+                        return git2::TreeWalkResult::Abort;
+                    }
+                }
+            }
+            git2::TreeWalkResult::Ok
+        });
+        if let Some(e) = error {
+            return Err(e.into());
+        }
+        walked?;
+
+        trace!("Backup {} holds {} bytes", backup_id, size);
+        Ok(size)
+    }
+
     fn list_ids(&self) -> Result<Vec<String>> {
         let mut rev_walk = self.repository.revwalk()?;
         // Try HEAD first; if it fails, fall back to any available reference target.
@@ -434,6 +646,27 @@ impl BackupManager {
     pub fn backup(&self, description: Option<String>) -> Result<String> {
         info!("Creating backup with description: {:?}", description);
 
+        let tree_id = self.stage_working_directory()?;
+        self.commit_tree(tree_id, description)
+    }
+
+    /// Creates a backup like [`backup`](Self::backup), unless nothing changed since the last one.
+    ///
+    /// Returns `Ok(None)` without creating a commit when the working directory matches the most
+    /// recent backup, so unattended backups don't pile up identical snapshots.
+    pub fn backup_if_changed(&self, description: Option<String>) -> Result<Option<String>> {
+        let tree_id = self.stage_working_directory()?;
+        if let Ok(head) = self.repository.head()
+            && head.peel_to_tree()?.id() == tree_id
+        {
+            info!("No changes since the last backup, skipping");
+            return Ok(None);
+        }
+        self.commit_tree(tree_id, description).map(Some)
+    }
+
+    /// Stages the whole working directory, honoring exclusions, and writes it as a tree.
+    fn stage_working_directory(&self) -> Result<Oid> {
         debug!("Getting repository index");
         let mut index = self.repository.index()?;
 
@@ -458,7 +691,11 @@ impl BackupManager {
         debug!("Creating tree from index");
         let tree_id = index.write_tree()?;
         debug!("Tree created with ID: {}", tree_id);
+        Ok(tree_id)
+    }
 
+    /// Commits a staged tree on top of HEAD.
+    fn commit_tree(&self, tree_id: Oid, description: Option<String>) -> Result<String> {
         let tree = self.repository.find_tree(tree_id)?;
         let head = self.repository.head();
 
@@ -563,6 +800,36 @@ impl BackupManager {
     /// }
     /// ```
     pub fn restore(&self, backup_id: impl AsRef<str>) -> Result<()> {
+        self.restore_with_progress(backup_id, |_, _| {})
+    }
+
+    /// Restores a backup like [`restore`](Self::restore), reporting progress as files are
+    /// checked out.
+    ///
+    /// `progress` is called with the number of files checked out so far and the total number
+    /// that need checking out. Files that already match the backup aren't counted, so restoring
+    /// over an unchanged working directory reports a total of zero.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use obsidian_backups::BackupManager;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")
+    ///     .expect("Failed to initialize BackupManager");
+    ///
+    /// let last_backup = manager.last().unwrap().expect("No backups found");
+    /// manager
+    ///     .restore_with_progress(&last_backup.id, |completed, total| {
+    ///         println!("Restored {}/{} files", completed, total);
+    ///     })
+    ///     .expect("Failed to restore backup");
+    /// ```
+    pub fn restore_with_progress(
+        &self,
+        backup_id: impl AsRef<str>,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<()> {
         let backup_id = backup_id.as_ref();
         info!("Restoring backup with ID: {}", backup_id);
 
@@ -585,6 +852,7 @@ impl BackupManager {
             checkout_opts.force();
             checkout_opts.remove_untracked(true);
             checkout_opts.recreate_missing(true);
+            checkout_opts.progress(|_, completed, total| progress(completed, total));
 
             self.repository
                 .checkout_tree(tree.as_object(), Some(&mut checkout_opts))?;
@@ -594,13 +862,174 @@ impl BackupManager {
             warn!("No working directory configured for repository");
             // For bare repositories, just update HEAD
             debug!("Checking out tree in bare repository");
-            self.repository.checkout_tree(tree.as_object(), None)?;
+            let mut checkout_opts = git2::build::CheckoutBuilder::new();
+            checkout_opts.progress(|_, completed, total| progress(completed, total));
+            self.repository
+                .checkout_tree(tree.as_object(), Some(&mut checkout_opts))?;
         }
 
         info!("Backup restored successfully");
         Ok(())
     }
 
+    /// Restores a backup into `target` instead of the working directory.
+    ///
+    /// The directory is created if needed and receives every file in the backup, overwriting
+    /// files of the same name; other files already in `target` are left alone. The working
+    /// directory and the repository index are not touched, so this is safe to use for
+    /// restoring a copy for inspection while the original keeps running.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backup ID is invalid or not found, or if `target` can't be written.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use obsidian_backups::BackupManager;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")
+    ///     .expect("Failed to initialize BackupManager");
+    ///
+    /// let last_backup = manager.last().unwrap().expect("No backups found");
+    /// manager.restore_to(&last_backup.id, "./inspect")
+    ///     .expect("Failed to restore backup copy");
+    /// ```
+    pub fn restore_to(&self, backup_id: impl AsRef<str>, target: impl AsRef<Path>) -> Result<()> {
+        let backup_id = backup_id.as_ref();
+        let target = target.as_ref();
+        info!("Restoring backup {} to {:?}", backup_id, target);
+
+        let oid = Oid::from_str(backup_id)?;
+        let tree = self.repository.find_commit(oid)?.tree()?;
+
+        fs::create_dir_all(target)?;
+        let target = fs::canonicalize(target)?;
+        debug!("Checking out tree {} to {:?}", tree.id(), target);
+        let mut checkout_opts = git2::build::CheckoutBuilder::new();
+        checkout_opts.target_dir(&target);
+        checkout_opts.force();
+        checkout_opts.recreate_missing(true);
+        checkout_opts.update_index(false);
+        self.repository
+            .checkout_tree(tree.as_object(), Some(&mut checkout_opts))?;
+
+        info!("Backup restored to {:?}", target);
+        Ok(())
+    }
+
+    /// Restores a single file from a backup, returning the path it was written to.
+    ///
+    /// `file_path` is relative to the working directory, e.g. `world/level.dat`. The file is
+    /// written to `dest` if given, otherwise back to its original location in the working
+    /// directory; missing parent directories are created and an existing file is overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backup ID is invalid or not found, or if `file_path` is not a
+    /// file in that backup (it is missing or a directory).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use obsidian_backups::BackupManager;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")
+    ///     .expect("Failed to initialize BackupManager");
+    ///
+    /// let last_backup = manager.last().unwrap().expect("No backups found");
+    /// manager.restore_file(&last_backup.id, "config/settings.toml", None)
+    ///     .expect("Failed to restore file");
+    /// ```
+    pub fn restore_file(
+        &self,
+        backup_id: impl AsRef<str>,
+        file_path: impl AsRef<Path>,
+        dest: Option<&Path>,
+    ) -> Result<std::path::PathBuf> {
+        let backup_id = backup_id.as_ref();
+        let file_path = file_path.as_ref();
+        info!("Restoring {:?} from backup {}", file_path, backup_id);
+
+        let blob = self.find_file(backup_id, file_path)?;
+
+        let dest = match dest {
+            Some(dest) => dest.to_path_buf(),
+            None => self
+                .repository
+                .workdir()
+                .ok_or_else(|| anyhow!("Repository has no working directory"))?
+                .join(file_path),
+        };
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        debug!("Writing {} bytes to {:?}", blob.content().len(), dest);
+        fs::write(&dest, blob.content())?;
+
+        info!("Restored {:?} to {:?}", file_path, dest);
+        Ok(dest)
+    }
+
+    /// Reads the content of a single file in a backup without touching the filesystem.
+    ///
+    /// Useful for previewing a file before deciding to restore it with
+    /// [`restore_file`](Self::restore_file).
+    ///
+    /// # Arguments
+    ///
+    /// * `backup_id` - The ID of the backup to read from.
+    /// * `file_path` - Path of the file relative to the working directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backup ID is invalid, or the path is a directory or isn't in
+    /// the backup.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use obsidian_backups::BackupManager;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")
+    ///     .expect("Failed to initialize BackupManager");
+    ///
+    /// let last_backup = manager.last().unwrap().expect("No backups found");
+    /// let content = manager.read_file(&last_backup.id, "server.properties")
+    ///     .expect("Failed to read file");
+    /// println!("{}", String::from_utf8_lossy(&content));
+    /// ```
+    pub fn read_file(
+        &self,
+        backup_id: impl AsRef<str>,
+        file_path: impl AsRef<Path>,
+    ) -> Result<Vec<u8>> {
+        let backup_id = backup_id.as_ref();
+        let file_path = file_path.as_ref();
+        debug!("Reading {:?} from backup {}", file_path, backup_id);
+
+        let blob = self.find_file(backup_id, file_path)?;
+        Ok(blob.content().to_vec())
+    }
+
+    /// Helper method to look up the blob of a file in a backup
+    fn find_file(&self, backup_id: &str, file_path: &Path) -> Result<git2::Blob<'_>> {
+        let oid = Oid::from_str(backup_id)?;
+        let tree = self.repository.find_commit(oid)?.tree()?;
+        let entry = tree
+            .get_path(file_path)
+            .map_err(|_| anyhow!("{:?} is not in backup {}", file_path, backup_id))?;
+        match entry.kind() {
+            Some(git2::ObjectType::Blob) => Ok(self.repository.find_blob(entry.id())?),
+            Some(git2::ObjectType::Tree) => Err(anyhow!(
+                "{:?} is a directory in backup {}",
+                file_path,
+                backup_id
+            )),
+            _ => Err(anyhow!("{:?} is not a file in backup {}", file_path, backup_id)),
+        }
+    }
+
     /// Exports a backup identified by its ID into a compressed archive.
     ///
     /// This function retrieves a backup commit from the Git repository using the provided `backup_id`,
@@ -767,6 +1196,433 @@ impl BackupManager {
         Ok(())
     }
 
+    /// Checks that an exported 7z archive is readable and complete.
+    ///
+    /// Every entry is decompressed and checked against the CRC stored in the archive, so a
+    /// truncated upload or a damaged copy is caught before the archive is needed for a restore.
+    ///
+    /// # Arguments
+    ///
+    /// * `archive_path` - Path to a `.7z` archive created by [`export`](Self::export)
+    ///
+    /// # Returns
+    ///
+    /// The paths of the files in the archive, in the order they were stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive can't be opened, is truncated, or any entry fails to
+    /// decompress or doesn't match its checksum.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use obsidian_backups::BackupManager;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")
+    ///     .expect("Failed to initialize BackupManager");
+    ///
+    /// let files = manager.verify_export("backup.7z").expect("Archive is corrupt");
+    /// println!("Archive holds {} files", files.len());
+    /// ```
+    #[cfg(feature = "zip")]
+    pub fn verify_export(&self, archive_path: impl AsRef<Path>) -> Result<Vec<String>> {
+        let archive_path = archive_path.as_ref();
+        info!("Verifying exported archive {:?}", archive_path);
+
+        let mut reader =
+            sevenz_rust2::ArchiveReader::open(archive_path, sevenz_rust2::Password::empty())
+                .map_err(|e| anyhow!("Failed to open archive {archive_path:?}: {e}"))?;
+
+        let mut files = Vec::new();
+        reader
+            .for_each_entries(|entry, data| {
+                // Reading an entry to the end makes the decoder verify its CRC
+                std::io::copy(data, &mut std::io::sink())?;
+                if !entry.is_directory() {
+                    files.push(entry.name().to_string());
+                }
+                Ok(true)
+            })
+            .map_err(|e| {
+                error!("Archive {:?} failed verification: {}", archive_path, e);
+                anyhow!("Archive {archive_path:?} is corrupt: {e}")
+            })?;
+
+        info!("Archive {:?} verified with {} files", archive_path, files.len());
+        Ok(files)
+    }
+
+    /// Exports a backup as a gzip-compressed tar archive.
+    ///
+    /// Unlike [`export`](Self::export), this needs no 7z tooling to extract, which makes it the
+    /// more convenient format on Linux. Files keep their executable bit and are stamped with the
+    /// backup's time.
+    ///
+    /// # Arguments
+    ///
+    /// * `backup_id` - The ID of the backup to export.
+    /// * `output_path` - Where to write the `.tar.gz` file.
+    /// * `level` - Gzip compression level (0-9, clamped to this range).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backup ID is invalid, the backup can't be read, or the archive
+    /// can't be written.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use obsidian_backups::BackupManager;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")
+    ///     .expect("Failed to initialize BackupManager");
+    ///
+    /// let last_backup = manager.last().unwrap().expect("No backups found");
+    /// manager.export_targz(&last_backup.id, "backup.tar.gz", 6)
+    ///     .expect("Failed to export backup");
+    /// ```
+    #[cfg(feature = "targz")]
+    pub fn export_targz(
+        &self,
+        backup_id: impl AsRef<str>,
+        output_path: impl AsRef<Path>,
+        level: u8,
+    ) -> Result<()> {
+        let file = fs::File::create(output_path)?;
+        self.export_targz_to_stream(backup_id, file, level)
+    }
+
+    /// Exports a backup as a gzip-compressed tar archive to a writer.
+    ///
+    /// The stream counterpart of [`export_targz`](Self::export_targz). The tar format is
+    /// written sequentially, so unlike the 7z export the writer doesn't need to be seekable and
+    /// can be a socket or an HTTP response body.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backup ID is invalid, the backup can't be read, or writing to the
+    /// stream fails.
+    #[cfg(feature = "targz")]
+    pub fn export_targz_to_stream<W: std::io::Write>(
+        &self,
+        backup_id: impl AsRef<str>,
+        writer: W,
+        level: u8,
+    ) -> Result<()> {
+        // Validate and clamp compression level to 0-9 range
+        let level = level.clamp(0, 9);
+
+        let backup_id = backup_id.as_ref();
+        info!("Exporting backup with ID: {} to tar.gz", backup_id);
+        let oid = Oid::from_str(backup_id)?;
+        let commit = self.repository.find_commit(oid)?;
+        let tree = commit.tree()?;
+
+        let encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::new(level as u32));
+        let mut builder = tar::Builder::new(encoder);
+        self.add_tree_to_tar(&mut builder, &tree, commit.time().seconds().max(0) as u64)?;
+
+        debug!("Finalizing tar.gz archive");
+        builder.into_inner()?.finish()?;
+
+        info!("Archive created successfully");
+        Ok(())
+    }
+
+    /// Extracts an exported archive into the working directory and backs it up.
+    ///
+    /// This is the counterpart of [`export`](Self::export) and
+    /// [`export_targz`](Self::export_targz), letting a backup be moved to another machine or
+    /// into a fresh store. The format is chosen by extension: `.7z` needs the `zip` feature,
+    /// `.tar.gz` and `.tgz` need the `targz` feature.
+    ///
+    /// Extracted files overwrite files of the same name; other files in the working directory
+    /// are left alone and end up in the new backup too, so import into an empty directory to
+    /// reproduce the archive exactly.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the backup created from the imported files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the format is unsupported or its feature is disabled, the archive
+    /// can't be extracted, or the backup fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use obsidian_backups::BackupManager;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")
+    ///     .expect("Failed to initialize BackupManager");
+    ///
+    /// let backup_id = manager.import_archive("backup.tar.gz")
+    ///     .expect("Failed to import archive");
+    /// println!("Imported as backup {}", backup_id);
+    /// ```
+    #[cfg(any(feature = "zip", feature = "targz"))]
+    pub fn import_archive(&self, archive_path: impl AsRef<Path>) -> Result<String> {
+        let archive_path = archive_path.as_ref();
+        let workdir = self
+            .repository
+            .workdir()
+            .ok_or_else(|| anyhow!("Repository has no working directory"))?;
+        let file_name = archive_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let lowercase_name = file_name.to_lowercase();
+        info!("Importing archive {} into {}", archive_path.display(), workdir.display());
+
+        if lowercase_name.ends_with(".tar.gz") || lowercase_name.ends_with(".tgz") {
+            #[cfg(feature = "targz")]
+            {
+                let decoder = flate2::read::GzDecoder::new(fs::File::open(archive_path)?);
+                tar::Archive::new(decoder).unpack(workdir).map_err(|e| {
+                    anyhow!("Failed to extract {}: {}", archive_path.display(), e)
+                })?;
+            }
+            #[cfg(not(feature = "targz"))]
+            return Err(anyhow!("Importing tar.gz archives requires the `targz` feature"));
+        } else if lowercase_name.ends_with(".7z") {
+            #[cfg(feature = "zip")]
+            sevenz_rust2::decompress_file(archive_path, workdir).map_err(|e| {
+                anyhow!("Failed to extract {}: {}", archive_path.display(), e)
+            })?;
+            #[cfg(not(feature = "zip"))]
+            return Err(anyhow!("Importing 7z archives requires the `zip` feature"));
+        } else {
+            return Err(anyhow!(
+                "Unsupported archive format: {}",
+                archive_path.display()
+            ));
+        }
+
+        debug!("Archive extracted, creating backup");
+        self.backup(Some(format!("Imported from {}", file_name)))
+    }
+
+    /// Exports a backup as a 7z archive and uploads it to S3-compatible storage.
+    ///
+    /// The archive is built in memory, then uploaded as `key` in `bucket` with a single request,
+    /// or with a multipart upload when it's larger than 8 MiB. Set
+    /// [`S3Credentials::endpoint`] to upload to MinIO, Cloudflare R2, or another S3-compatible
+    /// service instead of AWS.
+    ///
+    /// The export happens when this is called; the returned future only performs the upload, so
+    /// it can be spawned on a Tokio runtime without borrowing the manager.
+    ///
+    /// # Errors
+    ///
+    /// The future fails if the backup can't be exported or any request to the endpoint fails.
+    /// A failed multipart upload is aborted so the parts don't linger in the bucket.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use obsidian_backups::BackupManager;
+    /// use obsidian_backups::data::s3_credentials::S3Credentials;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")?;
+    /// let last_backup = manager.last()?.expect("No backups found");
+    ///
+    /// let credentials = S3Credentials::new("access-key", "secret-key", "auto")
+    ///     .with_endpoint("https://<account>.r2.cloudflarestorage.com");
+    /// manager
+    ///     .push_to_s3(&last_backup.id, "backups", format!("{}.7z", last_backup.id), &credentials)
+    ///     .await?;
+    /// ```
+    #[cfg(feature = "s3")]
+    pub fn push_to_s3(
+        &self,
+        backup_id: impl AsRef<str>,
+        bucket: impl Into<String>,
+        key: impl Into<String>,
+        credentials: &S3Credentials,
+    ) -> impl Future<Output = Result<()>> + Send + 'static {
+        let backup_id = backup_id.as_ref();
+        info!("Pushing backup {} to S3", backup_id);
+
+        let mut archive = std::io::Cursor::new(Vec::new());
+        let archive = self
+            .export_to_stream(backup_id, &mut archive, S3_COMPRESSION_LEVEL)
+            .map(|()| archive.into_inner());
+        let client = s3_client(credentials);
+        let bucket = bucket.into();
+        let key = key.into();
+
+        async move {
+            let archive = archive?;
+            debug!("Uploading {} bytes to {}/{}", archive.len(), bucket, key);
+            upload_to_s3(&client, &bucket, &key, archive).await?;
+            info!("Backup uploaded to {}/{}", bucket, key);
+            Ok(())
+        }
+    }
+
+    /// Adds a git remote the backup history can be pushed to, or changes its URL if it exists.
+    ///
+    /// The remote is saved in the store's git config, so it persists across managers. Any URL
+    /// git understands works, such as `https://`, `ssh://`, `git@host:path`, or a local path to
+    /// a bare repository.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the remote, e.g. `offsite`
+    /// * `url` - Location of the remote repository
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name or URL is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use obsidian_backups::BackupManager;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")
+    ///     .expect("Failed to initialize BackupManager");
+    ///
+    /// manager
+    ///     .set_remote("offsite", "git@backups.example.com:survival.git")
+    ///     .expect("Failed to set remote");
+    /// manager.push("offsite").expect("Failed to push backups");
+    /// ```
+    pub fn set_remote(&self, name: impl AsRef<str>, url: impl AsRef<str>) -> Result<()> {
+        let name = name.as_ref();
+        let url = url.as_ref();
+        info!("Setting remote {} to {}", name, url);
+
+        match self.repository.find_remote(name) {
+            Ok(_) => self.repository.remote_set_url(name, url)?,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => {
+                self.repository.remote(name, url)?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+        Ok(())
+    }
+
+    /// Pushes every backup, and the labels on them, to a remote added with
+    /// [`set_remote`](Self::set_remote).
+    ///
+    /// Purging and renaming backups rewrite history, so the push is forced: the remote always
+    /// ends up with exactly the backups this store has.
+    ///
+    /// SSH remotes authenticate through the running SSH agent, and HTTPS remotes through the
+    /// git credential helper configured for the current user.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the remote doesn't exist, authentication fails, or the remote
+    /// rejects the push.
+    pub fn push(&self, remote: impl AsRef<str>) -> Result<()> {
+        let remote_name = remote.as_ref();
+        info!("Pushing backups to remote {}", remote_name);
+
+        let mut remote = self
+            .repository
+            .find_remote(remote_name)
+            .map_err(|e| anyhow!("Remote {remote_name} not found: {e}"))?;
+
+        let mut refspecs = vec!["+refs/heads/master:refs/heads/master".to_string()];
+        if self.repository.find_reference(LABELS_NOTES_REF).is_ok() {
+            refspecs.push(format!("+{LABELS_NOTES_REF}:{LABELS_NOTES_REF}"));
+        }
+
+        let config = self.repository.config()?;
+        let mut rejected = Vec::new();
+        {
+            let mut callbacks = git2::RemoteCallbacks::new();
+            let mut attempts = 0;
+            callbacks.credentials(move |url, username, allowed| {
+                // libgit2 asks again after a rejected credential, so give up rather than loop
+                attempts += 1;
+                if attempts > 3 {
+                    return Err(git2::Error::from_str("Authentication failed"));
+                }
+                if allowed.contains(git2::CredentialType::SSH_KEY) {
+                    git2::Cred::ssh_key_from_agent(username.unwrap_or("git"))
+                } else if allowed.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
+                    git2::Cred::credential_helper(&config, url, username)
+                } else {
+                    git2::Cred::default()
+                }
+            });
+            callbacks.push_update_reference(|refname, status| {
+                if let Some(status) = status {
+                    rejected.push(format!("{refname}: {status}"));
+                }
+                Ok(())
+            });
+
+            let mut push_options = git2::PushOptions::new();
+            push_options.remote_callbacks(callbacks);
+            debug!("Pushing refspecs {:?}", refspecs);
+            remote.push(&refspecs, Some(&mut push_options))?;
+        }
+
+        if !rejected.is_empty() {
+            error!("Remote {} rejected the push: {:?}", remote_name, rejected);
+            return Err(anyhow!(
+                "Remote {remote_name} rejected the push: {}",
+                rejected.join(", ")
+            ));
+        }
+
+        info!("Backups pushed to remote {}", remote_name);
+        Ok(())
+    }
+
+    /// Previews what [`restore`](Self::restore) would change, without touching the working
+    /// directory.
+    ///
+    /// Unlike [`diff`](Self::diff), which compares a backup with the backup before it, this
+    /// compares the backup with the working directory as it is now. In each returned
+    /// `ModifiedFile`, `content_before` is the file currently on disk and `content_after` is the
+    /// file the restore would leave, so a file the restore would create has no
+    /// `content_before` and a file it would delete has no `content_after`. Files excluded by the
+    /// ignore rules aren't reported.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backup ID is invalid, the repository has no working directory,
+    /// or the working directory can't be read.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use obsidian_backups::BackupManager;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")
+    ///     .expect("Failed to initialize BackupManager");
+    ///
+    /// let last_backup = manager.last().unwrap().expect("No backups found");
+    /// for file in manager.restore_preview(&last_backup.id).expect("Failed to preview restore") {
+    ///     match (&file.content_before, &file.content_after) {
+    ///         (None, Some(_)) => println!("create {}", file.path),
+    ///         (Some(_), None) => println!("delete {}", file.path),
+    ///         _ => println!("overwrite {}", file.path),
+    ///     }
+    /// }
+    /// ```
+    pub fn restore_preview(&self, backup_id: impl AsRef<str>) -> Result<Vec<ModifiedFile>> {
+        let backup_id = backup_id.as_ref();
+        info!("Previewing restore of backup {}", backup_id);
+        let oid = Oid::from_str(backup_id)?;
+        let tree = self.repository.find_commit(oid)?.tree()?;
+
+        debug!("Snapshotting working directory for comparison");
+        let current_tree = self.repository.find_tree(self.stage_working_directory()?)?;
+
+        let mut files = Vec::new();
+        self.diff_trees_recursive(&tree, Some(&current_tree), "", &mut files)?;
+        debug!("Restore would change {} files", files.len());
+        Ok(files)
+    }
+
     /// Computes the list of files that were modified (added, updated, or deleted)
     /// in the specified backup/commit within the repository.
     ///
@@ -990,42 +1846,310 @@ impl BackupManager {
             }
         }
 
-        Ok(())
+        Ok(())
+    }
+
+    /// Helper method to mark all files in a tree as deleted
+    fn mark_tree_as_deleted(
+        &self,
+        tree: &git2::Tree,
+        path_prefix: &str,
+        files: &mut Vec<ModifiedFile>,
+    ) -> Result<()> {
+        for entry in tree.iter() {
+            let name = entry.name().unwrap_or("");
+            let full_path = if path_prefix.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}/{}", path_prefix, name)
+            };
+
+            match entry.kind() {
+                Some(git2::ObjectType::Blob) => {
+                    let blob = self.repository.find_blob(entry.id())?;
+                    files.push(ModifiedFile {
+                        path: full_path,
+                        content_before: Some(blob.content().to_vec()),
+                        content_after: None,
+                    });
+                }
+                Some(git2::ObjectType::Tree) => {
+                    let subtree = self.repository.find_tree(entry.id())?;
+                    self.mark_tree_as_deleted(&subtree, &full_path, files)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+    /// Attaches a label, such as "pre-upgrade" or "manual", to a backup.
+    ///
+    /// A backup can carry any number of labels and a label can be on any number of backups.
+    /// Labels are stored as git notes, so they don't change the backup's ID, and they follow
+    /// their backup when purging rewrites history. Adding a label the backup already has does
+    /// nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backup doesn't exist, or the label is empty or spans several
+    /// lines.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use obsidian_backups::BackupManager;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")
+    ///     .expect("Failed to initialize BackupManager");
+    ///
+    /// let backup_id = manager.backup(Some("Before 1.21 upgrade".to_string()))
+    ///     .expect("Failed to create backup");
+    /// manager.tag_backup(&backup_id, "pre-upgrade").expect("Failed to tag backup");
+    ///
+    /// for backup in manager.find_by_tag("pre-upgrade").expect("Failed to find backups") {
+    ///     println!("{}: {}", backup.id, backup.description);
+    /// }
+    /// ```
+    pub fn tag_backup(&self, backup_id: impl AsRef<str>, label: impl AsRef<str>) -> Result<()> {
+        let backup_id = backup_id.as_ref();
+        let label = label.as_ref().trim();
+        if label.is_empty() || label.contains('\n') {
+            return Err(anyhow!("Invalid backup label: {:?}", label));
+        }
+
+        let oid = Oid::from_str(backup_id)?;
+        self.repository.find_commit(oid)?;
+        let mut labels = self.labels_of(oid)?;
+        if labels.iter().any(|existing| existing == label) {
+            debug!("Backup {} is already labeled {:?}", backup_id, label);
+            return Ok(());
+        }
+
+        info!("Labeling backup {} as {:?}", backup_id, label);
+        labels.push(label.to_string());
+        self.write_labels(oid, &labels)
+    }
+
+    /// Removes a label from a backup. Removing a label the backup doesn't have does nothing.
+    pub fn untag_backup(&self, backup_id: impl AsRef<str>, label: impl AsRef<str>) -> Result<()> {
+        let backup_id = backup_id.as_ref();
+        let label = label.as_ref().trim();
+        let oid = Oid::from_str(backup_id)?;
+
+        let mut labels = self.labels_of(oid)?;
+        let count = labels.len();
+        labels.retain(|existing| existing != label);
+        if labels.len() != count {
+            info!("Removing label {:?} from backup {}", label, backup_id);
+            self.write_labels(oid, &labels)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the labels attached to a backup, in the order they were added.
+    pub fn tags(&self, backup_id: impl AsRef<str>) -> Result<Vec<String>> {
+        self.labels_of(Oid::from_str(backup_id.as_ref())?)
+    }
+
+    /// Lists the backups carrying a label, newest first.
+    pub fn find_by_tag(&self, label: impl AsRef<str>) -> Result<Vec<BackupItem>> {
+        let label = label.as_ref().trim();
+        debug!("Finding backups labeled {:?}", label);
+        let mut items = Vec::new();
+        for item in self.list()? {
+            if self
+                .labels_of(Oid::from_str(&item.id)?)?
+                .iter()
+                .any(|existing| existing == label)
+            {
+                items.push(item);
+            }
+        }
+        Ok(items)
+    }
+
+    /// Lists the files and directories in a backup without restoring anything.
+    ///
+    /// Entries are returned in tree order: each directory is followed by its contents, and
+    /// entries within a directory are sorted by name. Only object headers are read to get file
+    /// sizes, so this stays cheap for large backups.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backup ID is invalid or the backup's objects can't be read.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use obsidian_backups::BackupManager;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")
+    ///     .expect("Failed to initialize BackupManager");
+    ///
+    /// let last_backup = manager.last().unwrap().expect("No backups found");
+    /// for entry in manager.list_files(&last_backup.id).expect("Failed to list files") {
+    ///     if entry.is_directory {
+    ///         println!("{}/", entry.path);
+    ///     } else {
+    ///         println!("{} ({} bytes)", entry.path, entry.size);
+    ///     }
+    /// }
+    /// ```
+    pub fn list_files(&self, backup_id: impl AsRef<str>) -> Result<Vec<BackupFileEntry>> {
+        let backup_id = backup_id.as_ref();
+        debug!("Listing files in backup {}", backup_id);
+        let oid = Oid::from_str(backup_id)?;
+        let tree = self.repository.find_commit(oid)?.tree()?;
+        let odb = self.repository.odb()?;
+
+        let mut entries = Vec::new();
+        let mut error = None;
+        let walked = tree.walk(git2::TreeWalkMode::PreOrder, |root, entry| {
+            let path = format!("{}{}", root, entry.name().unwrap_or(""));
+            match entry.kind() {
+                Some(git2::ObjectType::Tree) => entries.push(BackupFileEntry {
+                    path,
+                    size: 0,
+                    is_directory: true,
+                }),
+                Some(git2::ObjectType::Blob) => match odb.read_header(entry.id()) {
+                    Ok((size, _)) => entries.push(BackupFileEntry {
+                        path,
+                        size: size as u64,
+                        is_directory: false,
+                    }),
+                    Err(e) => {
+                        error = Some(e);
+                        return git2::TreeWalkResult::Abort;
+                    }
+                },
+                _ => {
+                    trace!("Skipping object type: {:?} for {}", entry.kind(), path);
+                }
+            }
+            git2::TreeWalkResult::Ok
+        });
+        if let Some(e) = error {
+            return Err(e.into());
+        }
+        walked?;
+
+        debug!("Found {} entries in backup {}", entries.len(), backup_id);
+        Ok(entries)
+    }
+
+    /// Checks that every object a backup needs is present and intact.
+    ///
+    /// Walks the backup's commit and tree, reading each tree and blob from the object database
+    /// and hashing its content to confirm it matches its ID. Problems are collected in the
+    /// report rather than returned as errors, so one damaged file doesn't hide the others.
+    /// Run this before relying on a backup to catch silent corruption of the store.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backup ID is invalid or its commit can't be read at all.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use obsidian_backups::BackupManager;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")
+    ///     .expect("Failed to initialize BackupManager");
+    ///
+    /// let last_backup = manager.last().unwrap().expect("No backups found");
+    /// let report = manager.verify(&last_backup.id).expect("Failed to verify backup");
+    /// for object in &report.corrupt_objects {
+    ///     eprintln!("{} ({}): {}", object.path, object.id, object.reason);
+    /// }
+    /// ```
+    pub fn verify(&self, backup_id: impl AsRef<str>) -> Result<VerifyReport> {
+        let backup_id = backup_id.as_ref();
+        info!("Verifying backup {}", backup_id);
+
+        let oid = Oid::from_str(backup_id)?;
+        let commit = self.repository.find_commit(oid)?;
+        let odb = self.repository.odb()?;
+        let mut report = VerifyReport {
+            backup_id: backup_id.to_string(),
+            objects_checked: 0,
+            corrupt_objects: Vec::new(),
+        };
+
+        if self.verify_object(&odb, oid, "", &mut report) {
+            self.verify_tree(&odb, commit.tree_id(), "", &mut report);
+        }
+
+        if report.is_healthy() {
+            info!("Backup {} verified: {} objects intact", backup_id, report.objects_checked);
+        } else {
+            warn!(
+                "Backup {} has {} corrupt objects",
+                backup_id,
+                report.corrupt_objects.len()
+            );
+        }
+        Ok(report)
+    }
+
+    /// Verifies every backup, newest first. See [`verify`](Self::verify).
+    pub fn verify_all(&self) -> Result<Vec<VerifyReport>> {
+        self.list_ids()?
+            .iter()
+            .map(|id| self.verify(id))
+            .collect()
     }
 
-    /// Helper method to mark all files in a tree as deleted
-    fn mark_tree_as_deleted(
-        &self,
-        tree: &git2::Tree,
-        path_prefix: &str,
-        files: &mut Vec<ModifiedFile>,
-    ) -> Result<()> {
+    /// Helper method to verify a tree and everything below it
+    fn verify_tree(&self, odb: &git2::Odb, tree_id: Oid, path: &str, report: &mut VerifyReport) {
+        if !self.verify_object(odb, tree_id, path, report) {
+            return;
+        }
+        let Ok(tree) = self.repository.find_tree(tree_id) else {
+            return;
+        };
+
         for entry in tree.iter() {
             let name = entry.name().unwrap_or("");
-            let full_path = if path_prefix.is_empty() {
+            let full_path = if path.is_empty() {
                 name.to_string()
             } else {
-                format!("{}/{}", path_prefix, name)
+                format!("{}/{}", path, name)
             };
 
             match entry.kind() {
                 Some(git2::ObjectType::Blob) => {
-                    let blob = self.repository.find_blob(entry.id())?;
-                    files.push(ModifiedFile {
-                        path: full_path,
-                        content_before: Some(blob.content().to_vec()),
-                        content_after: None,
-                    });
+                    self.verify_object(odb, entry.id(), &full_path, report);
                 }
                 Some(git2::ObjectType::Tree) => {
-                    let subtree = self.repository.find_tree(entry.id())?;
-                    self.mark_tree_as_deleted(&subtree, &full_path, files)?;
+                    self.verify_tree(odb, entry.id(), &full_path, report);
                 }
                 _ => {}
             }
         }
-        Ok(())
     }
+
+    /// Reads an object and checks its content hashes to its ID, recording it if it doesn't
+    fn verify_object(&self, odb: &git2::Odb, oid: Oid, path: &str, report: &mut VerifyReport) -> bool {
+        report.objects_checked += 1;
+        let reason = match odb.read(oid) {
+            Ok(object) => match Oid::hash_object(object.kind(), object.data()) {
+                Ok(actual) if actual == oid => return true,
+                Ok(actual) => format!("content hashes to {}", actual),
+                Err(e) => format!("could not hash content: {}", e.message()),
+            },
+            Err(e) => e.message().to_string(),
+        };
+
+        debug!("Corrupt object {} at {:?}: {}", oid, path, reason);
+        report.corrupt_objects.push(CorruptObject {
+            id: oid.to_string(),
+            path: path.to_string(),
+            reason,
+        });
+        false
+    }
+
     pub fn last(&self) -> Result<Option<BackupItem>> {
         // Check if HEAD exists first
         if self.repository.head().is_err() {
@@ -1045,6 +2169,7 @@ impl BackupManager {
                     .message()
                     .unwrap_or("No description was provided")
                     .to_string(),
+                size: None,
             };
             Ok(Some(item))
         } else {
@@ -1058,6 +2183,47 @@ impl BackupManager {
         writer: &mut ArchiveWriter<W>,
         tree: &git2::Tree,
         path_prefix: &str,
+    ) -> Result<()> {
+        self.for_each_file_in_tree(tree, path_prefix, &mut |full_path, _filemode, content| {
+            writer.push_archive_entry(
+                sevenz_rust2::ArchiveEntry::new_file(full_path),
+                Some(content),
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Helper method to add the files of a git tree to a tar archive
+    #[cfg(feature = "targz")]
+    fn add_tree_to_tar<W: std::io::Write>(
+        &self,
+        builder: &mut tar::Builder<W>,
+        tree: &git2::Tree,
+        mtime: u64,
+    ) -> Result<()> {
+        self.for_each_file_in_tree(tree, "", &mut |full_path, filemode, content| {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(tar::EntryType::Regular);
+            header.set_size(content.len() as u64);
+            header.set_mode(if filemode == i32::from(git2::FileMode::BlobExecutable) {
+                0o755
+            } else {
+                0o644
+            });
+            header.set_mtime(mtime);
+            builder.append_data(&mut header, full_path, content)?;
+            Ok(())
+        })
+    }
+
+    /// Walks a git tree recursively, calling `visit` with the path, file mode, and content of
+    /// each file
+    #[cfg(any(feature = "zip", feature = "targz"))]
+    fn for_each_file_in_tree<F: FnMut(&str, i32, &[u8]) -> Result<()>>(
+        &self,
+        tree: &git2::Tree,
+        path_prefix: &str,
+        visit: &mut F,
     ) -> Result<()> {
         for entry in tree.iter() {
             let name = entry.name().unwrap_or("");
@@ -1072,18 +2238,13 @@ impl BackupManager {
                     // It's a file
                     debug!("Adding file to archive: {}", full_path);
                     let blob = self.repository.find_blob(entry.id())?;
-                    let content = blob.content();
-
-                    writer.push_archive_entry(
-                        sevenz_rust2::ArchiveEntry::new_file(&full_path),
-                        Some(content),
-                    )?;
+                    visit(&full_path, entry.filemode(), blob.content())?;
                 }
                 Some(git2::ObjectType::Tree) => {
                     // It's a directory, recurse into it
                     debug!("Entering directory: {}", full_path);
                     let subtree = self.repository.find_tree(entry.id())?;
-                    self.add_tree_to_archive(writer, &subtree, &full_path)?;
+                    self.for_each_file_in_tree(&subtree, &full_path, visit)?;
                 }
                 _ => {
                     // Skip other object types (commits, tags, etc.)
@@ -1094,6 +2255,78 @@ impl BackupManager {
         Ok(())
     }
 
+    /// Changes the description of an existing backup.
+    ///
+    /// The backup's commit is rewritten with the new message, keeping its files, author, and
+    /// timestamp. Because a commit's ID covers its message, the backup and every newer backup
+    /// get new IDs, the same way [`purge_commit`](Self::purge_commit) rewrites history. Older
+    /// backups keep their IDs, and labels move to the rewritten backups.
+    ///
+    /// # Returns
+    ///
+    /// The new ID of the renamed backup.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backup ID is invalid or the backup isn't in the current history.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use obsidian_backups::BackupManager;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")
+    ///     .expect("Failed to initialize BackupManager");
+    ///
+    /// let last_backup = manager.last().unwrap().expect("No backups found");
+    /// let new_id = manager.rename_backup(&last_backup.id, "Before installing mods")
+    ///     .expect("Failed to rename backup");
+    /// ```
+    pub fn rename_backup(
+        &self,
+        backup_id: impl AsRef<str>,
+        new_description: impl AsRef<str>,
+    ) -> Result<String> {
+        let backup_id = backup_id.as_ref();
+        let new_description = new_description.as_ref();
+        info!("Renaming backup {} to {:?}", backup_id, new_description);
+
+        let oid = Oid::from_str(backup_id)?;
+        let commit = self.repository.find_commit(oid)?;
+        let all_ids = self.list_ids()?;
+        let position = all_ids
+            .iter()
+            .position(|id| id == backup_id)
+            .ok_or_else(|| anyhow!("Commit {} is not in the current branch history", backup_id))?;
+
+        let parents = commit.parents().collect::<Vec<_>>();
+        let new_oid = self.repository.commit(
+            None,
+            &commit.author(),
+            &commit.committer(),
+            new_description,
+            &commit.tree()?,
+            &parents.iter().collect::<Vec<_>>(),
+        )?;
+        debug!("Created renamed commit: {} (was: {})", new_oid, oid);
+        self.move_labels(oid, new_oid)?;
+
+        if position == 0 {
+            self.repository.reference(
+                "refs/heads/master",
+                new_oid,
+                true,
+                &format!("Renamed backup {}", backup_id),
+            )?;
+            self.repository.set_head("refs/heads/master")?;
+        } else {
+            self.rewrite_commit_chain(&all_ids[..position], new_oid)?;
+        }
+
+        info!("Backup {} renamed, new ID: {}", backup_id, new_oid);
+        Ok(new_oid.to_string())
+    }
+
     /// Purges a specific commit by its ID from the repository.
     ///
     /// This function removes a commit from the repository's history by rewriting
@@ -1234,6 +2467,131 @@ impl BackupManager {
             return Ok(());
         }
 
+        self.truncate_history(&ids, count)?;
+
+        // Force garbage collection to remove unreferenced objects
+        self.cleanup_orphaned_commits()?;
+
+        info!("Successfully purged {} old backups", ids.len() - count);
+        Ok(())
+    }
+
+    /// Removes old backups so that none break any limit of the policy.
+    ///
+    /// This is the combined form of [`purge_backups_over_count`](Self::purge_backups_over_count),
+    /// [`purge_backups_older_than`](Self::purge_backups_older_than) and
+    /// [`purge_backups_over_size`](Self::purge_backups_over_size): every limit is evaluated
+    /// first, then history is rewritten and garbage collected once, instead of once per limit.
+    ///
+    /// `max_size` counts the uncompressed size of the distinct files in the kept backups, so a
+    /// file unchanged across backups is only counted once. It doesn't measure the store on disk,
+    /// which is smaller after compression.
+    ///
+    /// The newest backup is always kept, even if it is older than `max_age` or larger than
+    /// `max_size`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backups can't be read or the history can't be rewritten.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use obsidian_backups::BackupManager;
+    /// use obsidian_backups::data::retention_policy::RetentionPolicy;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")
+    ///     .expect("Failed to initialize BackupManager");
+    ///
+    /// // Keep at most 30 backups, none older than 90 days
+    /// manager
+    ///     .apply_retention(RetentionPolicy {
+    ///         max_count: Some(30),
+    ///         max_age: Some(chrono::Duration::days(90)),
+    ///         ..Default::default()
+    ///     })
+    ///     .expect("Failed to apply retention");
+    /// ```
+    pub fn apply_retention(&self, policy: RetentionPolicy) -> Result<()> {
+        info!("Applying retention policy: {:?}", policy);
+
+        let ids = self.list_ids()?;
+        let mut keep = ids.len();
+
+        if let Some(max_count) = policy.max_count {
+            keep = keep.min(max_count);
+        }
+
+        if let Some(max_age) = policy.max_age {
+            let cutoff_timestamp = (chrono::Utc::now() - max_age).timestamp();
+            let mut recent = 0;
+            for commit_id in &ids[..keep] {
+                let commit = self.repository.find_commit(Oid::from_str(commit_id)?)?;
+                if commit.time().seconds() < cutoff_timestamp {
+                    break;
+                }
+                recent += 1;
+            }
+            keep = recent;
+        }
+
+        if let Some(max_size) = policy.max_size {
+            keep = self.count_backups_within_size(&ids[..keep], max_size)?;
+        }
+
+        // Never remove every backup
+        let keep = keep.max(1);
+        if keep >= ids.len() {
+            info!("All {} backups are within the retention policy", ids.len());
+            return Ok(());
+        }
+
+        debug!("Keeping {} of {} backups", keep, ids.len());
+        self.truncate_history(&ids, keep)?;
+        self.cleanup_orphaned_commits()?;
+
+        info!("Successfully purged {} old backups", ids.len() - keep);
+        Ok(())
+    }
+
+    /// Helper method to count how many of the newest backups fit within `max_size` bytes of distinct file content
+    fn count_backups_within_size(&self, ids: &[String], max_size: u64) -> Result<usize> {
+        let odb = self.repository.odb()?;
+        let mut seen = std::collections::HashSet::new();
+        let mut total = 0u64;
+
+        for (index, commit_id) in ids.iter().enumerate() {
+            let tree = self.repository.find_commit(Oid::from_str(commit_id)?)?.tree()?;
+            let mut error = None;
+            let walked = tree.walk(git2::TreeWalkMode::PreOrder, |_, entry| {
+                if entry.kind() == Some(git2::ObjectType::Blob) && seen.insert(entry.id()) {
+                    match odb.read_header(entry.id()) {
+                        Ok((length, _)) => total += length as u64,
+                        Err(e) => {
+                            error = Some(e);
+                            return git2::TreeWalkResult::Abort;
+                        }
+                    }
+                }
+                git2::TreeWalkResult::Ok
+            });
+            if let Some(e) = error {
+                return Err(e.into());
+            }
+            walked?;
+
+            if total > max_size {
+                trace!("Backup {} brings the total to {} bytes", commit_id, total);
+                return Ok(index);
+            }
+        }
+        Ok(ids.len())
+    }
+
+    /// Helper method to drop every backup after the newest `count`, folding the oldest kept backup into a new root commit
+    ///
+    /// Garbage collection is left to the caller.
+    fn truncate_history(&self, ids: &[String], count: usize) -> Result<()> {
         // Keep the most recent 'count' commits
         let commits_to_keep = &ids[..count];
         let oldest_commit_to_keep = &ids[count - 1];
@@ -1261,14 +2619,22 @@ impl BackupManager {
         )?;
 
         debug!("Created new base commit: {}", new_base_oid);
+        self.move_labels(oldest_oid, new_base_oid)?;
 
         // Now we need to rewrite the remaining commits to use this new base
-        self.rewrite_commit_chain(&commits_to_keep[..commits_to_keep.len() - 1], new_base_oid)?;
-
-        // Force garbage collection to remove unreferenced objects
-        self.cleanup_orphaned_commits()?;
+        if commits_to_keep.len() > 1 {
+            self.rewrite_commit_chain(&commits_to_keep[..commits_to_keep.len() - 1], new_base_oid)?;
+        } else {
+            // Only one commit to keep, just update HEAD to the new base
+            self.repository.reference(
+                "refs/heads/master",
+                new_base_oid,
+                true,
+                "Purged old backups",
+            )?;
+            self.repository.set_head("refs/heads/master")?;
+        }
 
-        info!("Successfully purged {} old backups", ids.len() - count);
         Ok(())
     }
 
@@ -1330,6 +2696,7 @@ impl BackupManager {
         )?;
 
         debug!("Created new base commit: {}", new_base_oid);
+        self.move_labels(oldest_oid, new_base_oid)?;
 
         // Rewrite remaining commits
         if commits_to_keep.len() > 1 {
@@ -1406,6 +2773,78 @@ impl BackupManager {
         ))
     }
 
+    /// Creates backups on the policy's interval or cron schedule and applies its retention after
+    /// each one.
+    ///
+    /// Each tick calls [`backup_if_changed`](Self::backup_if_changed), so an unchanged working
+    /// directory doesn't produce an empty backup, then purges backups over `max_count` and older
+    /// than `max_age`. The git work runs on Tokio's blocking pool against a manager reopened from
+    /// this one's directories and ignore rules. Failures are logged and retried on the next tick.
+    ///
+    /// The returned future resolves to the started timer; stop it to end the schedule.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use obsidian_backups::BackupManager;
+    /// use obsidian_backups::data::schedule_policy::SchedulePolicy;
+    /// use obsidian_scheduler::timer_trait::Timer;
+    /// use std::time::Duration;
+    ///
+    /// let manager = BackupManager::new("./backup_store", "./my_data")?;
+    /// let policy = SchedulePolicy {
+    ///     max_count: Some(48),
+    ///     ..SchedulePolicy::every(Duration::from_secs(60 * 60))
+    /// };
+    /// let timer = manager.schedule(policy).await?;
+    /// // ...
+    /// timer.stop().await?;
+    /// ```
+    #[cfg(feature = "scheduler")]
+    pub fn schedule(
+        &self,
+        policy: SchedulePolicy,
+    ) -> impl Future<Output = Result<Arc<CallbackTimer>>> + Send + 'static {
+        let scheduled = self.repository.workdir().map(|workdir| {
+            Arc::new(ScheduledBackup {
+                store_directory: self.repository.path().to_path_buf(),
+                working_directory: workdir.to_path_buf(),
+                ignore_matcher: self.ignore_matcher.clone(),
+                policy,
+                started: (chrono::Local::now(), tokio::time::Instant::now()),
+            })
+        });
+
+        async move {
+            let scheduled =
+                scheduled.ok_or_else(|| anyhow!("Repository has no working directory"))?;
+            match &scheduled.policy.schedule {
+                BackupSchedule::Interval(interval) => info!("Scheduling backups every {:?}", interval),
+                BackupSchedule::Cron(schedule) => info!("Scheduling backups on '{}'", schedule.source()),
+            }
+            // The callback waits for each backup itself, since cron gaps vary between runs
+            let timer = CallbackTimer::new(
+                move |handle| {
+                    let scheduled = scheduled.clone();
+                    async move {
+                        let Some(delay) = scheduled.next_delay() else {
+                            info!("Backup schedule has no upcoming occurrence, stopping");
+                            handle.stop();
+                            // Wait for the stop to abort this task
+                            return std::future::pending().await;
+                        };
+                        tokio::time::sleep(delay).await;
+                        tokio::task::spawn_blocking(move || scheduled.run()).await??;
+                        Ok(())
+                    }
+                },
+                Duration::ZERO,
+            );
+            timer.start().await?;
+            Ok(timer)
+        }
+    }
+
     /// Helper function to rewrite a chain of commits with a new parent
     fn rewrite_commit_chain(&self, commit_ids: &[String], new_parent_oid: Oid) -> Result<()> {
         debug!("Rewriting commit chain with {} commits", commit_ids.len());
@@ -1433,6 +2872,7 @@ impl BackupManager {
             )?;
 
             debug!("Created new commit: {} (was: {})", new_oid, old_oid);
+            self.move_labels(old_oid, new_oid)?;
 
             current_parent = new_oid;
             new_head = Some(new_oid);
@@ -1463,6 +2903,9 @@ impl BackupManager {
     fn cleanup_orphaned_commits(&self) -> Result<()> {
         info!("Starting comprehensive garbage collection");
 
+        // Step 0: Drop labels of backups that no longer exist
+        self.prune_orphaned_labels()?;
+
         // Step 1: Expire reflog entries immediately
         debug!("Expiring reflog entries");
         self.expire_reflogs()?;
@@ -1490,6 +2933,83 @@ impl BackupManager {
         Ok(())
     }
 
+    /// Read the labels attached to a commit
+    fn labels_of(&self, oid: Oid) -> Result<Vec<String>> {
+        match self.repository.find_note(Some(LABELS_NOTES_REF), oid) {
+            Ok(note) => Ok(note
+                .message()
+                .unwrap_or("")
+                .lines()
+                .filter(|label| !label.is_empty())
+                .map(str::to_string)
+                .collect()),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Replace the labels attached to a commit, removing its note when there are none
+    fn write_labels(&self, oid: Oid, labels: &[String]) -> Result<()> {
+        let sig = self.repository.signature()?;
+        if labels.is_empty() {
+            match self
+                .repository
+                .note_delete(oid, Some(LABELS_NOTES_REF), &sig, &sig)
+            {
+                Err(e) if e.code() != git2::ErrorCode::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        } else {
+            self.repository.note(
+                &sig,
+                &sig,
+                Some(LABELS_NOTES_REF),
+                oid,
+                &labels.join("\n"),
+                true,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Move the labels of a rewritten commit to the commit replacing it
+    fn move_labels(&self, from: Oid, to: Oid) -> Result<()> {
+        let labels = self.labels_of(from)?;
+        if labels.is_empty() {
+            return Ok(());
+        }
+        trace!("Moving labels {:?} from {} to {}", labels, from, to);
+        let mut merged = self.labels_of(to)?;
+        for label in labels {
+            if !merged.contains(&label) {
+                merged.push(label);
+            }
+        }
+        self.write_labels(to, &merged)?;
+        self.write_labels(from, &[])
+    }
+
+    /// Remove the labels of commits that are no longer part of the backup history
+    fn prune_orphaned_labels(&self) -> Result<()> {
+        let notes = match self.repository.notes(Some(LABELS_NOTES_REF)) {
+            Ok(notes) => notes,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        let annotated = notes
+            .map(|note| note.map(|(_, annotated_id)| annotated_id))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let ids: std::collections::HashSet<String> = self.list_ids()?.into_iter().collect();
+        for oid in annotated {
+            if !ids.contains(&oid.to_string()) {
+                debug!("Removing labels of purged backup {}", oid);
+                self.write_labels(oid, &[])?;
+            }
+        }
+        Ok(())
+    }
+
     /// Expire all reflog entries
     fn expire_reflogs(&self) -> Result<()> {
         let reflog_refs = vec!["HEAD", "refs/heads/master"];
@@ -1685,20 +3205,13 @@ impl BackupManager {
             }
         }
 
-        // Write the packfile
+        // Write the packfile along with its index, without which the packed objects can't be read
         let pack_dir = objects_dir.join("pack");
         fs::create_dir_all(&pack_dir)?;
 
-        // Generate a unique pack name based on timestamp
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)?
-            .as_secs();
-        let pack_path = pack_dir.join(format!("pack-{:x}.pack", timestamp));
-
-        debug!("Writing packfile to: {:?}", pack_path);
-        let mut buf = git2::Buf::new();
-        packbuilder.write_buf(&mut buf)?;
-        fs::write::<&std::path::PathBuf, &[u8]>(&pack_path, buf.as_ref())?;
+        debug!("Writing packfile to: {:?}", pack_dir);
+        packbuilder.write(&pack_dir, 0o644)?;
+        debug!("Wrote pack-{}.pack", packbuilder.name().unwrap_or("unknown"));
 
         // After successful packing, remove the loose objects
         for oid in &loose_oids {
@@ -1806,7 +3319,7 @@ impl BackupManager {
     ///
     /// * `backup_id` - A string-like identifier of the backup to export. This must correspond to a valid Git object ID (OID) in the repository.
     /// * `writer` - An async writer implementing `AsyncWrite` where the ZIP archive will be streamed to.
-    /// * `level` - Compression level (0-9, clamped to this range). The value determines the trade-off between compression size and speed.
+    /// * `level` - Compression level (0-9, clamped to this range). Level 0 stores files uncompressed; any other level deflates them.
     ///
     /// # Returns
     ///
@@ -1845,7 +3358,38 @@ impl BackupManager {
     /// manager.export_to_stream_async(&last_backup.id, &mut file, 6).await
     ///     .expect("Failed to export backup to stream");
     /// ```
-    ///
+    #[cfg(feature = "async-stream")]
+    pub async fn export_to_stream_async<W: tokio::io::AsyncWrite + Unpin + Send>(
+        &self,
+        backup_id: impl AsRef<str>,
+        writer: W,
+        level: u32,
+    ) -> Result<()> {
+        use archflow::compress::FileOptions;
+        use archflow::compress::tokio::archive::ZipArchive;
+        use archflow::compression::CompressionMethod;
+
+        let backup_id = backup_id.as_ref();
+        info!("Streaming backup {} as ZIP archive", backup_id);
+
+        let compression_method = match level.min(9) {
+            0 => CompressionMethod::Store(),
+            _ => CompressionMethod::Deflate(),
+        };
+        let options = FileOptions::default().compression_method(compression_method);
+
+        let mut archive = ZipArchive::new_streamable(writer);
+        self.populate_archive_async(backup_id, &mut archive, &options)
+            .await?;
+        archive
+            .finalize()
+            .await
+            .map_err(|e| anyhow!("Failed to finalize archive: {}", e))?;
+
+        info!("Backup {} streamed successfully", backup_id);
+        Ok(())
+    }
+
     /// Populate an archflow ZipArchive with files from a backup commit.
     ///
     /// This method allows external code to provide their own ZipArchive (with custom
@@ -1947,3 +3491,173 @@ impl BackupManager {
         })
     }
 }
+
+/// The state a scheduled backup needs, since the repository can't be shared across threads.
+#[cfg(feature = "scheduler")]
+struct ScheduledBackup {
+    store_directory: PathBuf,
+    working_directory: PathBuf,
+    ignore_matcher: Option<Gitignore>,
+    policy: SchedulePolicy,
+    /// Wall-clock time and Tokio instant the schedule started at, so cron times follow Tokio's clock.
+    started: (chrono::DateTime<chrono::Local>, tokio::time::Instant),
+}
+
+#[cfg(feature = "scheduler")]
+impl ScheduledBackup {
+    /// Time until the next scheduled backup, or `None` once the schedule has run out.
+    fn next_delay(&self) -> Option<Duration> {
+        let (wall_clock, instant) = self.started;
+        let now = wall_clock + chrono::Duration::from_std(instant.elapsed()).ok()?;
+        self.policy.schedule.next_delay(now)
+    }
+
+    /// Run one scheduled backup followed by retention.
+    fn run(&self) -> Result<()> {
+        let mut manager = BackupManager::new(&self.store_directory, &self.working_directory)?;
+        manager.ignore_matcher = self.ignore_matcher.clone();
+
+        let description = self
+            .policy
+            .description
+            .clone()
+            .unwrap_or_else(|| "Scheduled backup".to_string());
+        if let Some(backup_id) = manager.backup_if_changed(Some(description))? {
+            debug!("Scheduled backup created: {}", backup_id);
+        }
+
+        if let Some(max_count) = self.policy.max_count {
+            manager.purge_backups_over_count(max_count)?;
+        }
+        if let Some(max_age) = self.policy.max_age
+            && let Err(e) = manager.purge_backups_older_than(max_age)
+        {
+            // Every backup being older than max_age leaves nothing to keep
+            warn!("Skipping age-based retention: {}", e);
+        }
+        Ok(())
+    }
+}
+
+/// Build an S3 client for the credentials, addressing buckets by path on custom endpoints.
+#[cfg(feature = "s3")]
+fn s3_client(credentials: &S3Credentials) -> aws_sdk_s3::Client {
+    use aws_sdk_s3::config::{BehaviorVersion, Credentials, Region};
+
+    let mut config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .region(Region::new(credentials.region.clone()))
+        .credentials_provider(Credentials::new(
+            &credentials.access_key_id,
+            &credentials.secret_access_key,
+            None,
+            None,
+            "obsidian-backups",
+        ));
+    if let Some(endpoint) = &credentials.endpoint {
+        // MinIO and most S3-compatible services don't resolve bucket subdomains
+        config = config.endpoint_url(endpoint).force_path_style(true);
+    }
+    aws_sdk_s3::Client::from_conf(config.build())
+}
+
+/// Upload an archive, in parts if it's larger than [`S3_PART_SIZE`].
+#[cfg(feature = "s3")]
+async fn upload_to_s3(
+    client: &aws_sdk_s3::Client,
+    bucket: &str,
+    key: &str,
+    archive: Vec<u8>,
+) -> Result<()> {
+    use aws_sdk_s3::primitives::ByteStream;
+    use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+
+    if archive.len() <= S3_PART_SIZE {
+        client
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from(archive))
+            .send()
+            .await
+            .map_err(|e| s3_error("upload archive", e))?;
+        return Ok(());
+    }
+
+    let upload = client
+        .create_multipart_upload()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await
+        .map_err(|e| s3_error("start multipart upload", e))?;
+    let upload_id = upload
+        .upload_id()
+        .ok_or_else(|| anyhow!("S3 returned no upload ID"))?;
+
+    let mut parts = Vec::new();
+    for (index, chunk) in archive.chunks(S3_PART_SIZE).enumerate() {
+        let part_number = index as i32 + 1;
+        debug!("Uploading part {} of {}", part_number, key);
+        let part = match client
+            .upload_part()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .body(ByteStream::from(chunk.to_vec()))
+            .send()
+            .await
+        {
+            Ok(part) => part,
+            Err(e) => {
+                if let Err(abort_error) = client
+                    .abort_multipart_upload()
+                    .bucket(bucket)
+                    .key(key)
+                    .upload_id(upload_id)
+                    .send()
+                    .await
+                {
+                    warn!(
+                        "Failed to abort multipart upload {}: {}",
+                        upload_id,
+                        aws_sdk_s3::error::DisplayErrorContext(abort_error)
+                    );
+                }
+                return Err(s3_error("upload part", e));
+            }
+        };
+        parts.push(
+            CompletedPart::builder()
+                .set_e_tag(part.e_tag().map(str::to_string))
+                .part_number(part_number)
+                .build(),
+        );
+    }
+
+    client
+        .complete_multipart_upload()
+        .bucket(bucket)
+        .key(key)
+        .upload_id(upload_id)
+        .multipart_upload(
+            CompletedMultipartUpload::builder()
+                .set_parts(Some(parts))
+                .build(),
+        )
+        .send()
+        .await
+        .map_err(|e| s3_error("complete multipart upload", e))?;
+    Ok(())
+}
+
+/// Describe a failed S3 request with the service's error details rather than just its kind.
+#[cfg(feature = "s3")]
+fn s3_error<E: std::error::Error + Send + Sync + 'static>(action: &str, e: E) -> anyhow::Error {
+    anyhow!(
+        "Failed to {}: {}",
+        action,
+        aws_sdk_s3::error::DisplayErrorContext(e)
+    )
+}
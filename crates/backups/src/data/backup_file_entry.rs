@@ -0,0 +1,11 @@
+/// A file or directory stored in a backup, as returned by `BackupManager::list_files`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BackupFileEntry {
+	/// Path relative to the working directory, using `/` as the separator.
+	pub path: String,
+	/// Size of the file in bytes, 0 for directories.
+	pub size: u64,
+	/// Whether the entry is a directory.
+	pub is_directory: bool,
+}
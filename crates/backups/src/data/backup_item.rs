@@ -7,4 +7,8 @@ pub struct BackupItem {
 	pub timestamp: chrono::DateTime<chrono::Utc>,
 	/// A description or notes about the backup item.
 	pub description: String,
+	/// Total uncompressed size of the backup's files in bytes, only filled in by
+	/// `list_with_sizes`.
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+	pub size: Option<u64>,
 }
\ No newline at end of file
@@ -1,2 +1,9 @@
+pub mod backup_file_entry;
 pub mod backup_item;
 pub mod modified_file;
+pub mod retention_policy;
+#[cfg(feature = "s3")]
+pub mod s3_credentials;
+#[cfg(feature = "scheduler")]
+pub mod schedule_policy;
+pub mod verify_report;
@@ -0,0 +1,13 @@
+/// Limits `BackupManager::apply_retention` enforces together in a single pass.
+///
+/// Every limit left as `None` is ignored. The newest backup is always kept, even when it
+/// breaks a limit, so the store is never emptied.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+	/// Keep at most this many backups.
+	pub max_count: Option<usize>,
+	/// Remove backups older than this.
+	pub max_age: Option<chrono::Duration>,
+	/// Keep only as many recent backups as fit in this many bytes of distinct file content.
+	pub max_size: Option<u64>,
+}
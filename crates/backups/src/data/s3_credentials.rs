@@ -0,0 +1,38 @@
+/// Where and as whom [`BackupManager::push_to_s3`](crate::BackupManager::push_to_s3) uploads.
+///
+/// Leave `endpoint` unset for AWS itself, or point it at any S3-compatible service such as
+/// MinIO or Cloudflare R2.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct S3Credentials {
+	/// Access key ID.
+	pub access_key_id: String,
+	/// Secret access key.
+	pub secret_access_key: String,
+	/// Region of the bucket, e.g. `us-east-1`. R2 uses `auto`.
+	pub region: String,
+	/// URL of an S3-compatible endpoint. Buckets are addressed by path when set.
+	pub endpoint: Option<String>,
+}
+
+impl S3Credentials {
+	/// Credentials for a bucket on AWS S3.
+	pub fn new(
+		access_key_id: impl Into<String>,
+		secret_access_key: impl Into<String>,
+		region: impl Into<String>,
+	) -> Self {
+		Self {
+			access_key_id: access_key_id.into(),
+			secret_access_key: secret_access_key.into(),
+			region: region.into(),
+			endpoint: None,
+		}
+	}
+
+	/// Use an S3-compatible endpoint instead of AWS.
+	pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+		self.endpoint = Some(endpoint.into());
+		self
+	}
+}
@@ -0,0 +1,62 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+/// When [`BackupManager::schedule`](crate::BackupManager::schedule) creates backups.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackupSchedule {
+	/// Back up every `Duration`, measured from the end of the previous backup.
+	Interval(Duration),
+	/// Back up at the times matched by a cron expression (local time), e.g. `"0 0 4 * * *"` for 4 AM daily.
+	Cron(Box<cron::Schedule>),
+}
+
+impl BackupSchedule {
+	/// Time from `now` until the next backup, or `None` if the schedule has no upcoming occurrence.
+	pub(crate) fn next_delay(&self, now: chrono::DateTime<chrono::Local>) -> Option<Duration> {
+		match self {
+			Self::Interval(interval) => Some(*interval),
+			Self::Cron(schedule) => {
+				let next = schedule.after(&now).next()?;
+				Some((next - now).to_std().unwrap_or(Duration::ZERO))
+			}
+		}
+	}
+}
+
+/// When [`BackupManager::schedule`](crate::BackupManager::schedule) creates backups and
+/// which of them it keeps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchedulePolicy {
+	/// When backups are created.
+	pub schedule: BackupSchedule,
+	/// Description given to each scheduled backup.
+	pub description: Option<String>,
+	/// Keep at most this many backups, see `purge_backups_over_count`.
+	pub max_count: Option<usize>,
+	/// Purge backups older than this, see `purge_backups_older_than`.
+	pub max_age: Option<chrono::Duration>,
+}
+
+impl SchedulePolicy {
+	/// Back up every `interval`, keeping every backup.
+	pub fn every(interval: Duration) -> Self {
+		Self::new(BackupSchedule::Interval(interval))
+	}
+
+	/// Back up according to a cron expression (`sec min hour day-of-month month day-of-week [year]`),
+	/// keeping every backup.
+	pub fn cron(expression: &str) -> anyhow::Result<Self> {
+		let schedule = cron::Schedule::from_str(expression)
+			.map_err(|e| anyhow::anyhow!("Invalid cron expression '{}': {}", expression, e))?;
+		Ok(Self::new(BackupSchedule::Cron(Box::new(schedule))))
+	}
+
+	fn new(schedule: BackupSchedule) -> Self {
+		Self {
+			schedule,
+			description: None,
+			max_count: None,
+			max_age: None,
+		}
+	}
+}
@@ -0,0 +1,30 @@
+/// The result of checking a backup's objects with `BackupManager::verify`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VerifyReport {
+	/// The backup that was checked.
+	pub backup_id: String,
+	/// How many commit, tree, and blob objects were read.
+	pub objects_checked: usize,
+	/// Objects that are missing or whose content doesn't match their ID.
+	pub corrupt_objects: Vec<CorruptObject>,
+}
+
+impl VerifyReport {
+	/// Whether every object of the backup is present and intact.
+	pub fn is_healthy(&self) -> bool {
+		self.corrupt_objects.is_empty()
+	}
+}
+
+/// An object of a backup that can't be trusted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CorruptObject {
+	/// The object ID the backup refers to.
+	pub id: String,
+	/// Path of the file or directory in the backup, empty for the commit and its root tree.
+	pub path: String,
+	/// What is wrong with the object.
+	pub reason: String,
+}
@@ -7,29 +7,50 @@ pub use crate::{debug, error, info, trace, warn};
 #[cfg(not(feature = "logging"))]
 #[macro_export]
 macro_rules! debug {
-    ($($arg:tt)*) => {};
+    // Arguments are type-checked but never evaluated, so variables only logged still count as used
+    ($($arg:tt)*) => {{
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    }};
 }
 
 #[cfg(not(feature = "logging"))]
 #[macro_export]
 macro_rules! info {
-    ($($arg:tt)*) => {};
+    ($($arg:tt)*) => {{
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    }};
 }
 
 #[cfg(not(feature = "logging"))]
 #[macro_export]
 macro_rules! warn {
-    ($($arg:tt)*) => {};
+    ($($arg:tt)*) => {{
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    }};
 }
 
 #[cfg(not(feature = "logging"))]
 #[macro_export]
 macro_rules! error {
-    ($($arg:tt)*) => {};
+    ($($arg:tt)*) => {{
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    }};
 }
 
 #[cfg(not(feature = "logging"))]
 #[macro_export]
 macro_rules! trace {
-    ($($arg:tt)*) => {};
+    ($($arg:tt)*) => {{
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    }};
 }
\ No newline at end of file
@@ -32,6 +32,7 @@ mod test {
     }
 
     use obsidian_backups::BackupManager;
+    use obsidian_backups::data::retention_policy::RetentionPolicy;
 
     #[test]
     fn test_backup_manager_new() {
@@ -231,6 +232,89 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_restore_preview() {
+        use obsidian_backups::data::modified_file::ModifiedFile;
+
+        let (store_dir, working_dir) = setup_test_env("restore_preview");
+
+        create_test_file(&working_dir, "server.properties", b"motd=Hello");
+        create_test_file(&working_dir, "ops.json", b"[]");
+        create_test_file(&working_dir, "eula.txt", b"eula=true");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let backup_id = manager.backup(None).expect("Failed to create backup");
+        assert!(
+            manager.restore_preview(&backup_id).unwrap().is_empty(),
+            "An unchanged working directory needs no changes"
+        );
+
+        create_test_file(&working_dir, "server.properties", b"motd=Changed");
+        fs::remove_file(working_dir.join("ops.json")).unwrap();
+        create_test_file(&working_dir, "banned-players.json", b"[]");
+
+        let mut preview = manager
+            .restore_preview(&backup_id)
+            .expect("Failed to preview restore");
+        preview.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(
+            preview,
+            vec![
+                ModifiedFile {
+                    path: "banned-players.json".to_string(),
+                    content_before: Some(b"[]".to_vec()),
+                    content_after: None,
+                },
+                ModifiedFile {
+                    path: "ops.json".to_string(),
+                    content_before: None,
+                    content_after: Some(b"[]".to_vec()),
+                },
+                ModifiedFile {
+                    path: "server.properties".to_string(),
+                    content_before: Some(b"motd=Changed".to_vec()),
+                    content_after: Some(b"motd=Hello".to_vec()),
+                },
+            ]
+        );
+        assert!(
+            !working_dir.join("ops.json").exists(),
+            "Previewing should not restore anything"
+        );
+        assert_eq!(manager.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_restore_with_progress() {
+        let (store_dir, working_dir) = setup_test_env("restore_progress");
+
+        fs::create_dir_all(working_dir.join("world")).unwrap();
+        create_test_file(&working_dir, "server.properties", b"motd=Hello");
+        create_test_file(&working_dir, "ops.json", b"[]");
+        create_test_file(&working_dir, "world/level.dat", b"Level data");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let backup_id = manager.backup(None).expect("Failed to create backup");
+
+        fs::remove_file(working_dir.join("server.properties")).unwrap();
+        fs::remove_file(working_dir.join("ops.json")).unwrap();
+        fs::remove_dir_all(working_dir.join("world")).unwrap();
+
+        let mut reports = Vec::new();
+        manager
+            .restore_with_progress(&backup_id, |completed, total| {
+                reports.push((completed, total))
+            })
+            .expect("Failed to restore backup");
+
+        assert!(!reports.is_empty(), "Progress should be reported");
+        assert_eq!(reports.last(), Some(&(3, 3)), "Progress: {:?}", reports);
+        assert!(reports.windows(2).all(|w| w[0].0 <= w[1].0));
+        assert!(working_dir.join("world/level.dat").exists());
+    }
+
     #[test]
     fn test_restore_with_multiple_files() {
         let (store_dir, working_dir) = setup_test_env("restore_multiple");
@@ -534,6 +618,64 @@ mod test {
         assert!(export_path.exists(), "Export file should exist");
     }
 
+    #[test]
+    #[cfg(feature = "zip")]
+    fn test_verify_export() {
+        let (store_dir, working_dir) = setup_test_env("verify_export");
+
+        fs::create_dir_all(working_dir.join("world/region")).unwrap();
+        create_test_file(&working_dir, "server.properties", b"motd=Hello");
+        create_test_file(&working_dir, "world/region/r.0.0.mca", &[7u8; 4096]);
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let backup_id = manager.backup(None).expect("Failed to create backup");
+
+        let export_path = PathBuf::from("target/test_backup_manager/verify_export.7z");
+        let _ = fs::remove_file(&export_path); // Clean up if exists
+        manager
+            .export(&backup_id, &export_path, 5)
+            .expect("Failed to export backup");
+
+        let mut files = manager
+            .verify_export(&export_path)
+            .expect("Exported archive should verify");
+        files.sort();
+        assert_eq!(files, vec!["server.properties", "world/region/r.0.0.mca"]);
+    }
+
+    #[test]
+    #[cfg(feature = "zip")]
+    fn test_verify_truncated_export() {
+        let (store_dir, working_dir) = setup_test_env("verify_export_truncated");
+
+        create_test_file(&working_dir, "test.txt", b"Content to export");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let backup_id = manager.backup(None).expect("Failed to create backup");
+
+        let export_path = PathBuf::from("target/test_backup_manager/verify_export_truncated.7z");
+        let _ = fs::remove_file(&export_path); // Clean up if exists
+        manager
+            .export(&backup_id, &export_path, 5)
+            .expect("Failed to export backup");
+
+        let bytes = fs::read(&export_path).unwrap();
+        fs::write(&export_path, &bytes[..bytes.len() / 2]).unwrap();
+
+        assert!(
+            manager.verify_export(&export_path).is_err(),
+            "Truncated archive should fail verification"
+        );
+        assert!(
+            manager
+                .verify_export("target/test_backup_manager/missing.7z")
+                .is_err(),
+            "Missing archive should fail verification"
+        );
+    }
+
     #[test]
     #[cfg(feature = "zip")]
     fn test_export_invalid_backup_id() {
@@ -551,6 +693,130 @@ mod test {
         );
     }
 
+    #[test]
+    #[cfg(feature = "targz")]
+    fn test_export_targz() {
+        use std::collections::BTreeMap;
+        use std::io::Read;
+
+        let (store_dir, working_dir) = setup_test_env("export_targz");
+
+        fs::create_dir_all(working_dir.join("world/region")).unwrap();
+        create_test_file(&working_dir, "server.properties", b"motd=Hello");
+        create_test_file(&working_dir, "world/region/r.0.0.mca", &[7u8; 4096]);
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let backup_id = manager.backup(None).expect("Failed to create backup");
+
+        let export_path = PathBuf::from("target/test_backup_manager/export_test.tar.gz");
+        let _ = fs::remove_file(&export_path); // Clean up if exists
+        manager
+            .export_targz(&backup_id, &export_path, 6)
+            .expect("Failed to export backup");
+
+        let decoder = flate2::read::GzDecoder::new(fs::File::open(&export_path).unwrap());
+        let mut archive = tar::Archive::new(decoder);
+        let mut entries = BTreeMap::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().to_string_lossy().into_owned();
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content).unwrap();
+            entries.insert(path, content);
+        }
+
+        let expected = BTreeMap::from([
+            ("server.properties".to_string(), b"motd=Hello".to_vec()),
+            ("world/region/r.0.0.mca".to_string(), vec![7u8; 4096]),
+        ]);
+        assert_eq!(entries, expected);
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async-stream")]
+    async fn test_export_to_stream_async() {
+        use std::collections::BTreeMap;
+        use std::io::Read;
+
+        let (store_dir, working_dir) = setup_test_env("export_stream_async");
+
+        fs::create_dir_all(working_dir.join("world/region")).unwrap();
+        create_test_file(&working_dir, "server.properties", b"motd=Hello");
+        create_test_file(&working_dir, "world/region/r.0.0.mca", &[7u8; 4096]);
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let backup_id = manager.backup(None).expect("Failed to create backup");
+
+        let mut buffer = Vec::new();
+        manager
+            .export_to_stream_async(&backup_id, &mut buffer, 6)
+            .await
+            .expect("Failed to stream backup");
+
+        let mut archive = zip_reader::ZipArchive::new(std::io::Cursor::new(buffer))
+            .expect("Streamed archive should be a valid ZIP");
+        let mut entries = BTreeMap::new();
+        for index in 0..archive.len() {
+            let mut file = archive.by_index(index).unwrap();
+            let mut content = Vec::new();
+            file.read_to_end(&mut content).unwrap();
+            entries.insert(file.name().to_string(), content);
+        }
+
+        let expected = BTreeMap::from([
+            ("server.properties".to_string(), b"motd=Hello".to_vec()),
+            ("world/region/r.0.0.mca".to_string(), vec![7u8; 4096]),
+        ]);
+        assert_eq!(entries, expected);
+    }
+
+    #[test]
+    #[cfg(feature = "targz")]
+    fn test_import_exported_archive() {
+        let (store_dir, working_dir) = setup_test_env("import_archive");
+
+        fs::create_dir_all(working_dir.join("world")).unwrap();
+        create_test_file(&working_dir, "server.properties", b"motd=Hello");
+        create_test_file(&working_dir, "world/level.dat", b"Level data");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let backup_id = manager.backup(None).expect("Failed to create backup");
+        let export_path = PathBuf::from("target/test_backup_manager/import_test.tar.gz");
+        manager
+            .export_targz(&backup_id, &export_path, 6)
+            .expect("Failed to export backup");
+        drop(manager);
+
+        // Start over with an empty store and working directory
+        let (store_dir, working_dir) = setup_test_env("import_archive");
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let imported_id = manager
+            .import_archive(&export_path)
+            .expect("Failed to import archive");
+
+        assert_eq!(
+            fs::read(working_dir.join("server.properties")).unwrap(),
+            b"motd=Hello"
+        );
+        assert_eq!(
+            fs::read(working_dir.join("world/level.dat")).unwrap(),
+            b"Level data"
+        );
+        let backups = manager.list().expect("Failed to list backups");
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].id, imported_id);
+        assert_eq!(backups[0].description, "Imported from import_test.tar.gz");
+
+        assert!(
+            manager.import_archive("backup.zip").is_err(),
+            "Unknown formats should be rejected"
+        );
+    }
+
     #[test]
     #[cfg(feature = "zip")]
     fn test_export_to_stream() {
@@ -803,6 +1069,209 @@ mod test {
         );
     }
 
+    /// Rewrites the newest backup as if it had been made `days_ago` days ago.
+    fn backdate_last_backup(store_dir: &Path, days_ago: i64) {
+        let repository = git2::Repository::open(store_dir).unwrap();
+        let head = repository.head().unwrap().peel_to_commit().unwrap();
+        let time = git2::Time::new(chrono::Utc::now().timestamp() - days_ago * 24 * 60 * 60, 0);
+        let signature = git2::Signature::new("Backup", "backup@localhost", &time).unwrap();
+        head.amend(Some("HEAD"), Some(&signature), Some(&signature), None, None, None)
+            .expect("Failed to backdate backup");
+    }
+
+    /// Creates a backup per age, oldest first, each with different content.
+    fn create_aged_backups(
+        manager: &BackupManager,
+        store_dir: &Path,
+        working_dir: &Path,
+        days_ago: &[i64],
+    ) {
+        for (i, days) in days_ago.iter().enumerate() {
+            create_test_file(working_dir, "test.txt", format!("Content {}", i + 1).as_bytes());
+            manager
+                .backup(Some(format!("Backup {}", i + 1)))
+                .expect("Failed to create backup");
+            backdate_last_backup(store_dir, *days);
+        }
+    }
+
+    fn backup_descriptions(manager: &BackupManager) -> Vec<String> {
+        manager
+            .list()
+            .expect("Failed to list backups")
+            .into_iter()
+            .map(|backup| backup.description)
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_retention_max_count() {
+        let (store_dir, working_dir) = setup_test_env("retention_count");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        create_aged_backups(&manager, &store_dir, &working_dir, &[4, 3, 2, 1, 0]);
+
+        manager
+            .apply_retention(RetentionPolicy::default())
+            .expect("Failed to apply empty retention policy");
+        assert_eq!(backup_descriptions(&manager).len(), 5, "An empty policy keeps everything");
+
+        manager
+            .apply_retention(RetentionPolicy {
+                max_count: Some(3),
+                ..Default::default()
+            })
+            .expect("Failed to apply retention");
+
+        let backups = manager.list().expect("Failed to list backups");
+        assert_eq!(backups.len(), 3);
+        assert_eq!(backups[0].description, "Backup 5");
+        assert_eq!(backups[1].description, "Backup 4");
+        assert_eq!(
+            manager.read_file(&backups[2].id, "test.txt").unwrap(),
+            b"Content 3",
+            "The oldest kept backup should keep its files"
+        );
+    }
+
+    #[test]
+    fn test_apply_retention_max_age() {
+        let (store_dir, working_dir) = setup_test_env("retention_age");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        create_aged_backups(&manager, &store_dir, &working_dir, &[120, 60, 30, 0]);
+
+        let policy = RetentionPolicy {
+            max_age: Some(chrono::Duration::days(90)),
+            ..Default::default()
+        };
+        manager.apply_retention(policy.clone()).expect("Failed to apply retention");
+
+        let backups = manager.list().expect("Failed to list backups");
+        assert_eq!(backups.len(), 3, "Only the backup older than 90 days should be removed");
+        assert_eq!(backups[0].description, "Backup 4");
+        assert_eq!(manager.read_file(&backups[2].id, "test.txt").unwrap(), b"Content 2");
+
+        // When every backup is too old, the newest one is still kept
+        let (store_dir, working_dir) = setup_test_env("retention_age_all_old");
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        create_aged_backups(&manager, &store_dir, &working_dir, &[200, 100]);
+
+        manager.apply_retention(policy).expect("Failed to apply retention");
+        let backups = manager.list().expect("Failed to list backups");
+        assert_eq!(backups.len(), 1);
+        assert_eq!(manager.read_file(&backups[0].id, "test.txt").unwrap(), b"Content 2");
+    }
+
+    #[test]
+    fn test_apply_retention_max_size() {
+        let (store_dir, working_dir) = setup_test_env("retention_size");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+
+        // The unchanged file is shared by every backup and only counted once
+        create_test_file(&working_dir, "shared.dat", &[0u8; 1000]);
+        for i in 1..=3u8 {
+            create_test_file(&working_dir, "world.dat", &[i; 1000]);
+            manager
+                .backup(Some(format!("Backup {}", i)))
+                .expect("Failed to create backup");
+        }
+
+        // The two newest backups hold 3000 distinct bytes, all three hold 4000
+        manager
+            .apply_retention(RetentionPolicy {
+                max_size: Some(3500),
+                ..Default::default()
+            })
+            .expect("Failed to apply retention");
+
+        let backups = manager.list().expect("Failed to list backups");
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].description, "Backup 3");
+        assert_eq!(manager.read_file(&backups[1].id, "world.dat").unwrap(), vec![2u8; 1000]);
+    }
+
+    #[test]
+    fn test_apply_retention_combined() {
+        let (store_dir, working_dir) = setup_test_env("retention_combined");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        create_aged_backups(&manager, &store_dir, &working_dir, &[150, 120, 60, 30, 0]);
+
+        // The age limit is the stricter one here
+        let mut policy = RetentionPolicy {
+            max_count: Some(4),
+            max_age: Some(chrono::Duration::days(90)),
+            max_size: None,
+        };
+        manager.apply_retention(policy.clone()).expect("Failed to apply retention");
+        assert_eq!(backup_descriptions(&manager).len(), 3);
+
+        // Now the count limit is
+        policy.max_count = Some(2);
+        manager.apply_retention(policy).expect("Failed to apply retention");
+        let backups = manager.list().expect("Failed to list backups");
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].description, "Backup 5");
+        assert_eq!(manager.read_file(&backups[1].id, "test.txt").unwrap(), b"Content 4");
+    }
+
+    #[test]
+    fn test_push_to_bare_remote() {
+        let (store_dir, working_dir) = setup_test_env("push_remote");
+        let remote_dir = PathBuf::from("target/test_backup_manager/push_remote.git");
+        let _ = fs::remove_dir_all(&remote_dir);
+        let remote = git2::Repository::init_bare(&remote_dir).unwrap();
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        for i in 1..=3 {
+            create_test_file(&working_dir, "test.txt", format!("Content {}", i).as_bytes());
+            manager
+                .backup(Some(format!("Backup {}", i)))
+                .expect("Failed to create backup");
+        }
+        let newest = manager.last().unwrap().unwrap();
+        manager.tag_backup(&newest.id, "pre-update").unwrap();
+
+        let remote_url = fs::canonicalize(&remote_dir).unwrap();
+        manager
+            .set_remote("offsite", remote_url.to_str().unwrap())
+            .expect("Failed to set remote");
+        manager.push("offsite").expect("Failed to push backups");
+
+        let pushed_head = |remote: &git2::Repository| {
+            remote
+                .find_reference("refs/heads/master")
+                .unwrap()
+                .target()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(pushed_head(&remote), newest.id);
+        let mut walk = remote.revwalk().unwrap();
+        walk.push_ref("refs/heads/master").unwrap();
+        assert_eq!(walk.count(), 3, "The remote should have every backup");
+        assert!(
+            remote.find_reference("refs/notes/labels").is_ok(),
+            "Labels should be pushed"
+        );
+
+        // Rewritten history replaces what the remote has
+        manager.purge_backups_over_count(2).unwrap();
+        manager.push("offsite").expect("Failed to push rewritten backups");
+        let newest = manager.last().unwrap().unwrap();
+        assert_eq!(pushed_head(&remote), newest.id);
+
+        assert!(manager.push("missing").is_err(), "Unknown remotes should fail");
+    }
+
     #[test]
     fn test_excluded_files_not_backed_up() {
         let (store_dir, working_dir) = setup_test_env("excluded_files");
@@ -866,6 +1335,405 @@ mod test {
         assert_eq!(content2, b"Original content 2", "file2 should be restored");
     }
 
+    #[test]
+    fn test_restore_to_other_directory() {
+        let (store_dir, working_dir) = setup_test_env("restore_to");
+        let target_dir = PathBuf::from("target/test_backup_manager/restore_to_target");
+        let _ = fs::remove_dir_all(&target_dir);
+
+        create_test_file(&working_dir, "server.properties", b"motd=Original");
+        fs::create_dir_all(working_dir.join("world/region")).unwrap();
+        create_test_file(&working_dir, "world/region/r.0.0.mca", b"region data");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let backup_id = manager
+            .backup(Some("Original state".to_string()))
+            .expect("Failed to create backup");
+
+        // Later changes to the working directory must survive the restore
+        create_test_file(&working_dir, "server.properties", b"motd=Changed");
+        create_test_file(&working_dir, "new.txt", b"New file");
+
+        manager
+            .restore_to(&backup_id, &target_dir)
+            .expect("Failed to restore to target directory");
+
+        assert_eq!(fs::read(target_dir.join("server.properties")).unwrap(), b"motd=Original");
+        assert_eq!(fs::read(target_dir.join("world/region/r.0.0.mca")).unwrap(), b"region data");
+        assert!(!target_dir.join("new.txt").exists(), "Untracked files are not copied");
+
+        assert_eq!(fs::read(working_dir.join("server.properties")).unwrap(), b"motd=Changed");
+        assert!(working_dir.join("new.txt").exists(), "Working directory should be untouched");
+    }
+
+    #[test]
+    fn test_restore_nested_file() {
+        let (store_dir, working_dir) = setup_test_env("restore_file");
+
+        fs::create_dir_all(working_dir.join("config/plugins")).unwrap();
+        create_test_file(&working_dir, "config/plugins/essentials.yml", b"spawn: true");
+        create_test_file(&working_dir, "server.properties", b"motd=Original");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let backup_id = manager.backup(None).expect("Failed to create backup");
+
+        // Lose the nested file and change another one
+        fs::remove_dir_all(working_dir.join("config")).unwrap();
+        create_test_file(&working_dir, "server.properties", b"motd=Changed");
+
+        let restored = manager
+            .restore_file(&backup_id, "config/plugins/essentials.yml", None)
+            .expect("Failed to restore file");
+        assert!(restored.ends_with("config/plugins/essentials.yml"));
+        assert_eq!(
+            fs::read(working_dir.join("config/plugins/essentials.yml")).unwrap(),
+            b"spawn: true"
+        );
+        assert_eq!(
+            fs::read(working_dir.join("server.properties")).unwrap(),
+            b"motd=Changed",
+            "Other files should be untouched"
+        );
+
+        // Restore a copy elsewhere
+        let copy = PathBuf::from("target/test_backup_manager/restore_file_copy/server.properties");
+        let _ = fs::remove_dir_all(copy.parent().unwrap());
+        manager
+            .restore_file(&backup_id, "server.properties", Some(&copy))
+            .expect("Failed to restore file copy");
+        assert_eq!(fs::read(&copy).unwrap(), b"motd=Original");
+    }
+
+    #[test]
+    fn test_restore_missing_file() {
+        let (store_dir, working_dir) = setup_test_env("restore_file_missing");
+
+        fs::create_dir_all(working_dir.join("world")).unwrap();
+        create_test_file(&working_dir, "world/level.dat", b"level");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let backup_id = manager.backup(None).expect("Failed to create backup");
+
+        assert!(
+            manager.restore_file(&backup_id, "world/missing.dat", None).is_err(),
+            "Missing path should fail"
+        );
+        assert!(
+            manager.restore_file(&backup_id, "world", None).is_err(),
+            "Directory path should fail"
+        );
+        assert!(!working_dir.join("world/missing.dat").exists());
+    }
+
+    #[test]
+    fn test_read_file() {
+        let (store_dir, working_dir) = setup_test_env("read_file");
+
+        fs::create_dir_all(working_dir.join("world")).unwrap();
+        create_test_file(&working_dir, "world/level.dat", b"Original level");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let backup_id = manager.backup(None).expect("Failed to create backup");
+        create_test_file(&working_dir, "world/level.dat", b"Changed level");
+
+        let content = manager
+            .read_file(&backup_id, "world/level.dat")
+            .expect("Failed to read file");
+        assert_eq!(content, b"Original level");
+        assert_eq!(
+            fs::read(working_dir.join("world/level.dat")).unwrap(),
+            b"Changed level",
+            "Reading should not touch the working directory"
+        );
+    }
+
+    #[test]
+    fn test_read_file_invalid_paths() {
+        let (store_dir, working_dir) = setup_test_env("read_file_invalid");
+
+        fs::create_dir_all(working_dir.join("world")).unwrap();
+        create_test_file(&working_dir, "world/level.dat", b"level");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let backup_id = manager.backup(None).expect("Failed to create backup");
+
+        let error = manager.read_file(&backup_id, "world").unwrap_err();
+        assert!(
+            error.to_string().contains("is a directory"),
+            "Unexpected error: {}",
+            error
+        );
+        let error = manager.read_file(&backup_id, "world/missing.dat").unwrap_err();
+        assert!(
+            error.to_string().contains("is not in backup"),
+            "Unexpected error: {}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_rename_backup() {
+        let (store_dir, working_dir) = setup_test_env("rename_backup");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let mut ids = Vec::new();
+        for i in 0..3 {
+            create_test_file(&working_dir, "world.dat", format!("v{}", i).as_bytes());
+            ids.push(manager.backup(Some(format!("Backup {}", i))).unwrap());
+        }
+        manager.tag_backup(&ids[1], "manual").unwrap();
+        let before = manager.list().unwrap();
+
+        let new_id = manager
+            .rename_backup(&ids[1], "Before installing mods")
+            .expect("Failed to rename backup");
+
+        let after = manager.list().unwrap();
+        let descriptions: Vec<_> = after.iter().map(|b| b.description.as_str()).collect();
+        assert_eq!(descriptions, vec!["Backup 2", "Before installing mods", "Backup 0"]);
+        assert_eq!(after[1].id, new_id);
+        assert_eq!(after[2].id, ids[0], "Older backups should keep their IDs");
+        for (old, new) in before.iter().zip(&after) {
+            assert_eq!(old.timestamp, new.timestamp, "Timestamps should be preserved");
+        }
+        for (i, backup) in after.iter().rev().enumerate() {
+            assert_eq!(
+                manager.read_file(&backup.id, "world.dat").unwrap(),
+                format!("v{}", i).as_bytes(),
+                "Files should be preserved"
+            );
+        }
+        assert_eq!(manager.find_by_tag("manual").unwrap()[0].id, new_id);
+
+        // Renaming the newest backup moves HEAD
+        let head_id = manager.rename_backup(&after[0].id, "Latest").unwrap();
+        assert_eq!(manager.last().unwrap().unwrap().id, head_id);
+        assert_eq!(manager.list().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_tag_backups() {
+        let (store_dir, working_dir) = setup_test_env("tag_backups");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        create_test_file(&working_dir, "world.dat", b"v1");
+        let first_id = manager.backup(Some("First".to_string())).unwrap();
+        create_test_file(&working_dir, "world.dat", b"v2");
+        let second_id = manager.backup(Some("Second".to_string())).unwrap();
+
+        manager.tag_backup(&first_id, "manual").expect("Failed to tag backup");
+        manager.tag_backup(&second_id, "manual").expect("Failed to tag backup");
+        manager.tag_backup(&second_id, "pre-upgrade").expect("Failed to tag backup");
+        manager.tag_backup(&second_id, "pre-upgrade").expect("Tagging twice should succeed");
+
+        assert_eq!(manager.tags(&second_id).unwrap(), vec!["manual", "pre-upgrade"]);
+        let manual: Vec<_> = manager
+            .find_by_tag("manual")
+            .unwrap()
+            .into_iter()
+            .map(|b| b.id)
+            .collect();
+        assert_eq!(manual, vec![second_id.clone(), first_id.clone()]);
+        assert!(manager.find_by_tag("nightly").unwrap().is_empty());
+
+        manager.untag_backup(&first_id, "manual").expect("Failed to untag backup");
+        assert!(manager.tags(&first_id).unwrap().is_empty());
+        assert_eq!(manager.find_by_tag("manual").unwrap().len(), 1);
+
+        assert!(manager.tag_backup(&first_id, "").is_err(), "Empty labels should fail");
+        assert!(manager.tag_backup("invalid_id", "manual").is_err());
+    }
+
+    #[test]
+    fn test_tags_survive_purge() {
+        let (store_dir, working_dir) = setup_test_env("tags_purge");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let mut ids = Vec::new();
+        for i in 0..3 {
+            create_test_file(&working_dir, "world.dat", format!("v{}", i).as_bytes());
+            ids.push(manager.backup(Some(format!("Backup {}", i))).unwrap());
+        }
+        manager.tag_backup(&ids[0], "oldest").unwrap();
+        manager.tag_backup(&ids[2], "pre-upgrade").unwrap();
+
+        // Rewrites every remaining backup, giving them new IDs
+        manager.purge_commit(&ids[1]).expect("Failed to purge backup");
+
+        let tagged = manager.find_by_tag("pre-upgrade").unwrap();
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].description, "Backup 2");
+        assert_ne!(tagged[0].id, ids[2], "The backup should have been rewritten");
+        assert_eq!(manager.find_by_tag("oldest").unwrap()[0].id, ids[0]);
+
+        // The consolidated base takes over the labels of the backup it replaces
+        manager.purge_backups_over_count(1).expect("Failed to purge backups");
+        assert_eq!(manager.find_by_tag("pre-upgrade").unwrap().len(), 1);
+        assert!(manager.find_by_tag("oldest").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_list_files() {
+        use obsidian_backups::data::backup_file_entry::BackupFileEntry;
+
+        let (store_dir, working_dir) = setup_test_env("list_files");
+
+        fs::create_dir_all(working_dir.join("world/region")).unwrap();
+        create_test_file(&working_dir, "server.properties", b"motd=Hello");
+        create_test_file(&working_dir, "world/level.dat", b"Level data");
+        create_test_file(&working_dir, "world/region/r.0.0.mca", &[0u8; 2048]);
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let backup_id = manager.backup(None).expect("Failed to create backup");
+
+        let file = |path: &str, size| BackupFileEntry {
+            path: path.to_string(),
+            size,
+            is_directory: false,
+        };
+        let directory = |path: &str| BackupFileEntry {
+            path: path.to_string(),
+            size: 0,
+            is_directory: true,
+        };
+        assert_eq!(
+            manager.list_files(&backup_id).expect("Failed to list files"),
+            vec![
+                file("server.properties", 10),
+                directory("world"),
+                file("world/level.dat", 10),
+                directory("world/region"),
+                file("world/region/r.0.0.mca", 2048),
+            ]
+        );
+        assert!(manager.list_files("invalid_id").is_err());
+    }
+
+    #[test]
+    fn test_backup_sizes() {
+        let (store_dir, working_dir) = setup_test_env("backup_size");
+
+        fs::create_dir_all(working_dir.join("world")).unwrap();
+        create_test_file(&working_dir, "server.properties", b"motd=Hello");
+        create_test_file(&working_dir, "world/level.dat", &[1u8; 1500]);
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let first_id = manager.backup(None).expect("Failed to create first backup");
+        create_test_file(&working_dir, "whitelist.json", b"[]");
+        let second_id = manager.backup(None).expect("Failed to create second backup");
+
+        assert_eq!(manager.backup_size(&first_id).unwrap(), 10 + 1500);
+        assert_eq!(manager.backup_size(&second_id).unwrap(), 10 + 1500 + 2);
+
+        let backups = manager.list_with_sizes().expect("Failed to list backups");
+        let sizes: Vec<_> = backups.iter().map(|b| (b.id.as_str(), b.size)).collect();
+        assert_eq!(
+            sizes,
+            vec![(second_id.as_str(), Some(1512)), (first_id.as_str(), Some(1510))]
+        );
+        assert!(manager.list().unwrap().iter().all(|b| b.size.is_none()));
+    }
+
+    #[test]
+    fn test_set_compression() {
+        let (store_dir, working_dir) = setup_test_env("compression");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        assert_eq!(manager.compression().unwrap(), None);
+        manager.set_compression(9).expect("Failed to set compression");
+        assert!(manager.set_compression(10).is_err(), "Levels above 9 should fail");
+        assert_eq!(manager.compression().unwrap(), Some(9));
+
+        let config = git2::Repository::open(&store_dir)
+            .unwrap()
+            .config()
+            .unwrap()
+            .snapshot()
+            .unwrap();
+        for key in ["core.compression", "core.looseCompression", "pack.compression"] {
+            assert_eq!(config.get_i32(key).unwrap(), 9, "{} should be set", key);
+        }
+
+        // Purging packs the remaining objects, which must still be readable
+        for i in 0..3 {
+            create_test_file(&working_dir, "world.dat", &vec![i as u8; 10_000]);
+            manager.backup(None).expect("Failed to create backup");
+        }
+        manager.purge_backups_over_count(2).expect("Failed to purge backups");
+        let packs: Vec<_> = fs::read_dir(store_dir.join("objects/pack"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert!(packs.iter().any(|p| p.extension().is_some_and(|e| e == "pack")));
+        assert!(packs.iter().any(|p| p.extension().is_some_and(|e| e == "idx")));
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        assert_eq!(manager.compression().unwrap(), Some(9), "The level should persist");
+        assert!(manager.verify_all().unwrap().iter().all(|r| r.is_healthy()));
+        let last = manager.last().unwrap().unwrap();
+        assert_eq!(manager.read_file(&last.id, "world.dat").unwrap(), vec![2u8; 10_000]);
+    }
+
+    #[test]
+    fn test_verify_backups() {
+        let (store_dir, working_dir) = setup_test_env("verify");
+
+        fs::create_dir_all(working_dir.join("world")).unwrap();
+        create_test_file(&working_dir, "world/level.dat", b"Level data");
+        create_test_file(&working_dir, "server.properties", b"motd=Hello");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        manager.backup(None).expect("Failed to create first backup");
+        create_test_file(&working_dir, "whitelist.json", b"[]");
+        let backup_id = manager.backup(None).expect("Failed to create second backup");
+
+        let report = manager.verify(&backup_id).expect("Failed to verify backup");
+        assert!(report.is_healthy(), "Fresh backup should verify: {:?}", report);
+        // Commit, root tree, world tree, and three files
+        assert_eq!(report.objects_checked, 6);
+        let reports = manager.verify_all().expect("Failed to verify all backups");
+        assert_eq!(reports.len(), 2);
+        assert!(reports.iter().all(|r| r.is_healthy()));
+
+        // Delete the loose object holding world/level.dat
+        let repository = git2::Repository::open(&store_dir).unwrap();
+        let blob_id = repository
+            .find_commit(git2::Oid::from_str(&backup_id).unwrap())
+            .unwrap()
+            .tree()
+            .unwrap()
+            .get_path(Path::new("world/level.dat"))
+            .unwrap()
+            .id()
+            .to_string();
+        fs::remove_file(store_dir.join("objects").join(&blob_id[..2]).join(&blob_id[2..]))
+            .expect("Failed to delete object");
+
+        // Reopen so no cached copy of the object is used
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let report = manager.verify(&backup_id).expect("Failed to verify backup");
+        assert!(!report.is_healthy(), "Missing object should be reported");
+        assert_eq!(report.corrupt_objects.len(), 1);
+        assert_eq!(report.corrupt_objects[0].id, blob_id);
+        assert_eq!(report.corrupt_objects[0].path, "world/level.dat");
+        let reports = manager.verify_all().expect("Failed to verify all backups");
+        assert!(reports.iter().all(|r| !r.is_healthy()), "Both backups share the object");
+    }
+
     #[test]
     fn test_diff_first_backup() {
         let (store_dir, working_dir) = setup_test_env("diff_first");
@@ -1037,6 +1905,236 @@ mod test {
         assert_eq!(diffs.len(), 1, "Should include the file without ignores");
         assert_eq!(diffs[0].path, "foo.txt");
     }
-}
 
+    #[test]
+    fn test_layered_ignore_files_and_patterns() {
+        let (store_dir, working_dir) = setup_test_env("ignore_layered");
+
+        fs::create_dir_all(working_dir.join("logs")).unwrap();
+        fs::create_dir_all(working_dir.join("cache")).unwrap();
+        create_test_file(&working_dir, "server.properties", b"motd=Hello");
+        create_test_file(&working_dir, "logs/latest.log", b"Ignored by the global file");
+        create_test_file(&working_dir, "logs/keep.log", b"Re-included inline");
+        create_test_file(&working_dir, "cache/chunk.bin", b"Ignored by the server file");
+        create_test_file(&working_dir, "crash.dmp", b"Ignored inline");
+
+        let global_ignore = store_dir.join("global.ignore");
+        fs::write(&global_ignore, b"*.log\n").unwrap();
+        let server_ignore = working_dir.join(".backupignore");
+        fs::write(&server_ignore, b"cache/\n.backupignore\n").unwrap();
+
+        let mut manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        manager.add_ignore_file(&global_ignore).expect("Failed to add global ignore file");
+        manager.add_ignore_file(&server_ignore).expect("Failed to add server ignore file");
+        manager.add_ignore_pattern("*.dmp").expect("Failed to add ignore pattern");
+        manager.add_ignore_pattern("!logs/keep.log").expect("Failed to add ignore pattern");
+
+        let backup_id = manager.backup(None).expect("Failed to create backup");
+        let paths: Vec<_> = manager
+            .list_files(&backup_id)
+            .unwrap()
+            .into_iter()
+            .filter(|entry| !entry.is_directory)
+            .map(|entry| entry.path)
+            .collect();
+        assert_eq!(paths, vec!["logs/keep.log", "server.properties"]);
+
+        assert!(
+            manager.add_ignore_file(working_dir.join("missing.ignore")).is_err(),
+            "Missing ignore files should fail"
+        );
+    }
 
+    /// Polls until the store holds `count` backups, giving blocking backup work time to finish.
+    #[cfg(feature = "scheduler")]
+    async fn wait_for_backups(manager: &BackupManager, count: usize) -> usize {
+        for _ in 0..200 {
+            let found = manager.list().expect("Failed to list backups").len();
+            if found >= count {
+                return found;
+            }
+            tokio::task::yield_now().await;
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        manager.list().expect("Failed to list backups").len()
+    }
+
+    #[tokio::test(start_paused = true)]
+    #[cfg(feature = "scheduler")]
+    async fn test_scheduled_backups_skip_unchanged() {
+        use obsidian_backups::data::schedule_policy::SchedulePolicy;
+        use obsidian_scheduler::timer_trait::Timer;
+        use std::time::Duration;
+
+        let (store_dir, working_dir) = setup_test_env("schedule");
+        create_test_file(&working_dir, "world.dat", b"Initial world");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let hour = Duration::from_secs(60 * 60);
+        let timer = manager
+            .schedule(SchedulePolicy::every(hour))
+            .await
+            .expect("Failed to schedule backups");
+
+        tokio::time::sleep(hour / 2).await;
+        assert_eq!(wait_for_backups(&manager, 1).await, 0, "Nothing should run before the interval");
+
+        tokio::time::sleep(hour).await;
+        assert_eq!(wait_for_backups(&manager, 1).await, 1, "First scheduled backup");
+
+        // Nothing changed, so the next tick must not add an empty backup
+        tokio::time::sleep(hour).await;
+        assert_eq!(wait_for_backups(&manager, 2).await, 1, "Unchanged tick should be skipped");
+
+        create_test_file(&working_dir, "world.dat", b"Changed world");
+        tokio::time::sleep(hour).await;
+        assert_eq!(wait_for_backups(&manager, 2).await, 2, "Changed tick should back up");
+        assert_eq!(
+            manager.last().unwrap().unwrap().description,
+            "Scheduled backup"
+        );
+
+        timer.stop().await.expect("Failed to stop timer");
+    }
+
+    #[tokio::test(start_paused = true)]
+    #[cfg(feature = "scheduler")]
+    async fn test_scheduled_backups_apply_retention() {
+        use obsidian_backups::data::schedule_policy::SchedulePolicy;
+        use obsidian_scheduler::timer_trait::Timer;
+        use std::time::Duration;
+
+        let (store_dir, working_dir) = setup_test_env("schedule_retention");
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let minute = Duration::from_secs(60);
+        let policy = SchedulePolicy {
+            max_count: Some(2),
+            ..SchedulePolicy::every(minute)
+        };
+        let timer = manager.schedule(policy).await.expect("Failed to schedule backups");
+
+        for i in 0..4 {
+            create_test_file(&working_dir, "level.dat", format!("Tick {}", i).as_bytes());
+            tokio::time::sleep(minute).await;
+            wait_for_backups(&manager, (i + 1).min(2)).await;
+        }
+        // Let the last tick's retention finish
+        tokio::time::sleep(minute).await;
+        assert_eq!(wait_for_backups(&manager, 3).await, 2, "Retention should keep 2 backups");
+
+        timer.stop().await.expect("Failed to stop timer");
+    }
+
+    #[tokio::test(start_paused = true)]
+    #[cfg(feature = "scheduler")]
+    async fn test_scheduled_backups_follow_cron() {
+        use obsidian_backups::data::schedule_policy::SchedulePolicy;
+        use obsidian_scheduler::timer_trait::Timer;
+        use std::time::Duration;
+
+        assert!(SchedulePolicy::cron("every night").is_err());
+
+        let (store_dir, working_dir) = setup_test_env("schedule_cron");
+        create_test_file(&working_dir, "world.dat", b"Initial world");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let minute = Duration::from_secs(60);
+        let policy = SchedulePolicy {
+            description: Some("Minutely backup".to_string()),
+            ..SchedulePolicy::cron("0 * * * * *").expect("Failed to parse cron expression")
+        };
+        let timer = manager.schedule(policy).await.expect("Failed to schedule backups");
+
+        // The first occurrence is the start of the next minute
+        tokio::time::sleep(minute).await;
+        assert_eq!(wait_for_backups(&manager, 1).await, 1, "First cron backup");
+
+        for i in 0..2 {
+            create_test_file(&working_dir, "world.dat", format!("Minute {}", i).as_bytes());
+            tokio::time::sleep(minute).await;
+            assert_eq!(wait_for_backups(&manager, i + 2).await, i + 2, "One backup per occurrence");
+        }
+        assert_eq!(
+            manager.last().unwrap().unwrap().description,
+            "Minutely backup"
+        );
+
+        timer.stop().await.expect("Failed to stop timer");
+    }
+
+    /// Serves just enough of the S3 API for uploads, recording each request line.
+    #[cfg(feature = "s3")]
+    async fn mock_s3() -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    let mut stream = BufReader::new(stream);
+                    let mut request_line = String::new();
+                    while stream.read_line(&mut request_line).await.unwrap_or(0) > 0 {
+                        let mut length = 0;
+                        loop {
+                            let mut header = String::new();
+                            stream.read_line(&mut header).await.unwrap();
+                            if header.trim().is_empty() {
+                                break;
+                            }
+                            if let Some((name, value)) = header.split_once(':')
+                                && name.eq_ignore_ascii_case("content-length")
+                            {
+                                length = value.trim().parse().unwrap();
+                            }
+                        }
+                        let mut body = vec![0; length];
+                        stream.read_exact(&mut body).await.unwrap();
+
+                        recorded.lock().unwrap().push(request_line.trim().to_string());
+                        let response = "HTTP/1.1 200 OK\r\nETag: \"etag\"\r\nContent-Length: 0\r\n\r\n";
+                        stream.get_mut().write_all(response.as_bytes()).await.unwrap();
+                        request_line.clear();
+                    }
+                });
+            }
+        });
+        (endpoint, requests)
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "s3")]
+    async fn test_push_to_s3() {
+        use obsidian_backups::data::s3_credentials::S3Credentials;
+
+        let (store_dir, working_dir) = setup_test_env("push_s3");
+        create_test_file(&working_dir, "world.dat", b"World data");
+
+        let manager =
+            BackupManager::new(&store_dir, &working_dir).expect("Failed to create BackupManager");
+        let backup_id = manager.backup(None).expect("Failed to create backup");
+
+        let (endpoint, requests) = mock_s3().await;
+        let credentials = S3Credentials::new("access-key", "secret-key", "us-east-1")
+            .with_endpoint(endpoint);
+        manager
+            .push_to_s3(&backup_id, "backups", "server/latest.7z", &credentials)
+            .await
+            .expect("Failed to push backup to S3");
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1, "Small archives need one request: {:?}", requests);
+        assert!(
+            requests[0].starts_with("PUT /backups/server/latest.7z"),
+            "Unexpected request: {}",
+            requests[0]
+        );
+    }
+}
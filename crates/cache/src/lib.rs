@@ -100,6 +100,41 @@ where
         );
     }
 
+    /// Atomically replaces the value for `key` using its default TTL. See
+    /// [`update_with_ttl`](Self::update_with_ttl).
+    pub async fn update<R>(&self, key: K, f: impl FnOnce(Option<V>) -> (V, R)) -> R {
+        self.update_with_ttl(key, self.default_ttl, f).await
+    }
+
+    /// Atomically replaces the value for `key` with a custom TTL.
+    ///
+    /// `f` receives the current value, or `None` if the key is missing or
+    /// expired, and returns the new value along with a result that is passed
+    /// back to the caller. No other access to the cache happens in between,
+    /// so read-modify-write sequences such as counters cannot race.
+    pub async fn update_with_ttl<R>(
+        &self,
+        key: K,
+        ttl: Duration,
+        f: impl FnOnce(Option<V>) -> (V, R),
+    ) -> R {
+        let mut entries = self.entries.write().await;
+        let current = entries
+            .get(&key)
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| entry.value.clone());
+        let (value, result) = f(current);
+        entries.insert(
+            key,
+            CacheEntry {
+                value,
+                inserted_at: Instant::now(),
+                ttl,
+            },
+        );
+        result
+    }
+
     /// Removes a specific entry from the cache.
     pub async fn invalidate(&self, key: &K) {
         let mut entries = self.entries.write().await;
@@ -198,6 +233,52 @@ mod tests {
         assert!(cache.get(&"fresh".to_string()).await.is_some());
     }
 
+    #[tokio::test]
+    async fn test_update() {
+        let cache: TtlCache<String, u32> = TtlCache::new(Duration::from_secs(60));
+        let previous = cache
+            .update("count".to_string(), |count| (count.unwrap_or(0) + 1, count))
+            .await;
+        assert_eq!(previous, None);
+        let previous = cache
+            .update("count".to_string(), |count| (count.unwrap_or(0) + 1, count))
+            .await;
+        assert_eq!(previous, Some(1));
+        assert_eq!(cache.get(&"count".to_string()).await, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_update_ignores_expired_value() {
+        let cache: TtlCache<String, u32> = TtlCache::new(Duration::from_secs(60));
+        cache
+            .update_with_ttl("count".to_string(), Duration::from_millis(50), |_| (5, ()))
+            .await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let previous = cache
+            .update("count".to_string(), |count| (count.unwrap_or(0) + 1, count))
+            .await;
+        assert_eq!(previous, None);
+        assert_eq!(cache.get(&"count".to_string()).await, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_updates_do_not_race() {
+        let cache: Arc<TtlCache<String, u32>> = Arc::new(TtlCache::new(Duration::from_secs(60)));
+        let mut handles = Vec::new();
+        for _ in 0..50 {
+            let cache = cache.clone();
+            handles.push(tokio::spawn(async move {
+                cache
+                    .update("count".to_string(), |count| (count.unwrap_or(0) + 1, ()))
+                    .await;
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(cache.get(&"count".to_string()).await, Some(50));
+    }
+
     #[tokio::test]
     async fn test_overwrite_existing_key() {
         let cache: TtlCache<String, String> = TtlCache::new(Duration::from_secs(60));
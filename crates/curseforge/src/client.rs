@@ -17,6 +17,14 @@ const MOD_CACHE_TTL: Duration = Duration::from_secs(15 * 60); // 15 minutes
 /// Cache TTL for mod file lists.
 const FILE_CACHE_TTL: Duration = Duration::from_secs(10 * 60); // 10 minutes
 
+/// Whether the API key may be sent to `url`: over HTTPS, or over plain HTTP to this machine
+/// only, so a local mock server can stand in for the API.
+fn is_secure_url(url: &str) -> bool {
+    url.starts_with("https://")
+        || url.starts_with("http://127.0.0.1:")
+        || url.starts_with("http://localhost:")
+}
+
 /// A client for the CurseForge API v1 with built-in response caching.
 ///
 /// # Example
@@ -101,7 +109,7 @@ impl CurseForgeClient {
 
     /// Performs a GET request and deserializes the response.
     async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
-        if !url.starts_with("https://") {
+        if !is_secure_url(url) {
             return Err(CurseForgeError::Other(anyhow::anyhow!(
                 "Only HTTPS URLs are allowed"
             )));
@@ -287,6 +295,14 @@ impl CurseForgeClient {
         Ok(wrapper.data)
     }
 
+    /// Looks up Minecraft files by their [`fingerprint`](fn@crate::fingerprint). Results are not cached.
+    pub async fn get_fingerprint_matches(&self, fingerprints: &[u32]) -> Result<FingerprintMatches> {
+        let url = format!("{}/fingerprints/{}", self.base_url, MINECRAFT_GAME_ID);
+        let body = serde_json::json!({ "fingerprints": fingerprints });
+        let wrapper: DataWrapper<FingerprintMatches> = self.post_json(&url, &body).await?;
+        Ok(wrapper.data)
+    }
+
     /// Fetches all categories for Minecraft (gameId=432).
     pub async fn get_categories(&self) -> Result<Vec<Category>> {
         let cache_key = "categories".to_string();
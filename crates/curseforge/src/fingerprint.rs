@@ -0,0 +1,58 @@
+//! CurseForge's file fingerprint, used to look files up with
+//! [`CurseForgeClient::get_fingerprint_matches`](crate::CurseForgeClient::get_fingerprint_matches).
+
+/// Computes the CurseForge fingerprint of a file's contents.
+///
+/// This is MurmurHash2 with a seed of 1, taken over the contents with every tab, line feed,
+/// carriage return and space removed.
+pub fn fingerprint(contents: &[u8]) -> u32 {
+    const M: u32 = 0x5bd1e995;
+    const R: u32 = 24;
+
+    let bytes: Vec<u8> = contents
+        .iter()
+        .copied()
+        .filter(|byte| !matches!(byte, 9 | 10 | 13 | 32))
+        .collect();
+
+    let mut hash = 1 ^ bytes.len() as u32;
+    let mut chunks = bytes.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        hash = hash.wrapping_mul(M) ^ k;
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (i, byte) in tail.iter().enumerate() {
+            hash ^= (*byte as u32) << (8 * i);
+        }
+        hash = hash.wrapping_mul(M);
+    }
+
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(M);
+    hash ^= hash >> 15;
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whitespace_is_ignored() {
+        assert_eq!(fingerprint(b"abc def\r\n\tghi"), fingerprint(b"abcdefghi"));
+        assert_ne!(fingerprint(b"abcdefghi"), fingerprint(b"abcdefghj"));
+    }
+
+    #[test]
+    fn test_fingerprint_values() {
+        assert_eq!(fingerprint(b""), 0x5bd15e36);
+        // Two full blocks and a one byte tail
+        assert_eq!(fingerprint(b"abcdefghi"), 0x137c8cbe);
+    }
+}
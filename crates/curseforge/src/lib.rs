@@ -6,6 +6,7 @@
 //! ## Features
 //!
 //! - Full coverage of CurseForge's mod, file, and category endpoints
+//! - File lookup by fingerprint, with [`fingerprint()`] to compute them
 //! - Automatic in-memory TTL caching per resource type
 //! - Fluent [`SearchBuilder`] for constructing search queries
 //! - Rate-limit detection with `Retry-After` parsing
@@ -33,10 +34,12 @@
 pub mod cache;
 pub mod client;
 pub mod error;
+pub mod fingerprint;
 pub mod models;
 pub mod search;
 
 pub use client::CurseForgeClient;
 pub use error::{CurseForgeError, Result};
+pub use fingerprint::fingerprint;
 pub use models::*;
 pub use search::{SearchBuilder, SearchParams, CLASS_ID_MODPACKS, CLASS_ID_MODS, MINECRAFT_GAME_ID};
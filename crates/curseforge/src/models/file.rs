@@ -58,6 +58,48 @@ pub struct File {
     pub modules: Vec<FileModule>,
 }
 
+/// Host that serves CurseForge files, including files whose authors opted out of third-party
+/// distribution.
+pub const CDN_BASE_URL: &str = "https://edge.forgecdn.net";
+
+/// Hash algorithm ID for SHA-1 in [`FileHash::algo`].
+pub const HASH_ALGO_SHA1: u32 = 1;
+
+impl File {
+    /// The URL to download this file from.
+    ///
+    /// Uses `download_url` when the API provides one and falls back to [`cdn_url`](Self::cdn_url)
+    /// when it is null, which happens for mods that disallow third-party distribution.
+    pub fn resolved_download_url(&self) -> String {
+        self.download_url
+            .clone()
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| self.cdn_url())
+    }
+
+    /// The CDN URL of this file, built from its ID and file name.
+    ///
+    /// The CDN splits the file ID into the thousands and the zero-padded remainder, so file
+    /// `5000001` named `mod.jar` is served at `/files/5000/001/mod.jar`.
+    pub fn cdn_url(&self) -> String {
+        format!(
+            "{}/files/{}/{:03}/{}",
+            CDN_BASE_URL,
+            self.id / 1000,
+            self.id % 1000,
+            self.file_name
+        )
+    }
+
+    /// The SHA-1 hash of this file, if CurseForge provides one.
+    pub fn sha1(&self) -> Option<&str> {
+        self.hashes
+            .iter()
+            .find(|hash| hash.algo == HASH_ALGO_SHA1)
+            .map(|hash| hash.value.as_str())
+    }
+}
+
 /// A hash digest for a file.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
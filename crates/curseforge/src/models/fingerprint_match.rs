@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+use super::File;
+
+/// The result of looking files up by their fingerprints.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FingerprintMatches {
+    /// Whether CurseForge has finished indexing fingerprints.
+    #[serde(default)]
+    pub is_cache_built: bool,
+    /// Files whose fingerprint matched exactly.
+    #[serde(default)]
+    pub exact_matches: Vec<FingerprintMatch>,
+    /// The fingerprints that matched exactly.
+    #[serde(default)]
+    pub exact_fingerprints: Vec<u64>,
+    /// The fingerprints that matched no file.
+    #[serde(default)]
+    pub unmatched_fingerprints: Option<Vec<u64>>,
+}
+
+/// A file found by its fingerprint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FingerprintMatch {
+    /// The mod ID the file belongs to.
+    pub id: u32,
+    /// The matched file.
+    pub file: File,
+    /// The mod's latest files.
+    #[serde(default)]
+    pub latest_files: Vec<File>,
+}
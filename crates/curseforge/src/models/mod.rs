@@ -1,10 +1,12 @@
 pub mod category;
 pub mod file;
+pub mod fingerprint_match;
 pub mod project;
 pub mod search;
 
 pub use category::Category;
 pub use file::{File, FileDependency, FileHash, FileModule, SortableGameVersion};
+pub use fingerprint_match::{FingerprintMatch, FingerprintMatches};
 pub use project::{
     FileIndex, Mod, ModAsset, ModAuthor, ModLinks, ProjectCategory,
 };
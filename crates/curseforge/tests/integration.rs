@@ -1,5 +1,5 @@
 use curseforge::{CurseForgeClient, SearchBuilder};
-use wiremock::matchers::{method, path};
+use wiremock::matchers::{body_json, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 fn search_result_json() -> serde_json::Value {
@@ -235,6 +235,33 @@ async fn test_get_mod_file() {
     assert_eq!(file.file_name, "sodium-fabric-0.5.3+mc1.20.1.jar");
 }
 
+#[tokio::test]
+async fn test_download_url_falls_back_to_cdn() {
+    let wrapper = files_json();
+    let mut files: Vec<curseforge::File> = serde_json::from_value(wrapper["data"].clone()).unwrap();
+
+    // The reconstructed URL matches what the API returns when it does provide one
+    for file in &files {
+        assert_eq!(Some(file.cdn_url()), file.download_url);
+        assert_eq!(file.resolved_download_url(), file.cdn_url());
+    }
+
+    let file = &mut files[0];
+    file.download_url = None;
+    assert_eq!(
+        file.resolved_download_url(),
+        "https://edge.forgecdn.net/files/5000/001/sodium-fabric-0.5.3+mc1.20.1.jar"
+    );
+
+    file.id = 4_123_050;
+    file.file_name = "create-1.20.1-0.5.1.f.jar".to_string();
+    assert_eq!(
+        file.resolved_download_url(),
+        "https://edge.forgecdn.net/files/4123/050/create-1.20.1-0.5.1.f.jar"
+    );
+    assert_eq!(file.sha1(), Some("abc123def456"));
+}
+
 #[tokio::test]
 async fn test_get_categories() {
     let mock_server = MockServer::start().await;
@@ -358,3 +385,38 @@ async fn test_model_deserialization_from_fixtures() {
         serde_json::from_str(include_str!("../test_fixtures/categories.json")).unwrap();
     let _: Vec<curseforge::Category> = serde_json::from_value(wrapper["data"].clone()).unwrap();
 }
+
+#[tokio::test]
+async fn test_get_fingerprint_matches() {
+    let mock_server = MockServer::start().await;
+
+    let files = files_json()["data"].clone();
+    let response = serde_json::json!({
+        "data": {
+            "isCacheBuilt": true,
+            "exactMatches": [
+                { "id": 394468, "file": files[1], "latestFiles": [files[0]] }
+            ],
+            "exactFingerprints": [3456789012u32],
+            "partialMatches": [],
+            "installedFingerprints": [3456789012u32, 42],
+            "unmatchedFingerprints": [42]
+        }
+    });
+
+    Mock::given(method("POST"))
+        .and(path("/v1/fingerprints/432"))
+        .and(body_json(serde_json::json!({ "fingerprints": [3456789012u32, 42] })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(&mock_server)
+        .await;
+
+    let client = CurseForgeClient::with_base_url("test-key", format!("{}/v1", mock_server.uri()));
+    let matches = client.get_fingerprint_matches(&[3456789012, 42]).await.unwrap();
+
+    assert!(matches.is_cache_built);
+    assert_eq!(matches.exact_matches.len(), 1);
+    assert_eq!(matches.exact_matches[0].id, 394468);
+    assert_eq!(matches.exact_matches[0].latest_files[0].id, 5000001);
+    assert_eq!(matches.unmatched_fingerprints, Some(vec![42]));
+}
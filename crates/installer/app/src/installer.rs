@@ -261,11 +261,24 @@ pub async fn perform_uninstall(state: Arc<Mutex<InstallerState>>) -> Result<()>
 
     let mut manager = InstallationManager::new(config);
 
+    // Subscribe to progress updates
+    let mut progress_rx = manager.subscribe();
+
+    // Clone state for the spawned task
+    let state_clone = Arc::clone(&state);
+
+    // Spawn a task to listen for progress updates
+    tokio::spawn(async move {
+        while let Ok(progress) = progress_rx.recv().await {
+            update_progress_state(&state_clone, &progress);
+        }
+    });
+
     // Update state
     {
         let mut s = state.lock().unwrap();
         s.status = "Uninstalling...".to_string();
-        s.progress = 0.5;
+        s.progress = 0.0;
     }
 
     match manager.uninstall().await {
@@ -318,5 +331,10 @@ fn update_progress_state(state: &Arc<Mutex<InstallerState>>, progress: &StatePro
             s.progress = 0.5 + (progress.progress * 0.5);
             debug!("Updating progress: {:.2}%", progress.progress * 100.0);
         }
+        State::Uninstalling => {
+            s.status = "Uninstalling...".to_string();
+            s.progress = progress.progress;
+            debug!("Uninstalling progress: {:.2}%", progress.progress * 100.0);
+        }
     }
 }
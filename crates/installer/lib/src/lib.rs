@@ -1,6 +1,8 @@
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use anyhow::{Context, Result};
 use tokio::sync::broadcast;
 
@@ -122,7 +124,8 @@ pub enum State{
     Downloading,
     Extracting,
     Installing,
-    Updating
+    Updating,
+    Uninstalling
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -138,6 +141,48 @@ impl StateProgress {
     }
 }
 
+/// Error returned when an operation is aborted through [`InstallationManager::cancel`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Operation was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Token bucket used to pace downloads to a maximum average rate
+struct Throttle {
+    bytes_per_sec: f64,
+    available: f64,
+    last_refill: tokio::time::Instant,
+}
+
+impl Throttle {
+    /// Create a throttle for the given rate, or `None` if the rate is unlimited
+    fn new(bytes_per_sec: Option<u64>) -> Option<Self> {
+        let bytes_per_sec = bytes_per_sec.filter(|&rate| rate > 0)? as f64;
+        // Start with an empty bucket so the average never exceeds the cap, even for small downloads
+        Some(Self { bytes_per_sec, available: 0.0, last_refill: tokio::time::Instant::now() })
+    }
+
+    /// Take `bytes` tokens from the bucket, sleeping until the deficit has been refilled
+    async fn consume(&mut self, bytes: usize) {
+        let now = tokio::time::Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.available = (self.available + elapsed * self.bytes_per_sec).min(self.bytes_per_sec);
+        self.last_refill = now;
+
+        self.available -= bytes as f64;
+        if self.available < 0.0 {
+            let wait = std::time::Duration::from_secs_f64(-self.available / self.bytes_per_sec);
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
 /// Configuration for the installation manager
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InstallationConfig {
@@ -159,6 +204,18 @@ pub struct InstallationConfig {
     pub version_file_dir: Option<String>,
     /// Working directory for the service (optional, defaults to install_path)
     pub working_directory: Option<PathBuf>,
+    /// Directory used for downloads and extraction staging (optional, defaults to the system temp dir)
+    pub temp_dir: Option<PathBuf>,
+    /// Maximum average download speed in bytes per second (optional, unlimited if not set)
+    pub max_download_bytes_per_sec: Option<u64>,
+    /// Number of previous installs to keep for rollback (0 disables archiving)
+    #[serde(default)]
+    pub keep_versions: usize,
+    /// Base URL of the GitHub API (optional, defaults to https://api.github.com)
+    pub github_api_url: Option<String>,
+    /// Only manage the files and recorded version, without registering or controlling a service
+    #[serde(default)]
+    pub skip_service: bool,
 }
 
 impl InstallationConfig {
@@ -178,6 +235,11 @@ impl InstallationConfig {
             registry_path: None,
             version_file_dir: None,
             working_directory: None,
+            temp_dir: None,
+            max_download_bytes_per_sec: None,
+            keep_versions: 0,
+            github_api_url: None,
+            skip_service: false,
         }
     }
 
@@ -217,6 +279,36 @@ impl InstallationConfig {
         self
     }
 
+    /// Set a custom directory for downloads and extraction staging
+    pub fn temp_dir(mut self, dir: PathBuf) -> Self {
+        self.temp_dir = Some(dir);
+        self
+    }
+
+    /// Cap the average download speed, or remove the cap with `None`
+    pub fn max_download_bytes_per_sec(mut self, limit: Option<u64>) -> Self {
+        self.max_download_bytes_per_sec = limit;
+        self
+    }
+
+    /// Keep up to `count` previous installs so they can be restored with `rollback()`
+    pub fn keep_versions(mut self, count: usize) -> Self {
+        self.keep_versions = count;
+        self
+    }
+
+    /// Fetch releases from a different GitHub API host, such as GitHub Enterprise or a mirror
+    pub fn github_api_url(mut self, url: String) -> Self {
+        self.github_api_url = Some(url);
+        self
+    }
+
+    /// Install, update and roll back the files without registering, starting or stopping a service
+    pub fn skip_service(mut self, skip: bool) -> Self {
+        self.skip_service = skip;
+        self
+    }
+
     /// Get the service display name (returns service_name if not set)
     pub fn get_display_name(&self) -> &str {
         self.service_display_name.as_deref().unwrap_or(&self.service_name)
@@ -234,6 +326,20 @@ impl InstallationConfig {
         self.working_directory.as_ref().unwrap_or(&self.install_path)
     }
 
+    /// Get the temp directory (returns the system temp dir if not set)
+    pub fn get_temp_dir(&self) -> PathBuf {
+        self.temp_dir.clone().unwrap_or_else(std::env::temp_dir)
+    }
+
+    /// Get the directory holding archived versions (a sibling of install_path)
+    pub fn get_versions_dir(&self) -> PathBuf {
+        let name = self.install_path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.service_name.clone());
+        self.install_path.with_file_name(format!("{}-versions", name))
+    }
+
     /// Get the registry path (Windows)
     pub fn get_registry_path(&self) -> &str {
         self.registry_path.as_deref().unwrap_or(r"SOFTWARE\ObsidianInstallationManager")
@@ -243,6 +349,11 @@ impl InstallationConfig {
     pub fn get_version_file_dir(&self) -> &str {
         self.version_file_dir.as_deref().unwrap_or("/var/lib/oim")
     }
+
+    /// Get the GitHub API base URL, without a trailing slash
+    pub fn get_github_api_url(&self) -> &str {
+        self.github_api_url.as_deref().unwrap_or("https://api.github.com").trim_end_matches('/')
+    }
 }
 
 /// Check if an installation exists by querying the system (Windows registry or Linux version file)
@@ -283,6 +394,8 @@ pub struct InstallationManager {
     config: InstallationConfig,
     #[serde(skip)]
     progress_tx: broadcast::Sender<StateProgress>,
+    #[serde(skip)]
+    cancel_flag: Arc<AtomicBool>,
 }
 
 impl InstallationManager {
@@ -295,6 +408,7 @@ impl InstallationManager {
             latest_version: None,
             config,
             progress_tx: tx,
+            cancel_flag: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -322,6 +436,29 @@ impl InstallationManager {
         let _ = self.progress_tx.send(StateProgress::new(state, progress));
     }
 
+    /// Get a handle that cancels the running download or extraction when set to `true`
+    pub fn cancel_handle(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.cancel_flag)
+    }
+
+    /// Cancel the running download or extraction (the flag is cleared when `install()`, `repair()` or `update()` starts)
+    pub fn cancel(&self) {
+        self.cancel_flag.store(true, Ordering::SeqCst);
+    }
+
+    /// Check if cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_flag.load(Ordering::SeqCst)
+    }
+
+    /// Fail with [`Cancelled`] if cancellation has been requested (internal helper)
+    fn check_cancelled(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Cancelled.into());
+        }
+        Ok(())
+    }
+
     /// Check if the application is currently installed
     pub fn is_installed(&self) -> bool {
         self.is_installed
@@ -363,7 +500,8 @@ impl InstallationManager {
     /// Fetch releases from GitHub
     pub async fn fetch_releases(&self) -> Result<Vec<GitHubRelease>> {
         let url = format!(
-            "https://api.github.com/repos/{}/releases",
+            "{}/repos/{}/releases",
+            self.config.get_github_api_url(),
             self.config.github_repo
         );
 
@@ -656,10 +794,17 @@ impl InstallationManager {
 
         let mut downloaded: u64 = 0;
         let mut stream = response.bytes_stream();
+        let mut throttle = Throttle::new(self.config.max_download_bytes_per_sec);
 
         self.broadcast_progress(State::Downloading, 0.0);
 
         while let Some(chunk) = stream.next().await {
+            if self.is_cancelled() {
+                drop(file);
+                let _ = tokio::fs::remove_file(dest_path).await;
+                return Err(Cancelled.into());
+            }
+
             let chunk = chunk.context(format!(
                 "Network error while downloading '{}'. The connection may have been interrupted.",
                 asset.name
@@ -678,41 +823,87 @@ impl InstallationManager {
                 let progress = downloaded as f32 / total_size as f32;
                 self.broadcast_progress(State::Downloading, progress);
             }
+
+            if let Some(throttle) = throttle.as_mut() {
+                throttle.consume(chunk.len()).await;
+            }
         }
 
+        file.flush()
+            .await
+            .context(format!(
+                "Failed to write to '{}'. Check available disk space.",
+                dest_path.display()
+            ))?;
+
         self.broadcast_progress(State::Downloading, 1.0);
         Ok(())
     }
 
-    /// Extract downloaded archive
-    pub fn extract_archive(&self, archive_path: &PathBuf, extract_to: &PathBuf) -> Result<()> {
-        self.broadcast_progress(State::Extracting, 0.0);
-        std::fs::create_dir_all(extract_to)
+    /// Get the staging directory used for downloads of this service
+    pub fn download_dir(&self) -> PathBuf {
+        self.config.get_temp_dir().join(format!("oim-{}", self.config.service_name))
+    }
+
+    /// Download a release asset into the staging directory, returning the downloaded file path
+    pub async fn download_to_temp(&self, asset: &GitHubAsset) -> Result<PathBuf> {
+        let temp_dir = self.download_dir();
+        tokio::fs::create_dir_all(&temp_dir)
+            .await
             .context(format!(
-                "Failed to create extraction directory '{}'. Check write permissions.",
-                extract_to.display()
+                "Failed to create download directory '{}'. Check write permissions.",
+                temp_dir.display()
             ))?;
 
+        let download_path = temp_dir.join(&asset.name);
+        self.download_asset(asset, &download_path).await?;
+        Ok(download_path)
+    }
+
+    /// Extract downloaded archive
+    ///
+    /// The archive is unpacked into a staging directory first and only moved into `extract_to`
+    /// once every entry was extracted, so a cancelled or failed extraction leaves it untouched.
+    pub fn extract_archive(&self, archive_path: &PathBuf, extract_to: &std::path::Path) -> Result<()> {
+        self.broadcast_progress(State::Extracting, 0.0);
+
         let file_name = archive_path
             .file_name()
             .and_then(|n| n.to_str())
             .context(format!("Invalid archive path: {}", archive_path.display()))?;
 
-        if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
-            self.extract_tar_gz(archive_path, extract_to)
-                .context(format!("Failed to extract TAR.GZ archive '{}'", file_name))?;
+        let staging_dir = self.download_dir().join("extracting");
+        if staging_dir.exists() {
+            std::fs::remove_dir_all(&staging_dir)?;
+        }
+        std::fs::create_dir_all(&staging_dir)
+            .context(format!(
+                "Failed to create extraction directory '{}'. Check write permissions.",
+                staging_dir.display()
+            ))?;
+
+        let extracted = if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+            self.extract_tar_gz(archive_path, &staging_dir)
+                .context(format!("Failed to extract TAR.GZ archive '{}'", file_name))
         } else if file_name.ends_with(".zip") {
-            self.extract_zip(archive_path, extract_to)
-                .context(format!("Failed to extract ZIP archive '{}'", file_name))?;
+            self.extract_zip(archive_path, &staging_dir)
+                .context(format!("Failed to extract ZIP archive '{}'", file_name))
         } else {
-            anyhow::bail!(
+            Err(anyhow::anyhow!(
                 "Unsupported archive format: '{}'. Supported formats: .zip, .tar.gz, .tgz",
                 file_name
-            );
-        }
+            ))
+        };
 
         // Progress is now reported from within the extraction functions
-        Ok(())
+        let result = extracted.and_then(|_| {
+            move_dir_all(&staging_dir, extract_to).context(format!(
+                "Failed to move extracted files to '{}'. Check write permissions.",
+                extract_to.display()
+            ))
+        });
+        let _ = std::fs::remove_dir_all(&staging_dir);
+        result
     }
 
     fn extract_tar_gz(&self, archive_path: &PathBuf, extract_to: &PathBuf) -> Result<()> {
@@ -733,6 +924,7 @@ impl InstallationManager {
         // Second pass: extract with progress based on bytes
         let mut extracted_bytes: u64 = 0;
         for entry in archive.entries()? {
+            self.check_cancelled()?;
             let mut entry = entry?;
             let entry_size = entry.header().size().unwrap_or(0);
             entry.unpack_in(extract_to)?;
@@ -764,6 +956,7 @@ impl InstallationManager {
         let mut extracted_bytes: u64 = 0;
 
         for i in 0..archive.len() {
+            self.check_cancelled()?;
             let mut file = archive.by_index(i)?;
             let file_size = file.size();
             let outpath = match file.enclosed_name() {
@@ -804,21 +997,28 @@ impl InstallationManager {
 
     /// Install a release from the specified channel
     pub async fn install(&mut self, channel: ReleaseChannel) -> Result<()> {
+        self.cancel_flag.store(false, Ordering::SeqCst);
+        self.install_release(channel).await
+    }
+
+    /// Install a release without clearing the cancel flag, so `update()` keeps a cancel
+    /// requested before it got here (internal helper)
+    async fn install_release(&mut self, channel: ReleaseChannel) -> Result<()> {
         let release = self.get_latest_release(channel).await?;
         let asset = self.select_asset(&release)?;
 
         println!("Installing {} version {}...", self.config.service_name, release.tag_name);
         println!("Downloading {}...", asset.name);
 
-        // Create temporary download directory
-        let temp_dir = std::env::temp_dir().join(format!("oim-{}", self.config.service_name));
-        tokio::fs::create_dir_all(&temp_dir).await?;
+        let download_path = self.download_to_temp(&asset).await?;
 
-        let download_path = temp_dir.join(&asset.name);
-        self.download_asset(&asset, &download_path).await?;
+        self.archive_current_install()?;
 
         println!("Extracting to {}...", self.config.install_path.display());
-        self.extract_archive(&download_path, &self.config.install_path)?;
+        if let Err(e) = self.extract_archive(&download_path, &self.config.install_path) {
+            let _ = tokio::fs::remove_file(&download_path).await;
+            return Err(e);
+        }
 
         // Set directory permissions on Windows
         #[cfg(target_os = "windows")]
@@ -858,6 +1058,7 @@ impl InstallationManager {
     /// This preserves configuration files and user data while updating application files
     pub async fn repair(&mut self, channel: ReleaseChannel) -> Result<()> {
         println!("Repairing {} installation...", self.config.service_name);
+        self.cancel_flag.store(false, Ordering::SeqCst);
 
         let release = self.get_latest_release(channel).await?;
         let asset = self.select_asset(&release)?;
@@ -865,16 +1066,14 @@ impl InstallationManager {
         println!("Downloading {} version {}...", self.config.service_name, release.tag_name);
         println!("Downloading {}...", asset.name);
 
-        // Create temporary download directory
-        let temp_dir = std::env::temp_dir().join(format!("oim-{}", self.config.service_name));
-        tokio::fs::create_dir_all(&temp_dir).await?;
-
-        let download_path = temp_dir.join(&asset.name);
-        self.download_asset(&asset, &download_path).await?;
+        let download_path = self.download_to_temp(&asset).await?;
 
         println!("Extracting to {}... (existing files will be preserved)", self.config.install_path.display());
         // Extract overwrites files but doesn't delete existing ones
-        self.extract_archive(&download_path, &self.config.install_path)?;
+        if let Err(e) = self.extract_archive(&download_path, &self.config.install_path) {
+            let _ = tokio::fs::remove_file(&download_path).await;
+            return Err(e);
+        }
 
         // Set directory permissions on Windows
         #[cfg(target_os = "windows")]
@@ -915,6 +1114,7 @@ impl InstallationManager {
         if !self.is_installed {
             anyhow::bail!("No installation found. Use install() instead.");
         }
+        self.cancel_flag.store(false, Ordering::SeqCst);
 
         let has_update = self.check_for_updates(channel).await?;
         if !has_update {
@@ -929,6 +1129,7 @@ impl InstallationManager {
         );
 
         self.broadcast_progress(State::Updating, 0.0);
+        self.check_cancelled()?;
 
         // Platform-specific service stop
         #[cfg(target_os = "windows")]
@@ -944,7 +1145,21 @@ impl InstallationManager {
         self.broadcast_progress(State::Updating, 0.2);
 
         // Perform installation (which will overwrite existing files)
-        self.install(channel).await?;
+        if let Err(e) = self.install_release(channel).await {
+            // A cancelled update leaves the previous install in place, so bring it back up
+            if e.is::<Cancelled>() {
+                #[cfg(target_os = "windows")]
+                {
+                    win::start_service(&self.config)?;
+                }
+
+                #[cfg(target_os = "linux")]
+                {
+                    nix::start_service(&self.config)?;
+                }
+            }
+            return Err(e);
+        }
 
         self.broadcast_progress(State::Updating, 0.8);
 
@@ -980,6 +1195,7 @@ impl InstallationManager {
         }
 
         println!("Uninstalling {}...", self.config.service_name);
+        self.broadcast_progress(State::Uninstalling, 0.0);
 
         // Platform-specific service removal
         #[cfg(target_os = "windows")]
@@ -992,10 +1208,12 @@ impl InstallationManager {
             nix::uninstall_service(&self.config)?;
         }
 
+        self.broadcast_progress(State::Uninstalling, 0.2);
+
         // Remove installation directory
-        if self.config.install_path.exists() {
-            tokio::fs::remove_dir_all(&self.config.install_path).await?;
-        }
+        self.remove_install_dir().await?;
+
+        self.broadcast_progress(State::Uninstalling, 1.0);
 
         self.is_installed = false;
         self.current_version = None;
@@ -1003,11 +1221,236 @@ impl InstallationManager {
         println!("Uninstall complete!");
         Ok(())
     }
+
+    /// Get the archived versions available for rollback, oldest first
+    pub fn archived_versions(&self) -> Result<Vec<Version>> {
+        let versions_dir = self.config.get_versions_dir();
+        if !versions_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut versions: Vec<Version> = std::fs::read_dir(&versions_dir)
+            .context(format!("Failed to read versions directory '{}'", versions_dir.display()))?
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_dir())
+            .filter_map(|e| Version::parse(&e.file_name().to_string_lossy()).ok())
+            .collect();
+        versions.sort();
+        Ok(versions)
+    }
+
+    /// Copy the current install into the versions directory and prune old archives (internal helper)
+    fn archive_current_install(&self) -> Result<()> {
+        if self.config.keep_versions == 0 || !self.config.install_path.exists() {
+            return Ok(());
+        }
+        let Some(version) = &self.current_version else {
+            return Ok(());
+        };
+
+        println!("Archiving version {} for rollback...", version);
+        let archive_path = self.config.get_versions_dir().join(version.to_string());
+        if archive_path.exists() {
+            std::fs::remove_dir_all(&archive_path)?;
+        }
+        copy_dir_all(&self.config.install_path, &archive_path).context(format!(
+            "Failed to archive version {} to '{}'",
+            version,
+            archive_path.display()
+        ))?;
+
+        let versions = self.archived_versions()?;
+        let excess = versions.len().saturating_sub(self.config.keep_versions);
+        for old in &versions[..excess] {
+            std::fs::remove_dir_all(self.config.get_versions_dir().join(old.to_string()))
+                .context(format!("Failed to prune archived version {}", old))?;
+        }
+
+        Ok(())
+    }
+
+    /// Replace the install with the most recent archive, returning the restored version (internal helper)
+    fn restore_latest_archive(&mut self) -> Result<Version> {
+        let version = self
+            .archived_versions()?
+            .pop()
+            .context("No archived versions available for rollback.")?;
+        let archive_path = self.config.get_versions_dir().join(version.to_string());
+        let install_path = &self.config.install_path;
+
+        if install_path.exists() {
+            std::fs::remove_dir_all(install_path).context(format!(
+                "Failed to remove current installation at '{}'",
+                install_path.display()
+            ))?;
+        }
+
+        // Rename is instant on the same filesystem; fall back to copying otherwise
+        if std::fs::rename(&archive_path, install_path).is_err() {
+            copy_dir_all(&archive_path, install_path).context(format!(
+                "Failed to restore version {} to '{}'",
+                version,
+                install_path.display()
+            ))?;
+            std::fs::remove_dir_all(&archive_path)?;
+        }
+
+        self.current_version = Some(version.clone());
+        self.is_installed = true;
+        Ok(version)
+    }
+
+    /// Roll back to the most recently archived version (requires `keep_versions` to be set)
+    pub async fn rollback(&mut self) -> Result<()> {
+        println!("Rolling back {}...", self.config.service_name);
+
+        #[cfg(target_os = "windows")]
+        {
+            win::stop_service(&self.config)?;
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            nix::stop_service(&self.config)?;
+        }
+
+        let version = self.restore_latest_archive()?;
+
+        #[cfg(target_os = "windows")]
+        {
+            win::set_installed_version(&self.config, &version.to_string())?;
+            win::start_service(&self.config)?;
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            nix::set_installed_version(&self.config, &version.to_string())?;
+            nix::start_service(&self.config)?;
+        }
+
+        println!("Rolled back to version {}!", version);
+        Ok(())
+    }
+
+    /// Remove the installation directory entry by entry, reporting progress from 0.2 to 1.0
+    async fn remove_install_dir(&self) -> Result<()> {
+        let install_path = &self.config.install_path;
+        if !install_path.exists() {
+            return Ok(());
+        }
+
+        // Children are collected before their parents so directories are empty when removed
+        let mut entries = Vec::new();
+        collect_entries(install_path, &mut entries).context(format!(
+            "Failed to read installation directory '{}'",
+            install_path.display()
+        ))?;
+        entries.push(install_path.clone());
+
+        let total = entries.len();
+        for (index, entry) in entries.iter().enumerate() {
+            if entry.is_dir() && !entry.is_symlink() {
+                tokio::fs::remove_dir(entry).await
+            } else {
+                tokio::fs::remove_file(entry).await
+            }
+            .context(format!("Failed to remove '{}'", entry.display()))?;
+
+            let progress = (index + 1) as f32 / total as f32;
+            self.broadcast_progress(State::Uninstalling, 0.2 + progress * 0.8);
+        }
+
+        Ok(())
+    }
+}
+
+/// Recursively collect all entries under `dir`, children before their parent directory
+fn collect_entries(dir: &std::path::Path, entries: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() && !path.is_symlink() {
+            collect_entries(&path, entries)?;
+        }
+        entries.push(path);
+    }
+    Ok(())
+}
+
+/// Recursively move the contents of `src` into `dst`, replacing files that already exist there
+fn move_dir_all(src: &std::path::Path, dst: &std::path::Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dst)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() && target.is_dir() && !target.is_symlink() {
+            move_dir_all(&entry.path(), &target)?;
+            continue;
+        }
+        if target.is_dir() && !target.is_symlink() {
+            std::fs::remove_dir_all(&target)?;
+        } else if target.symlink_metadata().is_ok() {
+            std::fs::remove_file(&target)?;
+        }
+        // Rename is instant on the same filesystem; fall back to copying otherwise
+        if std::fs::rename(entry.path(), &target).is_err() {
+            if entry.file_type()?.is_dir() {
+                copy_dir_all(&entry.path(), &target)?;
+            } else {
+                std::fs::copy(entry.path(), &target)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Recursively copy `src` into `dst`, creating `dst` if needed
+fn copy_dir_all(src: &std::path::Path, dst: &std::path::Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dst)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::{Read, Write};
+
+    /// Serve `body` once over a local HTTP listener, returning the URL to fetch it from
+    fn serve_once(body: Vec<u8>) -> String {
+        serve_throttled(body, usize::MAX, std::time::Duration::ZERO)
+    }
+
+    /// Serve `body` once, writing it in `chunk_size` pieces separated by `delay`
+    fn serve_throttled(body: Vec<u8>, chunk_size: usize, delay: std::time::Duration) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(header.as_bytes());
+                for chunk in body.chunks(chunk_size) {
+                    if stream.write_all(chunk).and_then(|_| stream.flush()).is_err() {
+                        break;
+                    }
+                    std::thread::sleep(delay);
+                }
+            }
+        });
+        format!("http://{}/asset.tar.gz", addr)
+    }
 
     #[test]
     fn test_architecture_detect() {
@@ -1117,4 +1560,360 @@ mod tests {
             assert!(asset.name.contains("linux"));
         }
     }
+
+    #[tokio::test]
+    async fn test_download_uses_custom_temp_dir() {
+        let temp_root = std::env::temp_dir().join("oim-test-custom-temp-dir");
+        let _ = std::fs::remove_dir_all(&temp_root);
+
+        let config = InstallationConfig::new(
+            PathBuf::from("/opt/myapp"),
+            "owner/repo".to_string(),
+            "myapp".to_string(),
+        )
+        .temp_dir(temp_root.clone());
+        assert_eq!(config.get_temp_dir(), temp_root);
+
+        let manager = InstallationManager::new(config);
+        let body = b"not really an archive".to_vec();
+        let asset = GitHubAsset {
+            name: "myapp-linux-x64.tar.gz".to_string(),
+            browser_download_url: serve_once(body.clone()),
+            size: body.len() as u64,
+        };
+
+        let download_path = manager.download_to_temp(&asset).await.unwrap();
+        assert!(download_path.starts_with(&temp_root));
+        assert_eq!(std::fs::read(&download_path).unwrap(), body);
+
+        std::fs::remove_dir_all(&temp_root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_remove_install_dir_reports_progress() {
+        let install_path = std::env::temp_dir().join("oim-test-uninstall-progress");
+        let _ = std::fs::remove_dir_all(&install_path);
+        std::fs::create_dir_all(install_path.join("bin")).unwrap();
+        std::fs::write(install_path.join("bin").join("myapp"), b"binary").unwrap();
+        std::fs::write(install_path.join("config.json"), b"{}").unwrap();
+
+        let manager = InstallationManager::with_defaults(
+            install_path.clone(),
+            "owner/repo".to_string(),
+            "myapp".to_string(),
+        );
+        let mut rx = manager.subscribe();
+
+        manager.remove_install_dir().await.unwrap();
+        assert!(!install_path.exists());
+
+        let mut updates = Vec::new();
+        while let Ok(progress) = rx.try_recv() {
+            assert!(matches!(progress.state, State::Uninstalling));
+            updates.push(progress.progress);
+        }
+
+        // One update per removed entry: bin/myapp, bin, config.json and the root itself
+        assert_eq!(updates.len(), 4);
+        assert!(updates.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(updates.last().copied(), Some(1.0));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_mid_download_removes_partial_file() {
+        let temp_root = std::env::temp_dir().join("oim-test-cancel-download");
+        let _ = std::fs::remove_dir_all(&temp_root);
+        std::fs::create_dir_all(&temp_root).unwrap();
+
+        let manager = InstallationManager::with_defaults(
+            PathBuf::from("/opt/myapp"),
+            "owner/repo".to_string(),
+            "myapp".to_string(),
+        );
+        let body = vec![0u8; 256 * 1024];
+        let asset = GitHubAsset {
+            name: "myapp-linux-x64.tar.gz".to_string(),
+            browser_download_url: serve_throttled(body.clone(), 4096, std::time::Duration::from_millis(5)),
+            size: body.len() as u64,
+        };
+
+        // Cancel as soon as the first bytes have been written
+        let cancel = manager.cancel_handle();
+        let mut rx = manager.subscribe();
+        tokio::spawn(async move {
+            while let Ok(progress) = rx.recv().await {
+                if progress.progress > 0.0 {
+                    cancel.store(true, Ordering::SeqCst);
+                    break;
+                }
+            }
+        });
+
+        let dest_path = temp_root.join(&asset.name);
+        let err = manager.download_asset(&asset, &dest_path).await.unwrap_err();
+        assert!(err.is::<Cancelled>());
+        assert!(!dest_path.exists());
+
+        std::fs::remove_dir_all(&temp_root).unwrap();
+    }
+
+    #[test]
+    fn test_cancel_mid_extraction_leaves_install_untouched() {
+        let temp_root = std::env::temp_dir().join("oim-test-cancel-extraction");
+        let _ = std::fs::remove_dir_all(&temp_root);
+        let install_path = temp_root.join("myapp");
+        std::fs::create_dir_all(&install_path).unwrap();
+        std::fs::write(install_path.join("app"), "1.0.0").unwrap();
+
+        // Enough entries that the extraction is still running when the cancel arrives
+        let archive_path = temp_root.join("myapp-linux-x64.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(&archive_path).unwrap(),
+            flate2::Compression::fast(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        for i in 0..2000 {
+            let data = vec![0u8; 4096];
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, format!("lib/file-{}", i), data.as_slice()).unwrap();
+        }
+        builder.append_data(&mut tar::Header::new_gnu(), "app", &b""[..]).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let config = InstallationConfig::new(
+            install_path.clone(),
+            "owner/repo".to_string(),
+            "myapp".to_string(),
+        )
+        .temp_dir(temp_root.clone());
+        let manager = InstallationManager::new(config);
+
+        // Cancel as soon as the first entry has been extracted
+        let cancel = manager.cancel_handle();
+        let mut rx = manager.subscribe();
+        std::thread::spawn(move || {
+            loop {
+                match rx.blocking_recv() {
+                    Ok(progress) if progress.progress > 0.0 => {
+                        cancel.store(true, Ordering::SeqCst);
+                        break;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                    _ => {}
+                }
+            }
+        });
+
+        let err = manager.extract_archive(&archive_path, &install_path).unwrap_err();
+        assert!(err.is::<Cancelled>());
+        assert!(!install_path.join("lib").exists());
+        assert_eq!(std::fs::read_to_string(install_path.join("app")).unwrap(), "1.0.0");
+        assert!(!manager.download_dir().join("extracting").exists());
+
+        std::fs::remove_dir_all(&temp_root).unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_download_respects_bandwidth_cap() {
+        let temp_root = std::env::temp_dir().join("oim-test-throttled-download");
+        let _ = std::fs::remove_dir_all(&temp_root);
+        std::fs::create_dir_all(&temp_root).unwrap();
+
+        let config = InstallationConfig::new(
+            PathBuf::from("/opt/myapp"),
+            "owner/repo".to_string(),
+            "myapp".to_string(),
+        )
+        .max_download_bytes_per_sec(Some(16 * 1024));
+        let manager = InstallationManager::new(config);
+        let mut rx = manager.subscribe();
+
+        let body = vec![0u8; 64 * 1024];
+        let asset = GitHubAsset {
+            name: "myapp-linux-x64.tar.gz".to_string(),
+            browser_download_url: serve_once(body.clone()),
+            size: body.len() as u64,
+        };
+
+        let started = tokio::time::Instant::now();
+        let dest_path = temp_root.join(&asset.name);
+        manager.download_asset(&asset, &dest_path).await.unwrap();
+
+        // 64 KiB at 16 KiB/s cannot finish in under four (virtual) seconds
+        assert!(started.elapsed() >= std::time::Duration::from_secs(4));
+        assert_eq!(std::fs::read(&dest_path).unwrap().len(), body.len());
+
+        let mut last = None;
+        while let Ok(progress) = rx.try_recv() {
+            last = Some(progress.progress);
+        }
+        assert_eq!(last, Some(1.0));
+
+        std::fs::remove_dir_all(&temp_root).unwrap();
+    }
+
+    /// Published `(tag, archive)` pairs, oldest first
+    type Releases = Arc<std::sync::Mutex<Vec<(String, Vec<u8>)>>>;
+
+    /// Serve a fake GitHub releases API for `owner/repo` until the test exits, publishing every
+    /// `(tag, archive)` pushed onto the returned list as the newest release
+    fn serve_releases() -> (String, Releases) {
+        use std::io::BufRead;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let published = Arc::new(std::sync::Mutex::new(Vec::<(String, Vec<u8>)>::new()));
+
+        let releases = Arc::clone(&published);
+        let asset_base_url = base_url.clone();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request_line = String::new();
+                let mut reader = std::io::BufReader::new(&stream);
+                let _ = reader.read_line(&mut request_line);
+                let mut header = String::new();
+                while reader.read_line(&mut header).is_ok_and(|n| n > 2) {
+                    header.clear();
+                }
+
+                let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+                let releases = releases.lock().unwrap();
+                let body = if path == "/repos/owner/repo/releases" {
+                    let list: Vec<String> = releases
+                        .iter()
+                        .rev()
+                        .map(|(tag, archive)| format!(
+                            r#"{{"tag_name":"{tag}","name":"{tag}","prerelease":false,"assets":[{{"name":"myapp-linux-x64.tar.gz","browser_download_url":"{}/download/{tag}/myapp-linux-x64.tar.gz","size":{}}}]}}"#,
+                            asset_base_url,
+                            archive.len()
+                        ))
+                        .collect();
+                    format!("[{}]", list.join(",")).into_bytes()
+                } else {
+                    releases
+                        .iter()
+                        .find(|(tag, _)| path.starts_with(&format!("/download/{}/", tag)))
+                        .map(|(_, archive)| archive.clone())
+                        .unwrap_or_default()
+                };
+
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(header.as_bytes());
+                let _ = stream.write_all(&body);
+            }
+        });
+        (base_url, published)
+    }
+
+    /// Build a tar.gz archive holding a single `app` file with `contents`
+    fn app_archive(contents: &str) -> Vec<u8> {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast()));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder.append_data(&mut header, "app", contents.as_bytes()).unwrap();
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_rollback_restores_previous_version() {
+        let root = std::env::temp_dir().join("oim-test-rollback");
+        let _ = std::fs::remove_dir_all(&root);
+        let install_path = root.join("myapp");
+        let (api_url, published) = serve_releases();
+
+        let config = InstallationConfig::new(
+            install_path.clone(),
+            "owner/repo".to_string(),
+            "myapp".to_string(),
+        )
+        .version_file_dir(root.join("state").to_string_lossy().into_owned())
+        .temp_dir(root.join("tmp"))
+        .github_api_url(api_url)
+        .keep_versions(1)
+        .skip_service(true);
+        let mut manager = InstallationManager::new(config.clone());
+
+        published.lock().unwrap().push(("v1.0.0".to_string(), app_archive("1.0.0")));
+        manager.install(ReleaseChannel::Release).await.unwrap();
+        for next in ["2.0.0", "3.0.0"] {
+            published.lock().unwrap().push((format!("v{}", next), app_archive(next)));
+            manager.update(ReleaseChannel::Release).await.unwrap();
+            assert_eq!(std::fs::read_to_string(install_path.join("app")).unwrap(), next);
+        }
+
+        // Only the most recent archive is kept
+        assert_eq!(manager.archived_versions().unwrap(), vec![Version::new(2, 0, 0)]);
+
+        manager.rollback().await.unwrap();
+        assert_eq!(manager.current_version(), Some(&Version::new(2, 0, 0)));
+        assert_eq!(std::fs::read_to_string(install_path.join("app")).unwrap(), "2.0.0");
+        assert!(manager.archived_versions().unwrap().is_empty());
+
+        let (recorded, _) = check_installation_exists(&config).unwrap().unwrap();
+        assert_eq!(recorded, Version::new(2, 0, 0));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_cancel_during_update_keeps_previous_version() {
+        let root = std::env::temp_dir().join("oim-test-cancel-update");
+        let _ = std::fs::remove_dir_all(&root);
+        let install_path = root.join("myapp");
+        let (api_url, published) = serve_releases();
+
+        let config = InstallationConfig::new(
+            install_path.clone(),
+            "owner/repo".to_string(),
+            "myapp".to_string(),
+        )
+        .version_file_dir(root.join("state").to_string_lossy().into_owned())
+        .temp_dir(root.join("tmp"))
+        .github_api_url(api_url)
+        .skip_service(true);
+        let mut manager = InstallationManager::new(config.clone());
+
+        published.lock().unwrap().push(("v1.0.0".to_string(), app_archive("1.0.0")));
+        manager.install(ReleaseChannel::Release).await.unwrap();
+        published.lock().unwrap().push(("v2.0.0".to_string(), app_archive("2.0.0")));
+
+        // The spawned task first runs while update() is checking for the new release
+        let cancel = manager.cancel_handle();
+        tokio::spawn(async move { cancel.store(true, Ordering::SeqCst) });
+        let err = manager.update(ReleaseChannel::Release).await.unwrap_err();
+        assert!(err.is::<Cancelled>());
+        assert_eq!(manager.current_version(), Some(&Version::new(1, 0, 0)));
+
+        // Cancel once the update has stopped the service and moved on to the install
+        let cancel = manager.cancel_handle();
+        let mut rx = manager.subscribe();
+        tokio::spawn(async move {
+            while let Ok(progress) = rx.recv().await {
+                if matches!(progress.state, State::Updating) && progress.progress > 0.0 {
+                    cancel.store(true, Ordering::SeqCst);
+                    break;
+                }
+            }
+        });
+
+        let err = manager.update(ReleaseChannel::Release).await.unwrap_err();
+        assert!(err.is::<Cancelled>());
+        assert_eq!(manager.current_version(), Some(&Version::new(1, 0, 0)));
+        assert_eq!(std::fs::read_to_string(install_path.join("app")).unwrap(), "1.0.0");
+
+        let (recorded, _) = check_installation_exists(&config).unwrap().unwrap();
+        assert_eq!(recorded, Version::new(1, 0, 0));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
 }
@@ -15,7 +15,7 @@ pub fn get_installed_version(config: &InstallationConfig) -> Result<Option<Versi
 
     match std::fs::read_to_string(&version_file) {
         Ok(version_str) => {
-            let version = Version::parse(version_str.trim())
+            let version = Version::parse(version_str.trim().trim_start_matches('v'))
                 .context("Failed to parse version from file")?;
             Ok(Some(version))
         }
@@ -165,6 +165,11 @@ pub fn install_service(
     config: &InstallationConfig,
     version: &str,
 ) -> Result<()> {
+    if config.skip_service {
+        set_installed_version(config, version)?;
+        return set_install_path(config, &config.install_path);
+    }
+
     // Find the executable
     let exe_path = find_executable(config)?;
 
@@ -211,6 +216,10 @@ pub fn install_service(
 
 /// Start a systemd service
 pub fn start_service(config: &InstallationConfig) -> Result<()> {
+    if config.skip_service {
+        return Ok(());
+    }
+
     // Check if service is already running
     let status_output = Command::new("systemctl")
         .arg("is-active")
@@ -240,6 +249,10 @@ pub fn start_service(config: &InstallationConfig) -> Result<()> {
 
 /// Stop a systemd service
 pub fn stop_service(config: &InstallationConfig) -> Result<()> {
+    if config.skip_service {
+        return Ok(());
+    }
+
     let output = Command::new("systemctl")
         .arg("stop")
         .arg(&config.service_name)
@@ -275,6 +288,10 @@ pub fn stop_service(config: &InstallationConfig) -> Result<()> {
 
 /// Uninstall a systemd service
 pub fn uninstall_service(config: &InstallationConfig) -> Result<()> {
+    if config.skip_service {
+        return remove_metadata_files(config);
+    }
+
     // Stop the service first
     stop_service(config).ok();
 
@@ -19,7 +19,7 @@ pub fn get_installed_version(config: &InstallationConfig) -> Result<Option<Versi
             let version_key = format!("{}_version", config.service_name);
             match key.get_value::<String, _>(&version_key) {
                 Ok(version_str) => {
-                    let version = Version::parse(&version_str)
+                    let version = Version::parse(version_str.trim_start_matches('v'))
                         .context("Failed to parse version from registry")?;
                     Ok(Some(version))
                 }
@@ -122,6 +122,11 @@ pub fn install_service(
     config: &InstallationConfig,
     version: &str,
 ) -> Result<()> {
+    if config.skip_service {
+        set_installed_version(config, version)?;
+        return set_install_path(config, &config.install_path);
+    }
+
     unsafe {
         let sc_manager = open_sc_manager()?;
 
@@ -274,6 +279,10 @@ fn find_any_executable(install_path: &PathBuf) -> Result<PathBuf> {
 
 /// Start a Windows service
 pub fn start_service(config: &InstallationConfig) -> Result<()> {
+    if config.skip_service {
+        return Ok(());
+    }
+
     unsafe {
         let sc_manager = open_sc_manager()?;
         let service_name_wide = to_wide_string(&config.service_name);
@@ -306,6 +315,10 @@ pub fn start_service(config: &InstallationConfig) -> Result<()> {
 
 /// Stop a Windows service
 pub fn stop_service(config: &InstallationConfig) -> Result<()> {
+    if config.skip_service {
+        return Ok(());
+    }
+
     unsafe {
         let sc_manager = open_sc_manager()?;
         let service_name_wide = to_wide_string(&config.service_name);
@@ -339,6 +352,10 @@ pub fn stop_service(config: &InstallationConfig) -> Result<()> {
 
 /// Uninstall a Windows service
 pub fn uninstall_service(config: &InstallationConfig) -> Result<()> {
+    if config.skip_service {
+        return remove_registry_entries(config);
+    }
+
     // Stop the service first
     stop_service(config).ok();
 
@@ -0,0 +1,118 @@
+use crate::error::McServerError;
+use crate::Result;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Archive the server's worlds (see [`crate::upgrade::world_directories`]) into
+/// `<destination>/<level-name>-<timestamp>.zip`, returning the archive path.
+///
+/// Each world is stored under its directory name, so the archive can be extracted
+/// straight into a server directory.
+pub async fn archive_worlds(server_dir: &Path, destination: &Path) -> Result<PathBuf> {
+    let worlds = crate::upgrade::world_directories(server_dir);
+    let Some(level) = worlds.first().and_then(|w| w.file_name()) else {
+        return Err(McServerError::InvalidConfig(format!(
+            "No world directory found in {}",
+            server_dir.display()
+        )));
+    };
+    let archive = destination.join(format!(
+        "{}-{}.zip",
+        level.to_string_lossy(),
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+
+    let path = archive.clone();
+    let destination = destination.to_path_buf();
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        std::fs::create_dir_all(&destination)?;
+        let mut zip = ZipWriter::new(File::create(&path)?);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        for world in &worlds {
+            let name = world.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            add_dir(&mut zip, world, &name, options)?;
+        }
+        zip.finish().map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| anyhow::anyhow!("World backup task failed: {}", e))??;
+
+    Ok(archive)
+}
+
+fn add_dir(
+    zip: &mut ZipWriter<File>,
+    dir: &Path,
+    prefix: &str,
+    options: SimpleFileOptions,
+) -> Result<()> {
+    zip.add_directory(prefix, options)
+        .map_err(|e| anyhow::anyhow!("Failed to add {}: {}", prefix, e))?;
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+        if entry.file_type()?.is_dir() {
+            add_dir(zip, &entry.path(), &name, options)?;
+        } else {
+            // session.lock is held open by a running server and is recreated on start
+            if entry.file_name() == "session.lock" {
+                continue;
+            }
+            zip.start_file(name.as_str(), options)
+                .map_err(|e| anyhow::anyhow!("Failed to add {}: {}", name, e))?;
+            zip.write_all(&std::fs::read(entry.path())?)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::io::Read;
+
+    #[tokio::test]
+    async fn test_archive_contains_world_files() {
+        let dir = temp_dir("backup");
+        std::fs::create_dir_all(dir.join("server/hardcore/region")).unwrap();
+        std::fs::write(dir.join("server/server.properties"), "level-name=hardcore\n").unwrap();
+        std::fs::write(dir.join("server/hardcore/level.dat"), "level").unwrap();
+        std::fs::write(dir.join("server/hardcore/session.lock"), "lock").unwrap();
+        std::fs::write(dir.join("server/hardcore/region/r.0.0.mca"), "region").unwrap();
+
+        let archive = archive_worlds(&dir.join("server"), &dir.join("backups")).await.unwrap();
+        assert_eq!(archive.parent(), Some(dir.join("backups").as_path()));
+        assert!(archive.file_name().unwrap().to_string_lossy().starts_with("hardcore-"));
+
+        let mut zip = zip::ZipArchive::new(File::open(&archive).unwrap()).unwrap();
+        let mut names: Vec<_> = zip.file_names().map(str::to_string).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            ["hardcore/", "hardcore/level.dat", "hardcore/region/", "hardcore/region/r.0.0.mca"]
+        );
+        let mut region = String::new();
+        zip.by_name("hardcore/region/r.0.0.mca")
+            .unwrap()
+            .read_to_string(&mut region)
+            .unwrap();
+        assert_eq!(region, "region");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_missing_world_is_an_error() {
+        let dir = temp_dir("backup-empty");
+        assert!(matches!(
+            archive_worlds(&dir, &dir.join("backups")).await,
+            Err(McServerError::InvalidConfig(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,345 @@
+use crate::mojang::MojangClient;
+use crate::player_list::{self, PlayerEntry};
+use crate::Result;
+use chrono::{DateTime, FixedOffset, Local};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+const BANNED_PLAYERS_FILENAME: &str = "banned-players.json";
+const BANNED_IPS_FILENAME: &str = "banned-ips.json";
+
+/// Source recorded by the server for bans issued from the console.
+pub const DEFAULT_BAN_SOURCE: &str = "Server";
+
+/// Reason recorded by the server when none is given.
+pub const DEFAULT_BAN_REASON: &str = "Banned by an operator.";
+
+/// Date format of the `created` and `expires` fields.
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S %z";
+
+/// A banned player in `banned-players.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BanEntry {
+    /// Hyphenated UUID.
+    pub uuid: String,
+    pub name: String,
+    #[serde(with = "date")]
+    pub created: DateTime<FixedOffset>,
+    pub source: String,
+    /// When the ban ends; `None` for permanent bans (written as "forever").
+    #[serde(with = "expiry")]
+    pub expires: Option<DateTime<FixedOffset>>,
+    pub reason: String,
+}
+
+/// A banned IP address in `banned-ips.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IpBanEntry {
+    pub ip: IpAddr,
+    #[serde(with = "date")]
+    pub created: DateTime<FixedOffset>,
+    pub source: String,
+    /// When the ban ends; `None` for permanent bans (written as "forever").
+    #[serde(with = "expiry")]
+    pub expires: Option<DateTime<FixedOffset>>,
+    pub reason: String,
+}
+
+impl PlayerEntry for BanEntry {
+    fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl BanEntry {
+    /// Whether the ban has ended by `now`.
+    pub fn is_expired(&self, now: DateTime<FixedOffset>) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+}
+
+impl IpBanEntry {
+    /// Whether the ban has ended by `now`.
+    pub fn is_expired(&self, now: DateTime<FixedOffset>) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+}
+
+mod date {
+    use super::DATE_FORMAT;
+    use chrono::{DateTime, FixedOffset};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        date: &DateTime<FixedOffset>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&date.format(DATE_FORMAT).to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<DateTime<FixedOffset>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        DateTime::parse_from_str(&s, DATE_FORMAT).map_err(serde::de::Error::custom)
+    }
+}
+
+mod expiry {
+    use super::DATE_FORMAT;
+    use chrono::{DateTime, FixedOffset};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    const FOREVER: &str = "forever";
+
+    pub fn serialize<S: Serializer>(
+        expires: &Option<DateTime<FixedOffset>>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        match expires {
+            Some(date) => serializer.serialize_str(&date.format(DATE_FORMAT).to_string()),
+            None => serializer.serialize_str(FOREVER),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Option<DateTime<FixedOffset>>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        if s.eq_ignore_ascii_case(FOREVER) {
+            return Ok(None);
+        }
+        DateTime::parse_from_str(&s, DATE_FORMAT)
+            .map(Some)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// The ban lists of a server directory, backed by `banned-players.json` and `banned-ips.json`.
+///
+/// Changes are only written to disk by [`save`](Self::save). To also update a running
+/// server, use [`ServerManager::ban`](crate::ServerManager::ban) and its siblings.
+#[derive(Debug, Clone)]
+pub struct Bans {
+    directory: PathBuf,
+    players: Vec<BanEntry>,
+    ips: Vec<IpBanEntry>,
+    client: MojangClient,
+}
+
+impl Bans {
+    /// Load the ban lists from a server directory. Missing files mean no bans.
+    pub fn load(server_dir: &Path) -> Result<Self> {
+        Ok(Self {
+            directory: server_dir.to_path_buf(),
+            players: player_list::load_list(&server_dir.join(BANNED_PLAYERS_FILENAME))?,
+            ips: player_list::load_list(&server_dir.join(BANNED_IPS_FILENAME))?,
+            client: MojangClient::default(),
+        })
+    }
+
+    /// Use a custom Mojang client for name resolution.
+    pub fn with_client(mut self, client: MojangClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Banned players.
+    pub fn list(&self) -> &[BanEntry] {
+        &self.players
+    }
+
+    /// Banned IP addresses.
+    pub fn list_ips(&self) -> &[IpBanEntry] {
+        &self.ips
+    }
+
+    /// Look up a banned player by name or UUID.
+    pub fn get(&self, name_or_uuid: &str) -> Option<&BanEntry> {
+        self.position(name_or_uuid).map(|index| &self.players[index])
+    }
+
+    /// Ban a player by name or UUID, resolving the other via Mojang's API.
+    ///
+    /// `reason` and `source` default to what the server records for console bans;
+    /// `expires` of `None` bans permanently. Banning an already banned player replaces their entry.
+    pub async fn ban(
+        &mut self,
+        name_or_uuid: &str,
+        reason: Option<&str>,
+        source: Option<&str>,
+        expires: Option<DateTime<FixedOffset>>,
+    ) -> Result<BanEntry> {
+        let profile = self.client.resolve(name_or_uuid).await?;
+        self.pardon(&profile.uuid);
+        let entry = BanEntry {
+            uuid: profile.uuid,
+            name: profile.name,
+            created: now(),
+            source: source.unwrap_or(DEFAULT_BAN_SOURCE).to_string(),
+            expires,
+            reason: reason.unwrap_or(DEFAULT_BAN_REASON).to_string(),
+        };
+        self.players.push(entry.clone());
+        Ok(entry)
+    }
+
+    /// Ban an IP address. Banning an already banned address replaces its entry.
+    pub fn ban_ip(
+        &mut self,
+        ip: IpAddr,
+        reason: Option<&str>,
+        source: Option<&str>,
+        expires: Option<DateTime<FixedOffset>>,
+    ) -> IpBanEntry {
+        self.pardon_ip(ip);
+        let entry = IpBanEntry {
+            ip,
+            created: now(),
+            source: source.unwrap_or(DEFAULT_BAN_SOURCE).to_string(),
+            expires,
+            reason: reason.unwrap_or(DEFAULT_BAN_REASON).to_string(),
+        };
+        self.ips.push(entry.clone());
+        entry
+    }
+
+    /// Lift a player's ban by name or UUID, returning the removed entry.
+    pub fn pardon(&mut self, name_or_uuid: &str) -> Option<BanEntry> {
+        let index = self.position(name_or_uuid)?;
+        Some(self.players.remove(index))
+    }
+
+    /// Lift an IP ban, returning the removed entry.
+    pub fn pardon_ip(&mut self, ip: IpAddr) -> Option<IpBanEntry> {
+        let index = self.ips.iter().position(|e| e.ip == ip)?;
+        Some(self.ips.remove(index))
+    }
+
+    /// Write both ban lists back to disk.
+    pub fn save(&self) -> Result<()> {
+        player_list::save_list(&self.directory.join(BANNED_PLAYERS_FILENAME), &self.players)?;
+        player_list::save_list(&self.directory.join(BANNED_IPS_FILENAME), &self.ips)
+    }
+
+    fn position(&self, name_or_uuid: &str) -> Option<usize> {
+        player_list::position(&self.players, name_or_uuid)
+    }
+}
+
+fn now() -> DateTime<FixedOffset> {
+    // Ban dates have second precision
+    let now = Local::now().fixed_offset();
+    DateTime::parse_from_str(&now.format(DATE_FORMAT).to_string(), DATE_FORMAT).unwrap_or(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const NOTCH_UUID: &str = "069a79f4-44e9-4726-a5be-fca90e38aaf5";
+
+    fn date(s: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_str(s, DATE_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn test_read_and_write_vanilla_json() {
+        let dir = temp_dir("bans-round-trip");
+        let players = serde_json::json!([{
+            "uuid": NOTCH_UUID,
+            "name": "Notch",
+            "created": "2025-06-01 12:00:00 +0000",
+            "source": "Server",
+            "expires": "forever",
+            "reason": "Banned by an operator."
+        }]);
+        let ips = serde_json::json!([{
+            "ip": "192.0.2.7",
+            "created": "2025-06-01 12:00:00 +0200",
+            "source": "Steve",
+            "expires": "2025-07-01 12:00:00 +0200",
+            "reason": "Griefing"
+        }]);
+        std::fs::write(dir.join(BANNED_PLAYERS_FILENAME), players.to_string()).unwrap();
+        std::fs::write(dir.join(BANNED_IPS_FILENAME), ips.to_string()).unwrap();
+
+        let bans = Bans::load(&dir).unwrap();
+        let notch = bans.get("notch").unwrap();
+        assert_eq!(notch.expires, None);
+        assert_eq!(notch.created, date("2025-06-01 12:00:00 +0000"));
+        let ip_ban = &bans.list_ips()[0];
+        assert_eq!(ip_ban.ip, "192.0.2.7".parse::<IpAddr>().unwrap());
+        assert_eq!(ip_ban.expires, Some(date("2025-07-01 12:00:00 +0200")));
+
+        bans.save().unwrap();
+        let read = |name| -> serde_json::Value {
+            serde_json::from_str(&std::fs::read_to_string(dir.join(name)).unwrap()).unwrap()
+        };
+        assert_eq!(read(BANNED_PLAYERS_FILENAME), players);
+        assert_eq!(read(BANNED_IPS_FILENAME), ips);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_expiry_serialization() {
+        let dir = temp_dir("bans-expiry");
+        let mut bans = Bans::load(&dir).unwrap();
+        let ip: IpAddr = "2001:db8::1".parse().unwrap();
+        let entry = bans.ban_ip(ip, None, None, Some(date("2030-01-02 03:04:05 -0500")));
+        assert_eq!(entry.reason, DEFAULT_BAN_REASON);
+        assert_eq!(entry.source, DEFAULT_BAN_SOURCE);
+
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["expires"], "2030-01-02 03:04:05 -0500");
+        assert_eq!(json["ip"], "2001:db8::1");
+        assert!(!entry.is_expired(date("2030-01-02 03:04:04 -0500")));
+        assert!(entry.is_expired(date("2030-01-02 08:04:05 +0000")));
+
+        let permanent = bans.ban_ip(ip, Some("Spam"), Some("Alex"), None);
+        assert_eq!(serde_json::to_value(&permanent).unwrap()["expires"], "forever");
+        assert_eq!(bans.list_ips().len(), 1);
+        assert!(bans.pardon_ip(ip).is_some());
+        assert!(bans.list_ips().is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_ban_resolves_name_and_pardon() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/users/profiles/minecraft/Notch"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "069a79f444e94726a5befca90e38aaf5",
+                "name": "Notch"
+            })))
+            .mount(&server)
+            .await;
+        let dir = temp_dir("bans-ban");
+
+        let mut bans = Bans::load(&dir)
+            .unwrap()
+            .with_client(MojangClient::with_base_urls(server.uri(), server.uri()));
+        let entry = bans.ban("Notch", Some("Testing"), Some("Console"), None).await.unwrap();
+        assert_eq!(entry.uuid, NOTCH_UUID);
+        assert_eq!(entry.reason, "Testing");
+        bans.save().unwrap();
+
+        let mut bans = Bans::load(&dir).unwrap();
+        assert_eq!(bans.list(), [entry]);
+        assert!(bans.pardon(NOTCH_UUID).is_some());
+        assert!(bans.pardon("Notch").is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -66,7 +66,7 @@ pub fn show_status(dir: Option<String>) -> anyhow::Result<()> {
     println!("Minecraft: {}", config.minecraft_version);
     println!("Type: {}", config.server_type);
     println!("JAR: {}", config.server_jar);
-    println!("Memory: {}-{}GB", config.min_memory_gb, config.max_memory_gb);
+    println!("Memory: {}-{}MB", config.min_memory_mb, config.max_memory_mb);
     println!("Java: {}", config.java_executable);
 
     if let Some(loader) = &config.loader_version {
@@ -1,4 +1,4 @@
-use dialoguer::{Input, Select};
+use dialoguer::{Confirm, Input, Select};
 use minecraft_server::{ServerConfig, ServerManager, ServerType};
 use std::path::PathBuf;
 
@@ -29,11 +29,72 @@ impl minecraft_server::ServerEventHandler for CliEventHandler {
             minecraft_server::ServerEvent::Started => {
                 println!("[Server] Started successfully!");
             }
+            minecraft_server::ServerEvent::Ready { startup_time } => {
+                println!("[Server] Ready after {:.1}s", startup_time.as_secs_f64());
+            }
             minecraft_server::ServerEvent::Stopped => {
                 println!("[Server] Stopped.");
             }
-            minecraft_server::ServerEvent::Crashed { exit_code } => {
+            minecraft_server::ServerEvent::Crashed {
+                exit_code,
+                ref report_path,
+                ref summary,
+            } => {
                 eprintln!("[Server] Crashed with exit code {}", exit_code);
+                if let Some(summary) = summary {
+                    eprintln!("[Server] {}", summary);
+                }
+                if let Some(path) = report_path {
+                    eprintln!("[Server] Crash report: {}", path.display());
+                }
+            }
+            minecraft_server::ServerEvent::Restarting {
+                attempt,
+                delay_secs,
+            } => {
+                println!(
+                    "[Server] Restarting in {}s (attempt {})...",
+                    delay_secs, attempt
+                );
+            }
+            minecraft_server::ServerEvent::CrashLoopDetected { attempts } => {
+                eprintln!(
+                    "[Server] Crashed {} times in a row, not restarting again.",
+                    attempts
+                );
+            }
+            minecraft_server::ServerEvent::RestartWarning { seconds_remaining } => {
+                println!("[Server] Scheduled restart in {}s", seconds_remaining);
+            }
+            minecraft_server::ServerEvent::ScheduledRestart => {
+                println!("[Server] Performing scheduled restart...");
+            }
+            minecraft_server::ServerEvent::PlayerJoined { ref player } => {
+                println!("[Player] {} joined", player);
+            }
+            minecraft_server::ServerEvent::PlayerLeft { ref player } => {
+                println!("[Player] {} left", player);
+            }
+            minecraft_server::ServerEvent::Chat { .. } => {
+                // Chat is already printed as console output
+            }
+            minecraft_server::ServerEvent::UpgradeProgress {
+                ref from_version,
+                ref to_version,
+                ref stage,
+            } => {
+                let step = match stage {
+                    minecraft_server::UpgradeStage::Stopping => "stopping server".to_string(),
+                    minecraft_server::UpgradeStage::BackingUp => "backing up".to_string(),
+                    minecraft_server::UpgradeStage::Installing => "installing".to_string(),
+                    minecraft_server::UpgradeStage::Completed { backup } => {
+                        format!("done (backup at {})", backup.display())
+                    }
+                    minecraft_server::UpgradeStage::RolledBack { backup } => {
+                        format!("failed, restored from {}", backup.display())
+                    }
+                };
+                println!("[Upgrade] {} -> {}: {}", from_version, to_version, step);
             }
             minecraft_server::ServerEvent::JavaVersionError => {
                 eprintln!("[Server] Java version mismatch! Please update your Java installation.");
@@ -76,7 +137,7 @@ pub async fn create_server(dir: Option<String>) -> anyhow::Result<()> {
     let minecraft_version = version_names[version_idx].to_string();
 
     // Server type
-    let type_options = ["Vanilla", "Fabric", "Forge", "NeoForge", "Custom"];
+    let type_options = ["Vanilla", "Fabric", "Forge", "NeoForge", "Paper", "Purpur", "Spigot", "Custom"];
     let type_idx = Select::new()
         .with_prompt("Server type")
         .items(&type_options)
@@ -88,14 +149,26 @@ pub async fn create_server(dir: Option<String>) -> anyhow::Result<()> {
         1 => ServerType::Fabric,
         2 => ServerType::Forge,
         3 => ServerType::NeoForge,
+        4 => ServerType::Paper,
+        5 => ServerType::Purpur,
+        6 => ServerType::Spigot,
         _ => ServerType::Custom,
     };
 
-    // Loader version (if modded)
+    // Loader version (if modded) or Paper/Purpur build
     let loader_version = match server_type {
-        ServerType::Fabric | ServerType::Forge | ServerType::NeoForge => {
+        ServerType::Fabric
+        | ServerType::Forge
+        | ServerType::NeoForge
+        | ServerType::Paper
+        | ServerType::Purpur => {
+            let prompt = match server_type {
+                ServerType::Paper => "Paper build (leave empty for latest)",
+                ServerType::Purpur => "Purpur build (leave empty for latest)",
+                _ => "Loader version (leave empty for latest)",
+            };
             let version: String = Input::new()
-                .with_prompt("Loader version (leave empty for latest)")
+                .with_prompt(prompt)
                 .allow_empty(true)
                 .interact_text()?;
             if version.is_empty() {
@@ -108,36 +181,45 @@ pub async fn create_server(dir: Option<String>) -> anyhow::Result<()> {
     };
 
     // Memory settings
-    let max_memory: u8 = Input::new()
-        .with_prompt("Maximum memory (GB)")
-        .default(2)
+    let max_memory: u32 = Input::new()
+        .with_prompt("Maximum memory (MB)")
+        .default(2048)
         .interact_text()?;
 
-    let min_memory: u8 = Input::new()
-        .with_prompt("Minimum memory (GB)")
-        .default(1)
+    let min_memory: u32 = Input::new()
+        .with_prompt("Minimum memory (MB)")
+        .default(1024)
         .interact_text()?;
 
     // Java executable
     let java_executable: String = Input::new()
-        .with_prompt("Java executable")
+        .with_prompt("Java executable (\"java\" to auto-detect)")
         .default("java".to_string())
         .interact_text()?;
 
+    // EULA
+    let accept_eula = Confirm::new()
+        .with_prompt("Do you agree to the Minecraft EULA (https://aka.ms/MinecraftEULA)?")
+        .default(false)
+        .interact()?;
+
     // Build config
     let config = ServerConfig {
         name: name.clone(),
         directory: directory.clone(),
         java_executable,
         java_args: String::new(),
-        max_memory_gb: max_memory,
-        min_memory_gb: min_memory,
+        max_memory_mb: max_memory,
+        min_memory_mb: min_memory,
         minecraft_args: String::new(),
         server_jar: "server.jar".to_string(),
         minecraft_version,
         server_type,
         loader_version,
+        accept_eula,
+        ..Default::default()
     };
+    config.validate()?;
 
     // Install
     println!("\nInstalling server...");
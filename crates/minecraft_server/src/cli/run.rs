@@ -1,5 +1,5 @@
 use super::create::CliEventHandler;
-use minecraft_server::{ServerConfig, ServerManager};
+use minecraft_server::{ServerConfig, ServerManager, StopOutcome};
 use std::path::PathBuf;
 
 pub async fn run_server(dir: Option<String>) -> anyhow::Result<()> {
@@ -44,9 +44,9 @@ pub async fn run_server(dir: Option<String>) -> anyhow::Result<()> {
             }
             _ = &mut shutdown_rx => {
                 println!("\nStopping server...");
-                manager.stop().await?;
-                // Wait a moment for the server to stop
-                tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                if manager.stop().await? == StopOutcome::Killed {
+                    println!("Server did not stop in time and was killed.");
+                }
                 break;
             }
         }
@@ -0,0 +1,235 @@
+use crate::events::ServerEvent;
+use regex::Regex;
+use std::collections::VecDeque;
+use std::sync::LazyLock;
+
+/// Bounded buffer of the most recent console lines, so newly connected viewers
+/// can show output from before they subscribed.
+#[derive(Debug, Clone)]
+pub struct ConsoleHistory {
+    lines: VecDeque<String>,
+    capacity: usize,
+}
+
+impl ConsoleHistory {
+    /// Create a buffer that retains at most `capacity` lines.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::with_capacity(capacity.min(1024)),
+            capacity,
+        }
+    }
+
+    /// Maximum number of lines retained.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Change the maximum number of lines, dropping the oldest lines if needed.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.lines.len() > capacity {
+            self.lines.pop_front();
+        }
+    }
+
+    /// Append a line, evicting the oldest line when full.
+    pub fn push(&mut self, line: String) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+
+    /// Retained lines, oldest first.
+    pub fn lines(&self) -> Vec<String> {
+        self.lines.iter().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+}
+
+/// Log prefix ending in `]: `, shared by vanilla (`[12:00:00] [Server thread/INFO]: `),
+/// Forge/NeoForge (`[12:00:00] [Server thread/INFO] [minecraft/MinecraftServer]: `)
+/// and Paper/Spigot (`[12:00:00 INFO]: `) logs.
+const PREFIX: &str = r"^(?:\[[^\]]*\]\s*)+:\s*";
+
+/// Player names are up to 16 word characters; Bedrock players bridged through
+/// Floodgate get a `.` prefix, and some proxies use longer names.
+const NAME: &str = r"([.\w]{1,32})";
+
+static JOINED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(&format!(r"{PREFIX}{NAME} joined the game\s*$")).unwrap());
+static LEFT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(&format!(r"{PREFIX}{NAME} left the game\s*$")).unwrap());
+static CHAT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(r"{PREFIX}(?:\[Not Secure\]\s*)?<{NAME}> (.*)$")).unwrap()
+});
+
+/// Recognize gameplay events (joins, leaves, and chat) in a line of console output.
+///
+/// Returns `None` for lines that are not gameplay events.
+pub fn parse_console_line(line: &str) -> Option<ServerEvent> {
+    let line = line.trim_end();
+    if let Some(captures) = CHAT.captures(line) {
+        return Some(ServerEvent::Chat {
+            player: captures[1].to_string(),
+            message: captures[2].to_string(),
+        });
+    }
+    if let Some(captures) = JOINED.captures(line) {
+        return Some(ServerEvent::PlayerJoined {
+            player: captures[1].to_string(),
+        });
+    }
+    if let Some(captures) = LEFT.captures(line) {
+        return Some(ServerEvent::PlayerLeft {
+            player: captures[1].to_string(),
+        });
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn joined(line: &str) -> Option<String> {
+        match parse_console_line(line) {
+            Some(ServerEvent::PlayerJoined { player }) => Some(player),
+            _ => None,
+        }
+    }
+
+    fn left(line: &str) -> Option<String> {
+        match parse_console_line(line) {
+            Some(ServerEvent::PlayerLeft { player }) => Some(player),
+            _ => None,
+        }
+    }
+
+    fn chat(line: &str) -> Option<(String, String)> {
+        match parse_console_line(line) {
+            Some(ServerEvent::Chat { player, message }) => Some((player, message)),
+            _ => None,
+        }
+    }
+
+    // ── History tests ──────────────────────────────────────────────
+
+    #[test]
+    fn test_history_caps_at_capacity_keeping_recent_lines() {
+        let mut history = ConsoleHistory::new(3);
+        for i in 0..5 {
+            history.push(format!("line {}", i));
+        }
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.lines(), vec!["line 2", "line 3", "line 4"]);
+    }
+
+    #[test]
+    fn test_history_shrinking_capacity_drops_oldest() {
+        let mut history = ConsoleHistory::new(5);
+        for i in 0..5 {
+            history.push(format!("line {}", i));
+        }
+        history.set_capacity(2);
+        assert_eq!(history.lines(), vec!["line 3", "line 4"]);
+    }
+
+    #[test]
+    fn test_history_with_zero_capacity_is_empty() {
+        let mut history = ConsoleHistory::new(0);
+        history.push("line".to_string());
+        assert!(history.is_empty());
+    }
+
+    // ── Join / leave tests ─────────────────────────────────────────
+
+    #[test]
+    fn test_vanilla_join_and_leave() {
+        assert_eq!(
+            joined("[12:34:56] [Server thread/INFO]: Steve joined the game").as_deref(),
+            Some("Steve")
+        );
+        assert_eq!(
+            left("[12:40:01] [Server thread/INFO]: Steve left the game").as_deref(),
+            Some("Steve")
+        );
+    }
+
+    #[test]
+    fn test_forge_prefix() {
+        assert_eq!(
+            joined("[12:34:56] [Server thread/INFO] [minecraft/MinecraftServer]: Alex_99 joined the game")
+                .as_deref(),
+            Some("Alex_99")
+        );
+    }
+
+    #[test]
+    fn test_paper_prefix_and_crlf() {
+        assert_eq!(
+            joined("[12:34:56 INFO]: Notch joined the game\r").as_deref(),
+            Some("Notch")
+        );
+    }
+
+    #[test]
+    fn test_floodgate_player_name() {
+        assert_eq!(
+            left("[12:34:56] [Server thread/INFO]: .BedrockGuy left the game").as_deref(),
+            Some(".BedrockGuy")
+        );
+    }
+
+    // ── Chat tests ─────────────────────────────────────────────────
+
+    #[test]
+    fn test_vanilla_chat() {
+        assert_eq!(
+            chat("[12:34:56] [Server thread/INFO]: <Steve> hello world"),
+            Some(("Steve".to_string(), "hello world".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_unsigned_chat_from_async_thread() {
+        assert_eq!(
+            chat("[12:34:56] [Async Chat Thread - #0/INFO]: [Not Secure] <Alex> hi <3"),
+            Some(("Alex".to_string(), "hi <3".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_chat_mentioning_join_is_chat() {
+        assert_eq!(
+            chat("[12:34:56] [Server thread/INFO]: <Steve> Bob joined the game"),
+            Some(("Steve".to_string(), "Bob joined the game".to_string()))
+        );
+    }
+
+    // ── Non-matching lines ─────────────────────────────────────────
+
+    #[test]
+    fn test_other_lines_are_ignored() {
+        for line in [
+            "[12:34:56] [Server thread/INFO]: Done (3.456s)! For help, type \"help\"",
+            "[12:34:56] [Server thread/INFO]: Steve lost connection: Disconnected",
+            "[12:34:56] [Server thread/INFO]: Steve has made the advancement [Stone Age]",
+            "Steve joined the game",
+            "",
+        ] {
+            assert!(parse_console_line(line).is_none(), "matched: {line}");
+        }
+    }
+}
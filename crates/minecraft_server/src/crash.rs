@@ -0,0 +1,114 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Filesystem timestamps are coarser than the system clock, so a report written
+/// just after startup can appear slightly older than the process.
+const TIMESTAMP_SLACK: Duration = Duration::from_secs(2);
+
+/// A crash report written by the server under `crash-reports/`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashReport {
+    pub path: PathBuf,
+    /// The report's description followed by the exception that caused it,
+    /// e.g. "Exception in server tick loop: java.lang.NullPointerException: boom".
+    pub summary: Option<String>,
+}
+
+/// Find the newest `crash-reports/crash-*.txt` in `server_dir` written at or after `since`.
+///
+/// Reports older than `since` belong to an earlier run and are ignored.
+pub fn find_crash_report(server_dir: &Path, since: SystemTime) -> Option<CrashReport> {
+    let entries = std::fs::read_dir(server_dir.join("crash-reports")).ok()?;
+    let since = since.checked_sub(TIMESTAMP_SLACK).unwrap_or(since);
+    let (path, _) = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("crash-") && name.ends_with(".txt")
+        })
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            (modified >= since).then(|| (entry.path(), modified))
+        })
+        .max_by_key(|(_, modified)| *modified)?;
+
+    let summary = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|contents| parse_summary(&contents));
+    Some(CrashReport { path, summary })
+}
+
+/// Extract the error summary from the contents of a crash report.
+pub fn parse_summary(contents: &str) -> Option<String> {
+    let mut lines = contents.lines().map(str::trim);
+    let description = lines
+        .find_map(|line| line.strip_prefix("Description:"))?
+        .trim()
+        .to_string();
+    // The exception is the first non-empty line after the description
+    let exception = lines.find(|line| !line.is_empty());
+    Some(match exception {
+        Some(exception) if !exception.starts_with("--") => format!("{}: {}", description, exception),
+        _ => description,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    const REPORT: &str = "---- Minecraft Crash Report ----
+// Surprise! Haha. Well, this is awkward.
+
+Time: 2025-06-01 12:00:00
+Description: Exception in server tick loop
+
+java.lang.NullPointerException: Cannot invoke \"Object.toString()\" because \"value\" is null
+\tat net.minecraft.server.MinecraftServer.tick(MinecraftServer.java:100)
+
+-- System Details --
+";
+
+    #[test]
+    fn test_summary_includes_description_and_exception() {
+        assert_eq!(
+            parse_summary(REPORT).as_deref(),
+            Some("Exception in server tick loop: java.lang.NullPointerException: Cannot invoke \"Object.toString()\" because \"value\" is null")
+        );
+    }
+
+    #[test]
+    fn test_summary_without_exception_is_description() {
+        let report = "Description: Watching Server\n\n-- System Details --\n";
+        assert_eq!(parse_summary(report).as_deref(), Some("Watching Server"));
+        assert_eq!(parse_summary("not a crash report"), None);
+    }
+
+    #[test]
+    fn test_newest_report_since_start_is_found() {
+        let dir = temp_dir("crash");
+        let reports = dir.join("crash-reports");
+        std::fs::create_dir_all(&reports).unwrap();
+        let since = SystemTime::now();
+        let old = reports.join("crash-2025-06-01_11.00.00-server.txt");
+        std::fs::write(&old, "Description: old").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(since - Duration::from_secs(3600))
+            .unwrap();
+        std::fs::write(reports.join("notes.txt"), "Description: unrelated").unwrap();
+        assert_eq!(find_crash_report(&dir, since), None);
+
+        let newest = reports.join("crash-2025-06-01_12.00.00-server.txt");
+        std::fs::write(&newest, REPORT).unwrap();
+        let report = find_crash_report(&dir, since).unwrap();
+        assert_eq!(report.path, newest);
+        assert!(report.summary.unwrap().starts_with("Exception in server tick loop: "));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
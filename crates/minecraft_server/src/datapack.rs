@@ -0,0 +1,136 @@
+use crate::error::McServerError;
+use crate::properties::ServerProperties;
+use crate::Result;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// Metadata file every datapack has at its root.
+pub const PACK_METADATA: &str = "pack.mcmeta";
+
+/// Directory of the active world (named by `level-name`) that holds its datapacks.
+///
+/// The world does not need to exist yet: packs placed here before the first start
+/// are applied when the world is generated.
+pub fn datapacks_directory(server_dir: &Path) -> PathBuf {
+    let level_name = ServerProperties::load_from_dir(server_dir)
+        .unwrap_or_default()
+        .level_name();
+    server_dir.join(level_name).join("datapacks")
+}
+
+/// Copy a datapack (a zip archive or an unpacked directory) into the active world's
+/// `datapacks/` directory, returning the installed path.
+///
+/// The pack must have a `pack.mcmeta` at its root; otherwise
+/// [`McServerError::InvalidConfig`] is returned and nothing is copied.
+pub fn install_datapack(server_dir: &Path, source: &Path) -> Result<PathBuf> {
+    let file_name = source.file_name().ok_or_else(|| {
+        McServerError::InvalidConfig(format!("Not a datapack: {}", source.display()))
+    })?;
+    if source.is_dir() {
+        if !source.join(PACK_METADATA).is_file() {
+            return Err(missing_metadata(source));
+        }
+    } else {
+        let archive = zip::ZipArchive::new(File::open(source)?).map_err(|e| {
+            McServerError::InvalidConfig(format!("Not a datapack archive {}: {}", source.display(), e))
+        })?;
+        if archive.index_for_name(PACK_METADATA).is_none() {
+            return Err(missing_metadata(source));
+        }
+    }
+
+    let directory = datapacks_directory(server_dir);
+    std::fs::create_dir_all(&directory)?;
+    let destination = directory.join(file_name);
+    if destination.exists() {
+        return Err(McServerError::AlreadyInstalled(file_name.to_string_lossy().into_owned()));
+    }
+    if source.is_dir() {
+        crate::upgrade::copy_dir(source, &destination)?;
+    } else {
+        std::fs::copy(source, &destination)?;
+    }
+    Ok(destination)
+}
+
+fn missing_metadata(source: &Path) -> McServerError {
+    McServerError::InvalidConfig(format!(
+        "{} has no {} at its root",
+        source.display(),
+        PACK_METADATA
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    const METADATA: &str = r#"{"pack":{"pack_format":48,"description":"Test pack"}}"#;
+
+    fn temp_server(name: &str) -> PathBuf {
+        let dir = temp_dir(&format!("datapack-{}", name));
+        std::fs::create_dir_all(dir.join("server")).unwrap();
+        std::fs::write(dir.join("server/server.properties"), "level-name=survival\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_zip_installed_into_world() {
+        let dir = temp_server("zip");
+        let source = dir.join("terralith.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&source).unwrap());
+        zip.start_file(PACK_METADATA, SimpleFileOptions::default()).unwrap();
+        zip.write_all(METADATA.as_bytes()).unwrap();
+        zip.add_directory("data/", SimpleFileOptions::default()).unwrap();
+        zip.finish().unwrap();
+
+        let installed = install_datapack(&dir.join("server"), &source).unwrap();
+        assert_eq!(installed, dir.join("server/survival/datapacks/terralith.zip"));
+        assert!(installed.is_file());
+        assert!(matches!(
+            install_datapack(&dir.join("server"), &source),
+            Err(McServerError::AlreadyInstalled(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_directory_installed_into_world() {
+        let dir = temp_server("dir");
+        std::fs::create_dir_all(dir.join("better-trades/data/trades")).unwrap();
+        std::fs::write(dir.join("better-trades").join(PACK_METADATA), METADATA).unwrap();
+        std::fs::write(dir.join("better-trades/data/trades/villager.json"), "{}").unwrap();
+
+        let installed = install_datapack(&dir.join("server"), &dir.join("better-trades")).unwrap();
+        assert_eq!(installed, dir.join("server/survival/datapacks/better-trades"));
+        assert!(installed.join("data/trades/villager.json").is_file());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_pack_without_metadata_is_rejected() {
+        let dir = temp_server("invalid");
+        std::fs::create_dir_all(dir.join("not-a-pack/data")).unwrap();
+        let source = dir.join("not-a-pack.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&source).unwrap());
+        zip.start_file("data/readme.txt", SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"hello").unwrap();
+        zip.finish().unwrap();
+
+        for source in [dir.join("not-a-pack"), source] {
+            assert!(matches!(
+                install_datapack(&dir.join("server"), &source),
+                Err(McServerError::InvalidConfig(_))
+            ));
+        }
+        assert!(!dir.join("server/survival/datapacks").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,196 @@
+use crate::error::McServerError;
+use crate::Result;
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use tokio::io::AsyncWriteExt;
+
+/// Expected checksum of a downloaded file, as published by the download API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Checksum {
+    Sha256(String),
+    Md5(String),
+}
+
+/// A server JAR build selected from a download API (Paper, Purpur).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerJar {
+    /// Build number.
+    pub build: u32,
+    /// JAR filename (e.g. "paper-1.21.4-232.jar").
+    pub file_name: String,
+    pub url: String,
+    pub checksum: Checksum,
+}
+
+impl Checksum {
+    /// The expected hex digest.
+    pub fn expected(&self) -> &str {
+        match self {
+            Self::Sha256(hash) | Self::Md5(hash) => hash,
+        }
+    }
+}
+
+enum Hasher {
+    Sha256(Sha256),
+    Md5(Md5),
+}
+
+impl Hasher {
+    fn new(checksum: &Checksum) -> Self {
+        match checksum {
+            Checksum::Sha256(_) => Self::Sha256(Sha256::new()),
+            Checksum::Md5(_) => Self::Md5(Md5::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha256(hasher) => hasher.update(data),
+            Self::Md5(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finalize(self) -> String {
+        match self {
+            Self::Sha256(hasher) => format!("{:x}", hasher.finalize()),
+            Self::Md5(hasher) => format!("{:x}", hasher.finalize()),
+        }
+    }
+}
+
+/// Download `url` to `destination`, verifying it against `checksum` if given.
+///
+/// The file is written next to the destination first and only moved into place
+/// once complete and verified, so a failed download never leaves a corrupt JAR behind.
+pub async fn download_file(
+    http: &reqwest::Client,
+    url: &str,
+    destination: &Path,
+    checksum: Option<&Checksum>,
+) -> Result<()> {
+    let mut response = http
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| McServerError::InstallFailed(format!("Failed to download {}: {}", url, e)))?;
+
+    if let Some(parent) = destination.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let partial = destination.with_extension("part");
+    let mut file = tokio::fs::File::create(&partial).await?;
+    let mut hasher = checksum.map(Hasher::new);
+
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| McServerError::InstallFailed(format!("Failed to download {}: {}", url, e)))?
+    {
+        if let Some(hasher) = hasher.as_mut() {
+            hasher.update(&chunk);
+        }
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+    drop(file);
+
+    if let (Some(hasher), Some(checksum)) = (hasher, checksum) {
+        let expected = checksum.expected();
+        let actual = hasher.finalize();
+        if !actual.eq_ignore_ascii_case(expected) {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(McServerError::InstallFailed(format!(
+                "Checksum mismatch for {}: expected {}, got {}",
+                url, expected, actual
+            )));
+        }
+    }
+
+    tokio::fs::rename(&partial, destination).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const BODY: &[u8] = b"not really a jar";
+
+    async fn serve_body() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/server.jar"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(BODY))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    fn temp_jar(name: &str) -> std::path::PathBuf {
+        temp_dir(&format!("download-{}", name)).join("server.jar")
+    }
+
+    #[tokio::test]
+    async fn test_download_verifies_checksum() {
+        let server = serve_body().await;
+        let destination = temp_jar("valid");
+        let checksum = Checksum::Sha256(format!("{:x}", Sha256::digest(BODY)));
+
+        download_file(
+            &reqwest::Client::new(),
+            &format!("{}/server.jar", server.uri()),
+            &destination,
+            Some(&checksum),
+        )
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read(&destination).unwrap(), BODY);
+
+        std::fs::remove_dir_all(destination.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_verifies_md5() {
+        let server = serve_body().await;
+        let destination = temp_jar("md5");
+        let checksum = Checksum::Md5(format!("{:x}", Md5::digest(BODY)));
+
+        download_file(
+            &reqwest::Client::new(),
+            &format!("{}/server.jar", server.uri()),
+            &destination,
+            Some(&checksum),
+        )
+        .await
+        .unwrap();
+        assert!(destination.exists());
+
+        std::fs::remove_dir_all(destination.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_checksum_mismatch_leaves_no_file() {
+        let server = serve_body().await;
+        let destination = temp_jar("mismatch");
+        let checksum = Checksum::Md5("0".repeat(32));
+
+        let result = download_file(
+            &reqwest::Client::new(),
+            &format!("{}/server.jar", server.uri()),
+            &destination,
+            Some(&checksum),
+        )
+        .await;
+        assert!(matches!(result, Err(McServerError::InstallFailed(_))));
+        assert!(!destination.exists());
+        assert!(!destination.with_extension("part").exists());
+
+        std::fs::remove_dir_all(destination.parent().unwrap()).unwrap();
+    }
+}
@@ -11,6 +11,9 @@ pub enum McServerError {
     #[error("Server process not found")]
     ProcessNotFound,
 
+    #[error("Server is not accepting connections yet")]
+    ServerNotReady,
+
     #[error("Version not found: {0}")]
     VersionNotFound(String),
 
@@ -23,6 +26,18 @@ pub enum McServerError {
     #[error("EULA not accepted")]
     EulaNotAccepted,
 
+    #[error("Already installed: {0}")]
+    AlreadyInstalled(String),
+
+    #[error("RCON password was rejected")]
+    RconAuthFailed,
+
+    #[error("Player not found: {0}")]
+    PlayerNotFound(String),
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
     #[error("Invalid configuration: {0}")]
     InvalidConfig(String),
 
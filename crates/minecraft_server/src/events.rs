@@ -1,4 +1,6 @@
 use crate::models::ServerStatus;
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Events emitted during server lifecycle operations.
 #[derive(Debug, Clone)]
@@ -16,14 +18,59 @@ pub enum ServerEvent {
     },
     /// Server has successfully started (detected "Done" in console output).
     Started,
+    /// Server is accepting players, having taken `startup_time` to start
+    /// (as reported on its "Done" line). Emitted right after [`ServerEvent::Started`].
+    Ready { startup_time: Duration },
     /// Server has stopped normally.
     Stopped,
     /// Server process crashed with the given exit code.
-    Crashed { exit_code: i32 },
+    ///
+    /// If the server wrote a crash report during this run, `report_path` points to it
+    /// and `summary` holds its description and exception.
+    Crashed {
+        exit_code: i32,
+        report_path: Option<PathBuf>,
+        summary: Option<String>,
+    },
+    /// The server exited unexpectedly and will be restarted after `delay_secs`.
+    Restarting { attempt: u32, delay_secs: u64 },
+    /// The server kept exiting after `attempts` automatic restarts, so it was left stopped.
+    CrashLoopDetected { attempts: u32 },
+    /// A scheduled restart will happen in `seconds_remaining`; players have been warned in chat.
+    RestartWarning { seconds_remaining: u64 },
+    /// A scheduled restart is starting now.
+    ScheduledRestart,
+    /// A player joined the game.
+    PlayerJoined { player: String },
+    /// A player left the game.
+    PlayerLeft { player: String },
+    /// A player sent a chat message.
+    Chat { player: String, message: String },
+    /// Progress of upgrading the server from one Minecraft version to another.
+    UpgradeProgress {
+        from_version: String,
+        to_version: String,
+        stage: UpgradeStage,
+    },
     /// Java version mismatch detected in console output.
     JavaVersionError,
 }
 
+/// Steps of [`ServerManager::upgrade`](crate::ServerManager::upgrade), in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpgradeStage {
+    /// Stopping the running server.
+    Stopping,
+    /// Snapshotting the worlds and server files.
+    BackingUp,
+    /// Installing the server JAR for the new version.
+    Installing,
+    /// The upgrade finished; the snapshot is kept at `backup`.
+    Completed { backup: PathBuf },
+    /// Installing failed and the previous install was restored from `backup`.
+    RolledBack { backup: PathBuf },
+}
+
 /// Trait for handling server events. Implement this to receive callbacks
 /// during server lifecycle operations (start, stop, crash, console output, etc.).
 ///
@@ -0,0 +1,83 @@
+use crate::error::McServerError;
+use crate::Result;
+use image::imageops::FilterType;
+use image::{ImageFormat, ImageReader};
+use std::path::Path;
+
+/// Filename the server loads its icon from.
+pub const ICON_FILENAME: &str = "server-icon.png";
+
+/// Width and height, in pixels, the server requires for its icon.
+pub const ICON_SIZE: u32 = 64;
+
+/// Convert an image to a 64×64 PNG at `destination`.
+///
+/// Non-square images are cropped to their centered square first so they are not distorted.
+/// PNG, JPEG, GIF, BMP, and WebP are supported; anything else is rejected with
+/// [`McServerError::InvalidConfig`].
+pub fn convert_icon(source: &Path, destination: &Path) -> Result<()> {
+    let reader = ImageReader::open(source)?.with_guessed_format()?;
+    if reader.format().is_none() {
+        return Err(McServerError::InvalidConfig(format!(
+            "Unsupported image format: {}",
+            source.display()
+        )));
+    }
+    let image = reader.decode().map_err(|e| {
+        McServerError::InvalidConfig(format!("Unable to read image {}: {}", source.display(), e))
+    })?;
+
+    let side = image.width().min(image.height());
+    let cropped = image.crop_imm(
+        (image.width() - side) / 2,
+        (image.height() - side) / 2,
+        side,
+        side,
+    );
+    cropped
+        .resize_exact(ICON_SIZE, ICON_SIZE, FilterType::Lanczos3)
+        .save_with_format(destination, ImageFormat::Png)
+        .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", destination.display(), e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use image::{Rgba, RgbaImage};
+
+    #[test]
+    fn test_large_image_becomes_64px_png() {
+        let dir = temp_dir("icon-convert");
+        let source = dir.join("banner.png");
+        RgbaImage::from_pixel(300, 200, Rgba([200, 40, 40, 255]))
+            .save_with_format(&source, ImageFormat::Png)
+            .unwrap();
+
+        let destination = dir.join(ICON_FILENAME);
+        convert_icon(&source, &destination).unwrap();
+
+        let reader = ImageReader::open(&destination).unwrap().with_guessed_format().unwrap();
+        assert_eq!(reader.format(), Some(ImageFormat::Png));
+        let icon = reader.decode().unwrap();
+        assert_eq!((icon.width(), icon.height()), (ICON_SIZE, ICON_SIZE));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unsupported_format_is_rejected() {
+        let dir = temp_dir("icon-unsupported");
+        let source = dir.join("icon.png");
+        std::fs::write(&source, "definitely not an image").unwrap();
+
+        assert!(matches!(
+            convert_icon(&source, &dir.join(ICON_FILENAME)),
+            Err(McServerError::InvalidConfig(_))
+        ));
+        assert!(!dir.join(ICON_FILENAME).exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -56,9 +56,6 @@ pub async fn install_vanilla(
     #[cfg(feature = "logging")]
     info!("Server JAR downloaded to {}", jar_path.display());
 
-    // Accept EULA
-    crate::eula::accept_eula(&config.directory)?;
-
     handler
         .on_event(ServerEvent::InstallProgress {
             file: "server.jar".to_string(),
@@ -71,6 +68,177 @@ pub async fn install_vanilla(
     Ok(jar_name)
 }
 
+/// Parse `config.loader_version` as a build number for APIs that publish numbered builds.
+fn requested_build(config: &ServerConfig, project: &str) -> Result<Option<u32>> {
+    config
+        .loader_version
+        .as_deref()
+        .map(|v| {
+            v.parse::<u32>().map_err(|_| {
+                McServerError::InvalidConfig(format!("Invalid {} build number: {}", project, v))
+            })
+        })
+        .transpose()
+}
+
+/// Download a server JAR selected from a download API into the server directory.
+async fn install_server_jar(
+    config: &ServerConfig,
+    handler: &impl ServerEventHandler,
+    http: &reqwest::Client,
+    jar: crate::download::ServerJar,
+) -> Result<InstallResult> {
+    handler
+        .on_event(ServerEvent::InstallProgress {
+            file: jar.file_name.clone(),
+            completed: false,
+            total: 1,
+            current: 0,
+        })
+        .await;
+
+    crate::download::download_file(
+        http,
+        &jar.url,
+        &config.directory.join(&jar.file_name),
+        Some(&jar.checksum),
+    )
+    .await?;
+
+    handler
+        .on_event(ServerEvent::InstallProgress {
+            file: jar.file_name.clone(),
+            completed: true,
+            total: 1,
+            current: 1,
+        })
+        .await;
+
+    Ok(InstallResult {
+        server_jar: jar.file_name,
+        java_args: String::new(),
+        loader_version: Some(jar.build.to_string()),
+    })
+}
+
+/// Install a Paper server by downloading the JAR from the PaperMC API.
+///
+/// `config.loader_version` selects a Paper build number; the latest stable build is used if unset.
+pub async fn install_paper(
+    config: &ServerConfig,
+    handler: &impl ServerEventHandler,
+) -> Result<InstallResult> {
+    let build = requested_build(config, "Paper")?;
+    let client = crate::paper::PaperClient::new();
+    let jar = client.find_build(&config.minecraft_version, build).await?;
+
+    #[cfg(feature = "logging")]
+    info!(
+        "Downloading Paper build {} for Minecraft {}",
+        jar.build, config.minecraft_version
+    );
+
+    install_server_jar(config, handler, client.http(), jar).await
+}
+
+/// Install a Purpur server by downloading the JAR from the Purpur API.
+///
+/// `config.loader_version` selects a Purpur build number; the latest build is used if unset.
+pub async fn install_purpur(
+    config: &ServerConfig,
+    handler: &impl ServerEventHandler,
+) -> Result<InstallResult> {
+    let build = requested_build(config, "Purpur")?;
+    let client = crate::purpur::PurpurClient::new();
+    let jar = client.find_build(&config.minecraft_version, build).await?;
+
+    #[cfg(feature = "logging")]
+    info!(
+        "Downloading Purpur build {} for Minecraft {}",
+        jar.build, config.minecraft_version
+    );
+
+    install_server_jar(config, handler, client.http(), jar).await
+}
+
+const BUILD_TOOLS_URL: &str =
+    "https://hub.spigotmc.org/jenkins/job/BuildTools/lastSuccessfulBuild/artifact/target/BuildTools.jar";
+
+/// Install a Spigot server by compiling it with SpigotMC's BuildTools.
+///
+/// Spigot does not publish server JARs, so this requires Java and Git and can take several minutes.
+pub async fn install_spigot(
+    config: &ServerConfig,
+    handler: &impl ServerEventHandler,
+) -> Result<String> {
+    let jar_name = format!("spigot-{}.jar", config.minecraft_version);
+    let build_dir = config.directory.join(".buildtools");
+
+    handler
+        .on_event(ServerEvent::InstallProgress {
+            file: "BuildTools.jar".to_string(),
+            completed: false,
+            total: 2,
+            current: 0,
+        })
+        .await;
+
+    let build_tools = build_dir.join("BuildTools.jar");
+    crate::download::download_file(&reqwest::Client::new(), BUILD_TOOLS_URL, &build_tools, None)
+        .await?;
+
+    handler
+        .on_event(ServerEvent::InstallProgress {
+            file: jar_name.clone(),
+            completed: false,
+            total: 2,
+            current: 1,
+        })
+        .await;
+
+    #[cfg(feature = "logging")]
+    info!(
+        "Building Spigot for Minecraft {} with BuildTools",
+        config.minecraft_version
+    );
+
+    let output_dir = config.directory.canonicalize()?;
+    let status = tokio::process::Command::new(&config.java_executable)
+        .arg("-jar")
+        .arg("BuildTools.jar")
+        .arg("--rev")
+        .arg(&config.minecraft_version)
+        .arg("--output-dir")
+        .arg(&output_dir)
+        .current_dir(&build_dir)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .await?;
+    if !status.success() || !config.directory.join(&jar_name).exists() {
+        return Err(McServerError::InstallFailed(format!(
+            "BuildTools failed to build Spigot {} ({}); see {}",
+            config.minecraft_version,
+            status,
+            build_dir.join("BuildTools.log.txt").display()
+        )));
+    }
+
+    // The build workspace is large and only needed to rebuild
+    let _ = tokio::fs::remove_dir_all(&build_dir).await;
+
+    handler
+        .on_event(ServerEvent::InstallProgress {
+            file: jar_name.clone(),
+            completed: true,
+            total: 2,
+            current: 2,
+        })
+        .await;
+
+    Ok(jar_name)
+}
+
 /// Install a Fabric server using the fabric-loader crate.
 pub async fn install_fabric(config: &ServerConfig) -> Result<InstallResult> {
     #[cfg(feature = "logging")]
@@ -113,9 +281,6 @@ pub async fn install_fabric(config: &ServerConfig) -> Result<InstallResult> {
         .await
         .map_err(|e| McServerError::InstallFailed(e.to_string()))?;
 
-    // Accept EULA
-    crate::eula::accept_eula(&config.directory)?;
-
     Ok(InstallResult {
         server_jar: result
             .server_jar
@@ -153,9 +318,6 @@ pub async fn install_forge(config: &ServerConfig) -> Result<InstallResult> {
         .await
         .map_err(|e| McServerError::InstallFailed(e.to_string()))?;
 
-    // Accept EULA
-    crate::eula::accept_eula(&config.directory)?;
-
     Ok(InstallResult {
         server_jar: result.server_jar,
         java_args: result.java_args,
@@ -189,9 +351,6 @@ pub async fn install_neoforge(config: &ServerConfig) -> Result<InstallResult> {
         .await
         .map_err(|e| McServerError::InstallFailed(e.to_string()))?;
 
-    // Accept EULA
-    crate::eula::accept_eula(&config.directory)?;
-
     Ok(InstallResult {
         server_jar: result.server_jar,
         java_args: result.java_args,
@@ -212,6 +371,8 @@ pub struct InstallResult {
 
 /// Install a server based on the configuration's server type.
 /// Updates `config.server_jar` and `config.java_args` with the installation results.
+///
+/// If `config.accept_eula` is set, the Minecraft EULA is accepted on the owner's behalf.
 pub async fn install_server(
     config: &mut ServerConfig,
     handler: &impl ServerEventHandler,
@@ -241,6 +402,19 @@ pub async fn install_server(
             config.server_jar = result.server_jar;
             config.java_args = result.java_args;
         }
+        ServerType::Paper => {
+            let result = install_paper(config, handler).await?;
+            config.server_jar = result.server_jar;
+            config.loader_version = result.loader_version;
+        }
+        ServerType::Purpur => {
+            let result = install_purpur(config, handler).await?;
+            config.server_jar = result.server_jar;
+            config.loader_version = result.loader_version;
+        }
+        ServerType::Spigot => {
+            config.server_jar = install_spigot(config, handler).await?;
+        }
         ServerType::Custom => {
             // Custom servers bring their own JAR - nothing to install
             #[cfg(feature = "logging")]
@@ -253,5 +427,44 @@ pub async fn install_server(
         }
     }
 
+    if config.accept_eula {
+        crate::eula::accept_eula(&config.directory)?;
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::NoOpHandler;
+    use crate::test_util::temp_dir;
+
+    async fn install_custom(name: &str, accept_eula: bool) -> std::path::PathBuf {
+        let directory = temp_dir(&format!("install-{}", name));
+        let mut config = ServerConfig {
+            directory: directory.clone(),
+            server_type: ServerType::Custom,
+            accept_eula,
+            ..Default::default()
+        };
+        install_server(&mut config, &NoOpHandler).await.unwrap();
+        directory
+    }
+
+    #[tokio::test]
+    async fn test_eula_accepted_when_flag_set() {
+        let directory = install_custom("eula", true).await;
+        let eula = std::fs::read_to_string(directory.join("eula.txt")).unwrap();
+        assert!(eula.lines().any(|line| line == "eula=true"));
+        assert!(crate::eula::is_eula_accepted(&directory));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_eula_untouched_when_flag_unset() {
+        let directory = install_custom("no-eula", false).await;
+        assert!(!directory.join("eula.txt").exists());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
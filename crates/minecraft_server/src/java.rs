@@ -0,0 +1,232 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "logging")]
+use log::debug;
+
+/// The `java_executable` value that means "use whatever `java` is on the PATH".
+///
+/// When a config leaves it at this default, a compatible installation is picked automatically.
+pub const DEFAULT_JAVA_EXECUTABLE: &str = "java";
+
+/// A Java installation found on this machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JavaInstall {
+    /// Path to the `java` executable.
+    pub path: PathBuf,
+    /// Major version (e.g. 8, 17, 21).
+    pub major_version: u32,
+}
+
+/// The minimum Java major version a Minecraft version needs to run.
+///
+/// 1.20.5+ needs Java 21, 1.17+ needs Java 17, and older versions need Java 8.
+/// Snapshots and unrecognized versions are assumed to be recent.
+pub fn required_java_major(minecraft_version: &str) -> u32 {
+    let Some((minor, patch)) = parse_release(minecraft_version) else {
+        return 21;
+    };
+    if (minor, patch) >= (20, 5) {
+        21
+    } else if minor >= 17 {
+        17
+    } else {
+        8
+    }
+}
+
+/// Parse "1.20.5" into `(20, 5)`. Returns `None` for snapshots and other non-release IDs.
+fn parse_release(minecraft_version: &str) -> Option<(u32, u32)> {
+    let mut parts = minecraft_version.split('.');
+    if parts.next()? != "1" {
+        return None;
+    }
+    let minor = parts.next()?.parse().ok()?;
+    let patch = match parts.next() {
+        Some(patch) => patch.parse().ok()?,
+        None => 0,
+    };
+    Some((minor, patch))
+}
+
+/// Pick a Java installation able to run `minecraft_version`.
+///
+/// Old versions (and the mod loaders built for them) often break on newer Java, so
+/// the compatible install with the lowest major version is preferred.
+pub fn select_java_for(minecraft_version: &str, candidates: &[JavaInstall]) -> Option<PathBuf> {
+    let required = required_java_major(minecraft_version);
+    candidates
+        .iter()
+        .filter(|install| install.major_version >= required)
+        .min_by_key(|install| install.major_version)
+        .map(|install| install.path.clone())
+}
+
+/// Find Java installations in `JAVA_HOME`, on the PATH, and in the usual install directories.
+pub async fn discover_java_installs() -> Vec<JavaInstall> {
+    let mut installs = Vec::new();
+    let mut seen = HashSet::new();
+
+    for path in candidate_executables() {
+        let key = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if !seen.insert(key) {
+            continue;
+        }
+        if let Some(major_version) = probe_major_version(&path).await {
+            #[cfg(feature = "logging")]
+            debug!("Found Java {} at {}", major_version, path.display());
+            installs.push(JavaInstall {
+                path,
+                major_version,
+            });
+        }
+    }
+    installs
+}
+
+fn java_binary_name() -> &'static str {
+    if cfg!(windows) { "java.exe" } else { "java" }
+}
+
+fn candidate_executables() -> Vec<PathBuf> {
+    let binary = java_binary_name();
+    let mut candidates = Vec::new();
+
+    if let Some(home) = std::env::var_os("JAVA_HOME") {
+        candidates.push(PathBuf::from(home).join("bin").join(binary));
+    }
+    if let Some(path) = std::env::var_os("PATH") {
+        candidates.extend(
+            std::env::split_paths(&path)
+                .map(|dir| dir.join(binary))
+                .filter(|java| java.is_file()),
+        );
+    }
+
+    let install_roots: &[&str] = if cfg!(windows) {
+        &[
+            r"C:\Program Files\Java",
+            r"C:\Program Files\Eclipse Adoptium",
+            r"C:\Program Files\Zulu",
+        ]
+    } else if cfg!(target_os = "macos") {
+        &["/Library/Java/JavaVirtualMachines"]
+    } else {
+        &["/usr/lib/jvm", "/usr/java", "/opt/java"]
+    };
+    for root in install_roots {
+        let Ok(entries) = std::fs::read_dir(root) else {
+            continue;
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let home = entry.path();
+            for java in [
+                home.join("bin").join(binary),
+                home.join("Contents/Home/bin").join(binary),
+            ] {
+                if java.is_file() {
+                    candidates.push(java);
+                }
+            }
+        }
+    }
+
+    candidates
+}
+
+/// Run `java -version` and read the major version from its output.
+async fn probe_major_version(java: &Path) -> Option<u32> {
+    let output = tokio::process::Command::new(java)
+        .arg("-version")
+        .output()
+        .await
+        .ok()?;
+    // `java -version` prints to stderr
+    let text = String::from_utf8_lossy(&output.stderr);
+    parse_java_version_output(&text)
+}
+
+/// Read the major version from `java -version` output, e.g. `openjdk version "21.0.2"` or
+/// `java version "1.8.0_392"`.
+pub fn parse_java_version_output(output: &str) -> Option<u32> {
+    let line = output.lines().find(|line| line.contains(" version \""))?;
+    let version = line.split('"').nth(1)?;
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    let major: u32 = parts.next()?.parse().ok()?;
+    if major == 1 {
+        // Java 8 and earlier report "1.x"
+        parts.next()?.parse().ok()
+    } else {
+        Some(major)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn install(path: &str, major_version: u32) -> JavaInstall {
+        JavaInstall {
+            path: PathBuf::from(path),
+            major_version,
+        }
+    }
+
+    #[test]
+    fn test_required_java_major() {
+        assert_eq!(required_java_major("1.8.9"), 8);
+        assert_eq!(required_java_major("1.12.2"), 8);
+        assert_eq!(required_java_major("1.16.5"), 8);
+        assert_eq!(required_java_major("1.17"), 17);
+        assert_eq!(required_java_major("1.18.2"), 17);
+        assert_eq!(required_java_major("1.20.4"), 17);
+        assert_eq!(required_java_major("1.20.5"), 21);
+        assert_eq!(required_java_major("1.21"), 21);
+        assert_eq!(required_java_major("1.21.4"), 21);
+        assert_eq!(required_java_major("24w14a"), 21);
+    }
+
+    #[test]
+    fn test_select_prefers_lowest_compatible_major() {
+        let installs = [
+            install("/jvm/21/bin/java", 21),
+            install("/jvm/8/bin/java", 8),
+            install("/jvm/17/bin/java", 17),
+        ];
+        assert_eq!(
+            select_java_for("1.12.2", &installs),
+            Some(PathBuf::from("/jvm/8/bin/java"))
+        );
+        assert_eq!(
+            select_java_for("1.19.4", &installs),
+            Some(PathBuf::from("/jvm/17/bin/java"))
+        );
+        assert_eq!(
+            select_java_for("1.21.4", &installs),
+            Some(PathBuf::from("/jvm/21/bin/java"))
+        );
+    }
+
+    #[test]
+    fn test_select_without_compatible_install() {
+        let installs = [install("/jvm/17/bin/java", 17)];
+        assert_eq!(select_java_for("1.21.4", &installs), None);
+        assert_eq!(select_java_for("1.21.4", &[]), None);
+    }
+
+    #[test]
+    fn test_parse_java_version_output() {
+        assert_eq!(
+            parse_java_version_output(
+                "openjdk version \"21.0.2\" 2024-01-16\nOpenJDK Runtime Environment (build 21.0.2+13-58)"
+            ),
+            Some(21)
+        );
+        assert_eq!(
+            parse_java_version_output("java version \"1.8.0_392\"\nJava(TM) SE Runtime Environment"),
+            Some(8)
+        );
+        assert_eq!(parse_java_version_output("openjdk version \"17-ea\" 2021-09-14"), Some(17));
+        assert_eq!(parse_java_version_output("command not found"), None);
+    }
+}
@@ -0,0 +1,73 @@
+/// Heaps above this size use the large-heap variant of Aikar's flags.
+const LARGE_HEAP_MB: u32 = 12 * 1024;
+
+/// Aikar's G1GC flags (<https://docs.papermc.io/paper/aikars-flags>), tuned for the given heap size.
+///
+/// Heaps larger than 12 GB get a bigger young generation and region size, as recommended.
+pub fn aikar_flags(memory_mb: u32) -> Vec<String> {
+    let large = memory_mb > LARGE_HEAP_MB;
+    let (new_size, max_new_size, region_size, reserve, initiating_occupancy) = if large {
+        (40, 50, "16M", 15, 20)
+    } else {
+        (30, 40, "8M", 20, 15)
+    };
+
+    vec![
+        "-XX:+UseG1GC".to_string(),
+        "-XX:+ParallelRefProcEnabled".to_string(),
+        "-XX:MaxGCPauseMillis=200".to_string(),
+        "-XX:+UnlockExperimentalVMOptions".to_string(),
+        "-XX:+DisableExplicitGC".to_string(),
+        "-XX:+AlwaysPreTouch".to_string(),
+        format!("-XX:G1NewSizePercent={}", new_size),
+        format!("-XX:G1MaxNewSizePercent={}", max_new_size),
+        format!("-XX:G1HeapRegionSize={}", region_size),
+        format!("-XX:G1ReservePercent={}", reserve),
+        "-XX:G1HeapWastePercent=5".to_string(),
+        "-XX:G1MixedGCCountTarget=4".to_string(),
+        format!("-XX:InitiatingHeapOccupancyPercent={}", initiating_occupancy),
+        "-XX:G1MixedGCLiveThresholdPercent=90".to_string(),
+        "-XX:G1RSetUpdatingPauseTimePercent=5".to_string(),
+        "-XX:SurvivorRatio=32".to_string(),
+        "-XX:+PerfDisableSharedMem".to_string(),
+        "-XX:MaxTenuringThreshold=1".to_string(),
+        "-Dusing.aikars.flags=https://mcflags.emc.gs".to_string(),
+        "-Daikars.new.flags=true".to_string(),
+    ]
+}
+
+/// Whether `java_args` already choose a garbage collector, in which case
+/// Aikar's flags would conflict with the user's settings.
+pub fn selects_garbage_collector(java_args: &str) -> bool {
+    java_args
+        .split_whitespace()
+        .any(|arg| arg.starts_with("-XX:+Use") && arg.ends_with("GC"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aikar_flags_for_small_heap() {
+        let flags = aikar_flags(4096);
+        assert!(flags.contains(&"-XX:+UseG1GC".to_string()));
+        assert!(flags.contains(&"-XX:G1HeapRegionSize=8M".to_string()));
+        assert!(flags.contains(&"-XX:G1NewSizePercent=30".to_string()));
+    }
+
+    #[test]
+    fn test_aikar_flags_for_large_heap() {
+        let flags = aikar_flags(16384);
+        assert!(flags.contains(&"-XX:+UseG1GC".to_string()));
+        assert!(flags.contains(&"-XX:G1HeapRegionSize=16M".to_string()));
+        assert!(flags.contains(&"-XX:G1NewSizePercent=40".to_string()));
+        assert!(flags.contains(&"-XX:InitiatingHeapOccupancyPercent=20".to_string()));
+    }
+
+    #[test]
+    fn test_selects_garbage_collector() {
+        assert!(selects_garbage_collector("-XX:+UseZGC -Dfoo=bar"));
+        assert!(!selects_garbage_collector("-Dfoo=bar @user_jvm_args.txt"));
+    }
+}
@@ -7,8 +7,25 @@
 //!
 //! - Download and install vanilla Minecraft servers via [piston-mc](https://crates.io/crates/piston-mc)
 //! - Install modded servers (Fabric, Forge, NeoForge) via loader crates
+//! - Install plugin servers (Paper, Purpur, Spigot)
+//! - Pick an installed Java compatible with the Minecraft version
 //! - Manage server lifecycle: start, stop, restart, kill
-//! - Send commands to running servers and read console output
+//! - Upgrade to a new Minecraft version with a snapshot and rollback on failure
+//! - Back up worlds to zip archives, pausing auto-save while the server runs
+//! - Launch with Aikar's G1GC flags scaled to the heap size
+//! - Automatically restart crashed servers with backoff and crash-loop protection
+//! - Surface the crash report summary when a server crashes
+//! - Schedule restarts by interval or cron expression, with in-game warnings
+//! - Send commands to running servers and read console output, with recent history
+//! - Run commands over RCON and read back exactly their output
+//! - Report player joins, leaves, and chat messages parsed from the console
+//! - Install datapacks into the active world
+//! - Set the server list icon from any common image format
+//! - Read and edit server.properties without losing comments or unknown keys
+//! - Query live MOTD and player counts via Server List Ping
+//! - Monitor CPU and memory usage of the server process tree
+//! - Report TPS and tick times from the server's own statistics
+//! - Manage the whitelist, operators, and bans, resolving player names through Mojang's API
 //! - Event-based architecture with no database dependencies
 //!
 //! ## Quick Start
@@ -32,17 +49,55 @@
 //! # }
 //! ```
 
+pub mod backup;
+pub mod bans;
+pub mod console;
+pub mod crash;
+pub mod datapack;
+pub mod download;
 pub mod error;
 pub mod eula;
 pub mod events;
+pub mod icon;
 pub mod installer;
+pub mod java;
+pub mod jvm;
 pub mod models;
+pub mod mojang;
+pub mod ops;
+pub mod paper;
+pub mod ping;
 pub mod process;
 pub mod properties;
+pub mod purpur;
+pub mod rcon;
+pub mod restart;
+pub mod schedule;
 pub mod server;
+pub mod tps;
+pub mod upgrade;
+pub mod usage;
 pub mod versions;
+pub mod whitelist;
 
+mod player_list;
+
+#[cfg(test)]
+mod test_util;
+
+pub use bans::{BanEntry, Bans, IpBanEntry};
+pub use crash::CrashReport;
 pub use error::{McServerError, Result};
-pub use events::{NoOpHandler, ServerEvent, ServerEventHandler};
-pub use models::{ServerConfig, ServerInfo, ServerStatus, ServerType};
+pub use events::{NoOpHandler, ServerEvent, ServerEventHandler, UpgradeStage};
+pub use java::JavaInstall;
+pub use models::{RestartPolicy, ServerConfig, ServerInfo, ServerStatus, ServerType};
+pub use ops::{OpEntry, Ops};
+pub use ping::ServerStatusResponse;
+pub use process::StopOutcome;
+pub use properties::{Difficulty, Gamemode, ServerProperties};
+pub use rcon::RconClient;
+pub use schedule::RestartSchedule;
 pub use server::ServerManager;
+pub use tps::TpsReport;
+pub use usage::ResourceUsage;
+pub use whitelist::{Whitelist, WhitelistEntry};
@@ -12,6 +12,9 @@ pub enum ServerType {
     NeoForge,
     Quilt,
     Custom,
+    Paper,
+    Spigot,
+    Purpur,
 }
 
 impl From<u8> for ServerType {
@@ -22,6 +25,9 @@ impl From<u8> for ServerType {
             2 => Self::Fabric,
             3 => Self::NeoForge,
             4 => Self::Quilt,
+            6 => Self::Paper,
+            7 => Self::Spigot,
+            8 => Self::Purpur,
             _ => Self::Custom,
         }
     }
@@ -39,6 +45,9 @@ impl Display for ServerType {
                 Self::NeoForge => "NeoForge",
                 Self::Quilt => "Quilt",
                 Self::Custom => "Custom",
+                Self::Paper => "Paper",
+                Self::Spigot => "Spigot",
+                Self::Purpur => "Purpur",
             }
         )
     }
@@ -55,6 +64,9 @@ impl FromStr for ServerType {
             "neo-forge" | "neoforge" => Ok(Self::NeoForge),
             "quilt" => Ok(Self::Quilt),
             "custom" => Ok(Self::Custom),
+            "paper" => Ok(Self::Paper),
+            "spigot" => Ok(Self::Spigot),
+            "purpur" => Ok(Self::Purpur),
             _ => Err(format!("Unknown server type: {}", s)),
         }
     }
@@ -70,6 +82,18 @@ impl<'de> Deserialize<'de> for ServerType {
     }
 }
 
+impl ServerType {
+    /// Directory, relative to the server directory, that the server loads mods or
+    /// plugins from. `None` for server types without mod support.
+    pub fn mods_directory(&self) -> Option<&'static str> {
+        match self {
+            Self::Forge | Self::Fabric | Self::NeoForge | Self::Quilt => Some("mods"),
+            Self::Paper | Self::Spigot | Self::Purpur => Some("plugins"),
+            Self::Vanilla | Self::Custom => None,
+        }
+    }
+}
+
 /// Server lifecycle status.
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum ServerStatus {
@@ -130,6 +154,24 @@ impl<'de> Deserialize<'de> for ServerStatus {
     }
 }
 
+/// What to do when the server process exits without being asked to stop.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RestartPolicy {
+    /// Leave the server stopped.
+    #[default]
+    Never,
+    /// Restart only when the process exits with a non-zero code.
+    OnCrash,
+    /// Restart whenever the process exits, including clean exits.
+    Always,
+}
+
+/// Smallest heap accepted for a server; Minecraft will not start with less.
+pub const MIN_HEAP_MB: u32 = 512;
+
+/// Largest heap accepted for a server, to catch values entered in the wrong unit.
+pub const MAX_HEAP_MB: u32 = 1024 * 1024;
+
 /// Database-free server configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
@@ -137,14 +179,18 @@ pub struct ServerConfig {
     pub name: String,
     /// Absolute path to the server directory.
     pub directory: PathBuf,
-    /// Path to the Java executable (e.g. "java" or "/usr/bin/java").
+    /// Path to the Java executable (e.g. "/usr/bin/java").
+    /// Left as "java", a Java compatible with `minecraft_version` is picked automatically.
     pub java_executable: String,
     /// Extra JVM arguments (not including -Xmx/-Xms).
     pub java_args: String,
-    /// Maximum heap memory in GB (-Xmx).
-    pub max_memory_gb: u8,
-    /// Minimum heap memory in GB (-Xms).
-    pub min_memory_gb: u8,
+    /// Add Aikar's G1GC tuning flags, unless `java_args` already select a garbage collector.
+    #[serde(default = "default_aikar_flags")]
+    pub aikar_flags: bool,
+    /// Maximum heap memory in MB (-Xmx).
+    pub max_memory_mb: u32,
+    /// Minimum heap memory in MB (-Xms).
+    pub min_memory_mb: u32,
     /// Extra Minecraft server arguments.
     pub minecraft_args: String,
     /// Server JAR filename (e.g. "server.jar").
@@ -155,9 +201,60 @@ pub struct ServerConfig {
     pub server_type: ServerType,
     /// Loader version (e.g. "0.15.0" for Fabric, "47.3.22" for Forge).
     pub loader_version: Option<String>,
+    /// Whether to restart the server automatically after it exits unexpectedly.
+    #[serde(default)]
+    pub restart_policy: RestartPolicy,
+    /// Consecutive automatic restarts allowed before giving up as a crash loop.
+    #[serde(default = "default_max_restart_attempts")]
+    pub max_restart_attempts: u32,
+    /// Delay before the first automatic restart, doubled on each consecutive attempt.
+    #[serde(default = "default_restart_delay_secs")]
+    pub restart_delay_secs: u64,
+    /// How long to wait for the server to exit after "stop" before killing it.
+    #[serde(default = "default_stop_timeout_secs")]
+    pub stop_timeout_secs: u64,
+    /// Number of recent console lines kept for viewers that connect later.
+    #[serde(default = "default_console_history_lines")]
+    pub console_history_lines: usize,
+    /// Write `eula=true` to `eula.txt` during install.
+    ///
+    /// Setting this means the server owner agrees to the Minecraft EULA
+    /// (<https://aka.ms/MinecraftEULA>); only enable it on their explicit instruction.
+    /// Without it, the server refuses to start until `eula.txt` is edited by hand.
+    #[serde(default)]
+    pub accept_eula: bool,
+    /// When set, `install` and `upgrade` archive the world into this directory first.
+    #[serde(default)]
+    pub world_backup_dir: Option<PathBuf>,
+}
+
+fn default_aikar_flags() -> bool {
+    true
+}
+
+fn default_max_restart_attempts() -> u32 {
+    3
+}
+
+fn default_restart_delay_secs() -> u64 {
+    5
+}
+
+fn default_stop_timeout_secs() -> u64 {
+    60
+}
+
+fn default_console_history_lines() -> usize {
+    1000
 }
 
 impl ServerConfig {
+    /// Enable or disable Aikar's G1GC flags.
+    pub fn with_aikar_flags(mut self, enabled: bool) -> Self {
+        self.aikar_flags = enabled;
+        self
+    }
+
     /// Save the configuration to a JSON file.
     pub fn save(&self, path: &std::path::Path) -> crate::Result<()> {
         let json = serde_json::to_string_pretty(self)
@@ -169,10 +266,53 @@ impl ServerConfig {
     /// Load a configuration from a JSON file.
     pub fn load(path: &std::path::Path) -> crate::Result<Self> {
         let json = std::fs::read_to_string(path)?;
-        let config: Self =
+        let mut value: serde_json::Value =
             serde_json::from_str(&json).map_err(|e| crate::McServerError::Other(e.into()))?;
+        migrate_memory_fields(&mut value);
+        let config: Self =
+            serde_json::from_value(value).map_err(|e| crate::McServerError::Other(e.into()))?;
         Ok(config)
     }
+
+    /// Check that the heap settings are usable: both within
+    /// [`MIN_HEAP_MB`]..=[`MAX_HEAP_MB`] and max at least min.
+    pub fn validate(&self) -> crate::Result<()> {
+        for (name, value) in [
+            ("min_memory_mb", self.min_memory_mb),
+            ("max_memory_mb", self.max_memory_mb),
+        ] {
+            if !(MIN_HEAP_MB..=MAX_HEAP_MB).contains(&value) {
+                return Err(crate::McServerError::InvalidConfig(format!(
+                    "{} must be between {} and {} MB, got {}",
+                    name, MIN_HEAP_MB, MAX_HEAP_MB, value
+                )));
+            }
+        }
+        if self.max_memory_mb < self.min_memory_mb {
+            return Err(crate::McServerError::InvalidConfig(format!(
+                "max_memory_mb ({}) must be at least min_memory_mb ({})",
+                self.max_memory_mb, self.min_memory_mb
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Convert the `max_memory_gb`/`min_memory_gb` fields of older config files to MB.
+fn migrate_memory_fields(value: &mut serde_json::Value) {
+    let Some(object) = value.as_object_mut() else {
+        return;
+    };
+    for (old, new) in [
+        ("max_memory_gb", "max_memory_mb"),
+        ("min_memory_gb", "min_memory_mb"),
+    ] {
+        if let Some(gb) = object.remove(old).and_then(|v| v.as_u64()) {
+            object
+                .entry(new)
+                .or_insert_with(|| serde_json::Value::from(gb * 1024));
+        }
+    }
 }
 
 impl Default for ServerConfig {
@@ -180,15 +320,23 @@ impl Default for ServerConfig {
         Self {
             name: "Minecraft Server".to_string(),
             directory: PathBuf::from("."),
-            java_executable: "java".to_string(),
+            java_executable: crate::java::DEFAULT_JAVA_EXECUTABLE.to_string(),
             java_args: String::new(),
-            max_memory_gb: 2,
-            min_memory_gb: 1,
+            aikar_flags: default_aikar_flags(),
+            max_memory_mb: 2048,
+            min_memory_mb: 1024,
             minecraft_args: String::new(),
             server_jar: "server.jar".to_string(),
             minecraft_version: String::new(),
             server_type: ServerType::Vanilla,
             loader_version: None,
+            restart_policy: RestartPolicy::Never,
+            max_restart_attempts: default_max_restart_attempts(),
+            restart_delay_secs: default_restart_delay_secs(),
+            stop_timeout_secs: default_stop_timeout_secs(),
+            console_history_lines: default_console_history_lines(),
+            accept_eula: false,
+            world_backup_dir: None,
         }
     }
 }
@@ -200,3 +348,57 @@ pub struct ServerInfo {
     pub status: ServerStatus,
     pub pid: Option<u32>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    fn with_memory(min_memory_mb: u32, max_memory_mb: u32) -> ServerConfig {
+        ServerConfig {
+            min_memory_mb,
+            max_memory_mb,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_defaults() {
+        assert!(ServerConfig::default().validate().is_ok());
+        assert!(with_memory(4096, 4096).validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_memory() {
+        for config in [
+            with_memory(4096, 2048),
+            with_memory(0, 2048),
+            with_memory(1024, 100),
+            with_memory(1024, MAX_HEAP_MB + 1),
+        ] {
+            assert!(matches!(
+                config.validate(),
+                Err(crate::McServerError::InvalidConfig(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_load_migrates_memory_in_gb() {
+        let dir = temp_dir("config");
+        let path = dir.join("config.json");
+        let mut json = serde_json::to_value(ServerConfig::default()).unwrap();
+        let object = json.as_object_mut().unwrap();
+        object.remove("max_memory_mb");
+        object.remove("min_memory_mb");
+        object.insert("max_memory_gb".to_string(), 4.into());
+        object.insert("min_memory_gb".to_string(), 2.into());
+        std::fs::write(&path, json.to_string()).unwrap();
+
+        let config = ServerConfig::load(&path).unwrap();
+        assert_eq!(config.max_memory_mb, 4096);
+        assert_eq!(config.min_memory_mb, 2048);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
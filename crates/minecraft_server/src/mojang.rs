@@ -0,0 +1,126 @@
+use crate::error::McServerError;
+use crate::Result;
+use serde::Deserialize;
+
+const DEFAULT_API_URL: &str = "https://api.mojang.com";
+const DEFAULT_SESSION_URL: &str = "https://sessionserver.mojang.com";
+
+/// A player's name and UUID as known to Mojang.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerProfile {
+    /// Hyphenated UUID, as used in `whitelist.json` and `ops.json`.
+    pub uuid: String,
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+struct RawProfile {
+    id: String,
+    name: String,
+}
+
+/// Minimal client for resolving player names and UUIDs via Mojang's public API.
+#[derive(Debug, Clone)]
+pub struct MojangClient {
+    http: reqwest::Client,
+    api_url: String,
+    session_url: String,
+}
+
+impl Default for MojangClient {
+    fn default() -> Self {
+        Self::with_base_urls(DEFAULT_API_URL, DEFAULT_SESSION_URL)
+    }
+}
+
+impl MojangClient {
+    /// Create a client pointing at Mojang's production API.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a client pointing at custom endpoints (useful for testing with a mock server).
+    pub fn with_base_urls(api_url: impl Into<String>, session_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            api_url: api_url.into().trim_end_matches('/').to_string(),
+            session_url: session_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Look up a profile by player name or UUID (with or without hyphens).
+    pub async fn resolve(&self, name_or_uuid: &str) -> Result<PlayerProfile> {
+        let url = match normalize_uuid(name_or_uuid) {
+            Some(uuid) => format!(
+                "{}/session/minecraft/profile/{}",
+                self.session_url,
+                uuid.replace('-', "")
+            ),
+            None => format!("{}/users/profiles/minecraft/{}", self.api_url, name_or_uuid),
+        };
+
+        let response = self
+            .http
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to query Mojang API: {}", e))?;
+        // Mojang answers 204 or 404 for unknown players
+        if response.status() == reqwest::StatusCode::NO_CONTENT
+            || response.status() == reqwest::StatusCode::NOT_FOUND
+        {
+            return Err(McServerError::PlayerNotFound(name_or_uuid.to_string()));
+        }
+        let raw: RawProfile = response
+            .error_for_status()
+            .map_err(|e| anyhow::anyhow!("Mojang API error: {}", e))?
+            .json()
+            .await
+            .map_err(|e| anyhow::anyhow!("Invalid Mojang API response: {}", e))?;
+
+        let uuid = normalize_uuid(&raw.id)
+            .ok_or_else(|| anyhow::anyhow!("Invalid UUID from Mojang API: {}", raw.id))?;
+        Ok(PlayerProfile {
+            uuid,
+            name: raw.name,
+        })
+    }
+}
+
+/// Return the hyphenated, lowercase form of `value` if it is a UUID (with or without hyphens).
+pub fn normalize_uuid(value: &str) -> Option<String> {
+    let hex: String = value.chars().filter(|c| *c != '-').collect();
+    let hyphens_valid = value.len() == 32
+        || (value.len() == 36 && [8, 13, 18, 23].iter().all(|&i| value.as_bytes()[i] == b'-'));
+    if hex.len() != 32 || !hyphens_valid || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let hex = hex.to_ascii_lowercase();
+    Some(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_uuid() {
+        assert_eq!(
+            normalize_uuid("069A79F444E94726A5BEFCA90E38AAF5").as_deref(),
+            Some("069a79f4-44e9-4726-a5be-fca90e38aaf5")
+        );
+        assert_eq!(
+            normalize_uuid("069a79f4-44e9-4726-a5be-fca90e38aaf5").as_deref(),
+            Some("069a79f4-44e9-4726-a5be-fca90e38aaf5")
+        );
+        assert_eq!(normalize_uuid("Notch"), None);
+        assert_eq!(normalize_uuid("069a79f444e94726a5befca90e38aaf-"), None);
+    }
+}
@@ -0,0 +1,221 @@
+use crate::mojang::MojangClient;
+use crate::player_list::{self, PlayerEntry};
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const OPS_FILENAME: &str = "ops.json";
+
+/// Permission level given to operators when none is specified, matching the
+/// server's default `op-permission-level`.
+pub const DEFAULT_OP_LEVEL: u8 = 4;
+
+fn default_op_level() -> u8 {
+    DEFAULT_OP_LEVEL
+}
+
+/// An operator in `ops.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpEntry {
+    /// Hyphenated UUID.
+    pub uuid: String,
+    pub name: String,
+    /// Permission level from 1 (bypass spawn protection) to 4 (all commands).
+    #[serde(default = "default_op_level")]
+    pub level: u8,
+    /// Whether the player can join when the server is full.
+    #[serde(default)]
+    pub bypasses_player_limit: bool,
+}
+
+impl PlayerEntry for OpEntry {
+    fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The operators of a server directory, backed by its `ops.json`.
+///
+/// Changes are only written to disk by [`save`](Self::save). To also update a running
+/// server, use [`ServerManager::op`](crate::ServerManager::op) and
+/// [`ServerManager::deop`](crate::ServerManager::deop).
+#[derive(Debug, Clone)]
+pub struct Ops {
+    path: PathBuf,
+    entries: Vec<OpEntry>,
+    client: MojangClient,
+}
+
+impl Ops {
+    /// Load `ops.json` from a server directory. A missing file means no operators.
+    pub fn load(server_dir: &Path) -> Result<Self> {
+        let path = server_dir.join(OPS_FILENAME);
+        let entries = player_list::load_list(&path)?;
+        Ok(Self {
+            path,
+            entries,
+            client: MojangClient::default(),
+        })
+    }
+
+    /// Use a custom Mojang client for name resolution.
+    pub fn with_client(mut self, client: MojangClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Current operators.
+    pub fn list(&self) -> &[OpEntry] {
+        &self.entries
+    }
+
+    /// Look up an operator by name or UUID.
+    pub fn get(&self, name_or_uuid: &str) -> Option<&OpEntry> {
+        self.position(name_or_uuid).map(|index| &self.entries[index])
+    }
+
+    /// Make a player an operator by name or UUID with [`DEFAULT_OP_LEVEL`], resolving
+    /// the other via Mojang's API.
+    ///
+    /// Adding a player that is already an operator returns the existing entry.
+    pub async fn add(&mut self, name_or_uuid: &str) -> Result<OpEntry> {
+        if let Some(entry) = self.get(name_or_uuid) {
+            return Ok(entry.clone());
+        }
+        let profile = self.client.resolve(name_or_uuid).await?;
+        if let Some(entry) = self.get(&profile.uuid) {
+            return Ok(entry.clone());
+        }
+        let entry = OpEntry {
+            uuid: profile.uuid,
+            name: profile.name,
+            level: DEFAULT_OP_LEVEL,
+            bypasses_player_limit: false,
+        };
+        self.entries.push(entry.clone());
+        Ok(entry)
+    }
+
+    /// Change an operator's permission level (clamped to 1–4). Returns `false` if the player is not an operator.
+    pub fn set_level(&mut self, name_or_uuid: &str, level: u8) -> bool {
+        match self.position(name_or_uuid) {
+            Some(index) => {
+                self.entries[index].level = level.clamp(1, 4);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Change whether an operator bypasses the player limit. Returns `false` if the player is not an operator.
+    pub fn set_bypasses_player_limit(&mut self, name_or_uuid: &str, bypass: bool) -> bool {
+        match self.position(name_or_uuid) {
+            Some(index) => {
+                self.entries[index].bypasses_player_limit = bypass;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove an operator by name or UUID, returning the removed entry.
+    pub fn remove(&mut self, name_or_uuid: &str) -> Option<OpEntry> {
+        let index = self.position(name_or_uuid)?;
+        Some(self.entries.remove(index))
+    }
+
+    /// Write the operators back to `ops.json`.
+    pub fn save(&self) -> Result<()> {
+        player_list::save_list(&self.path, &self.entries)
+    }
+
+    fn position(&self, name_or_uuid: &str) -> Option<usize> {
+        player_list::position(&self.entries, name_or_uuid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const NOTCH_UUID: &str = "069a79f4-44e9-4726-a5be-fca90e38aaf5";
+
+    #[test]
+    fn test_read_and_write_ops_json() {
+        let dir = temp_dir("ops-round-trip");
+        std::fs::write(
+            dir.join(OPS_FILENAME),
+            format!(
+                r#"[{{"uuid": "{}", "name": "Notch", "level": 2, "bypassesPlayerLimit": true}}]"#,
+                NOTCH_UUID
+            ),
+        )
+        .unwrap();
+
+        let mut ops = Ops::load(&dir).unwrap();
+        let notch = ops.get("Notch").unwrap();
+        assert_eq!(notch.level, 2);
+        assert!(notch.bypasses_player_limit);
+
+        assert!(ops.set_level("notch", 9));
+        ops.save().unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join(OPS_FILENAME)).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "uuid": NOTCH_UUID,
+                "name": "Notch",
+                "level": 4,
+                "bypassesPlayerLimit": true
+            }])
+        );
+
+        assert!(ops.remove(NOTCH_UUID).is_some());
+        ops.save().unwrap();
+        assert!(Ops::load(&dir).unwrap().list().is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_level_defaults_when_missing() {
+        let entries: Vec<OpEntry> =
+            serde_json::from_str(&format!(r#"[{{"uuid": "{}", "name": "Notch"}}]"#, NOTCH_UUID)).unwrap();
+        assert_eq!(entries[0].level, DEFAULT_OP_LEVEL);
+        assert!(!entries[0].bypasses_player_limit);
+    }
+
+    #[tokio::test]
+    async fn test_add_resolves_name_with_default_level() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/users/profiles/minecraft/Notch"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "069a79f444e94726a5befca90e38aaf5",
+                "name": "Notch"
+            })))
+            .mount(&server)
+            .await;
+        let dir = temp_dir("ops-add");
+
+        let mut ops = Ops::load(&dir)
+            .unwrap()
+            .with_client(MojangClient::with_base_urls(server.uri(), server.uri()));
+        let entry = ops.add("Notch").await.unwrap();
+        assert_eq!(entry.uuid, NOTCH_UUID);
+        assert_eq!(entry.level, DEFAULT_OP_LEVEL);
+        assert!(!entry.bypasses_player_limit);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,239 @@
+use crate::download::{Checksum, ServerJar};
+use crate::error::McServerError;
+use crate::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+const DEFAULT_BASE_URL: &str = "https://fill.papermc.io/v3";
+
+/// Download key for the server JAR in a build's `downloads` map.
+const SERVER_DOWNLOAD: &str = "server:default";
+
+#[derive(Deserialize)]
+struct RawBuild {
+    id: u32,
+    #[serde(default)]
+    channel: String,
+    downloads: HashMap<String, RawDownload>,
+}
+
+#[derive(Deserialize)]
+struct RawDownload {
+    name: String,
+    checksums: RawChecksums,
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct RawChecksums {
+    sha256: String,
+}
+
+/// Client for the PaperMC downloads API.
+#[derive(Debug, Clone)]
+pub struct PaperClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl Default for PaperClient {
+    fn default() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+}
+
+impl PaperClient {
+    /// Create a client pointing at the production PaperMC API.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a client pointing at a custom URL (useful for testing with a mock server).
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// The underlying HTTP client, for downloading the selected build.
+    pub fn http(&self) -> &reqwest::Client {
+        &self.http
+    }
+
+    /// Find the build to install for a Minecraft version.
+    ///
+    /// With `build` set, that exact build is returned. Otherwise the newest stable
+    /// build is chosen, falling back to the newest build of any channel for versions
+    /// that have no stable builds yet.
+    pub async fn find_build(&self, minecraft_version: &str, build: Option<u32>) -> Result<ServerJar> {
+        let url = format!(
+            "{}/projects/paper/versions/{}/builds",
+            self.base_url, minecraft_version
+        );
+        let response = self
+            .http
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to query PaperMC API: {}", e))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(McServerError::VersionNotFound(minecraft_version.to_string()));
+        }
+        let builds: Vec<RawBuild> = response
+            .error_for_status()
+            .map_err(|e| anyhow::anyhow!("PaperMC API error: {}", e))?
+            .json()
+            .await
+            .map_err(|e| anyhow::anyhow!("Invalid PaperMC API response: {}", e))?;
+
+        select_build(builds, minecraft_version, build)
+    }
+}
+
+fn select_build(builds: Vec<RawBuild>, minecraft_version: &str, build: Option<u32>) -> Result<ServerJar> {
+    let selected = match build {
+        Some(id) => builds.into_iter().find(|b| b.id == id).ok_or_else(|| {
+            McServerError::InstallFailed(format!(
+                "Paper build {} not found for Minecraft {}",
+                id, minecraft_version
+            ))
+        })?,
+        None => {
+            let (stable, other): (Vec<_>, Vec<_>) = builds
+                .into_iter()
+                .partition(|b| b.channel.eq_ignore_ascii_case("stable"));
+            let candidates = if stable.is_empty() { other } else { stable };
+            candidates
+                .into_iter()
+                .max_by_key(|b| b.id)
+                .ok_or_else(|| McServerError::NoServerDownload(minecraft_version.to_string()))?
+        }
+    };
+
+    let id = selected.id;
+    let download = selected
+        .downloads
+        .into_iter()
+        .find(|(key, _)| key == SERVER_DOWNLOAD)
+        .map(|(_, download)| download)
+        .ok_or_else(|| McServerError::NoServerDownload(minecraft_version.to_string()))?;
+    Ok(ServerJar {
+        build: id,
+        file_name: download.name,
+        url: download.url,
+        checksum: Checksum::Sha256(download.checksums.sha256),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Trimmed response of `GET /v3/projects/paper/versions/1.21.4/builds`.
+    const BUILDS_JSON: &str = r#"[
+        {
+            "id": 233,
+            "time": "2025-06-10T12:00:00Z",
+            "channel": "BETA",
+            "commits": [],
+            "downloads": {
+                "server:default": {
+                    "name": "paper-1.21.4-233.jar",
+                    "checksums": {"sha256": "b2c1"},
+                    "size": 51234567,
+                    "url": "https://fill-data.papermc.io/v1/objects/b2c1/paper-1.21.4-233.jar"
+                }
+            }
+        },
+        {
+            "id": 232,
+            "time": "2025-06-01T12:00:00Z",
+            "channel": "STABLE",
+            "commits": [],
+            "downloads": {
+                "server:default": {
+                    "name": "paper-1.21.4-232.jar",
+                    "checksums": {"sha256": "a1b2"},
+                    "size": 51234000,
+                    "url": "https://fill-data.papermc.io/v1/objects/a1b2/paper-1.21.4-232.jar"
+                }
+            }
+        },
+        {
+            "id": 231,
+            "time": "2025-05-20T12:00:00Z",
+            "channel": "STABLE",
+            "commits": [],
+            "downloads": {
+                "server:default": {
+                    "name": "paper-1.21.4-231.jar",
+                    "checksums": {"sha256": "9f8e"},
+                    "size": 51233000,
+                    "url": "https://fill-data.papermc.io/v1/objects/9f8e/paper-1.21.4-231.jar"
+                }
+            }
+        }
+    ]"#;
+
+    async fn mock_paper() -> (MockServer, PaperClient) {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/projects/paper/versions/1.21.4/builds"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(BUILDS_JSON, "application/json"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/projects/paper/versions/0.0.1/builds"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        let client = PaperClient::with_base_url(server.uri());
+        (server, client)
+    }
+
+    #[tokio::test]
+    async fn test_latest_stable_build_is_chosen() {
+        let (_server, client) = mock_paper().await;
+        let build = client.find_build("1.21.4", None).await.unwrap();
+        assert_eq!(build.build, 232);
+        assert_eq!(build.file_name, "paper-1.21.4-232.jar");
+        assert_eq!(
+            build.url,
+            "https://fill-data.papermc.io/v1/objects/a1b2/paper-1.21.4-232.jar"
+        );
+        assert_eq!(build.checksum, Checksum::Sha256("a1b2".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_specific_build_is_chosen() {
+        let (_server, client) = mock_paper().await;
+        let build = client.find_build("1.21.4", Some(231)).await.unwrap();
+        assert_eq!(
+            build.url,
+            "https://fill-data.papermc.io/v1/objects/9f8e/paper-1.21.4-231.jar"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_version_and_build() {
+        let (_server, client) = mock_paper().await;
+        assert!(matches!(
+            client.find_build("0.0.1", None).await,
+            Err(McServerError::VersionNotFound(_))
+        ));
+        assert!(matches!(
+            client.find_build("1.21.4", Some(1)).await,
+            Err(McServerError::InstallFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_prerelease_versions_fall_back_to_newest_build() {
+        let builds: Vec<RawBuild> = serde_json::from_str(BUILDS_JSON).unwrap();
+        let builds = builds.into_iter().filter(|b| b.channel == "BETA").collect();
+        assert_eq!(select_build(builds, "1.21.4", None).unwrap().build, 233);
+    }
+}
@@ -0,0 +1,291 @@
+use crate::error::McServerError;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Protocol version sent in the handshake. `-1` asks the server to report its own version.
+const HANDSHAKE_PROTOCOL_VERSION: i32 = -1;
+
+/// Upper bound for a status response packet, to avoid allocating on garbage lengths.
+const MAX_PACKET_LENGTH: i32 = 1024 * 1024;
+
+/// Default time allowed for connecting and reading the status response.
+pub const DEFAULT_PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Server version reported in a status response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusVersion {
+    /// Version name (e.g. "1.21.4" or "Paper 1.21.4").
+    pub name: String,
+    /// Protocol number (e.g. 769).
+    pub protocol: i32,
+}
+
+/// A player listed in the status response sample.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusPlayer {
+    pub name: String,
+    pub id: String,
+}
+
+/// Player counts reported in a status response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusPlayers {
+    pub online: u32,
+    pub max: u32,
+    /// A subset of the online players, if the server shares it.
+    #[serde(default)]
+    pub sample: Vec<StatusPlayer>,
+}
+
+/// Parsed Server List Ping response.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServerStatusResponse {
+    /// Message of the day with formatting removed.
+    pub motd: String,
+    pub version: StatusVersion,
+    pub players: StatusPlayers,
+}
+
+/// Raw status JSON as sent by the server.
+#[derive(Deserialize)]
+struct RawStatusResponse {
+    version: StatusVersion,
+    players: StatusPlayers,
+    #[serde(default)]
+    description: serde_json::Value,
+}
+
+impl ServerStatusResponse {
+    /// Parse the JSON payload of a status response.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let raw: RawStatusResponse = serde_json::from_str(json)
+            .map_err(|e| McServerError::Other(anyhow::anyhow!("Invalid status response: {}", e)))?;
+        let mut motd = String::new();
+        flatten_text(&raw.description, &mut motd);
+        Ok(Self {
+            motd,
+            version: raw.version,
+            players: raw.players,
+        })
+    }
+}
+
+/// Flatten a chat component (plain string, object with `text`/`extra`, or array) into plain text.
+fn flatten_text(component: &serde_json::Value, out: &mut String) {
+    match component {
+        serde_json::Value::String(text) => out.push_str(text),
+        serde_json::Value::Array(parts) => parts.iter().for_each(|part| flatten_text(part, out)),
+        serde_json::Value::Object(object) => {
+            if let Some(text) = object.get("text") {
+                flatten_text(text, out);
+            }
+            if let Some(extra) = object.get("extra") {
+                flatten_text(extra, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Query a server's status using the Server List Ping protocol.
+///
+/// Returns [`McServerError::ServerNotReady`] if the server is not accepting
+/// connections yet (e.g. it is still starting up).
+pub async fn ping(host: &str, port: u16) -> Result<ServerStatusResponse> {
+    ping_with_timeout(host, port, DEFAULT_PING_TIMEOUT).await
+}
+
+/// Query a server's status, failing if the exchange takes longer than `timeout`.
+pub async fn ping_with_timeout(
+    host: &str,
+    port: u16,
+    timeout: Duration,
+) -> Result<ServerStatusResponse> {
+    tokio::time::timeout(timeout, exchange(host, port))
+        .await
+        .map_err(|_| {
+            McServerError::Io(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Server list ping to {}:{} timed out", host, port),
+            ))
+        })?
+}
+
+async fn exchange(host: &str, port: u16) -> Result<ServerStatusResponse> {
+    let mut stream = match TcpStream::connect((host, port)).await {
+        Ok(stream) => stream,
+        Err(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => {
+            return Err(McServerError::ServerNotReady);
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    // Handshake: protocol version, server address, port, next state (1 = status)
+    let mut handshake = Vec::new();
+    write_varint(&mut handshake, 0x00);
+    write_varint(&mut handshake, HANDSHAKE_PROTOCOL_VERSION);
+    write_string(&mut handshake, host);
+    handshake.extend_from_slice(&port.to_be_bytes());
+    write_varint(&mut handshake, 1);
+    write_packet(&mut stream, &handshake).await?;
+
+    // Status request has no payload
+    write_packet(&mut stream, &[0x00]).await?;
+
+    let length = read_varint(&mut stream).await?;
+    if !(1..=MAX_PACKET_LENGTH).contains(&length) {
+        return Err(protocol_error(format!("invalid packet length {}", length)));
+    }
+    let mut packet = vec![0u8; length as usize];
+    stream.read_exact(&mut packet).await?;
+
+    let mut cursor = packet.as_slice();
+    let packet_id = read_varint(&mut cursor).await?;
+    if packet_id != 0x00 {
+        return Err(protocol_error(format!("unexpected packet id {:#04x}", packet_id)));
+    }
+    let json_length = read_varint(&mut cursor).await?;
+    if json_length < 0 || json_length as usize > cursor.len() {
+        return Err(protocol_error(format!("invalid string length {}", json_length)));
+    }
+    let json = std::str::from_utf8(&cursor[..json_length as usize])
+        .map_err(|e| protocol_error(e.to_string()))?;
+
+    ServerStatusResponse::from_json(json)
+}
+
+fn protocol_error(message: String) -> McServerError {
+    McServerError::Other(anyhow::anyhow!("Server list ping failed: {}", message))
+}
+
+async fn write_packet(stream: &mut TcpStream, payload: &[u8]) -> Result<()> {
+    let mut packet = Vec::with_capacity(payload.len() + 5);
+    write_varint(&mut packet, payload.len() as i32);
+    packet.extend_from_slice(payload);
+    stream.write_all(&packet).await?;
+    Ok(())
+}
+
+fn write_varint(buf: &mut Vec<u8>, value: i32) {
+    let mut value = value as u32;
+    loop {
+        if value & !0x7F == 0 {
+            buf.push(value as u8);
+            return;
+        }
+        buf.push((value & 0x7F | 0x80) as u8);
+        value >>= 7;
+    }
+}
+
+fn write_string(buf: &mut Vec<u8>, value: &str) {
+    write_varint(buf, value.len() as i32);
+    buf.extend_from_slice(value.as_bytes());
+}
+
+async fn read_varint<R: AsyncReadExt + Unpin>(reader: &mut R) -> Result<i32> {
+    let mut value: u32 = 0;
+    for position in 0..5 {
+        let byte = reader.read_u8().await?;
+        value |= ((byte & 0x7F) as u32) << (7 * position);
+        if byte & 0x80 == 0 {
+            return Ok(value as i32);
+        }
+    }
+    Err(protocol_error("VarInt is too long".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    const STATUS_JSON: &str = r#"{
+        "version": {"name": "1.21.4", "protocol": 769},
+        "players": {"max": 20, "online": 2, "sample": [{"name": "Steve", "id": "8667ba71-b85a-4004-af54-457a9734eed7"}]},
+        "description": {"text": "A ", "extra": [{"text": "Minecraft", "bold": true}, " Server"]}
+    }"#;
+
+    /// Accept a single connection, consume the handshake and status request, and reply with `json`.
+    async fn mock_server(json: &'static str) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            for _ in 0..2 {
+                let length = read_varint(&mut stream).await.unwrap();
+                let mut packet = vec![0u8; length as usize];
+                stream.read_exact(&mut packet).await.unwrap();
+            }
+            let mut payload = Vec::new();
+            write_varint(&mut payload, 0x00);
+            write_string(&mut payload, json);
+            write_packet(&mut stream, &payload).await.unwrap();
+        });
+        port
+    }
+
+    // ── VarInt tests ───────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn test_varint_round_trip() {
+        for value in [0, 1, 127, 128, 255, 25565, 2097151, i32::MAX, -1] {
+            let mut buf = Vec::new();
+            write_varint(&mut buf, value);
+            let mut cursor = buf.as_slice();
+            assert_eq!(read_varint(&mut cursor).await.unwrap(), value);
+            assert!(cursor.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_varint_encoding() {
+        let mut buf = Vec::new();
+        write_varint(&mut buf, 25565);
+        assert_eq!(buf, vec![0xDD, 0xC7, 0x01]);
+    }
+
+    // ── Response parsing tests ─────────────────────────────────────
+
+    #[test]
+    fn test_parse_chat_component_motd() {
+        let status = ServerStatusResponse::from_json(STATUS_JSON).unwrap();
+        assert_eq!(status.motd, "A Minecraft Server");
+        assert_eq!(status.version.name, "1.21.4");
+        assert_eq!(status.version.protocol, 769);
+        assert_eq!(status.players.online, 2);
+        assert_eq!(status.players.max, 20);
+        assert_eq!(status.players.sample[0].name, "Steve");
+    }
+
+    #[test]
+    fn test_parse_plain_string_motd_without_sample() {
+        let json = r#"{"version":{"name":"1.8.9","protocol":47},"players":{"max":10,"online":0},"description":"Hello"}"#;
+        let status = ServerStatusResponse::from_json(json).unwrap();
+        assert_eq!(status.motd, "Hello");
+        assert!(status.players.sample.is_empty());
+    }
+
+    // ── Network tests ──────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn test_ping_mock_server() {
+        let port = mock_server(STATUS_JSON).await;
+        let status = ping("127.0.0.1", port).await.unwrap();
+        assert_eq!(status.motd, "A Minecraft Server");
+        assert_eq!(status.players.online, 2);
+    }
+
+    #[tokio::test]
+    async fn test_ping_connection_refused_is_not_ready() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let result = ping("127.0.0.1", port).await;
+        assert!(matches!(result, Err(McServerError::ServerNotReady)));
+    }
+}
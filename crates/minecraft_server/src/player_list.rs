@@ -0,0 +1,76 @@
+//! Shared handling of the server's JSON player lists (`whitelist.json`, `ops.json`,
+//! `banned-players.json` and `banned-ips.json`).
+
+use crate::mojang::normalize_uuid;
+use crate::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::Path;
+
+/// An entry in a player list that identifies a player by UUID and name.
+pub(crate) trait PlayerEntry {
+    fn uuid(&self) -> &str;
+    fn name(&self) -> &str;
+}
+
+/// Read a JSON list from `path`. A missing file is an empty list.
+pub(crate) fn load_list<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let json = std::fs::read_to_string(path)?;
+    let list = serde_json::from_str(&json)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))?;
+    Ok(list)
+}
+
+/// Write a list to `path` as pretty-printed JSON, as the server does.
+pub(crate) fn save_list<T: Serialize>(path: &Path, list: &[T]) -> Result<()> {
+    let json = serde_json::to_string_pretty(list).map_err(|e| crate::McServerError::Other(e.into()))?;
+    std::fs::write(path, json)?;
+    Ok(())
+}
+
+/// Find a player by UUID (with or without hyphens) or, failing that, by case-insensitive name.
+pub(crate) fn position<T: PlayerEntry>(entries: &[T], name_or_uuid: &str) -> Option<usize> {
+    match normalize_uuid(name_or_uuid) {
+        Some(uuid) => entries.iter().position(|e| e.uuid().eq_ignore_ascii_case(&uuid)),
+        None => entries
+            .iter()
+            .position(|e| e.name().eq_ignore_ascii_case(name_or_uuid)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use crate::WhitelistEntry;
+
+    const NOTCH_UUID: &str = "069a79f4-44e9-4726-a5be-fca90e38aaf5";
+
+    #[test]
+    fn test_position_matches_name_or_uuid() {
+        let entries = vec![WhitelistEntry {
+            uuid: NOTCH_UUID.to_string(),
+            name: "Notch".to_string(),
+        }];
+
+        assert_eq!(position(&entries, "notch"), Some(0));
+        assert_eq!(position(&entries, "069a79f444e94726a5befca90e38aaf5"), Some(0));
+        assert_eq!(position(&entries, &NOTCH_UUID.to_uppercase()), Some(0));
+        assert_eq!(position(&entries, "jeb_"), None);
+    }
+
+    #[test]
+    fn test_missing_list_is_empty() {
+        let dir = temp_dir("player-list-missing");
+        let path = dir.join("whitelist.json");
+
+        assert!(load_list::<WhitelistEntry>(&path).unwrap().is_empty());
+        std::fs::write(&path, "not json").unwrap();
+        assert!(load_list::<WhitelistEntry>(&path).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,18 +1,46 @@
+use crate::console::{parse_console_line, ConsoleHistory};
 use crate::error::McServerError;
 use crate::events::{ServerEvent, ServerEventHandler};
+use crate::jvm;
 use crate::models::{ServerConfig, ServerStatus};
 use crate::Result;
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::mpsc;
+use regex::Regex;
+use std::path::Path;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::{broadcast, mpsc, watch};
 use tokio_interactive::AsynchronousInteractiveProcess;
 
 #[cfg(feature = "logging")]
 use log::debug;
 
+/// How long the console must stay quiet after a command's output before
+/// [`ServerProcess::send_command_response`] treats the response as complete.
+pub(crate) const RESPONSE_QUIET_PERIOD: Duration = Duration::from_millis(250);
+
+/// The startup time the server reports on its readiness line: `Done (12.345s)! For help, type "help"`
+static DONE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"Done \(([\d.]+)s\)!").unwrap());
+
+/// How a server process ended after being asked to stop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopOutcome {
+    /// The server shut down on its own after the "stop" command.
+    Graceful,
+    /// The server did not exit within the stop timeout and was killed.
+    Killed,
+}
+
 /// A running Minecraft server process.
+#[derive(Clone)]
 pub struct ServerProcess {
     pid: u32,
+    exit_rx: watch::Receiver<Option<i32>>,
+    /// Console lines read after startup. Cleared when the process ends so subscribers see the end of output.
+    output_tx: Arc<Mutex<Option<broadcast::Sender<String>>>>,
+    /// Recent console lines. Locked before `output_tx` so history and live output never overlap.
+    history: Arc<Mutex<ConsoleHistory>>,
+    /// How long the server took to become ready, or `None` if it never did.
+    startup_time: Option<Duration>,
 }
 
 impl ServerProcess {
@@ -24,35 +52,43 @@ impl ServerProcess {
         config: &ServerConfig,
         handler: Arc<H>,
     ) -> Result<Self> {
-        let directory_path = config.directory.canonicalize()?;
-
-        // Build the process
-        let mut process_builder = AsynchronousInteractiveProcess::new(&config.java_executable);
-
-        // Add memory arguments
-        process_builder = process_builder
-            .with_argument(format!("-Xmx{}G", config.max_memory_gb))
-            .with_argument(format!("-Xms{}G", config.min_memory_gb));
+        let history = Arc::new(Mutex::new(ConsoleHistory::new(config.console_history_lines)));
+        Self::start_with_history(config, handler, history).await
+    }
 
-        // Add extra Java arguments
-        if !config.java_args.trim().is_empty() {
-            for arg in config.java_args.split_whitespace() {
-                process_builder = process_builder.with_argument(arg);
-            }
-        }
+    /// Start a new Minecraft server process, recording its console output in `history`.
+    ///
+    /// Passing the same history to successive processes keeps output across restarts.
+    pub async fn start_with_history<H: ServerEventHandler>(
+        config: &ServerConfig,
+        handler: Arc<H>,
+        history: Arc<Mutex<ConsoleHistory>>,
+    ) -> Result<Self> {
+        config.validate()?;
+        Self::spawn(
+            &config.java_executable,
+            launch_arguments(config),
+            &config.directory,
+            handler,
+            history,
+        )
+        .await
+    }
 
-        // Add -jar and server JAR
-        if !config.server_jar.is_empty() {
-            process_builder = process_builder
-                .with_argument("-jar")
-                .with_argument(&config.server_jar);
-        }
+    /// Spawn `program` with `args` in `directory` and wait for the server to finish starting.
+    pub(crate) async fn spawn<H: ServerEventHandler>(
+        program: &str,
+        args: Vec<String>,
+        directory: &Path,
+        handler: Arc<H>,
+        history: Arc<Mutex<ConsoleHistory>>,
+    ) -> Result<Self> {
+        let directory_path = directory.canonicalize()?;
 
-        // Add extra Minecraft arguments
-        if !config.minecraft_args.trim().is_empty() {
-            for arg in config.minecraft_args.split_whitespace() {
-                process_builder = process_builder.with_argument(arg);
-            }
+        // Build the process
+        let mut process_builder = AsynchronousInteractiveProcess::new(program);
+        for arg in args {
+            process_builder = process_builder.with_argument(arg);
         }
 
         // Emit starting status
@@ -64,17 +100,27 @@ impl ServerProcess {
 
         // Set up exit callback
         let exit_handler = handler.clone();
+        let (exit_tx, exit_rx) = watch::channel(None);
+        let crash_dir = directory_path.clone();
+        let started_at = SystemTime::now();
         let pid = process_builder
             .with_working_directory(&directory_path)
             .process_exit_callback(move |exit_code| {
+                let _ = exit_tx.send(Some(exit_code));
                 let handler = exit_handler.clone();
+                let crash_dir = crash_dir.clone();
                 tokio::spawn(async move {
                     #[cfg(feature = "logging")]
                     debug!("Server exited with code {}", exit_code);
 
                     if exit_code != 0 {
+                        let report = crate::crash::find_crash_report(&crash_dir, started_at);
                         handler
-                            .on_event(ServerEvent::Crashed { exit_code })
+                            .on_event(ServerEvent::Crashed {
+                                exit_code,
+                                report_path: report.as_ref().map(|r| r.path.clone()),
+                                summary: report.and_then(|r| r.summary),
+                            })
                             .await;
                         handler
                             .on_event(ServerEvent::StatusChanged {
@@ -103,46 +149,79 @@ impl ServerProcess {
         let mut process = AsynchronousInteractiveProcess::get_process_by_pid(pid)
             .await
             .ok_or(McServerError::ProcessNotFound)?;
+        let mut ended = false;
+        let mut startup_time = None;
+        let spawned_at = Instant::now();
 
         loop {
             let line = process
                 .receive_output()
                 .await
                 .map_err(McServerError::Other)?;
-            if let Some(line) = line {
-                // Emit console output
+            // The process ended before finishing startup; the exit callback reports it
+            let Some(line) = line else {
+                ended = true;
+                break;
+            };
+
+            // Emit console output
+            history.lock().unwrap().push(line.clone());
+            startup_handler
+                .on_event(ServerEvent::ConsoleOutput { line: line.clone() })
+                .await;
+
+            // Detect successful startup
+            if line.contains("Done (") && line.contains(r#")! For help, type "help""#) {
+                let elapsed = parse_startup_time(&line).unwrap_or_else(|| spawned_at.elapsed());
+                startup_time = Some(elapsed);
                 startup_handler
-                    .on_event(ServerEvent::ConsoleOutput { line: line.clone() })
+                    .on_event(ServerEvent::StatusChanged {
+                        status: ServerStatus::Running,
+                    })
                     .await;
+                startup_handler.on_event(ServerEvent::Started).await;
+                startup_handler
+                    .on_event(ServerEvent::Ready {
+                        startup_time: elapsed,
+                    })
+                    .await;
+                break;
+            }
 
-                // Detect successful startup
-                if line.contains("Done (") && line.contains(r#")! For help, type "help""#) {
-                    startup_handler
-                        .on_event(ServerEvent::StatusChanged {
-                            status: ServerStatus::Running,
-                        })
-                        .await;
-                    startup_handler.on_event(ServerEvent::Started).await;
-                    break;
-                }
-
-                // Detect Java version mismatch
-                if line.contains("has been compiled by a more recent version of the Java Runtime")
-                {
-                    startup_handler
-                        .on_event(ServerEvent::JavaVersionError)
-                        .await;
-                    startup_handler
-                        .on_event(ServerEvent::StatusChanged {
-                            status: ServerStatus::Crashed,
-                        })
-                        .await;
-                    break;
-                }
+            // Detect Java version mismatch
+            if line.contains("has been compiled by a more recent version of the Java Runtime")
+            {
+                startup_handler
+                    .on_event(ServerEvent::JavaVersionError)
+                    .await;
+                startup_handler
+                    .on_event(ServerEvent::StatusChanged {
+                        status: ServerStatus::Crashed,
+                    })
+                    .await;
+                break;
             }
         }
 
-        Ok(Self { pid })
+        let output_tx = Arc::new(Mutex::new(None));
+        if !ended {
+            let (tx, _) = broadcast::channel(256);
+            *output_tx.lock().unwrap() = Some(tx);
+            tokio::spawn(monitor_output(
+                process,
+                handler,
+                output_tx.clone(),
+                history.clone(),
+            ));
+        }
+
+        Ok(Self {
+            pid,
+            exit_rx,
+            output_tx,
+            history,
+            startup_time,
+        })
     }
 
     /// Get the PID of the running server process.
@@ -150,6 +229,23 @@ impl ServerProcess {
         self.pid
     }
 
+    /// How long the server took to log its readiness line, as reported by the server
+    /// itself when possible. `None` if the process exited or failed before becoming ready.
+    pub fn startup_time(&self) -> Option<Duration> {
+        self.startup_time
+    }
+
+    /// Wait for the process to exit, returning its exit code.
+    /// Returns `None` if the exit could not be observed.
+    pub async fn wait_for_exit(&self) -> Option<i32> {
+        let mut exit_rx = self.exit_rx.clone();
+        exit_rx
+            .wait_for(|exit_code| exit_code.is_some())
+            .await
+            .ok()
+            .and_then(|exit_code| *exit_code)
+    }
+
     /// Send a command to the server's stdin.
     pub async fn send_command(&self, command: &str) -> Result<()> {
         let process = AsynchronousInteractiveProcess::get_process_by_pid(self.pid)
@@ -162,11 +258,86 @@ impl ServerProcess {
         Ok(())
     }
 
+    /// Send a command and collect the console lines that follow it, until `done` returns
+    /// `true` for the lines collected so far, `timeout` elapses, or the process ends.
+    pub(crate) async fn send_command_collect(
+        &self,
+        command: &str,
+        timeout: Duration,
+        mut done: impl FnMut(&[String]) -> bool,
+    ) -> Result<Vec<String>> {
+        // Subscribe first so no line written in response is missed
+        let mut output = self.subscribe_output().await?;
+        self.send_command(command).await?;
+
+        let mut lines = Vec::new();
+        let deadline = tokio::time::Instant::now() + timeout;
+        while let Ok(Some(line)) = tokio::time::timeout_at(deadline, output.recv()).await {
+            lines.push(line);
+            if done(&lines) {
+                break;
+            }
+        }
+        Ok(lines)
+    }
+
+    /// Send a command and collect the console lines that follow it as its response.
+    ///
+    /// Collection ends when a line matches `until`, or, without a pattern, once the console
+    /// has been quiet for [`RESPONSE_QUIET_PERIOD`] after the first line. Returns
+    /// [`McServerError::Timeout`] if nothing (or nothing matching) arrives within `timeout`.
+    pub(crate) async fn send_command_response(
+        &self,
+        command: &str,
+        timeout: Duration,
+        until: Option<&Regex>,
+    ) -> Result<Vec<String>> {
+        let mut output = self.subscribe_output().await?;
+        self.send_command(command).await?;
+
+        let mut lines = Vec::new();
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let wait = match (until, lines.is_empty()) {
+                (None, false) => deadline.min(tokio::time::Instant::now() + RESPONSE_QUIET_PERIOD),
+                _ => deadline,
+            };
+            let Ok(Some(line)) = tokio::time::timeout_at(wait, output.recv()).await else {
+                break;
+            };
+            let matched = until.is_some_and(|pattern| pattern.is_match(&line));
+            lines.push(line);
+            if matched {
+                return Ok(lines);
+            }
+        }
+
+        if lines.is_empty() || until.is_some() {
+            return Err(McServerError::Timeout(format!("no response to '{}'", command)));
+        }
+        Ok(lines)
+    }
+
     /// Send the "stop" command to gracefully shut down the server.
     pub async fn stop(&self) -> Result<()> {
         self.send_command("stop").await
     }
 
+    /// Send "stop" and wait up to `timeout` for the server to exit, killing it if it does not.
+    pub async fn stop_with_timeout(&self, timeout: Duration) -> Result<StopOutcome> {
+        self.stop().await?;
+        if tokio::time::timeout(timeout, self.wait_for_exit()).await.is_ok() {
+            return Ok(StopOutcome::Graceful);
+        }
+
+        #[cfg(feature = "logging")]
+        debug!("Server did not stop within {:?}, killing PID {}", timeout, self.pid);
+
+        self.kill().await?;
+        self.wait_for_exit().await;
+        Ok(StopOutcome::Killed)
+    }
+
     /// Force kill the server process.
     pub async fn kill(&self) -> Result<()> {
         let process = AsynchronousInteractiveProcess::get_process_by_pid(self.pid)
@@ -188,47 +359,273 @@ impl ServerProcess {
         }
     }
 
-    /// Subscribe to console output. Returns a receiver that yields output lines.
+    /// Subscribe to console output. Returns a receiver that yields output lines
+    /// and closes when the process ends.
     pub async fn subscribe_output(&self) -> Result<mpsc::Receiver<String>> {
+        let (_, rx) = self.subscribe_output_with_history().await?;
+        Ok(rx)
+    }
+
+    /// Get the recent console history together with a subscription to the lines that follow it,
+    /// so a viewer can show past output without missing or repeating lines.
+    pub async fn subscribe_output_with_history(
+        &self,
+    ) -> Result<(Vec<String>, mpsc::Receiver<String>)> {
         let (tx, rx) = mpsc::channel(256);
-        let pid = self.pid;
+        let (lines, output_rx) = {
+            let history = self.history.lock().unwrap();
+            let output_rx = self.output_tx.lock().unwrap().as_ref().map(|tx| tx.subscribe());
+            (history.lines(), output_rx)
+        };
+        let Some(mut output_rx) = output_rx else {
+            return Ok((lines, rx));
+        };
 
         tokio::spawn(async move {
-            let mut process = match AsynchronousInteractiveProcess::get_process_by_pid(pid).await {
-                Some(p) => p,
-                None => return,
-            };
-
             loop {
-                let timeout = tokio::time::sleep(Duration::from_secs(30));
-
-                tokio::select! {
-                    line_result = process.receive_output() => {
-                        match line_result {
-                            Ok(Some(line)) => {
-                                if tx.send(line).await.is_err() {
-                                    // Receiver dropped
-                                    break;
-                                }
-                            }
-                            Ok(None) => {
-                                // Process ended
-                                break;
-                            }
-                            Err(_) => {
-                                break;
-                            }
-                        }
-                    }
-                    _ = timeout => {
-                        if tx.is_closed() {
+                match output_rx.recv().await {
+                    Ok(line) => {
+                        if tx.send(line).await.is_err() {
+                            // Receiver dropped
                             break;
                         }
                     }
+                    // Slow subscriber; skip the lines it missed
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    // Process ended
+                    Err(broadcast::error::RecvError::Closed) => break,
                 }
             }
         });
 
-        Ok(rx)
+        Ok((lines, rx))
+    }
+
+    /// Recent console lines, oldest first.
+    pub fn console_history(&self) -> Vec<String> {
+        self.history.lock().unwrap().lines()
+    }
+}
+
+/// Build the Java command line arguments for a server.
+pub(crate) fn launch_arguments(config: &ServerConfig) -> Vec<String> {
+    // Memory arguments
+    let mut args = vec![
+        format!("-Xmx{}M", config.max_memory_mb),
+        format!("-Xms{}M", config.min_memory_mb),
+    ];
+
+    // GC tuning, unless the user chose their own collector
+    if config.aikar_flags && !jvm::selects_garbage_collector(&config.java_args) {
+        args.extend(jvm::aikar_flags(config.max_memory_mb));
+    }
+
+    // Extra Java arguments
+    args.extend(config.java_args.split_whitespace().map(String::from));
+
+    // -jar and server JAR
+    if !config.server_jar.is_empty() {
+        args.push("-jar".to_string());
+        args.push(config.server_jar.clone());
+    }
+
+    // Extra Minecraft arguments
+    args.extend(config.minecraft_args.split_whitespace().map(String::from));
+    args
+}
+
+/// The startup time a readiness line reports, e.g. 12.345s for `Done (12.345s)! For help, type "help"`.
+pub(crate) fn parse_startup_time(line: &str) -> Option<Duration> {
+    let seconds: f64 = DONE.captures(line)?.get(1)?.as_str().parse().ok()?;
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Read console output for the lifetime of the process, emitting gameplay events
+/// and forwarding each line to output subscribers.
+async fn monitor_output<H: ServerEventHandler>(
+    mut process: AsynchronousInteractiveProcess,
+    handler: Arc<H>,
+    output_tx: Arc<Mutex<Option<broadcast::Sender<String>>>>,
+    history: Arc<Mutex<ConsoleHistory>>,
+) {
+    while let Ok(Some(line)) = process.receive_output().await {
+        if let Some(event) = parse_console_line(&line) {
+            handler.on_event(event).await;
+        }
+        let mut recent = history.lock().unwrap();
+        recent.push(line.clone());
+        if let Some(tx) = output_tx.lock().unwrap().as_ref() {
+            // No subscribers is fine
+            let _ = tx.send(line);
+        }
+    }
+    *output_tx.lock().unwrap() = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::NoOpHandler;
+    use crate::test_util::temp_dir;
+
+    /// Start a shell script standing in for a server; it reports startup like Minecraft does.
+    #[cfg(unix)]
+    async fn spawn_dummy(script: &str) -> ServerProcess {
+        let script = format!(r#"echo 'Done (0.1s)! For help, type "help"'; {}"#, script);
+        ServerProcess::spawn(
+            "sh",
+            vec!["-c".to_string(), script],
+            &std::env::temp_dir(),
+            Arc::new(NoOpHandler),
+            Arc::new(Mutex::new(ConsoleHistory::new(100))),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_startup_time() {
+        assert_eq!(
+            parse_startup_time(r#"[12:00:04 INFO]: Done (12.345s)! For help, type "help""#),
+            Some(Duration::from_millis(12345))
+        );
+        assert_eq!(parse_startup_time("[12:00:04 INFO]: Preparing spawn area: 83%"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_console_history_primes_subscribers() {
+        let process = spawn_dummy("echo first; echo second; while read line; do echo \"$line\"; done").await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let (history, mut rx) = process.subscribe_output_with_history().await.unwrap();
+        assert_eq!(
+            history,
+            vec![r#"Done (0.1s)! For help, type "help""#, "first", "second"]
+        );
+
+        process.send_command("third").await.unwrap();
+        assert_eq!(rx.recv().await.as_deref(), Some("third"));
+        assert_eq!(process.console_history().last().map(String::as_str), Some("third"));
+        process.kill().await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stop_is_graceful_when_server_exits() {
+        let process = spawn_dummy(r#"while read line; do [ "$line" = stop ] && exit 0; done"#).await;
+        let outcome = process.stop_with_timeout(Duration::from_secs(5)).await.unwrap();
+        assert_eq!(outcome, StopOutcome::Graceful);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stop_kills_server_ignoring_stop() {
+        let process = spawn_dummy("while read line; do :; done").await;
+        let started = std::time::Instant::now();
+        let outcome = process.stop_with_timeout(Duration::from_millis(500)).await.unwrap();
+        assert_eq!(outcome, StopOutcome::Killed);
+        assert!(started.elapsed() >= Duration::from_millis(500));
+        assert!(!process.is_running().await);
+    }
+
+    /// Records every event it receives.
+    #[derive(Default)]
+    struct RecordingHandler(Mutex<Vec<ServerEvent>>);
+
+    impl ServerEventHandler for RecordingHandler {
+        async fn on_event(&self, event: ServerEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_crash_event_carries_crash_report() {
+        let dir = temp_dir("process-crash");
+        std::fs::create_dir_all(dir.join("crash-reports")).unwrap();
+        let handler = Arc::new(RecordingHandler::default());
+        let script = r#"echo 'Done (0.1s)! For help, type "help"'
+            read line
+            printf 'Description: Exception in server tick loop\n\njava.lang.IllegalStateException: boom\n' > crash-reports/crash-2025-06-01_12.00.00-server.txt
+            exit 1"#;
+        let process = ServerProcess::spawn(
+            "sh",
+            vec!["-c".to_string(), script.to_string()],
+            &dir,
+            handler.clone(),
+            Arc::new(Mutex::new(ConsoleHistory::new(100))),
+        )
+        .await
+        .unwrap();
+
+        process.send_command("crash").await.unwrap();
+        assert_eq!(process.wait_for_exit().await, Some(1));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let crash = handler.0.lock().unwrap().iter().find_map(|event| match event {
+            ServerEvent::Crashed {
+                exit_code,
+                report_path,
+                summary,
+            } => Some((*exit_code, report_path.clone(), summary.clone())),
+            _ => None,
+        });
+        let (exit_code, report_path, summary) = crash.expect("no crash event");
+        assert_eq!(exit_code, 1);
+        assert_eq!(
+            report_path,
+            Some(
+                dir.canonicalize()
+                    .unwrap()
+                    .join("crash-reports/crash-2025-06-01_12.00.00-server.txt")
+            )
+        );
+        assert_eq!(
+            summary.as_deref(),
+            Some("Exception in server tick loop: java.lang.IllegalStateException: boom")
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_launch_arguments_include_heap_flags() {
+        let config = ServerConfig {
+            min_memory_mb: 1536,
+            max_memory_mb: 6144,
+            ..Default::default()
+        };
+        let args = launch_arguments(&config);
+        assert_eq!(&args[..2], ["-Xmx6144M", "-Xms1536M"]);
+    }
+
+    #[test]
+    fn test_launch_arguments_include_aikar_flags() {
+        let config = ServerConfig {
+            max_memory_mb: 4096,
+            ..Default::default()
+        };
+        let args = launch_arguments(&config);
+        assert!(args.contains(&"-XX:+UseG1GC".to_string()));
+        assert!(args.contains(&"-XX:G1HeapRegionSize=8M".to_string()));
+        assert_eq!(&args[args.len() - 2..], ["-jar", "server.jar"]);
+    }
+
+    #[test]
+    fn test_launch_arguments_without_aikar_flags() {
+        let config = ServerConfig::default().with_aikar_flags(false);
+        assert!(!launch_arguments(&config).iter().any(|arg| arg.starts_with("-XX:")));
+    }
+
+    #[test]
+    fn test_custom_gc_disables_aikar_flags() {
+        let config = ServerConfig {
+            java_args: "-XX:+UseZGC".to_string(),
+            ..Default::default()
+        };
+        let args = launch_arguments(&config);
+        assert!(!args.contains(&"-XX:+UseG1GC".to_string()));
+        assert!(args.contains(&"-XX:+UseZGC".to_string()));
     }
 }
@@ -1,24 +1,486 @@
+use crate::error::McServerError;
 use crate::Result;
-use std::path::PathBuf;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
-/// Parsed server.properties file.
-#[derive(Debug, Clone, serde::Deserialize)]
+const PROPERTIES_FILENAME: &str = "server.properties";
+
+const DEFAULT_SERVER_PORT: u16 = 25565;
+const DEFAULT_MAX_PLAYERS: u32 = 20;
+const DEFAULT_LEVEL_NAME: &str = "world";
+const DEFAULT_RCON_PORT: u16 = 25575;
+
+/// Default game mode for new players.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Gamemode {
+    #[default]
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+impl Display for Gamemode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Survival => "survival",
+                Self::Creative => "creative",
+                Self::Adventure => "adventure",
+                Self::Spectator => "spectator",
+            }
+        )
+    }
+}
+
+impl FromStr for Gamemode {
+    type Err = String;
+
+    /// Accepts the names used since 1.14 and the numeric IDs used before.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "survival" | "0" => Ok(Self::Survival),
+            "creative" | "1" => Ok(Self::Creative),
+            "adventure" | "2" => Ok(Self::Adventure),
+            "spectator" | "3" => Ok(Self::Spectator),
+            _ => Err(format!("Unknown gamemode: {}", s)),
+        }
+    }
+}
+
+/// World difficulty.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Difficulty {
+    Peaceful,
+    #[default]
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Peaceful => "peaceful",
+                Self::Easy => "easy",
+                Self::Normal => "normal",
+                Self::Hard => "hard",
+            }
+        )
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    /// Accepts the names used since 1.14 and the numeric IDs used before.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "peaceful" | "0" => Ok(Self::Peaceful),
+            "easy" | "1" => Ok(Self::Easy),
+            "normal" | "2" => Ok(Self::Normal),
+            "hard" | "3" => Ok(Self::Hard),
+            _ => Err(format!("Unknown difficulty: {}", s)),
+        }
+    }
+}
+
+/// A server.properties file.
+///
+/// Lines are kept as written, so saving preserves comments, ordering, and keys this
+/// type knows nothing about; only the lines of properties that were changed are rewritten.
+///
+/// The typed getters fall back to Minecraft's defaults when a key is missing or its
+/// value cannot be parsed, matching how the server itself reads the file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct ServerProperties {
-    #[serde(rename = "server-port")]
-    pub server_port: Option<i64>,
+    lines: Vec<String>,
 }
 
 impl ServerProperties {
     /// Load and parse a server.properties file from the given path.
     pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
         let file_content = std::fs::read_to_string(path.into())?;
-        let properties: Self =
-            serde_ini::from_str(&file_content).map_err(|e| anyhow::anyhow!("Failed to parse server.properties: {}", e))?;
-        Ok(properties)
+        Ok(Self::parse(&file_content))
     }
 
     /// Load server.properties from a server directory.
-    pub fn load_from_dir(server_dir: &std::path::Path) -> Result<Self> {
-        Self::load(server_dir.join("server.properties"))
+    pub fn load_from_dir(server_dir: &Path) -> Result<Self> {
+        Self::load(server_dir.join(PROPERTIES_FILENAME))
+    }
+
+    /// Parse the contents of a server.properties file.
+    pub fn parse(content: &str) -> Self {
+        Self {
+            lines: content.lines().map(str::to_string).collect(),
+        }
+    }
+
+    /// Write the properties to the given path.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        std::fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /// Write server.properties to a server directory.
+    pub fn save_to_dir(&self, server_dir: &Path) -> Result<()> {
+        self.save(server_dir.join(PROPERTIES_FILENAME))
+    }
+
+    /// Raw value of a property, if set.
+    pub fn get(&self, key: &str) -> Option<String> {
+        // Like java.util.Properties, the last occurrence of a key wins
+        self.lines
+            .iter()
+            .rev()
+            .filter_map(|line| parse_line(line))
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Set a property, replacing its existing line or appending a new one.
+    pub fn set(&mut self, key: &str, value: impl Display) {
+        let line = format!("{}={}", escape(key, true), escape(&value.to_string(), false));
+        match self.position(key) {
+            Some(index) => self.lines[index] = line,
+            None => self.lines.push(line),
+        }
+    }
+
+    /// Remove every line setting a property. Returns `true` if it was set.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let before = self.lines.len();
+        self.lines
+            .retain(|line| parse_line(line).is_none_or(|(k, _)| k != key));
+        self.lines.len() != before
+    }
+
+    fn position(&self, key: &str) -> Option<usize> {
+        self.lines
+            .iter()
+            .rposition(|line| parse_line(line).is_some_and(|(k, _)| k == key))
+    }
+
+    fn parsed<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.get(key).and_then(|value| value.trim().parse().ok())
+    }
+
+    /// Port the server listens on (`server-port`, default 25565).
+    pub fn server_port(&self) -> u16 {
+        self.parsed("server-port")
+            .filter(|port| *port != 0)
+            .unwrap_or(DEFAULT_SERVER_PORT)
+    }
+
+    /// Set `server-port`. Port 0 is rejected.
+    pub fn set_server_port(&mut self, port: u16) -> Result<()> {
+        if port == 0 {
+            return Err(McServerError::InvalidConfig(
+                "server-port must be between 1 and 65535".to_string(),
+            ));
+        }
+        self.set("server-port", port);
+        Ok(())
+    }
+
+    /// Default game mode for new players (`gamemode`, default survival).
+    pub fn gamemode(&self) -> Gamemode {
+        self.parsed("gamemode").unwrap_or_default()
+    }
+
+    /// Set `gamemode`.
+    pub fn set_gamemode(&mut self, gamemode: Gamemode) {
+        self.set("gamemode", gamemode);
+    }
+
+    /// World difficulty (`difficulty`, default easy).
+    pub fn difficulty(&self) -> Difficulty {
+        self.parsed("difficulty").unwrap_or_default()
+    }
+
+    /// Set `difficulty`.
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.set("difficulty", difficulty);
+    }
+
+    /// Maximum number of players online at once (`max-players`, default 20).
+    pub fn max_players(&self) -> u32 {
+        self.parsed("max-players").unwrap_or(DEFAULT_MAX_PLAYERS)
+    }
+
+    /// Set `max-players`. Zero is rejected since nobody could join.
+    pub fn set_max_players(&mut self, max_players: u32) -> Result<()> {
+        if max_players == 0 || max_players > i32::MAX as u32 {
+            return Err(McServerError::InvalidConfig(format!(
+                "max-players must be between 1 and {}, got {}",
+                i32::MAX,
+                max_players
+            )));
+        }
+        self.set("max-players", max_players);
+        Ok(())
+    }
+
+    /// Name of the world directory (`level-name`, default "world").
+    pub fn level_name(&self) -> String {
+        self.get("level-name")
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_LEVEL_NAME.to_string())
+    }
+
+    /// Whether players are authenticated with Mojang (`online-mode`, default true).
+    pub fn online_mode(&self) -> bool {
+        self.parsed("online-mode").unwrap_or(true)
+    }
+
+    /// Set `online-mode`.
+    pub fn set_online_mode(&mut self, online_mode: bool) {
+        self.set("online-mode", online_mode);
+    }
+
+    /// Whether the server accepts remote console connections (`enable-rcon`, default false).
+    pub fn rcon_enabled(&self) -> bool {
+        self.parsed("enable-rcon").unwrap_or(false)
+    }
+
+    /// Port the remote console listens on (`rcon.port`, default 25575).
+    pub fn rcon_port(&self) -> u16 {
+        self.parsed("rcon.port")
+            .filter(|port| *port != 0)
+            .unwrap_or(DEFAULT_RCON_PORT)
+    }
+
+    /// Password remote console clients authenticate with (`rcon.password`), if one is set.
+    ///
+    /// The server refuses to start RCON without a password, so an empty value is `None`.
+    pub fn rcon_password(&self) -> Option<String> {
+        self.get("rcon.password").filter(|password| !password.is_empty())
+    }
+}
+
+impl Display for ServerProperties {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Split a `key=value` line into its unescaped key and value.
+/// Returns `None` for blank lines and comments.
+fn parse_line(line: &str) -> Option<(String, String)> {
+    let line = line.trim_start();
+    if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+        return None;
+    }
+
+    // The key ends at the first unescaped '=', ':', or whitespace
+    let mut key_end = line.len();
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '=' || c == ':' || c.is_whitespace() {
+            key_end = i;
+            break;
+        }
+    }
+
+    let rest = line[key_end..].trim_start();
+    let rest = rest
+        .strip_prefix('=')
+        .or_else(|| rest.strip_prefix(':'))
+        .unwrap_or(rest)
+        .trim_start();
+    Some((unescape(&line[..key_end]), unescape(rest)))
+}
+
+fn unescape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('f') => result.push('\u{c}'),
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                if let Some(c) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    result.push(c);
+                }
+            }
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+    result
+}
+
+fn escape(s: &str, is_key: bool) -> String {
+    let mut result = String::with_capacity(s.len());
+    for (i, c) in s.chars().enumerate() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '\t' => result.push_str("\\t"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\u{c}' => result.push_str("\\f"),
+            '=' | ':' | ' ' if is_key => {
+                result.push('\\');
+                result.push(c);
+            }
+            ' ' if i == 0 => result.push_str("\\ "),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    const PROPERTIES: &str = "#Minecraft server properties
+#Sat Jun 01 12:00:00 UTC 2025
+enable-jmx-monitoring=false
+difficulty=normal
+gamemode=creative
+motd=A Minecraft Server
+max-players=42
+online-mode=false
+server-port=25570
+level-seed=
+generator-settings={}
+custom-plugin-key=keep me
+";
+
+    // ── Typed getters ──────────────────────────────────────────────
+
+    #[test]
+    fn test_typed_getters() {
+        let properties = ServerProperties::parse(PROPERTIES);
+        assert_eq!(properties.server_port(), 25570);
+        assert_eq!(properties.gamemode(), Gamemode::Creative);
+        assert_eq!(properties.difficulty(), Difficulty::Normal);
+        assert_eq!(properties.max_players(), 42);
+        assert!(!properties.online_mode());
+        assert_eq!(properties.get("motd").as_deref(), Some("A Minecraft Server"));
+        assert_eq!(properties.get("level-seed").as_deref(), Some(""));
+        assert_eq!(properties.level_name(), "world");
+        assert!(!properties.rcon_enabled());
+        assert_eq!(properties.rcon_port(), 25575);
+        assert_eq!(properties.rcon_password(), None);
+    }
+
+    #[test]
+    fn test_rcon_settings() {
+        let properties =
+            ServerProperties::parse("enable-rcon=true\nrcon.port=25580\nrcon.password=hunter2\n");
+        assert!(properties.rcon_enabled());
+        assert_eq!(properties.rcon_port(), 25580);
+        assert_eq!(properties.rcon_password().as_deref(), Some("hunter2"));
+        assert_eq!(ServerProperties::parse("rcon.password=\n").rcon_password(), None);
+    }
+
+    #[test]
+    fn test_defaults_for_missing_or_invalid_values() {
+        let properties = ServerProperties::parse("server-port=not-a-port\ngamemode=hardcore\n");
+        assert_eq!(properties.server_port(), 25565);
+        assert_eq!(properties.gamemode(), Gamemode::Survival);
+        assert_eq!(properties.difficulty(), Difficulty::Easy);
+        assert_eq!(properties.max_players(), 20);
+        assert!(properties.online_mode());
+    }
+
+    #[test]
+    fn test_legacy_numeric_gamemode_and_difficulty() {
+        let properties = ServerProperties::parse("gamemode=2\ndifficulty=3\n");
+        assert_eq!(properties.gamemode(), Gamemode::Adventure);
+        assert_eq!(properties.difficulty(), Difficulty::Hard);
+    }
+
+    #[test]
+    fn test_escaped_values() {
+        let properties = ServerProperties::parse("motd=\\u00a7aGreen\\: welcome\nkey\\ with\\ spaces : value\n");
+        assert_eq!(properties.get("motd").as_deref(), Some("\u{a7}aGreen: welcome"));
+        assert_eq!(properties.get("key with spaces").as_deref(), Some("value"));
+    }
+
+    // ── Setters and round trips ────────────────────────────────────
+
+    #[test]
+    fn test_unchanged_file_round_trips_exactly() {
+        assert_eq!(ServerProperties::parse(PROPERTIES).to_string(), PROPERTIES);
+    }
+
+    #[test]
+    fn test_edits_preserve_unknown_keys_and_comments() {
+        let mut properties = ServerProperties::parse(PROPERTIES);
+        properties.set_gamemode(Gamemode::Survival);
+        properties.set_difficulty(Difficulty::Hard);
+        properties.set_server_port(25580).unwrap();
+        properties.set_max_players(10).unwrap();
+        properties.set_online_mode(true);
+        properties.set("view-distance", 12);
+
+        let saved = properties.to_string();
+        let expected = PROPERTIES
+            .replace("difficulty=normal", "difficulty=hard")
+            .replace("gamemode=creative", "gamemode=survival")
+            .replace("max-players=42", "max-players=10")
+            .replace("online-mode=false", "online-mode=true")
+            .replace("server-port=25570", "server-port=25580")
+            + "view-distance=12\n";
+        assert_eq!(saved, expected);
+
+        let reloaded = ServerProperties::parse(&saved);
+        assert_eq!(reloaded.get("custom-plugin-key").as_deref(), Some("keep me"));
+        assert_eq!(reloaded.gamemode(), Gamemode::Survival);
+        assert_eq!(reloaded.server_port(), 25580);
+    }
+
+    #[test]
+    fn test_save_and_load_file() {
+        let dir = temp_dir("properties");
+        let mut properties = ServerProperties::parse(PROPERTIES);
+        properties.set("motd", "Line one\nline two");
+        properties.save_to_dir(&dir).unwrap();
+
+        let loaded = ServerProperties::load_from_dir(&dir).unwrap();
+        assert_eq!(loaded.get("motd").as_deref(), Some("Line one\nline two"));
+        assert_eq!(loaded, properties);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_setters_validate() {
+        let mut properties = ServerProperties::default();
+        assert!(properties.set_server_port(0).is_err());
+        assert!(properties.set_max_players(0).is_err());
+        assert!(properties.get("server-port").is_none());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut properties = ServerProperties::parse(PROPERTIES);
+        assert!(properties.remove("custom-plugin-key"));
+        assert!(!properties.remove("custom-plugin-key"));
+        assert_eq!(properties.get("custom-plugin-key"), None);
     }
 }
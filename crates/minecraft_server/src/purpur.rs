@@ -0,0 +1,204 @@
+use crate::download::{Checksum, ServerJar};
+use crate::error::McServerError;
+use crate::Result;
+use serde::Deserialize;
+
+const DEFAULT_BASE_URL: &str = "https://api.purpurmc.org/v2";
+
+#[derive(Deserialize)]
+struct RawVersion {
+    builds: RawBuilds,
+}
+
+#[derive(Deserialize)]
+struct RawBuilds {
+    latest: String,
+}
+
+#[derive(Deserialize)]
+struct RawBuild {
+    build: String,
+    #[serde(default)]
+    result: String,
+    md5: String,
+}
+
+/// Client for the Purpur downloads API.
+#[derive(Debug, Clone)]
+pub struct PurpurClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl Default for PurpurClient {
+    fn default() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+}
+
+impl PurpurClient {
+    /// Create a client pointing at the production Purpur API.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a client pointing at a custom URL (useful for testing with a mock server).
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// The underlying HTTP client, for downloading the selected build.
+    pub fn http(&self) -> &reqwest::Client {
+        &self.http
+    }
+
+    /// Find the build to install for a Minecraft version: `build` if given, otherwise the latest.
+    pub async fn find_build(&self, minecraft_version: &str, build: Option<u32>) -> Result<ServerJar> {
+        let build = match build {
+            Some(build) => build.to_string(),
+            None => {
+                let version: RawVersion = self
+                    .get_json(&format!("{}/purpur/{}", self.base_url, minecraft_version))
+                    .await?
+                    .ok_or_else(|| McServerError::VersionNotFound(minecraft_version.to_string()))?;
+                version.builds.latest
+            }
+        };
+
+        let raw: RawBuild = self
+            .get_json(&format!(
+                "{}/purpur/{}/{}",
+                self.base_url, minecraft_version, build
+            ))
+            .await?
+            .ok_or_else(|| {
+                McServerError::InstallFailed(format!(
+                    "Purpur build {} not found for Minecraft {}",
+                    build, minecraft_version
+                ))
+            })?;
+        if !raw.result.is_empty() && !raw.result.eq_ignore_ascii_case("success") {
+            return Err(McServerError::InstallFailed(format!(
+                "Purpur build {} for Minecraft {} did not succeed ({})",
+                raw.build, minecraft_version, raw.result
+            )));
+        }
+
+        let build: u32 = raw
+            .build
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid Purpur build number: {}", raw.build))?;
+        Ok(ServerJar {
+            build,
+            file_name: format!("purpur-{}-{}.jar", minecraft_version, build),
+            url: format!(
+                "{}/purpur/{}/{}/download",
+                self.base_url, minecraft_version, build
+            ),
+            checksum: Checksum::Md5(raw.md5),
+        })
+    }
+
+    /// GET a JSON document, returning `None` on 404.
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<Option<T>> {
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to query Purpur API: {}", e))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let value = response
+            .error_for_status()
+            .map_err(|e| anyhow::anyhow!("Purpur API error: {}", e))?
+            .json()
+            .await
+            .map_err(|e| anyhow::anyhow!("Invalid Purpur API response: {}", e))?;
+        Ok(Some(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Trimmed response of `GET /v2/purpur/1.21.4`.
+    const VERSION_JSON: &str = r#"{
+        "project": "purpur",
+        "version": "1.21.4",
+        "builds": {"latest": "2416", "all": ["2414", "2415", "2416"]}
+    }"#;
+
+    /// Trimmed response of `GET /v2/purpur/1.21.4/2416`.
+    const BUILD_JSON: &str = r#"{
+        "project": "purpur",
+        "version": "1.21.4",
+        "build": "2416",
+        "result": "SUCCESS",
+        "timestamp": 1739400000000,
+        "duration": 120000,
+        "commits": [],
+        "md5": "4c8a0ef1d1f3a2b5e6d7c8b9a0f1e2d3"
+    }"#;
+
+    async fn mock_purpur() -> (MockServer, PurpurClient) {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/purpur/1.21.4"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(VERSION_JSON, "application/json"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/purpur/1.21.4/2416"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(BUILD_JSON, "application/json"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/purpur/0.0.1"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        let client = PurpurClient::with_base_url(server.uri());
+        (server, client)
+    }
+
+    #[tokio::test]
+    async fn test_latest_build_is_chosen() {
+        let (server, client) = mock_purpur().await;
+        let jar = client.find_build("1.21.4", None).await.unwrap();
+        assert_eq!(jar.build, 2416);
+        assert_eq!(jar.file_name, "purpur-1.21.4-2416.jar");
+        assert_eq!(jar.url, format!("{}/purpur/1.21.4/2416/download", server.uri()));
+        assert_eq!(
+            jar.checksum,
+            Checksum::Md5("4c8a0ef1d1f3a2b5e6d7c8b9a0f1e2d3".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_specific_build_is_chosen() {
+        let (server, client) = mock_purpur().await;
+        let jar = client.find_build("1.21.4", Some(2416)).await.unwrap();
+        assert_eq!(jar.url, format!("{}/purpur/1.21.4/2416/download", server.uri()));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_version_and_build() {
+        let (_server, client) = mock_purpur().await;
+        assert!(matches!(
+            client.find_build("0.0.1", None).await,
+            Err(McServerError::VersionNotFound(_))
+        ));
+        assert!(matches!(
+            client.find_build("1.21.4", Some(1)).await,
+            Err(McServerError::InstallFailed(_))
+        ));
+    }
+}
@@ -0,0 +1,305 @@
+use crate::error::McServerError;
+use crate::Result;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Default time allowed for connecting and for each exchange with the server.
+pub const DEFAULT_RCON_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest command the server accepts; longer requests are dropped without a response.
+pub const MAX_COMMAND_LENGTH: usize = 1446;
+
+/// Upper bound for a response packet, to avoid allocating on garbage lengths.
+const MAX_PACKET_LENGTH: i32 = 1024 * 1024;
+
+/// Packet types of the Source RCON protocol that Minecraft implements.
+const SERVERDATA_AUTH: i32 = 3;
+const SERVERDATA_AUTH_RESPONSE: i32 = 2;
+const SERVERDATA_EXECCOMMAND: i32 = 2;
+const SERVERDATA_RESPONSE_VALUE: i32 = 0;
+
+/// An authenticated connection to a server's remote console.
+///
+/// Unlike commands written to the server console, a command sent over RCON returns
+/// exactly its own output. The server must have `enable-rcon=true` and a password set
+/// in server.properties, see [`ServerProperties::rcon_port`](crate::ServerProperties::rcon_port).
+pub struct RconClient {
+    stream: TcpStream,
+    /// `host:port`, for error messages.
+    address: String,
+    next_id: i32,
+    timeout: Duration,
+}
+
+#[derive(Debug, PartialEq)]
+struct Packet {
+    id: i32,
+    kind: i32,
+    body: String,
+}
+
+impl RconClient {
+    /// Connect and log in to the remote console at `host:port`.
+    ///
+    /// Returns [`McServerError::ServerNotReady`] if nothing is listening yet and
+    /// [`McServerError::RconAuthFailed`] if the password is wrong.
+    pub async fn connect(host: &str, port: u16, password: &str) -> Result<Self> {
+        Self::connect_with_timeout(host, port, password, DEFAULT_RCON_TIMEOUT).await
+    }
+
+    /// Like [`connect`](Self::connect), failing any exchange that takes longer than `timeout`.
+    pub async fn connect_with_timeout(
+        host: &str,
+        port: u16,
+        password: &str,
+        timeout: Duration,
+    ) -> Result<Self> {
+        let address = format!("{}:{}", host, port);
+        let stream = match with_timeout(timeout, &address, async {
+            Ok(TcpStream::connect((host, port)).await)
+        })
+        .await?
+        {
+            Ok(stream) => stream,
+            Err(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => {
+                return Err(McServerError::ServerNotReady);
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut client = Self {
+            stream,
+            address,
+            next_id: 1,
+            timeout,
+        };
+        let id = client.next_id();
+        with_timeout(timeout, &client.address, async {
+            write_packet(&mut client.stream, id, SERVERDATA_AUTH, password).await?;
+            loop {
+                // Some servers send an empty response value ahead of the auth response
+                let packet = read_packet(&mut client.stream).await?;
+                if packet.kind != SERVERDATA_AUTH_RESPONSE {
+                    continue;
+                }
+                return match packet.id {
+                    -1 => Err(McServerError::RconAuthFailed),
+                    _ => Ok(()),
+                };
+            }
+        })
+        .await?;
+        Ok(client)
+    }
+
+    /// Run a command and return its output.
+    ///
+    /// Output longer than one packet arrives in several; they are joined in order.
+    pub async fn command(&mut self, command: &str) -> Result<String> {
+        if command.len() > MAX_COMMAND_LENGTH {
+            return Err(McServerError::InvalidConfig(format!(
+                "RCON commands are limited to {} bytes, got {}",
+                MAX_COMMAND_LENGTH,
+                command.len()
+            )));
+        }
+
+        let id = self.next_id();
+        // The server answers requests in order, so the reply to a second request marks
+        // the end of the first one's output
+        let end_id = self.next_id();
+        let stream = &mut self.stream;
+        with_timeout(self.timeout, &self.address, async move {
+            write_packet(stream, id, SERVERDATA_EXECCOMMAND, command).await?;
+            write_packet(stream, end_id, SERVERDATA_RESPONSE_VALUE, "").await?;
+
+            let mut output = String::new();
+            loop {
+                let packet = read_packet(stream).await?;
+                if packet.id == end_id {
+                    return Ok(output);
+                }
+                if packet.id == id {
+                    output.push_str(&packet.body);
+                }
+            }
+        })
+        .await
+    }
+
+    fn next_id(&mut self) -> i32 {
+        let id = self.next_id;
+        // -1 is reserved for failed authentication
+        self.next_id = self.next_id.checked_add(1).unwrap_or(1);
+        id
+    }
+}
+
+async fn with_timeout<T>(
+    timeout: Duration,
+    address: &str,
+    future: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::time::timeout(timeout, future)
+        .await
+        .map_err(|_| McServerError::Timeout(format!("RCON connection to {}", address)))?
+}
+
+fn protocol_error(message: String) -> McServerError {
+    McServerError::Other(anyhow::anyhow!("RCON failed: {}", message))
+}
+
+async fn write_packet(stream: &mut TcpStream, id: i32, kind: i32, body: &str) -> Result<()> {
+    stream.write_all(&encode_packet(id, kind, body)).await?;
+    Ok(())
+}
+
+/// Encode a packet: little-endian length, ID, and type, then the body and two NUL bytes.
+fn encode_packet(id: i32, kind: i32, body: &str) -> Vec<u8> {
+    let length = 4 + 4 + body.len() + 2;
+    let mut packet = Vec::with_capacity(4 + length);
+    packet.extend_from_slice(&(length as i32).to_le_bytes());
+    packet.extend_from_slice(&id.to_le_bytes());
+    packet.extend_from_slice(&kind.to_le_bytes());
+    packet.extend_from_slice(body.as_bytes());
+    packet.extend_from_slice(&[0, 0]);
+    packet
+}
+
+async fn read_packet<R: AsyncReadExt + Unpin>(reader: &mut R) -> Result<Packet> {
+    let length = reader.read_i32_le().await?;
+    if !(10..=MAX_PACKET_LENGTH).contains(&length) {
+        return Err(protocol_error(format!("invalid packet length {}", length)));
+    }
+    let mut packet = vec![0u8; length as usize];
+    reader.read_exact(&mut packet).await?;
+
+    let id = i32::from_le_bytes(packet[0..4].try_into().unwrap());
+    let kind = i32::from_le_bytes(packet[4..8].try_into().unwrap());
+    let body = &packet[8..packet.len() - 2];
+    Ok(Packet {
+        id,
+        kind,
+        body: String::from_utf8_lossy(body).into_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    const PASSWORD: &str = "hunter2";
+
+    /// Accept a single connection and answer like a vanilla server: `list` gets a fixed
+    /// reply, `help` a reply split over two packets, and anything else is echoed back.
+    async fn mock_server() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            while let Ok(packet) = read_packet(&mut stream).await {
+                let replies = match packet.kind {
+                    SERVERDATA_AUTH if packet.body == PASSWORD => {
+                        vec![(packet.id, SERVERDATA_AUTH_RESPONSE, String::new())]
+                    }
+                    SERVERDATA_AUTH => vec![(-1, SERVERDATA_AUTH_RESPONSE, String::new())],
+                    SERVERDATA_EXECCOMMAND => match packet.body.as_str() {
+                        "list" => vec![(
+                            packet.id,
+                            SERVERDATA_RESPONSE_VALUE,
+                            "There are 1 of a max of 20 players online: Steve".to_string(),
+                        )],
+                        "help" => vec![
+                            (packet.id, SERVERDATA_RESPONSE_VALUE, "/ban <targets>".to_string()),
+                            (packet.id, SERVERDATA_RESPONSE_VALUE, "/kick <targets>".to_string()),
+                        ],
+                        other => vec![(packet.id, SERVERDATA_RESPONSE_VALUE, other.to_string())],
+                    },
+                    kind => vec![(
+                        packet.id,
+                        SERVERDATA_RESPONSE_VALUE,
+                        format!("Unknown request {:x}", kind),
+                    )],
+                };
+                for (id, kind, body) in replies {
+                    write_packet(&mut stream, id, kind, &body).await.unwrap();
+                }
+            }
+        });
+        port
+    }
+
+    // ── Packet tests ───────────────────────────────────────────────
+
+    #[test]
+    fn test_packet_encoding() {
+        assert_eq!(
+            encode_packet(7, SERVERDATA_EXECCOMMAND, "list"),
+            vec![14, 0, 0, 0, 7, 0, 0, 0, 2, 0, 0, 0, b'l', b'i', b's', b't', 0, 0]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_packet_round_trip() {
+        let encoded = encode_packet(-1, SERVERDATA_AUTH_RESPONSE, "§aGreen");
+        let packet = read_packet(&mut encoded.as_slice()).await.unwrap();
+        assert_eq!(
+            packet,
+            Packet {
+                id: -1,
+                kind: SERVERDATA_AUTH_RESPONSE,
+                body: "§aGreen".to_string()
+            }
+        );
+    }
+
+    // ── Network tests ──────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn test_command_returns_output() {
+        let port = mock_server().await;
+        let mut client = RconClient::connect("127.0.0.1", port, PASSWORD).await.unwrap();
+        assert_eq!(
+            client.command("list").await.unwrap(),
+            "There are 1 of a max of 20 players online: Steve"
+        );
+        assert_eq!(client.command("say hi").await.unwrap(), "say hi");
+    }
+
+    #[tokio::test]
+    async fn test_fragmented_output_is_joined() {
+        let port = mock_server().await;
+        let mut client = RconClient::connect("127.0.0.1", port, PASSWORD).await.unwrap();
+        assert_eq!(client.command("help").await.unwrap(), "/ban <targets>/kick <targets>");
+    }
+
+    #[tokio::test]
+    async fn test_wrong_password_is_rejected() {
+        let port = mock_server().await;
+        let result = RconClient::connect("127.0.0.1", port, "wrong").await;
+        assert!(matches!(result, Err(McServerError::RconAuthFailed)));
+    }
+
+    #[tokio::test]
+    async fn test_long_command_is_rejected() {
+        let port = mock_server().await;
+        let mut client = RconClient::connect("127.0.0.1", port, PASSWORD).await.unwrap();
+        let command = "say ".to_string() + &"a".repeat(MAX_COMMAND_LENGTH);
+        assert!(matches!(
+            client.command(&command).await,
+            Err(McServerError::InvalidConfig(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_connection_refused_is_not_ready() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let result = RconClient::connect("127.0.0.1", port, PASSWORD).await;
+        assert!(matches!(result, Err(McServerError::ServerNotReady)));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::models::{RestartPolicy, ServerConfig};
+use std::time::Duration;
+
+/// A server that stays up this long is considered healthy, resetting the attempt counter.
+pub const STABLE_UPTIME: Duration = Duration::from_secs(5 * 60);
+
+/// Upper bound for the exponential restart backoff.
+pub const MAX_RESTART_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// Outcome of an unexpected process exit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestartDecision {
+    /// The policy does not call for a restart.
+    Stop,
+    /// Restart the server after `delay`.
+    Restart { attempt: u32, delay: Duration },
+    /// The restart budget is exhausted; leave the server stopped.
+    CrashLoop { attempts: u32 },
+}
+
+/// Tracks consecutive automatic restarts for a [`RestartPolicy`], applying
+/// exponential backoff and a cap on attempts to avoid crash loops.
+#[derive(Debug, Clone)]
+pub struct RestartTracker {
+    policy: RestartPolicy,
+    max_attempts: u32,
+    base_delay: Duration,
+    attempts: u32,
+}
+
+impl RestartTracker {
+    /// Create a tracker from the restart settings of a server configuration.
+    pub fn new(config: &ServerConfig) -> Self {
+        Self {
+            policy: config.restart_policy,
+            max_attempts: config.max_restart_attempts,
+            base_delay: Duration::from_secs(config.restart_delay_secs),
+            attempts: 0,
+        }
+    }
+
+    /// Number of consecutive restarts performed so far.
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Decide what to do after the process exited with `exit_code` having run for `uptime`.
+    ///
+    /// Must only be called for exits that were not requested by the user.
+    pub fn on_exit(&mut self, exit_code: i32, uptime: Duration) -> RestartDecision {
+        let should_restart = match self.policy {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnCrash => exit_code != 0,
+            RestartPolicy::Always => true,
+        };
+        if !should_restart {
+            return RestartDecision::Stop;
+        }
+
+        if uptime >= STABLE_UPTIME {
+            self.attempts = 0;
+        }
+        if self.attempts >= self.max_attempts {
+            return RestartDecision::CrashLoop {
+                attempts: self.attempts,
+            };
+        }
+
+        self.attempts += 1;
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(self.attempts - 1))
+            .min(MAX_RESTART_DELAY);
+        RestartDecision::Restart {
+            attempt: self.attempts,
+            delay,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(policy: RestartPolicy) -> RestartTracker {
+        RestartTracker::new(&ServerConfig {
+            restart_policy: policy,
+            max_restart_attempts: 3,
+            restart_delay_secs: 5,
+            ..Default::default()
+        })
+    }
+
+    const QUICK: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn test_never_policy_does_not_restart() {
+        let mut tracker = tracker(RestartPolicy::Never);
+        assert_eq!(tracker.on_exit(1, QUICK), RestartDecision::Stop);
+    }
+
+    #[test]
+    fn test_on_crash_ignores_clean_exit() {
+        let mut tracker = tracker(RestartPolicy::OnCrash);
+        assert_eq!(tracker.on_exit(0, QUICK), RestartDecision::Stop);
+        assert!(matches!(tracker.on_exit(1, QUICK), RestartDecision::Restart { attempt: 1, .. }));
+    }
+
+    #[test]
+    fn test_always_restarts_clean_exit() {
+        let mut tracker = tracker(RestartPolicy::Always);
+        assert!(matches!(tracker.on_exit(0, QUICK), RestartDecision::Restart { attempt: 1, .. }));
+    }
+
+    #[test]
+    fn test_backoff_doubles_then_detects_crash_loop() {
+        let mut tracker = tracker(RestartPolicy::OnCrash);
+        for (attempt, secs) in [(1, 5), (2, 10), (3, 20)] {
+            assert_eq!(
+                tracker.on_exit(1, QUICK),
+                RestartDecision::Restart {
+                    attempt,
+                    delay: Duration::from_secs(secs)
+                }
+            );
+        }
+        assert_eq!(tracker.on_exit(1, QUICK), RestartDecision::CrashLoop { attempts: 3 });
+    }
+
+    #[test]
+    fn test_stable_uptime_resets_attempts() {
+        let mut tracker = tracker(RestartPolicy::OnCrash);
+        tracker.on_exit(1, QUICK);
+        tracker.on_exit(1, QUICK);
+        assert_eq!(tracker.attempts(), 2);
+
+        assert_eq!(
+            tracker.on_exit(1, STABLE_UPTIME),
+            RestartDecision::Restart {
+                attempt: 1,
+                delay: Duration::from_secs(5)
+            }
+        );
+    }
+
+    #[test]
+    fn test_delay_is_capped() {
+        let mut tracker = RestartTracker::new(&ServerConfig {
+            restart_policy: RestartPolicy::Always,
+            max_restart_attempts: 20,
+            restart_delay_secs: 60,
+            ..Default::default()
+        });
+        let mut last = RestartDecision::Stop;
+        for _ in 0..10 {
+            last = tracker.on_exit(1, QUICK);
+        }
+        assert_eq!(
+            last,
+            RestartDecision::Restart {
+                attempt: 10,
+                delay: MAX_RESTART_DELAY
+            }
+        );
+    }
+}
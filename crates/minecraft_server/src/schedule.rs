@@ -0,0 +1,171 @@
+use crate::error::McServerError;
+use crate::Result;
+use std::future::Future;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// When a scheduled restart should happen.
+#[derive(Debug, Clone)]
+pub enum RestartSchedule {
+    /// Restart every `Duration`, measured from when the schedule was set.
+    Interval(Duration),
+    /// Restart at the times matched by a cron expression (local time), e.g. `"0 0 4 * * *"` for 4 AM daily.
+    Cron(Box<cron::Schedule>),
+}
+
+impl RestartSchedule {
+    /// Restart at a fixed interval.
+    pub fn every(interval: Duration) -> Self {
+        Self::Interval(interval)
+    }
+
+    /// Restart according to a cron expression (`sec min hour day-of-month month day-of-week [year]`).
+    pub fn cron(expression: &str) -> Result<Self> {
+        let schedule = cron::Schedule::from_str(expression).map_err(|e| {
+            McServerError::InvalidConfig(format!("Invalid cron expression '{}': {}", expression, e))
+        })?;
+        Ok(Self::Cron(Box::new(schedule)))
+    }
+
+    /// Time until the next restart, or `None` if the schedule has no upcoming occurrence.
+    pub fn next_delay(&self) -> Option<Duration> {
+        match self {
+            Self::Interval(interval) => Some(*interval),
+            Self::Cron(schedule) => {
+                let now = chrono::Local::now();
+                let next = schedule.after(&now).next()?;
+                Some((next - now).to_std().unwrap_or(Duration::ZERO))
+            }
+        }
+    }
+}
+
+/// A step in a restart schedule, passed to the callback of [`run_schedule`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleStep {
+    /// The restart will happen after the given duration.
+    Warning(Duration),
+    /// The restart is due now.
+    Restart,
+}
+
+/// Drive a restart schedule, invoking `on_step` for each warning and restart until
+/// the schedule runs out of occurrences. Intended to be spawned as a task and aborted to cancel.
+pub async fn run_schedule<F, Fut>(schedule: RestartSchedule, warning: Option<Duration>, mut on_step: F)
+where
+    F: FnMut(ScheduleStep) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    while let Some(delay) = schedule.next_delay() {
+        match warning {
+            Some(warning) if delay > warning => {
+                tokio::time::sleep(delay - warning).await;
+                on_step(ScheduleStep::Warning(warning)).await;
+                tokio::time::sleep(warning).await;
+            }
+            _ => tokio::time::sleep(delay).await,
+        }
+        on_step(ScheduleStep::Restart).await;
+    }
+}
+
+/// Format a warning duration for an in-game message, e.g. "5 minutes" or "30 seconds".
+pub fn describe_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..=59 => format!("{} second{}", secs, if secs == 1 { "" } else { "s" }),
+        _ if secs.is_multiple_of(3600) => {
+            let hours = secs / 3600;
+            format!("{} hour{}", hours, if hours == 1 { "" } else { "s" })
+        }
+        _ => {
+            let minutes = secs / 60;
+            format!("{} minute{}", minutes, if minutes == 1 { "" } else { "s" })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::time::Instant;
+
+    #[test]
+    fn test_cron_parses_and_has_next_occurrence() {
+        let schedule = RestartSchedule::cron("0 0 4 * * *").unwrap();
+        let delay = schedule.next_delay().unwrap();
+        assert!(delay <= Duration::from_secs(24 * 60 * 60));
+    }
+
+    #[test]
+    fn test_invalid_cron_is_rejected() {
+        assert!(matches!(
+            RestartSchedule::cron("every night"),
+            Err(McServerError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_describe_duration() {
+        assert_eq!(describe_duration(Duration::from_secs(1)), "1 second");
+        assert_eq!(describe_duration(Duration::from_secs(30)), "30 seconds");
+        assert_eq!(describe_duration(Duration::from_secs(300)), "5 minutes");
+        assert_eq!(describe_duration(Duration::from_secs(3600)), "1 hour");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_interval_schedule_fires_warning_then_restart() {
+        let start = Instant::now();
+        let steps = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = steps.clone();
+        let task = tokio::spawn(run_schedule(
+            RestartSchedule::every(Duration::from_secs(3600)),
+            Some(Duration::from_secs(300)),
+            move |step| {
+                recorded.lock().unwrap().push((step, start.elapsed()));
+                async {}
+            },
+        ));
+
+        // Just past the second restart
+        tokio::time::sleep(Duration::from_secs(2 * 3600 + 1)).await;
+        task.abort();
+
+        let steps = steps.lock().unwrap();
+        assert_eq!(
+            *steps,
+            vec![
+                (ScheduleStep::Warning(Duration::from_secs(300)), Duration::from_secs(3300)),
+                (ScheduleStep::Restart, Duration::from_secs(3600)),
+                (ScheduleStep::Warning(Duration::from_secs(300)), Duration::from_secs(6900)),
+                (ScheduleStep::Restart, Duration::from_secs(7200)),
+            ]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_warning_longer_than_interval_is_skipped() {
+        let start = Instant::now();
+        let steps = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = steps.clone();
+        let task = tokio::spawn(run_schedule(
+            RestartSchedule::every(Duration::from_secs(60)),
+            Some(Duration::from_secs(300)),
+            move |step| {
+                recorded.lock().unwrap().push((step, start.elapsed()));
+                async {}
+            },
+        ));
+
+        tokio::time::sleep(Duration::from_secs(61)).await;
+        task.abort();
+
+        assert_eq!(
+            *steps.lock().unwrap(),
+            vec![(ScheduleStep::Restart, Duration::from_secs(60))]
+        );
+    }
+}
@@ -1,13 +1,36 @@
+use crate::bans::{BanEntry, Bans, IpBanEntry};
+use crate::console::ConsoleHistory;
 use crate::error::McServerError;
-use crate::events::ServerEventHandler;
-use crate::models::{ServerConfig, ServerInfo, ServerStatus};
-use crate::process::ServerProcess;
+use crate::events::{ServerEvent, ServerEventHandler, UpgradeStage};
+use crate::models::{RestartPolicy, ServerConfig, ServerInfo, ServerStatus};
+use crate::ops::{OpEntry, Ops};
+use crate::ping::ServerStatusResponse;
+use crate::process::{ServerProcess, StopOutcome};
+use crate::properties::ServerProperties;
+use crate::restart::{RestartDecision, RestartTracker};
+use crate::schedule::{RestartSchedule, ScheduleStep};
+use crate::tps::TpsReport;
+use crate::usage::ResourceUsage;
+use crate::whitelist::{Whitelist, WhitelistEntry};
 use crate::Result;
-use std::sync::Arc;
-use tokio::sync::mpsc;
+use chrono::{DateTime, FixedOffset};
+use regex::Regex;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
 
 #[cfg(feature = "logging")]
-use log::info;
+use log::{error, info, warn};
+
+/// How long `backup_world` waits for a running server to confirm `save-all`.
+const SAVE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long `tps` waits for the server to answer.
+const TPS_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Main orchestrator for managing a single Minecraft server instance.
 ///
@@ -16,19 +39,97 @@ use log::info;
 /// the CLI can provide a handler that prints to the terminal.
 pub struct ServerManager<H: ServerEventHandler> {
     config: ServerConfig,
+    /// Shared with the restart supervisor and scheduled restarts, which swap in new processes.
+    shared: Arc<Shared<H>>,
+    restart_schedule: Option<JoinHandle<()>>,
+}
+
+/// Process state shared between the manager and its background tasks.
+struct Shared<H: ServerEventHandler> {
     handler: Arc<H>,
-    process: Option<ServerProcess>,
-    status: ServerStatus,
+    process: Mutex<Option<ServerProcess>>,
+    /// Watched by [`ServerManager::wait_until_ready`].
+    status: watch::Sender<ServerStatus>,
+    /// Set when the user asked the server to stop, so the exit is not treated as a crash.
+    stop_requested: AtomicBool,
+    supervisor: Mutex<Option<JoinHandle<()>>>,
+    /// Recent console output, kept across restarts.
+    history: Arc<Mutex<ConsoleHistory>>,
+}
+
+impl<H: ServerEventHandler> Shared<H> {
+    fn set_status(&self, status: ServerStatus) {
+        self.status.send_replace(status);
+    }
+
+    fn current_process(&self) -> Option<ServerProcess> {
+        self.process.lock().unwrap().clone()
+    }
+
+    async fn start_process(&self, config: &ServerConfig) -> Result<ServerProcess> {
+        self.history
+            .lock()
+            .unwrap()
+            .set_capacity(config.console_history_lines);
+        ServerProcess::start_with_history(config, self.handler.clone(), self.history.clone()).await
+    }
+
+    fn abort_supervisor(&self) {
+        if let Some(supervisor) = self.supervisor.lock().unwrap().take() {
+            supervisor.abort();
+        }
+    }
+
+    /// Start a new server process and, if the restart policy asks for it, a supervisor for it.
+    async fn launch(self: &Arc<Self>, config: &ServerConfig) -> Result<()> {
+        self.stop_requested.store(false, Ordering::SeqCst);
+        self.set_status(ServerStatus::Starting);
+        let process = match self.start_process(config).await {
+            Ok(process) => process,
+            Err(e) => {
+                self.set_status(ServerStatus::Error);
+                return Err(e);
+            }
+        };
+        self.set_status(ServerStatus::Running);
+        *self.process.lock().unwrap() = Some(process);
+
+        self.abort_supervisor();
+        if config.restart_policy != RestartPolicy::Never {
+            *self.supervisor.lock().unwrap() =
+                Some(tokio::spawn(supervise(config.clone(), self.clone())));
+        }
+        Ok(())
+    }
+
+    /// Gracefully stop the running process, wait for it to exit, and start it again.
+    async fn restart_gracefully(self: &Arc<Self>, config: &ServerConfig) -> Result<()> {
+        let process = self.current_process().ok_or(McServerError::NotRunning)?;
+        self.stop_requested.store(true, Ordering::SeqCst);
+        self.set_status(ServerStatus::Stopping);
+        process
+            .stop_with_timeout(Duration::from_secs(config.stop_timeout_secs))
+            .await?;
+        *self.process.lock().unwrap() = None;
+        self.launch(config).await
+    }
 }
 
 impl<H: ServerEventHandler> ServerManager<H> {
     /// Create a new server manager with the given configuration and event handler.
     pub fn new(config: ServerConfig, handler: H) -> Self {
+        let history = ConsoleHistory::new(config.console_history_lines);
         Self {
             config,
-            handler: Arc::new(handler),
-            process: None,
-            status: ServerStatus::Idle,
+            shared: Arc::new(Shared {
+                handler: Arc::new(handler),
+                process: Mutex::new(None),
+                status: watch::Sender::new(ServerStatus::Idle),
+                stop_requested: AtomicBool::new(false),
+                supervisor: Mutex::new(None),
+                history: Arc::new(Mutex::new(history)),
+            }),
+            restart_schedule: None,
         }
     }
 
@@ -43,16 +144,21 @@ impl<H: ServerEventHandler> ServerManager<H> {
     }
 
     /// Get the current server status.
-    pub fn status(&self) -> &ServerStatus {
-        &self.status
+    pub fn status(&self) -> ServerStatus {
+        self.shared.status.borrow().clone()
+    }
+
+    /// Get a handle to the current server process, if any.
+    fn current_process(&self) -> Option<ServerProcess> {
+        self.shared.current_process()
     }
 
     /// Get information about the server.
     pub fn info(&self) -> ServerInfo {
         ServerInfo {
             config: self.config.clone(),
-            status: self.status.clone(),
-            pid: self.process.as_ref().map(|p| p.pid()),
+            status: self.status(),
+            pid: self.current_process().map(|p| p.pid()),
         }
     }
 
@@ -65,7 +171,9 @@ impl<H: ServerEventHandler> ServerManager<H> {
             self.config.server_type, self.config.minecraft_version
         );
 
-        crate::installer::install_server(&mut self.config, self.handler.as_ref()).await?;
+        self.backup_before_change().await?;
+        self.resolve_java().await;
+        crate::installer::install_server(&mut self.config, self.shared.handler.as_ref()).await?;
 
         #[cfg(feature = "logging")]
         info!("Installation complete. Server JAR: {}", self.config.server_jar);
@@ -73,44 +181,310 @@ impl<H: ServerEventHandler> ServerManager<H> {
         Ok(())
     }
 
+    /// Archive the active world (named by `level-name` in server.properties) into a
+    /// timestamped zip in `destination`, returning the archive path.
+    ///
+    /// If the server is running, auto-save is paused with `save-off` and the world is
+    /// flushed with `save-all flush` first, so the copy is consistent; `save-on` is sent
+    /// once the archive is written.
+    pub async fn backup_world(&self, destination: &Path) -> Result<PathBuf> {
+        let Some(process) = self.current_process() else {
+            return crate::backup::archive_worlds(&self.config.directory, destination).await;
+        };
+
+        let mut output = process.subscribe_output().await?;
+        process.send_command("save-off").await?;
+        process.send_command("save-all flush").await?;
+        let saved = tokio::time::timeout(SAVE_TIMEOUT, async {
+            while let Some(line) = output.recv().await {
+                if line.contains("Saved the game") {
+                    return true;
+                }
+            }
+            false
+        })
+        .await
+        .unwrap_or(false);
+        if !saved {
+            #[cfg(feature = "logging")]
+            warn!(
+                "Server '{}' did not confirm saving, backing up anyway",
+                self.config.name
+            );
+        }
+
+        let result = crate::backup::archive_worlds(&self.config.directory, destination).await;
+        process.send_command("save-on").await?;
+        result
+    }
+
+    /// Archive the world into `config.world_backup_dir`, if configured and the world exists.
+    async fn backup_before_change(&self) -> Result<()> {
+        let Some(destination) = &self.config.world_backup_dir else {
+            return Ok(());
+        };
+        if crate::upgrade::world_directories(&self.config.directory).is_empty() {
+            return Ok(());
+        }
+        let _archive = self.backup_world(destination).await?;
+
+        #[cfg(feature = "logging")]
+        info!("Backed up world of '{}' to {}", self.config.name, _archive.display());
+        Ok(())
+    }
+
+    /// Upgrade the server to another Minecraft version.
+    ///
+    /// Stops the server if it is running, snapshots its worlds and top-level files into
+    /// `.upgrade-backups/`, and installs the server JAR for `new_version` (keeping the
+    /// server type; the loader version or build is reset to the newest for that version).
+    /// If installing fails, the previous files and configuration are restored.
+    ///
+    /// Returns the snapshot directory. The worlds themselves are converted by the server
+    /// on its next start.
+    pub async fn upgrade(&mut self, new_version: &str) -> Result<PathBuf> {
+        self.upgrade_with(new_version, async |config: &mut ServerConfig, handler: &H| {
+            crate::installer::install_server(config, handler).await
+        })
+        .await
+    }
+
+    async fn upgrade_with(
+        &mut self,
+        new_version: &str,
+        install: impl AsyncFnOnce(&mut ServerConfig, &H) -> Result<()>,
+    ) -> Result<PathBuf> {
+        let handler = self.shared.handler.clone();
+        let from_version = self.config.minecraft_version.clone();
+        let progress = async |stage| {
+            handler
+                .on_event(ServerEvent::UpgradeProgress {
+                    from_version: from_version.clone(),
+                    to_version: new_version.to_string(),
+                    stage,
+                })
+                .await;
+        };
+
+        #[cfg(feature = "logging")]
+        info!(
+            "Upgrading server '{}' from {} to {}",
+            self.config.name, from_version, new_version
+        );
+
+        if self.current_process().is_some() {
+            progress(UpgradeStage::Stopping).await;
+            self.stop().await?;
+        }
+
+        progress(UpgradeStage::BackingUp).await;
+        self.backup_before_change().await?;
+        let backup = crate::upgrade::snapshot(&self.config.directory, &from_version)?;
+
+        progress(UpgradeStage::Installing).await;
+        let previous = self.config.clone();
+        self.config.minecraft_version = new_version.to_string();
+        self.config.loader_version = None;
+        if let Err(e) = install(&mut self.config, handler.as_ref()).await {
+            #[cfg(feature = "logging")]
+            error!(
+                "Upgrade of server '{}' to {} failed, restoring previous install: {}",
+                self.config.name, new_version, e
+            );
+
+            self.config = previous;
+            crate::upgrade::restore_files(&backup, &self.config.directory)?;
+            progress(UpgradeStage::RolledBack { backup }).await;
+            return Err(e);
+        }
+
+        progress(UpgradeStage::Completed {
+            backup: backup.clone(),
+        })
+        .await;
+        Ok(backup)
+    }
+
+    /// Copy a downloaded mod or plugin JAR into the directory the server type loads it
+    /// from (`mods/` or `plugins/`), returning the installed path.
+    ///
+    /// A file with the same name is only replaced when `overwrite` is set; otherwise
+    /// [`McServerError::AlreadyInstalled`] is returned.
+    pub fn install_mod(&self, jar_path: &Path, overwrite: bool) -> Result<PathBuf> {
+        let mods_directory = self.config.server_type.mods_directory().ok_or_else(|| {
+            McServerError::InvalidConfig(format!(
+                "{} servers do not support mods or plugins",
+                self.config.server_type
+            ))
+        })?;
+        let file_name = jar_path
+            .file_name()
+            .filter(|_| jar_path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("jar")))
+            .ok_or_else(|| {
+                McServerError::InvalidConfig(format!("Not a JAR file: {}", jar_path.display()))
+            })?;
+
+        let directory = self.config.directory.join(mods_directory);
+        std::fs::create_dir_all(&directory)?;
+        let destination = directory.join(file_name);
+        if destination.exists() && !overwrite {
+            return Err(McServerError::AlreadyInstalled(
+                file_name.to_string_lossy().into_owned(),
+            ));
+        }
+        std::fs::copy(jar_path, &destination)?;
+
+        #[cfg(feature = "logging")]
+        info!("Installed {} into server '{}'", destination.display(), self.config.name);
+
+        Ok(destination)
+    }
+
+    /// Install a datapack (a zip archive or an unpacked directory containing a
+    /// `pack.mcmeta`) into the active world's `datapacks/` directory, returning the
+    /// installed path.
+    ///
+    /// If the server is running, `reload` is sent so it discovers the pack, followed by
+    /// `datapack enable` in case the pack was previously disabled.
+    pub async fn install_datapack(&self, zip_or_dir: &Path) -> Result<PathBuf> {
+        let destination = crate::datapack::install_datapack(&self.config.directory, zip_or_dir)?;
+
+        #[cfg(feature = "logging")]
+        info!("Installed datapack {} into server '{}'", destination.display(), self.config.name);
+
+        if let Some(process) = self.current_process() {
+            let name = destination.file_name().unwrap_or_default().to_string_lossy();
+            process.send_command("reload").await?;
+            process
+                .send_command(&format!("datapack enable \"file/{}\"", name))
+                .await?;
+        }
+        Ok(destination)
+    }
+
+    /// Set the icon shown in the multiplayer server list from an image file.
+    ///
+    /// The image is cropped to a square, scaled to 64×64, and written as
+    /// `server-icon.png`. The server loads the icon when it starts.
+    pub fn set_icon(&self, image_path: &Path) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.config.directory)?;
+        let destination = self.config.directory.join(crate::icon::ICON_FILENAME);
+        crate::icon::convert_icon(image_path, &destination)?;
+        Ok(destination)
+    }
+
+    /// Accept the Minecraft EULA for this server by writing `eula=true` to `eula.txt`.
+    ///
+    /// This records the server owner's agreement to <https://aka.ms/MinecraftEULA>;
+    /// only call it on their explicit instruction. Later installs keep it accepted.
+    pub fn accept_eula(&mut self) -> Result<()> {
+        std::fs::create_dir_all(&self.config.directory)?;
+        crate::eula::accept_eula(&self.config.directory)?;
+        self.config.accept_eula = true;
+        Ok(())
+    }
+
+    /// If `java_executable` is left as the default, switch it to an installed Java that
+    /// can run the configured Minecraft version.
+    async fn resolve_java(&mut self) {
+        if self.config.java_executable != crate::java::DEFAULT_JAVA_EXECUTABLE {
+            return;
+        }
+        let installs = crate::java::discover_java_installs().await;
+        if let Some(java) = crate::java::select_java_for(&self.config.minecraft_version, &installs) {
+            #[cfg(feature = "logging")]
+            info!(
+                "Using {} for Minecraft {}",
+                java.display(),
+                self.config.minecraft_version
+            );
+            self.config.java_executable = java.to_string_lossy().into_owned();
+        }
+    }
+
     /// Start the server process.
+    ///
+    /// If the configuration has a [`RestartPolicy`] other than `Never`, a supervisor
+    /// task is spawned that restarts the server when it exits unexpectedly.
     pub async fn start(&mut self) -> Result<()> {
-        if self.process.is_some() {
+        if self.current_process().is_some() {
             return Err(McServerError::AlreadyRunning);
         }
 
         #[cfg(feature = "logging")]
         info!("Starting server '{}'", self.config.name);
 
-        self.status = ServerStatus::Starting;
-        let process = ServerProcess::start(&self.config, self.handler.clone()).await?;
-        self.status = ServerStatus::Running;
-        self.process = Some(process);
+        self.resolve_java().await;
+        self.shared.launch(&self.config).await
+    }
 
-        Ok(())
+    /// Wait for the server to finish starting, returning how long startup took.
+    ///
+    /// [`start`](Self::start) itself returns once the server logs its "Done" line, so this
+    /// is for waiting on a start that happens elsewhere, such as an automatic restart after
+    /// a crash or a scheduled restart; it returns straight away if the server is already up.
+    /// Fails with [`McServerError::Timeout`] if startup takes longer than `timeout`, and with
+    /// [`McServerError::NotRunning`] if the server is not starting or exits before it is ready.
+    pub async fn wait_until_ready(&self, timeout: Duration) -> Result<Duration> {
+        let mut status = self.shared.status.subscribe();
+        let starting = status.wait_for(|status| *status != ServerStatus::Starting);
+        if tokio::time::timeout(timeout, starting).await.is_err() {
+            return Err(McServerError::Timeout("server did not finish starting".to_string()));
+        }
+        if *status.borrow() != ServerStatus::Running {
+            return Err(McServerError::NotRunning);
+        }
+        self.current_process()
+            .and_then(|process| process.startup_time())
+            .ok_or(McServerError::NotRunning)
     }
 
-    /// Stop the server gracefully by sending the "stop" command.
-    pub async fn stop(&mut self) -> Result<()> {
-        let process = self.process.as_ref().ok_or(McServerError::NotRunning)?;
+    /// Stop the server gracefully by sending the "stop" command and waiting for it to exit.
+    ///
+    /// If the server is still running after `config.stop_timeout_secs`, it is killed
+    /// and [`StopOutcome::Killed`] is returned.
+    pub async fn stop(&mut self) -> Result<StopOutcome> {
+        let process = self.current_process().ok_or(McServerError::NotRunning)?;
 
         #[cfg(feature = "logging")]
         info!("Stopping server '{}'", self.config.name);
 
-        self.status = ServerStatus::Stopping;
-        process.stop().await?;
-        Ok(())
+        self.shared.stop_requested.store(true, Ordering::SeqCst);
+        self.shared.set_status(ServerStatus::Stopping);
+        let outcome = process
+            .stop_with_timeout(Duration::from_secs(self.config.stop_timeout_secs))
+            .await?;
+
+        #[cfg(feature = "logging")]
+        if outcome == StopOutcome::Killed {
+            warn!(
+                "Server '{}' did not stop within {}s and was killed",
+                self.config.name, self.config.stop_timeout_secs
+            );
+        }
+
+        *self.shared.process.lock().unwrap() = None;
+        self.shared.set_status(ServerStatus::Stopped);
+        Ok(outcome)
     }
 
     /// Force kill the server process.
     pub async fn kill(&mut self) -> Result<()> {
-        let process = self.process.take().ok_or(McServerError::NotRunning)?;
+        let process = self
+            .shared
+            .process
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or(McServerError::NotRunning)?;
 
         #[cfg(feature = "logging")]
         info!("Killing server '{}'", self.config.name);
 
+        self.shared.stop_requested.store(true, Ordering::SeqCst);
+        self.shared.abort_supervisor();
         process.kill().await?;
-        self.status = ServerStatus::Stopped;
+        self.shared.set_status(ServerStatus::Stopped);
         Ok(())
     }
 
@@ -120,27 +494,261 @@ impl<H: ServerEventHandler> ServerManager<H> {
         info!("Restarting server '{}'", self.config.name);
 
         self.stop().await?;
-        tokio::time::sleep(std::time::Duration::from_secs(3)).await;
-        self.process = None;
+        tokio::time::sleep(Duration::from_secs(3)).await;
         self.start().await?;
         Ok(())
     }
 
     /// Send a command to the running server.
     pub async fn send_command(&self, command: &str) -> Result<()> {
-        let process = self.process.as_ref().ok_or(McServerError::NotRunning)?;
+        let process = self.current_process().ok_or(McServerError::NotRunning)?;
         process.send_command(command).await
     }
 
+    /// Send a command to the running server and return the console output that follows
+    /// it, one line per line, once the console has been quiet briefly.
+    ///
+    /// The server console has no request/response framing, so this is a heuristic: any
+    /// output written at the same time (player chat, plugin logs) is included, and a
+    /// command that prints nothing ends in [`McServerError::Timeout`] after `timeout`.
+    /// RCON returns exactly the command's own output; prefer it where it is enabled.
+    pub async fn send_command_await(&self, command: &str, timeout: Duration) -> Result<String> {
+        let process = self.current_process().ok_or(McServerError::NotRunning)?;
+        let lines = process.send_command_response(command, timeout, None).await?;
+        Ok(lines.join("\n"))
+    }
+
+    /// Like [`send_command_await`](Self::send_command_await), but collect output until a
+    /// line matches `pattern`, which suits commands whose last line is predictable.
+    pub async fn send_command_await_until(
+        &self,
+        command: &str,
+        pattern: &Regex,
+        timeout: Duration,
+    ) -> Result<String> {
+        let process = self.current_process().ok_or(McServerError::NotRunning)?;
+        let lines = process.send_command_response(command, timeout, Some(pattern)).await?;
+        Ok(lines.join("\n"))
+    }
+
     /// Subscribe to console output from the running server.
     pub async fn subscribe_output(&self) -> Result<mpsc::Receiver<String>> {
-        let process = self.process.as_ref().ok_or(McServerError::NotRunning)?;
+        let process = self.current_process().ok_or(McServerError::NotRunning)?;
         process.subscribe_output().await
     }
 
+    /// Subscribe to console output, also returning the recent lines that precede it
+    /// so a newly connected viewer can show them first.
+    pub async fn subscribe_output_with_history(
+        &self,
+    ) -> Result<(Vec<String>, mpsc::Receiver<String>)> {
+        let process = self.current_process().ok_or(McServerError::NotRunning)?;
+        process.subscribe_output_with_history().await
+    }
+
+    /// The last `config.console_history_lines` lines of console output, oldest first.
+    ///
+    /// Output is kept across restarts and remains available after the server stops.
+    pub fn console_history(&self) -> Vec<String> {
+        self.shared.history.lock().unwrap().lines()
+    }
+
+    /// Query the server's MOTD, version, and player counts via Server List Ping.
+    ///
+    /// Returns [`McServerError::ServerNotReady`] while the server is still starting.
+    pub async fn query_status(&self) -> Result<ServerStatusResponse> {
+        let port = ServerProperties::load_from_dir(&self.config.directory)
+            .map(|p| p.server_port())
+            .unwrap_or(25565);
+        crate::ping::ping("127.0.0.1", port).await
+    }
+
+    /// Measure CPU, memory, and uptime of the server process, including any child
+    /// processes spawned by the Java launcher.
+    pub async fn resource_usage(&self) -> Result<ResourceUsage> {
+        let process = self.current_process().ok_or(McServerError::NotRunning)?;
+        crate::usage::process_tree_usage(process.pid()).await
+    }
+
+    /// Ask the running server for its tick statistics using the command for its
+    /// server type (see [`crate::tps::tps_command`]) and parse the response.
+    pub async fn tps(&self) -> Result<TpsReport> {
+        let process = self.current_process().ok_or(McServerError::NotRunning)?;
+        let server_type = &self.config.server_type;
+        let command = crate::tps::tps_command(server_type).ok_or_else(|| {
+            McServerError::InvalidConfig(format!("{} servers do not report TPS", server_type))
+        })?;
+
+        let lines = process
+            .send_command_collect(command, TPS_TIMEOUT, |lines| {
+                crate::tps::parse_tps_output(server_type, lines).is_some()
+            })
+            .await?;
+        crate::tps::parse_tps_output(server_type, &lines).ok_or_else(|| {
+            McServerError::Timeout(format!("no response to '{}'", command))
+        })
+    }
+
+    /// Whitelist a player by name or UUID, updating `whitelist.json` and, if the server
+    /// is running, the live whitelist via the `whitelist add` command.
+    pub async fn whitelist_add(&self, name_or_uuid: &str) -> Result<WhitelistEntry> {
+        let mut whitelist = Whitelist::load(&self.config.directory)?;
+        let entry = whitelist.add(name_or_uuid).await?;
+        whitelist.save()?;
+        if let Some(process) = self.current_process() {
+            process.send_command(&format!("whitelist add {}", entry.name)).await?;
+        }
+        Ok(entry)
+    }
+
+    /// Remove a player from the whitelist by name or UUID, updating `whitelist.json`
+    /// and, if the server is running, the live whitelist via `whitelist remove`.
+    pub async fn whitelist_remove(&self, name_or_uuid: &str) -> Result<Option<WhitelistEntry>> {
+        let mut whitelist = Whitelist::load(&self.config.directory)?;
+        let Some(entry) = whitelist.remove(name_or_uuid) else {
+            return Ok(None);
+        };
+        whitelist.save()?;
+        if let Some(process) = self.current_process() {
+            process.send_command(&format!("whitelist remove {}", entry.name)).await?;
+        }
+        Ok(Some(entry))
+    }
+
+    /// Make a player an operator by name or UUID, updating `ops.json` and, if the server
+    /// is running, the live operator list via the `op` command.
+    pub async fn op(&self, name_or_uuid: &str) -> Result<OpEntry> {
+        let mut ops = Ops::load(&self.config.directory)?;
+        let entry = ops.add(name_or_uuid).await?;
+        ops.save()?;
+        if let Some(process) = self.current_process() {
+            process.send_command(&format!("op {}", entry.name)).await?;
+        }
+        Ok(entry)
+    }
+
+    /// Remove a player's operator status by name or UUID, updating `ops.json` and,
+    /// if the server is running, the live operator list via `deop`.
+    pub async fn deop(&self, name_or_uuid: &str) -> Result<Option<OpEntry>> {
+        let mut ops = Ops::load(&self.config.directory)?;
+        let Some(entry) = ops.remove(name_or_uuid) else {
+            return Ok(None);
+        };
+        ops.save()?;
+        if let Some(process) = self.current_process() {
+            process.send_command(&format!("deop {}", entry.name)).await?;
+        }
+        Ok(Some(entry))
+    }
+
+    /// Ban a player by name or UUID, updating `banned-players.json` and, if the server
+    /// is running, the live ban list via the `ban` command (which also kicks the player).
+    ///
+    /// The `ban` command has no expiry, so a running server records the ban as permanent
+    /// in its own copy of the list; apply temporary bans while the server is stopped.
+    pub async fn ban(
+        &self,
+        name_or_uuid: &str,
+        reason: Option<&str>,
+        source: Option<&str>,
+        expires: Option<DateTime<FixedOffset>>,
+    ) -> Result<BanEntry> {
+        let mut bans = Bans::load(&self.config.directory)?;
+        let entry = bans.ban(name_or_uuid, reason, source, expires).await?;
+        bans.save()?;
+        if let Some(process) = self.current_process() {
+            process
+                .send_command(&format!("ban {} {}", entry.name, entry.reason))
+                .await?;
+        }
+        Ok(entry)
+    }
+
+    /// Lift a player's ban by name or UUID, updating `banned-players.json` and, if the
+    /// server is running, the live ban list via `pardon`.
+    pub async fn pardon(&self, name_or_uuid: &str) -> Result<Option<BanEntry>> {
+        let mut bans = Bans::load(&self.config.directory)?;
+        let Some(entry) = bans.pardon(name_or_uuid) else {
+            return Ok(None);
+        };
+        bans.save()?;
+        if let Some(process) = self.current_process() {
+            process.send_command(&format!("pardon {}", entry.name)).await?;
+        }
+        Ok(Some(entry))
+    }
+
+    /// Ban an IP address, updating `banned-ips.json` and, if the server is running,
+    /// the live ban list via `ban-ip`. See [`ban`](Self::ban) about expiry.
+    pub async fn ban_ip(
+        &self,
+        ip: IpAddr,
+        reason: Option<&str>,
+        source: Option<&str>,
+        expires: Option<DateTime<FixedOffset>>,
+    ) -> Result<IpBanEntry> {
+        let mut bans = Bans::load(&self.config.directory)?;
+        let entry = bans.ban_ip(ip, reason, source, expires);
+        bans.save()?;
+        if let Some(process) = self.current_process() {
+            process
+                .send_command(&format!("ban-ip {} {}", entry.ip, entry.reason))
+                .await?;
+        }
+        Ok(entry)
+    }
+
+    /// Lift an IP ban, updating `banned-ips.json` and, if the server is running,
+    /// the live ban list via `pardon-ip`.
+    pub async fn pardon_ip(&self, ip: IpAddr) -> Result<Option<IpBanEntry>> {
+        let mut bans = Bans::load(&self.config.directory)?;
+        let Some(entry) = bans.pardon_ip(ip) else {
+            return Ok(None);
+        };
+        bans.save()?;
+        if let Some(process) = self.current_process() {
+            process.send_command(&format!("pardon-ip {}", entry.ip)).await?;
+        }
+        Ok(Some(entry))
+    }
+
+    /// Gracefully restart the server on a schedule, replacing any previous schedule.
+    ///
+    /// If `warning` is set, players are told in chat that long before each restart
+    /// (e.g. "Server restarting in 5 minutes"). A [`ServerEvent::RestartWarning`] and
+    /// [`ServerEvent::ScheduledRestart`] are emitted for each occurrence. Restarts that
+    /// come due while the server is not running are skipped.
+    pub fn schedule_restart(&mut self, schedule: RestartSchedule, warning: Option<Duration>) {
+        self.cancel_scheduled_restart();
+
+        let config = self.config.clone();
+        let shared = self.shared.clone();
+        self.restart_schedule = Some(tokio::spawn(crate::schedule::run_schedule(
+            schedule,
+            warning,
+            move |step| {
+                let config = config.clone();
+                let shared = shared.clone();
+                async move { run_scheduled_step(step, &config, &shared).await }
+            },
+        )));
+    }
+
+    /// Cancel the restart schedule set by [`schedule_restart`](Self::schedule_restart), if any.
+    pub fn cancel_scheduled_restart(&mut self) {
+        if let Some(schedule) = self.restart_schedule.take() {
+            schedule.abort();
+        }
+    }
+
+    /// Whether a restart schedule is active.
+    pub fn has_scheduled_restart(&self) -> bool {
+        self.restart_schedule.as_ref().is_some_and(|s| !s.is_finished())
+    }
+
     /// Check if the server process is still running.
     pub async fn is_running(&self) -> bool {
-        match &self.process {
+        match self.current_process() {
             Some(process) => process.is_running().await,
             None => false,
         }
@@ -149,13 +757,481 @@ impl<H: ServerEventHandler> ServerManager<H> {
     /// Clean up after the server process has exited.
     /// Call this when notified of a stop/crash event to update internal state.
     pub fn mark_stopped(&mut self) {
-        self.process = None;
-        self.status = ServerStatus::Stopped;
+        *self.shared.process.lock().unwrap() = None;
+        self.shared.set_status(ServerStatus::Stopped);
     }
 
     /// Mark the server as crashed and clean up.
     pub fn mark_crashed(&mut self) {
-        self.process = None;
-        self.status = ServerStatus::Crashed;
+        *self.shared.process.lock().unwrap() = None;
+        self.shared.set_status(ServerStatus::Crashed);
+    }
+}
+
+impl<H: ServerEventHandler> Drop for ServerManager<H> {
+    fn drop(&mut self) {
+        self.cancel_scheduled_restart();
+        self.shared.abort_supervisor();
+    }
+}
+
+/// Handle one step of a restart schedule for the server described by `config`.
+async fn run_scheduled_step<H: ServerEventHandler>(
+    step: ScheduleStep,
+    config: &ServerConfig,
+    shared: &Arc<Shared<H>>,
+) {
+    let Some(process) = shared.current_process() else {
+        return;
+    };
+
+    match step {
+        ScheduleStep::Warning(remaining) => {
+            let message = format!(
+                "say Server restarting in {}",
+                crate::schedule::describe_duration(remaining)
+            );
+            if let Err(_e) = process.send_command(&message).await {
+                #[cfg(feature = "logging")]
+                warn!("Failed to send restart warning to '{}': {}", config.name, _e);
+            }
+            shared
+                .handler
+                .on_event(ServerEvent::RestartWarning {
+                    seconds_remaining: remaining.as_secs(),
+                })
+                .await;
+        }
+        ScheduleStep::Restart => {
+            #[cfg(feature = "logging")]
+            info!("Performing scheduled restart of server '{}'", config.name);
+
+            shared.handler.on_event(ServerEvent::ScheduledRestart).await;
+            if let Err(_e) = shared.restart_gracefully(config).await {
+                #[cfg(feature = "logging")]
+                error!("Scheduled restart of server '{}' failed: {}", config.name, _e);
+            }
+        }
+    }
+}
+
+/// Watch the running process and restart it according to the configured [`RestartPolicy`].
+async fn supervise<H: ServerEventHandler>(config: ServerConfig, shared: Arc<Shared<H>>) {
+    let mut tracker = RestartTracker::new(&config);
+
+    loop {
+        let Some(current) = shared.current_process() else {
+            return;
+        };
+        let started_at = tokio::time::Instant::now();
+        let Some(exit_code) = current.wait_for_exit().await else {
+            return;
+        };
+        if shared.stop_requested.load(Ordering::SeqCst) {
+            return;
+        }
+
+        match tracker.on_exit(exit_code, started_at.elapsed()) {
+            RestartDecision::Stop => return,
+            RestartDecision::CrashLoop { attempts } => {
+                #[cfg(feature = "logging")]
+                warn!(
+                    "Server '{}' exited {} times in a row, giving up on automatic restarts",
+                    config.name, attempts
+                );
+
+                *shared.process.lock().unwrap() = None;
+                shared.set_status(ServerStatus::Crashed);
+                shared
+                    .handler
+                    .on_event(ServerEvent::CrashLoopDetected { attempts })
+                    .await;
+                return;
+            }
+            RestartDecision::Restart { attempt, delay } => {
+                #[cfg(feature = "logging")]
+                info!(
+                    "Restarting server '{}' in {:?} (attempt {})",
+                    config.name, delay, attempt
+                );
+
+                *shared.process.lock().unwrap() = None;
+                shared
+                    .handler
+                    .on_event(ServerEvent::Restarting {
+                        attempt,
+                        delay_secs: delay.as_secs(),
+                    })
+                    .await;
+                tokio::time::sleep(delay).await;
+                if shared.stop_requested.load(Ordering::SeqCst) {
+                    return;
+                }
+
+                shared.set_status(ServerStatus::Starting);
+                match shared.start_process(&config).await {
+                    Ok(restarted) => {
+                        *shared.process.lock().unwrap() = Some(restarted);
+                        shared.set_status(ServerStatus::Running);
+                    }
+                    Err(_e) => {
+                        #[cfg(feature = "logging")]
+                        error!("Failed to restart server '{}': {}", config.name, _e);
+
+                        shared.set_status(ServerStatus::Error);
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::ConsoleHistory;
+    use crate::models::ServerType;
+    use crate::test_util::temp_dir;
+
+    /// Records upgrade stages and the status changes around them.
+    #[derive(Clone, Default)]
+    struct RecordingHandler(Arc<Mutex<Vec<String>>>);
+
+    impl ServerEventHandler for RecordingHandler {
+        async fn on_event(&self, event: ServerEvent) {
+            let entry = match event {
+                ServerEvent::UpgradeProgress { stage, .. } => match stage {
+                    UpgradeStage::Completed { .. } => "completed".to_string(),
+                    UpgradeStage::RolledBack { .. } => "rolled back".to_string(),
+                    stage => format!("{:?}", stage).to_lowercase(),
+                },
+                ServerEvent::Stopped => "stopped".to_string(),
+                _ => return,
+            };
+            self.0.lock().unwrap().push(entry);
+        }
+    }
+
+    fn temp_server(name: &str) -> std::path::PathBuf {
+        let dir = temp_dir(&format!("server-{}", name));
+        std::fs::create_dir_all(dir.join("world")).unwrap();
+        std::fs::write(dir.join("world/level.dat"), "level").unwrap();
+        std::fs::write(dir.join("paper-1.21.3-82.jar"), "old jar").unwrap();
+        dir
+    }
+
+    fn manager(dir: &std::path::Path, log: &RecordingHandler) -> ServerManager<RecordingHandler> {
+        let config = ServerConfig {
+            directory: dir.to_path_buf(),
+            minecraft_version: "1.21.3".to_string(),
+            server_type: ServerType::Paper,
+            loader_version: Some("82".to_string()),
+            server_jar: "paper-1.21.3-82.jar".to_string(),
+            ..Default::default()
+        };
+        ServerManager::new(config, log.clone())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_upgrade_stops_backs_up_then_reinstalls() {
+        let dir = temp_server("upgrade");
+        let log = RecordingHandler::default();
+        let mut manager = manager(&dir, &log);
+        let script = r#"echo 'Done (0.1s)! For help, type "help"'; while read line; do [ "$line" = stop ] && exit 0; done"#;
+        let process = ServerProcess::spawn(
+            "sh",
+            vec!["-c".to_string(), script.to_string()],
+            &dir,
+            manager.shared.handler.clone(),
+            Arc::new(Mutex::new(ConsoleHistory::new(10))),
+        )
+        .await
+        .unwrap();
+        *manager.shared.process.lock().unwrap() = Some(process);
+
+        let install_log = log.clone();
+        let backup = manager
+            .upgrade_with("1.21.4", async |config: &mut ServerConfig, _: &RecordingHandler| {
+                // The server is down and the world is backed up before installing
+                let snapshots = config.directory.join(crate::upgrade::SNAPSHOT_DIR);
+                let snapshot = std::fs::read_dir(snapshots).unwrap().next().unwrap().unwrap().path();
+                assert!(snapshot.join("world/level.dat").exists());
+                assert_eq!(config.minecraft_version, "1.21.4");
+                assert_eq!(config.loader_version, None);
+
+                install_log.0.lock().unwrap().push("install".to_string());
+                std::fs::write(config.directory.join("paper-1.21.4-100.jar"), "new jar").unwrap();
+                config.server_jar = "paper-1.21.4-100.jar".to_string();
+                config.loader_version = Some("100".to_string());
+                Ok(())
+            })
+            .await
+            .unwrap();
+
+        // Wait for the exit callback's Stopped event
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let mut events = log.0.lock().unwrap().clone();
+        events.retain(|e| e != "stopped");
+        assert_eq!(
+            events,
+            ["stopping", "backingup", "installing", "install", "completed"]
+        );
+        assert!(!manager.is_running().await);
+        assert_eq!(manager.config().minecraft_version, "1.21.4");
+        assert_eq!(manager.config().server_jar, "paper-1.21.4-100.jar");
+        assert!(backup.join("paper-1.21.3-82.jar").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_backup_world_pauses_auto_save_while_running() {
+        let dir = temp_server("backup");
+        let manager = manager(&dir, &RecordingHandler::default());
+        let script = r#"echo 'Done (0.1s)! For help, type "help"'
+            while read line; do
+                echo "> $line"
+                [ "$line" = "save-all flush" ] && echo "[12:00:00] [Server thread/INFO]: Saved the game"
+            done"#;
+        let process = ServerProcess::spawn(
+            "sh",
+            vec!["-c".to_string(), script.to_string()],
+            &dir,
+            manager.shared.handler.clone(),
+            manager.shared.history.clone(),
+        )
+        .await
+        .unwrap();
+        *manager.shared.process.lock().unwrap() = Some(process.clone());
+
+        let archive = manager.backup_world(&dir.join("backups")).await.unwrap();
+        assert!(archive.exists());
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let commands: Vec<_> = manager
+            .console_history()
+            .into_iter()
+            .filter(|line| line.starts_with("> "))
+            .collect();
+        assert_eq!(commands, ["> save-off", "> save-all flush", "> save-on"]);
+
+        process.kill().await.unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_tps_sends_command_for_server_type() {
+        let dir = temp_server("tps");
+        let mut manager = manager(&dir, &RecordingHandler::default());
+        manager.config_mut().server_type = ServerType::Forge;
+        let script = r#"echo 'Done (0.1s)! For help, type "help"'
+            while read line; do
+                if [ "$line" = "forge tps" ]; then
+                    echo "[12:00:00] [Server thread/INFO] [minecraft/MinecraftServer]: Dim minecraft:overworld (minecraft:overworld): Mean tick time: 40.000 ms. Mean TPS: 20.000"
+                    echo "[12:00:00] [Server thread/INFO] [minecraft/MinecraftServer]: Overall : Mean tick time: 40.000 ms. Mean TPS: 19.500"
+                fi
+            done"#;
+        let process = ServerProcess::spawn(
+            "sh",
+            vec!["-c".to_string(), script.to_string()],
+            &dir,
+            manager.shared.handler.clone(),
+            manager.shared.history.clone(),
+        )
+        .await
+        .unwrap();
+        *manager.shared.process.lock().unwrap() = Some(process.clone());
+
+        let report = manager.tps().await.unwrap();
+        assert_eq!(report.tps, 19.5);
+        assert_eq!(report.mspt, Some(40.0));
+
+        process.kill().await.unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_send_command_await_captures_response() {
+        let dir = temp_server("await");
+        let manager = manager(&dir, &RecordingHandler::default());
+        let script = r#"echo 'Done (0.1s)! For help, type "help"'
+            while read line; do
+                case "$line" in
+                    list)
+                        echo "[12:00:00 INFO]: There are 2 of a max of 20 players online:"
+                        echo "[12:00:00 INFO]: Alex, Steve" ;;
+                    seed)
+                        sleep 1; echo "[12:00:01 INFO]: Seed: [-4172144997902289642]" ;;
+                esac
+            done"#;
+        let process = ServerProcess::spawn(
+            "sh",
+            vec!["-c".to_string(), script.to_string()],
+            &dir,
+            manager.shared.handler.clone(),
+            manager.shared.history.clone(),
+        )
+        .await
+        .unwrap();
+        *manager.shared.process.lock().unwrap() = Some(process.clone());
+
+        let response = manager.send_command_await("list", Duration::from_secs(5)).await.unwrap();
+        assert_eq!(
+            response,
+            "[12:00:00 INFO]: There are 2 of a max of 20 players online:\n[12:00:00 INFO]: Alex, Steve"
+        );
+
+        // The quiet period only starts after the first line, so slow responses are still caught
+        let seed = Regex::new(r"Seed: \[").unwrap();
+        let response = manager
+            .send_command_await_until("seed", &seed, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(response.ends_with("Seed: [-4172144997902289642]"));
+
+        assert!(matches!(
+            manager.send_command_await("help", Duration::from_millis(300)).await,
+            Err(McServerError::Timeout(_))
+        ));
+
+        process.kill().await.unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_wait_until_ready_reports_startup_time() {
+        let dir = temp_server("ready");
+        let manager = manager(&dir, &RecordingHandler::default());
+        assert!(matches!(
+            manager.wait_until_ready(Duration::from_secs(1)).await,
+            Err(McServerError::NotRunning)
+        ));
+
+        // Start the way an automatic restart does, while the caller waits
+        let launch = |script: &'static str| {
+            let shared = manager.shared.clone();
+            shared.set_status(ServerStatus::Starting);
+            let dir = dir.clone();
+            tokio::spawn(async move {
+                let process = ServerProcess::spawn(
+                    "sh",
+                    vec!["-c".to_string(), script.to_string()],
+                    &dir,
+                    shared.handler.clone(),
+                    shared.history.clone(),
+                )
+                .await
+                .unwrap();
+                *shared.process.lock().unwrap() = Some(process);
+                shared.set_status(ServerStatus::Running);
+            })
+        };
+
+        launch(
+            r#"echo '[12:00:00] [Server thread/INFO]: Preparing level "world"'
+            sleep 0.3
+            echo '[12:00:04] [Server thread/INFO]: Done (4.321s)! For help, type "help"'
+            while read line; do :; done"#,
+        );
+        let startup = manager.wait_until_ready(Duration::from_secs(5)).await.unwrap();
+        assert_eq!(startup, Duration::from_millis(4321));
+        manager.current_process().unwrap().kill().await.unwrap();
+
+        launch("echo 'Preparing level'; exit 1");
+        assert!(matches!(
+            manager.wait_until_ready(Duration::from_secs(5)).await,
+            Err(McServerError::NotRunning)
+        ));
+
+        manager.shared.set_status(ServerStatus::Starting);
+        assert!(matches!(
+            manager.wait_until_ready(Duration::from_millis(200)).await,
+            Err(McServerError::Timeout(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_install_mod_targets_directory_per_server_type() {
+        let dir = temp_server("mods");
+        let jar = dir.join("example-mod-1.0.jar");
+        std::fs::write(&jar, "mod").unwrap();
+
+        for (server_type, expected) in [
+            (ServerType::Fabric, "mods"),
+            (ServerType::Forge, "mods"),
+            (ServerType::NeoForge, "mods"),
+            (ServerType::Paper, "plugins"),
+            (ServerType::Spigot, "plugins"),
+            (ServerType::Purpur, "plugins"),
+        ] {
+            let mut manager = manager(&dir, &RecordingHandler::default());
+            manager.config_mut().server_type = server_type.clone();
+            let installed = manager.install_mod(&jar, true).unwrap();
+            assert_eq!(installed, dir.join(expected).join("example-mod-1.0.jar"), "{server_type}");
+            assert_eq!(std::fs::read_to_string(installed).unwrap(), "mod");
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_install_mod_refuses_duplicates_and_vanilla() {
+        let dir = temp_server("mods-duplicate");
+        let jar = dir.join("example-plugin.jar");
+        std::fs::write(&jar, "v1").unwrap();
+        let mut manager = manager(&dir, &RecordingHandler::default());
+
+        manager.install_mod(&jar, false).unwrap();
+        std::fs::write(&jar, "v2").unwrap();
+        assert!(matches!(
+            manager.install_mod(&jar, false),
+            Err(McServerError::AlreadyInstalled(_))
+        ));
+        let installed = manager.install_mod(&jar, true).unwrap();
+        assert_eq!(std::fs::read_to_string(installed).unwrap(), "v2");
+
+        manager.config_mut().server_type = ServerType::Vanilla;
+        assert!(matches!(
+            manager.install_mod(&jar, false),
+            Err(McServerError::InvalidConfig(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_upgrade_restores_previous_install() {
+        let dir = temp_server("rollback");
+        let log = RecordingHandler::default();
+        let mut manager = manager(&dir, &log);
+
+        let result = manager
+            .upgrade_with("1.21.4", async |config: &mut ServerConfig, _: &RecordingHandler| {
+                std::fs::write(config.directory.join("paper-1.21.3-82.jar"), "clobbered").unwrap();
+                std::fs::write(config.directory.join("paper-1.21.4-100.jar"), "partial").unwrap();
+                Err(McServerError::InstallFailed("download failed".to_string()))
+            })
+            .await;
+
+        assert!(matches!(result, Err(McServerError::InstallFailed(_))));
+        assert_eq!(
+            *log.0.lock().unwrap(),
+            ["backingup", "installing", "rolled back"]
+        );
+        assert_eq!(manager.config().minecraft_version, "1.21.3");
+        assert_eq!(manager.config().loader_version.as_deref(), Some("82"));
+        assert_eq!(
+            std::fs::read_to_string(dir.join("paper-1.21.3-82.jar")).unwrap(),
+            "old jar"
+        );
+        assert!(!dir.join("paper-1.21.4-100.jar").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -0,0 +1,12 @@
+//! Fixtures shared by the unit tests
+
+use std::path::PathBuf;
+
+/// An empty directory for a test, named after `name` and the process id so concurrent test runs
+/// don't share it. Whatever an earlier run left there is removed first.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mcserver-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,158 @@
+use crate::models::ServerType;
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Server performance as reported by the server's own tick statistics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TpsReport {
+    /// Mean ticks per second (20 when the server keeps up).
+    pub tps: f64,
+    /// Mean milliseconds per tick, when the server reports it.
+    pub mspt: Option<f64>,
+}
+
+/// Paper/Spigot/Purpur `tps`: `TPS from last 1m, 5m, 15m: 20.0, 19.98, *20.0`
+static BUKKIT_TPS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"TPS from last 1m, 5m, 15m: \*?([\d.]+)").unwrap()
+});
+/// Forge `forge tps`: `Overall: Mean tick time: 1.234 ms. Mean TPS: 20.000`
+static FORGE_TPS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"Overall\s*: Mean tick time: ([\d.]+) ms\. Mean TPS: ([\d.]+)").unwrap()
+});
+/// NeoForge `neoforge tps`: `Overall: 20.000 TPS (1.234 ms/tick)`
+static NEOFORGE_TPS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"Overall\s*: ([\d.]+) TPS \(([\d.]+) ms/tick\)").unwrap()
+});
+/// Vanilla 1.20.3+ `tick query`: `Target tick rate: 20.0 per second.`
+static TICK_RATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"Target tick rate: ([\d.]+) per second").unwrap());
+/// Vanilla 1.20.3+ `tick query`: `Average time per tick: 1.2ms (Target: 50.0ms)`
+static TICK_TIME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"Average time per tick: ([\d.]+)ms").unwrap());
+/// Minecraft formatting codes, which some plugin servers leave in console output.
+static FORMATTING: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"§.").unwrap());
+
+/// Console command that reports tick statistics for a server type.
+///
+/// Vanilla and Fabric servers use `tick query`, which needs Minecraft 1.20.3 or newer.
+pub fn tps_command(server_type: &ServerType) -> Option<&'static str> {
+    match server_type {
+        ServerType::Paper | ServerType::Spigot | ServerType::Purpur => Some("tps"),
+        ServerType::Forge => Some("forge tps"),
+        ServerType::NeoForge => Some("neoforge tps"),
+        ServerType::Vanilla | ServerType::Fabric | ServerType::Quilt => Some("tick query"),
+        ServerType::Custom => None,
+    }
+}
+
+/// Parse the console output of [`tps_command`], returning `None` until a complete report is seen.
+pub fn parse_tps_output(server_type: &ServerType, lines: &[String]) -> Option<TpsReport> {
+    let text = FORMATTING.replace_all(&lines.join("\n"), "").into_owned();
+    let number = |regex: &Regex, group: usize| -> Option<f64> {
+        regex.captures(&text)?.get(group)?.as_str().parse().ok()
+    };
+
+    match server_type {
+        ServerType::Paper | ServerType::Spigot | ServerType::Purpur => Some(TpsReport {
+            tps: number(&BUKKIT_TPS, 1)?,
+            mspt: None,
+        }),
+        ServerType::Forge | ServerType::NeoForge => {
+            if let (Some(mspt), Some(tps)) = (number(&FORGE_TPS, 1), number(&FORGE_TPS, 2)) {
+                return Some(TpsReport { tps, mspt: Some(mspt) });
+            }
+            Some(TpsReport {
+                tps: number(&NEOFORGE_TPS, 1)?,
+                mspt: Some(number(&NEOFORGE_TPS, 2)?),
+            })
+        }
+        ServerType::Vanilla | ServerType::Fabric | ServerType::Quilt => {
+            let target = number(&TICK_RATE, 1)?;
+            let mspt = number(&TICK_TIME, 1)?;
+            // A tick rate above what the tick time allows means the server is behind
+            let tps = if mspt > 0.0 { target.min(1000.0 / mspt) } else { target };
+            Some(TpsReport {
+                tps,
+                mspt: Some(mspt),
+            })
+        }
+        ServerType::Custom => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(output: &str) -> Vec<String> {
+        output.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_paper_tps() {
+        let output = lines("[12:00:00 INFO]: §6TPS from last 1m, 5m, 15m: §a*20.0, §a19.87, §a19.95");
+        assert_eq!(
+            parse_tps_output(&ServerType::Paper, &output),
+            Some(TpsReport {
+                tps: 20.0,
+                mspt: None
+            })
+        );
+    }
+
+    #[test]
+    fn test_forge_tps() {
+        let output = lines(
+            "[12:00:00] [Server thread/INFO] [minecraft/MinecraftServer]: Dim minecraft:overworld (minecraft:overworld): Mean tick time: 12.500 ms. Mean TPS: 20.000
+[12:00:00] [Server thread/INFO] [minecraft/MinecraftServer]: Dim minecraft:the_nether (minecraft:the_nether): Mean tick time: 0.400 ms. Mean TPS: 20.000
+[12:00:00] [Server thread/INFO] [minecraft/MinecraftServer]: Overall : Mean tick time: 62.500 ms. Mean TPS: 16.000",
+        );
+        assert_eq!(
+            parse_tps_output(&ServerType::Forge, &output),
+            Some(TpsReport {
+                tps: 16.0,
+                mspt: Some(62.5)
+            })
+        );
+        // Dimension lines alone are not a complete report
+        assert_eq!(parse_tps_output(&ServerType::Forge, &output[..2]), None);
+    }
+
+    #[test]
+    fn test_neoforge_tps() {
+        let output = lines(
+            "[12:00:00] [Server thread/INFO] [minecraft/MinecraftServer]: minecraft:overworld: 20.000 TPS (3.210 ms/tick)
+[12:00:00] [Server thread/INFO] [minecraft/MinecraftServer]: Overall: 20.000 TPS (3.456 ms/tick)",
+        );
+        assert_eq!(
+            parse_tps_output(&ServerType::NeoForge, &output),
+            Some(TpsReport {
+                tps: 20.0,
+                mspt: Some(3.456)
+            })
+        );
+    }
+
+    #[test]
+    fn test_vanilla_tick_query() {
+        let output = lines(
+            "[12:00:00] [Server thread/INFO]: The game is running normally
+[12:00:00] [Server thread/INFO]: Target tick rate: 20.0 per second.
+[12:00:00] [Server thread/INFO]: Average time per tick: 80.0ms (Target: 50.0ms)",
+        );
+        assert_eq!(
+            parse_tps_output(&ServerType::Vanilla, &output),
+            Some(TpsReport {
+                tps: 12.5,
+                mspt: Some(80.0)
+            })
+        );
+    }
+
+    #[test]
+    fn test_unrelated_output() {
+        let output = lines("[12:00:00] [Server thread/INFO]: Unknown command");
+        assert_eq!(parse_tps_output(&ServerType::Paper, &output), None);
+        assert_eq!(tps_command(&ServerType::Custom), None);
+    }
+}
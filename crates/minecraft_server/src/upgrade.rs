@@ -0,0 +1,139 @@
+use crate::properties::ServerProperties;
+use crate::Result;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Directory inside the server directory that holds pre-upgrade snapshots.
+pub const SNAPSHOT_DIR: &str = ".upgrade-backups";
+
+/// World directories of the server: the overworld named by `level-name` and,
+/// on Bukkit-based servers, its separate nether and end folders.
+pub fn world_directories(server_dir: &Path) -> Vec<PathBuf> {
+    let level_name = ServerProperties::load_from_dir(server_dir)
+        .unwrap_or_default()
+        .level_name();
+    [
+        level_name.clone(),
+        format!("{}_nether", level_name),
+        format!("{}_the_end", level_name),
+    ]
+    .into_iter()
+    .map(|name| server_dir.join(name))
+    .filter(|dir| dir.is_dir())
+    .collect()
+}
+
+/// Copy the server's top-level files (JARs and config) and its worlds into
+/// `<server_dir>/.upgrade-backups/<label>-<timestamp>`, returning the snapshot directory.
+pub fn snapshot(server_dir: &Path, label: &str) -> Result<PathBuf> {
+    let destination = server_dir.join(SNAPSHOT_DIR).join(format!(
+        "{}-{}",
+        label,
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::create_dir_all(&destination)?;
+
+    for entry in std::fs::read_dir(server_dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            std::fs::copy(entry.path(), destination.join(entry.file_name()))?;
+        }
+    }
+    for world in world_directories(server_dir) {
+        if let Some(name) = world.file_name() {
+            copy_dir(&world, &destination.join(name))?;
+        }
+    }
+    Ok(destination)
+}
+
+/// Put the top-level files of `snapshot` back into `server_dir`, removing
+/// top-level files created since the snapshot was taken.
+pub fn restore_files(snapshot: &Path, server_dir: &Path) -> Result<()> {
+    let mut saved = HashSet::new();
+    for entry in std::fs::read_dir(snapshot)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            std::fs::copy(entry.path(), server_dir.join(entry.file_name()))?;
+            saved.insert(entry.file_name());
+        }
+    }
+    for entry in std::fs::read_dir(server_dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() && !saved.contains(&entry.file_name()) {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn copy_dir(source: &Path, destination: &Path) -> Result<()> {
+    std::fs::create_dir_all(destination)?;
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let target = destination.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    fn temp_server(name: &str) -> PathBuf {
+        let dir = temp_dir(&format!("upgrade-{}", name));
+        std::fs::create_dir_all(dir.join("survival/region")).unwrap();
+        std::fs::create_dir_all(dir.join("survival_nether")).unwrap();
+        std::fs::create_dir_all(dir.join("world/region")).unwrap();
+        std::fs::write(dir.join("server.properties"), "level-name=survival\n").unwrap();
+        std::fs::write(dir.join("server.jar"), "old jar").unwrap();
+        std::fs::write(dir.join("survival/level.dat"), "level").unwrap();
+        std::fs::write(dir.join("survival/region/r.0.0.mca"), "region").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_world_directories_follow_level_name() {
+        let dir = temp_server("worlds");
+        assert_eq!(
+            world_directories(&dir),
+            vec![dir.join("survival"), dir.join("survival_nether")]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_copies_files_and_world() {
+        let dir = temp_server("snapshot");
+        let snapshot = snapshot(&dir, "1.21.4").unwrap();
+        assert!(snapshot.starts_with(dir.join(SNAPSHOT_DIR)));
+        assert_eq!(std::fs::read_to_string(snapshot.join("server.jar")).unwrap(), "old jar");
+        assert_eq!(
+            std::fs::read_to_string(snapshot.join("survival/region/r.0.0.mca")).unwrap(),
+            "region"
+        );
+        assert!(snapshot.join("survival_nether").is_dir());
+        assert!(!snapshot.join("world").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_restore_files_undoes_install() {
+        let dir = temp_server("restore");
+        let snapshot = snapshot(&dir, "1.21.4").unwrap();
+        std::fs::write(dir.join("server.jar"), "broken jar").unwrap();
+        std::fs::write(dir.join("new-server.jar"), "new jar").unwrap();
+
+        restore_files(&snapshot, &dir).unwrap();
+        assert_eq!(std::fs::read_to_string(dir.join("server.jar")).unwrap(), "old jar");
+        assert!(!dir.join("new-server.jar").exists());
+        assert!(dir.join("survival/level.dat").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,122 @@
+use crate::error::McServerError;
+use crate::Result;
+use serde::Serialize;
+use std::collections::HashSet;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+/// CPU and memory usage of a server process and everything it spawned.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResourceUsage {
+    /// CPU usage in percent of a single core, so it can exceed 100 on multi-core hosts.
+    pub cpu_percent: f32,
+    /// Resident memory in bytes.
+    pub memory_bytes: u64,
+    /// Time since the root process started, in seconds.
+    pub uptime_secs: u64,
+    /// Number of processes included in the totals (the root process plus its descendants).
+    pub process_count: usize,
+}
+
+/// Measure the resource usage of process `pid` and all of its descendants.
+///
+/// CPU usage is computed from two samples taken
+/// [`MINIMUM_CPU_UPDATE_INTERVAL`](sysinfo::MINIMUM_CPU_UPDATE_INTERVAL) apart,
+/// so this takes at least that long to return.
+pub async fn process_tree_usage(pid: u32) -> Result<ResourceUsage> {
+    let refresh_kind = ProcessRefreshKind::nothing()
+        .with_cpu()
+        .with_memory()
+        .without_tasks();
+    let mut system = System::new();
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, refresh_kind);
+    tokio::time::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL).await;
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, refresh_kind);
+
+    let root_pid = Pid::from_u32(pid);
+    let root = system
+        .process(root_pid)
+        .ok_or(McServerError::ProcessNotFound)?;
+
+    let mut usage = ResourceUsage {
+        cpu_percent: 0.0,
+        memory_bytes: 0,
+        uptime_secs: root.run_time(),
+        process_count: 0,
+    };
+    for process in descendants(&system, root_pid)
+        .iter()
+        .filter_map(|pid| system.process(*pid))
+        .filter(|process| process.thread_kind().is_none())
+    {
+        usage.cpu_percent += process.cpu_usage();
+        usage.memory_bytes += process.memory();
+        usage.process_count += 1;
+    }
+    Ok(usage)
+}
+
+/// Collect `root` and the PIDs of all processes descended from it.
+fn descendants(system: &System, root: Pid) -> HashSet<Pid> {
+    let mut tree = HashSet::from([root]);
+    // Repeat until no new children are found, since the process map is unordered
+    loop {
+        let before = tree.len();
+        for (pid, process) in system.processes() {
+            if process.parent().is_some_and(|parent| tree.contains(&parent)) {
+                tree.insert(*pid);
+            }
+        }
+        if tree.len() == before {
+            return tree;
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::{Child, Command};
+
+    /// Kills the dummy process when the test ends, even on panic.
+    struct DummyProcess(Child);
+
+    impl Drop for DummyProcess {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    fn spawn(script: &str) -> DummyProcess {
+        DummyProcess(Command::new("sh").arg("-c").arg(script).spawn().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_usage_of_running_process() {
+        let dummy = spawn("sleep 10");
+        let usage = process_tree_usage(dummy.0.id()).await.unwrap();
+        assert!(usage.memory_bytes > 0);
+        assert!(usage.process_count >= 1);
+        assert!(usage.cpu_percent >= 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_usage_includes_child_processes() {
+        let dummy = spawn("sleep 10 & sleep 10 & wait");
+        // Give the shell a moment to fork its children
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+        let usage = process_tree_usage(dummy.0.id()).await.unwrap();
+        assert_eq!(usage.process_count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_process_is_not_found() {
+        let mut child = Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+
+        let result = process_tree_usage(pid).await;
+        assert!(matches!(result, Err(McServerError::ProcessNotFound)));
+    }
+}
@@ -0,0 +1,212 @@
+use crate::mojang::MojangClient;
+use crate::player_list::{self, PlayerEntry};
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const WHITELIST_FILENAME: &str = "whitelist.json";
+
+/// A player in `whitelist.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WhitelistEntry {
+    /// Hyphenated UUID.
+    pub uuid: String,
+    pub name: String,
+}
+
+impl PlayerEntry for WhitelistEntry {
+    fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The whitelist of a server directory, backed by its `whitelist.json`.
+///
+/// Changes are only written to disk by [`save`](Self::save). To also update a running
+/// server, use [`ServerManager::whitelist_add`](crate::ServerManager::whitelist_add) and
+/// [`ServerManager::whitelist_remove`](crate::ServerManager::whitelist_remove).
+#[derive(Debug, Clone)]
+pub struct Whitelist {
+    path: PathBuf,
+    entries: Vec<WhitelistEntry>,
+    client: MojangClient,
+}
+
+impl Whitelist {
+    /// Load `whitelist.json` from a server directory. A missing file is an empty whitelist.
+    pub fn load(server_dir: &Path) -> Result<Self> {
+        let path = server_dir.join(WHITELIST_FILENAME);
+        let entries = player_list::load_list(&path)?;
+        Ok(Self {
+            path,
+            entries,
+            client: MojangClient::default(),
+        })
+    }
+
+    /// Use a custom Mojang client for name resolution.
+    pub fn with_client(mut self, client: MojangClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Whitelisted players.
+    pub fn list(&self) -> &[WhitelistEntry] {
+        &self.entries
+    }
+
+    /// Whether a player (by name or UUID) is whitelisted.
+    pub fn contains(&self, name_or_uuid: &str) -> bool {
+        self.position(name_or_uuid).is_some()
+    }
+
+    /// Add a player by name or UUID, resolving the other via Mojang's API.
+    ///
+    /// Adding a player that is already whitelisted returns the existing entry.
+    pub async fn add(&mut self, name_or_uuid: &str) -> Result<WhitelistEntry> {
+        if let Some(index) = self.position(name_or_uuid) {
+            return Ok(self.entries[index].clone());
+        }
+        let profile = self.client.resolve(name_or_uuid).await?;
+        let entry = WhitelistEntry {
+            uuid: profile.uuid,
+            name: profile.name,
+        };
+        if !self.contains(&entry.uuid) {
+            self.entries.push(entry.clone());
+        }
+        Ok(entry)
+    }
+
+    /// Remove a player by name or UUID, returning the removed entry.
+    pub fn remove(&mut self, name_or_uuid: &str) -> Option<WhitelistEntry> {
+        let index = self.position(name_or_uuid)?;
+        Some(self.entries.remove(index))
+    }
+
+    /// Write the whitelist back to `whitelist.json`.
+    pub fn save(&self) -> Result<()> {
+        player_list::save_list(&self.path, &self.entries)
+    }
+
+    fn position(&self, name_or_uuid: &str) -> Option<usize> {
+        player_list::position(&self.entries, name_or_uuid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const NOTCH_UUID: &str = "069a79f4-44e9-4726-a5be-fca90e38aaf5";
+
+    async fn mock_mojang() -> (MockServer, MojangClient) {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/users/profiles/minecraft/Notch"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "069a79f444e94726a5befca90e38aaf5",
+                "name": "Notch"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/session/minecraft/profile/069a79f444e94726a5befca90e38aaf5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "069a79f444e94726a5befca90e38aaf5",
+                "name": "Notch",
+                "properties": []
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/users/profiles/minecraft/NobodyHere"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        let client = MojangClient::with_base_urls(server.uri(), server.uri());
+        (server, client)
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let dir = temp_dir("whitelist-round-trip");
+        std::fs::write(
+            dir.join(WHITELIST_FILENAME),
+            format!(r#"[{{"uuid": "{}", "name": "Notch"}}]"#, NOTCH_UUID),
+        )
+        .unwrap();
+
+        let mut whitelist = Whitelist::load(&dir).unwrap();
+        assert_eq!(whitelist.list().len(), 1);
+        assert!(whitelist.contains("notch"));
+        assert!(whitelist.contains(&NOTCH_UUID.replace('-', "")));
+
+        whitelist.save().unwrap();
+        let reloaded = Whitelist::load(&dir).unwrap();
+        assert_eq!(reloaded.list(), whitelist.list());
+
+        assert!(whitelist.remove("Notch").is_some());
+        whitelist.save().unwrap();
+        assert!(Whitelist::load(&dir).unwrap().list().is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_file_is_empty() {
+        let dir = temp_dir("whitelist-missing");
+        assert!(Whitelist::load(&dir).unwrap().list().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_add_resolves_name_to_uuid() {
+        let (_server, client) = mock_mojang().await;
+        let dir = temp_dir("whitelist-add-name");
+
+        let mut whitelist = Whitelist::load(&dir).unwrap().with_client(client);
+        let entry = whitelist.add("Notch").await.unwrap();
+        assert_eq!(entry.uuid, NOTCH_UUID);
+        assert_eq!(entry.name, "Notch");
+
+        // Adding again by UUID does not duplicate the entry
+        whitelist.add(NOTCH_UUID).await.unwrap();
+        assert_eq!(whitelist.list().len(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_add_resolves_uuid_to_name() {
+        let (_server, client) = mock_mojang().await;
+        let dir = temp_dir("whitelist-add-uuid");
+
+        let mut whitelist = Whitelist::load(&dir).unwrap().with_client(client);
+        let entry = whitelist.add("069A79F444E94726A5BEFCA90E38AAF5").await.unwrap();
+        assert_eq!(entry.uuid, NOTCH_UUID);
+        assert_eq!(entry.name, "Notch");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_add_unknown_player_fails() {
+        let (_server, client) = mock_mojang().await;
+        let dir = temp_dir("whitelist-add-unknown");
+
+        let mut whitelist = Whitelist::load(&dir).unwrap().with_client(client);
+        let result = whitelist.add("NobodyHere").await;
+        assert!(matches!(result, Err(crate::McServerError::PlayerNotFound(_))));
+        assert!(whitelist.list().is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -4,6 +4,7 @@ use crate::models::*;
 use crate::search::SearchParams;
 use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
 use serde::de::DeserializeOwned;
+use std::collections::HashMap;
 use std::time::Duration;
 
 const DEFAULT_BASE_URL: &str = "https://api.modrinth.com/v2";
@@ -138,6 +139,43 @@ impl ModrinthClient {
         Ok(value)
     }
 
+    /// Performs a POST request with a JSON body and deserializes the response.
+    async fn post_json<T: DeserializeOwned, B: serde::Serialize>(
+        &self,
+        url: &str,
+        body: &B,
+    ) -> Result<T> {
+        let response = self.http.post(url).json(body).send().await?;
+        let status = response.status();
+
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get("x-ratelimit-reset")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(60);
+            return Err(ModrinthError::RateLimited {
+                retry_after_ms: retry_after * 1000,
+            });
+        }
+
+        if !status.is_success() {
+            let message = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(ModrinthError::Api {
+                status: status.as_u16(),
+                message,
+            });
+        }
+
+        let body = response.text().await?;
+        let value: T = serde_json::from_str(&body)?;
+        Ok(value)
+    }
+
     /// Searches for projects matching the given parameters.
     pub async fn search(&self, params: &SearchParams) -> Result<SearchResult> {
         let cache_key = params.cache_key();
@@ -270,6 +308,28 @@ impl ModrinthClient {
         Ok(versions)
     }
 
+    /// Finds the latest version of the project each file belongs to, by the files' SHA-1 hashes.
+    ///
+    /// Only versions for one of `loaders` and `game_versions` are considered; an empty list
+    /// allows any. The result maps each hash to its project's latest version and leaves out
+    /// hashes Modrinth doesn't know. Results are not cached.
+    pub async fn get_latest_versions_from_hashes(
+        &self,
+        hashes: &[&str],
+        loaders: &[&str],
+        game_versions: &[&str],
+    ) -> Result<HashMap<String, Version>> {
+        let url = format!("{}/version_files/update", self.base_url);
+        let mut body = serde_json::json!({ "hashes": hashes, "algorithm": "sha1" });
+        if !loaders.is_empty() {
+            body["loaders"] = serde_json::json!(loaders);
+        }
+        if !game_versions.is_empty() {
+            body["game_versions"] = serde_json::json!(game_versions);
+        }
+        self.post_json(&url, &body).await
+    }
+
     /// Fetches all available project categories.
     pub async fn get_categories(&self) -> Result<Vec<Category>> {
         let cache_key = "categories".to_string();
@@ -1,5 +1,5 @@
 use modrinth::{ModrinthClient, SearchBuilder};
-use wiremock::matchers::{method, path};
+use wiremock::matchers::{body_json, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 fn search_result_json() -> serde_json::Value {
@@ -316,3 +316,37 @@ async fn test_get_projects_batch() {
     assert_eq!(result.len(), 1);
     assert_eq!(result[0].title, "Sodium");
 }
+
+#[tokio::test]
+async fn test_get_latest_versions_from_hashes() {
+    let mock_server = MockServer::start().await;
+
+    // Only the first hash is known; the version it maps to is the latest one for the filters
+    let latest = versions_json()[0].clone();
+    Mock::given(method("POST"))
+        .and(path("/v2/version_files/update"))
+        .and(body_json(serde_json::json!({
+            "hashes": ["da39a3ee5e6b4b0d3255bfef95601890afd80709", "0000000000000000000000000000000000000000"],
+            "algorithm": "sha1",
+            "loaders": ["fabric"],
+            "game_versions": ["1.20.4"],
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "da39a3ee5e6b4b0d3255bfef95601890afd80709": latest,
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = ModrinthClient::with_base_url(format!("{}/v2", mock_server.uri()));
+    let result = client
+        .get_latest_versions_from_hashes(
+            &["da39a3ee5e6b4b0d3255bfef95601890afd80709", "0000000000000000000000000000000000000000"],
+            &["fabric"],
+            &["1.20.4"],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(result["da39a3ee5e6b4b0d3255bfef95601890afd80709"].version_number, "mc1.20.4-0.5.8");
+}
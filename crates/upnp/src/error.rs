@@ -1,3 +1,6 @@
+use crate::manager::PortMapping;
+use std::net::IpAddr;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Errors that can occur during UPnP operations.
@@ -11,6 +14,37 @@ pub enum UpnpError {
     #[error("port {0} is already mapped")]
     PortAlreadyMapped(u16),
 
+    /// The external port is already forwarded to another host on the network.
+    #[error(
+        "port {} ({}) is already forwarded to another host",
+        .existing.port,
+        .existing.protocol
+    )]
+    Conflict { existing: PortMapping },
+
+    /// No UPnP gateway could be found on the local network.
+    #[error("gateway discovery failed: {0}")]
+    DiscoveryFailed(String),
+
+    /// A network error interrupted gateway discovery, e.g. a lost or malformed
+    /// SSDP response. Unlike [`DiscoveryFailed`](Self::DiscoveryFailed), trying
+    /// again may succeed.
+    #[error("gateway discovery interrupted: {0}")]
+    DiscoveryInterrupted(String),
+
+    /// Gateway discovery did not finish within the manager's discovery timeout.
+    #[error("gateway discovery timed out after {0:?}")]
+    DiscoveryTimeout(Duration),
+
+    /// The gateway reported a private or placeholder external address, which
+    /// usually means the router is itself behind another NAT (e.g. carrier-grade NAT).
+    #[error("gateway reported non-public external address {0}")]
+    NonPublicExternalIp(IpAddr),
+
+    /// The gateway's protocol cannot perform the operation.
+    #[error("{0} is not supported by the gateway")]
+    Unsupported(String),
+
     /// A UPnP network operation failed (add or delete ports).
     #[error("UPnP operation failed: {0}")]
     UpnpOperationFailed(String),
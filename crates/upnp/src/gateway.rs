@@ -0,0 +1,205 @@
+use crate::error::UpnpError;
+use crate::manager::PortMapping;
+use igd_next::{GetGenericPortMappingEntryError, PortMappingProtocol, SearchError, SearchOptions};
+use log::{debug, warn};
+use std::fmt;
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Retries [`UpnpManager::global`](crate::UpnpManager::global) makes when an
+/// SSDP search is interrupted.
+pub const DEFAULT_DISCOVERY_RETRIES: u32 = 2;
+
+/// Pause between discovery attempts of a [`RetryDiscovery`].
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// The protocol a gateway forwards ports with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GatewayProtocol {
+    /// UPnP Internet Gateway Device.
+    Upnp,
+    /// NAT Port Mapping Protocol, see [`NatPmpGateway`](crate::nat_pmp::NatPmpGateway).
+    NatPmp,
+}
+
+impl fmt::Display for GatewayProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GatewayProtocol::Upnp => "UPnP",
+            GatewayProtocol::NatPmp => "NAT-PMP",
+        })
+    }
+}
+
+/// Port-forwarding operations on the router's Internet Gateway Device.
+///
+/// Implemented for [`igd_next::Gateway`]. The methods perform blocking network
+/// I/O, so [`UpnpManager`](crate::UpnpManager) calls them from `spawn_blocking`.
+pub trait Gateway: Send + Sync {
+    /// Forward `mapping.port` on the router to `mapping.internal_port` on
+    /// `mapping.internal_address`, or on this machine if no address is set.
+    fn add_mapping(&self, mapping: &PortMapping) -> Result<(), UpnpError>;
+
+    /// Stop forwarding an external port.
+    fn remove_mapping(&self, port: u16, protocol: PortMappingProtocol) -> Result<(), UpnpError>;
+
+    /// Read one entry of the router's port-mapping table (`GetGenericPortMappingEntry`).
+    ///
+    /// Returns `None` once `index` is past the last entry.
+    fn mapping_entry(&self, index: u32) -> Result<Option<PortMapping>, UpnpError>;
+
+    /// The router's address on the internet side (`GetExternalIPAddress`).
+    fn external_ip(&self) -> Result<IpAddr, UpnpError>;
+
+    /// This machine's address on the network shared with the router, which
+    /// mappings without an `internal_address` forward to.
+    fn local_address(&self) -> Result<IpAddr, UpnpError>;
+
+    /// The protocol this gateway speaks.
+    fn protocol(&self) -> GatewayProtocol {
+        GatewayProtocol::Upnp
+    }
+}
+
+/// Locates the gateway that port mappings are sent to.
+pub trait Discovery: Send + Sync {
+    /// Find the gateway. Blocks until one answers or the search gives up.
+    fn discover(&self) -> Result<Arc<dyn Gateway>, UpnpError>;
+}
+
+/// Finds the router with an SSDP search on the local network.
+pub struct SsdpDiscovery;
+
+impl Discovery for SsdpDiscovery {
+    fn discover(&self) -> Result<Arc<dyn Gateway>, UpnpError> {
+        let gateway = igd_next::search_gateway(SearchOptions::default()).map_err(|e| match e {
+            SearchError::NoResponseWithinTimeout => UpnpError::DiscoveryFailed(e.to_string()),
+            e => UpnpError::DiscoveryInterrupted(e.to_string()),
+        })?;
+        Ok(Arc::new(gateway))
+    }
+}
+
+/// Searches again, up to `retries` times, when a search is interrupted by a
+/// network error such as a lost SSDP response.
+///
+/// A search that completes without finding a gateway is not repeated.
+pub struct RetryDiscovery<D> {
+    inner: D,
+    retries: u32,
+}
+
+impl<D: Discovery> RetryDiscovery<D> {
+    pub fn new(inner: D, retries: u32) -> Self {
+        Self { inner, retries }
+    }
+}
+
+impl<D: Discovery> Discovery for RetryDiscovery<D> {
+    fn discover(&self) -> Result<Arc<dyn Gateway>, UpnpError> {
+        let mut attempt = 0;
+        loop {
+            match self.inner.discover() {
+                Err(UpnpError::DiscoveryInterrupted(reason)) if attempt < self.retries => {
+                    attempt += 1;
+                    warn!(
+                        "Gateway discovery interrupted ({reason}), retrying ({attempt}/{})",
+                        self.retries
+                    );
+                    std::thread::sleep(RETRY_DELAY);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Tries `primary` first and `fallback` if it finds no gateway, e.g. SSDP
+/// followed by NAT-PMP on routers with UPnP disabled.
+pub struct FallbackDiscovery<P, F> {
+    primary: P,
+    fallback: F,
+}
+
+impl<P: Discovery, F: Discovery> FallbackDiscovery<P, F> {
+    pub fn new(primary: P, fallback: F) -> Self {
+        Self { primary, fallback }
+    }
+}
+
+impl<P: Discovery, F: Discovery> Discovery for FallbackDiscovery<P, F> {
+    fn discover(&self) -> Result<Arc<dyn Gateway>, UpnpError> {
+        match self.primary.discover() {
+            Err(UpnpError::DiscoveryFailed(reason) | UpnpError::DiscoveryInterrupted(reason)) => {
+                debug!("Primary gateway discovery failed ({reason}), trying fallback");
+                self.fallback.discover().map_err(|e| match e {
+                    UpnpError::DiscoveryFailed(fallback_reason) => {
+                        UpnpError::DiscoveryFailed(format!("{reason}; {fallback_reason}"))
+                    }
+                    e => e,
+                })
+            }
+            result => result,
+        }
+    }
+}
+
+impl Gateway for igd_next::Gateway {
+    fn add_mapping(&self, mapping: &PortMapping) -> Result<(), UpnpError> {
+        let internal_address = match mapping.internal_address {
+            Some(address) => address,
+            None => self.local_address()?,
+        };
+        self.add_port(
+            mapping.protocol,
+            mapping.port,
+            SocketAddr::new(internal_address, mapping.internal_port),
+            mapping.lease_duration,
+            &mapping.description,
+        )
+        .map_err(|e| {
+            UpnpError::UpnpOperationFailed(format!("failed to forward port {}: {e}", mapping.port))
+        })
+    }
+
+    fn remove_mapping(&self, port: u16, protocol: PortMappingProtocol) -> Result<(), UpnpError> {
+        self.remove_port(protocol, port).map_err(|e| {
+            UpnpError::UpnpOperationFailed(format!("failed to delete port {port}: {e}"))
+        })
+    }
+
+    fn mapping_entry(&self, index: u32) -> Result<Option<PortMapping>, UpnpError> {
+        match self.get_generic_port_mapping_entry(index) {
+            Ok(entry) => Ok(Some(PortMapping {
+                port: entry.external_port,
+                description: entry.port_mapping_description,
+                protocol: entry.protocol,
+                internal_address: entry.internal_client.parse().ok(),
+                internal_port: entry.internal_port,
+                lease_duration: entry.lease_duration,
+            })),
+            Err(GetGenericPortMappingEntryError::SpecifiedArrayIndexInvalid) => Ok(None),
+            Err(e) => Err(UpnpError::UpnpOperationFailed(format!(
+                "failed to read port mapping {index}: {e}"
+            ))),
+        }
+    }
+
+    fn external_ip(&self) -> Result<IpAddr, UpnpError> {
+        self.get_external_ip().map_err(|e| {
+            UpnpError::UpnpOperationFailed(format!("failed to get external IP: {e}"))
+        })
+    }
+
+    fn local_address(&self) -> Result<IpAddr, UpnpError> {
+        // Connecting a UDP socket only selects a route; nothing is sent
+        let socket = UdpSocket::bind(("0.0.0.0", 0))
+            .and_then(|socket| socket.connect(self.addr).map(|()| socket))
+            .map_err(|e| UpnpError::UpnpOperationFailed(format!("no route to gateway: {e}")))?;
+        socket
+            .local_addr()
+            .map(|address| address.ip())
+            .map_err(|e| UpnpError::UpnpOperationFailed(format!("no route to gateway: {e}")))
+    }
+}
@@ -1,11 +1,19 @@
 //! # obsidian-upnp
 //!
 //! An async-first wrapper for UPnP port forwarding with automatic lease
-//! renewal via `obsidian-scheduler`.
+//! renewal via `obsidian-scheduler`, falling back to NAT-PMP on routers
+//! with UPnP disabled.
 
 pub mod error;
+pub mod gateway;
 pub mod manager;
+pub mod nat_pmp;
 
-pub use easy_upnp::PortMappingProtocol;
 pub use error::UpnpError;
-pub use manager::{PortMapping, UpnpManager};
+pub use gateway::{
+    DEFAULT_DISCOVERY_RETRIES, Discovery, FallbackDiscovery, Gateway, GatewayProtocol,
+    RetryDiscovery, SsdpDiscovery,
+};
+pub use igd_next::PortMappingProtocol;
+pub use manager::{DEFAULT_DISCOVERY_TIMEOUT, PortMapping, UpnpManager};
+pub use nat_pmp::NatPmpDiscovery;
@@ -1,9 +1,16 @@
 use crate::error::UpnpError;
-use easy_upnp::PortMappingProtocol;
-use log::{debug, error, info, trace};
+use crate::gateway::{
+    DEFAULT_DISCOVERY_RETRIES, Discovery, FallbackDiscovery, Gateway, GatewayProtocol,
+    RetryDiscovery, SsdpDiscovery,
+};
+use crate::nat_pmp::NatPmpDiscovery;
+use igd_next::PortMappingProtocol;
+use log::{debug, error, info, trace, warn};
 use obsidian_scheduler::callback::CallbackTimer;
 use obsidian_scheduler::timer_trait::Timer;
+use std::net::IpAddr;
 use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
 /// Renewal interval: how often port leases are refreshed.
@@ -13,18 +20,81 @@ const RENEWAL_INTERVAL_SECS: u64 = 300; // 5 minutes
 /// to provide a safety buffer if a renewal cycle is delayed.
 const LEASE_DURATION_SECS: u32 = 600; // 10 minutes
 
+/// Fraction of the lease after which [`UpnpManager::enable_auto_renew`] renews a mapping.
+const AUTO_RENEW_AT: f64 = 0.8;
+
+/// How long [`UpnpManager::new`] waits for gateway discovery: long enough for
+/// an SSDP search followed by the NAT-PMP fallback.
+pub const DEFAULT_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How long the gateway's external IP is cached before it is queried again.
+const EXTERNAL_IP_CACHE: Duration = Duration::from_secs(60);
+
+/// Upper bound on port-mapping table entries read, in case a router never reports the end.
+const MAX_MAPPING_ENTRIES: u32 = 1024;
+
 /// Represents a single active UPnP port mapping.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct PortMapping {
+    /// External port opened on the router.
     pub port: u16,
     pub description: String,
     pub protocol: PortMappingProtocol,
+    /// Host the router forwards to; `None` means this machine.
+    pub internal_address: Option<IpAddr>,
+    /// Port on the internal host that traffic is forwarded to.
+    pub internal_port: u16,
+    /// Lease in seconds; `0` means the mapping does not expire.
+    pub lease_duration: u32,
+}
+
+impl PortMapping {
+    /// A mapping that forwards `port` to the same port on this machine.
+    pub fn new(port: u16, description: impl Into<String>, protocol: PortMappingProtocol) -> Self {
+        Self {
+            port,
+            description: description.into(),
+            protocol,
+            internal_address: None,
+            internal_port: port,
+            lease_duration: LEASE_DURATION_SECS,
+        }
+    }
+
+    /// Set the lease in seconds; `0` asks for a mapping that does not expire.
+    pub fn with_lease_duration(mut self, lease_duration: u32) -> Self {
+        self.lease_duration = lease_duration;
+        self
+    }
 }
 
 /// Internal state holding all active port mappings and the renewal timer.
 struct UpnpState {
     ports: Vec<PortMapping>,
     renewal_timer: Option<Arc<CallbackTimer>>,
+    /// Mappings renewed on their own schedule instead of by `renewal_timer`.
+    auto_renewals: Vec<AutoRenewal>,
+    /// Last external IP reported by the gateway, and when it was fetched.
+    external_ip: Option<(IpAddr, Instant)>,
+}
+
+/// A mapping renewed on its own schedule, see [`UpnpManager::enable_auto_renew`].
+struct AutoRenewal {
+    port: u16,
+    protocol: PortMappingProtocol,
+    timer: Arc<CallbackTimer>,
+}
+
+impl AutoRenewal {
+    fn renews(&self, mapping: &PortMapping) -> bool {
+        self.port == mapping.port && self.protocol == mapping.protocol
+    }
+}
+
+/// A [`Discovery`] and how long to wait for it.
+struct GatewayFinder {
+    source: Box<dyn Discovery>,
+    timeout: Duration,
 }
 
 /// Thread-safe, async-first UPnP port manager.
@@ -33,9 +103,13 @@ struct UpnpState {
 /// via UPnP IGD. Port leases are automatically renewed every 5 minutes
 /// using a [`CallbackTimer`] from `obsidian-scheduler`.
 ///
+/// Every mapping the manager adds is tracked and deleted again by
+/// [`close`](Self::close), or on a best-effort basis when the manager is dropped.
+///
 /// Access the global singleton via [`UpnpManager::global()`].
 pub struct UpnpManager {
-    state: Mutex<UpnpState>,
+    state: Arc<Mutex<UpnpState>>,
+    discovery: Arc<GatewayFinder>,
 }
 
 static INSTANCE: OnceLock<UpnpManager> = OnceLock::new();
@@ -43,118 +117,246 @@ static INSTANCE: OnceLock<UpnpManager> = OnceLock::new();
 impl UpnpManager {
     /// Get the global `UpnpManager` instance, creating it on first call.
     pub fn global() -> &'static UpnpManager {
-        INSTANCE.get_or_init(|| UpnpManager {
-            state: Mutex::new(UpnpState {
-                ports: Vec::new(),
-                renewal_timer: None,
-            }),
+        INSTANCE.get_or_init(|| {
+            UpnpManager::new(FallbackDiscovery::new(
+                RetryDiscovery::new(SsdpDiscovery, DEFAULT_DISCOVERY_RETRIES),
+                NatPmpDiscovery,
+            ))
         })
     }
 
-    /// Register a port mapping with the router.
+    /// Create a manager that finds its gateway with `discovery`, waiting up to
+    /// [`DEFAULT_DISCOVERY_TIMEOUT`] for it.
     ///
-    /// The port is immediately forwarded via UPnP. If this is the first
-    /// active port, the automatic renewal timer is started.
+    /// Most callers should share the [`global`](Self::global) instance, which
+    /// discovers the router over SSDP, retrying interrupted searches, and falls
+    /// back to NAT-PMP.
+    pub fn new(discovery: impl Discovery + 'static) -> Self {
+        Self::with_discovery_timeout(discovery, DEFAULT_DISCOVERY_TIMEOUT)
+    }
+
+    /// Create a manager that gives up on finding its gateway after
+    /// `discovery_timeout`, failing the operation with
+    /// [`UpnpError::DiscoveryTimeout`].
     ///
-    /// Returns [`UpnpError::PortAlreadyMapped`] if the port is already tracked.
+    /// Searches on networks without UPnP can otherwise hang for a long time.
+    pub fn with_discovery_timeout(
+        discovery: impl Discovery + 'static,
+        discovery_timeout: Duration,
+    ) -> Self {
+        UpnpManager {
+            state: Arc::new(Mutex::new(UpnpState {
+                ports: Vec::new(),
+                renewal_timer: None,
+                auto_renewals: Vec::new(),
+                external_ip: None,
+            })),
+            discovery: Arc::new(GatewayFinder {
+                source: Box::new(discovery),
+                timeout: discovery_timeout,
+            }),
+        }
+    }
+
+    /// Register a port mapping with the router, forwarding `port` to the same
+    /// port on this machine. See [`add_mapping`](Self::add_mapping).
     pub async fn add_port(
         &self,
         port: u16,
         description: String,
         protocol: PortMappingProtocol,
-    ) -> Result<(), UpnpError> {
+    ) -> Result<GatewayProtocol, UpnpError> {
+        self.add_mapping(PortMapping::new(port, description, protocol))
+            .await
+    }
+
+    /// Register a port mapping with the router.
+    ///
+    /// The port is immediately forwarded by the gateway the manager's discovery
+    /// finds: UPnP for the [`global`](Self::global) instance, or NAT-PMP when no
+    /// UPnP gateway answers. The protocol used is returned. If this is the first
+    /// active mapping, the automatic renewal timer is started. TCP and UDP
+    /// mappings of the same port are separate and can be added side by side.
+    ///
+    /// Some routers refuse mappings that never expire. If one rejects a mapping
+    /// with a `lease_duration` of `0`, it is added again with a 10 minute lease
+    /// that is renewed on its own schedule, see
+    /// [`enable_auto_renew`](Self::enable_auto_renew).
+    ///
+    /// Returns [`UpnpError::PortAlreadyMapped`] if the port is already tracked
+    /// for the mapping's protocol.
+    pub async fn add_mapping(&self, mut mapping: PortMapping) -> Result<GatewayProtocol, UpnpError> {
+        let (port, protocol) = (mapping.port, mapping.protocol);
+
         // Phase 1: check for duplicate (short lock)
         {
             let state = self.state.lock().await;
-            if state.ports.iter().any(|p| p.port == port) {
+            if state.ports.iter().any(|p| p.port == port && p.protocol == protocol) {
                 return Err(UpnpError::PortAlreadyMapped(port));
             }
         }
 
         // Phase 2: blocking UPnP call (no lock held)
-        let desc_clone = description.clone();
-        tokio::task::spawn_blocking(move || {
-            let config = easy_upnp::UpnpConfig {
-                address: None,
-                port,
-                protocol,
-                duration: LEASE_DURATION_SECS,
-                comment: desc_clone,
-            };
-            for result in easy_upnp::add_ports(vec![config]) {
-                if let Err(e) = result {
-                    return Err(UpnpError::UpnpOperationFailed(format!(
-                        "failed to forward port {port}: {e}"
-                    )));
-                }
+        let gateway = discover(self.discovery.clone()).await?;
+        let gateway_protocol = gateway.protocol();
+        let finite_lease = match add_to_gateway(gateway.clone(), mapping.clone()).await {
+            Err(UpnpError::UpnpOperationFailed(reason)) if mapping.lease_duration == 0 => {
+                warn!(
+                    "Gateway refused an indefinite lease for port {port} ({reason}), \
+                     retrying with a {LEASE_DURATION_SECS}s lease"
+                );
+                mapping.lease_duration = LEASE_DURATION_SECS;
+                add_to_gateway(gateway, mapping.clone()).await?;
+                true
             }
-            Ok(())
-        })
-        .await
-        .map_err(|e| UpnpError::UpnpOperationFailed(e.to_string()))??;
+            result => result.map(|()| false)?,
+        };
 
         // Phase 3: store mapping and ensure renewal timer (short lock)
         {
             let mut state = self.state.lock().await;
             // Re-check in case of concurrent add
-            if !state.ports.iter().any(|p| p.port == port) {
-                state.ports.push(PortMapping {
-                    port,
-                    description,
-                    protocol,
-                });
+            if !state.ports.iter().any(|p| p.port == port && p.protocol == protocol) {
+                state.ports.push(mapping);
             }
             if state.renewal_timer.is_none() {
                 self.start_renewal_timer(&mut state).await?;
             }
         }
+        if finite_lease {
+            let lease = Duration::from_secs(LEASE_DURATION_SECS.into());
+            self.schedule_auto_renew(port, protocol, lease).await?;
+        }
 
-        info!("UPnP port {port} mapped successfully");
-        Ok(())
+        info!("Port {port} ({protocol}) mapped successfully via {gateway_protocol}");
+        Ok(gateway_protocol)
     }
 
-    /// Remove a port mapping from the router.
+    /// Register several port mappings, e.g. a server's game, query, and voice
+    /// chat ports, returning one result per mapping in the same order.
     ///
-    /// The port is deleted from the router on a best-effort basis (failures
-    /// are logged but not propagated). If this was the last active port,
-    /// the renewal timer is stopped.
+    /// Each mapping is added with [`add_mapping`](Self::add_mapping), so one that
+    /// fails doesn't stop the rest, and those that succeed are tracked for
+    /// renewal and cleanup like any other.
+    pub async fn add_mappings(
+        &self,
+        mappings: Vec<PortMapping>,
+    ) -> Vec<Result<PortMapping, UpnpError>> {
+        let mut results = Vec::with_capacity(mappings.len());
+        for mapping in mappings {
+            let (port, protocol) = (mapping.port, mapping.protocol);
+            let result = self.add_mapping(mapping.clone()).await.map(|_| mapping);
+            if let Err(e) = &result {
+                warn!("Failed to map port {port} ({protocol}): {e}");
+            }
+            results.push(result);
+        }
+        results
+    }
+
+    /// Register a port mapping like [`add_mapping`](Self::add_mapping), after
+    /// checking the router's table for a mapping of the same external port and
+    /// protocol that forwards to a different host.
+    ///
+    /// Returns [`UpnpError::Conflict`] with the existing mapping instead of
+    /// overwriting it, which some routers do silently and others refuse.
+    pub async fn add_mapping_checked(
+        &self,
+        mapping: PortMapping,
+    ) -> Result<GatewayProtocol, UpnpError> {
+        let gateway = discover(self.discovery.clone()).await?;
+        let request = mapping.clone();
+        let existing = tokio::task::spawn_blocking(move || {
+            let target = target_address(gateway.as_ref(), &request)?;
+            let mappings = read_mappings(gateway.as_ref())?;
+            Ok::<_, UpnpError>(mappings.into_iter().find(|m| {
+                m.port == request.port
+                    && m.protocol == request.protocol
+                    && m.internal_address != Some(target)
+            }))
+        })
+        .await
+        .map_err(|e| UpnpError::UpnpOperationFailed(e.to_string()))??;
+
+        if let Some(existing) = existing {
+            return Err(UpnpError::Conflict { existing });
+        }
+        self.add_mapping(mapping).await
+    }
+
+    /// Check that the router still forwards `mapping`, by looking for it in the
+    /// gateway's port-mapping table with the expected internal address and port.
+    ///
+    /// Returns `false` if the router dropped the mapping or points it elsewhere,
+    /// which some routers do despite accepting the request.
+    pub async fn verify_mapping(&self, mapping: &PortMapping) -> Result<bool, UpnpError> {
+        let gateway = discover(self.discovery.clone()).await?;
+        let expected = mapping.clone();
+        tokio::task::spawn_blocking(move || {
+            let target = target_address(gateway.as_ref(), &expected)?;
+            let mappings = read_mappings(gateway.as_ref())?;
+            Ok(mappings.iter().any(|m| {
+                m.port == expected.port
+                    && m.protocol == expected.protocol
+                    && m.internal_address == Some(target)
+                    && m.internal_port == expected.internal_port
+            }))
+        })
+        .await
+        .map_err(|e| UpnpError::UpnpOperationFailed(e.to_string()))?
+    }
+
+    /// Remove the mappings of a port from the router, for every protocol it
+    /// is mapped with. See [`remove_mapping`](Self::remove_mapping).
     ///
     /// Returns [`UpnpError::PortNotFound`] if the port is not tracked.
     pub async fn remove_port(&self, port: u16) -> Result<(), UpnpError> {
+        self.remove_tracked(port, None).await
+    }
+
+    /// Remove the mapping of a port for one protocol from the router.
+    ///
+    /// The port is deleted from the router on a best-effort basis (failures
+    /// are logged but not propagated). If this was the last active mapping,
+    /// the renewal timer is stopped.
+    ///
+    /// Returns [`UpnpError::PortNotFound`] if the port is not tracked for `protocol`.
+    pub async fn remove_mapping(
+        &self,
+        port: u16,
+        protocol: PortMappingProtocol,
+    ) -> Result<(), UpnpError> {
+        self.remove_tracked(port, Some(protocol)).await
+    }
+
+    async fn remove_tracked(
+        &self,
+        port: u16,
+        protocol: Option<PortMappingProtocol>,
+    ) -> Result<(), UpnpError> {
         // Phase 1: find and remove from tracking (short lock)
-        let mapping = {
+        let removed = {
             let mut state = self.state.lock().await;
-            let index = state
-                .ports
-                .iter()
-                .position(|p| p.port == port)
-                .ok_or(UpnpError::PortNotFound(port))?;
-            let mapping = state.ports.remove(index);
+            let (removed, kept) = std::mem::take(&mut state.ports)
+                .into_iter()
+                .partition::<Vec<_>, _>(|p| {
+                    p.port == port && protocol.is_none_or(|protocol| p.protocol == protocol)
+                });
+            state.ports = kept;
+            if removed.is_empty() {
+                return Err(UpnpError::PortNotFound(port));
+            }
+            Self::stop_auto_renewals(&mut state, &removed).await;
 
             // Stop renewal if no ports remain
             if state.ports.is_empty() {
                 Self::stop_renewal_timer(&mut state).await;
             }
-            mapping
+            removed
         };
 
         // Phase 2: best-effort UPnP delete (no lock held)
-        tokio::task::spawn_blocking(move || {
-            let config = easy_upnp::UpnpConfig {
-                address: None,
-                port: mapping.port,
-                protocol: mapping.protocol,
-                duration: 0,
-                comment: String::new(),
-            };
-            for result in easy_upnp::delete_ports(vec![config]) {
-                if let Err(e) = result {
-                    error!("Failed to delete UPnP port {}: {}", mapping.port, e);
-                }
-            }
-        })
-        .await
-        .map_err(|e| UpnpError::UpnpOperationFailed(e.to_string()))?;
+        self.delete_mappings(removed).await?;
 
         info!("UPnP port {port} removed");
         Ok(())
@@ -168,7 +370,9 @@ impl UpnpManager {
         let mappings = {
             let mut state = self.state.lock().await;
             Self::stop_renewal_timer(&mut state).await;
-            std::mem::take(&mut state.ports)
+            let mappings = std::mem::take(&mut state.ports);
+            Self::stop_auto_renewals(&mut state, &mappings).await;
+            mappings
         };
 
         if mappings.is_empty() {
@@ -177,82 +381,226 @@ impl UpnpManager {
 
         info!("Removing all {} UPnP port mappings", mappings.len());
 
-        tokio::task::spawn_blocking(move || {
-            let configs: Vec<easy_upnp::UpnpConfig> = mappings
-                .iter()
-                .map(|m| easy_upnp::UpnpConfig {
-                    address: None,
-                    port: m.port,
-                    protocol: m.protocol,
-                    duration: 0,
-                    comment: String::new(),
-                })
-                .collect();
-
-            for result in easy_upnp::delete_ports(configs) {
-                if let Err(e) = result {
-                    error!("Failed to delete UPnP port: {e}");
-                }
-            }
-        })
-        .await
-        .map_err(|e| UpnpError::UpnpOperationFailed(e.to_string()))?;
+        self.delete_mappings(mappings).await?;
 
         info!("All UPnP ports removed");
         Ok(())
     }
 
+    /// Delete every mapping this manager created and stop renewing them.
+    ///
+    /// Call this on shutdown: dropping the manager cleans up as well, but cannot
+    /// wait for the router to answer, and the [`global`](Self::global) instance
+    /// is never dropped.
+    pub async fn close(&self) -> Result<(), UpnpError> {
+        self.remove_all_ports().await
+    }
+
+    /// Renew a tracked mapping on its own schedule with a lease of `lease`,
+    /// instead of with the other mappings every 5 minutes.
+    ///
+    /// The mapping is re-added straight away with the new lease, then again at
+    /// 80% of each lease so the router never lets it expire. Renewal stops when
+    /// the mapping is removed or the manager is dropped; a failed renewal is
+    /// logged as a warning and tried again on the next cycle.
+    ///
+    /// Returns [`UpnpError::PortNotFound`] if the mapping is not tracked.
+    pub async fn enable_auto_renew(
+        &self,
+        mapping: &PortMapping,
+        lease: Duration,
+    ) -> Result<(), UpnpError> {
+        let (port, protocol) = (mapping.port, mapping.protocol);
+        let renewed = {
+            let mut state = self.state.lock().await;
+            let tracked = state
+                .ports
+                .iter_mut()
+                .find(|p| p.port == port && p.protocol == protocol)
+                .ok_or(UpnpError::PortNotFound(port))?;
+            tracked.lease_duration = u32::try_from(lease.as_secs()).unwrap_or(u32::MAX).max(1);
+            tracked.clone()
+        };
+        renew(self.discovery.clone(), renewed).await?;
+        self.schedule_auto_renew(port, protocol, lease).await
+    }
+
+    /// Renew a tracked mapping at 80% of `lease`, replacing an earlier schedule.
+    async fn schedule_auto_renew(
+        &self,
+        port: u16,
+        protocol: PortMappingProtocol,
+        lease: Duration,
+    ) -> Result<(), UpnpError> {
+        let weak_state = Arc::downgrade(&self.state);
+        let discovery = self.discovery.clone();
+        let timer = CallbackTimer::new(
+            move |handle| {
+                let weak_state = weak_state.clone();
+                let discovery = discovery.clone();
+                async move {
+                    // Stop once the manager is gone or the mapping was removed
+                    let Some(state) = weak_state.upgrade() else {
+                        handle.stop();
+                        return Ok(());
+                    };
+                    let mapping = {
+                        let state = state.lock().await;
+                        state
+                            .ports
+                            .iter()
+                            .find(|p| p.port == port && p.protocol == protocol)
+                            .cloned()
+                    };
+                    let Some(mapping) = mapping else {
+                        handle.stop();
+                        return Ok(());
+                    };
+
+                    match renew(discovery, mapping).await {
+                        Ok(()) => trace!("Port {port} ({protocol}) renewed"),
+                        Err(e) => warn!("Failed to renew port {port} ({protocol}): {e}"),
+                    }
+                    Ok(())
+                }
+            },
+            lease.mul_f64(AUTO_RENEW_AT),
+        );
+        timer
+            .start()
+            .await
+            .map_err(|e| UpnpError::RenewalError(e.to_string()))?;
+
+        let mut state = self.state.lock().await;
+        if let Some(index) = state
+            .auto_renewals
+            .iter()
+            .position(|r| r.port == port && r.protocol == protocol)
+        {
+            let _ = state.auto_renewals.remove(index).timer.stop().await;
+        }
+        state.auto_renewals.push(AutoRenewal {
+            port,
+            protocol,
+            timer,
+        });
+        debug!("Auto-renewing port {port} ({protocol}) every {:?}", lease.mul_f64(AUTO_RENEW_AT));
+        Ok(())
+    }
+
     /// Return a snapshot of all currently active port mappings.
     pub async fn get_ports(&self) -> Vec<PortMapping> {
         let state = self.state.lock().await;
         state.ports.clone()
     }
 
-    /// Check whether a specific port is currently mapped.
+    /// Read every mapping in the router's port-mapping table, including those
+    /// created by other devices and applications, with their remaining lease times.
+    pub async fn list_mappings(&self) -> Result<Vec<PortMapping>, UpnpError> {
+        let gateway = discover(self.discovery.clone()).await?;
+        tokio::task::spawn_blocking(move || read_mappings(gateway.as_ref()))
+            .await
+            .map_err(|e| UpnpError::UpnpOperationFailed(e.to_string()))?
+    }
+
+    /// The router's public IP address, as reported by the gateway.
+    ///
+    /// The address is cached for a minute. Returns [`UpnpError::NonPublicExternalIp`]
+    /// if the gateway reports a private or placeholder address, as routers behind
+    /// another NAT do; port mappings on such a router are not reachable from the internet.
+    pub async fn external_ip(&self) -> Result<IpAddr, UpnpError> {
+        if let Some((ip, fetched)) = self.state.lock().await.external_ip
+            && fetched.elapsed() < EXTERNAL_IP_CACHE
+        {
+            return Ok(ip);
+        }
+
+        let gateway = discover(self.discovery.clone()).await?;
+        let ip = tokio::task::spawn_blocking(move || gateway.external_ip())
+            .await
+            .map_err(|e| UpnpError::UpnpOperationFailed(e.to_string()))??;
+        if !is_public(ip) {
+            return Err(UpnpError::NonPublicExternalIp(ip));
+        }
+
+        self.state.lock().await.external_ip = Some((ip, Instant::now()));
+        Ok(ip)
+    }
+
+    /// Check whether a specific port is currently mapped, for any protocol.
     pub async fn has_port(&self, port: u16) -> bool {
         let state = self.state.lock().await;
         state.ports.iter().any(|p| p.port == port)
     }
 
+    /// Check whether a specific port is currently mapped for `protocol`.
+    pub async fn has_mapping(&self, port: u16, protocol: PortMappingProtocol) -> bool {
+        let state = self.state.lock().await;
+        state
+            .ports
+            .iter()
+            .any(|p| p.port == port && p.protocol == protocol)
+    }
+
+    /// Delete mappings from the router on a best-effort basis, logging failures.
+    async fn delete_mappings(&self, mappings: Vec<PortMapping>) -> Result<(), UpnpError> {
+        let gateway = match discover(self.discovery.clone()).await {
+            Ok(gateway) => gateway,
+            Err(e) => {
+                error!("Failed to delete {} UPnP port mappings: {e}", mappings.len());
+                return Ok(());
+            }
+        };
+        tokio::task::spawn_blocking(move || remove_from_gateway(gateway.as_ref(), &mappings))
+            .await
+            .map_err(|e| UpnpError::UpnpOperationFailed(e.to_string()))
+    }
+
     /// Start the renewal timer. Called internally when the first port is added.
     async fn start_renewal_timer(&self, state: &mut UpnpState) -> Result<(), UpnpError> {
+        let weak_state = Arc::downgrade(&self.state);
+        let discovery = self.discovery.clone();
         let timer = CallbackTimer::new(
-            move |_handle| async move {
-                let manager = UpnpManager::global();
-                let ports = {
-                    let state = manager.state.lock().await;
-                    state.ports.clone()
-                };
-
-                if ports.is_empty() {
-                    return Ok(());
-                }
+            move |handle| {
+                let weak_state = weak_state.clone();
+                let discovery = discovery.clone();
+                async move {
+                    // The manager was dropped
+                    let Some(shared_state) = weak_state.upgrade() else {
+                        handle.stop();
+                        return Ok(());
+                    };
+                    let ports: Vec<PortMapping> = {
+                        let state = shared_state.lock().await;
+                        state
+                            .ports
+                            .iter()
+                            .filter(|p| !state.auto_renewals.iter().any(|r| r.renews(p)))
+                            .cloned()
+                            .collect()
+                    };
 
-                debug!("Renewing {} UPnP port mappings", ports.len());
+                    if ports.is_empty() {
+                        return Ok(());
+                    }
 
-                let configs: Vec<easy_upnp::UpnpConfig> = ports
-                    .iter()
-                    .map(|p| easy_upnp::UpnpConfig {
-                        address: None,
-                        port: p.port,
-                        protocol: p.protocol,
-                        duration: LEASE_DURATION_SECS,
-                        comment: p.description.clone(),
-                    })
-                    .collect();
+                    debug!("Renewing {} UPnP port mappings", ports.len());
 
-                tokio::task::spawn_blocking(move || {
-                    for result in easy_upnp::add_ports(configs) {
-                        match result {
-                            Ok(()) => trace!("Port renewed"),
-                            Err(e) => error!("Port renewal failed: {e}"),
+                    let gateway = discover(discovery).await?;
+                    tokio::task::spawn_blocking(move || {
+                        // Each mapping is renewed on its own so one failure doesn't drop the rest
+                        for mapping in &ports {
+                            match gateway.add_mapping(mapping) {
+                                Ok(()) => trace!("Port {} ({}) renewed", mapping.port, mapping.protocol),
+                                Err(e) => error!("Port renewal failed: {e}"),
+                            }
                         }
-                    }
-                })
-                .await
-                .map_err(|e| anyhow::anyhow!("renewal spawn_blocking failed: {e}"))?;
+                    })
+                    .await
+                    .map_err(|e| anyhow::anyhow!("renewal spawn_blocking failed: {e}"))?;
 
-                Ok(())
+                    Ok(())
+                }
             },
             std::time::Duration::from_secs(RENEWAL_INTERVAL_SECS),
         );
@@ -269,6 +617,17 @@ impl UpnpManager {
         Ok(())
     }
 
+    /// Stop the auto-renewal schedules of `mappings`.
+    async fn stop_auto_renewals(state: &mut UpnpState, mappings: &[PortMapping]) {
+        let (stopped, kept) = std::mem::take(&mut state.auto_renewals)
+            .into_iter()
+            .partition::<Vec<_>, _>(|r| mappings.iter().any(|m| r.renews(m)));
+        state.auto_renewals = kept;
+        for renewal in stopped {
+            let _ = renewal.timer.stop().await;
+        }
+    }
+
     /// Stop the renewal timer if running.
     async fn stop_renewal_timer(state: &mut UpnpState) {
         if let Some(timer) = state.renewal_timer.take() {
@@ -277,3 +636,579 @@ impl UpnpManager {
         }
     }
 }
+
+impl Drop for UpnpManager {
+    /// Delete mappings that were not removed with [`close`](Self::close).
+    ///
+    /// Renewal timers stop on their own once the state is gone. The router is
+    /// asked from a background task when a runtime is available, so the process
+    /// may exit before it answers.
+    fn drop(&mut self) {
+        let Ok(mut state) = self.state.try_lock() else {
+            return;
+        };
+        let mappings = std::mem::take(&mut state.ports);
+        if mappings.is_empty() {
+            return;
+        }
+
+        warn!("UPnP manager dropped with {} port mappings open", mappings.len());
+        let discovery = self.discovery.clone();
+        let cleanup = move || match discovery.source.discover() {
+            Ok(gateway) => remove_from_gateway(gateway.as_ref(), &mappings),
+            Err(e) => error!("Failed to delete {} UPnP port mappings: {e}", mappings.len()),
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => drop(runtime.spawn_blocking(cleanup)),
+            Err(_) => cleanup(),
+        }
+    }
+}
+
+/// Read the gateway's port-mapping table entry by entry until it reports the end.
+fn read_mappings(gateway: &dyn Gateway) -> Result<Vec<PortMapping>, UpnpError> {
+    let mut mappings = Vec::new();
+    for index in 0..MAX_MAPPING_ENTRIES {
+        match gateway.mapping_entry(index)? {
+            Some(mapping) => mappings.push(mapping),
+            None => break,
+        }
+    }
+    Ok(mappings)
+}
+
+/// The host `mapping` forwards to: its internal address, or this machine.
+fn target_address(gateway: &dyn Gateway, mapping: &PortMapping) -> Result<IpAddr, UpnpError> {
+    match mapping.internal_address {
+        Some(address) => Ok(address),
+        None => gateway.local_address(),
+    }
+}
+
+/// Whether `ip` can be reached from the internet, rather than being a private,
+/// shared (carrier-grade NAT), or placeholder address.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let carrier_grade_nat = ip.octets()[0] == 100 && (ip.octets()[1] & 0xc0) == 64;
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || carrier_grade_nat)
+        }
+        IpAddr::V6(ip) => {
+            let unique_local = (ip.segments()[0] & 0xfe00) == 0xfc00;
+            let link_local = (ip.segments()[0] & 0xffc0) == 0xfe80;
+            !(ip.is_loopback() || ip.is_unspecified() || unique_local || link_local)
+        }
+    }
+}
+
+/// Delete mappings from a gateway, logging each one that fails.
+fn remove_from_gateway(gateway: &dyn Gateway, mappings: &[PortMapping]) {
+    for mapping in mappings {
+        if let Err(e) = gateway.remove_mapping(mapping.port, mapping.protocol) {
+            error!(
+                "Failed to delete UPnP port {} ({}): {}",
+                mapping.port, mapping.protocol, e
+            );
+        }
+    }
+}
+
+/// Re-add a mapping so the router restarts its lease.
+async fn renew(discovery: Arc<GatewayFinder>, mapping: PortMapping) -> Result<(), UpnpError> {
+    let gateway = discover(discovery).await?;
+    add_to_gateway(gateway, mapping).await
+}
+
+/// Add a mapping on the blocking thread pool.
+async fn add_to_gateway(gateway: Arc<dyn Gateway>, mapping: PortMapping) -> Result<(), UpnpError> {
+    tokio::task::spawn_blocking(move || gateway.add_mapping(&mapping))
+        .await
+        .map_err(|e| UpnpError::UpnpOperationFailed(e.to_string()))?
+}
+
+/// Run gateway discovery on the blocking thread pool, giving up after the
+/// finder's timeout.
+///
+/// A search that times out keeps its blocking thread until it returns on its own.
+async fn discover(discovery: Arc<GatewayFinder>) -> Result<Arc<dyn Gateway>, UpnpError> {
+    let timeout = discovery.timeout;
+    let search = tokio::task::spawn_blocking(move || discovery.source.discover());
+    tokio::time::timeout(timeout, search)
+        .await
+        .map_err(|_| UpnpError::DiscoveryTimeout(timeout))?
+        .map_err(|e| UpnpError::UpnpOperationFailed(e.to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// An in-memory gateway whose port-mapping table behaves like a router's.
+    #[derive(Default)]
+    struct MockGateway {
+        table: std::sync::Mutex<Vec<PortMapping>>,
+        external_ip: std::sync::Mutex<Option<IpAddr>>,
+        external_ip_queries: AtomicUsize,
+        adds: AtomicUsize,
+        nat_pmp: bool,
+        /// External port the router refuses to forward.
+        refused_port: Option<u16>,
+        /// Whether the router refuses leases that never expire.
+        finite_leases_only: bool,
+    }
+
+    impl Gateway for MockGateway {
+        fn add_mapping(&self, mapping: &PortMapping) -> Result<(), UpnpError> {
+            self.adds.fetch_add(1, Ordering::SeqCst);
+            if self.refused_port == Some(mapping.port) {
+                return Err(UpnpError::UpnpOperationFailed(format!(
+                    "failed to forward port {}: ConflictInMappingEntry",
+                    mapping.port
+                )));
+            }
+            if self.finite_leases_only && mapping.lease_duration == 0 {
+                return Err(UpnpError::UpnpOperationFailed(format!(
+                    "failed to forward port {}: RequestError(ErrorCode(402, \"Invalid Args\"))",
+                    mapping.port
+                )));
+            }
+            let mut table = self.table.lock().unwrap();
+            table.retain(|m| !(m.port == mapping.port && m.protocol == mapping.protocol));
+            // Like a router, record the host the mapping actually forwards to
+            let mut recorded = mapping.clone();
+            recorded.internal_address.get_or_insert(IpAddr::V4(LOCAL_ADDRESS));
+            table.push(recorded);
+            Ok(())
+        }
+
+        fn remove_mapping(&self, port: u16, protocol: PortMappingProtocol) -> Result<(), UpnpError> {
+            let mut table = self.table.lock().unwrap();
+            table.retain(|m| !(m.port == port && m.protocol == protocol));
+            Ok(())
+        }
+
+        fn mapping_entry(&self, index: u32) -> Result<Option<PortMapping>, UpnpError> {
+            Ok(self.table.lock().unwrap().get(index as usize).cloned())
+        }
+
+        fn external_ip(&self) -> Result<IpAddr, UpnpError> {
+            self.external_ip_queries.fetch_add(1, Ordering::SeqCst);
+            self.external_ip
+                .lock()
+                .unwrap()
+                .ok_or_else(|| UpnpError::UpnpOperationFailed("no WAN connection".to_string()))
+        }
+
+        fn local_address(&self) -> Result<IpAddr, UpnpError> {
+            Ok(IpAddr::V4(LOCAL_ADDRESS))
+        }
+
+        fn protocol(&self) -> GatewayProtocol {
+            if self.nat_pmp {
+                GatewayProtocol::NatPmp
+            } else {
+                GatewayProtocol::Upnp
+            }
+        }
+    }
+
+    /// Discovery on a network where nothing answers.
+    struct NoGateway;
+
+    impl Discovery for NoGateway {
+        fn discover(&self) -> Result<Arc<dyn Gateway>, UpnpError> {
+            Err(UpnpError::DiscoveryFailed("no response".to_string()))
+        }
+    }
+
+    /// Discovery on a network where the search never gets an answer, until
+    /// `release` is dropped.
+    struct Unresponsive {
+        release: std::sync::Mutex<std::sync::mpsc::Receiver<()>>,
+    }
+
+    impl Discovery for Unresponsive {
+        fn discover(&self) -> Result<Arc<dyn Gateway>, UpnpError> {
+            let _ = self.release.lock().unwrap().recv();
+            Err(UpnpError::DiscoveryFailed("no response".to_string()))
+        }
+    }
+
+    /// Discovery whose first search loses the router's response.
+    struct Flaky {
+        gateway: Arc<MockGateway>,
+        failure: fn() -> UpnpError,
+        attempts: AtomicUsize,
+    }
+
+    impl Discovery for Flaky {
+        fn discover(&self) -> Result<Arc<dyn Gateway>, UpnpError> {
+            if self.attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Err((self.failure)());
+            }
+            Ok(self.gateway.clone())
+        }
+    }
+
+    impl Discovery for Arc<Flaky> {
+        fn discover(&self) -> Result<Arc<dyn Gateway>, UpnpError> {
+            self.as_ref().discover()
+        }
+    }
+
+    const LOCAL_ADDRESS: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 10);
+
+    impl Discovery for Arc<MockGateway> {
+        fn discover(&self) -> Result<Arc<dyn Gateway>, UpnpError> {
+            Ok(self.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_mappings_reads_gateway_table() {
+        let gateway = Arc::new(MockGateway::default());
+        let console = PortMapping {
+            port: 3074,
+            description: "Xbox".to_string(),
+            protocol: PortMappingProtocol::UDP,
+            internal_address: Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 20))),
+            internal_port: 3074,
+            lease_duration: 3600,
+        };
+        gateway.table.lock().unwrap().push(console.clone());
+        let manager = UpnpManager::new(gateway.clone());
+        manager
+            .add_port(25565, "Minecraft Server 1".to_string(), PortMappingProtocol::TCP)
+            .await
+            .unwrap();
+
+        let mappings = manager.list_mappings().await.unwrap();
+        assert_eq!(mappings.len(), 2);
+        assert_eq!(mappings[0], console);
+        assert_eq!(mappings[1].port, 25565);
+        assert_eq!(mappings[1].protocol, PortMappingProtocol::TCP);
+        assert_eq!(mappings[1].description, "Minecraft Server 1");
+        assert_eq!(mappings[1].lease_duration, LEASE_DURATION_SECS);
+
+        manager.remove_all_ports().await.unwrap();
+        assert_eq!(manager.list_mappings().await.unwrap(), vec![console]);
+    }
+
+    #[tokio::test]
+    async fn test_external_ip_is_cached() {
+        let gateway = Arc::new(MockGateway::default());
+        *gateway.external_ip.lock().unwrap() = Some(IpAddr::V4(Ipv4Addr::new(81, 2, 69, 142)));
+        let manager = UpnpManager::new(gateway.clone());
+
+        let ip = manager.external_ip().await.unwrap();
+        assert_eq!(ip, IpAddr::V4(Ipv4Addr::new(81, 2, 69, 142)));
+        assert_eq!(manager.external_ip().await.unwrap(), ip);
+        assert_eq!(gateway.external_ip_queries.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_private_external_ip_is_rejected() {
+        for placeholder in ["192.168.0.1", "100.72.14.3", "0.0.0.0"] {
+            let gateway = Arc::new(MockGateway::default());
+            *gateway.external_ip.lock().unwrap() = Some(placeholder.parse().unwrap());
+            let manager = UpnpManager::new(gateway);
+            assert!(matches!(
+                manager.external_ip().await,
+                Err(UpnpError::NonPublicExternalIp(ip)) if ip.to_string() == placeholder
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tcp_and_udp_mappings_are_tracked_separately() {
+        let gateway = Arc::new(MockGateway::default());
+        let manager = UpnpManager::new(gateway.clone());
+        manager
+            .add_mapping(PortMapping::new(19132, "Bedrock", PortMappingProtocol::TCP))
+            .await
+            .unwrap();
+        manager
+            .add_mapping(PortMapping::new(19132, "Bedrock", PortMappingProtocol::UDP))
+            .await
+            .unwrap();
+        assert!(matches!(
+            manager
+                .add_mapping(PortMapping::new(19132, "Bedrock", PortMappingProtocol::UDP))
+                .await,
+            Err(UpnpError::PortAlreadyMapped(19132))
+        ));
+
+        assert_eq!(manager.get_ports().await.len(), 2);
+        assert_eq!(gateway.table.lock().unwrap().len(), 2);
+
+        manager.remove_mapping(19132, PortMappingProtocol::UDP).await.unwrap();
+        assert!(manager.has_mapping(19132, PortMappingProtocol::TCP).await);
+        assert!(!manager.has_mapping(19132, PortMappingProtocol::UDP).await);
+        let table = gateway.table.lock().unwrap().clone();
+        assert_eq!(table.len(), 1);
+        assert_eq!(table[0].protocol, PortMappingProtocol::TCP);
+
+        manager.remove_port(19132).await.unwrap();
+        assert!(!manager.has_port(19132).await);
+        assert!(gateway.table.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_add_mapping_checked_reports_conflict() {
+        let gateway = Arc::new(MockGateway::default());
+        let other_host = PortMapping {
+            internal_address: Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 42))),
+            ..PortMapping::new(25565, "Another server", PortMappingProtocol::TCP)
+        };
+        gateway.table.lock().unwrap().push(other_host.clone());
+        let manager = UpnpManager::new(gateway.clone());
+
+        let result = manager
+            .add_mapping_checked(PortMapping::new(25565, "Minecraft", PortMappingProtocol::TCP))
+            .await;
+        assert!(matches!(result, Err(UpnpError::Conflict { existing }) if existing == other_host));
+        assert_eq!(gateway.table.lock().unwrap().clone(), vec![other_host]);
+        assert!(!manager.has_port(25565).await);
+
+        // The same port over UDP, or forwarded to this machine already, is not a conflict
+        manager
+            .add_mapping_checked(PortMapping::new(25565, "Minecraft", PortMappingProtocol::UDP))
+            .await
+            .unwrap();
+        gateway.table.lock().unwrap().push(PortMapping {
+            internal_address: Some(IpAddr::V4(LOCAL_ADDRESS)),
+            ..PortMapping::new(8080, "Panel", PortMappingProtocol::TCP)
+        });
+        manager
+            .add_mapping_checked(PortMapping::new(8080, "Panel", PortMappingProtocol::TCP))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_nat_pmp_is_used_when_upnp_is_unavailable() {
+        let upnp = Arc::new(MockGateway::default());
+        let nat_pmp = Arc::new(MockGateway {
+            nat_pmp: true,
+            ..Default::default()
+        });
+
+        let manager = UpnpManager::new(FallbackDiscovery::new(upnp.clone(), nat_pmp.clone()));
+        let used = manager
+            .add_port(25565, "Minecraft".to_string(), PortMappingProtocol::TCP)
+            .await
+            .unwrap();
+        assert_eq!(used, GatewayProtocol::Upnp);
+        assert_eq!(upnp.table.lock().unwrap().len(), 1);
+        assert!(nat_pmp.table.lock().unwrap().is_empty());
+
+        let manager = UpnpManager::new(FallbackDiscovery::new(NoGateway, nat_pmp.clone()));
+        let used = manager
+            .add_port(25565, "Minecraft".to_string(), PortMappingProtocol::TCP)
+            .await
+            .unwrap();
+        assert_eq!(used, GatewayProtocol::NatPmp);
+        assert_eq!(nat_pmp.table.lock().unwrap().len(), 1);
+
+        let manager = UpnpManager::new(FallbackDiscovery::new(NoGateway, NoGateway));
+        assert!(matches!(
+            manager
+                .add_port(25565, "Minecraft".to_string(), PortMappingProtocol::TCP)
+                .await,
+            Err(UpnpError::DiscoveryFailed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_verify_mapping_checks_gateway_table() {
+        let gateway = Arc::new(MockGateway::default());
+        let manager = UpnpManager::new(gateway.clone());
+        let mapping = PortMapping::new(25565, "Minecraft", PortMappingProtocol::TCP);
+        manager.add_mapping(mapping.clone()).await.unwrap();
+        assert!(manager.verify_mapping(&mapping).await.unwrap());
+
+        gateway.table.lock().unwrap()[0].internal_address =
+            Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 42)));
+        assert!(!manager.verify_mapping(&mapping).await.unwrap());
+
+        // Dropped by the router, though the manager still tracks it
+        gateway.table.lock().unwrap().clear();
+        assert!(manager.has_port(25565).await);
+        assert!(!manager.verify_mapping(&mapping).await.unwrap());
+    }
+
+    /// Let renewals running in the background finish their blocking gateway calls.
+    async fn settle() {
+        for _ in 0..50 {
+            tokio::task::yield_now().await;
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_auto_renew_fires_before_lease_expires() {
+        let gateway = Arc::new(MockGateway::default());
+        let manager = UpnpManager::new(gateway.clone());
+        let mapping = PortMapping::new(25565, "Minecraft", PortMappingProtocol::TCP);
+        manager.add_mapping(mapping.clone()).await.unwrap();
+        manager
+            .enable_auto_renew(&mapping, Duration::from_secs(100))
+            .await
+            .unwrap();
+        // Re-added straight away with the new lease
+        assert_eq!(gateway.adds.load(Ordering::SeqCst), 2);
+        assert_eq!(gateway.table.lock().unwrap()[0].lease_duration, 100);
+
+        tokio::time::sleep(Duration::from_secs(79)).await;
+        settle().await;
+        assert_eq!(gateway.adds.load(Ordering::SeqCst), 2);
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        settle().await;
+        assert_eq!(gateway.adds.load(Ordering::SeqCst), 3);
+
+        manager.remove_port(25565).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(200)).await;
+        settle().await;
+        assert_eq!(gateway.adds.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_close_deletes_every_tracked_mapping() {
+        let gateway = Arc::new(MockGateway::default());
+        // Someone else's mapping, which must survive
+        gateway
+            .add_mapping(&PortMapping::new(8080, "Other", PortMappingProtocol::TCP))
+            .unwrap();
+        let manager = UpnpManager::new(gateway.clone());
+        manager.add_port(25565, "Minecraft".into(), PortMappingProtocol::TCP).await.unwrap();
+        manager.add_port(25565, "Minecraft".into(), PortMappingProtocol::UDP).await.unwrap();
+        manager.add_port(24454, "Voice chat".into(), PortMappingProtocol::UDP).await.unwrap();
+        assert_eq!(gateway.table.lock().unwrap().len(), 4);
+
+        manager.close().await.unwrap();
+        assert!(manager.get_ports().await.is_empty());
+        let table = gateway.table.lock().unwrap();
+        assert_eq!(table.len(), 1);
+        assert_eq!(table[0].port, 8080);
+    }
+
+    #[test]
+    fn test_drop_deletes_open_mappings() {
+        let gateway = Arc::new(MockGateway::default());
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let manager = UpnpManager::new(gateway.clone());
+        runtime
+            .block_on(manager.add_port(25565, "Minecraft".into(), PortMappingProtocol::TCP))
+            .unwrap();
+
+        // Outside a runtime the mappings are deleted before drop returns
+        drop(manager);
+        assert!(gateway.table.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_discovery_timeout() {
+        let (release, receiver) = std::sync::mpsc::channel();
+        let discovery = Unresponsive {
+            release: std::sync::Mutex::new(receiver),
+        };
+        let timeout = Duration::from_millis(50);
+        let manager = UpnpManager::with_discovery_timeout(discovery, timeout);
+
+        let started = Instant::now();
+        let result = manager.add_port(25565, "Minecraft".into(), PortMappingProtocol::TCP).await;
+        assert!(matches!(result, Err(UpnpError::DiscoveryTimeout(t)) if t == timeout));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(manager.get_ports().await.is_empty());
+
+        // Let the abandoned search finish so the runtime can shut down
+        drop(release);
+    }
+
+    #[tokio::test]
+    async fn test_add_mappings_reports_each_port() {
+        let gateway = Arc::new(MockGateway {
+            refused_port: Some(25575),
+            ..Default::default()
+        });
+        let manager = UpnpManager::new(gateway.clone());
+        let results = manager
+            .add_mappings(vec![
+                PortMapping::new(25565, "Minecraft", PortMappingProtocol::TCP),
+                PortMapping::new(25575, "RCON", PortMappingProtocol::TCP),
+                PortMapping::new(24454, "Voice chat", PortMappingProtocol::UDP),
+            ])
+            .await;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().port, 25565);
+        assert!(matches!(results[1], Err(UpnpError::UpnpOperationFailed(_))));
+        assert_eq!(results[2].as_ref().unwrap().port, 24454);
+
+        // The ports that were forwarded are tracked, and cleaned up on close
+        let tracked: Vec<u16> = manager.get_ports().await.iter().map(|p| p.port).collect();
+        assert_eq!(tracked, vec![25565, 24454]);
+        manager.close().await.unwrap();
+        assert!(gateway.table.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rejected_indefinite_lease_falls_back_to_finite_lease() {
+        let gateway = Arc::new(MockGateway {
+            finite_leases_only: true,
+            ..Default::default()
+        });
+        let manager = UpnpManager::new(gateway.clone());
+        let mapping =
+            PortMapping::new(25565, "Minecraft", PortMappingProtocol::TCP).with_lease_duration(0);
+        manager.add_mapping(mapping).await.unwrap();
+
+        assert_eq!(gateway.adds.load(Ordering::SeqCst), 2);
+        assert_eq!(gateway.table.lock().unwrap()[0].lease_duration, LEASE_DURATION_SECS);
+        assert_eq!(manager.get_ports().await[0].lease_duration, LEASE_DURATION_SECS);
+
+        // Renewed on its own schedule at 80% of the lease
+        tokio::time::sleep(Duration::from_secs(479)).await;
+        settle().await;
+        assert_eq!(gateway.adds.load(Ordering::SeqCst), 2);
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        settle().await;
+        assert_eq!(gateway.adds.load(Ordering::SeqCst), 3);
+
+        manager.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_discovery_is_retried() {
+        let flaky = Arc::new(Flaky {
+            gateway: Arc::new(MockGateway::default()),
+            failure: || UpnpError::DiscoveryInterrupted("IoError(ConnectionReset)".to_string()),
+            attempts: AtomicUsize::new(0),
+        });
+        let manager = UpnpManager::new(RetryDiscovery::new(flaky.clone(), 2));
+        manager.add_port(25565, "Minecraft".into(), PortMappingProtocol::TCP).await.unwrap();
+        assert_eq!(flaky.attempts.load(Ordering::SeqCst), 2);
+        assert_eq!(flaky.gateway.table.lock().unwrap().len(), 1);
+        manager.close().await.unwrap();
+
+        // A search that found nothing is not repeated
+        let empty = Arc::new(Flaky {
+            gateway: Arc::new(MockGateway::default()),
+            failure: || UpnpError::DiscoveryFailed("NoResponseWithinTimeout".to_string()),
+            attempts: AtomicUsize::new(0),
+        });
+        let manager = UpnpManager::new(RetryDiscovery::new(empty.clone(), 2));
+        let result = manager.add_port(25565, "Minecraft".into(), PortMappingProtocol::TCP).await;
+        assert!(matches!(result, Err(UpnpError::DiscoveryFailed(_))));
+        assert_eq!(empty.attempts.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::error::UpnpError;
+use crate::gateway::{Discovery, Gateway, GatewayProtocol};
+use crate::manager::PortMapping;
+use igd_next::PortMappingProtocol;
+use natpmp::{Natpmp, Protocol, Response};
+use std::net::{IpAddr, Ipv4Addr, UdpSocket};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Port NAT-PMP gateways listen on.
+const NAT_PMP_PORT: u16 = 5351;
+
+/// How long to wait for the gateway to answer a request, including retransmissions.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Lease requested for mappings that ask for no expiry, which NAT-PMP cannot
+/// express. This is the lifetime RFC 6886 recommends.
+const DEFAULT_LIFETIME_SECS: u32 = 7200;
+
+/// Finds a NAT-PMP gateway at the router of the default route.
+///
+/// PCP routers that keep NAT-PMP compatibility answer as well.
+pub struct NatPmpDiscovery;
+
+impl Discovery for NatPmpDiscovery {
+    fn discover(&self) -> Result<Arc<dyn Gateway>, UpnpError> {
+        let address = natpmp::get_default_gateway()
+            .map_err(|e| UpnpError::DiscoveryFailed(format!("no default gateway: {e}")))?;
+        let gateway = NatPmpGateway { address };
+        // Only a router that answers actually speaks NAT-PMP
+        gateway.public_address().map_err(|e| {
+            UpnpError::DiscoveryFailed(format!("no NAT-PMP gateway at {address}: {e}"))
+        })?;
+        Ok(Arc::new(gateway))
+    }
+}
+
+/// A router that forwards ports through NAT-PMP (RFC 6886).
+///
+/// NAT-PMP only forwards to the host that asks and cannot list existing
+/// mappings, so [`Gateway::mapping_entry`] is unsupported.
+pub struct NatPmpGateway {
+    address: Ipv4Addr,
+}
+
+impl NatPmpGateway {
+    /// Send a request and wait for its response, letting the client retransmit.
+    fn request(
+        &self,
+        send: impl FnOnce(&mut Natpmp) -> natpmp::Result<()>,
+    ) -> Result<Response, UpnpError> {
+        let failed = |e: natpmp::Error| UpnpError::UpnpOperationFailed(format!("NAT-PMP: {e}"));
+        let mut client = Natpmp::new_with(self.address).map_err(failed)?;
+        send(&mut client).map_err(failed)?;
+
+        let deadline = Instant::now() + RESPONSE_TIMEOUT;
+        loop {
+            match client.read_response_or_retry() {
+                Err(natpmp::Error::NATPMP_TRYAGAIN) if Instant::now() < deadline => {
+                    std::thread::sleep(Duration::from_millis(50));
+                }
+                Err(natpmp::Error::NATPMP_TRYAGAIN) => {
+                    return Err(UpnpError::UpnpOperationFailed(format!(
+                        "NAT-PMP gateway {} did not respond",
+                        self.address
+                    )));
+                }
+                result => return result.map_err(failed),
+            }
+        }
+    }
+
+    fn public_address(&self) -> Result<Ipv4Addr, UpnpError> {
+        match self.request(|client| client.send_public_address_request())? {
+            Response::Gateway(response) => Ok(*response.public_address()),
+            _ => Err(unexpected_response()),
+        }
+    }
+
+    fn map(
+        &self,
+        protocol: PortMappingProtocol,
+        private_port: u16,
+        public_port: u16,
+        lifetime: u32,
+    ) -> Result<(), UpnpError> {
+        let protocol = match protocol {
+            PortMappingProtocol::TCP => Protocol::TCP,
+            PortMappingProtocol::UDP => Protocol::UDP,
+        };
+        let response = self.request(|client| {
+            client.send_port_mapping_request(protocol, private_port, public_port, lifetime)
+        })?;
+        match response {
+            Response::TCP(_) | Response::UDP(_) => Ok(()),
+            Response::Gateway(_) => Err(unexpected_response()),
+        }
+    }
+}
+
+impl Gateway for NatPmpGateway {
+    fn add_mapping(&self, mapping: &PortMapping) -> Result<(), UpnpError> {
+        if let Some(address) = mapping.internal_address
+            && address != self.local_address()?
+        {
+            return Err(UpnpError::Unsupported(
+                "NAT-PMP forwarding to another host".to_string(),
+            ));
+        }
+        let lifetime = match mapping.lease_duration {
+            0 => DEFAULT_LIFETIME_SECS,
+            lease => lease,
+        };
+        self.map(mapping.protocol, mapping.internal_port, mapping.port, lifetime)
+    }
+
+    fn remove_mapping(&self, port: u16, protocol: PortMappingProtocol) -> Result<(), UpnpError> {
+        // A zero lifetime and public port delete the mapping for the private port
+        self.map(protocol, port, 0, 0)
+    }
+
+    fn mapping_entry(&self, _index: u32) -> Result<Option<PortMapping>, UpnpError> {
+        Err(UpnpError::Unsupported(
+            "listing NAT-PMP port mappings".to_string(),
+        ))
+    }
+
+    fn external_ip(&self) -> Result<IpAddr, UpnpError> {
+        self.public_address().map(IpAddr::V4)
+    }
+
+    fn local_address(&self) -> Result<IpAddr, UpnpError> {
+        // Connecting a UDP socket only selects a route; nothing is sent
+        let socket = UdpSocket::bind(("0.0.0.0", 0))
+            .and_then(|socket| socket.connect((self.address, NAT_PMP_PORT)).map(|()| socket))
+            .map_err(|e| UpnpError::UpnpOperationFailed(format!("no route to gateway: {e}")))?;
+        socket
+            .local_addr()
+            .map(|address| address.ip())
+            .map_err(|e| UpnpError::UpnpOperationFailed(format!("no route to gateway: {e}")))
+    }
+
+    fn protocol(&self) -> GatewayProtocol {
+        GatewayProtocol::NatPmp
+    }
+}
+
+fn unexpected_response() -> UpnpError {
+    UpnpError::UpnpOperationFailed("unexpected NAT-PMP response".to_string())
+}
@@ -0,0 +1,10 @@
+-- Audit log: security-relevant actions taken by users. Kept when the user is deleted.
+CREATE TABLE IF NOT EXISTS `audit_log` (
+	`id` VARCHAR(255) PRIMARY KEY NOT NULL,
+	`user_id` INT UNSIGNED NOT NULL,
+	`action` VARCHAR(50) NOT NULL,
+	`target` TEXT NOT NULL,
+	`timestamp` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	INDEX `idx_audit_log_timestamp` (`timestamp` DESC),
+	INDEX `idx_audit_log_user_id` (`user_id`)
+);
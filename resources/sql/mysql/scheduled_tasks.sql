@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS `scheduled_tasks`
+(
+	`id` INT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+	`server_id`  INT UNSIGNED NOT NULL,
+	`action`     VARCHAR(20)  NOT NULL,
+	`command`    TEXT                  DEFAULT NULL,
+	`cron`       VARCHAR(100) NOT NULL,
+	`enabled`    BOOLEAN      NOT NULL DEFAULT 1,
+	`owner_id`   INT UNSIGNED NOT NULL,
+	`last_run`   INT                   DEFAULT NULL,
+	`created_at` INT          NOT NULL DEFAULT (UNIX_TIMESTAMP()),
+	FOREIGN KEY (`server_id`) REFERENCES `servers` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+	FOREIGN KEY (`owner_id`) REFERENCES `users` (`id`) ON DELETE CASCADE ON UPDATE CASCADE
+);
@@ -0,0 +1,23 @@
+CREATE TABLE IF NOT EXISTS `server_templates`
+(
+	`id` INT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+	`name`              VARCHAR(255) NOT NULL,
+	`description`       TEXT,
+	`server_type`       TINYINT NOT NULL DEFAULT 0,
+	`minecraft_version` VARCHAR(50) DEFAULT '',
+	`loader_version`    VARCHAR(50) DEFAULT NULL,
+	`java_executable`   VARCHAR(512) NOT NULL,
+	`java_args`         TEXT NOT NULL,
+	`max_memory`        TINYINT NOT NULL DEFAULT 4,
+	`min_memory`        TINYINT NOT NULL DEFAULT 2,
+	`minecraft_args`    VARCHAR(255) NOT NULL DEFAULT 'nogui',
+	`auto_restart`      BOOLEAN NOT NULL DEFAULT 1,
+	`backup_enabled`    BOOLEAN NOT NULL DEFAULT 1,
+	`backup_cron`       VARCHAR(50) NOT NULL DEFAULT '0 0 * * * *',
+	`backup_retention`  INT NOT NULL DEFAULT 7,
+	`properties`        TEXT NOT NULL,
+	`mods`              TEXT NOT NULL,
+	`owner_id` INT UNSIGNED NOT NULL,
+	`created_at`        INT NOT NULL DEFAULT (UNIX_TIMESTAMP()),
+	FOREIGN KEY (`owner_id`) REFERENCES `users` (`id`) ON DELETE CASCADE ON UPDATE CASCADE
+);
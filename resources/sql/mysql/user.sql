@@ -9,3 +9,43 @@ CREATE TABLE IF NOT EXISTS `users`
 	`needs_password_change` TINYINT NOT NULL DEFAULT 0,
 	`is_active`             TINYINT NOT NULL DEFAULT 1
 );
+
+-- Two-factor authentication: per-user TOTP secret and hashed recovery codes
+CREATE TABLE IF NOT EXISTS `user_two_factor`
+(
+	`user_id`        INT UNSIGNED PRIMARY KEY NOT NULL,
+	`secret`         VARCHAR(64) NOT NULL,
+	`enabled`        TINYINT     NOT NULL DEFAULT 0,
+	`recovery_codes` TEXT        NOT NULL,
+	`last_used_step` BIGINT      NOT NULL DEFAULT 0,
+	FOREIGN KEY (`user_id`) REFERENCES `users` (`id`) ON DELETE CASCADE
+);
+
+-- API tokens for automation; only a SHA-256 hash of each token is stored
+CREATE TABLE IF NOT EXISTS `api_tokens`
+(
+	`id`          INT UNSIGNED AUTO_INCREMENT PRIMARY KEY,
+	`user_id`     INT UNSIGNED NOT NULL,
+	`name`        VARCHAR(255) NOT NULL,
+	`token_hash`  CHAR(64)     NOT NULL UNIQUE,
+	`permissions` INT          NOT NULL DEFAULT 0,
+	`created_at`  DATETIME     NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	`last_used`   DATETIME     NULL,
+	`expires_at`  DATETIME     NULL,
+	`revoked`     TINYINT      NOT NULL DEFAULT 0,
+	FOREIGN KEY (`user_id`) REFERENCES `users` (`id`) ON DELETE CASCADE
+);
+
+-- Signed-in sessions; a session token is only accepted while its session isn't revoked
+CREATE TABLE IF NOT EXISTS `user_sessions`
+(
+	`id`         INT UNSIGNED AUTO_INCREMENT PRIMARY KEY,
+	`user_id`    INT UNSIGNED NOT NULL,
+	`token_hash` CHAR(64)     NOT NULL UNIQUE,
+	`user_agent` VARCHAR(512) NULL,
+	`ip_address` VARCHAR(64)  NULL,
+	`created_at` DATETIME     NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	`last_used`  DATETIME     NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	`revoked`    TINYINT      NOT NULL DEFAULT 0,
+	FOREIGN KEY (`user_id`) REFERENCES `users` (`id`) ON DELETE CASCADE
+);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS scheduled_tasks
+(
+	id         SERIAL PRIMARY KEY,
+	server_id  INT          NOT NULL REFERENCES servers(id) ON DELETE CASCADE ON UPDATE CASCADE,
+	action     VARCHAR(20)  NOT NULL,
+	command    TEXT                  DEFAULT NULL,
+	cron       VARCHAR(100) NOT NULL,
+	enabled    BOOLEAN      NOT NULL DEFAULT TRUE,
+	owner_id   INT          NOT NULL REFERENCES users(id) ON DELETE CASCADE ON UPDATE CASCADE,
+	last_run   BIGINT                DEFAULT NULL,
+	created_at BIGINT       NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())::BIGINT)
+);
@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS server_templates
+(
+	id                SERIAL PRIMARY KEY,
+	name              VARCHAR(255) NOT NULL,
+	description       TEXT,
+	server_type       SMALLINT NOT NULL DEFAULT 0,
+	minecraft_version VARCHAR(50) DEFAULT '',
+	loader_version    VARCHAR(50) DEFAULT NULL,
+	java_executable   VARCHAR(512) NOT NULL,
+	java_args         TEXT NOT NULL,
+	max_memory        SMALLINT NOT NULL DEFAULT 4,
+	min_memory        SMALLINT NOT NULL DEFAULT 2,
+	minecraft_args    VARCHAR(255) NOT NULL DEFAULT 'nogui',
+	auto_restart      BOOLEAN NOT NULL DEFAULT TRUE,
+	backup_enabled    BOOLEAN NOT NULL DEFAULT TRUE,
+	backup_cron       VARCHAR(50) NOT NULL DEFAULT '0 0 * * * *',
+	backup_retention  INT NOT NULL DEFAULT 7,
+	properties        TEXT NOT NULL DEFAULT '{}',
+	mods              TEXT NOT NULL DEFAULT '[]',
+	owner_id          INT NOT NULL REFERENCES users(id) ON DELETE CASCADE ON UPDATE CASCADE,
+	created_at        BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW())::BIGINT)
+);
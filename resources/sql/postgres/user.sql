@@ -9,3 +9,40 @@ CREATE TABLE IF NOT EXISTS users
 	needs_password_change SMALLINT NOT NULL DEFAULT 0,
 	is_active             SMALLINT NOT NULL DEFAULT 1
 );
+
+-- Two-factor authentication: per-user TOTP secret and hashed recovery codes
+CREATE TABLE IF NOT EXISTS user_two_factor
+(
+	user_id        INT PRIMARY KEY NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+	secret         VARCHAR(64) NOT NULL,
+	enabled        SMALLINT    NOT NULL DEFAULT 0,
+	recovery_codes TEXT        NOT NULL DEFAULT '[]',
+	last_used_step BIGINT      NOT NULL DEFAULT 0
+);
+
+-- API tokens for automation; only a SHA-256 hash of each token is stored
+CREATE TABLE IF NOT EXISTS api_tokens
+(
+	id          SERIAL PRIMARY KEY,
+	user_id     INT          NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+	name        VARCHAR(255) NOT NULL,
+	token_hash  CHAR(64)     NOT NULL UNIQUE,
+	permissions INT          NOT NULL DEFAULT 0,
+	created_at  TIMESTAMPTZ  NOT NULL DEFAULT NOW(),
+	last_used   TIMESTAMPTZ,
+	expires_at  TIMESTAMPTZ,
+	revoked     SMALLINT     NOT NULL DEFAULT 0
+);
+
+-- Signed-in sessions; a session token is only accepted while its session isn't revoked
+CREATE TABLE IF NOT EXISTS user_sessions
+(
+	id         SERIAL PRIMARY KEY,
+	user_id    INT          NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+	token_hash CHAR(64)     NOT NULL UNIQUE,
+	user_agent VARCHAR(512),
+	ip_address VARCHAR(64),
+	created_at TIMESTAMPTZ  NOT NULL DEFAULT NOW(),
+	last_used  TIMESTAMPTZ  NOT NULL DEFAULT NOW(),
+	revoked    SMALLINT     NOT NULL DEFAULT 0
+);
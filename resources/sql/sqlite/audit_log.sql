@@ -0,0 +1,10 @@
+-- Audit log: security-relevant actions taken by users. Kept when the user is deleted.
+CREATE TABLE IF NOT EXISTS audit_log (
+	id        TEXT PRIMARY KEY NOT NULL,
+	user_id   INTEGER NOT NULL,
+	action    TEXT NOT NULL,
+	target    TEXT NOT NULL,
+	timestamp TEXT NOT NULL DEFAULT (DATETIME('now'))
+);
+CREATE INDEX IF NOT EXISTS idx_audit_log_timestamp ON audit_log(timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_log_user_id ON audit_log(user_id);
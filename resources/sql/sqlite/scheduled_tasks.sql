@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS scheduled_tasks
+(
+	id         INTEGER PRIMARY KEY AUTOINCREMENT,
+	server_id  INTEGER NOT NULL REFERENCES servers(id) ON DELETE CASCADE,
+	action     TEXT    NOT NULL,
+	command    TEXT             DEFAULT NULL,
+	cron       TEXT    NOT NULL,
+	enabled    INTEGER NOT NULL DEFAULT 1,
+	owner_id   INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+	last_run   INTEGER          DEFAULT NULL,
+	created_at INTEGER NOT NULL DEFAULT (STRFTIME('%s', 'now'))
+);
@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS server_templates
+(
+	id                INTEGER PRIMARY KEY AUTOINCREMENT,
+	name              TEXT NOT NULL,
+	description       TEXT,
+	server_type       INTEGER NOT NULL DEFAULT 0,
+	minecraft_version TEXT DEFAULT '',
+	loader_version    TEXT DEFAULT NULL,
+	java_executable   TEXT NOT NULL,
+	java_args         TEXT NOT NULL,
+	max_memory        INTEGER NOT NULL DEFAULT 4,
+	min_memory        INTEGER NOT NULL DEFAULT 2,
+	minecraft_args    TEXT NOT NULL DEFAULT 'nogui',
+	auto_restart      INTEGER NOT NULL DEFAULT 1,
+	backup_enabled    INTEGER NOT NULL DEFAULT 1,
+	backup_cron       TEXT NOT NULL DEFAULT '0 0 * * * *',
+	backup_retention  INTEGER NOT NULL DEFAULT 7,
+	properties        TEXT NOT NULL DEFAULT '{}',
+	mods              TEXT NOT NULL DEFAULT '[]',
+	owner_id          INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+	created_at        INTEGER NOT NULL DEFAULT (STRFTIME('%s', 'now'))
+);
@@ -9,3 +9,40 @@ CREATE TABLE IF NOT EXISTS users
 	needs_password_change INTEGER NOT NULL DEFAULT 0,
 	is_active             INTEGER NOT NULL DEFAULT 1
 );
+
+-- Two-factor authentication: per-user TOTP secret and hashed recovery codes
+CREATE TABLE IF NOT EXISTS user_two_factor
+(
+	user_id        INTEGER PRIMARY KEY NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+	secret         TEXT    NOT NULL,
+	enabled        INTEGER NOT NULL DEFAULT 0,
+	recovery_codes TEXT    NOT NULL DEFAULT '[]',
+	last_used_step INTEGER NOT NULL DEFAULT 0
+);
+
+-- API tokens for automation; only a SHA-256 hash of each token is stored
+CREATE TABLE IF NOT EXISTS api_tokens
+(
+	id          INTEGER PRIMARY KEY AUTOINCREMENT,
+	user_id     INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+	name        TEXT    NOT NULL,
+	token_hash  TEXT    NOT NULL UNIQUE,
+	permissions INTEGER NOT NULL DEFAULT 0,
+	created_at  TEXT    NOT NULL DEFAULT (DATETIME('now')),
+	last_used   TEXT,
+	expires_at  TEXT,
+	revoked     INTEGER NOT NULL DEFAULT 0
+);
+
+-- Signed-in sessions; a session token is only accepted while its session isn't revoked
+CREATE TABLE IF NOT EXISTS user_sessions
+(
+	id         INTEGER PRIMARY KEY AUTOINCREMENT,
+	user_id    INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+	token_hash TEXT    NOT NULL UNIQUE,
+	user_agent TEXT,
+	ip_address TEXT,
+	created_at TEXT    NOT NULL DEFAULT (DATETIME('now')),
+	last_used  TEXT    NOT NULL DEFAULT (DATETIME('now')),
+	revoked    INTEGER NOT NULL DEFAULT 0
+);
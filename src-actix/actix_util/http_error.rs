@@ -35,17 +35,27 @@ pub mod http_error;
     // Specific error for header installed_mods failures
     #[error("unable to parse headers: {0:?}")]
     HeaderParse(ToStrError),
+
+    // The authenticated user lacks a permission the request needs
+    #[error("{0}")]
+    Forbidden(String),
 }
 
 impl ResponseError for Error {
     fn status_code(&self) -> StatusCode {
         match &self {
             Self::Internal(_) | Self::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
             _ => StatusCode::BAD_REQUEST,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
+        // Permission errors are expected, so they use the same shape as the handlers' own error responses
+        if let Self::Forbidden(message) = self {
+            return HttpResponse::Forbidden().json(json!({ "error": message }));
+        }
+
         let status_code = self.status_code();
 
         // Extract only the error message without the backtrace
@@ -1,3 +1,4 @@
 pub mod asset_endpoint;
 pub mod http_error;
 pub mod path_sanitize;
+pub mod rate_limit;
@@ -17,7 +17,9 @@ pub fn sanitize_path_component(s: &str) -> String {
 /// This function handles:
 /// - Stripping leading `/` and `\` from user input
 /// - Rejecting `..` components that would escape the base
-/// - Canonicalizing to resolve symlinks when the path exists
+/// - Canonicalizing the closest existing ancestor, so a symlinked directory
+///   can't redirect a path that doesn't exist yet out of the base
+/// - Rejecting dangling symlinks, which would be followed when written to
 pub fn ensure_path_within(base: &Path, user_path: &str) -> Result<PathBuf> {
     // Strip leading slashes/backslashes from the user input
     let cleaned = user_path
@@ -44,30 +46,51 @@ pub fn ensure_path_within(base: &Path, user_path: &str) -> Result<PathBuf> {
 
     let joined = base.join(cleaned);
 
-    // If the path exists, canonicalize both and verify containment
-    if joined.exists() {
-        let canonical_base = base.canonicalize().map_err(|e| {
-            anyhow!("Failed to canonicalize base path '{}': {}", base.display(), e)
-        })?;
-        let canonical_joined = joined.canonicalize().map_err(|e| {
-            anyhow!(
-                "Failed to canonicalize path '{}': {}",
-                joined.display(),
-                e
-            )
-        })?;
-
-        if !canonical_joined.starts_with(&canonical_base) {
-            return Err(anyhow!(
-                "Path '{}' escapes the base directory",
-                user_path
-            ));
-        }
+    // Nothing below a missing base can exist yet, let alone be a symlink
+    if !base.exists() {
+        return Ok(joined);
+    }
+    let canonical_base = base.canonicalize().map_err(|e| {
+        anyhow!("Failed to canonicalize base path '{}': {}", base.display(), e)
+    })?;
+
+    // The closest entry that exists decides where the rest of the path ends up
+    let existing = joined
+        .ancestors()
+        .find(|ancestor| ancestor.symlink_metadata().is_ok())
+        .ok_or_else(|| anyhow!("Path '{}' has no existing ancestor", user_path))?;
+    let canonical_existing = existing.canonicalize().map_err(|_| {
+        anyhow!("Path '{}' points through a dangling symlink", user_path)
+    })?;
+
+    if !canonical_existing.starts_with(&canonical_base) {
+        return Err(anyhow!(
+            "Path '{}' escapes the base directory",
+            user_path
+        ));
     }
 
     Ok(joined)
 }
 
+/// Resolve a user-provided path like [`ensure_path_within`] and require a
+/// file to exist there.
+///
+/// A missing file fails with an [`std::io::ErrorKind::NotFound`] error, so
+/// callers can tell it apart from an escaping path with [`is_not_found`].
+pub fn ensure_file_within(base: &Path, user_path: &str) -> Result<PathBuf> {
+    let path = ensure_path_within(base, user_path)?;
+    if !path.is_file() {
+        return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "File not found").into());
+    }
+    Ok(path)
+}
+
+/// Whether `error` means the file a checked path points to doesn't exist
+pub fn is_not_found(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound)
+}
+
 /// Validate that a path does not contain traversal sequences (`..`).
 /// Unlike `ensure_path_within`, this does not require a base directory
 /// and simply rejects any path that contains parent directory references.
@@ -82,3 +105,61 @@ pub fn reject_path_traversal(path: &Path) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_paths_stay_within_base() {
+        let dir = temp_dir("path-within");
+        let base = dir.join("server");
+        std::fs::create_dir_all(base.join("config")).unwrap();
+
+        assert_eq!(ensure_path_within(&base, "/config/new.json").unwrap(), base.join("config/new.json"));
+        assert_eq!(ensure_path_within(&base, "mods/new.jar").unwrap(), base.join("mods/new.jar"));
+        assert!(ensure_path_within(&base, "../outside").is_err());
+        assert!(ensure_path_within(&base, "config/../../outside").is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_out_of_base_are_rejected() {
+        let dir = temp_dir("path-within-symlink");
+        let base = dir.join("server");
+        std::fs::create_dir_all(&base).unwrap();
+        std::fs::create_dir_all(dir.join("outside")).unwrap();
+        std::os::unix::fs::symlink(dir.join("outside"), base.join("linked")).unwrap();
+        std::os::unix::fs::symlink(dir.join("missing"), base.join("dangling")).unwrap();
+        std::os::unix::fs::symlink(base.join("config"), base.join("inside")).unwrap();
+        std::fs::create_dir_all(base.join("config")).unwrap();
+
+        assert!(ensure_path_within(&base, "linked").is_err());
+        assert!(ensure_path_within(&base, "linked/new.jar").is_err());
+        assert!(ensure_path_within(&base, "linked/new/deeper/new.jar").is_err());
+        assert!(ensure_path_within(&base, "dangling").is_err());
+        assert!(ensure_path_within(&base, "dangling/new.jar").is_err());
+
+        // A symlink that stays inside the base is fine
+        assert_eq!(ensure_path_within(&base, "inside/new.json").unwrap(), base.join("inside/new.json"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_missing_file_is_not_found() {
+        let dir = temp_dir("path-within-file");
+        std::fs::create_dir_all(dir.join("config")).unwrap();
+        std::fs::write(dir.join("server.properties"), "").unwrap();
+
+        assert_eq!(ensure_file_within(&dir, "server.properties").unwrap(), dir.join("server.properties"));
+        assert!(is_not_found(&ensure_file_within(&dir, "missing.txt").unwrap_err()));
+        assert!(is_not_found(&ensure_file_within(&dir, "config").unwrap_err()));
+        assert!(!is_not_found(&ensure_file_within(&dir, "../server.properties").unwrap_err()));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
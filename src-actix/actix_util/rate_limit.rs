@@ -0,0 +1,241 @@
+use crate::authentication::auth_data::UserData;
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform}, error::InternalError, http::header::RETRY_AFTER,
+    Error,
+    HttpMessage,
+    HttpResponse,
+};
+use cache::TtlCache;
+use futures::future::LocalBoxFuture;
+use serde_json::json;
+use std::rc::Rc;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+/// Buckets of every route group, keyed by `group:client`.
+///
+/// Shared between workers so each client has one bucket per group no matter which worker
+/// handles the request.
+static BUCKETS: LazyLock<TtlCache<String, TokenBucket>> = LazyLock::new(|| TtlCache::new(Duration::from_secs(60)));
+
+/// How often buckets that have filled up again are dropped
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Periodically drop the buckets of idle clients. Expired buckets are only skipped on lookup,
+/// so without this every address that ever made a request would keep its bucket forever.
+pub fn start_cleanup() {
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+        loop {
+            interval.tick().await;
+            BUCKETS.cleanup().await;
+        }
+    });
+}
+
+/// How many requests a client may make in a route group
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitConfig {
+    /// Requests that may be made at once before throttling starts
+    pub burst: u32,
+    /// Requests allowed per second once the burst is used up
+    pub per_second: f64,
+}
+
+impl RateLimitConfig {
+    /// Time for an empty bucket to fill up again; after this an idle bucket can be forgotten
+    fn refill_time(&self) -> Duration {
+        Duration::from_secs_f64(self.burst as f64 / self.per_second)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn full(config: &RateLimitConfig, now: Instant) -> Self {
+        Self { tokens: config.burst as f64, refilled_at: now }
+    }
+
+    /// Take a token at `now`, or return how long until one is available
+    fn take(&mut self, config: &RateLimitConfig, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * config.per_second).min(config.burst as f64);
+        self.refilled_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / config.per_second))
+        }
+    }
+}
+
+/// Limits requests per client with a token bucket, answering `429 Too Many Requests` with a
+/// `Retry-After` header once a client runs out.
+///
+/// Clients are identified by user when the request is already authenticated, i.e. when this is
+/// wrapped inside [`AuthenticationMiddleware`](crate::authentication::AuthenticationMiddleware),
+/// and by IP address otherwise. The socket address is used rather than forwarding headers, which
+/// a client could set to anything.
+pub struct RateLimit {
+    group: &'static str,
+    config: RateLimitConfig,
+}
+
+impl RateLimit {
+    pub fn new(group: &'static str, config: RateLimitConfig) -> Self {
+        Self { group, config }
+    }
+
+    /// Strict limit for login and registration, to slow down password guessing
+    pub fn login() -> Self {
+        Self::new("login", RateLimitConfig { burst: 10, per_second: 1.0 / 6.0 })
+    }
+
+    /// General limit for the API, high enough for the frontend and chunked uploads
+    pub fn api() -> Self {
+        Self::new("api", RateLimitConfig { burst: 300, per_second: 30.0 })
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RateLimitMiddleware<S>;
+    type InitError = ();
+    type Future = std::pin::Pin<Box<dyn Future<Output = Result<Self::Transform, Self::InitError>> + 'static>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        let (group, config) = (self.group, self.config);
+        Box::pin(async move { Ok(RateLimitMiddleware { service: Rc::new(service), group, config }) })
+    }
+}
+pub struct RateLimitMiddleware<S> {
+    service: Rc<S>,
+    group: &'static str,
+    config: RateLimitConfig,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let config = self.config;
+        let client = match req.extensions().get::<UserData>().and_then(|user| user.id) {
+            Some(user_id) => format!("user:{}", user_id),
+            None => format!("ip:{}", req.peer_addr().map(|addr| addr.ip().to_string()).unwrap_or_default()),
+        };
+        let key = format!("{}:{}", self.group, client);
+
+        Box::pin(async move {
+            let now = Instant::now();
+            let result = BUCKETS
+                .update_with_ttl(key, config.refill_time(), |bucket| {
+                    let mut bucket = bucket.unwrap_or_else(|| TokenBucket::full(&config, now));
+                    let result = bucket.take(&config, now);
+                    (bucket, result)
+                })
+                .await;
+
+            if let Err(retry_after) = result {
+                let retry_after = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+                let response = HttpResponse::TooManyRequests().insert_header((RETRY_AFTER, retry_after)).json(json!({
+                    "error": format!("Too many requests, try again in {} seconds", retry_after),
+                    "retry_after": retry_after,
+                }));
+                return Err(InternalError::from_response("Too many requests", response).into());
+            }
+            service.call(req).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, init_service, try_call_service, TestRequest};
+    use actix_web::{web, App};
+
+    const BURST: RateLimitConfig = RateLimitConfig { burst: 5, per_second: 0.5 };
+
+    fn request(ip: &str) -> TestRequest {
+        TestRequest::get().uri("/").peer_addr(format!("{}:40000", ip).parse().unwrap())
+    }
+
+    #[actix_web::test]
+    async fn test_burst_is_throttled() {
+        let app = init_service(App::new().service(web::resource("/").wrap(RateLimit::new("test-burst", BURST)).to(HttpResponse::Ok))).await;
+
+        for _ in 0..BURST.burst {
+            let response = call_service(&app, request("10.0.0.1").to_request()).await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let response = try_call_service(&app, request("10.0.0.1").to_request()).await.unwrap_err().error_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = response.headers().get(RETRY_AFTER).unwrap().to_str().unwrap().parse().unwrap();
+        assert_eq!(retry_after, 2);
+
+        // Other clients have their own bucket
+        let response = call_service(&app, request("10.0.0.2").to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_authenticated_users_are_limited_separately() {
+        let app = init_service(App::new().service(web::resource("/").wrap(RateLimit::new("test-users", BURST)).to(HttpResponse::Ok))).await;
+        let as_user = |id: u64| {
+            let request = request("10.0.0.3").to_request();
+            request.extensions_mut().insert(UserData { id: Some(id), ..Default::default() });
+            request
+        };
+
+        for _ in 0..BURST.burst {
+            assert_eq!(call_service(&app, as_user(1)).await.status(), StatusCode::OK);
+        }
+        assert!(try_call_service(&app, as_user(1)).await.is_err());
+
+        // Same address, different user
+        assert_eq!(call_service(&app, as_user(2)).await.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::full(&BURST, start);
+        for _ in 0..BURST.burst {
+            assert!(bucket.take(&BURST, start).is_ok());
+        }
+        assert_eq!(bucket.take(&BURST, start), Err(Duration::from_secs(2)));
+
+        // One token every two seconds
+        assert!(bucket.take(&BURST, start + Duration::from_secs(1)).is_err());
+        assert!(bucket.take(&BURST, start + Duration::from_secs(2)).is_ok());
+        assert!(bucket.take(&BURST, start + Duration::from_secs(2)).is_err());
+
+        // Never more than the burst, however long the client was idle
+        let later = start + Duration::from_secs(3600);
+        for _ in 0..BURST.burst {
+            assert!(bucket.take(&BURST, later).is_ok());
+        }
+        assert!(bucket.take(&BURST, later).is_err());
+    }
+}
@@ -7,10 +7,12 @@ pub async fn initialize_databases(pool: &Pool) -> Result<()> {
 
 	// Initialize the databases
 	crate::authentication::initialize(pool).await?;
+	crate::audit::initialize(pool).await?;
 	crate::server::initialize(pool).await?;
 	crate::server::installed_mods::initialize(pool).await?;
 	crate::java::initialize(pool).await?;
 	crate::notifications::initialize(pool).await?;
+	crate::scheduled_tasks::initialize(pool).await?;
 
 	Ok(())
 }
@@ -0,0 +1,86 @@
+use crate::database::Row;
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::{Error, FromRow, Row as _};
+
+/// Security-relevant actions recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Login,
+    ServerStart,
+    ServerStop,
+    ServerRestart,
+    ServerKill,
+    FileDelete,
+    SettingsChange,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Login => "login",
+            Self::ServerStart => "server_start",
+            Self::ServerStop => "server_stop",
+            Self::ServerRestart => "server_restart",
+            Self::ServerKill => "server_kill",
+            Self::FileDelete => "file_delete",
+            Self::SettingsChange => "settings_change",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "login" => Some(Self::Login),
+            "server_start" => Some(Self::ServerStart),
+            "server_stop" => Some(Self::ServerStop),
+            "server_restart" => Some(Self::ServerRestart),
+            "server_kill" => Some(Self::ServerKill),
+            "file_delete" => Some(Self::FileDelete),
+            "settings_change" => Some(Self::SettingsChange),
+            _ => None,
+        }
+    }
+}
+
+/// One action in the audit log
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AuditEntry {
+    pub id: String,
+    #[serde(serialize_with = "hash_id")]
+    pub user_id: u64,
+    pub action: AuditAction,
+    /// What the action was taken on: the username for logins, the server id for server actions,
+    /// `server_id:path` for deleted files and `settings` for settings changes
+    pub target: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Narrows an audit log query; fields left as `None` match every entry
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub user_id: Option<u64>,
+    pub action: Option<AuditAction>,
+    /// Only entries recorded at or after this time
+    pub since: Option<DateTime<Utc>>,
+}
+
+fn hash_id<S>(id: &u64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&serde_hash::hashids::encode_single(*id))
+}
+
+impl<'a> FromRow<'a, Row> for AuditEntry {
+    fn from_row(row: &'a Row) -> Result<Self, Error> {
+        let action: String = row.try_get("action")?;
+        Ok(AuditEntry {
+            id: row.try_get("id")?,
+            user_id: row.try_get::<i64, _>("user_id")? as u64,
+            action: AuditAction::parse(&action).ok_or_else(|| Error::Decode(format!("Unknown audit action: {}", action).into()))?,
+            target: row.try_get("target")?,
+            timestamp: row.try_get("timestamp")?,
+        })
+    }
+}
@@ -0,0 +1,100 @@
+use crate::audit::audit_data::{AuditAction, AuditEntry, AuditFilter};
+use crate::database::{Pool, sql};
+use anyhow::Result;
+use log::debug;
+use sqlx::Executor;
+use uuid::Uuid;
+
+#[cfg(feature = "sqlite")]
+static CREATE_AUDIT_LOG_TABLE_SQL: &str = include_str!("../../resources/sql/sqlite/audit_log.sql");
+#[cfg(feature = "mysql")]
+static CREATE_AUDIT_LOG_TABLE_SQL: &str = include_str!("../../resources/sql/mysql/audit_log.sql");
+#[cfg(feature = "postgres")]
+static CREATE_AUDIT_LOG_TABLE_SQL: &str = include_str!("../../resources/sql/postgres/audit_log.sql");
+
+/// Most entries returned by a single query, newest first
+const MAX_ENTRIES: u32 = 1000;
+
+/// Initialize the audit log table
+pub async fn initialize(pool: &Pool) -> Result<()> {
+    debug!("Initializing audit log database...");
+    pool.execute(CREATE_AUDIT_LOG_TABLE_SQL).await?;
+    Ok(())
+}
+
+impl AuditEntry {
+    /// Append an entry to the audit log
+    pub async fn create(user_id: u64, action: AuditAction, target: impl Into<String>, pool: &Pool) -> Result<Self> {
+        let entry = AuditEntry { id: Uuid::new_v4().to_string(), user_id, action, target: target.into(), timestamp: chrono::Utc::now() };
+
+        sqlx::query(&sql("INSERT INTO audit_log (id, user_id, action, target, timestamp) VALUES (?, ?, ?, ?, ?)"))
+            .bind(&entry.id)
+            .bind(user_id as i64)
+            .bind(action.as_str())
+            .bind(&entry.target)
+            .bind(entry.timestamp)
+            .execute(pool)
+            .await?;
+
+        Ok(entry)
+    }
+
+    /// Get the entries matching `filter`, newest first
+    pub async fn find(filter: &AuditFilter, pool: &Pool) -> Result<Vec<Self>> {
+        let mut conditions = Vec::new();
+        if filter.user_id.is_some() {
+            conditions.push("user_id = ?");
+        }
+        if filter.action.is_some() {
+            conditions.push("action = ?");
+        }
+        if filter.since.is_some() {
+            conditions.push("timestamp >= ?");
+        }
+        let where_clause = if conditions.is_empty() { String::new() } else { format!(" WHERE {}", conditions.join(" AND ")) };
+        let statement = format!("SELECT id, user_id, action, target, timestamp FROM audit_log{} ORDER BY timestamp DESC LIMIT {}", where_clause, MAX_ENTRIES);
+        let statement = sql(&statement);
+
+        let mut query = sqlx::query_as::<_, AuditEntry>(&statement);
+        if let Some(user_id) = filter.user_id {
+            query = query.bind(user_id as i64);
+        }
+        if let Some(action) = filter.action {
+            query = query.bind(action.as_str());
+        }
+        if let Some(since) = filter.since {
+            query = query.bind(since);
+        }
+
+        Ok(query.fetch_all(pool).await?)
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[tokio::test]
+    async fn test_deletion_is_found_by_filter() {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        initialize(&pool).await.unwrap();
+
+        let before = chrono::Utc::now();
+        AuditEntry::create(1, AuditAction::Login, "admin", &pool).await.unwrap();
+        let deletion = AuditEntry::create(1, AuditAction::FileDelete, "jR3gkLqnZ9a2Vb7x:world/level.dat", &pool).await.unwrap();
+        AuditEntry::create(2, AuditAction::FileDelete, "jR3gkLqnZ9a2Vb7x:logs", &pool).await.unwrap();
+
+        let filter = AuditFilter { user_id: Some(1), action: Some(AuditAction::FileDelete), since: Some(before) };
+        assert_eq!(AuditEntry::find(&filter, &pool).await.unwrap(), vec![deletion.clone()]);
+
+        // Each filter narrows the log on its own
+        let by_action = AuditFilter { action: Some(AuditAction::FileDelete), ..Default::default() };
+        assert_eq!(AuditEntry::find(&by_action, &pool).await.unwrap().len(), 2);
+        let by_user = AuditFilter { user_id: Some(1), ..Default::default() };
+        assert_eq!(AuditEntry::find(&by_user, &pool).await.unwrap().len(), 2);
+        let later = AuditFilter { since: Some(deletion.timestamp + chrono::Duration::seconds(1)), ..Default::default() };
+        assert!(AuditEntry::find(&later, &pool).await.unwrap().is_empty());
+        assert_eq!(AuditEntry::find(&AuditFilter::default(), &pool).await.unwrap().len(), 3);
+    }
+}
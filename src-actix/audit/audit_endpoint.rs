@@ -0,0 +1,48 @@
+use crate::actix_util::http_error::Result;
+use crate::audit::audit_data::{AuditAction, AuditEntry, AuditFilter};
+use crate::authentication::auth_data::UserRequestExt;
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use anyhow::anyhow;
+use serde_hash::hashids::decode_single;
+use serde_json::json;
+use sqlx::types::chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// GET /api/audit?user=&action=&since= - Search the audit log, newest first
+#[get("")]
+pub async fn get_audit_log(query: web::Query<HashMap<String, String>>, req: HttpRequest) -> Result<impl Responder> {
+    let user = req.get_user()?;
+
+    // Only admins can see what other users have done
+    if !user.is_admin() {
+        return Ok(HttpResponse::Forbidden().json(json!({
+            "error": "You don't have permission to view the audit log"
+        })));
+    }
+
+    let user_id = query.get("user").map(|user| decode_single(user.as_str())).transpose()?;
+    let action = query
+        .get("action")
+        .map(|action| AuditAction::parse(action).ok_or(anyhow!("Unknown action: {}", action)))
+        .transpose()?;
+    let since = query
+        .get("since")
+        .map(|since| DateTime::parse_from_rfc3339(since).map(|since| since.with_timezone(&Utc)).map_err(|_| anyhow!("since must be an RFC 3339 timestamp")))
+        .transpose()?;
+
+    let filter = AuditFilter { user_id, action, since };
+    let entries = AuditEntry::find(&filter, crate::database::get_pool()).await?;
+    Ok(HttpResponse::Ok().json(json!({ "entries": entries })))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/audit")
+            .service(get_audit_log)
+            .default_service(web::to(|| async {
+                HttpResponse::NotFound().json(json!({
+                    "error": "API endpoint not found".to_string(),
+                }))
+            })),
+    );
+}
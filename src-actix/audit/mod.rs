@@ -0,0 +1,22 @@
+pub mod audit_data;
+mod audit_db;
+mod audit_endpoint;
+
+pub use audit_data::{AuditAction, AuditEntry, AuditFilter};
+pub use audit_db::initialize;
+pub use audit_endpoint::configure;
+
+use log::{error, warn};
+
+/// Record that `user_id` took `action` on `target`. A failure to write the entry is logged
+/// rather than returned, so it never undoes or blocks the action being recorded.
+pub async fn record(user_id: u64, action: AuditAction, target: impl Into<String>) {
+    let target = target.into();
+    let Some(pool) = crate::database::try_get_pool() else {
+        warn!("Database not ready, not auditing {} of {} by user {}", action.as_str(), target, user_id);
+        return;
+    };
+    if let Err(e) = AuditEntry::create(user_id, action, target.clone(), pool).await {
+        error!("Failed to audit {} of {} by user {}: {}", action.as_str(), target, user_id, e);
+    }
+}
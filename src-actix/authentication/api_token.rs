@@ -0,0 +1,257 @@
+use crate::authentication::auth_data::UserData;
+use crate::authentication::user_permissions::PermissionFlag;
+use crate::database::{sql, Pool, Row};
+use anyhow::{anyhow, Result};
+use enumflags2::BitFlags;
+use rand::Rng;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::{Error, FromRow, Row as _};
+
+/// Prefix that tells API tokens apart from session tokens in the `Authorization` header
+pub const API_TOKEN_PREFIX: &str = "obsp_";
+
+/// Random characters after the prefix
+const API_TOKEN_LENGTH: usize = 40;
+
+/// A token for scripting the panel without a session, e.g. from CI.
+///
+/// Only a SHA-256 hash of the token is stored. Requests made with a token act as its owner,
+/// limited to the permissions in the token's scope.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiTokenData {
+    #[serde(serialize_with = "hash_id")]
+    pub id: u64,
+    #[serde(skip)]
+    pub user_id: u64,
+    pub name: String,
+    #[serde(skip)]
+    pub token_hash: String,
+    /// Permissions the token may use; the owner's current permissions still apply on top
+    #[serde(serialize_with = "serialize_permissions")]
+    pub permissions: BitFlags<PermissionFlag>,
+    pub created_at: DateTime<Utc>,
+    pub last_used: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked: bool,
+}
+
+fn hash_id<S>(id: &u64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&serde_hash::hashids::encode_single(*id))
+}
+
+fn serialize_permissions<S>(permissions: &BitFlags<PermissionFlag>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let permissions: Vec<PermissionFlag> = permissions.iter().collect();
+    serializer.serialize_some(&permissions)
+}
+
+impl<'a> FromRow<'a, Row> for ApiTokenData {
+    fn from_row(row: &'a Row) -> Result<Self, Error> {
+        let id: i64 = row.try_get("id")?;
+        let user_id: i64 = row.try_get("user_id")?;
+        let name: String = row.try_get("name")?;
+        let token_hash: String = row.try_get("token_hash")?;
+        let permissions: i32 = row.try_get("permissions")?;
+        let created_at: DateTime<Utc> = row.try_get("created_at")?;
+        let last_used: Option<DateTime<Utc>> = row.try_get("last_used")?;
+        let expires_at: Option<DateTime<Utc>> = row.try_get("expires_at")?;
+        let revoked: i32 = row.try_get("revoked")?;
+        Ok(ApiTokenData {
+            id: id as u64,
+            user_id: user_id as u64,
+            name,
+            token_hash,
+            permissions: BitFlags::<PermissionFlag>::from_bits_truncate(permissions as u16),
+            created_at,
+            last_used,
+            expires_at,
+            revoked: revoked != 0,
+        })
+    }
+}
+
+impl ApiTokenData {
+    /// Whether the token can still be used at `now`
+    pub fn is_usable(&self, now: DateTime<Utc>) -> bool {
+        !self.revoked && self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+
+    /// Restrict `user` to the permissions that both the user and this token have.
+    ///
+    /// Admin users are expanded to every permission first, so a token scoped to e.g.
+    /// `OperateServer` only grants that, even for an admin.
+    pub fn apply_scope(&self, mut user: UserData) -> UserData {
+        let user_permissions = if user.is_admin() { BitFlags::<PermissionFlag>::all() } else { user.permissions };
+        user.permissions = user_permissions & self.permissions;
+        user
+    }
+
+    /// Issue a token for `user`, returning the stored token and the plain token, which is
+    /// only shown once.
+    pub async fn create(
+        user: &UserData,
+        name: impl Into<String>,
+        permissions: BitFlags<PermissionFlag>,
+        expires_at: Option<DateTime<Utc>>,
+        pool: &Pool,
+    ) -> Result<(Self, String)> {
+        let user_id = user.id.ok_or(anyhow!("User ID is not set"))?;
+        let token = generate_token();
+        let token_hash = hash_token(&token);
+        sqlx::query(&sql("INSERT INTO api_tokens (user_id, name, token_hash, permissions, expires_at) VALUES (?, ?, ?, ?, ?)"))
+            .bind(user_id as i64)
+            .bind(name.into())
+            .bind(&token_hash)
+            .bind(permissions.bits() as i32)
+            .bind(expires_at)
+            .execute(pool)
+            .await?;
+        let data = sqlx::query_as::<_, ApiTokenData>(&sql("SELECT * FROM api_tokens WHERE token_hash = ? LIMIT 1"))
+            .bind(&token_hash)
+            .fetch_one(pool)
+            .await?;
+        Ok((data, token))
+    }
+
+    /// Find the user an API token belongs to, with the token's scope applied
+    pub async fn authenticate(token: &str, pool: &Pool) -> Result<UserData> {
+        let data = sqlx::query_as::<_, ApiTokenData>(&sql("SELECT * FROM api_tokens WHERE token_hash = ? LIMIT 1"))
+            .bind(hash_token(token))
+            .fetch_optional(pool)
+            .await?
+            .filter(|data| data.is_usable(Utc::now()))
+            .ok_or(anyhow!("API token is invalid, expired, or revoked"))?;
+
+        let user = sqlx::query_as::<_, UserData>(&sql("SELECT * FROM users WHERE id = ? LIMIT 1"))
+            .bind(data.user_id as i64)
+            .fetch_optional(pool)
+            .await?
+            .filter(|user| user.is_active)
+            .ok_or(anyhow!("API token owner does not exist or is disabled"))?;
+
+        sqlx::query(&sql("UPDATE api_tokens SET last_used = ? WHERE id = ?"))
+            .bind(Utc::now())
+            .bind(data.id as i64)
+            .execute(pool)
+            .await?;
+        Ok(data.apply_scope(user))
+    }
+
+    pub async fn list(user_id: u64, pool: &Pool) -> Result<Vec<Self>> {
+        let tokens = sqlx::query_as::<_, ApiTokenData>(&sql("SELECT * FROM api_tokens WHERE user_id = ? ORDER BY created_at DESC"))
+            .bind(user_id as i64)
+            .fetch_all(pool)
+            .await?;
+        Ok(tokens)
+    }
+
+    /// Revoke one of `user_id`'s tokens, returning whether it existed
+    pub async fn revoke(id: u64, user_id: u64, pool: &Pool) -> Result<bool> {
+        let result = sqlx::query(&sql("UPDATE api_tokens SET revoked = 1 WHERE id = ? AND user_id = ?"))
+            .bind(id as i64)
+            .bind(user_id as i64)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// Whether a bearer token is an API token rather than a session token
+pub fn is_api_token(token: &str) -> bool {
+    token.starts_with(API_TOKEN_PREFIX)
+}
+
+fn generate_token() -> String {
+    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    let mut rng = rand::rng();
+    let random: String = (0..API_TOKEN_LENGTH).map(|_| CHARSET[rng.random_range(0..CHARSET.len())] as char).collect();
+    format!("{}{}", API_TOKEN_PREFIX, random)
+}
+
+/// Tokens are long and random, so a fast hash is enough and keeps lookups by hash possible
+pub fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(permissions: BitFlags<PermissionFlag>) -> ApiTokenData {
+        ApiTokenData {
+            id: 1,
+            user_id: 1,
+            name: "ci".to_string(),
+            token_hash: String::new(),
+            permissions,
+            created_at: Utc::now(),
+            last_used: None,
+            expires_at: None,
+            revoked: false,
+        }
+    }
+
+    fn user(permissions: BitFlags<PermissionFlag>) -> UserData {
+        UserData { id: Some(1), username: "ci".to_string(), permissions, ..Default::default() }
+    }
+
+    #[test]
+    fn test_generated_tokens() {
+        let first = generate_token();
+        let second = generate_token();
+        assert!(is_api_token(&first));
+        assert_eq!(first.len(), API_TOKEN_PREFIX.len() + API_TOKEN_LENGTH);
+        assert_ne!(first, second);
+        assert_eq!(hash_token(&first), hash_token(&first));
+        assert_ne!(hash_token(&first), hash_token(&second));
+        assert!(!is_api_token("Mg8xK2Fe3dBa9zLq$2b$10$abcdef"));
+    }
+
+    #[test]
+    fn test_token_auth_acts_as_owner() {
+        let data = token(PermissionFlag::OperateServer | PermissionFlag::CreateBackup);
+        let user = data.apply_scope(user(PermissionFlag::OperateServer | PermissionFlag::CreateBackup | PermissionFlag::DeleteFiles));
+        assert_eq!(user.id, Some(1));
+        assert!(user.can_operate_server());
+        assert!(user.can_create_backup());
+    }
+
+    #[test]
+    fn test_scope_is_enforced() {
+        // A token cannot grant what the scope leaves out, even to an admin
+        let data = token(PermissionFlag::OperateServer.into());
+        let admin = data.apply_scope(user(PermissionFlag::Admin.into()));
+        assert!(admin.can_operate_server());
+        assert!(!admin.is_admin());
+        assert!(!admin.can_delete_files());
+
+        // Nor more than the owner has
+        let data = token(PermissionFlag::OperateServer | PermissionFlag::ManageUsers);
+        let user = data.apply_scope(user(PermissionFlag::OperateServer.into()));
+        assert!(user.can_operate_server());
+        assert!(!user.has_permission(PermissionFlag::ManageUsers));
+    }
+
+    #[test]
+    fn test_revoked_and_expired_tokens_are_unusable() {
+        let now = Utc::now();
+        let mut data = token(PermissionFlag::OperateServer.into());
+        assert!(data.is_usable(now));
+
+        data.expires_at = Some(now + chrono::Duration::hours(1));
+        assert!(data.is_usable(now));
+        data.expires_at = Some(now - chrono::Duration::seconds(1));
+        assert!(!data.is_usable(now));
+
+        data.expires_at = None;
+        data.revoked = true;
+        assert!(!data.is_usable(now));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::authentication::session::SessionData;
 use crate::authentication::user_permissions::PermissionFlag;
 use actix_web::HttpMessage;
 use anyhow::Result;
@@ -146,7 +147,7 @@ impl UserData {
         self.has_permission(PermissionFlag::ModifyFiles)
     }
 
-    pub async fn authenticate_with_session_token(token: &str) -> Result<UserData> {
+    pub async fn authenticate_with_session_token(token: &str) -> Result<(UserData, SessionData)> {
         let pool = crate::database::get_pool();
         let user = UserData::login_with_token(token, pool).await?;
         if let Some(user) = user { Ok(user) } else { Err(anyhow::anyhow!("User doesn't exist or token is invalid")) }
@@ -155,10 +156,16 @@ impl UserData {
 
 pub trait UserRequestExt {
     fn get_user(&self) -> Result<UserData>;
+    /// The session the request was made with, or `None` for API tokens
+    fn get_session(&self) -> Option<SessionData>;
 }
 impl UserRequestExt for actix_web::HttpRequest {
     fn get_user(&self) -> Result<UserData> {
         let user = self.extensions().get::<UserData>().cloned();
         if let Some(user) = user { Ok(user) } else { Err(anyhow::anyhow!("User doesn't exist or token is invalid")) }
     }
+
+    fn get_session(&self) -> Option<SessionData> {
+        self.extensions().get::<SessionData>().cloned()
+    }
 }
@@ -1,4 +1,5 @@
 use crate::authentication::auth_data::UserData;
+use crate::authentication::session::SessionData;
 use crate::authentication::user_permissions::PermissionFlag;
 use crate::database::{Pool, sql};
 use anyhow::Result;
@@ -35,19 +36,22 @@ impl UserData {
             Err(anyhow::anyhow!("User not found"))
         }
     }
-    pub async fn login_with_token(token: &str, pool: &Pool) -> Result<Option<Self>> {
+    /// Find the user a session token belongs to, along with its session. Tokens whose session
+    /// has been revoked are rejected even though they are otherwise valid.
+    pub async fn login_with_token(token: &str, pool: &Pool) -> Result<Option<(Self, SessionData)>> {
         let id_part = &token[..16];
-        let token = &token[16..];
+        let token_part = &token[16..];
         let id = serde_hash::hashids::decode_single(id_part).map_err(|e| anyhow::anyhow!("Failed to decode user ID: {}", e))?;
         let user = sqlx::query_as::<_, UserData>(&*sql(r#"SELECT * FROM users WHERE id = ? LIMIT 1"#)).bind(id as i64).fetch_optional(pool).await?;
-        if let Some(ref user) = user {
-            if !bcrypt::verify(format!("{}{}", user.username, user.password), token)? {
-                return Err(anyhow::anyhow!("Invalid token"));
-            } else {
-                user.update_login_time(pool).await?;
-            }
+        let Some(user) = user else {
+            return Ok(None);
+        };
+        if !bcrypt::verify(format!("{}{}", user.username, user.password), token_part)? {
+            return Err(anyhow::anyhow!("Invalid token"));
         }
-        Ok(user)
+        let session = SessionData::authenticate(token, id, pool).await?;
+        user.update_login_time(pool).await?;
+        Ok(Some((user, session)))
     }
 
     pub async fn register(username: impl  Into<String>, password: impl Into<String>, pool: &Pool) -> Result<Self> {
@@ -1,20 +1,50 @@
 use crate::actix_util::http_error::Result;
+use crate::actix_util::rate_limit::RateLimit;
+use crate::audit;
+use crate::audit::AuditAction;
 use crate::authentication;
+use crate::authentication::api_token::ApiTokenData;
 use crate::authentication::auth_data::{TOKEN_KEY, UserData, UserRequestExt};
+use crate::authentication::session::SessionData;
+use crate::authentication::two_factor;
+use crate::authentication::two_factor::TwoFactorData;
 use crate::authentication::user_permissions::PermissionFlag;
-use actix_web::{HttpRequest, HttpResponse, Responder, get, post, web, put};
+use actix_web::{HttpRequest, HttpResponse, Responder, delete, get, post, web, put};
 use anyhow::anyhow;
 use enumflags2::BitFlags;
+use serde::Serialize;
 use serde_json::json;
 
-#[post("/")]
-pub async fn login(body: web::Json<serde_json::Value>) -> Result<impl Responder> {
+#[post("/", wrap = "RateLimit::login()")]
+pub async fn login(body: web::Json<serde_json::Value>, req: HttpRequest) -> Result<impl Responder> {
     let username = body.get("username").expect("Missing username").as_str().expect("Username must be a string").to_string();
     let password = body.get("password").expect("Missing password").as_str().expect("Password must be a string").to_string();
     let remember = body.get("remember").is_some_and(|v| v.as_bool().unwrap_or(false));
 
     let pool = crate::database::get_pool();
     let (token, user) = UserData::login(username, password, pool).await?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    // Users with two-factor authentication enabled also need a code from their authenticator app
+    if let Some(mut two_factor) = TwoFactorData::get(user_id, pool).await?.filter(|data| data.enabled) {
+        let Some(code) = body.get("code").and_then(|v| v.as_str()) else {
+            return Ok(HttpResponse::Unauthorized().json(json!({
+                "message": "Two-factor authentication code required",
+                "two_factor_required": true,
+            })));
+        };
+        if !two_factor.verify(code, two_factor::now()) {
+            return Ok(HttpResponse::Unauthorized().json(json!({
+                "message": "Invalid two-factor authentication code",
+                "two_factor_required": true,
+            })));
+        }
+        two_factor.save(pool).await?;
+    }
+    let user_agent = req.headers().get(actix_web::http::header::USER_AGENT).and_then(|v| v.to_str().ok()).map(String::from);
+    let ip_address = req.connection_info().realip_remote_addr().map(String::from);
+    SessionData::create(user_id, &token, user_agent, ip_address, pool).await?;
+    audit::record(user_id, AuditAction::Login, user.username.clone()).await;
 
     let cookie = actix_web::cookie::Cookie::build(TOKEN_KEY, &token).path("/").secure(true).http_only(true);
     let cookie = if remember { cookie.max_age(actix_web::cookie::time::Duration::days(30)) } else { cookie }.finish();
@@ -34,8 +64,11 @@ pub async fn login_with_token(req: HttpRequest) -> Result<impl Responder> {
 }
 
 #[get("/logout/")]
-pub async fn logout() -> Result<impl Responder> {
-    // Invalidate the session by clearing the token cookie
+pub async fn logout(req: HttpRequest) -> Result<impl Responder> {
+    // Invalidate the session so the token stops working, and clear the token cookie
+    if let Some(session) = req.get_session() {
+        SessionData::revoke(session.id, session.user_id, crate::database::get_pool()).await?;
+    }
     let cookie = actix_web::cookie::Cookie::build(TOKEN_KEY, "")
         .path("/")
         .secure(true)
@@ -45,7 +78,7 @@ pub async fn logout() -> Result<impl Responder> {
     Ok(HttpResponse::PermanentRedirect().append_header(("Location", "/")).cookie(cookie.clone()).finish())
 }
 
-#[put("/")]
+#[put("/", wrap = "RateLimit::login()")]
 pub async fn register(body: web::Json<serde_json::Value>) -> Result<impl Responder> {
     let username = body.get("username").expect("Missing username").as_str().expect("Username must be a string").to_string();
     let password = body.get("password").expect("Missing password").as_str().expect("Password must be a string").to_string();
@@ -380,6 +413,218 @@ pub async fn force_password_reset(path: web::Path<String>, req: HttpRequest) ->
     })))
 }
 
+#[get("/2fa")]
+pub async fn get_two_factor_status(req: HttpRequest) -> Result<impl Responder> {
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    let pool = crate::database::get_pool();
+    let two_factor = TwoFactorData::get(user_id, pool).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "enabled": two_factor.as_ref().is_some_and(|data| data.enabled),
+        "recovery_codes_remaining": two_factor.filter(|data| data.enabled).map(|data| data.recovery_codes.len()).unwrap_or(0),
+    })))
+}
+
+#[post("/2fa/enroll")]
+pub async fn enroll_two_factor(req: HttpRequest) -> Result<impl Responder> {
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    let pool = crate::database::get_pool();
+    if TwoFactorData::is_enabled(user_id, pool).await? {
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "message": "Two-factor authentication is already enabled, disable it before enrolling again",
+        })));
+    }
+
+    // Replaces any unconfirmed enrollment
+    let (two_factor, recovery_codes) = TwoFactorData::enroll(user_id);
+    two_factor.save(pool).await?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "secret": two_factor.secret,
+        "otpauth_uri": two_factor.otpauth_uri(&user.username),
+        "recovery_codes": recovery_codes,
+    })))
+}
+
+#[post("/2fa/confirm")]
+pub async fn confirm_two_factor(body: web::Json<serde_json::Value>, req: HttpRequest) -> Result<impl Responder> {
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+    let code = body.get("code").and_then(|v| v.as_str()).ok_or(anyhow!("Missing code"))?;
+
+    let pool = crate::database::get_pool();
+    let Some(mut two_factor) = TwoFactorData::get(user_id, pool).await?.filter(|data| !data.enabled) else {
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "message": "No pending two-factor enrollment, enroll first",
+        })));
+    };
+    // Recovery codes cannot confirm an enrollment; the authenticator app must be set up
+    if !two_factor.verify_totp(code, two_factor::now()) {
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "message": "Invalid two-factor authentication code",
+        })));
+    }
+    two_factor.enabled = true;
+    two_factor.save(pool).await?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "message": "Two-factor authentication enabled",
+    })))
+}
+
+#[post("/2fa/disable")]
+pub async fn disable_two_factor(body: web::Json<serde_json::Value>, req: HttpRequest) -> Result<impl Responder> {
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+    let code = body.get("code").and_then(|v| v.as_str()).ok_or(anyhow!("Missing code"))?;
+
+    let pool = crate::database::get_pool();
+    let Some(mut two_factor) = TwoFactorData::get(user_id, pool).await?.filter(|data| data.enabled) else {
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "message": "Two-factor authentication is not enabled",
+        })));
+    };
+    if !two_factor.verify(code, two_factor::now()) {
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "message": "Invalid two-factor authentication code",
+        })));
+    }
+    TwoFactorData::delete(user_id, pool).await?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "message": "Two-factor authentication disabled",
+    })))
+}
+
+#[get("/tokens")]
+pub async fn get_api_tokens(req: HttpRequest) -> Result<impl Responder> {
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    let pool = crate::database::get_pool();
+    let tokens = ApiTokenData::list(user_id, pool).await?;
+    Ok(HttpResponse::Ok().json(tokens))
+}
+
+#[post("/tokens")]
+pub async fn create_api_token(body: web::Json<serde_json::Value>, req: HttpRequest) -> Result<impl Responder> {
+    let user = req.get_user()?;
+    let name = body.get("name").and_then(|v| v.as_str()).map(|s| s.trim()).unwrap_or_default();
+    if name.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "message": "Token name cannot be empty",
+        })));
+    }
+
+    // Without an explicit scope the token gets all of the user's current permissions
+    let available = if user.is_admin() { BitFlags::<PermissionFlag>::all() } else { user.permissions };
+    let permissions = match body.get("permissions").and_then(|v| v.as_array()) {
+        Some(ids) => {
+            let mut permissions = BitFlags::<PermissionFlag>::empty();
+            for id in ids.iter().filter_map(|v| v.as_u64()) {
+                permissions |= PermissionFlag::from_u16(id as u16);
+            }
+            permissions
+        }
+        None => available,
+    };
+    if !available.contains(permissions) {
+        return Ok(HttpResponse::Forbidden().json(json!({
+            "message": "A token cannot have permissions you do not have",
+            "user_permissions": user.permissions,
+        })));
+    }
+
+    let expires_at = body
+        .get("expires_in_days")
+        .and_then(|v| v.as_u64())
+        .map(|days| chrono::Utc::now() + chrono::Duration::days(days as i64));
+
+    let pool = crate::database::get_pool();
+    let (data, token) = ApiTokenData::create(&user, name, permissions, expires_at, pool).await?;
+    Ok(HttpResponse::Created().json(json!({
+        "message": "API token created, it will not be shown again",
+        "token": token,
+        "data": data,
+    })))
+}
+
+#[delete("/tokens/{token_id}")]
+pub async fn revoke_api_token(token_id: web::Path<String>, req: HttpRequest) -> Result<impl Responder> {
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+    let token_id = serde_hash::hashids::decode_single(token_id.as_str()).map_err(|_| anyhow!("Invalid token ID format"))?;
+
+    let pool = crate::database::get_pool();
+    if !ApiTokenData::revoke(token_id, user_id, pool).await? {
+        return Ok(HttpResponse::NotFound().json(json!({
+            "message": "API token not found",
+        })));
+    }
+    Ok(HttpResponse::Ok().json(json!({
+        "message": "API token revoked",
+    })))
+}
+
+/// A session as listed to its owner
+#[derive(Serialize)]
+struct SessionItem {
+    #[serde(flatten)]
+    session: SessionData,
+    /// Whether this is the session making the request
+    current: bool,
+}
+
+#[get("/sessions")]
+pub async fn get_sessions(req: HttpRequest) -> Result<impl Responder> {
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+    let current = req.get_session().map(|session| session.id);
+
+    let pool = crate::database::get_pool();
+    let sessions: Vec<SessionItem> = SessionData::list(user_id, pool)
+        .await?
+        .into_iter()
+        .map(|session| SessionItem { current: Some(session.id) == current, session })
+        .collect();
+    Ok(HttpResponse::Ok().json(sessions))
+}
+
+#[delete("/sessions/{session_id}")]
+pub async fn revoke_session(session_id: web::Path<String>, req: HttpRequest) -> Result<impl Responder> {
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+    let session_id = serde_hash::hashids::decode_single(session_id.as_str()).map_err(|_| anyhow!("Invalid session ID format"))?;
+
+    let pool = crate::database::get_pool();
+    if !SessionData::revoke(session_id, user_id, pool).await? {
+        return Ok(HttpResponse::NotFound().json(json!({
+            "message": "Session not found",
+        })));
+    }
+    Ok(HttpResponse::Ok().json(json!({
+        "message": "Session revoked",
+    })))
+}
+
+/// Sign out everywhere but here. Requests made with an API token revoke every session.
+#[delete("/sessions")]
+pub async fn revoke_other_sessions(req: HttpRequest) -> Result<impl Responder> {
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+    let current = req.get_session().map(|session| session.id);
+
+    let pool = crate::database::get_pool();
+    let revoked = SessionData::revoke_others(user_id, current, pool).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "message": "Other sessions revoked",
+        "revoked": revoked,
+    })))
+}
+
 fn generate_random_password() -> String {
     use rand::Rng;
     const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ\
@@ -404,6 +649,7 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .service(get_permissions_list)
             .service(
                 web::scope("")
+                    .wrap(RateLimit::api())
                     .wrap(authentication::AuthenticationMiddleware)
                     .service(get_users)
                     .service(login_with_token)
@@ -413,7 +659,17 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
                     .service(create_user)
                     .service(update_user)
                     .service(delete_user)
-                    .service(force_password_reset),
+                    .service(force_password_reset)
+                    .service(get_two_factor_status)
+                    .service(enroll_two_factor)
+                    .service(confirm_two_factor)
+                    .service(disable_two_factor)
+                    .service(get_api_tokens)
+                    .service(create_api_token)
+                    .service(revoke_api_token)
+                    .service(get_sessions)
+                    .service(revoke_session)
+                    .service(revoke_other_sessions),
             )
             .default_service(web::to(|| async {
                 HttpResponse::NotFound().json(json!({
@@ -1,3 +1,5 @@
+use crate::authentication::api_token;
+use crate::authentication::api_token::ApiTokenData;
 use crate::authentication::auth_data;
 use crate::authentication::auth_data::UserData;
 use actix_web::{
@@ -49,8 +51,17 @@ where
                 .and_then(|cookie| cookie.value().parse::<String>().ok())
                 .or_else(|| headers.get("Authorization").and_then(|h| h.to_str().ok()).map(|s| s.to_string()));
             if let Some(token) = token {
-                let user = UserData::authenticate_with_session_token(&token).await.map_err(ErrorUnauthorized)?;
-                req.extensions_mut().insert(user);
+                // Automation can send `Authorization: Bearer <api token>` instead of a session
+                let token = token.strip_prefix("Bearer ").unwrap_or(&token).trim();
+                if api_token::is_api_token(token) {
+                    let user = ApiTokenData::authenticate(token, crate::database::get_pool()).await.map_err(ErrorUnauthorized)?;
+                    req.extensions_mut().insert(user);
+                } else {
+                    // The session is kept alongside the user so it can be told apart from the user's other sessions
+                    let (user, session) = UserData::authenticate_with_session_token(token).await.map_err(ErrorUnauthorized)?;
+                    req.extensions_mut().insert(user);
+                    req.extensions_mut().insert(session);
+                }
                 return service.call(req).await.map_err(actix_web::error::ErrorInternalServerError);
             }
             Err(ErrorUnauthorized("Missing or invalid authentication token"))
@@ -1,7 +1,11 @@
+pub mod api_token;
 pub mod auth_data;
 mod auth_db;
 mod auth_endpoint;
 mod auth_middleware;
+pub mod permission_guard;
+pub mod session;
+pub mod two_factor;
 pub mod user_permissions;
 
 pub use auth_db::initialize;
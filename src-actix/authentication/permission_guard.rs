@@ -0,0 +1,88 @@
+use crate::actix_util::http_error::Error;
+use crate::authentication::auth_data::UserData;
+use crate::authentication::user_permissions::PermissionFlag;
+
+/// Something a request does to a server, checked before the handler touches it.
+///
+/// Ownership of the server is checked separately by `ServerData::get`; this only covers what
+/// the user's role allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerRight {
+    /// Start, stop, restart or kill the server and send console or RCON commands
+    ControlServer,
+    /// Change the server's configuration
+    EditSettings,
+    UploadFiles,
+    CreateFiles,
+    ModifyFiles,
+    DeleteFiles,
+}
+
+impl ServerRight {
+    /// The permission that grants this right; admins have every right
+    pub fn permission(self) -> PermissionFlag {
+        match self {
+            Self::ControlServer | Self::EditSettings => PermissionFlag::OperateServer,
+            Self::UploadFiles => PermissionFlag::UploadFiles,
+            Self::CreateFiles => PermissionFlag::CreateFiles,
+            Self::ModifyFiles => PermissionFlag::ModifyFiles,
+            Self::DeleteFiles => PermissionFlag::DeleteFiles,
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Self::ControlServer => "control servers",
+            Self::EditSettings => "modify server configuration",
+            Self::UploadFiles => "upload files",
+            Self::CreateFiles => "create files",
+            Self::ModifyFiles => "modify files",
+            Self::DeleteFiles => "delete files",
+        }
+    }
+}
+
+/// Fail with 403 Forbidden unless `user` has `right`
+pub fn require(user: &UserData, right: ServerRight) -> Result<(), Error> {
+    if user.has_permission(right.permission()) {
+        Ok(())
+    } else {
+        Err(Error::Forbidden(format!("You don't have permission to {}", right.description())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+
+    fn user(permissions: impl Into<enumflags2::BitFlags<PermissionFlag>>) -> UserData {
+        UserData { id: Some(1), username: "steve".to_string(), permissions: permissions.into(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_file_deletion_requires_permission() {
+        let viewer = user(PermissionFlag::OperateServer | PermissionFlag::UploadFiles);
+        let error = require(&viewer, ServerRight::DeleteFiles).unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::FORBIDDEN);
+        assert_eq!(error.error_response().status(), StatusCode::FORBIDDEN);
+        assert_eq!(error.to_string(), "You don't have permission to delete files");
+
+        assert!(require(&user(PermissionFlag::DeleteFiles), ServerRight::DeleteFiles).is_ok());
+        assert!(require(&user(PermissionFlag::Admin), ServerRight::DeleteFiles).is_ok());
+    }
+
+    #[test]
+    fn test_rights_map_to_permissions() {
+        let operator = user(PermissionFlag::OperateServer);
+        assert!(require(&operator, ServerRight::ControlServer).is_ok());
+        assert!(require(&operator, ServerRight::EditSettings).is_ok());
+        assert!(require(&operator, ServerRight::ModifyFiles).is_err());
+
+        let nobody = user(PermissionFlag::None);
+        for right in [ServerRight::ControlServer, ServerRight::UploadFiles, ServerRight::CreateFiles, ServerRight::ModifyFiles] {
+            assert!(require(&nobody, right).is_err());
+        }
+    }
+}
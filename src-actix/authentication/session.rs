@@ -0,0 +1,196 @@
+use crate::authentication::api_token::hash_token;
+use crate::database::{sql, Pool, Row};
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::{Error, FromRow, Row as _};
+
+/// A browser or device signed in with a session token.
+///
+/// Session tokens are only accepted while their session isn't revoked, so revoking one signs
+/// that device out on its next request. Only a SHA-256 hash of the token is stored.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionData {
+    #[serde(serialize_with = "hash_id")]
+    pub id: u64,
+    #[serde(skip)]
+    pub user_id: u64,
+    #[serde(skip)]
+    pub token_hash: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used: DateTime<Utc>,
+    #[serde(skip)]
+    pub revoked: bool,
+}
+
+fn hash_id<S>(id: &u64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&serde_hash::hashids::encode_single(*id))
+}
+
+impl<'a> FromRow<'a, Row> for SessionData {
+    fn from_row(row: &'a Row) -> Result<Self, Error> {
+        let id: i64 = row.try_get("id")?;
+        let user_id: i64 = row.try_get("user_id")?;
+        let revoked: i32 = row.try_get("revoked")?;
+        Ok(SessionData {
+            id: id as u64,
+            user_id: user_id as u64,
+            token_hash: row.try_get("token_hash")?,
+            user_agent: row.try_get("user_agent")?,
+            ip_address: row.try_get("ip_address")?,
+            created_at: row.try_get("created_at")?,
+            last_used: row.try_get("last_used")?,
+            revoked: revoked != 0,
+        })
+    }
+}
+
+impl SessionData {
+    /// Start a session for a token that was just issued to `user_id`
+    pub async fn create(user_id: u64, token: &str, user_agent: Option<String>, ip_address: Option<String>, pool: &Pool) -> Result<Self> {
+        let token_hash = hash_token(token);
+        sqlx::query(&sql("INSERT INTO user_sessions (user_id, token_hash, user_agent, ip_address, created_at, last_used) VALUES (?, ?, ?, ?, ?, ?)"))
+            .bind(user_id as i64)
+            .bind(&token_hash)
+            .bind(user_agent)
+            .bind(ip_address)
+            .bind(Utc::now())
+            .bind(Utc::now())
+            .execute(pool)
+            .await?;
+        let session = sqlx::query_as::<_, SessionData>(&sql("SELECT * FROM user_sessions WHERE token_hash = ? LIMIT 1"))
+            .bind(&token_hash)
+            .fetch_one(pool)
+            .await?;
+        Ok(session)
+    }
+
+    /// Find `user_id`'s session for `token`, failing if it has been revoked.
+    ///
+    /// Tokens issued before sessions were tracked have no session at all. They are still valid,
+    /// so a session is started for them instead of signing every user out on upgrade.
+    pub async fn authenticate(token: &str, user_id: u64, pool: &Pool) -> Result<Self> {
+        let session = sqlx::query_as::<_, SessionData>(&sql("SELECT * FROM user_sessions WHERE token_hash = ? AND user_id = ? LIMIT 1"))
+            .bind(hash_token(token))
+            .bind(user_id as i64)
+            .fetch_optional(pool)
+            .await?;
+        let session = match session {
+            Some(session) if session.revoked => return Err(anyhow!("Session has been signed out")),
+            Some(session) => session,
+            None => return Self::create(user_id, token, None, None, pool).await,
+        };
+
+        sqlx::query(&sql("UPDATE user_sessions SET last_used = ? WHERE id = ?"))
+            .bind(Utc::now())
+            .bind(session.id as i64)
+            .execute(pool)
+            .await?;
+        Ok(session)
+    }
+
+    /// `user_id`'s sessions, most recently used first
+    pub async fn list(user_id: u64, pool: &Pool) -> Result<Vec<Self>> {
+        let sessions = sqlx::query_as::<_, SessionData>(&sql("SELECT * FROM user_sessions WHERE user_id = ? AND revoked = 0 ORDER BY last_used DESC"))
+            .bind(user_id as i64)
+            .fetch_all(pool)
+            .await?;
+        Ok(sessions)
+    }
+
+    /// Revoke one of `user_id`'s sessions, returning whether it was still active.
+    ///
+    /// Revoked sessions are kept rather than deleted, so their token isn't taken for one issued
+    /// before sessions were tracked and started again.
+    pub async fn revoke(id: u64, user_id: u64, pool: &Pool) -> Result<bool> {
+        let result = sqlx::query(&sql("UPDATE user_sessions SET revoked = 1 WHERE id = ? AND user_id = ? AND revoked = 0"))
+            .bind(id as i64)
+            .bind(user_id as i64)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Revoke every one of `user_id`'s sessions except `keep`, returning how many were revoked
+    pub async fn revoke_others(user_id: u64, keep: Option<u64>, pool: &Pool) -> Result<u64> {
+        let result = sqlx::query(&sql("UPDATE user_sessions SET revoked = 1 WHERE user_id = ? AND id <> ? AND revoked = 0"))
+            .bind(user_id as i64)
+            // Ids start at 1, so 0 keeps nothing
+            .bind(keep.unwrap_or_default() as i64)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use crate::authentication::auth_data::UserData;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn pool() -> Pool {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        crate::authentication::initialize(&pool).await.unwrap();
+        UserData::register("steve", "hunter22", &pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_revoked_session_token_is_rejected() {
+        let pool = pool().await;
+        let (token, user) = UserData::login("steve".to_string(), "hunter22".to_string(), &pool).await.unwrap();
+        let session = SessionData::create(user.id.unwrap(), &token, Some("Firefox".to_string()), None, &pool).await.unwrap();
+
+        let (authenticated, current) = UserData::login_with_token(&token, &pool).await.unwrap().unwrap();
+        assert_eq!(authenticated.username, "steve");
+        assert_eq!(current.id, session.id);
+
+        assert!(SessionData::revoke(session.id, user.id.unwrap(), &pool).await.unwrap());
+        assert!(UserData::login_with_token(&token, &pool).await.is_err());
+        assert!(!SessionData::revoke(session.id, user.id.unwrap(), &pool).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_token_issued_before_sessions_starts_one() {
+        let pool = pool().await;
+        // Logging in without creating a session is what happened before sessions were tracked
+        let (token, user) = UserData::login("steve".to_string(), "hunter22".to_string(), &pool).await.unwrap();
+        assert!(SessionData::list(user.id.unwrap(), &pool).await.unwrap().is_empty());
+
+        let (_, session) = UserData::login_with_token(&token, &pool).await.unwrap().unwrap();
+        assert_eq!(SessionData::list(user.id.unwrap(), &pool).await.unwrap().len(), 1);
+        let (_, again) = UserData::login_with_token(&token, &pool).await.unwrap().unwrap();
+        assert_eq!(again.id, session.id);
+
+        // Once revoked, the token isn't taken for an old one and started again
+        assert!(SessionData::revoke(session.id, user.id.unwrap(), &pool).await.unwrap());
+        assert!(UserData::login_with_token(&token, &pool).await.is_err());
+        assert!(SessionData::list(user.id.unwrap(), &pool).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_revoke_others_keeps_the_current_session() {
+        let pool = pool().await;
+        let mut tokens = Vec::new();
+        for device in ["laptop", "phone", "lost tablet"] {
+            let (token, user) = UserData::login("steve".to_string(), "hunter22".to_string(), &pool).await.unwrap();
+            SessionData::create(user.id.unwrap(), &token, Some(device.to_string()), None, &pool).await.unwrap();
+            tokens.push(token);
+        }
+        let (user, current) = UserData::login_with_token(&tokens[0], &pool).await.unwrap().unwrap();
+        assert_eq!(SessionData::list(user.id.unwrap(), &pool).await.unwrap().len(), 3);
+
+        assert_eq!(SessionData::revoke_others(user.id.unwrap(), Some(current.id), &pool).await.unwrap(), 2);
+        assert!(UserData::login_with_token(&tokens[0], &pool).await.is_ok());
+        assert!(UserData::login_with_token(&tokens[1], &pool).await.is_err());
+        assert!(UserData::login_with_token(&tokens[2], &pool).await.is_err());
+        let sessions = SessionData::list(user.id.unwrap(), &pool).await.unwrap();
+        assert_eq!(sessions.iter().map(|session| session.user_agent.as_deref()).collect::<Vec<_>>(), vec![Some("laptop")]);
+    }
+}
@@ -0,0 +1,295 @@
+use crate::database::{sql, Pool, Row};
+use anyhow::Result;
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use sqlx::{Error, FromRow, Row as _};
+
+/// Issuer shown in authenticator apps
+pub const TOTP_ISSUER: &str = "Obsidian Server Panel";
+
+/// Digits in a code
+pub const TOTP_DIGITS: u32 = 6;
+
+/// Seconds each code is valid for
+pub const TOTP_PERIOD: u64 = 30;
+
+/// Codes from this many periods before or after the current one are still accepted, to
+/// tolerate clock drift between the server and the authenticator
+pub const TOTP_SKEW: u64 = 1;
+
+/// Number of one-time recovery codes issued on enrollment
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Length of the shared secret in bytes, as recommended by RFC 4226
+const SECRET_LENGTH: usize = 20;
+
+/// A user's TOTP settings.
+///
+/// Enrollment stores the secret with `enabled` unset; it is only enforced at login once the
+/// user confirms they can produce a valid code.
+#[derive(Debug, Clone)]
+pub struct TwoFactorData {
+    pub user_id: u64,
+    /// Base32 secret shared with the authenticator app
+    pub secret: String,
+    pub enabled: bool,
+    /// SHA-256 hashes of the recovery codes that have not been used yet
+    pub recovery_codes: Vec<String>,
+    /// Time step of the last accepted code, so a code cannot be used twice
+    pub last_used_step: u64,
+}
+
+impl<'a> FromRow<'a, Row> for TwoFactorData {
+    fn from_row(row: &'a Row) -> Result<Self, Error> {
+        let user_id: i64 = row.try_get("user_id")?;
+        let secret: String = row.try_get("secret")?;
+        let enabled: i32 = row.try_get("enabled")?;
+        let recovery_codes: String = row.try_get("recovery_codes")?;
+        let last_used_step: i64 = row.try_get("last_used_step")?;
+        Ok(TwoFactorData {
+            user_id: user_id as u64,
+            secret,
+            enabled: enabled != 0,
+            recovery_codes: serde_json::from_str(&recovery_codes).unwrap_or_default(),
+            last_used_step: last_used_step as u64,
+        })
+    }
+}
+
+impl TwoFactorData {
+    /// Start enrollment with a new secret and recovery codes.
+    ///
+    /// Returns the settings to store and the recovery codes in plain text, which are only
+    /// shown to the user this once.
+    pub fn enroll(user_id: u64) -> (Self, Vec<String>) {
+        let recovery_codes = generate_recovery_codes();
+        let data = Self {
+            user_id,
+            secret: generate_secret(),
+            enabled: false,
+            recovery_codes: recovery_codes.iter().map(|code| hash_recovery_code(code)).collect(),
+            last_used_step: 0,
+        };
+        (data, recovery_codes)
+    }
+
+    /// `otpauth://` URI for adding the secret to an authenticator app, usually shown as a QR code
+    pub fn otpauth_uri(&self, username: &str) -> String {
+        let issuer = percent_encode(TOTP_ISSUER);
+        format!(
+            "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+            issuer,
+            percent_encode(username),
+            self.secret,
+            issuer,
+            TOTP_DIGITS,
+            TOTP_PERIOD
+        )
+    }
+
+    /// Check a code from the authenticator app, or a recovery code, at `unix_time`.
+    ///
+    /// Accepted TOTP codes advance `last_used_step` and accepted recovery codes are removed,
+    /// so neither can be replayed; the caller must [`save`](Self::save) afterward.
+    pub fn verify(&mut self, code: &str, unix_time: u64) -> bool {
+        if self.verify_totp(code, unix_time) {
+            return true;
+        }
+
+        let hash = hash_recovery_code(code.trim());
+        if let Some(index) = self.recovery_codes.iter().position(|stored| *stored == hash) {
+            self.recovery_codes.remove(index);
+            return true;
+        }
+        false
+    }
+
+    /// Like [`verify`](Self::verify), but only accepts codes from the authenticator app
+    pub fn verify_totp(&mut self, code: &str, unix_time: u64) -> bool {
+        match verify_totp(&self.secret, code.trim(), unix_time) {
+            Some(step) if step > self.last_used_step => {
+                self.last_used_step = step;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub async fn get(user_id: u64, pool: &Pool) -> Result<Option<Self>> {
+        let data = sqlx::query_as::<_, TwoFactorData>(&sql("SELECT * FROM user_two_factor WHERE user_id = ? LIMIT 1"))
+            .bind(user_id as i64)
+            .fetch_optional(pool)
+            .await?;
+        Ok(data)
+    }
+
+    /// Whether `user_id` must provide a code at login
+    pub async fn is_enabled(user_id: u64, pool: &Pool) -> Result<bool> {
+        Ok(Self::get(user_id, pool).await?.is_some_and(|data| data.enabled))
+    }
+
+    pub async fn save(&self, pool: &Pool) -> Result<()> {
+        let mut transaction = pool.begin().await?;
+        sqlx::query(&sql("DELETE FROM user_two_factor WHERE user_id = ?"))
+            .bind(self.user_id as i64)
+            .execute(&mut *transaction)
+            .await?;
+        sqlx::query(&sql("INSERT INTO user_two_factor (user_id, secret, enabled, recovery_codes, last_used_step) VALUES (?, ?, ?, ?, ?)"))
+            .bind(self.user_id as i64)
+            .bind(&self.secret)
+            .bind(self.enabled as i32)
+            .bind(serde_json::to_string(&self.recovery_codes)?)
+            .bind(self.last_used_step as i64)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    pub async fn delete(user_id: u64, pool: &Pool) -> Result<()> {
+        sqlx::query(&sql("DELETE FROM user_two_factor WHERE user_id = ?"))
+            .bind(user_id as i64)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Seconds since the Unix epoch, the clock codes are checked against
+pub fn now() -> u64 {
+    chrono::Utc::now().timestamp() as u64
+}
+
+fn generate_secret() -> String {
+    let mut secret = [0u8; SECRET_LENGTH];
+    rand::rng().fill(&mut secret);
+    BASE32_NOPAD.encode(&secret)
+}
+
+/// Recovery codes in the form `xxxxx-xxxxx`
+fn generate_recovery_codes() -> Vec<String> {
+    const CHARSET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+    let mut rng = rand::rng();
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut code: String = (0..10).map(|_| CHARSET[rng.random_range(0..CHARSET.len())] as char).collect();
+            code.insert(5, '-');
+            code
+        })
+        .collect()
+}
+
+/// Recovery codes are random enough that a fast hash is sufficient
+fn hash_recovery_code(code: &str) -> String {
+    let digest = Sha256::digest(code.to_lowercase().as_bytes());
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The code for time step `counter` (RFC 4226 HOTP with HMAC-SHA1)
+fn totp_code(secret: &[u8], counter: u64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([hash[offset] & 0x7f, hash[offset + 1], hash[offset + 2], hash[offset + 3]]);
+    binary % 10u32.pow(TOTP_DIGITS)
+}
+
+/// Check `code` against the steps around `unix_time`, returning the matching step
+fn verify_totp(secret: &str, code: &str, unix_time: u64) -> Option<u64> {
+    if code.len() != TOTP_DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let code: u32 = code.parse().ok()?;
+    let secret = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+    let current = unix_time / TOTP_PERIOD;
+    (current.saturating_sub(TOTP_SKEW)..=current + TOTP_SKEW).find(|step| totp_code(&secret, *step) == code)
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Current code for a secret, as an authenticator app would show it
+    fn code_at(secret: &str, unix_time: u64) -> String {
+        let secret = BASE32_NOPAD.decode(secret.as_bytes()).unwrap();
+        format!("{:06}", totp_code(&secret, unix_time / TOTP_PERIOD))
+    }
+
+    #[test]
+    fn test_rfc_6238_vectors() {
+        // Test vectors from RFC 6238 appendix B, truncated to six digits
+        let secret = b"12345678901234567890";
+        assert_eq!(totp_code(secret, 59 / TOTP_PERIOD), 287082);
+        assert_eq!(totp_code(secret, 1111111109 / TOTP_PERIOD), 81804);
+        assert_eq!(totp_code(secret, 2000000000 / TOTP_PERIOD), 279037);
+    }
+
+    #[test]
+    fn test_enrollment() {
+        let (data, recovery_codes) = TwoFactorData::enroll(7);
+        assert!(!data.enabled);
+        assert_eq!(BASE32_NOPAD.decode(data.secret.as_bytes()).unwrap().len(), SECRET_LENGTH);
+        assert_eq!(recovery_codes.len(), RECOVERY_CODE_COUNT);
+        assert!(recovery_codes.iter().all(|code| code.len() == 11 && code.as_bytes()[5] == b'-'));
+        // Only hashes are stored
+        assert!(recovery_codes.iter().all(|code| !data.recovery_codes.contains(code)));
+
+        let uri = data.otpauth_uri("steve smith");
+        assert!(uri.starts_with("otpauth://totp/Obsidian%20Server%20Panel:steve%20smith?"));
+        assert!(uri.contains(&format!("secret={}", data.secret)));
+        assert!(uri.contains("issuer=Obsidian%20Server%20Panel"));
+    }
+
+    #[test]
+    fn test_valid_code_is_accepted_once() {
+        let (mut data, _) = TwoFactorData::enroll(7);
+        let time = 1_700_000_000;
+        let code = code_at(&data.secret, time);
+
+        assert!(data.verify(&code, time));
+        // Replaying the same code is rejected
+        assert!(!data.verify(&code, time));
+        // The next code is accepted, even with a little clock drift
+        assert!(data.verify(&code_at(&data.secret, time + TOTP_PERIOD), time + 10));
+    }
+
+    #[test]
+    fn test_wrong_or_expired_code_is_rejected() {
+        let (mut data, _) = TwoFactorData::enroll(7);
+        let time = 1_700_000_000;
+        let code = code_at(&data.secret, time);
+        let wrong = format!("{:06}", (code.parse::<u32>().unwrap() + 1) % 1_000_000);
+
+        assert!(!data.verify(&wrong, time));
+        assert!(!data.verify("12345", time));
+        assert!(!data.verify("abcdef", time));
+        // Two periods later the code has expired
+        assert!(!data.verify(&code, time + 2 * TOTP_PERIOD));
+        assert_eq!(data.last_used_step, 0);
+    }
+
+    #[test]
+    fn test_recovery_codes_are_single_use() {
+        let (mut data, recovery_codes) = TwoFactorData::enroll(7);
+        let time = 1_700_000_000;
+
+        assert!(data.verify(&recovery_codes[3].to_uppercase(), time));
+        assert_eq!(data.recovery_codes.len(), RECOVERY_CODE_COUNT - 1);
+        assert!(!data.verify(&recovery_codes[3], time));
+        assert!(!data.verify_totp(&recovery_codes[4], time));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::actions::actions_data::ActionData;
 use crate::notifications::notification_data::NotificationMessage;
 use crate::server::server_data::ServerData;
+use crate::server::server_metrics::ServerMetrics;
 use craftping::Response as PingResponse;
 use serde::{Deserialize, Serialize};
 
@@ -12,6 +13,14 @@ pub enum BroadcastMessage {
     ServerUpdate {
         server: ServerData,
     },
+    /// Server process crashed, sent to every client so operators notice wherever they are
+    ServerCrashed {
+        /// Hashed server ID (using serde_hash)
+        server_id: String,
+        server_name: String,
+        /// Description and exception from the crash report, if the server wrote one
+        summary: Option<String>,
+    },
     /// Server deleted
     ServerDeleted {
         /// Hashed server ID (using serde_hash)
@@ -23,6 +32,12 @@ pub enum BroadcastMessage {
         server_id: String,
         ping: PingResponse,
     },
+    /// Resource usage, TPS, and player count sample for a watched server
+    ServerMetrics {
+        /// Hashed server ID (using serde_hash)
+        server_id: String,
+        metrics: ServerMetrics,
+    },
     /// Action progress update
     ActionUpdate {
         action: ActionData,
@@ -41,6 +41,11 @@ pub fn get_pool() -> &'static Pool {
 	POOL.get().expect("Database pool not initialized — call database::init_pool() first")
 }
 
+/// Returns the shared database pool, or `None` before `init_pool()` has been called.
+pub fn try_get_pool() -> Option<&'static Pool> {
+	POOL.get()
+}
+
 /// Creates the pool, stores it globally, and returns a clone for actix-web `Data`.
 pub async fn init_pool() -> Result<Pool> {
 	let pool = open_pool().await?;
@@ -0,0 +1,299 @@
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use oim::{GitHubAsset, InstallationConfig, InstallationManager, StateProgress};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tokio::sync::broadcast::error::RecvError;
+
+pub const ADOPTIUM_API_URL: &str = "https://api.adoptium.net";
+
+/// Registry of the JDKs installed through Adoptium, kept in the Java directory
+const JDK_REGISTRY_FILE: &str = "jdks.json";
+
+/// Oldest Java release Minecraft servers have run on
+const MIN_JAVA_MAJOR: u32 = 8;
+
+#[derive(Deserialize)]
+struct AdoptiumRelease {
+    binary: AdoptiumBinary,
+    release_name: String,
+}
+
+#[derive(Deserialize)]
+struct AdoptiumBinary {
+    package: AdoptiumPackage,
+}
+
+#[derive(Deserialize)]
+struct AdoptiumPackage {
+    name: String,
+    link: String,
+    size: u64,
+    checksum: Option<String>,
+}
+
+/// A JDK installed by the panel, which servers can use as their Java executable
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InstalledJdk {
+    pub major: u32,
+    pub release: String,
+    pub vendor: String,
+    pub directory: PathBuf,
+    pub executable: PathBuf,
+}
+
+pub struct AdoptiumClient {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl Default for AdoptiumClient {
+    fn default() -> Self {
+        Self::with_base_url(ADOPTIUM_API_URL)
+    }
+}
+
+impl AdoptiumClient {
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self { base_url: base_url.into().trim_end_matches('/').to_string(), client: reqwest::Client::new() }
+    }
+
+    /// The latest Temurin JDK release of `major` for this platform
+    async fn latest_jdk(&self, major: u32) -> Result<AdoptiumRelease> {
+        let (os, architecture) = adoptium_platform()?;
+        let url = format!("{}/v3/assets/latest/{}/hotspot", self.base_url, major);
+        let response = self
+            .client
+            .get(&url)
+            .query(&[("architecture", architecture), ("image_type", "jdk"), ("os", os), ("vendor", "eclipse")])
+            .send()
+            .await?
+            .error_for_status()?;
+        let releases: Vec<AdoptiumRelease> = response.json().await?;
+        releases.into_iter().next().ok_or_else(|| anyhow!("No Temurin JDK {} is available for {} {}", major, os, architecture))
+    }
+}
+
+/// Download and unpack the latest Temurin JDK of `major` into `java_dir` and register it.
+/// Download and extraction progress is passed to `on_progress`.
+pub async fn install_jdk<F>(client: &AdoptiumClient, major: u32, java_dir: &Path, on_progress: F) -> Result<InstalledJdk>
+where
+    F: Fn(StateProgress) + Send + 'static,
+{
+    if major < MIN_JAVA_MAJOR {
+        return Err(anyhow!("Java {} is not supported, the oldest supported version is {}", major, MIN_JAVA_MAJOR));
+    }
+    let release = client.latest_jdk(major).await?;
+    let package = release.binary.package;
+    warn!("Installing Temurin {}, this may take some time!", release.release_name);
+
+    let directory = java_dir.join(format!("temurin-{}", major));
+    let staging = java_dir.join(format!(".temurin-{}.partial", major));
+    let manager = InstallationManager::new(
+        InstallationConfig::new(directory.clone(), format!("adoptium/temurin{}-binaries", major), format!("temurin-{}", major)).temp_dir(java_dir.join(".downloads")),
+    );
+    let mut progress = manager.subscribe();
+    let forwarder = tokio::spawn(async move {
+        loop {
+            match progress.recv().await {
+                Ok(update) => on_progress(update),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+
+    let asset = GitHubAsset { name: package.name, browser_download_url: package.link, size: package.size };
+    let archive = manager.download_to_temp(&asset).await?;
+    let result = async {
+        if let Some(checksum) = &package.checksum {
+            verify_sha256(&archive, checksum)?;
+        }
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging)?;
+        }
+        let extractor = manager.clone();
+        let extract_to = staging.clone();
+        let archive = archive.clone();
+        tokio::task::spawn_blocking(move || extractor.extract_archive(&archive, &extract_to)).await??;
+
+        // Archives hold a single top-level folder, which becomes the install directory
+        let mut entries = std::fs::read_dir(&staging)?.collect::<std::io::Result<Vec<_>>>()?;
+        let root = match entries.pop() {
+            Some(entry) if entries.is_empty() && entry.file_type()?.is_dir() => entry.path(),
+            _ => staging.clone(),
+        };
+        let executable = find_java_executable(&root).ok_or_else(|| anyhow!("The downloaded JDK does not contain a java executable"))?;
+        if directory.exists() {
+            std::fs::remove_dir_all(&directory)?;
+        }
+        std::fs::rename(&root, &directory)?;
+        let _ = std::fs::remove_dir_all(&staging);
+        Ok::<_, anyhow::Error>(directory.join(executable.strip_prefix(&root)?))
+    }
+    .await;
+
+    let _ = std::fs::remove_file(&archive);
+    if result.is_err() {
+        let _ = std::fs::remove_dir_all(&staging);
+    }
+    drop(manager);
+    let _ = forwarder.await;
+
+    let jdk = InstalledJdk { major, release: release.release_name, vendor: "temurin".to_string(), directory, executable: result? };
+    register_jdk(java_dir, &jdk)?;
+    info!("Installed Temurin {} to {}", jdk.release, jdk.directory.display());
+    Ok(jdk)
+}
+
+/// The JDKs registered in `java_dir`
+pub fn list_installed_jdks(java_dir: &Path) -> Result<Vec<InstalledJdk>> {
+    let path = java_dir.join(JDK_REGISTRY_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+/// Record `jdk` in the registry, replacing an earlier install of the same version
+fn register_jdk(java_dir: &Path, jdk: &InstalledJdk) -> Result<()> {
+    let mut jdks = list_installed_jdks(java_dir)?;
+    jdks.retain(|installed| !(installed.major == jdk.major && installed.vendor == jdk.vendor));
+    jdks.push(jdk.clone());
+    jdks.sort_by_key(|installed| installed.major);
+    std::fs::write(java_dir.join(JDK_REGISTRY_FILE), serde_json::to_string_pretty(&jdks)?)?;
+    Ok(())
+}
+
+fn verify_sha256(path: &Path, expected: &str) -> Result<()> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+    let actual = format!("{:x}", hasher.finalize());
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(anyhow!("Checksum mismatch for {}: expected {}, got {}", path.display(), expected, actual));
+    }
+    Ok(())
+}
+
+/// The `bin/java` executable of an unpacked JDK, which is under `Contents/Home` on macOS
+fn find_java_executable(dir: &Path) -> Option<PathBuf> {
+    let name = if cfg!(windows) { "java.exe" } else { "java" };
+    walkdir::WalkDir::new(dir)
+        .max_depth(5)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .find(|entry| entry.file_type().is_file() && entry.file_name() == name && entry.path().parent().is_some_and(|parent| parent.ends_with("bin")))
+        .map(|entry| entry.into_path())
+}
+
+/// Adoptium's names for the current OS and architecture
+fn adoptium_platform() -> Result<(&'static str, &'static str)> {
+    let os = match std::env::consts::OS {
+        "linux" => "linux",
+        "windows" => "windows",
+        "macos" => "mac",
+        os => return Err(anyhow!("Unsupported OS: {}", os)),
+    };
+    let architecture = match std::env::consts::ARCH {
+        "x86_64" => "x64",
+        "aarch64" => "aarch64",
+        "x86" => "x32",
+        arch => return Err(anyhow!("Unsupported architecture: {}", arch)),
+    };
+    Ok((os, architecture))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// A tar.gz laid out like a Temurin release
+    fn jdk_archive() -> Vec<u8> {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+        let executable = if cfg!(windows) { "jdk-21.0.5+11/bin/java.exe" } else { "jdk-21.0.5+11/bin/java" };
+        for (name, contents, mode) in [(executable, "#!/bin/sh\n", 0o755), ("jdk-21.0.5+11/release", "JAVA_VERSION=\"21.0.5\"\n", 0o644)] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(mode);
+            header.set_cksum();
+            builder.append_data(&mut header, name, contents.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    async fn mock_adoptium(archive: &[u8], checksum: &str) -> MockServer {
+        let server = MockServer::start().await;
+        let (os, architecture) = adoptium_platform().unwrap();
+        Mock::given(method("GET"))
+            .and(path("/v3/assets/latest/21/hotspot"))
+            .and(query_param("image_type", "jdk"))
+            .and(query_param("os", os))
+            .and(query_param("architecture", architecture))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "binary": {
+                    "architecture": architecture,
+                    "image_type": "jdk",
+                    "os": os,
+                    "package": {
+                        "name": "OpenJDK21U-jdk_hotspot_21.0.5_11.tar.gz",
+                        "link": format!("{}/download/OpenJDK21U-jdk_hotspot_21.0.5_11.tar.gz", server.uri()),
+                        "size": archive.len(),
+                        "checksum": checksum,
+                    }
+                },
+                "release_name": "jdk-21.0.5+11",
+                "vendor": "eclipse",
+                "version": { "major": 21, "semver": "21.0.5+11.0.LTS" }
+            }])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/download/OpenJDK21U-jdk_hotspot_21.0.5_11.tar.gz"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(archive.to_vec()))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_install_registers_jdk_path() {
+        let archive = jdk_archive();
+        let server = mock_adoptium(&archive, &format!("{:x}", Sha256::digest(&archive))).await;
+        let java_dir = temp_dir("adoptium-install");
+
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let recorded = updates.clone();
+        let jdk = install_jdk(&AdoptiumClient::with_base_url(server.uri()), 21, &java_dir, move |progress| recorded.lock().unwrap().push(progress))
+            .await
+            .unwrap();
+
+        let executable = if cfg!(windows) { "temurin-21/bin/java.exe" } else { "temurin-21/bin/java" };
+        assert_eq!(jdk.executable, java_dir.join(executable));
+        assert!(jdk.executable.is_file());
+        assert_eq!(jdk.release, "jdk-21.0.5+11");
+        assert_eq!(list_installed_jdks(&java_dir).unwrap(), vec![jdk]);
+        assert!(!java_dir.join(".temurin-21.partial").exists());
+
+        let updates = updates.lock().unwrap();
+        assert!(updates.iter().any(|update| matches!(update.state, oim::State::Downloading)));
+        assert!(updates.iter().any(|update| matches!(update.state, oim::State::Extracting) && update.progress == 1.0));
+    }
+
+    #[tokio::test]
+    async fn test_checksum_mismatch_is_not_registered() {
+        let archive = jdk_archive();
+        let server = mock_adoptium(&archive, &"0".repeat(64)).await;
+        let java_dir = temp_dir("adoptium-checksum");
+
+        let error = install_jdk(&AdoptiumClient::with_base_url(server.uri()), 21, &java_dir, |_| {}).await.unwrap_err();
+        assert!(error.to_string().contains("Checksum mismatch"), "{}", error);
+        assert!(list_installed_jdks(&java_dir).unwrap().is_empty());
+        assert!(!java_dir.join("temurin-21").exists());
+    }
+}
@@ -1,6 +1,8 @@
 use crate::actix_util::http_error::Result;
-use crate::java::versions::JavaVersion;
-use actix_web::{HttpResponse, Responder, delete, get, web};
+use crate::java::adoptium::{self, AdoptiumClient};
+use crate::java::versions::{java_directory, JavaVersion};
+use actix_web::{HttpResponse, Responder, delete, get, post, web};
+use anyhow::anyhow;
 use actix_web_lab::sse;
 use log::error;
 use serde_json::json;
@@ -54,6 +56,42 @@ pub async fn install_java_version(runtime: web::Path<String>) -> Result<impl Res
     Ok(sse::Sse::from_infallible_receiver(receiver).with_keep_alive(Duration::from_secs(10)))
 }
 
+/// POST /api/java/install - Download the latest Temurin JDK of a major version and register it,
+/// streaming download and extraction progress over SSE
+#[post("/install")]
+pub async fn install_jdk(body: web::Json<serde_json::Value>) -> Result<impl Responder> {
+    let major = body.get("version").and_then(|v| v.as_u64()).ok_or(anyhow!("version must be a Java major version, e.g. 21"))? as u32;
+
+    let (sender, receiver) = tokio::sync::mpsc::channel(100);
+    tokio::spawn(async move {
+        let progress_sender = sender.clone();
+        let result = adoptium::install_jdk(&AdoptiumClient::default(), major, &java_directory(), move |progress| {
+            if let Ok(data) = sse::Data::new_json(&progress) {
+                let _ = progress_sender.try_send(data.event("progress").into());
+            }
+        })
+        .await;
+
+        let event = match result {
+            Ok(jdk) => sse::Data::new_json(json!({"message": "Installation completed!", "jdk": jdk})).map(|data| data.event("completed")),
+            Err(e) => {
+                error!("Error installing Java {}: {}", major, e);
+                sse::Data::new_json(json!({"message": "Error installing java version", "stacktrace": e.to_string()})).map(|data| data.event("error"))
+            }
+        };
+        if let Ok(event) = event {
+            let _ = sender.send(event.into()).await;
+        }
+    });
+    Ok(sse::Sse::from_infallible_receiver(receiver).with_keep_alive(Duration::from_secs(10)))
+}
+
+/// GET /api/java/jdks - JDKs installed through `/java/install`
+#[get("/jdks")]
+pub async fn get_installed_jdks() -> Result<impl Responder> {
+    Ok(HttpResponse::Ok().json(adoptium::list_installed_jdks(&java_directory())?))
+}
+
 #[get("/version-map")]
 pub async fn version_map() -> Result<impl Responder> {
     match crate::java::java_minecraft_version_map::get_java_minecraft_version_map().await {
@@ -69,6 +107,8 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         web::scope("/java")
             .service(version_map)
             .service(install_java_version)
+            .service(install_jdk)
+            .service(get_installed_jdks)
             .service(get_java_versions)
             .service(get_installation_files)
             .service(uninstall_java_version)
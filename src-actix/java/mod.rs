@@ -1,3 +1,4 @@
+mod adoptium;
 mod java_data;
 mod java_db;
 mod versions;
@@ -125,19 +125,7 @@ impl JavaVersion {
     }
 
     fn get_installation_directory(&self) -> PathBuf {
-        // Try to load settings, fallback to default if it fails
-        let java_dir = if let Ok(settings) = crate::settings::load_settings() {
-            settings.storage.java_directory
-        } else {
-            // Fallback to default path if settings can't be loaded
-            PathBuf::from("./meta/java")
-        };
-
-        // Create the directory if it doesn't exist
-        if !java_dir.exists() {
-            let _ = std::fs::create_dir_all(&java_dir);
-        }
-
+        let java_dir = java_directory();
         let runtime = sanitize_path_component(&self.runtime.to_string());
         let version = sanitize_path_component(&self.version);
         java_dir.join(format!("{}-{}", runtime, version))
@@ -276,6 +264,23 @@ impl JavaVersion {
     }
 }
 
+/// The directory Java runtimes are installed into, created if it doesn't exist
+pub fn java_directory() -> PathBuf {
+    // Try to load settings, fallback to default if it fails
+    let java_dir = if let Ok(settings) = crate::settings::load_settings() {
+        settings.storage.java_directory
+    } else {
+        // Fallback to default path if settings can't be loaded
+        PathBuf::from("./meta/java")
+    };
+
+    // Create the directory if it doesn't exist
+    if !java_dir.exists() {
+        let _ = std::fs::create_dir_all(&java_dir);
+    }
+    java_dir
+}
+
 fn get_current_os() -> Option<OS> {
     if cfg!(target_os = "linux") {
         if cfg!(target_arch = "x86") {
@@ -1,5 +1,6 @@
 use crate::server::server_data::ServerData;
 use actix_util::asset_endpoint::AssetsAppConfig;
+use actix_util::rate_limit::RateLimit;
 use actix_web::Responder;
 use actix_web::{App, HttpResponse, HttpServer, get, middleware, web};
 use anyhow::Result;
@@ -15,6 +16,7 @@ use vite_actix::start_vite_server;
 mod actions;
 mod actix_util;
 mod app_db;
+mod audit;
 mod authentication;
 mod broadcast;
 mod command_line_args;
@@ -25,10 +27,13 @@ mod neoforge_endpoint;
 mod host_info;
 mod java;
 mod notifications;
+mod scheduled_tasks;
 mod server;
 mod settings;
 mod updater;
 mod platforms;
+#[cfg(test)]
+mod test_util;
 
 pub static DEBUG: bool = cfg!(debug_assertions);
 static ICON: &[u8] = include_bytes!("../resources/logo/icon.ico");
@@ -72,6 +77,7 @@ pub async fn run() -> Result<()> {
         let result: Result<()> = async {
             app_db::initialize_databases(pool).await?;
             ServerData::initialize_servers(pool).await?;
+            scheduled_tasks::start(pool).await?;
 
             // Only refresh Java version map if expired (older than 1 day)
             let is_expired = java::is_version_map_expired(pool).await?;
@@ -115,6 +121,9 @@ pub async fn run() -> Result<()> {
         }
     });
 
+    // Forget rate limit buckets of clients that went quiet
+    actix_util::rate_limit::start_cleanup();
+
     let server = HttpServer::new(move || {
         App::new()
             .wrap(middleware::Logger::default())
@@ -127,13 +136,18 @@ pub async fn run() -> Result<()> {
             .service(
                 web::scope("api").configure(host_info::configure).configure(authentication::configure).service(
                     web::scope("")
+                        // Registered first so it runs after authentication and can limit per user
+                        .wrap(RateLimit::api())
                         .wrap(authentication::AuthenticationMiddleware)
                         .configure(actions::configure)
+                        .configure(audit::configure)
                         .configure(java::configure)
                         .configure(fabric_endpoint::configure)
                         .configure(forge_endpoint::configure)
                         .configure(neoforge_endpoint::configure)
                         .configure(server::configure)
+                        .configure(scheduled_tasks::configure)
+                        .configure(notifications::configure)
                         .configure(settings::configure)
                         .configure(updater::configure)
                         .configure(broadcast::updates_endpoint::configure)
@@ -168,7 +182,7 @@ pub async fn run() -> Result<()> {
     debug!("Server stopped");
 
     // Close all UPnP ports
-    if let Err(e) = UpnpManager::global().remove_all_ports().await {
+    if let Err(e) = UpnpManager::global().close().await {
         error!("Failed to clean up UPnP ports: {}", e);
     }
 
@@ -3,5 +3,5 @@ mod notification_db;
 mod notification_endpoint;
 
 pub use notification_db::initialize;
-pub use notification_endpoint::broadcast_notification;
+pub use notification_endpoint::{broadcast_notification, configure};
 pub use notification_data::{NotificationActionType, NotificationData, NotificationItem, NotificationType};
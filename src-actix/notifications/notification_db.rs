@@ -133,6 +133,46 @@ impl NotificationData {
         Ok(())
     }
 
+    /// Mark several notifications as read for a specific user in one transaction,
+    /// returning how many of the user's notifications are still unread
+    pub async fn mark_many_as_read(notification_ids: &[String], user_id: u64, pool: &Pool) -> Result<u64> {
+        let mut transaction = pool.begin().await?;
+        for notification_id in notification_ids {
+            sqlx::query(
+                &*sql(r#"UPDATE user_notifications
+                   SET is_read = 1
+                   WHERE notification_id = ? AND user_id = ?"#),
+            )
+            .bind(notification_id)
+            .bind(user_id as i64)
+            .execute(&mut *transaction)
+            .await?;
+        }
+        let unread: i64 = sqlx::query_scalar(
+            &*sql(r#"SELECT COUNT(*) FROM user_notifications
+               WHERE user_id = ? AND is_read = 0 AND is_hidden = 0"#),
+        )
+        .bind(user_id as i64)
+        .fetch_one(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+
+        Ok(unread as u64)
+    }
+
+    /// Count the notifications a specific user has not read or hidden
+    pub async fn unread_count(user_id: u64, pool: &Pool) -> Result<u64> {
+        let unread: i64 = sqlx::query_scalar(
+            &*sql(r#"SELECT COUNT(*) FROM user_notifications
+               WHERE user_id = ? AND is_read = 0 AND is_hidden = 0"#),
+        )
+        .bind(user_id as i64)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(unread as u64)
+    }
+
     /// Hide (delete) a notification for a specific user
     pub async fn hide_for_user(notification_id: &str, user_id: u64, pool: &Pool) -> Result<()> {
         sqlx::query(
@@ -218,3 +258,44 @@ impl NotificationData {
         Ok(())
     }
 }
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn test_pool() -> Pool {
+        // A single connection, as every connection to an in-memory database gets its own
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        pool.execute("CREATE TABLE users (id INTEGER PRIMARY KEY NOT NULL)").await.unwrap();
+        initialize(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_mark_many_as_read() {
+        let pool = test_pool().await;
+        pool.execute("INSERT INTO users (id) VALUES (1), (2)").await.unwrap();
+
+        let mut ids = Vec::new();
+        for title in ["Server started", "Backup finished", "Update available"] {
+            let notification = NotificationData::create(title, "", NotificationType::System, 0, None, &pool).await.unwrap();
+            ids.push(notification.id);
+        }
+        assert_eq!(NotificationData::unread_count(1, &pool).await.unwrap(), 3);
+
+        let unread = NotificationData::mark_many_as_read(&ids[..2], 1, &pool).await.unwrap();
+        assert_eq!(unread, 1);
+        let read: Vec<String> =
+            NotificationData::get_for_user(1, &pool).await.unwrap().into_iter().filter(|n| n.is_read).map(|n| n.id).collect();
+        assert_eq!(read.len(), 2);
+        assert!(read.iter().all(|id| ids[..2].contains(id)));
+        assert_eq!(NotificationData::unread_count(2, &pool).await.unwrap(), 3, "other users are untouched");
+
+        let unread = NotificationData::mark_many_as_read(&ids, 1, &pool).await.unwrap();
+        assert_eq!(unread, 0);
+
+        NotificationData::mark_all_as_read(2, &pool).await.unwrap();
+        assert_eq!(NotificationData::unread_count(2, &pool).await.unwrap(), 0);
+    }
+}
@@ -1,9 +1,12 @@
+use crate::actix_util::http_error;
 use crate::authentication::auth_data::UserRequestExt;
+use crate::broadcast;
+use crate::broadcast::broadcast_data::BroadcastMessage;
 use crate::notifications::notification_data::{NotificationCommand, NotificationData, NotificationMessage};
 use actix::{Actor, ActorContext, AsyncContext, Handler, Message as ActixMessage, StreamHandler, WrapFuture};
-use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder};
 use actix_web_actors::ws;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use log::{debug, error, warn};
 use std::collections::HashMap;
 use std::sync::{Arc, LazyLock};
@@ -228,7 +231,38 @@ pub async fn send_to_user(user_id: u64, message: NotificationMessage) {
     }
 }
 
+/// POST /api/notifications/read - Mark a list of notifications, or all of them, as read
+#[post("/notifications/read")]
+async fn mark_notifications_read(body: web::Json<serde_json::Value>, req: HttpRequest) -> http_error::Result<impl Responder> {
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+    let pool = crate::database::get_pool();
+
+    let unread = if body.get("all").and_then(|v| v.as_bool()).unwrap_or(false) {
+        NotificationData::mark_all_as_read(user_id, pool).await?;
+        broadcast::broadcast(BroadcastMessage::Notification { message: NotificationMessage::MarkAllAsRead });
+        NotificationData::unread_count(user_id, pool).await?
+    } else {
+        let ids = body
+            .get("ids")
+            .and_then(|v| v.as_array())
+            .ok_or(anyhow!("ids or all is required"))?
+            .iter()
+            .map(|id| id.as_str().map(String::from))
+            .collect::<Option<Vec<String>>>()
+            .ok_or(anyhow!("ids must be a list of notification ids"))?;
+
+        let unread = NotificationData::mark_many_as_read(&ids, user_id, pool).await?;
+        for id in ids {
+            broadcast::broadcast(BroadcastMessage::Notification { message: NotificationMessage::MarkAsRead { id } });
+        }
+        unread
+    };
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "unread": unread })))
+}
+
 /// Configure notification routes
 pub fn configure(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::resource("/notifications/ws").route(web::get().to(notifications_ws)));
+    cfg.service(web::resource("/notifications/ws").route(web::get().to(notifications_ws))).service(mark_notifications_read);
 }
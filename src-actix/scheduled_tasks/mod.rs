@@ -0,0 +1,8 @@
+pub mod scheduled_task_data;
+mod scheduled_task_db;
+mod scheduled_task_endpoint;
+mod task_scheduler;
+
+pub use scheduled_task_db::initialize;
+pub use scheduled_task_endpoint::configure;
+pub use task_scheduler::start;
@@ -0,0 +1,144 @@
+use crate::database::Row;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use serde_hash::serde_hash;
+use sqlx::{Error, FromRow, Row as _};
+use std::str::FromStr;
+
+/// What a scheduled task does to its server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskAction {
+    /// Restart the server if it is running
+    Restart,
+    /// Take an incremental backup
+    Backup,
+    /// Send a console command if the server is running
+    Command,
+}
+
+impl TaskAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Restart => "restart",
+            Self::Backup => "backup",
+            Self::Command => "command",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "restart" => Some(Self::Restart),
+            "backup" => Some(Self::Backup),
+            "command" => Some(Self::Command),
+            _ => None,
+        }
+    }
+}
+
+/// An action run on a server at the times matched by a cron expression
+#[serde_hash]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTask {
+    #[serde(hash)]
+    pub id: u64,
+    #[serde(hash)]
+    pub server_id: u64,
+    pub action: TaskAction,
+    /// Console command to send, for `TaskAction::Command`
+    pub command: Option<String>,
+    /// Cron expression (`sec min hour day-of-month month day-of-week [year]`) in the panel's local time,
+    /// e.g. `0 0 4 * * *` for 4 AM daily
+    pub cron: String,
+    pub enabled: bool,
+    #[serde(hash)]
+    pub owner_id: u64,
+    /// Timestamp of the last run (seconds since epoch)
+    pub last_run: Option<u64>,
+    /// Timestamp of when the task was created (seconds since epoch)
+    pub created_at: u64,
+}
+
+impl<'a> FromRow<'a, Row> for ScheduledTask {
+    fn from_row(row: &'a Row) -> Result<Self, Error> {
+        let action: String = row.try_get("action")?;
+        let enabled: i16 = row.try_get("enabled")?;
+        let last_run: Option<i64> = row.try_get("last_run")?;
+        let created_at: i64 = row.try_get("created_at")?;
+
+        Ok(ScheduledTask {
+            id: row.try_get::<i64, _>("id")? as u64,
+            server_id: row.try_get::<i64, _>("server_id")? as u64,
+            action: TaskAction::parse(&action).ok_or_else(|| Error::Decode(format!("Unknown task action '{}'", action).into()))?,
+            command: row.try_get("command")?,
+            cron: row.try_get("cron")?,
+            enabled: enabled != 0,
+            owner_id: row.try_get::<i64, _>("owner_id")? as u64,
+            last_run: last_run.map(|t| t as u64),
+            created_at: created_at as u64,
+        })
+    }
+}
+
+impl ScheduledTask {
+    /// A new enabled task, checking the cron expression and that command tasks have a command
+    pub fn new(server_id: u64, action: TaskAction, command: Option<String>, cron: String, owner_id: u64) -> Result<Self> {
+        parse_cron(&cron)?;
+        let command = match action {
+            TaskAction::Command => Some(command.map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).ok_or(anyhow!("command is required for command tasks"))?),
+            TaskAction::Restart | TaskAction::Backup => None,
+        };
+
+        Ok(Self {
+            id: 0,
+            server_id,
+            action,
+            command,
+            cron,
+            enabled: true,
+            owner_id,
+            last_run: None,
+            created_at: chrono::Utc::now().timestamp() as u64,
+        })
+    }
+
+    /// The first time after `time` the task is due, or `None` if it never runs again
+    pub fn next_run_after(&self, time: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let schedule = parse_cron(&self.cron).ok()?;
+        schedule.after(&time.with_timezone(&Local)).next().map(|next| next.with_timezone(&Utc))
+    }
+}
+
+fn parse_cron(expression: &str) -> Result<cron::Schedule> {
+    cron::Schedule::from_str(expression).map_err(|e| anyhow!("Invalid cron expression '{}': {}", expression, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_new_task_is_validated() {
+        assert!(ScheduledTask::new(1, TaskAction::Restart, None, "0 0 4 * * *".to_string(), 1).is_ok());
+        assert!(ScheduledTask::new(1, TaskAction::Restart, None, "every night".to_string(), 1).is_err());
+        assert!(ScheduledTask::new(1, TaskAction::Command, Some("  ".to_string()), "0 0 4 * * *".to_string(), 1).is_err());
+
+        let task = ScheduledTask::new(1, TaskAction::Command, Some(" say Restarting soon ".to_string()), "0 0 4 * * *".to_string(), 1).unwrap();
+        assert_eq!(task.command.as_deref(), Some("say Restarting soon"));
+        // Only command tasks keep a command
+        let task = ScheduledTask::new(1, TaskAction::Backup, Some("stop".to_string()), "0 0 4 * * *".to_string(), 1).unwrap();
+        assert_eq!(task.command, None);
+    }
+
+    #[test]
+    fn test_next_run() {
+        let task = ScheduledTask::new(1, TaskAction::Backup, None, "0 */15 * * * *".to_string(), 1).unwrap();
+        let time = Utc.with_ymd_and_hms(2025, 1, 1, 10, 7, 30).unwrap();
+        assert_eq!(task.next_run_after(&time), Some(Utc.with_ymd_and_hms(2025, 1, 1, 10, 15, 0).unwrap()));
+
+        let task = ScheduledTask::new(1, TaskAction::Backup, None, "0 0 0 1 1 * 2024".to_string(), 1).unwrap();
+        assert_eq!(task.next_run_after(&time), None);
+    }
+}
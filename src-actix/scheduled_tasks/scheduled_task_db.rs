@@ -0,0 +1,82 @@
+use super::scheduled_task_data::ScheduledTask;
+use crate::database::{Pool, sql};
+use anyhow::Result;
+use log::debug;
+use sqlx::Executor;
+
+#[cfg(feature = "sqlite")]
+static CREATE_SCHEDULED_TASKS_TABLE_SQL: &str = include_str!("../../resources/sql/sqlite/scheduled_tasks.sql");
+#[cfg(feature = "mysql")]
+static CREATE_SCHEDULED_TASKS_TABLE_SQL: &str = include_str!("../../resources/sql/mysql/scheduled_tasks.sql");
+#[cfg(feature = "postgres")]
+static CREATE_SCHEDULED_TASKS_TABLE_SQL: &str = include_str!("../../resources/sql/postgres/scheduled_tasks.sql");
+
+/// Initialize the scheduled tasks table
+pub async fn initialize(pool: &Pool) -> Result<()> {
+    debug!("Initializing scheduled tasks database...");
+    pool.execute(CREATE_SCHEDULED_TASKS_TABLE_SQL).await?;
+    Ok(())
+}
+
+impl ScheduledTask {
+    pub async fn create(&mut self, pool: &Pool) -> Result<()> {
+        let result = sqlx::query(&*sql(r#"INSERT INTO scheduled_tasks (server_id, action, command, cron, enabled, owner_id) VALUES (?, ?, ?, ?, ?, ?)"#))
+            .bind(self.server_id as i64)
+            .bind(self.action.as_str())
+            .bind(self.command.as_deref())
+            .bind(&self.cron)
+            .bind(self.enabled)
+            .bind(self.owner_id as i64)
+            .execute(pool)
+            .await?;
+
+        #[cfg(feature = "sqlite")]
+        {
+            self.id = result.last_insert_rowid() as u64;
+        }
+        #[cfg(feature = "mysql")]
+        {
+            self.id = result.last_insert_id();
+        }
+        #[cfg(feature = "postgres")]
+        {
+            let id: (i64,) = sqlx::query_as(&*sql("SELECT currval(pg_get_serial_sequence('scheduled_tasks', 'id'))")).fetch_one(pool).await?;
+            self.id = id.0 as u64;
+        }
+        Ok(())
+    }
+
+    /// Tasks created by `owner_id`, optionally only those for one server
+    pub async fn list(owner_id: u64, server_id: Option<u64>, pool: &Pool) -> Result<Vec<Self>> {
+        Ok(match server_id {
+            Some(server_id) => {
+                sqlx::query_as(&*sql(r#"SELECT * FROM scheduled_tasks WHERE owner_id = ? AND server_id = ? ORDER BY id"#))
+                    .bind(owner_id as i64)
+                    .bind(server_id as i64)
+                    .fetch_all(pool)
+                    .await?
+            }
+            None => sqlx::query_as(&*sql(r#"SELECT * FROM scheduled_tasks WHERE owner_id = ? ORDER BY id"#)).bind(owner_id as i64).fetch_all(pool).await?,
+        })
+    }
+
+    /// Every enabled task, for the scheduler
+    pub async fn list_enabled(pool: &Pool) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as(&*sql(r#"SELECT * FROM scheduled_tasks WHERE enabled = ?"#)).bind(true).fetch_all(pool).await?)
+    }
+
+    /// Delete one of `owner_id`'s tasks, returning whether it existed
+    pub async fn delete(id: u64, owner_id: u64, pool: &Pool) -> Result<bool> {
+        let result = sqlx::query(&*sql(r#"DELETE FROM scheduled_tasks WHERE id = ? AND owner_id = ?"#))
+            .bind(id as i64)
+            .bind(owner_id as i64)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn update_last_run(id: u64, last_run: i64, pool: &Pool) -> Result<()> {
+        sqlx::query(&*sql(r#"UPDATE scheduled_tasks SET last_run = ? WHERE id = ?"#)).bind(last_run).bind(id as i64).execute(pool).await?;
+        Ok(())
+    }
+}
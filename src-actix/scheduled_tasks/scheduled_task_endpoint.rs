@@ -0,0 +1,90 @@
+use super::scheduled_task_data::{ScheduledTask, TaskAction};
+use super::task_scheduler::scheduler;
+use crate::actix_util::http_error::Result;
+use crate::authentication::auth_data::UserRequestExt;
+use crate::authentication::permission_guard;
+use crate::authentication::permission_guard::ServerRight;
+use crate::server::server_data::ServerData;
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
+use anyhow::anyhow;
+use serde_hash::hashids::decode_single;
+use serde_json::json;
+use std::collections::HashMap;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::scope("/scheduled-tasks").service(list_tasks).service(create_task).service(delete_task));
+}
+
+/// GET /api/scheduled-tasks?server_id= - List the user's scheduled tasks, optionally for one server
+#[get("")]
+async fn list_tasks(query: web::Query<HashMap<String, String>>, req: HttpRequest) -> Result<impl Responder> {
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+    let server_id = query.get("server_id").map(|id| decode_single(id.as_str())).transpose()?;
+
+    let tasks = ScheduledTask::list(user_id, server_id, crate::database::get_pool()).await?;
+    Ok(HttpResponse::Ok().json(tasks))
+}
+
+/// POST /api/scheduled-tasks - Schedule a restart, backup or console command on a server
+#[post("")]
+async fn create_task(body: web::Json<serde_json::Value>, req: HttpRequest) -> Result<impl Responder> {
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    let server_id = body.get("server_id").and_then(|v| v.as_str()).ok_or(anyhow!("server_id is required"))?;
+    let server_id = decode_single(server_id)?;
+    let action = body
+        .get("action")
+        .and_then(|v| v.as_str())
+        .and_then(TaskAction::parse)
+        .ok_or(anyhow!("action must be one of restart, backup or command"))?;
+    let cron = body.get("cron").and_then(|v| v.as_str()).map(str::trim).ok_or(anyhow!("cron is required"))?;
+    let command = body.get("command").and_then(|v| v.as_str()).map(String::from);
+
+    match action {
+        TaskAction::Backup if !user.can_create_backup() => {
+            return Ok(HttpResponse::Forbidden().json(json!({
+                "error": "You don't have permission to create backups"
+            })));
+        }
+        TaskAction::Backup => {}
+        TaskAction::Restart | TaskAction::Command => permission_guard::require(&user, ServerRight::ControlServer)?,
+    }
+
+    if ServerData::get(server_id, user_id).await?.is_none() {
+        return Ok(HttpResponse::NotFound().json(json!({
+            "error": "Server not found"
+        })));
+    }
+
+    let mut task = match ScheduledTask::new(server_id, action, command, cron.to_string(), user_id) {
+        Ok(task) => task,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "error": e.to_string()
+            })));
+        }
+    };
+    task.create(crate::database::get_pool()).await?;
+    scheduler().add(task.clone());
+
+    Ok(HttpResponse::Created().json(task))
+}
+
+/// DELETE /api/scheduled-tasks/:id - Delete one of the user's scheduled tasks
+#[delete("{task_id}")]
+async fn delete_task(task_id: web::Path<String>, req: HttpRequest) -> Result<impl Responder> {
+    let task_id = decode_single(task_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    if ScheduledTask::delete(task_id, user_id, crate::database::get_pool()).await? {
+        scheduler().remove(task_id);
+        Ok(HttpResponse::Ok().finish())
+    } else {
+        Ok(HttpResponse::NotFound().json(json!({
+            "error": "Scheduled task not found"
+        })))
+    }
+}
@@ -0,0 +1,197 @@
+use super::scheduled_task_data::{ScheduledTask, TaskAction};
+use crate::database::Pool;
+use crate::server::backups::backup_data::BackupType;
+use crate::server::backups::backup_service;
+use crate::server::server_data::ServerData;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use log::{error, info};
+use obsidian_scheduler::callback::CallbackTimer;
+use obsidian_scheduler::timer_trait::Timer;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// How often the scheduler looks for due tasks; cron expressions have second precision
+const TICK: Duration = Duration::from_secs(1);
+
+/// The time tasks are checked against, so tests can run the scheduler on a virtual clock
+pub type Clock = Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>;
+
+static SCHEDULER: OnceLock<Arc<TaskScheduler>> = OnceLock::new();
+static TIMER: OnceLock<Arc<CallbackTimer>> = OnceLock::new();
+
+/// The scheduler running the panel's tasks, on the system clock
+pub fn scheduler() -> &'static Arc<TaskScheduler> {
+    SCHEDULER.get_or_init(|| Arc::new(TaskScheduler::new(Arc::new(Utc::now))))
+}
+
+/// Load the enabled tasks from the database and start running them
+pub async fn start(pool: &Pool) -> Result<()> {
+    let tasks = ScheduledTask::list_enabled(pool).await?;
+    info!("Scheduling {} task(s)", tasks.len());
+    for task in tasks {
+        scheduler().add(task);
+    }
+
+    let timer = scheduler().timer(run_task);
+    timer.start().await?;
+    TIMER.set(timer).map_err(|_| anyhow!("Task scheduler is already running"))?;
+    Ok(())
+}
+
+/// Run a due task against its server
+async fn run_task(task: ScheduledTask) -> Result<()> {
+    let pool = crate::database::get_pool();
+    let mut server = ServerData::get_with_pool(task.server_id, pool).await?.ok_or(anyhow!("Server {} not found", task.server_id))?;
+    info!("Running scheduled {} task {} for server '{}'", task.action.as_str(), task.id, server.name);
+    ScheduledTask::update_last_run(task.id, Utc::now().timestamp(), pool).await?;
+
+    match task.action {
+        TaskAction::Restart if server.has_server_process().await => server.restart_server().await?,
+        TaskAction::Backup => {
+            let description = Some(format!("Scheduled backup at {}", Utc::now().format("%Y-%m-%d %H:%M:%S")));
+            backup_service::perform_backup(&server, BackupType::Incremental, description).await?;
+        }
+        TaskAction::Command if server.has_server_process().await => {
+            server.send_command(task.command.as_deref().ok_or(anyhow!("Command task has no command"))?).await?
+        }
+        TaskAction::Restart | TaskAction::Command => info!("Skipping scheduled {} for server '{}' as it is not running", task.action.as_str(), server.name),
+    }
+    Ok(())
+}
+
+struct ScheduledEntry {
+    task: ScheduledTask,
+    next_run: Option<DateTime<Utc>>,
+}
+
+/// Keeps the enabled tasks and when each one is next due
+pub struct TaskScheduler {
+    entries: Mutex<HashMap<u64, ScheduledEntry>>,
+    clock: Clock,
+}
+
+impl TaskScheduler {
+    pub fn new(clock: Clock) -> Self {
+        Self { entries: Mutex::new(HashMap::new()), clock }
+    }
+
+    /// Schedule `task` from now on, replacing any task with the same ID
+    pub fn add(&self, task: ScheduledTask) {
+        if !task.enabled {
+            self.remove(task.id);
+            return;
+        }
+        let next_run = task.next_run_after(&(self.clock)());
+        self.entries.lock().unwrap().insert(task.id, ScheduledEntry { task, next_run });
+    }
+
+    pub fn remove(&self, id: u64) {
+        self.entries.lock().unwrap().remove(&id);
+    }
+
+    /// Tasks due at `now`, each moved on to its next run
+    pub fn take_due(&self, now: DateTime<Utc>) -> Vec<ScheduledTask> {
+        let mut entries = self.entries.lock().unwrap();
+        let mut due = Vec::new();
+        for entry in entries.values_mut() {
+            if entry.next_run.is_some_and(|next_run| next_run <= now) {
+                entry.next_run = entry.task.next_run_after(&now);
+                due.push(entry.task.clone());
+            }
+        }
+        due.sort_by_key(|task| task.id);
+        due
+    }
+
+    /// A timer that hands due tasks to `runner`, each on its own task so a long restart
+    /// doesn't hold up the others
+    pub fn timer<F, Fut>(self: &Arc<Self>, runner: F) -> Arc<CallbackTimer>
+    where
+        F: Fn(ScheduledTask) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let scheduler = Arc::clone(self);
+        let runner = Arc::new(runner);
+        CallbackTimer::new(
+            move |_timer_handle| {
+                let scheduler = scheduler.clone();
+                let runner = runner.clone();
+                async move {
+                    for task in scheduler.take_due((scheduler.clock)()) {
+                        let id = task.id;
+                        let run = runner(task);
+                        tokio::spawn(async move {
+                            if let Err(e) = run.await {
+                                error!("Scheduled task {} failed: {}", id, e);
+                            }
+                        });
+                    }
+                    Ok(())
+                }
+            },
+            TICK,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tokio::time::Instant;
+
+    /// A clock that starts at `start` and advances with tokio's (paused) time
+    fn virtual_clock(start: DateTime<Utc>) -> Clock {
+        let started = Instant::now();
+        Arc::new(move || start + chrono::Duration::from_std(started.elapsed()).unwrap())
+    }
+
+    fn task(id: u64, action: TaskAction, cron: &str) -> ScheduledTask {
+        let command = (action == TaskAction::Command).then(|| "save-all".to_string());
+        ScheduledTask { id, ..ScheduledTask::new(1, action, command, cron.to_string(), 1).unwrap() }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_task_fires_at_scheduled_time() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 10, 7, 30).unwrap();
+        let clock = virtual_clock(start);
+        let scheduler = Arc::new(TaskScheduler::new(clock.clone()));
+        scheduler.add(task(1, TaskAction::Backup, "0 */15 * * * *"));
+        scheduler.add(task(2, TaskAction::Command, "0 0 12 * * *"));
+        scheduler.add(task(3, TaskAction::Restart, "0 */15 * * * *"));
+        scheduler.remove(3);
+
+        let fired = Arc::new(Mutex::new(Vec::new()));
+        let recorded = fired.clone();
+        let timer = scheduler.timer(move |task| {
+            recorded.lock().unwrap().push((task.id, clock()));
+            async { Ok(()) }
+        });
+        timer.start().await.unwrap();
+
+        // 10:14:59, just before the first run
+        tokio::time::sleep(Duration::from_secs(7 * 60 + 29)).await;
+        assert!(fired.lock().unwrap().is_empty());
+
+        // 10:30:30, past the second run
+        tokio::time::sleep(Duration::from_secs(15 * 60 + 31)).await;
+        timer.stop().await.unwrap();
+
+        assert_eq!(
+            *fired.lock().unwrap(),
+            vec![(1, Utc.with_ymd_and_hms(2025, 1, 1, 10, 15, 0).unwrap()), (1, Utc.with_ymd_and_hms(2025, 1, 1, 10, 30, 0).unwrap())]
+        );
+    }
+
+    #[test]
+    fn test_disabled_task_is_not_scheduled() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 10, 7, 30).unwrap();
+        let scheduler = TaskScheduler::new(Arc::new(move || start));
+        scheduler.add(task(1, TaskAction::Backup, "0 */15 * * * *"));
+        scheduler.add(ScheduledTask { enabled: false, ..task(1, TaskAction::Backup, "0 */15 * * * *") });
+        assert!(scheduler.take_due(start + chrono::Duration::hours(1)).is_empty());
+    }
+}
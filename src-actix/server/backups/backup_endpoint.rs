@@ -1,16 +1,19 @@
 use super::backup_data::{BackupScheduleRequest, BackupSettings, CreateBackupRequest, IgnoreEntry, IgnoreList};
-use super::{backup_db, backup_service};
+use super::{backup_db, backup_restore, backup_service};
 use crate::actix_util::http_error::Result;
 use crate::authentication::auth_data::UserRequestExt;
 use crate::server::server_data::ServerData;
 use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
 use actix_web::http::header::ContentDisposition;
+use actix_web_lab::sse::Sse;
 use anyhow::anyhow;
 use log::{error, info};
 use serde_hash::hashids::decode_single;
 use serde_json::json;
+use std::time::Duration;
 use tokio::fs;
 use tokio::io::{duplex};
+use tokio::sync::mpsc::unbounded_channel;
 use tokio_util::io::ReaderStream;
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
@@ -148,7 +151,9 @@ async fn delete_backup(
 
 }
 
-/// POST /api/server/:id/backups/:backupId/restore - Restore from a backup
+/// POST /api/server/:id/backups/:backupId/restore - Restore from a backup, streaming progress
+/// as server-sent events. A running server is stopped first and started again afterwards; the
+/// stream ends with a `completed` or `failed` event.
 #[post("/{commit_id}/restore")]
 async fn restore_backup(
     path: web::Path<(String, String)>,
@@ -159,8 +164,14 @@ async fn restore_backup(
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
 
+    if !user.can_restore_backup() {
+        return Ok(HttpResponse::Forbidden().json(json!({
+            "error": "You don't have permission to restore backups"
+        })));
+    }
+
     // Verify server exists and user has access
-    let server = match ServerData::get(server_id, user_id).await? {
+    let mut server = match ServerData::get(server_id, user_id).await? {
         Some(server) => server,
         None => {
             return Ok(HttpResponse::NotFound().json(json!({
@@ -174,17 +185,24 @@ async fn restore_backup(
         commit_id, server.name
     );
 
-    match backup_service::restore_backup(&server, &commit_id).await {
-        Ok(_) => Ok(HttpResponse::Ok().json(json!({
-            "message": "Backup restored successfully"
-        }))),
-        Err(e) => {
-            error!("Failed to restore backup: {}", e);
-            Ok(HttpResponse::InternalServerError().json(json!({
-                "error": format!("Failed to restore backup: {}", e)
-            })))
+    let (events, mut received) = unbounded_channel();
+    let (sender, receiver) = tokio::sync::mpsc::channel(100);
+    // The restore runs on its own task so it finishes even if the client disconnects
+    tokio::spawn(async move {
+        backup_restore::restore_with_progress(&mut server, &commit_id, events).await;
+    });
+    tokio::spawn(async move {
+        while let Some(event) = received.recv().await {
+            if sender.send(event.into_sse()).await.is_err() {
+                break;
+            }
         }
-    }
+    });
+
+    Ok(Sse::from_infallible_receiver(receiver)
+        .with_keep_alive(Duration::from_secs(5))
+        .respond_to(&req)
+        .map_into_boxed_body())
 }
 
 /// GET /api/server/:id/backups/:backupId/download - Download a backup as ZIP archive
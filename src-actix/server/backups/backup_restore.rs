@@ -0,0 +1,233 @@
+use super::backup_service;
+use crate::server::server_data::ServerData;
+use actix_web_lab::sse;
+use anyhow::{anyhow, Result};
+use log::{error, warn};
+use serde::Serialize;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// How long a server gets to shut down on its own before it is killed
+const STOP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A step of a restore, sent to the client as an SSE event named after the stage
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum RestoreEvent {
+    Stopping,
+    /// Files restored so far out of the files that differ from the backup
+    Restoring { completed: usize, total: usize },
+    Starting,
+    /// The final event of a successful restore
+    Completed { restarted: bool },
+    /// The final event of a failed restore
+    Failed { error: String },
+}
+
+impl RestoreEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Stopping => "stopping",
+            Self::Restoring { .. } => "progress",
+            Self::Starting => "starting",
+            Self::Completed { .. } => "completed",
+            Self::Failed { .. } => "failed",
+        }
+    }
+
+    pub fn into_sse(self) -> sse::Event {
+        let name = self.name();
+        // Every variant serializes, so the fallback is never sent
+        sse::Data::new_json(&self).unwrap_or_else(|_| sse::Data::new("{}")).event(name).into()
+    }
+}
+
+/// The server a backup is restored into
+pub trait RestoreTarget {
+    fn is_running(&self) -> impl Future<Output = bool> + Send;
+    /// Stop the server and wait for its process to exit
+    fn stop(&mut self) -> impl Future<Output = Result<()>> + Send;
+    fn start(&mut self) -> impl Future<Output = Result<()>> + Send;
+    /// Restore the backup's files, calling `progress` with the files done and the total
+    fn restore(&self, backup_id: &str, progress: impl FnMut(usize, usize) + Send + 'static) -> impl Future<Output = Result<()>> + Send;
+}
+
+impl RestoreTarget for ServerData {
+    async fn is_running(&self) -> bool {
+        self.has_server_process().await
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        self.stop_server().await?;
+        let deadline = tokio::time::Instant::now() + STOP_TIMEOUT;
+        while self.has_server_process().await {
+            if tokio::time::Instant::now() >= deadline {
+                warn!("Server '{}' didn't stop within {:?}, killing it", self.name, STOP_TIMEOUT);
+                return self.kill_server().await;
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        Ok(())
+    }
+
+    async fn start(&mut self) -> Result<()> {
+        self.start_server().await
+    }
+
+    async fn restore(&self, backup_id: &str, progress: impl FnMut(usize, usize) + Send + 'static) -> Result<()> {
+        backup_service::restore_backup(self, backup_id, progress).await
+    }
+}
+
+/// Restore `backup_id` into `server`, reporting each step to `events`.
+///
+/// A running server is stopped first so it doesn't write over the restored files, and started
+/// again afterwards, even if the restore failed. The last event is always `Completed` or
+/// `Failed`. Events are dropped once the client disconnects, but the restore carries on.
+pub async fn restore_with_progress<T: RestoreTarget>(server: &mut T, backup_id: &str, events: UnboundedSender<RestoreEvent>) {
+    let result = run(server, backup_id, &events).await;
+    let _ = events.send(match result {
+        Ok(restarted) => RestoreEvent::Completed { restarted },
+        Err(e) => {
+            error!("Failed to restore backup {}: {}", backup_id, e);
+            RestoreEvent::Failed { error: e.to_string() }
+        }
+    });
+}
+
+/// Returns whether the server was started again
+async fn run<T: RestoreTarget>(server: &mut T, backup_id: &str, events: &UnboundedSender<RestoreEvent>) -> Result<bool> {
+    let was_running = server.is_running().await;
+    if was_running {
+        let _ = events.send(RestoreEvent::Stopping);
+        server.stop().await.map_err(|e| anyhow!("Failed to stop the server: {}", e))?;
+    }
+
+    let progress_events = events.clone();
+    let mut last_percent = None;
+    let restored = server
+        .restore(backup_id, move |completed, total| {
+            // Checkouts report every file, so only whole percent steps are passed on
+            let percent = completed * 100 / total.max(1);
+            if last_percent != Some(percent) {
+                last_percent = Some(percent);
+                let _ = progress_events.send(RestoreEvent::Restoring { completed, total });
+            }
+        })
+        .await;
+
+    if was_running {
+        let _ = events.send(RestoreEvent::Starting);
+        if let Err(e) = server.start().await {
+            return match restored {
+                Ok(()) => Err(anyhow!("Backup restored, but the server failed to start: {}", e)),
+                Err(restore_error) => Err(restore_error),
+            };
+        }
+    }
+    restored.map(|_| was_running)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    /// A server that records what was done to it, restoring three files
+    struct FakeServer {
+        running: bool,
+        restore_fails: bool,
+        calls: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl FakeServer {
+        fn new(running: bool, restore_fails: bool) -> Self {
+            Self { running, restore_fails, calls: Arc::new(Mutex::new(Vec::new())) }
+        }
+
+        fn calls(&self) -> Vec<&'static str> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    impl RestoreTarget for FakeServer {
+        async fn is_running(&self) -> bool {
+            self.running
+        }
+
+        async fn stop(&mut self) -> Result<()> {
+            self.calls.lock().unwrap().push("stop");
+            self.running = false;
+            Ok(())
+        }
+
+        async fn start(&mut self) -> Result<()> {
+            self.calls.lock().unwrap().push("start");
+            self.running = true;
+            Ok(())
+        }
+
+        async fn restore(&self, _backup_id: &str, mut progress: impl FnMut(usize, usize) + Send + 'static) -> Result<()> {
+            // Restoring over a running server would have its files overwritten under it
+            assert!(!self.running);
+            self.calls.lock().unwrap().push("restore");
+            for completed in 0..=3 {
+                progress(completed, 3);
+            }
+            if self.restore_fails { Err(anyhow!("Backup not found")) } else { Ok(()) }
+        }
+    }
+
+    async fn restore(server: &mut FakeServer) -> Vec<RestoreEvent> {
+        let (sender, mut receiver) = unbounded_channel();
+        restore_with_progress(server, "abc123", sender).await;
+        let mut events = Vec::new();
+        while let Some(event) = receiver.recv().await {
+            events.push(event);
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn test_running_server_is_stopped_and_restarted() {
+        let mut server = FakeServer::new(true, false);
+        let events = restore(&mut server).await;
+
+        assert_eq!(server.calls(), vec!["stop", "restore", "start"]);
+        assert!(server.running);
+        assert_eq!(
+            events,
+            vec![
+                RestoreEvent::Stopping,
+                RestoreEvent::Restoring { completed: 0, total: 3 },
+                RestoreEvent::Restoring { completed: 1, total: 3 },
+                RestoreEvent::Restoring { completed: 2, total: 3 },
+                RestoreEvent::Restoring { completed: 3, total: 3 },
+                RestoreEvent::Starting,
+                RestoreEvent::Completed { restarted: true },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stopped_server_stays_stopped() {
+        let mut server = FakeServer::new(false, false);
+        let events = restore(&mut server).await;
+
+        assert_eq!(server.calls(), vec!["restore"]);
+        assert_eq!(events.first(), Some(&RestoreEvent::Restoring { completed: 0, total: 3 }));
+        assert_eq!(events.last(), Some(&RestoreEvent::Completed { restarted: false }));
+    }
+
+    #[tokio::test]
+    async fn test_failed_restore_still_restarts_the_server() {
+        let mut server = FakeServer::new(true, true);
+        let events = restore(&mut server).await;
+
+        assert_eq!(server.calls(), vec!["stop", "restore", "start"]);
+        assert_eq!(events[events.len() - 2], RestoreEvent::Starting);
+        assert_eq!(events.last(), Some(&RestoreEvent::Failed { error: "Backup not found".to_string() }));
+    }
+}
@@ -338,10 +338,11 @@ fn parse_worldedit_filename(filename: &str) -> Option<i64> {
     Some(datetime.and_utc().timestamp())
 }
 
-/// Restore a backup
+/// Restore a backup, calling `progress` with the number of files restored so far and the total
 pub async fn restore_backup(
     server: &ServerData,
     backup_id: &str,
+    progress: impl FnMut(usize, usize) + Send + 'static,
 ) -> Result<()> {
     info!(
         "Restoring backup {} for server '{}' (ID: {})",
@@ -350,12 +351,20 @@ pub async fn restore_backup(
 
     // Check if this is a World-Only backup (filename ends with .zip)
     if backup_id.ends_with(".zip") {
-        return restore_worldedit_backup(server, backup_id).await;
+        return restore_worldedit_backup(server, backup_id, progress).await;
     }
 
-    // Regular Git backup restore
-    let manager = create_backup_manager(server)?;
-    manager.restore(backup_id).map_err(|e| anyhow!("Failed to restore backup: {}", e))?;
+    // Regular Git backup restore, in a blocking task since checking out a world takes a while
+    // (git2::Repository is not Send, so the manager is created there too)
+    let server_clone = server.clone();
+    let backup_id_clone = backup_id.to_string();
+    tokio::task::spawn_blocking(move || {
+        let manager = create_backup_manager(&server_clone)?;
+        manager
+            .restore_with_progress(&backup_id_clone, progress)
+            .map_err(|e| anyhow!("Failed to restore backup: {}", e))
+    })
+    .await??;
 
     info!(
         "Backup {} restored successfully for server '{}'",
@@ -366,7 +375,11 @@ pub async fn restore_backup(
 }
 
 /// Restore a WorldEdit backup (unzip to server directory)
-async fn restore_worldedit_backup(server: &ServerData, filename: &str) -> Result<()> {
+async fn restore_worldedit_backup(
+    server: &ServerData,
+    filename: &str,
+    progress: impl FnMut(usize, usize) + Send + 'static,
+) -> Result<()> {
     let server_dir = get_server_dir(server);
     let backup_path = server_dir.join("backups").join(filename);
 
@@ -384,7 +397,7 @@ async fn restore_worldedit_backup(server: &ServerData, filename: &str) -> Result
     let backup_path_clone = backup_path.clone();
 
     tokio::task::spawn_blocking(move || {
-        extract_worldedit_backup(&backup_path_clone, &server_dir_clone)
+        extract_worldedit_backup(&backup_path_clone, &server_dir_clone, progress)
     })
     .await??;
 
@@ -397,13 +410,19 @@ async fn restore_worldedit_backup(server: &ServerData, filename: &str) -> Result
 }
 
 /// Extract a WorldEdit ZIP backup to the server directory
-fn extract_worldedit_backup(zip_path: &Path, server_dir: &Path) -> Result<()> {
+fn extract_worldedit_backup(
+    zip_path: &Path,
+    server_dir: &Path,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<()> {
     use zip::ZipArchive;
 
     let file = File::open(zip_path)?;
     let mut archive = ZipArchive::new(file)?;
+    let total = archive.len();
+    progress(0, total);
 
-    for i in 0..archive.len() {
+    for i in 0..total {
         let mut file = archive.by_index(i)?;
         let outpath = server_dir.join(file.name());
 
@@ -418,6 +437,7 @@ fn extract_worldedit_backup(zip_path: &Path, server_dir: &Path) -> Result<()> {
             let mut outfile = File::create(&outpath)?;
             std::io::copy(&mut file, &mut outfile)?;
         }
+        progress(i + 1, total);
     }
 
     Ok(())
@@ -1,6 +1,7 @@
 pub mod backup_data;
 pub mod backup_db;
 pub mod backup_endpoint;
+pub mod backup_restore;
 pub mod backup_scheduler;
 pub mod backup_service;
 
@@ -0,0 +1,187 @@
+use crate::actix_util::http_error::Result;
+use crate::audit;
+use crate::audit::AuditAction;
+use crate::authentication::auth_data::{UserData, UserRequestExt};
+use crate::authentication::permission_guard;
+use crate::authentication::permission_guard::ServerRight;
+use crate::server::server_data::ServerData;
+use crate::server::server_status::ServerStatus;
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder};
+use anyhow::anyhow;
+use log::error;
+use serde::{Deserialize, Serialize};
+use serde_hash::hashids::{decode_single, encode_single};
+use serde_hash::serde_hash;
+use serde_json::json;
+use std::future::Future;
+
+/// An action that can be run on several servers at once
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BulkAction {
+    Start,
+    Stop,
+    Restart,
+    Kill,
+}
+
+impl BulkAction {
+    /// Why the action can't run on a server that is or isn't running, if it can't
+    pub fn check(self, running: bool) -> anyhow::Result<()> {
+        match (self, running) {
+            (Self::Start, true) => Err(anyhow!("Server is already running")),
+            (Self::Stop | Self::Restart | Self::Kill, false) => Err(anyhow!("Server is not running")),
+            _ => Ok(()),
+        }
+    }
+
+    fn audit_action(self) -> AuditAction {
+        match self {
+            Self::Start => AuditAction::ServerStart,
+            Self::Stop => AuditAction::ServerStop,
+            Self::Restart => AuditAction::ServerRestart,
+            Self::Kill => AuditAction::ServerKill,
+        }
+    }
+}
+
+/// The outcome of a bulk action on one server
+#[serde_hash]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkActionResult {
+    #[serde(hash)]
+    pub server_id: u64,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Run `run` on every server concurrently, returning the results in the order of `server_ids`
+pub async fn run_all<F, Fut>(server_ids: &[u64], run: F) -> Vec<BulkActionResult>
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = anyhow::Result<()>>,
+{
+    futures::future::join_all(server_ids.iter().map(|&server_id| {
+        let result = run(server_id);
+        async move {
+            match result.await {
+                Ok(()) => BulkActionResult { server_id, success: true, error: None },
+                Err(e) => BulkActionResult { server_id, success: false, error: Some(e.to_string()) },
+            }
+        }
+    }))
+    .await
+}
+
+/// Run `action` on one of the user's servers.
+///
+/// Starting and restarting wait for the server to finish loading, so like the single-server
+/// endpoints they carry on in the background once the request has been checked.
+async fn run_action(user: &UserData, user_id: u64, server_id: u64, action: BulkAction) -> anyhow::Result<()> {
+    permission_guard::require(user, ServerRight::ControlServer).map_err(|e| anyhow!(e.to_string()))?;
+
+    let mut server = ServerData::get(server_id, user_id).await?.ok_or(anyhow!("Server not found"))?;
+    action.check(server.has_server_process().await)?;
+    audit::record(user_id, action.audit_action(), encode_single(server.id)).await;
+
+    match action {
+        BulkAction::Start | BulkAction::Restart => {
+            tokio::spawn(async move {
+                let result = if action == BulkAction::Start { server.start_server().await } else { server.restart_server().await };
+                if let Err(e) = result {
+                    error!("Failed to {:?} server {}: {}", action, server.name, e);
+                    server.status = ServerStatus::Crashed;
+                    if let Err(save_err) = server.save().await {
+                        error!("Failed to save server status after start failure: {}", save_err);
+                    }
+                }
+            });
+            Ok(())
+        }
+        BulkAction::Stop => server.stop_server().await,
+        BulkAction::Kill => server.kill_server().await,
+    }
+}
+
+/// POST /api/servers/actions - Start, stop, restart or kill several servers at once.
+///
+/// Each server is handled on its own, so the response lists whether the action succeeded for
+/// every server rather than failing the whole request.
+#[post("actions")]
+pub async fn run_bulk_action(body: web::Json<serde_json::Value>, req: HttpRequest) -> Result<impl Responder> {
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    let action: BulkAction = body
+        .get("action")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .ok_or(anyhow!("action must be one of start, stop, restart or kill"))?;
+    let server_ids = body
+        .get("server_ids")
+        .and_then(|v| v.as_array())
+        .ok_or(anyhow!("server_ids is required"))?
+        .iter()
+        .map(|id| id.as_str().ok_or(anyhow!("Invalid server ID")).and_then(|id| decode_single(id).map_err(|e| anyhow!(e.to_string()))))
+        .collect::<anyhow::Result<Vec<u64>>>()?;
+
+    if server_ids.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "error": "server_ids must not be empty"
+        })));
+    }
+
+    let results = run_all(&server_ids, |server_id| run_action(&user, user_id, server_id, action)).await;
+    Ok(HttpResponse::Ok().json(json!({
+        "action": action,
+        "results": results,
+    })))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::scope("/servers").service(run_bulk_action));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    /// Run `action` against fake servers, where `running` holds the IDs of the running ones
+    async fn run_fake(server_ids: &[u64], running: &HashSet<u64>, action: BulkAction) -> Vec<BulkActionResult> {
+        run_all(server_ids, |server_id| async move {
+            // Finish out of order, so the results have to be put back in request order
+            tokio::time::sleep(Duration::from_millis(30 - server_id * 5)).await;
+            action.check(running.contains(&server_id))
+        })
+        .await
+    }
+
+    fn outcome(results: &[BulkActionResult]) -> Vec<(u64, bool, Option<&str>)> {
+        results.iter().map(|result| (result.server_id, result.success, result.error.as_deref())).collect()
+    }
+
+    #[tokio::test]
+    async fn test_stop_reports_stopped_servers() {
+        let running = HashSet::from([1, 3]);
+        let results = run_fake(&[1, 2, 3, 4], &running, BulkAction::Stop).await;
+        assert_eq!(
+            outcome(&results),
+            vec![(1, true, None), (2, false, Some("Server is not running")), (3, true, None), (4, false, Some("Server is not running"))]
+        );
+
+        let results = run_fake(&[1, 2, 3], &running, BulkAction::Restart).await;
+        assert_eq!(outcome(&results), vec![(1, true, None), (2, false, Some("Server is not running")), (3, true, None)]);
+    }
+
+    #[tokio::test]
+    async fn test_start_reports_running_servers() {
+        let running = HashSet::from([2]);
+        let results = run_fake(&[1, 2, 3], &running, BulkAction::Start).await;
+        assert_eq!(outcome(&results), vec![(1, true, None), (2, false, Some("Server is already running")), (3, true, None)]);
+
+        let action: BulkAction = serde_json::from_value(json!("kill")).unwrap();
+        assert_eq!(action, BulkAction::Kill);
+        assert!(serde_json::from_value::<BulkAction>(json!("pause")).is_err());
+    }
+}
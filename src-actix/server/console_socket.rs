@@ -0,0 +1,220 @@
+use crate::server::server_console::ServerConsole;
+use actix::{Actor, ActorContext, AsyncContext, Handler, Message as ActixMessage, StreamHandler, WrapFuture};
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use anyhow::Result;
+use log::{debug, error, warn};
+use serde::Serialize;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Sends a console command to the server behind a console socket
+pub type CommandSender = Arc<dyn Fn(String) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> + Send + Sync>;
+
+/// Messages sent to console clients
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConsoleMessage {
+    /// Recent output, sent once when the client connects
+    History { lines: Vec<String> },
+    /// A line of output as the server writes it
+    Line { line: String },
+    Error { message: String },
+}
+
+/// WebSocket actor streaming a server's console and sending the text it receives as commands
+pub struct ConsoleWebSocket {
+    console: Arc<ServerConsole>,
+    commands: CommandSender,
+}
+
+impl ConsoleWebSocket {
+    pub fn new(console: Arc<ServerConsole>, commands: CommandSender) -> Self {
+        Self { console, commands }
+    }
+}
+
+impl Actor for ConsoleWebSocket {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        debug!("Console WebSocket started");
+        let (history, mut lines) = self.console.subscribe();
+        ctx.address().do_send(SendMessage(ConsoleMessage::History { lines: history }));
+
+        let addr = ctx.address();
+        ctx.spawn(
+            async move {
+                loop {
+                    match lines.recv().await {
+                        Ok(line) => addr.do_send(SendMessage(ConsoleMessage::Line { line })),
+                        Err(RecvError::Lagged(skipped)) => warn!("Console client fell behind, skipped {} lines", skipped),
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            }
+            .into_actor(self),
+        );
+    }
+
+    fn stopped(&mut self, _: &mut Self::Context) {
+        debug!("Console WebSocket stopped");
+    }
+}
+
+/// Internal message for sending console output through the WebSocket
+#[derive(ActixMessage)]
+#[rtype(result = "()")]
+struct SendMessage(ConsoleMessage);
+
+impl Handler<SendMessage> for ConsoleWebSocket {
+    type Result = ();
+
+    fn handle(&mut self, msg: SendMessage, ctx: &mut Self::Context) {
+        match serde_json::to_string(&msg.0) {
+            Ok(json) => ctx.text(json),
+            Err(e) => error!("Failed to serialize console message: {}", e),
+        }
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for ConsoleWebSocket {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        match msg {
+            Ok(ws::Message::Text(text)) => {
+                let command = text.trim().to_string();
+                if command.is_empty() {
+                    return;
+                }
+                let send = (self.commands)(command);
+                let addr = ctx.address();
+                // Not tied to the actor, so a command is still sent if the client closes right after it
+                actix::spawn(async move {
+                    if let Err(e) = send.await {
+                        warn!("Failed to send console command: {}", e);
+                        addr.do_send(SendMessage(ConsoleMessage::Error { message: e.to_string() }));
+                    }
+                });
+            }
+            Ok(ws::Message::Ping(msg)) => ctx.pong(&msg),
+            Ok(ws::Message::Pong(_)) => {}
+            Ok(ws::Message::Close(reason)) => {
+                debug!("WebSocket close requested: {:?}", reason);
+                ctx.close(reason);
+                ctx.stop();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Upgrade the request to a console WebSocket
+pub fn start(console: Arc<ServerConsole>, commands: CommandSender, req: &HttpRequest, stream: web::Payload) -> HttpResponse {
+    match ws::start(ConsoleWebSocket::new(console, commands), req, stream) {
+        Ok(response) => response,
+        Err(e) => {
+            error!("Failed to start WebSocket: {}", e);
+            HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Failed to start WebSocket"
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_codec::{Decoder, Encoder};
+    use actix_http::ws::Codec;
+    use actix_web::web::{Bytes, BytesMut};
+    use actix_web::{test, App};
+    use std::time::Duration;
+
+    /// Encode client frames the way a browser sends them, masked
+    fn client_frames(messages: Vec<ws::Message>) -> Bytes {
+        let mut codec = Codec::new().client_mode();
+        let mut buffer = BytesMut::new();
+        for message in messages {
+            codec.encode(message, &mut buffer).unwrap();
+        }
+        buffer.freeze()
+    }
+
+    fn server_frames(body: Bytes) -> Vec<ws::Frame> {
+        let mut codec = Codec::new().client_mode();
+        let mut buffer = BytesMut::from(&body[..]);
+        let mut frames = Vec::new();
+        while let Some(frame) = codec.decode(&mut buffer).unwrap() {
+            frames.push(frame);
+        }
+        frames
+    }
+
+    #[actix_web::test]
+    async fn test_console_socket_sends_history_and_accepts_commands() {
+        let console = Arc::new(ServerConsole::new(10));
+        console.push("[Server thread/INFO]: Starting minecraft server version 1.21.1");
+        console.push("[Server thread/INFO]: Done (3.2s)! For help, type \"help\"");
+
+        let (sent, mut received) = tokio::sync::mpsc::unbounded_channel();
+        let commands: CommandSender = Arc::new(move |command| {
+            let sent = sent.clone();
+            Box::pin(async move {
+                sent.send(command)?;
+                Ok(())
+            })
+        });
+
+        let app = test::init_service(App::new().route(
+            "/console/ws",
+            web::get().to(move |req: HttpRequest, stream: web::Payload| {
+                let (console, commands) = (console.clone(), commands.clone());
+                async move { start(console, commands, &req, stream) }
+            }),
+        ))
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/console/ws")
+            .insert_header(("upgrade", "websocket"))
+            .insert_header(("connection", "upgrade"))
+            .insert_header(("sec-websocket-version", "13"))
+            .insert_header(("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ=="))
+            .set_payload(client_frames(vec![
+                ws::Message::Text(" say Hello ".into()),
+                ws::Message::Text("   ".into()),
+                ws::Message::Close(None),
+            ]))
+            .to_request();
+        let response = test::call_service(&app, req).await;
+        assert_eq!(response.status(), actix_web::http::StatusCode::SWITCHING_PROTOCOLS);
+
+        let frames = server_frames(test::read_body(response).await);
+        let messages: Vec<serde_json::Value> = frames
+            .iter()
+            .filter_map(|frame| match frame {
+                ws::Frame::Text(text) => Some(serde_json::from_slice(text).unwrap()),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            messages[0],
+            serde_json::json!({
+                "type": "history",
+                "lines": [
+                    "[Server thread/INFO]: Starting minecraft server version 1.21.1",
+                    "[Server thread/INFO]: Done (3.2s)! For help, type \"help\""
+                ]
+            })
+        );
+        assert!(matches!(frames.last(), Some(ws::Frame::Close(_))));
+
+        // Commands run off the socket, so they may land just after it closes
+        let command = tokio::time::timeout(Duration::from_secs(5), received.recv()).await.unwrap();
+        assert_eq!(command.as_deref(), Some("say Hello"));
+        assert!(received.try_recv().is_err());
+    }
+}
@@ -0,0 +1,88 @@
+use crate::broadcast;
+use crate::broadcast::broadcast_data::BroadcastMessage;
+use crate::database::Pool;
+use crate::notifications::notification_data::NotificationMessage;
+use crate::notifications::{NotificationActionType, NotificationData, NotificationItem, NotificationType};
+use anyhow::Result;
+use serde_hash::hashids::encode_single;
+
+/// Alert the whole panel that a server crashed: a notification for every user, and a
+/// `server_crashed` message for every client connected right now, whatever they are viewing
+pub async fn alert_crash(server_id: u64, server_name: &str, summary: Option<String>, pool: &Pool) -> Result<NotificationItem> {
+    let server_id_hash = encode_single(server_id);
+    let message = match &summary {
+        Some(summary) => format!("Server \"{}\" has crashed unexpectedly: {}", server_name, summary),
+        None => format!("Server \"{}\" has crashed unexpectedly.", server_name),
+    };
+
+    let notification = NotificationData::create(
+        format!("{} Crashed", server_name),
+        message,
+        NotificationType::System,
+        NotificationActionType::RestartServer.to_bits() | NotificationActionType::ViewDetails.to_bits(),
+        Some(server_id_hash.clone()),
+        pool,
+    )
+    .await?;
+
+    let notification_item = NotificationItem {
+        id: notification.id.clone(),
+        title: notification.title.clone(),
+        message: notification.message.clone(),
+        is_read: false,
+        timestamp: notification.timestamp,
+        notification_type: notification.notification_type,
+        action: notification.action,
+        referenced_server: Some(server_id_hash.clone()),
+    };
+
+    crate::notifications::broadcast_notification(notification_item.clone()).await;
+    let message = NotificationMessage::NewNotification { notification: notification_item.clone() };
+    broadcast::broadcast(BroadcastMessage::Notification { message });
+    broadcast::broadcast(BroadcastMessage::ServerCrashed { server_id: server_id_hash, server_name: server_name.to_string(), summary });
+
+    Ok(notification_item)
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use sqlx::Executor;
+
+    #[tokio::test]
+    async fn test_crash_is_notified_and_broadcast() {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        pool.execute("CREATE TABLE users (id INTEGER PRIMARY KEY NOT NULL); INSERT INTO users (id) VALUES (1), (2);").await.unwrap();
+        crate::notifications::initialize(&pool).await.unwrap();
+
+        let mut messages = broadcast::subscribe();
+        let summary = "Exception in server tick loop: java.lang.NullPointerException: boom".to_string();
+        let item = alert_crash(4242, "Survival", Some(summary.clone()), &pool).await.unwrap();
+        assert_eq!(item.title, "Survival Crashed");
+        assert_eq!(item.message, format!("Server \"Survival\" has crashed unexpectedly: {}", summary));
+
+        // Every user gets the notification
+        for user_id in [1, 2] {
+            let notifications = NotificationData::get_for_user(user_id, &pool).await.unwrap();
+            assert_eq!(notifications.iter().map(|n| n.id.as_str()).collect::<Vec<_>>(), vec![item.id.as_str()]);
+        }
+
+        // Other tests share the broadcast channel, so only this server's messages are considered
+        let server_id = encode_single(4242);
+        let (mut notified, mut crashed) = (false, false);
+        while !(notified && crashed) {
+            match messages.recv().await.unwrap() {
+                BroadcastMessage::Notification { message: NotificationMessage::NewNotification { notification } } => {
+                    notified |= notification.id == item.id;
+                }
+                BroadcastMessage::ServerCrashed { server_id: id, server_name, summary: crash_summary } if id == server_id => {
+                    assert_eq!(server_name, "Survival");
+                    assert_eq!(crash_summary.as_deref(), Some(summary.as_str()));
+                    crashed = true;
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use anyhow::Result;
+use cache::TtlCache;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::LazyLock;
+use std::time::Duration;
+use sysinfo::Disks;
+use walkdir::WalkDir;
+
+/// How many of the server's folders are listed, largest first
+const LARGEST_DIRECTORIES: usize = 10;
+
+/// Walking a big world takes a while, so results are reused for a short time
+static USAGE_CACHE: LazyLock<TtlCache<u64, DiskUsage>> = LazyLock::new(|| TtlCache::new(Duration::from_secs(30)));
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DirectoryUsage {
+    /// Folder name relative to the server directory, e.g. `world`
+    pub name: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DiskUsage {
+    /// Bytes taken by everything in the server directory
+    pub total_size: u64,
+    /// The server's top level folders, largest first
+    pub largest_directories: Vec<DirectoryUsage>,
+    /// Free bytes on the volume holding the server, if the volume could be found
+    pub free_space: Option<u64>,
+    pub volume_size: Option<u64>,
+}
+
+/// Disk usage of the server in `directory`, measured at most once per cache lifetime
+pub async fn disk_usage(server_id: u64, directory: &Path) -> Result<DiskUsage> {
+    if let Some(usage) = USAGE_CACHE.get(&server_id).await {
+        return Ok(usage);
+    }
+    let directory = directory.to_path_buf();
+    let usage = tokio::task::spawn_blocking(move || measure(&directory)).await??;
+    USAGE_CACHE.insert(server_id, usage.clone()).await;
+    Ok(usage)
+}
+
+/// Sum the size of every file under `directory`. Symlinks are counted as links, not followed,
+/// so a linked world or shared mods folder isn't counted twice.
+fn measure(directory: &Path) -> Result<DiskUsage> {
+    let mut total_size = 0;
+    let mut directories = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            let size = directory_size(&entry.path());
+            total_size += size;
+            directories.push(DirectoryUsage { name: entry.file_name().to_string_lossy().into_owned(), size });
+        } else {
+            total_size += entry.metadata().map(|metadata| metadata.len()).unwrap_or_default();
+        }
+    }
+    directories.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    directories.truncate(LARGEST_DIRECTORIES);
+
+    let (free_space, volume_size) = volume_space(directory).unzip();
+    Ok(DiskUsage { total_size, largest_directories: directories, free_space, volume_size })
+}
+
+fn directory_size(directory: &Path) -> u64 {
+    WalkDir::new(directory)
+        .into_iter()
+        .flatten()
+        .filter(|entry| !entry.file_type().is_dir())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Free and total bytes of the volume mounted closest to `directory`
+fn volume_space(directory: &Path) -> Option<(u64, u64)> {
+    let directory = directory.canonicalize().ok()?;
+    let disks = Disks::new_with_refreshed_list();
+    disks
+        .iter()
+        .filter(|disk| directory.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| (disk.available_space(), disk.total_space()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::path::PathBuf;
+
+    /// A server directory with files of known sizes: 1000 bytes in `world`, 300 in `logs`,
+    /// 50 in `config` and 20 at the top level
+    fn fixture(name: &str) -> PathBuf {
+        let dir = temp_dir(&format!("disk-usage-{}", name));
+        std::fs::create_dir_all(dir.join("world/region")).unwrap();
+        std::fs::create_dir_all(dir.join("logs")).unwrap();
+        std::fs::create_dir_all(dir.join("config/empty")).unwrap();
+        std::fs::write(dir.join("world/level.dat"), vec![0; 200]).unwrap();
+        std::fs::write(dir.join("world/region/r.0.0.mca"), vec![0; 800]).unwrap();
+        std::fs::write(dir.join("logs/latest.log"), vec![b'a'; 100]).unwrap();
+        std::fs::write(dir.join("logs/2024-01-01-1.log.gz"), vec![b'a'; 200]).unwrap();
+        std::fs::write(dir.join("config/server.toml"), vec![b'a'; 50]).unwrap();
+        std::fs::write(dir.join("eula.txt"), vec![b'a'; 20]).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_measure_sums_the_server_directory() {
+        let dir = fixture("measure");
+        let usage = measure(&dir).unwrap();
+        assert_eq!(usage.total_size, 1370);
+        assert_eq!(
+            usage.largest_directories,
+            vec![
+                DirectoryUsage { name: "world".to_string(), size: 1000 },
+                DirectoryUsage { name: "logs".to_string(), size: 300 },
+                DirectoryUsage { name: "config".to_string(), size: 50 },
+            ]
+        );
+        if let (Some(free_space), Some(volume_size)) = (usage.free_space, usage.volume_size) {
+            assert!(free_space <= volume_size);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_disk_usage_is_cached() {
+        let dir = fixture("cached");
+        let usage = disk_usage(u64::MAX, &dir).await.unwrap();
+        assert_eq!(usage.total_size, 1370);
+
+        // Files written while the result is cached show up once it expires
+        std::fs::write(dir.join("world/level.dat_old"), vec![0; 200]).unwrap();
+        assert_eq!(disk_usage(u64::MAX, &dir).await.unwrap(), usage);
+        USAGE_CACHE.invalidate(&u64::MAX).await;
+        assert_eq!(disk_usage(u64::MAX, &dir).await.unwrap().total_size, 1570);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,237 @@
+use crate::actix_util::path_sanitize::ensure_path_within;
+use anyhow::{anyhow, Result};
+use actix_web::web::Bytes;
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::fs::OpenOptions;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+/// State of an upload that arrives as a sequence of chunks, one request per chunk.
+///
+/// Chunks are appended to a `.part` file next to the destination and must arrive in order;
+/// a chunk is only counted once all of its bytes are written, so a dropped connection
+/// loses at most the chunk in flight. The client resumes from [`next_chunk`](Self::next_chunk),
+/// which the status endpoint reports.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkedUpload {
+    pub upload_id: String,
+    /// Destination path relative to the server directory
+    pub path: String,
+    pub total_chunks: u32,
+    /// Number of chunks fully received, which is also the index of the next expected chunk
+    pub next_chunk: u32,
+    /// Bytes of the destination written so far, where the next chunk starts
+    pub bytes_received: u64,
+    #[serde(skip)]
+    pub server_id: u64,
+    #[serde(skip)]
+    destination: PathBuf,
+}
+
+/// Result of writing one chunk
+#[derive(Debug, PartialEq)]
+pub enum ChunkOutcome {
+    /// The chunk was stored; more are expected
+    Received,
+    /// The chunk was already received earlier, e.g. resent after a lost response
+    Duplicate,
+    /// The final chunk was stored and the file moved to its destination
+    Complete,
+    /// The cancel flag was raised while the chunk was being written
+    Cancelled,
+}
+
+impl ChunkedUpload {
+    pub fn new(upload_id: impl Into<String>, server_id: u64, path: impl Into<String>, destination: impl Into<PathBuf>, total_chunks: u32) -> Self {
+        Self {
+            upload_id: upload_id.into(),
+            path: path.into(),
+            total_chunks,
+            next_chunk: 0,
+            bytes_received: 0,
+            server_id,
+            destination: destination.into(),
+        }
+    }
+
+    /// Start an upload to `path` inside the server directory `base`, creating its missing parent
+    /// folders
+    pub fn create(base: &Path, upload_id: impl Into<String>, server_id: u64, path: impl Into<String>, total_chunks: u32) -> Result<Self> {
+        let path = path.into();
+        let destination = ensure_path_within(base, &path)?;
+        let directory = destination.parent().ok_or(anyhow!("Invalid file path"))?;
+        std::fs::create_dir_all(directory)?;
+        Ok(Self::new(upload_id, server_id, path, destination, total_chunks))
+    }
+
+    /// File the chunks are assembled in until the upload completes
+    pub fn part_path(&self) -> PathBuf {
+        let mut file_name = self.destination.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".part");
+        self.destination.with_file_name(file_name)
+    }
+
+    pub fn destination(&self) -> &Path {
+        &self.destination
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.next_chunk >= self.total_chunks
+    }
+
+    /// Progress in percent, by chunk count
+    pub fn progress(&self) -> i64 {
+        if self.total_chunks == 0 {
+            return 100;
+        }
+        (self.next_chunk as u64 * 100 / self.total_chunks as u64) as i64
+    }
+
+    /// Write chunk `index` from `data`.
+    ///
+    /// Anything past the last complete chunk is discarded first, so a chunk interrupted
+    /// half-way is rewritten from its start. Out-of-order chunks are rejected.
+    pub async fn write_chunk<S, E>(&mut self, index: u32, mut data: S, cancel_flag: &AtomicBool) -> Result<ChunkOutcome>
+    where
+        S: Stream<Item = std::result::Result<Bytes, E>> + Unpin,
+        E: std::fmt::Display,
+    {
+        if index >= self.total_chunks {
+            return Err(anyhow!("Chunk {} is out of range, upload has {} chunks", index, self.total_chunks));
+        }
+        if index < self.next_chunk {
+            return Ok(ChunkOutcome::Duplicate);
+        }
+        if index > self.next_chunk {
+            return Err(anyhow!("Expected chunk {}, got chunk {}", self.next_chunk, index));
+        }
+
+        let mut file = OpenOptions::new().create(true).write(true).truncate(false).open(self.part_path()).await?;
+        file.set_len(self.bytes_received).await?;
+        file.seek(std::io::SeekFrom::Start(self.bytes_received)).await?;
+
+        let mut written = 0u64;
+        while let Some(bytes) = data.next().await {
+            if cancel_flag.load(Ordering::Relaxed) {
+                return Ok(ChunkOutcome::Cancelled);
+            }
+            let bytes = bytes.map_err(|e| anyhow!("Failed to read chunk {}: {}", index, e))?;
+            file.write_all(&bytes).await?;
+            written += bytes.len() as u64;
+        }
+        file.flush().await?;
+        drop(file);
+
+        self.bytes_received += written;
+        self.next_chunk += 1;
+        if !self.is_complete() {
+            return Ok(ChunkOutcome::Received);
+        }
+
+        tokio::fs::rename(self.part_path(), &self.destination).await?;
+        Ok(ChunkOutcome::Complete)
+    }
+
+    /// Remove the partially assembled file
+    pub async fn discard(&self) {
+        tokio::fs::remove_file(self.part_path()).await.ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use futures::stream;
+
+    fn chunk(data: &'static [u8]) -> stream::Iter<std::vec::IntoIter<std::result::Result<Bytes, std::io::Error>>> {
+        stream::iter(vec![Ok(Bytes::from_static(data))])
+    }
+
+    #[tokio::test]
+    async fn test_chunks_are_assembled_in_order() {
+        let dir = temp_dir("chunked-upload-assemble");
+        let destination = dir.join("world.zip");
+        let cancel = AtomicBool::new(false);
+        let mut upload = ChunkedUpload::new("upload-1", 1, "world.zip", &destination, 3);
+
+        assert_eq!(upload.write_chunk(0, chunk(b"hello "), &cancel).await.unwrap(), ChunkOutcome::Received);
+        assert_eq!(upload.write_chunk(1, chunk(b"chunked "), &cancel).await.unwrap(), ChunkOutcome::Received);
+        assert!(!destination.exists());
+        assert_eq!(upload.write_chunk(0, chunk(b"hello "), &cancel).await.unwrap(), ChunkOutcome::Duplicate);
+        assert!(upload.write_chunk(3, chunk(b"!"), &cancel).await.is_err());
+        assert_eq!(upload.write_chunk(2, chunk(b"world"), &cancel).await.unwrap(), ChunkOutcome::Complete);
+
+        assert_eq!(std::fs::read(&destination).unwrap(), b"hello chunked world");
+        assert!(!upload.part_path().exists());
+        assert_eq!(upload.progress(), 100);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_chunk_is_resumed() {
+        let dir = temp_dir("chunked-upload-resume");
+        let destination = dir.join("world.zip");
+        let cancel = AtomicBool::new(false);
+        let mut upload = ChunkedUpload::new("upload-2", 1, "world.zip", &destination, 3);
+
+        upload.write_chunk(0, chunk(b"first "), &cancel).await.unwrap();
+
+        // The connection drops half-way through the second chunk
+        let interrupted = stream::iter(vec![
+            Ok(Bytes::from_static(b"sec")),
+            Err(std::io::Error::new(std::io::ErrorKind::ConnectionReset, "connection reset")),
+        ]);
+        assert!(upload.write_chunk(1, interrupted, &cancel).await.is_err());
+        assert_eq!(upload.next_chunk, 1);
+        assert_eq!(upload.bytes_received, 6);
+        assert!(upload.write_chunk(2, chunk(b"third"), &cancel).await.is_err());
+
+        // The client resumes from the reported chunk, overwriting the partial data
+        assert_eq!(upload.write_chunk(upload.next_chunk, chunk(b"second "), &cancel).await.unwrap(), ChunkOutcome::Received);
+        assert_eq!(upload.write_chunk(2, chunk(b"third"), &cancel).await.unwrap(), ChunkOutcome::Complete);
+        assert_eq!(std::fs::read(&destination).unwrap(), b"first second third");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_chunk_is_not_counted() {
+        let dir = temp_dir("chunked-upload-cancel");
+        let cancel = AtomicBool::new(true);
+        let mut upload = ChunkedUpload::new("upload-3", 1, "world.zip", dir.join("world.zip"), 2);
+
+        assert_eq!(upload.write_chunk(0, chunk(b"data"), &cancel).await.unwrap(), ChunkOutcome::Cancelled);
+        assert_eq!(upload.next_chunk, 0);
+        upload.discard().await;
+        assert!(!upload.part_path().exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_upload_into_symlinked_directory_is_rejected() {
+        let dir = temp_dir("chunked-upload-sandbox");
+        let server = dir.join("server");
+        std::fs::create_dir_all(&server).unwrap();
+
+        let upload = ChunkedUpload::create(&server, "upload-4", 1, "/mods/new/sodium.jar", 1).unwrap();
+        assert_eq!(upload.destination(), server.join("mods/new/sodium.jar"));
+        assert!(server.join("mods/new").is_dir());
+        assert!(ChunkedUpload::create(&server, "upload-5", 1, "../world.zip", 1).is_err());
+
+        #[cfg(unix)]
+        {
+            std::fs::create_dir_all(dir.join("outside")).unwrap();
+            std::os::unix::fs::symlink(dir.join("outside"), server.join("linked")).unwrap();
+            assert!(ChunkedUpload::create(&server, "upload-6", 1, "linked/world.zip", 1).is_err());
+            assert!(ChunkedUpload::create(&server, "upload-7", 1, "linked/new/world.zip", 1).is_err());
+            assert!(!dir.join("outside/new").exists());
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
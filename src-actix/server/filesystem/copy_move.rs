@@ -0,0 +1,221 @@
+use crate::actix_util::path_sanitize::ensure_path_within;
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// What to do when an entry with the same name is already in the destination folder
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStrategy {
+    /// Replace the existing file or folder
+    #[default]
+    Overwrite,
+    /// Leave the existing entry and don't copy or move this one
+    Skip,
+    /// Keep both, giving the new entry a `name (1).ext` style name
+    Rename,
+}
+
+/// Copy `source` into `directory`, both relative to the server directory `base`, returning where
+/// it was copied to, or `None` if it was skipped
+pub fn copy_entry(base: &Path, source: &str, directory: &str, conflict: ConflictStrategy) -> Result<Option<PathBuf>> {
+    let source = ensure_path_within(base, source)?;
+    let Some(destination) = destination(&source, &ensure_path_within(base, directory)?, conflict)? else {
+        return Ok(None);
+    };
+    if source.is_dir() {
+        copy_dir_all(&source, &destination)?;
+    } else {
+        std::fs::copy(&source, &destination)?;
+    }
+    Ok(Some(destination))
+}
+
+/// Move `source` into `directory`, both relative to the server directory `base`, returning where
+/// it was moved to, or `None` if it was skipped
+pub fn move_entry(base: &Path, source: &str, directory: &str, conflict: ConflictStrategy) -> Result<Option<PathBuf>> {
+    let source = ensure_path_within(base, source)?;
+    let Some(destination) = destination(&source, &ensure_path_within(base, directory)?, conflict)? else {
+        return Ok(None);
+    };
+    std::fs::rename(&source, &destination)?;
+    Ok(Some(destination))
+}
+
+/// Pick the path `source` goes to in `directory`, clearing the way for it when overwriting
+fn destination(source: &Path, directory: &Path, conflict: ConflictStrategy) -> Result<Option<PathBuf>> {
+    let name = source.file_name().ok_or(anyhow!("Invalid source path"))?;
+    if !source.exists() {
+        return Err(anyhow!("{} does not exist", name.to_string_lossy()));
+    }
+    if source.is_dir() && directory.starts_with(source) {
+        return Err(anyhow!("Cannot put folder {} inside itself", name.to_string_lossy()));
+    }
+    std::fs::create_dir_all(directory)?;
+
+    let destination = directory.join(name);
+    // symlink_metadata, so a dangling symlink still counts as taking the name
+    if destination.symlink_metadata().is_err() {
+        return Ok(Some(destination));
+    }
+
+    match conflict {
+        ConflictStrategy::Skip => Ok(None),
+        ConflictStrategy::Rename => Ok(Some(available_name(directory, source))),
+        // Copying or moving an entry onto itself leaves it as it is
+        ConflictStrategy::Overwrite if destination == source => Ok(None),
+        ConflictStrategy::Overwrite => {
+            if source.starts_with(&destination) {
+                return Err(anyhow!("Cannot replace {} with an entry inside it", name.to_string_lossy()));
+            }
+            if destination.is_dir() && !destination.is_symlink() {
+                std::fs::remove_dir_all(&destination)?;
+            } else {
+                std::fs::remove_file(&destination)?;
+            }
+            Ok(Some(destination))
+        }
+    }
+}
+
+/// The first of `name (1).ext`, `name (2).ext`, ... that is free in `directory`. Folders keep
+/// any dots in their name, so `my.world` becomes `my.world (1)`.
+fn available_name(directory: &Path, source: &Path) -> PathBuf {
+    let name = source.file_name().unwrap_or_default().to_string_lossy();
+    let (stem, extension) = match (source.is_file(), source.file_stem(), source.extension()) {
+        (true, Some(stem), Some(extension)) => (stem.to_string_lossy(), format!(".{}", extension.to_string_lossy())),
+        _ => (name, String::new()),
+    };
+
+    (1..)
+        .map(|n| directory.join(format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| candidate.symlink_metadata().is_err())
+        .expect("an unused name")
+}
+
+fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dst)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let ty = entry.file_type()?;
+        if ty.is_dir() {
+            copy_dir_all(&entry.path(), &dst.join(entry.file_name()))?;
+        } else {
+            std::fs::copy(entry.path(), dst.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    /// A server directory with `server.properties` and `world/` both in `incoming/` and at the root
+    fn fixture(name: &str) -> PathBuf {
+        let dir = temp_dir(&format!("fs-copy-move-{}", name));
+        std::fs::create_dir_all(dir.join("incoming/world/region")).unwrap();
+        std::fs::write(dir.join("incoming/server.properties"), "motd=incoming").unwrap();
+        std::fs::write(dir.join("incoming/world/level.dat"), "incoming level").unwrap();
+        std::fs::write(dir.join("incoming/world/region/r.0.0.mca"), "incoming region").unwrap();
+        std::fs::create_dir_all(dir.join("world")).unwrap();
+        std::fs::write(dir.join("server.properties"), "motd=existing").unwrap();
+        std::fs::write(dir.join("world/level.dat"), "existing level").unwrap();
+        std::fs::write(dir.join("world/session.lock"), "existing lock").unwrap();
+        dir
+    }
+
+    fn read(path: PathBuf) -> String {
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_overwrite_replaces_existing_entries() {
+        let dir = fixture("overwrite");
+        let copied = copy_entry(&dir, "incoming/server.properties", "", ConflictStrategy::Overwrite).unwrap();
+        assert_eq!(copied, Some(dir.join("server.properties")));
+        assert_eq!(read(dir.join("server.properties")), "motd=incoming");
+        assert!(dir.join("incoming/server.properties").exists());
+
+        // The folder is replaced rather than merged, for both copy and move
+        copy_entry(&dir, "incoming/world", "", ConflictStrategy::Overwrite).unwrap();
+        assert_eq!(read(dir.join("world/level.dat")), "incoming level");
+        assert_eq!(read(dir.join("world/region/r.0.0.mca")), "incoming region");
+        assert!(!dir.join("world/session.lock").exists());
+
+        std::fs::write(dir.join("world/session.lock"), "existing lock").unwrap();
+        let moved = move_entry(&dir, "incoming/world", "", ConflictStrategy::Overwrite).unwrap();
+        assert_eq!(moved, Some(dir.join("world")));
+        assert!(!dir.join("incoming/world").exists());
+        assert!(!dir.join("world/session.lock").exists());
+
+        // An entry dropped where it already is stays put
+        assert_eq!(copy_entry(&dir, "server.properties", "", ConflictStrategy::Overwrite).unwrap(), None);
+        assert_eq!(read(dir.join("server.properties")), "motd=incoming");
+        assert!(copy_entry(&dir, "world", "world/region", ConflictStrategy::Overwrite).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_skip_leaves_existing_entries() {
+        let dir = fixture("skip");
+        assert_eq!(copy_entry(&dir, "incoming/server.properties", "", ConflictStrategy::Skip).unwrap(), None);
+        assert_eq!(move_entry(&dir, "incoming/world", "", ConflictStrategy::Skip).unwrap(), None);
+        assert_eq!(read(dir.join("server.properties")), "motd=existing");
+        assert_eq!(read(dir.join("world/level.dat")), "existing level");
+        assert!(!dir.join("world/region").exists());
+        assert!(dir.join("incoming/world/level.dat").exists(), "skipped entries are not moved");
+
+        // Names that are free are still copied
+        std::fs::write(dir.join("incoming/ops.json"), "[]").unwrap();
+        assert_eq!(copy_entry(&dir, "incoming/ops.json", "", ConflictStrategy::Skip).unwrap(), Some(dir.join("ops.json")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rename_keeps_both_entries() {
+        let dir = fixture("rename");
+        let copied = copy_entry(&dir, "incoming/server.properties", "", ConflictStrategy::Rename).unwrap();
+        assert_eq!(copied, Some(dir.join("server (1).properties")));
+        let copied = copy_entry(&dir, "incoming/server.properties", "", ConflictStrategy::Rename).unwrap();
+        assert_eq!(copied, Some(dir.join("server (2).properties")));
+        assert_eq!(read(dir.join("server.properties")), "motd=existing");
+        assert_eq!(read(dir.join("server (2).properties")), "motd=incoming");
+
+        let moved = move_entry(&dir, "incoming/world", "", ConflictStrategy::Rename).unwrap();
+        assert_eq!(moved, Some(dir.join("world (1)")));
+        assert_eq!(read(dir.join("world/level.dat")), "existing level");
+        assert_eq!(read(dir.join("world (1)/region/r.0.0.mca")), "incoming region");
+
+        // Copying into the same folder makes a copy beside the original
+        std::fs::create_dir_all(dir.join("my.world")).unwrap();
+        assert_eq!(copy_entry(&dir, "my.world", "", ConflictStrategy::Rename).unwrap(), Some(dir.join("my.world (1)")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_paths_outside_server_directory_are_rejected() {
+        let dir = fixture("sandbox");
+        let server = dir.join("incoming");
+        assert!(copy_entry(&server, "../server.properties", "", ConflictStrategy::Overwrite).is_err());
+        assert!(move_entry(&server, "server.properties", "../world", ConflictStrategy::Overwrite).is_err());
+        assert_eq!(read(dir.join("world/level.dat")), "existing level");
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&dir, server.join("linked")).unwrap();
+            assert!(copy_entry(&server, "server.properties", "linked", ConflictStrategy::Overwrite).is_err());
+            assert!(copy_entry(&server, "world", "linked/new", ConflictStrategy::Overwrite).is_err());
+            assert!(move_entry(&server, "server.properties", "linked/new", ConflictStrategy::Overwrite).is_err());
+            assert!(!dir.join("new").exists());
+            assert_eq!(read(dir.join("server.properties")), "motd=existing");
+            assert!(server.join("server.properties").exists());
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,13 +1,21 @@
 use actix_web_lab::sse;
 use actix_web_lab::sse::Event;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use log::{debug, error, info, trace};
 use std::fs;
 use std::io::{Read, Write};
-use std::path::{Path};
+use std::path::{Component, Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::sync::mpsc::Sender;
 use crate::actions::actions_data::ActionData;
+use crate::actix_util::path_sanitize::{ensure_file_within, ensure_path_within};
+
+/// Resolve the archive and the folder it is extracted to, both relative to the server directory `base`
+pub fn resolve_extract_paths(base: &Path, archive: &str, directory: &str) -> Result<(PathBuf, PathBuf)> {
+    let archive_path = ensure_file_within(base, archive)?;
+    let output_path = ensure_path_within(base, directory)?;
+    Ok((archive_path, output_path))
+}
 
 pub async fn extract(
     archive_path: impl AsRef<Path>,
@@ -62,11 +70,12 @@ async fn extract_zip(
     let mut processed_bytes = 0u64;
     let mut total_bytes = 0u64;
 
-    // Calculate total uncompressed size
+    // Calculate total uncompressed size, refusing the whole archive before anything is
+    // written if an entry would land outside the output directory
     for i in 0..archive.len() {
-        if let Ok(file) = archive.by_index(i) {
-            total_bytes += file.size();
-        }
+        let file = archive.by_index(i)?;
+        entry_output_path(output_path, &zip_entry_name(&file))?;
+        total_bytes += file.size();
     }
 
     info!("ZIP archive contains {} files, {} total bytes", total_files, total_bytes);
@@ -88,7 +97,9 @@ async fn extract_zip(
         }
 
         let mut file = archive.by_index(i)?;
-        let file_path = output_path.join(file.mangled_name());
+        let file_path = entry_output_path(output_path, &zip_entry_name(&file))?;
+        // Like unpack_in for tar entries, refuse to write through symlinks already on disk
+        check_no_symlinks(output_path, &file_path)?;
 
         debug!("Extracting: {} -> {}", file.name(), file_path.display());
 
@@ -174,12 +185,17 @@ async fn extract_tar_gz(
 ) -> Result<()> {
     info!("Extracting TAR.GZ archive: {}", archive_path.display());
 
-    // First pass: count entries
+    // First pass: count entries, refusing the whole archive before anything is written if an
+    // entry or link would land outside the output directory
     let file = fs::File::open(archive_path)?;
     let decoder = flate2::read::GzDecoder::new(file);
     let mut archive = tar::Archive::new(decoder);
 
-    let total_files = archive.entries()?.count();
+    let mut total_files = 0;
+    for entry in archive.entries()? {
+        check_tar_entry(output_path, &entry?)?;
+        total_files += 1;
+    }
     info!("TAR.GZ archive contains {} entries", total_files);
 
     // Send initial progress
@@ -205,13 +221,15 @@ async fn extract_tar_gz(
         }
 
         let mut entry = entry_result?;
-        let entry_path = entry.path()?;
-        let output_file_path = output_path.join(&entry_path);
+        let entry_path = entry.path()?.into_owned();
+        let output_file_path = entry_output_path(output_path, &entry_path)?;
 
         debug!("Extracting: {} -> {}", entry_path.display(), output_file_path.display());
 
-        // Extract the entry
-        entry.unpack(&output_file_path)?;
+        // Extract the entry; unpack_in also refuses to write through symlinks already on disk
+        if !entry.unpack_in(output_path)? {
+            return Err(anyhow!("Archive entry {} would be extracted outside of the destination", entry_path.display()));
+        }
         trace!("Extracted: {}", output_file_path.display());
 
         processed_files += 1;
@@ -245,3 +263,199 @@ async fn extract_tar_gz(
     info!("TAR.GZ extraction completed successfully");
     Ok(())
 }
+
+/// Where the archive entry `name` goes under `output_path`. Entries that would land outside of
+/// it, through `..` or an absolute path ("zip-slip"), are rejected.
+fn entry_output_path(output_path: &Path, name: &Path) -> Result<PathBuf> {
+    let mut resolved = PathBuf::new();
+    for component in name.components() {
+        match component {
+            Component::Normal(part) => resolved.push(part),
+            Component::CurDir => {}
+            Component::ParentDir if resolved.pop() => {}
+            _ => return Err(anyhow!("Archive entry {} would be extracted outside of the destination", name.display())),
+        }
+    }
+    Ok(output_path.join(resolved))
+}
+
+/// Check nothing between `output_path` and `path`, `path` included, is a symlink that writing
+/// to `path` would follow out of the destination
+fn check_no_symlinks(output_path: &Path, path: &Path) -> Result<()> {
+    for ancestor in path.ancestors().take_while(|ancestor| *ancestor != output_path) {
+        if ancestor.symlink_metadata().is_ok_and(|metadata| metadata.file_type().is_symlink()) {
+            return Err(anyhow!("Archive entry {} would be extracted through a symlink", path.display()));
+        }
+    }
+    Ok(())
+}
+
+/// Zip entry names always use `/`, but archives made on Windows sometimes use `\` anyway
+fn zip_entry_name<R: Read>(file: &zip::read::ZipFile<R>) -> PathBuf {
+    PathBuf::from(file.name().replace('\\', "/"))
+}
+
+/// Check a tar entry, and the target of a symlink or hard link entry, stays inside `output_path`
+fn check_tar_entry<R: Read>(output_path: &Path, entry: &tar::Entry<R>) -> Result<()> {
+    let path = entry.path()?;
+    entry_output_path(output_path, &path)?;
+
+    let entry_type = entry.header().entry_type();
+    if entry_type.is_symlink() || entry_type.is_hard_link() {
+        let target = entry.link_name()?.ok_or(anyhow!("Archive link {} has no target", path.display()))?;
+        // Symlinks are relative to the folder they are in, hard links to the root of the archive
+        let target = if entry_type.is_symlink() { path.parent().unwrap_or(Path::new("")).join(&target) } else { target.into_owned() };
+        entry_output_path(output_path, &target)
+            .map_err(|_| anyhow!("Archive link {} points outside of the destination", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::io::Cursor;
+    use zip::write::SimpleFileOptions;
+
+    fn write_zip(path: &Path, entries: &[(&str, &str)]) {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in entries {
+            if name.ends_with('/') {
+                writer.add_directory(*name, SimpleFileOptions::default()).unwrap();
+            } else {
+                writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+                writer.write_all(contents.as_bytes()).unwrap();
+            }
+        }
+        std::fs::write(path, writer.finish().unwrap().into_inner()).unwrap();
+    }
+
+    /// Entries are written with raw names, since the tar builder itself refuses `..`
+    fn write_tar_gz(path: &Path, entries: &[(&str, tar::EntryType, &str)]) {
+        let encoder = flate2::write::GzEncoder::new(fs::File::create(path).unwrap(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, entry_type, contents) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_entry_type(*entry_type);
+            header.set_mode(0o644);
+            if entry_type.is_symlink() {
+                header.as_old_mut().linkname[..contents.len()].copy_from_slice(contents.as_bytes());
+                header.set_size(0);
+                header.set_cksum();
+                builder.append(&header, std::io::empty()).unwrap();
+            } else {
+                header.set_size(contents.len() as u64);
+                header.set_cksum();
+                builder.append(&header, contents.as_bytes()).unwrap();
+            }
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    async fn run_extract(archive: &Path, output: &Path) -> (Result<()>, Vec<Event>) {
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(100);
+        let result = extract(archive, output, &sender, &AtomicBool::new(false), "extract-test").await;
+        drop(sender);
+        let mut events = Vec::new();
+        while let Some(event) = receiver.recv().await {
+            events.push(event);
+        }
+        (result, events)
+    }
+
+    #[tokio::test]
+    async fn test_extract_known_archives() {
+        let dir = temp_dir("fs-extract-known");
+        fs::create_dir_all(dir.join("server")).unwrap();
+        write_zip(&dir.join("server/world.zip"), &[("world/", ""), ("world/level.dat", "level data"), ("world/region/r.0.0.mca", "region data")]);
+        let (result, events) = run_extract(&dir.join("server/world.zip"), &dir.join("server/restored")).await;
+        result.unwrap();
+        assert_eq!(fs::read_to_string(dir.join("server/restored/world/level.dat")).unwrap(), "level data");
+        assert_eq!(fs::read_to_string(dir.join("server/restored/world/region/r.0.0.mca")).unwrap(), "region data");
+        assert!(!events.is_empty());
+
+        write_tar_gz(
+            &dir.join("server/plugins.tar.gz"),
+            &[("plugins/config.yml", tar::EntryType::Regular, "enabled: true"), ("plugins/latest.yml", tar::EntryType::Symlink, "config.yml")],
+        );
+        let (result, _) = run_extract(&dir.join("server/plugins.tar.gz"), &dir.join("server")).await;
+        result.unwrap();
+        assert_eq!(fs::read_to_string(dir.join("server/plugins/config.yml")).unwrap(), "enabled: true");
+        assert_eq!(fs::read_to_string(dir.join("server/plugins/latest.yml")).unwrap(), "enabled: true");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_path_traversal_archives_are_rejected() {
+        let dir = temp_dir("fs-extract-traversal");
+        fs::create_dir_all(dir.join("server")).unwrap();
+        write_zip(&dir.join("server/evil.zip"), &[("harmless.txt", "fine"), ("../evil.txt", "escaped")]);
+        let (result, _) = run_extract(&dir.join("server/evil.zip"), &dir.join("server")).await;
+        assert!(result.unwrap_err().to_string().contains("outside of the destination"));
+
+        write_tar_gz(&dir.join("server/evil.tar.gz"), &[("harmless.txt", tar::EntryType::Regular, "fine"), ("a/../../evil.txt", tar::EntryType::Regular, "escaped")]);
+        assert!(run_extract(&dir.join("server/evil.tar.gz"), &dir.join("server")).await.0.is_err());
+
+        // A symlink out of the destination, followed by an entry written through it
+        write_tar_gz(
+            &dir.join("server/link.tar.gz"),
+            &[("escape", tar::EntryType::Symlink, ".."), ("escape/evil.txt", tar::EntryType::Regular, "escaped")],
+        );
+        assert!(run_extract(&dir.join("server/link.tar.gz"), &dir.join("server")).await.0.is_err());
+
+        // Archives are checked before anything is written
+        assert!(!dir.join("evil.txt").exists());
+        assert!(!dir.join("server/harmless.txt").exists());
+        assert!(!dir.join("server/escape").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_zip_entries_are_not_written_through_symlinks() {
+        let dir = temp_dir("fs-extract-zip-symlink");
+        fs::create_dir_all(dir.join("server")).unwrap();
+        fs::create_dir_all(dir.join("outside")).unwrap();
+        fs::write(dir.join("outside.txt"), "untouched").unwrap();
+        std::os::unix::fs::symlink(dir.join("outside.txt"), dir.join("server/latest.log")).unwrap();
+        std::os::unix::fs::symlink(dir.join("outside"), dir.join("server/mods")).unwrap();
+
+        write_zip(&dir.join("server/file.zip"), &[("latest.log", "overwritten")]);
+        assert!(run_extract(&dir.join("server/file.zip"), &dir.join("server")).await.0.is_err());
+        assert_eq!(fs::read_to_string(dir.join("outside.txt")).unwrap(), "untouched");
+
+        write_zip(&dir.join("server/folder.zip"), &[("mods/", ""), ("mods/evil.jar", "escaped")]);
+        assert!(run_extract(&dir.join("server/folder.zip"), &dir.join("server")).await.0.is_err());
+        assert!(!dir.join("outside/evil.jar").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_output_folder_stays_in_server_directory() {
+        let dir = temp_dir("fs-extract-output");
+        let server = dir.join("server");
+        fs::create_dir_all(&server).unwrap();
+        write_zip(&server.join("world.zip"), &[("level.dat", "level data")]);
+
+        assert!(resolve_extract_paths(&server, "world.zip", "../restored").is_err());
+        assert!(resolve_extract_paths(&server, "missing.zip", "restored").is_err());
+        let (archive, output) = resolve_extract_paths(&server, "/world.zip", "/restored").unwrap();
+        run_extract(&archive, &output).await.0.unwrap();
+        assert_eq!(fs::read_to_string(server.join("restored/level.dat")).unwrap(), "level data");
+
+        #[cfg(unix)]
+        {
+            fs::create_dir_all(dir.join("outside")).unwrap();
+            std::os::unix::fs::symlink(dir.join("outside"), server.join("linked")).unwrap();
+            assert!(resolve_extract_paths(&server, "world.zip", "linked").is_err());
+            assert!(resolve_extract_paths(&server, "world.zip", "linked/restored").is_err());
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
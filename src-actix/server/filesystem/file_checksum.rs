@@ -0,0 +1,119 @@
+use crate::actix_util::path_sanitize::ensure_file_within;
+use anyhow::{anyhow, Result};
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+/// Size of the chunks a file is hashed in
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Hash algorithms a file checksum can be computed with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Sha1,
+    Sha256,
+    Md5,
+}
+
+impl ChecksumAlgorithm {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().replace('-', "").as_str() {
+            "sha1" => Ok(Self::Sha1),
+            "sha256" => Ok(Self::Sha256),
+            "md5" => Ok(Self::Md5),
+            _ => Err(anyhow!("Unsupported checksum algorithm '{}', expected sha1, sha256 or md5", name)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sha1 => "sha1",
+            Self::Sha256 => "sha256",
+            Self::Md5 => "md5",
+        }
+    }
+}
+
+/// Hex digest of the file at `relative` inside the server directory `base`, read in chunks so
+/// large files are never held in memory
+pub async fn file_checksum(base: &Path, relative: &str, algorithm: ChecksumAlgorithm) -> Result<String> {
+    let path = ensure_file_within(base, relative)?;
+    match algorithm {
+        ChecksumAlgorithm::Sha1 => hash_file::<Sha1>(&path).await,
+        ChecksumAlgorithm::Sha256 => hash_file::<Sha256>(&path).await,
+        ChecksumAlgorithm::Md5 => hash_file::<Md5>(&path).await,
+    }
+}
+
+async fn hash_file<D: Digest>(path: &Path) -> Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = D::new();
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actix_util::path_sanitize::is_not_found;
+    use crate::test_util::temp_dir;
+
+    #[tokio::test]
+    async fn test_checksums_of_known_inputs() {
+        let dir = temp_dir("fs-checksum-known");
+        let server = dir.join("server");
+        std::fs::create_dir_all(&server).unwrap();
+        std::fs::write(server.join("abc.txt"), b"abc").unwrap();
+        std::fs::write(server.join("empty.txt"), b"").unwrap();
+
+        assert_eq!(file_checksum(&server, "/abc.txt", ChecksumAlgorithm::Sha1).await.unwrap(), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(file_checksum(&server, "abc.txt", ChecksumAlgorithm::Sha256).await.unwrap(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(file_checksum(&server, "abc.txt", ChecksumAlgorithm::Md5).await.unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(file_checksum(&server, "empty.txt", ChecksumAlgorithm::Sha256).await.unwrap(), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(file_checksum(&server, "empty.txt", ChecksumAlgorithm::Md5).await.unwrap(), "d41d8cd98f00b204e9800998ecf8427e");
+
+        // Spans several chunks
+        let contents: Vec<u8> = (0..CHUNK_SIZE * 3 + 17).map(|i| (i % 251) as u8).collect();
+        std::fs::write(server.join("world.dat"), &contents).unwrap();
+        let expected: String = Sha256::digest(&contents).iter().map(|byte| format!("{:02x}", byte)).collect();
+        assert_eq!(file_checksum(&server, "world.dat", ChecksumAlgorithm::Sha256).await.unwrap(), expected);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_algorithm_names() {
+        assert_eq!(ChecksumAlgorithm::parse("SHA-256").unwrap(), ChecksumAlgorithm::Sha256);
+        assert_eq!(ChecksumAlgorithm::parse("sha1").unwrap(), ChecksumAlgorithm::Sha1);
+        assert_eq!(ChecksumAlgorithm::parse("md5").unwrap().name(), "md5");
+        assert!(ChecksumAlgorithm::parse("crc32").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_checksum_path_is_sandboxed() {
+        let dir = temp_dir("fs-checksum-sandbox");
+        let server = dir.join("server");
+        std::fs::create_dir_all(&server).unwrap();
+        std::fs::write(dir.join("secret.txt"), b"secret").unwrap();
+        assert!(file_checksum(&server, "../secret.txt", ChecksumAlgorithm::Sha256).await.is_err());
+        assert!(file_checksum(&server, "/world/../../secret.txt", ChecksumAlgorithm::Sha256).await.is_err());
+        assert!(is_not_found(&file_checksum(&server, "missing.jar", ChecksumAlgorithm::Sha256).await.unwrap_err()));
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.join("secret.txt"), server.join("link.txt")).unwrap();
+            std::os::unix::fs::symlink(&dir, server.join("linked")).unwrap();
+            assert!(file_checksum(&server, "link.txt", ChecksumAlgorithm::Sha256).await.is_err());
+            assert!(file_checksum(&server, "linked/secret.txt", ChecksumAlgorithm::Sha256).await.is_err());
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
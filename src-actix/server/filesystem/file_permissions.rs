@@ -0,0 +1,97 @@
+use crate::actix_util::path_sanitize::ensure_path_within;
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+/// Parse an octal mode such as `755` or `0644`, accepting only permission bits.
+///
+/// Setuid, setgid and sticky bits are refused: the panel often runs as root, so letting a user
+/// mark an uploaded binary setuid would hand them root.
+pub fn parse_mode(mode: &str) -> Result<u32> {
+    let digits = mode.trim().trim_start_matches("0o");
+    if digits.is_empty() || digits.len() > 4 {
+        return Err(anyhow!("Invalid mode '{}', expected an octal mode such as 755", mode));
+    }
+    let parsed = u32::from_str_radix(digits, 8).map_err(|_| anyhow!("Invalid mode '{}', expected an octal mode such as 755", mode))?;
+    if parsed & !0o777 != 0 {
+        return Err(anyhow!("Invalid mode '{}', setuid, setgid and sticky bits can't be set", mode));
+    }
+    Ok(parsed)
+}
+
+/// Apply `mode` to the file or folder at `relative` inside the server directory `base`.
+///
+/// Returns `false` without touching the file on platforms without Unix permissions.
+pub fn set_mode(base: &Path, relative: &str, mode: u32) -> Result<bool> {
+    let path = ensure_path_within(base, relative)?;
+    // A dangling symlink doesn't exist either, so its target is never changed
+    if !path.exists() {
+        return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "File not found").into());
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode))?;
+        Ok(true)
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = mode;
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actix_util::path_sanitize::is_not_found;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(parse_mode("755").unwrap(), 0o755);
+        assert_eq!(parse_mode("0644").unwrap(), 0o644);
+        assert_eq!(parse_mode("0o700").unwrap(), 0o700);
+        assert!(parse_mode("").is_err());
+        assert!(parse_mode("789").is_err());
+        assert!(parse_mode("rwxr-xr-x").is_err());
+        assert!(parse_mode("107777").is_err());
+    }
+
+    #[test]
+    fn test_special_bits_are_rejected() {
+        assert!(parse_mode("4755").is_err());
+        assert!(parse_mode("2755").is_err());
+        assert!(parse_mode("1777").is_err());
+        assert!(parse_mode("0o4755").is_err());
+        assert_eq!(parse_mode("0755").unwrap(), 0o755);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_mode_is_applied() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("fs-permissions");
+        let server = dir.join("server");
+        std::fs::create_dir_all(&server).unwrap();
+        std::fs::write(server.join("run.sh"), "#!/bin/sh\njava -jar server.jar\n").unwrap();
+        std::fs::write(dir.join("outside.sh"), "").unwrap();
+        std::fs::set_permissions(dir.join("outside.sh"), std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        assert!(set_mode(&server, "/run.sh", parse_mode("755").unwrap()).unwrap());
+        assert_eq!(std::fs::metadata(server.join("run.sh")).unwrap().permissions().mode() & 0o7777, 0o755);
+        assert!(is_not_found(&set_mode(&server, "missing.sh", 0o755).unwrap_err()));
+
+        // Neither a path out of the server directory nor a symlink to a file outside it is changed
+        std::os::unix::fs::symlink(dir.join("outside.sh"), server.join("linked.sh")).unwrap();
+        std::os::unix::fs::symlink(dir.join("missing.sh"), server.join("dangling.sh")).unwrap();
+        assert!(set_mode(&server, "../outside.sh", 0o777).is_err());
+        assert!(set_mode(&server, "linked.sh", 0o777).is_err());
+        assert!(set_mode(&server, "dangling.sh", 0o777).is_err());
+        assert_eq!(std::fs::metadata(dir.join("outside.sh")).unwrap().permissions().mode() & 0o7777, 0o644);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
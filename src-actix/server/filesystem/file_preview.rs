@@ -0,0 +1,150 @@
+use crate::actix_util::path_sanitize::ensure_file_within;
+use anyhow::Result;
+use serde::Serialize;
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes of a file returned by a preview unless the request asks for less
+pub const DEFAULT_PREVIEW_BYTES: usize = 64 * 1024;
+
+/// Largest preview a request may ask for
+pub const MAX_PREVIEW_BYTES: usize = 1024 * 1024;
+
+#[derive(Serialize, Debug)]
+pub struct FilePreview {
+    /// The start of the file, cut at a character boundary
+    pub content: String,
+    /// Whether the file is longer than `content`
+    pub truncated: bool,
+    /// Size of the whole file in bytes
+    pub size: u64,
+    /// Syntax highlighting hint derived from the file extension
+    pub language: &'static str,
+}
+
+/// Read the first `max_bytes` of the text file at `relative` inside the server directory `base`.
+///
+/// Returns `None` for files that look binary, i.e. contain a NUL byte in the previewed part.
+pub fn read_preview(base: &Path, relative: &str, max_bytes: usize) -> Result<Option<FilePreview>> {
+    let path = ensure_file_within(base, relative)?;
+    let file = std::fs::File::open(&path)?;
+    let size = file.metadata()?.len();
+    let mut buffer = Vec::with_capacity(max_bytes.min(size as usize));
+    file.take(max_bytes as u64).read_to_end(&mut buffer)?;
+
+    if buffer.contains(&0) {
+        return Ok(None);
+    }
+
+    let content = match std::str::from_utf8(&buffer) {
+        Ok(content) => content.to_string(),
+        // The limit split a multi-byte character; drop the incomplete tail
+        Err(e) if e.error_len().is_none() => String::from_utf8_lossy(&buffer[..e.valid_up_to()]).into_owned(),
+        Err(_) => String::from_utf8_lossy(&buffer).into_owned(),
+    };
+
+    Ok(Some(FilePreview {
+        content,
+        truncated: (buffer.len() as u64) < size,
+        size,
+        language: language_for_path(&path),
+    }))
+}
+
+/// Language identifier for syntax highlighting, `plaintext` when unknown
+pub fn language_for_path(path: &Path) -> &'static str {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+    match extension.as_str() {
+        "json" | "mcmeta" | "json5" => "json",
+        "properties" => "properties",
+        "toml" => "toml",
+        "yml" | "yaml" => "yaml",
+        "xml" => "xml",
+        "cfg" | "conf" | "ini" => "ini",
+        "sh" => "shell",
+        "bat" | "cmd" => "bat",
+        "ps1" => "powershell",
+        "js" | "mjs" | "cjs" => "javascript",
+        "ts" => "typescript",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "py" => "python",
+        "md" => "markdown",
+        "html" | "htm" => "html",
+        "css" => "css",
+        "csv" => "csv",
+        "log" => "log",
+        _ => "plaintext",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actix_util::path_sanitize::is_not_found;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_text_file_preview() {
+        let dir = temp_dir("fs-preview-text");
+        let server = dir.join("server");
+        std::fs::create_dir_all(server.join("config")).unwrap();
+        let path = server.join("config/settings.json");
+        std::fs::write(&path, r#"{"name": "Café"}"#).unwrap();
+
+        let preview = read_preview(&server, "/config/settings.json", DEFAULT_PREVIEW_BYTES).unwrap().unwrap();
+        assert_eq!(preview.content, r#"{"name": "Café"}"#);
+        assert!(!preview.truncated);
+        assert_eq!(preview.language, "json");
+
+        // Cutting inside "é" keeps only whole characters
+        let cut = r#"{"name": "Caf"#.len() + 1;
+        let preview = read_preview(&server, "config/settings.json", cut).unwrap().unwrap();
+        assert_eq!(preview.content, r#"{"name": "Caf"#);
+        assert!(preview.truncated);
+        assert_eq!(preview.size, std::fs::metadata(&path).unwrap().len());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_binary_file_is_not_previewable() {
+        let dir = temp_dir("fs-preview-binary");
+        std::fs::create_dir_all(dir.join("server")).unwrap();
+        std::fs::write(dir.join("server/level.dat"), [0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00]).unwrap();
+
+        assert!(read_preview(&dir.join("server"), "level.dat", DEFAULT_PREVIEW_BYTES).unwrap().is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_preview_path_is_sandboxed() {
+        let dir = temp_dir("fs-preview-sandbox");
+        let server = dir.join("server");
+        std::fs::create_dir_all(server.join("config")).unwrap();
+        std::fs::write(dir.join("secret.txt"), "password=hunter2").unwrap();
+
+        assert!(read_preview(&server, "../secret.txt", DEFAULT_PREVIEW_BYTES).is_err());
+        assert!(read_preview(&server, "config/../../secret.txt", DEFAULT_PREVIEW_BYTES).is_err());
+        assert!(is_not_found(&read_preview(&server, "config/missing.txt", DEFAULT_PREVIEW_BYTES).unwrap_err()));
+        assert!(is_not_found(&read_preview(&server, "config", DEFAULT_PREVIEW_BYTES).unwrap_err()));
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.join("secret.txt"), server.join("config/secret.txt")).unwrap();
+            std::os::unix::fs::symlink(&dir, server.join("linked")).unwrap();
+            assert!(!is_not_found(&read_preview(&server, "config/secret.txt", DEFAULT_PREVIEW_BYTES).unwrap_err()));
+            assert!(!is_not_found(&read_preview(&server, "linked/secret.txt", DEFAULT_PREVIEW_BYTES).unwrap_err()));
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_language_hints() {
+        assert_eq!(language_for_path(Path::new("server.properties")), "properties");
+        assert_eq!(language_for_path(Path::new("config/sodium.TOML")), "toml");
+        assert_eq!(language_for_path(Path::new("pack.mcmeta")), "json");
+        assert_eq!(language_for_path(Path::new("eula")), "plaintext");
+    }
+}
@@ -0,0 +1,86 @@
+use crate::actix_util::path_sanitize::ensure_path_within;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Largest file that can be saved through the editor
+pub const MAX_WRITE_BYTES: usize = 2 * 1024 * 1024;
+
+#[derive(Serialize, Debug)]
+pub struct WrittenFile {
+    /// Size of the file after the write, in bytes
+    pub size: u64,
+    pub last_modified: Option<SystemTime>,
+}
+
+/// Write `content` to `relative` inside the server directory `base`, creating missing parent
+/// folders.
+pub fn write_file(base: &Path, relative: &str, content: &str) -> Result<WrittenFile> {
+    if content.len() > MAX_WRITE_BYTES {
+        return Err(anyhow!("File is larger than the {} MB limit", MAX_WRITE_BYTES / 1024 / 1024));
+    }
+    let path = ensure_path_within(base, relative)?;
+    if path.is_dir() {
+        return Err(anyhow!("Path is a directory"));
+    }
+
+    let parent = path.parent().ok_or(anyhow!("Invalid file path"))?;
+    std::fs::create_dir_all(parent)?;
+    std::fs::write(&path, content)?;
+
+    let metadata = path.metadata()?;
+    Ok(WrittenFile { size: metadata.len(), last_modified: metadata.modified().ok() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn test_write_config_file() {
+        let dir = temp_dir("fs-write-config");
+        let server = dir.join("server");
+        std::fs::create_dir_all(&server).unwrap();
+
+        let written = write_file(&server, "/config/sodium/options.json", "{\"quality\": \"fancy\"}\n").unwrap();
+        assert_eq!(written.size, 21);
+        assert!(written.last_modified.is_some());
+        assert_eq!(std::fs::read_to_string(server.join("config/sodium/options.json")).unwrap(), "{\"quality\": \"fancy\"}\n");
+
+        // Saving again replaces the contents
+        let written = write_file(&server, "config/sodium/options.json", "{}").unwrap();
+        assert_eq!(written.size, 2);
+        assert_eq!(std::fs::read_to_string(server.join("config/sodium/options.json")).unwrap(), "{}");
+
+        assert!(write_file(&server, "config", "motd=Survival").is_err());
+        assert!(write_file(&server, "server.properties", &"a".repeat(MAX_WRITE_BYTES + 1)).is_err());
+        assert!(!server.join("server.properties").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_write_outside_server_directory_is_rejected() {
+        let dir = temp_dir("fs-write-sandbox");
+        let server = dir.join("server");
+        std::fs::create_dir_all(&server).unwrap();
+
+        assert!(write_file(&server, "../secret.txt", "secret").is_err());
+        assert!(write_file(&server, "/config/../../secret.txt", "secret").is_err());
+        assert!(!dir.join("secret.txt").exists());
+        #[cfg(unix)]
+        {
+            std::fs::create_dir_all(dir.join("outside")).unwrap();
+            std::os::unix::fs::symlink(dir.join("outside"), server.join("linked")).unwrap();
+            std::os::unix::fs::symlink(dir.join("missing.txt"), server.join("dangling.txt")).unwrap();
+            assert!(write_file(&server, "linked/new/ops.json", "[]").is_err());
+            assert!(write_file(&server, "dangling.txt", "secret").is_err());
+            assert!(!dir.join("outside/new").exists());
+            assert!(!dir.join("missing.txt").exists());
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,12 +1,27 @@
 use crate::actix_util::http_error::Result;
+use crate::actix_util::path_sanitize::{ensure_path_within, is_not_found};
+use crate::audit;
+use crate::audit::AuditAction;
 use crate::authentication::auth_data::UserRequestExt;
+use crate::authentication::permission_guard;
+use crate::authentication::permission_guard::ServerRight;
 use crate::broadcast;
 use crate::broadcast::broadcast_data::BroadcastMessage;
+use crate::server::filesystem::chunked_upload::{ChunkOutcome, ChunkedUpload};
+use crate::server::filesystem::copy_move;
+use crate::server::filesystem::copy_move::ConflictStrategy;
+use crate::server::filesystem::extract_wrapper::resolve_extract_paths;
+use crate::server::filesystem::file_checksum::{file_checksum, ChecksumAlgorithm};
+use crate::server::filesystem::file_permissions::{parse_mode, set_mode};
+use crate::server::filesystem::file_preview::{read_preview, DEFAULT_PREVIEW_BYTES, MAX_PREVIEW_BYTES};
+use crate::server::filesystem::file_write::{write_file, MAX_WRITE_BYTES};
 use crate::server::filesystem::filesystem_data::FilesystemData;
+use crate::server::filesystem::filesystem_search::{search_files, SearchMode, MAX_SEARCH_RESULTS};
+use crate::server::filesystem::folder_download::zip_folder;
 use crate::server::server_data::ServerData;
 use crate::actions::actions_data::{ActionData, ActionType, ActionStatus};
-use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
-use serde_hash::hashids::decode_single;
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use serde_hash::hashids::{decode_single, encode_single};
 use serde_json::json;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -38,6 +53,7 @@ type FileProcessTracker = Arc<Mutex<HashMap<String, Sender<Event>>>>;
 type UploadCancelFlags = Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>;
 type ArchiveCancelFlags = Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>;
 type ExtractCancelFlags = Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>;
+type ChunkedUploads = Arc<Mutex<HashMap<String, ChunkedUpload>>>;
 
 static UPLOAD_TRACKERS: OnceLock<FileProcessTracker> = OnceLock::new();
 static ARCHIVE_TRACKERS: OnceLock<FileProcessTracker> = OnceLock::new();
@@ -45,6 +61,7 @@ static EXTRACT_TRACKERS: OnceLock<FileProcessTracker> = OnceLock::new();
 static UPLOAD_CANCEL_FLAGS: OnceLock<UploadCancelFlags> = OnceLock::new();
 static ARCHIVE_CANCEL_FLAGS: OnceLock<ArchiveCancelFlags> = OnceLock::new();
 static EXTRACT_CANCEL_FLAGS: OnceLock<ExtractCancelFlags> = OnceLock::new();
+static CHUNKED_UPLOADS: OnceLock<ChunkedUploads> = OnceLock::new();
 
 fn get_upload_trackers() -> &'static FileProcessTracker {
     UPLOAD_TRACKERS.get_or_init(|| Arc::new(Mutex::new(HashMap::new())))
@@ -70,11 +87,18 @@ fn get_extract_cancel_flags() -> &'static ExtractCancelFlags {
     EXTRACT_CANCEL_FLAGS.get_or_init(|| Arc::new(Mutex::new(HashMap::new())))
 }
 
+fn get_chunked_uploads() -> &'static ChunkedUploads {
+    CHUNKED_UPLOADS.get_or_init(|| Arc::new(Mutex::new(HashMap::new())))
+}
+
 // Request/Response structures
 #[derive(Deserialize)]
 struct CopyMoveRequest {
     entries: Vec<String>,
     path: String,
+    /// What to do with entries whose name is already taken in `path`
+    #[serde(default)]
+    conflict: ConflictStrategy,
 }
 
 #[derive(Deserialize)]
@@ -94,6 +118,13 @@ struct NewEntryRequest {
     is_directory: bool,
 }
 
+#[derive(Deserialize)]
+struct PermissionsRequest {
+    path: String,
+    /// Octal mode, e.g. "755"
+    mode: String,
+}
+
 #[derive(Deserialize)]
 struct ArchiveRequest {
     entries: Vec<String>,
@@ -141,6 +172,7 @@ pub async fn upload_file(
     let server_id = decode_single(server_id.as_str())?;
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow::anyhow!("User ID not found"))?;
+    permission_guard::require(&user, ServerRight::UploadFiles)?;
 
     // Extract upload ID and file path from query parameters
     let upload_id = query.get("upload_id").ok_or(anyhow::anyhow!("upload_id parameter is required"))?.clone();
@@ -150,6 +182,14 @@ pub async fn upload_file(
 
     // get server from server id
     let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow::anyhow!("Server not found"))?;
+
+    // Uploads split into chunks send one request per chunk and can be resumed
+    if let Some(chunk) = query.get("chunk") {
+        let chunk: u32 = chunk.parse().map_err(|_| anyhow::anyhow!("chunk must be a number"))?;
+        let total: u32 = query.get("total").ok_or(anyhow::anyhow!("total parameter is required for chunked uploads"))?.parse().map_err(|_| anyhow::anyhow!("total must be a number"))?;
+        return upload_chunk(&server, user_id, upload_id, file_path, chunk, total, payload).await;
+    }
+
     let full_path = server.get_directory_path().join(&file_path);
     let directory = full_path.parent().ok_or(anyhow::anyhow!("Invalid file path"))?;
     std::fs::create_dir_all(directory)?;
@@ -321,6 +361,165 @@ pub async fn upload_file(
     })))
 }
 
+/// Store one chunk of a chunked upload, creating the upload on its first chunk
+async fn upload_chunk(server: &ServerData, user_id: u64, upload_id: String, file_path: String, chunk: u32, total: u32, payload: web::Payload) -> Result<HttpResponse> {
+    if total == 0 {
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "error": "total must be at least 1"
+        })));
+    }
+
+    // Take the upload out of the map while writing so a concurrent request for the same
+    // upload cannot interleave with this one
+    let existing = get_chunked_uploads().lock().await.remove(&upload_id);
+    let mut upload = match existing {
+        Some(upload) if upload.server_id != server.id || upload.path != file_path || upload.total_chunks != total => {
+            get_chunked_uploads().lock().await.insert(upload_id.clone(), upload);
+            return Ok(HttpResponse::Conflict().json(json!({
+                "error": "upload_id is already in use for a different file"
+            })));
+        }
+        Some(upload) => upload,
+        None => {
+            let upload = match ChunkedUpload::create(&server.get_directory_path(), upload_id.clone(), server.id, file_path.clone(), total) {
+                Ok(upload) => upload,
+                Err(e) => {
+                    return Ok(HttpResponse::BadRequest().json(json!({
+                        "error": e.to_string()
+                    })));
+                }
+            };
+            let action_details = json!({
+                "file_path": file_path,
+                "full_path": upload.destination().to_string_lossy(),
+                "total_chunks": total
+            });
+            ActionData::create(user_id as i64, upload_id.clone(), ActionType::Upload, Some(action_details.to_string()))
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to create action tracking: {}", e))?;
+            upload
+        }
+    };
+
+    let progress_sender = {
+        let trackers = get_upload_trackers().lock().await;
+        trackers.get(&upload_id).cloned()
+    };
+    let cancel_flag = {
+        let mut cancel_flags = get_upload_cancel_flags().lock().await;
+        cancel_flags.entry(upload_id.clone()).or_insert_with(|| Arc::new(AtomicBool::new(false))).clone()
+    };
+
+    let outcome = match upload.write_chunk(chunk, payload, &cancel_flag).await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            // Keep what was received so the client can resume from the status endpoint
+            warn!("Chunk {} of upload {} failed: {}", chunk, upload_id, e);
+            let response = json!({
+                "error": e.to_string(),
+                "next_chunk": upload.next_chunk,
+                "bytes_received": upload.bytes_received
+            });
+            get_chunked_uploads().lock().await.insert(upload_id, upload);
+            return Ok(HttpResponse::Conflict().json(response));
+        }
+    };
+
+    match outcome {
+        ChunkOutcome::Cancelled => {
+            upload.discard().await;
+            get_upload_cancel_flags().lock().await.remove(&upload_id);
+            if let Some(sender) = &progress_sender {
+                let _ = sender
+                    .send(Event::from(Data::new(
+                        json!({
+                            "status": "cancelled",
+                            "bytesUploaded": upload.bytes_received
+                        })
+                        .to_string(),
+                    )))
+                    .await;
+            }
+            if let Ok(Some(action)) = ActionData::get_by_tracker_id(&upload_id).await {
+                let _ = action.update_status(ActionStatus::Failed, Some("Upload cancelled by user".to_string())).await;
+            }
+            Ok(HttpResponse::Ok().json(json!({
+                "status": "cancelled",
+                "message": "Upload cancelled by user"
+            })))
+        }
+        ChunkOutcome::Complete => {
+            get_upload_cancel_flags().lock().await.remove(&upload_id);
+            if let Some(sender) = &progress_sender {
+                let _ = sender
+                    .send(Event::from(Data::new(
+                        json!({
+                            "status": "complete",
+                            "bytesUploaded": upload.bytes_received
+                        })
+                        .to_string(),
+                    )))
+                    .await;
+            }
+            if let Ok(Some(action)) = ActionData::get_by_tracker_id(&upload_id).await {
+                let _ = action.update_status(ActionStatus::Completed, Some(format!("Upload completed successfully ({} bytes)", upload.bytes_received))).await;
+                let _ = action.update_progress(100).await;
+            }
+            if upload.destination().file_name().and_then(|n| n.to_str()) == Some("server-icon.png") {
+                broadcast::broadcast(BroadcastMessage::ServerUpdate {
+                    server: server.clone(),
+                });
+            }
+            Ok(HttpResponse::Ok().json(json!({
+                "status": "complete",
+                "bytesUploaded": upload.bytes_received
+            })))
+        }
+        ChunkOutcome::Received | ChunkOutcome::Duplicate => {
+            if let Some(sender) = &progress_sender {
+                let _ = sender
+                    .send(Event::from(Data::new(
+                        json!({
+                            "status": "progress",
+                            "bytesUploaded": upload.bytes_received,
+                            "chunk": upload.next_chunk,
+                            "totalChunks": upload.total_chunks
+                        })
+                        .to_string(),
+                    )))
+                    .await;
+            }
+            if let Ok(Some(action)) = ActionData::get_by_tracker_id(&upload_id).await {
+                let _ = action.update_progress(upload.progress()).await;
+            }
+            let response = json!({
+                "status": "progress",
+                "next_chunk": upload.next_chunk,
+                "bytes_received": upload.bytes_received
+            });
+            get_chunked_uploads().lock().await.insert(upload_id, upload);
+            Ok(HttpResponse::Ok().json(response))
+        }
+    }
+}
+
+#[get("/upload/{upload_id}/status")]
+pub async fn chunked_upload_status(params: web::Path<(String, String)>, req: HttpRequest) -> Result<impl Responder> {
+    let (server_id, upload_id) = params.into_inner();
+    let server_id = decode_single(server_id.as_str())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow::anyhow!("User ID not found"))?;
+    let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow::anyhow!("Server not found"))?;
+
+    let uploads = get_chunked_uploads().lock().await;
+    match uploads.get(&upload_id) {
+        Some(upload) if upload.server_id == server.id => Ok(HttpResponse::Ok().json(upload)),
+        _ => Ok(HttpResponse::NotFound().json(json!({
+            "error": "Upload not found or already completed"
+        }))),
+    }
+}
+
 #[get("/upload/progress/{upload_id}")]
 pub async fn upload_progress(params: web::Path<(String, String)>) -> impl Responder {
     let (_, upload_id) = params.into_inner();
@@ -367,6 +566,7 @@ pub async fn upload_url(server_id: web::Path<String>, query: web::Query<HashMap<
     let server_id = decode_single(server_id.as_str())?;
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow::anyhow!("User ID not found"))?;
+    permission_guard::require(&user, ServerRight::UploadFiles)?;
 
     // get server from server id
     let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow::anyhow!("Server not found"))?;
@@ -574,44 +774,142 @@ async fn download(server_id: web::Path<String>, req: HttpRequest, query: web::Qu
     Ok(HttpResponse::Ok().content_type("application/zip").insert_header(ContentDisposition::attachment(filename)).streaming(ReaderStream::new(r)))
 }
 
-#[post("/copy")]
-pub async fn copy_entry(server_id: web::Path<String>, body: web::Json<CopyMoveRequest>, req: HttpRequest) -> Result<impl Responder> {
+/// GET /api/server/:id/files/download-folder?path= - Download a directory as a zip archive.
+///
+/// The archive is streamed while it is built. Pass `tracker_id` to be able to stop the download
+/// through the archive cancel endpoint.
+#[get("/files/download-folder")]
+async fn download_folder(server_id: web::Path<String>, req: HttpRequest, query: web::Query<HashMap<String, String>>) -> Result<impl Responder> {
     let server_id = decode_single(server_id.as_str())?;
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow::anyhow!("User ID not found"))?;
 
     let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow::anyhow!("Server not found"))?;
-    let base_path = server.get_directory_path();
+    let folder = match ensure_path_within(&server.get_directory_path(), query.get("path").map(String::as_str).unwrap_or("")) {
+        Ok(folder) if folder.is_dir() => folder,
+        Ok(_) => return Ok(HttpResponse::BadRequest().json(json!({"error": "Path is not a directory"}))),
+        Err(e) => return Ok(HttpResponse::BadRequest().json(json!({"error": e.to_string()}))),
+    };
+    let filename = format!("{}.zip", folder.file_name().unwrap_or(OsStr::new(&server.directory)).to_string_lossy());
 
-    for entry_path in &body.entries {
-        let source = base_path.join(entry_path);
-        let dest = base_path.join(&body.path).join(source.file_name().ok_or(anyhow::anyhow!("Invalid source path"))?);
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    let tracker_id = query.get("tracker_id").cloned();
+    if let Some(tracker_id) = &tracker_id {
+        get_archive_cancel_flags().lock().await.insert(tracker_id.clone(), cancel_flag.clone());
+    }
 
-        if source.is_dir() {
-            copy_dir_all(&source, &dest)?;
-        } else {
-            if let Some(parent) = dest.parent() {
-                std::fs::create_dir_all(parent)?;
-            }
-            std::fs::copy(&source, &dest)?;
+    let (w, r) = duplex(262144); // 256KB buffer
+    tokio::spawn(async move {
+        if let Err(e) = zip_folder(&folder, w, &cancel_flag).await {
+            warn!("Folder download of {} stopped: {}", folder.display(), e);
+        }
+        if let Some(tracker_id) = tracker_id {
+            get_archive_cancel_flags().lock().await.remove(&tracker_id);
+        }
+    });
+
+    Ok(HttpResponse::Ok().content_type("application/zip").insert_header(ContentDisposition::attachment(filename)).streaming(ReaderStream::new(r)))
+}
+
+/// GET /api/server/:id/files/checksum?path=&algo=sha256 - Checksum of a file, for verifying
+/// uploads and downloads. Supports sha1, sha256 (the default) and md5.
+#[get("/files/checksum")]
+async fn get_file_checksum(server_id: web::Path<String>, req: HttpRequest, query: web::Query<HashMap<String, String>>) -> Result<impl Responder> {
+    let server_id = decode_single(server_id.as_str())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow::anyhow!("User ID not found"))?;
+
+    let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow::anyhow!("Server not found"))?;
+    let path = query.get("path").ok_or(anyhow::anyhow!("Missing 'path' query parameter"))?;
+    let algorithm = match ChecksumAlgorithm::parse(query.get("algo").map(String::as_str).unwrap_or("sha256")) {
+        Ok(algorithm) => algorithm,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "error": e.to_string()
+            })));
         }
+    };
+    match file_checksum(&server.get_directory_path(), path, algorithm).await {
+        Ok(checksum) => Ok(HttpResponse::Ok().json(json!({
+            "path": path,
+            "algorithm": algorithm.name(),
+            "checksum": checksum,
+        }))),
+        Err(e) if is_not_found(&e) => Ok(HttpResponse::NotFound().json(json!({
+            "error": e.to_string()
+        }))),
+        Err(e) => Ok(HttpResponse::BadRequest().json(json!({
+            "error": e.to_string()
+        }))),
     }
+}
 
-    Ok(HttpResponse::Ok().json(json!({"status": "success"})))
+/// PUT /api/server/:id/files/content?path= - Save the request body as a text file, creating it
+/// and any missing folders. Used by the config editor; files over 2 MB are refused.
+#[put("/files/content")]
+async fn save_file_content(server_id: web::Path<String>, req: HttpRequest, query: web::Query<HashMap<String, String>>, mut payload: web::Payload) -> Result<impl Responder> {
+    let server_id = decode_single(server_id.as_str())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow::anyhow!("User ID not found"))?;
+    permission_guard::require(&user, ServerRight::ModifyFiles)?;
+
+    let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow::anyhow!("Server not found"))?;
+    let path = query.get("path").ok_or(anyhow::anyhow!("Missing 'path' query parameter"))?;
+    let base_path = server.get_directory_path();
+    if !ensure_path_within(&base_path, path).is_ok_and(|filepath| filepath.exists()) {
+        permission_guard::require(&user, ServerRight::CreateFiles)?;
+    }
+
+    let mut body = web::BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|e| anyhow::anyhow!("Failed to read request body: {}", e))?;
+        if body.len() + chunk.len() > MAX_WRITE_BYTES {
+            return Ok(HttpResponse::PayloadTooLarge().json(json!({
+                "error": format!("File is larger than the {} MB limit", MAX_WRITE_BYTES / 1024 / 1024)
+            })));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let content = match String::from_utf8(body.to_vec()) {
+        Ok(content) => content,
+        Err(_) => {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "error": "File contents must be UTF-8 text"
+            })));
+        }
+    };
+
+    let path_clone = path.clone();
+    match tokio::task::spawn_blocking(move || write_file(&base_path, &path_clone, &content)).await.map_err(|e| anyhow::anyhow!("Save failed: {}", e))? {
+        Ok(written) => Ok(HttpResponse::Ok().json(json!({
+            "path": path,
+            "size": written.size,
+            "last_modified": written.last_modified,
+        }))),
+        Err(e) => Ok(HttpResponse::BadRequest().json(json!({
+            "error": e.to_string()
+        }))),
+    }
 }
 
-fn copy_dir_all(src: &std::path::Path, dst: &std::path::Path) -> std::io::Result<()> {
-    std::fs::create_dir_all(dst)?;
-    for entry in std::fs::read_dir(src)? {
-        let entry = entry?;
-        let ty = entry.file_type()?;
-        if ty.is_dir() {
-            copy_dir_all(&entry.path(), &dst.join(entry.file_name()))?;
-        } else {
-            std::fs::copy(entry.path(), dst.join(entry.file_name()))?;
+#[post("/copy")]
+pub async fn copy_entry(server_id: web::Path<String>, body: web::Json<CopyMoveRequest>, req: HttpRequest) -> Result<impl Responder> {
+    let server_id = decode_single(server_id.as_str())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow::anyhow!("User ID not found"))?;
+    permission_guard::require(&user, ServerRight::CreateFiles)?;
+
+    let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow::anyhow!("Server not found"))?;
+    let base_path = server.get_directory_path();
+
+    let mut skipped = Vec::new();
+    for entry_path in &body.entries {
+        if copy_move::copy_entry(&base_path, entry_path, &body.path, body.conflict)?.is_none() {
+            skipped.push(entry_path);
         }
     }
-    Ok(())
+
+    Ok(HttpResponse::Ok().json(json!({"status": "success", "skipped": skipped})))
 }
 
 #[post("/move")]
@@ -619,21 +917,19 @@ pub async fn move_entry(server_id: web::Path<String>, body: web::Json<CopyMoveRe
     let server_id = decode_single(server_id.as_str())?;
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow::anyhow!("User ID not found"))?;
+    permission_guard::require(&user, ServerRight::ModifyFiles)?;
 
     let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow::anyhow!("Server not found"))?;
     let base_path = server.get_directory_path();
 
+    let mut skipped = Vec::new();
     for entry_path in &body.entries {
-        let source = base_path.join(entry_path);
-        let dest = base_path.join(&body.path).join(source.file_name().ok_or(anyhow::anyhow!("Invalid source path"))?);
-
-        if let Some(parent) = dest.parent() {
-            std::fs::create_dir_all(parent)?;
+        if copy_move::move_entry(&base_path, entry_path, &body.path, body.conflict)?.is_none() {
+            skipped.push(entry_path);
         }
-        std::fs::rename(&source, &dest)?;
     }
 
-    Ok(HttpResponse::Ok().json(json!({"status": "success"})))
+    Ok(HttpResponse::Ok().json(json!({"status": "success", "skipped": skipped})))
 }
 
 #[post("/rename")]
@@ -641,6 +937,7 @@ pub async fn rename_entry(server_id: web::Path<String>, body: web::Json<RenameRe
     let server_id = decode_single(server_id.as_str())?;
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow::anyhow!("User ID not found"))?;
+    permission_guard::require(&user, ServerRight::ModifyFiles)?;
 
     let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow::anyhow!("Server not found"))?;
     let base_path = server.get_directory_path();
@@ -661,6 +958,7 @@ pub async fn delete_entry(server_id: web::Path<String>, body: web::Json<DeleteRe
     let server_id = decode_single(server_id.as_str())?;
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow::anyhow!("User ID not found"))?;
+    permission_guard::require(&user, ServerRight::DeleteFiles)?;
 
     let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow::anyhow!("Server not found"))?;
     let base_path = server.get_directory_path();
@@ -680,6 +978,7 @@ pub async fn delete_entry(server_id: web::Path<String>, body: web::Json<DeleteRe
         } else {
             std::fs::remove_file(&full_path)?;
         }
+        audit::record(user_id, AuditAction::FileDelete, format!("{}:{}", encode_single(server.id), path)).await;
     }
 
     // If the server icon was deleted, broadcast an update
@@ -697,6 +996,7 @@ pub async fn create_entry(server_id: web::Path<String>, body: web::Json<NewEntry
     let server_id = decode_single(server_id.as_str())?;
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow::anyhow!("User ID not found"))?;
+    permission_guard::require(&user, ServerRight::CreateFiles)?;
 
     let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow::anyhow!("Server not found"))?;
     let base_path = server.get_directory_path();
@@ -720,54 +1020,32 @@ pub async fn search(server_id: web::Path<String>, query: web::Query<HashMap<Stri
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow::anyhow!("User ID not found"))?;
 
-    let search_query = query.get("q").ok_or(anyhow::anyhow!("Search query parameter 'q' is required"))?.clone();
-    let filename_only = query.get("filename_only").unwrap_or(&"false".to_string()) == "true";
+    // `q` is the original name of the query parameter
+    let mode = match (query.get("glob"), query.get("query").or(query.get("q"))) {
+        (Some(pattern), _) => SearchMode::glob(pattern)?,
+        (None, Some(search_query)) => {
+            let filename_only = query.get("filename_only").unwrap_or(&"false".to_string()) == "true";
+            SearchMode::substring(search_query, filename_only)
+        }
+        (None, None) => return Err(anyhow::anyhow!("Either the 'query' or the 'glob' parameter is required").into()),
+    };
+    let limit = query.get("limit").and_then(|limit| limit.parse().ok()).unwrap_or(MAX_SEARCH_RESULTS).min(MAX_SEARCH_RESULTS);
 
     let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow::anyhow!("Server not found"))?;
     let base_path = server.get_directory_path();
+    let root = ensure_path_within(&base_path, query.get("path").map(String::as_str).unwrap_or(""))?;
 
-    let mut results = Vec::new();
-    search_directory(&base_path, &search_query, filename_only, &mut results)?;
+    let results = tokio::task::spawn_blocking(move || search_files(&base_path, &root, &mode, limit)).await.map_err(|e| anyhow::anyhow!("Search failed: {}", e))??;
 
     Ok(HttpResponse::Ok().json(results))
 }
 
-fn search_directory(dir: &std::path::Path, query: &str, filename_only: bool, results: &mut Vec<serde_json::Value>) -> std::io::Result<()> {
-    for entry in std::fs::read_dir(dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        let filename = entry.file_name().to_string_lossy().to_lowercase();
-
-        let matches = if filename_only {
-            filename.contains(&query.to_lowercase())
-        } else {
-            filename.contains(&query.to_lowercase()) || path.to_string_lossy().to_lowercase().contains(&query.to_lowercase())
-        };
-
-        if matches {
-            let metadata = path.metadata()?;
-            results.push(json!({
-                "filename": entry.file_name().to_string_lossy(),
-                "path": path.to_string_lossy(),
-                "size": metadata.len(),
-                "ctime": metadata.created().ok().and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).map(|d| d.as_secs()).unwrap_or(0),
-                "mtime": metadata.modified().ok().and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).map(|d| d.as_secs()).unwrap_or(0),
-            }));
-        }
-
-        if path.is_dir() {
-            search_directory(&path, query, filename_only, results)?;
-        }
-    }
-    Ok(())
-}
-
-
 #[post("/archive")]
 pub async fn archive_files(server_id: web::Path<String>, body: web::Json<ArchiveRequest>, req: HttpRequest) -> Result<impl Responder> {
     let server_id = decode_single(server_id.as_str())?;
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow::anyhow!("User ID not found"))?;
+    permission_guard::require(&user, ServerRight::CreateFiles)?;
 
     let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow::anyhow!("Server not found"))?;
     let base_path = server.get_directory_path();
@@ -885,6 +1163,31 @@ pub async fn get_file_contents(server_id: web::Path<String>, query: web::Query<H
     let content = tokio::fs::read_to_string(filepath).await?;
     Ok(HttpResponse::Ok().content_type(ContentType::plaintext()).body(content))
 }
+#[get("/preview")]
+pub async fn preview_file(server_id: web::Path<String>, query: web::Query<HashMap<String, String>>, req: HttpRequest) -> Result<impl Responder> {
+    let server_id = decode_single(server_id.as_str())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow::anyhow!("User ID not found"))?;
+
+    let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow::anyhow!("Server not found"))?;
+    let base_path = server.get_directory_path();
+    let path = query.get("path").ok_or(anyhow::anyhow!("Missing 'path' query parameter"))?.clone();
+    let max_bytes = query.get("max_kb").and_then(|kb| kb.parse::<usize>().ok()).map(|kb| kb * 1024).unwrap_or(DEFAULT_PREVIEW_BYTES).min(MAX_PREVIEW_BYTES);
+
+    match tokio::task::spawn_blocking(move || read_preview(&base_path, &path, max_bytes)).await.map_err(|e| anyhow::anyhow!("Preview failed: {}", e))? {
+        Ok(Some(preview)) => Ok(HttpResponse::Ok().json(preview)),
+        Ok(None) => Ok(HttpResponse::UnsupportedMediaType().json(json!({
+            "error": "File is not previewable because it appears to be binary"
+        }))),
+        Err(e) if is_not_found(&e) => Ok(HttpResponse::NotFound().json(json!({
+            "error": e.to_string()
+        }))),
+        Err(e) => Ok(HttpResponse::BadRequest().json(json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
 #[post("/contents")]
 pub async fn set_file_contents(
     server_id: web::Path<String>,
@@ -895,6 +1198,7 @@ pub async fn set_file_contents(
     let server_id = decode_single(server_id.as_str())?;
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow::anyhow!("User ID not found"))?;
+    permission_guard::require(&user, ServerRight::ModifyFiles)?;
 
     let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow::anyhow!("Server not found"))?;
     let base_path = server.get_directory_path();
@@ -906,27 +1210,49 @@ pub async fn set_file_contents(
     Ok(HttpResponse::Ok().json(json!({"status": "success"})))
 }
 
+#[post("/permissions")]
+pub async fn set_file_permissions(server_id: web::Path<String>, body: web::Json<PermissionsRequest>, req: HttpRequest) -> Result<impl Responder> {
+    let server_id = decode_single(server_id.as_str())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow::anyhow!("User ID not found"))?;
+    permission_guard::require(&user, ServerRight::ModifyFiles)?;
+
+    let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow::anyhow!("Server not found"))?;
+    let applied = parse_mode(&body.mode).and_then(|mode| Ok((set_mode(&server.get_directory_path(), &body.path, mode)?, mode)));
+    match applied {
+        Ok((true, mode)) => Ok(HttpResponse::Ok().json(json!({
+            "status": "success",
+            "mode": format!("{:o}", mode)
+        }))),
+        Ok((false, _)) => Ok(HttpResponse::Ok().json(json!({
+            "status": "unchanged",
+            "message": "File permissions can only be changed when the panel runs on Linux or macOS"
+        }))),
+        Err(e) if is_not_found(&e) => Ok(HttpResponse::NotFound().json(json!({
+            "error": e.to_string()
+        }))),
+        Err(e) => Ok(HttpResponse::BadRequest().json(json!({
+            "error": e.to_string()
+        }))),
+    }
+}
+
 #[post("/extract")]
 pub async fn extract_archive(server_id: web::Path<String>, query: web::Query<HashMap<String, String>>, req: HttpRequest) -> Result<impl Responder> {
     let server_id = decode_single(server_id.as_str())?;
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow::anyhow!("User ID not found"))?;
+    permission_guard::require(&user, ServerRight::CreateFiles)?;
 
     let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow::anyhow!("Server not found"))?;
     let base_path = server.get_directory_path();
 
-    // Trim leading slashes and get paths relative to server directory
+    // Both paths are relative to the server directory and may not leave it
     let archive_param = query.get("archive").ok_or(anyhow::anyhow!("Missing 'archive' query parameter"))?;
     let output_param = query.get("directory").ok_or(anyhow::anyhow!("Missing 'directory' query parameter"))?;
     let tracker_id = query.get("tracker").ok_or(anyhow::anyhow!("Missing 'tracker' query parameter"))?;
 
-    let archive_path = base_path.join(archive_param.trim_start_matches('/').trim_start_matches('\\'));
-    let output_path = base_path.join(output_param.trim_start_matches('/').trim_start_matches('\\'));
-
-    // Validate archive exists
-    if !archive_path.exists() || !archive_path.is_file() {
-        return Err(anyhow::anyhow!("Archive file not found").into());
-    }
+    let (archive_path, output_path) = resolve_extract_paths(&base_path, archive_param, output_param)?;
 
     // Create action tracking entry
     let action_details = json!({
@@ -981,10 +1307,7 @@ pub async fn extract_archive(server_id: web::Path<String>, query: web::Query<Has
                     if let Err(e) = result {
                         error!("Failed to extract archive: {}", e);
                         let _ = tracker
-                            .send(Event::from(sse::Data::new(format!(
-                                "{{ \"progress\": 0, \"status\": \"error\", \"error\": \"{}\" }}",
-                                e
-                            ))))
+                            .send(Event::from(sse::Data::new(json!({"progress": 0, "status": "error", "error": e.to_string()}).to_string())))
                             .await;
                     }
 
@@ -1046,6 +1369,7 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .service(upload_file)
             .service(upload_progress)
             .service(cancel_upload)
+            .service(chunked_upload_status)
             .service(upload_url)
             .service(download)
             .service(copy_entry)
@@ -1058,7 +1382,9 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .service(archive_status)
             .service(cancel_archive)
             .service(get_file_contents)
+            .service(preview_file)
             .service(set_file_contents)
+            .service(set_file_permissions)
             .service(extract_archive)
             .service(extract_status)
             .service(cancel_extract)
@@ -1067,5 +1393,8 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
                     "error": "API endpoint not found".to_string(),
                 }))
             })),
-    );
+    )
+    .service(download_folder)
+    .service(get_file_checksum)
+    .service(save_file_content);
 }
@@ -0,0 +1,185 @@
+use crate::server::filesystem::filesystem_data::is_special_file;
+use anyhow::{anyhow, Result};
+use glob::{MatchOptions, Pattern};
+use serde::Serialize;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Most results a search returns unless the request asks for fewer
+pub const MAX_SEARCH_RESULTS: usize = 500;
+
+/// How entries are matched
+pub enum SearchMode {
+    /// Case-insensitive substring of the filename, or of the relative path when `filename_only` is false
+    Substring { query: String, filename_only: bool },
+    /// Glob over the path relative to the search root, e.g. `**/*.json` or `config/*.toml`
+    Glob(Pattern),
+}
+
+impl SearchMode {
+    pub fn substring(query: &str, filename_only: bool) -> Self {
+        Self::Substring { query: query.to_lowercase(), filename_only }
+    }
+
+    pub fn glob(pattern: &str) -> Result<Self> {
+        Pattern::new(pattern).map(Self::Glob).map_err(|e| anyhow!("Invalid glob pattern: {}", e))
+    }
+
+    fn matches(&self, filename: &str, relative_path: &str) -> bool {
+        match self {
+            Self::Substring { query, filename_only: true } => filename.to_lowercase().contains(query),
+            Self::Substring { query, filename_only: false } => relative_path.to_lowercase().contains(query),
+            Self::Glob(pattern) => pattern.matches_with(
+                relative_path,
+                MatchOptions {
+                    case_sensitive: false,
+                    require_literal_separator: true,
+                    require_literal_leading_dot: false,
+                },
+            ),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct SearchResult {
+    pub filename: String,
+    /// Path relative to the server directory, with a leading `/` like entries from `get_files`
+    pub path: String,
+    pub size: u64,
+    pub is_dir: bool,
+    /// Creation time (seconds since epoch)
+    pub ctime: u64,
+    /// Modification time (seconds since epoch)
+    pub mtime: u64,
+}
+
+/// Recursively search `root` for entries matching `mode`, returning at most `limit` results.
+///
+/// Paths are reported relative to `base`, the server directory. Symlinks and special files
+/// are skipped and never followed, so the search cannot leave the server directory.
+pub fn search_files(base: &Path, root: &Path, mode: &SearchMode, limit: usize) -> Result<Vec<SearchResult>> {
+    let mut results = Vec::new();
+    for entry in WalkDir::new(root).min_depth(1).follow_links(false).sort_by_file_name() {
+        if results.len() >= limit {
+            break;
+        }
+        let Ok(entry) = entry else { continue };
+        if entry.path_is_symlink() || is_special_file(entry.path()) {
+            continue;
+        }
+
+        let filename = entry.file_name().to_string_lossy().to_string();
+        let search_path = relative_path(root, entry.path());
+        if !mode.matches(&filename, &search_path) {
+            continue;
+        }
+
+        let Ok(metadata) = entry.metadata() else { continue };
+        let seconds = |time: std::io::Result<std::time::SystemTime>| {
+            time.ok().and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).map(|d| d.as_secs()).unwrap_or(0)
+        };
+        results.push(SearchResult {
+            filename,
+            path: format!("/{}", relative_path(base, entry.path())),
+            size: metadata.len(),
+            is_dir: metadata.is_dir(),
+            ctime: seconds(metadata.created()),
+            mtime: seconds(metadata.modified()),
+        });
+    }
+    Ok(results)
+}
+
+/// `path` relative to `root` with `/` separators on every platform
+fn relative_path(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actix_util::path_sanitize::ensure_path_within;
+    use crate::test_util::temp_dir;
+    use std::path::PathBuf;
+
+    /// A server directory with a few configs, a world, and a sibling directory outside of it
+    fn fixture(name: &str) -> (PathBuf, PathBuf) {
+        let dir = temp_dir(&format!("fs-search-{}", name));
+        let server = dir.join("server");
+        std::fs::create_dir_all(server.join("config/fabric")).unwrap();
+        std::fs::create_dir_all(server.join("world/region")).unwrap();
+        std::fs::create_dir_all(dir.join("outside")).unwrap();
+        std::fs::write(server.join("server.properties"), "server-port=25565\n").unwrap();
+        std::fs::write(server.join("config/sodium.json"), "{}").unwrap();
+        std::fs::write(server.join("config/fabric/indigo-renderer.properties"), "").unwrap();
+        std::fs::write(server.join("world/region/r.0.0.mca"), [0u8; 16]).unwrap();
+        std::fs::write(dir.join("outside/secret.properties"), "password=hunter2").unwrap();
+        (dir, server)
+    }
+
+    fn paths(results: &[SearchResult]) -> Vec<&str> {
+        results.iter().map(|r| r.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_substring_search() {
+        let (dir, server) = fixture("substring");
+
+        let results = search_files(&server, &server, &SearchMode::substring("PROPERTIES", true), MAX_SEARCH_RESULTS).unwrap();
+        assert_eq!(paths(&results), vec!["/config/fabric/indigo-renderer.properties", "/server.properties"]);
+
+        let results = search_files(&server, &server, &SearchMode::substring("config/fab", false), MAX_SEARCH_RESULTS).unwrap();
+        assert_eq!(paths(&results), vec!["/config/fabric", "/config/fabric/indigo-renderer.properties"]);
+        assert!(results[0].is_dir);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_glob_search() {
+        let (dir, server) = fixture("glob");
+
+        let results = search_files(&server, &server, &SearchMode::glob("**/*.json").unwrap(), MAX_SEARCH_RESULTS).unwrap();
+        assert_eq!(paths(&results), vec!["/config/sodium.json"]);
+
+        // Without `**` the pattern does not cross directories
+        let results = search_files(&server, &server, &SearchMode::glob("*.properties").unwrap(), MAX_SEARCH_RESULTS).unwrap();
+        assert_eq!(paths(&results), vec!["/server.properties"]);
+
+        // Patterns are relative to the directory being searched, paths to the server directory
+        let world = ensure_path_within(&server, "/world").unwrap();
+        let results = search_files(&server, &world, &SearchMode::glob("region/*.mca").unwrap(), MAX_SEARCH_RESULTS).unwrap();
+        assert_eq!(paths(&results), vec!["/world/region/r.0.0.mca"]);
+
+        assert!(SearchMode::glob("[unclosed").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_result_limit() {
+        let (dir, server) = fixture("limit");
+
+        let results = search_files(&server, &server, &SearchMode::glob("**/*").unwrap(), 3).unwrap();
+        assert_eq!(results.len(), 3);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_search_stays_in_sandbox() {
+        let (dir, server) = fixture("sandbox");
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.join("outside"), server.join("config/escape")).unwrap();
+
+            let results = search_files(&server, &server, &SearchMode::substring("secret", true), MAX_SEARCH_RESULTS).unwrap();
+            assert!(results.is_empty());
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+use anyhow::{anyhow, Result};
+use archflow::compress::tokio::archive::ZipArchive;
+use archflow::compress::FileOptions;
+use archflow::compression::CompressionMethod;
+use archflow::types::FileDateTime;
+use log::debug;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::fs::File;
+use tokio::io::AsyncWrite;
+
+/// Write `folder` to `writer` as a zip archive, with every entry under the folder's name.
+///
+/// Entries are streamed as they are read, so nothing is staged on disk. Symlinks are skipped
+/// so the archive can't pick up files from outside the server directory. Stops with an error,
+/// leaving the archive unfinished, once `cancelled` is set or the reader goes away.
+pub async fn zip_folder<W: AsyncWrite + Unpin + Send>(folder: &Path, writer: W, cancelled: &AtomicBool) -> Result<()> {
+    let name = folder.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_else(|| "files".to_string());
+    let mut archive = ZipArchive::new_streamable(writer);
+    let options = FileOptions::default().last_modified_time(FileDateTime::Now).compression_method(CompressionMethod::Store());
+
+    for entry in walkdir::WalkDir::new(folder).sort_by_file_name() {
+        if cancelled.load(Ordering::Relaxed) {
+            return Err(anyhow!("Folder download cancelled"));
+        }
+
+        let entry = entry?;
+        if entry.path_is_symlink() {
+            debug!("Skipping symlink in folder download: {}", entry.path().display());
+            continue;
+        }
+
+        let relative_path = entry.path().strip_prefix(folder)?;
+        let entry_name = Path::new(&name).join(relative_path).to_string_lossy().replace('\\', "/");
+        if entry.file_type().is_dir() {
+            archive.append_directory(&entry_name, &options).await.map_err(|e| anyhow!("Failed to add directory to zip archive: {}", e))?;
+        } else {
+            let mut file = File::open(entry.path()).await?;
+            archive.append(&entry_name, &options, &mut file).await.map_err(|e| anyhow!("Failed to add file to zip archive: {}", e))?;
+        }
+    }
+
+    archive.finalize().await.map_err(|e| anyhow!("Failed to finalize zip archive: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::io::{Cursor, Read};
+    use std::path::PathBuf;
+    use tokio::io::AsyncReadExt;
+
+    fn fixture(name: &str) -> PathBuf {
+        let dir = temp_dir(&format!("fs-folder-download-{}", name));
+        std::fs::create_dir_all(dir.join("server/world/region")).unwrap();
+        std::fs::write(dir.join("server/world/level.dat"), b"level data").unwrap();
+        std::fs::write(dir.join("server/world/region/r.0.0.mca"), b"region data").unwrap();
+        std::fs::write(dir.join("secret.txt"), b"outside").unwrap();
+        dir
+    }
+
+    /// Zip `folder` through a pipe, like the download endpoint, and return the bytes received
+    async fn download(folder: &Path, cancelled: &AtomicBool) -> (Result<()>, Vec<u8>) {
+        let (writer, mut reader) = tokio::io::duplex(4096);
+        let mut bytes = Vec::new();
+        let (result, _) = tokio::join!(zip_folder(folder, writer, cancelled), reader.read_to_end(&mut bytes));
+        (result, bytes)
+    }
+
+    #[tokio::test]
+    async fn test_download_folder_as_zip() {
+        let dir = fixture("zip");
+        let world = dir.join("server/world");
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.join("secret.txt"), world.join("secret.txt")).unwrap();
+
+        let (result, bytes) = download(&world, &AtomicBool::new(false)).await;
+        result.unwrap();
+
+        let mut zip = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let mut names: Vec<String> = zip.file_names().map(|name| name.trim_end_matches('/').to_string()).collect();
+        names.sort();
+        assert_eq!(names, vec!["world", "world/level.dat", "world/region", "world/region/r.0.0.mca"]);
+
+        let mut contents = String::new();
+        zip.by_name("world/region/r.0.0.mca").unwrap().read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "region data");
+
+        let (result, bytes) = download(&world, &AtomicBool::new(true)).await;
+        assert!(result.is_err());
+        assert!(zip::ZipArchive::new(Cursor::new(bytes)).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,7 +1,15 @@
 mod filesystem_data;
 mod filesystem_endpoint;
+mod filesystem_search;
+mod file_preview;
+mod file_permissions;
 mod download_parameters;
 mod archive_wrapper;
 mod extract_wrapper;
+mod chunked_upload;
+mod file_checksum;
+mod file_write;
+mod folder_download;
+mod copy_move;
 
 pub use filesystem_endpoint::configure;
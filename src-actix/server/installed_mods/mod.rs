@@ -1,5 +1,7 @@
 pub mod installed_mod_db;
 mod installed_mods_watcher;
 pub mod mod_data;
+pub mod mod_install;
+pub mod mod_updates;
 
 pub use installed_mod_db::initialize;
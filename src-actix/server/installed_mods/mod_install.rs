@@ -0,0 +1,441 @@
+use crate::actions::actions_data::ActionData;
+use crate::server::server_type::ServerType;
+use anyhow::{anyhow, Result};
+use futures::StreamExt;
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+/// A mod file picked for a server, ready to be downloaded
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedModFile {
+    pub url: String,
+    pub filename: String,
+    /// Version number shown to users, e.g. `mc1.21.1-0.6.3`
+    pub version: String,
+    pub modrinth_id: Option<String>,
+    pub curseforge_id: Option<String>,
+    pub icon: Option<String>,
+    pub size: u64,
+    /// Expected SHA-1 of the file, checked after downloading when known
+    #[serde(skip)]
+    pub sha1: Option<String>,
+}
+
+/// Loader names mod platforms use for mods that run on `server_type`, best match first.
+///
+/// Empty for server types where any loader may apply, like custom servers.
+pub fn loader_names(server_type: &ServerType) -> &'static [&'static str] {
+    match server_type {
+        ServerType::Fabric => &["fabric"],
+        // Quilt loads most Fabric mods as well
+        ServerType::Quilt => &["quilt", "fabric"],
+        ServerType::Forge => &["forge"],
+        ServerType::NeoForge => &["neoforge"],
+        ServerType::Vanilla | ServerType::Custom => &[],
+    }
+}
+
+/// Release channels in order of preference
+fn channel_rank(channel: &str) -> u8 {
+    match channel {
+        "release" => 2,
+        "beta" => 1,
+        _ => 0,
+    }
+}
+
+/// The best version for a server: it must support `minecraft_version` and one of `loaders`,
+/// and releases are preferred over betas and alphas, then newer over older.
+pub fn pick_modrinth_version<'a>(
+    versions: &'a [modrinth::models::Version],
+    minecraft_version: &str,
+    loaders: &[&str],
+) -> Option<&'a modrinth::models::Version> {
+    versions
+        .iter()
+        .filter(|version| version.game_versions.iter().any(|v| v == minecraft_version))
+        .filter(|version| loaders.is_empty() || version.loaders.iter().any(|loader| loaders.contains(&loader.as_str())))
+        .filter(|version| !version.files.is_empty())
+        .max_by(|a, b| (channel_rank(&a.version_type), &a.date_published).cmp(&(channel_rank(&b.version_type), &b.date_published)))
+}
+
+/// Find the file to install for a Modrinth project.
+///
+/// When `version_id` is given that exact version is used, otherwise the best version for the
+/// server is picked with [`pick_modrinth_version`].
+pub async fn resolve_modrinth_file(
+    client: &modrinth::ModrinthClient,
+    project_id: &str,
+    version_id: Option<&str>,
+    minecraft_version: &str,
+    loaders: &[&str],
+) -> Result<ResolvedModFile> {
+    let project = client.get_project(project_id).await?;
+    let version = match version_id {
+        Some(version_id) => {
+            let version = client.get_version(version_id).await?;
+            if version.project_id != project.id {
+                return Err(anyhow!("Version {} does not belong to project {}", version_id, project_id));
+            }
+            version
+        }
+        None => {
+            let versions = client.get_project_versions(&project.id).await?;
+            pick_modrinth_version(&versions, minecraft_version, loaders).cloned().ok_or(anyhow!(
+                "{} has no version for Minecraft {} with {}",
+                project.title,
+                minecraft_version,
+                if loaders.is_empty() { "any loader".to_string() } else { loaders.join(" or ") }
+            ))?
+        }
+    };
+
+    let file = version.files.iter().find(|file| file.primary).or(version.files.first()).ok_or(anyhow!("Version {} has no files", version.id))?;
+    Ok(ResolvedModFile {
+        url: file.url.clone(),
+        filename: file.filename.clone(),
+        version: version.version_number.clone(),
+        modrinth_id: Some(project.id.clone()),
+        curseforge_id: None,
+        icon: project.icon_url.clone(),
+        size: file.size,
+        sha1: Some(file.hashes.sha1.clone()).filter(|hash| !hash.is_empty()),
+    })
+}
+
+/// Channel name of a CurseForge release type
+fn curseforge_channel(release_type: u32) -> &'static str {
+    match release_type {
+        1 => "release",
+        2 => "beta",
+        _ => "alpha",
+    }
+}
+
+/// The best file for a server, chosen like [`pick_modrinth_version`].
+///
+/// CurseForge lists loaders among a file's game versions, capitalized, e.g. `["1.21.1", "Fabric"]`.
+pub fn pick_curseforge_file<'a>(files: &'a [curseforge::File], minecraft_version: &str, loaders: &[&str]) -> Option<&'a curseforge::File> {
+    files
+        .iter()
+        .filter(|file| !file.is_server_pack)
+        .filter(|file| file.game_versions.iter().any(|v| v == minecraft_version))
+        .filter(|file| loaders.is_empty() || file.game_versions.iter().any(|v| loaders.iter().any(|loader| v.eq_ignore_ascii_case(loader))))
+        .max_by(|a, b| {
+            (channel_rank(curseforge_channel(a.release_type)), &a.file_date).cmp(&(channel_rank(curseforge_channel(b.release_type)), &b.file_date))
+        })
+}
+
+/// Find the file to install for a CurseForge mod.
+///
+/// When `file_id` is given that exact file is used, otherwise the best file for the server is
+/// picked with [`pick_curseforge_file`]. Files without a download URL, from authors who opted out
+/// of third-party distribution, are downloaded from the CDN instead.
+pub async fn resolve_curseforge_file(
+    client: &curseforge::CurseForgeClient,
+    mod_id: u32,
+    file_id: Option<u64>,
+    minecraft_version: &str,
+    loaders: &[&str],
+) -> Result<ResolvedModFile> {
+    let project = client.get_mod(mod_id).await?;
+    let file = match file_id {
+        Some(file_id) => client.get_mod_file(mod_id, file_id).await?,
+        None => {
+            let files = client.get_mod_files(mod_id).await?;
+            pick_curseforge_file(&files, minecraft_version, loaders).cloned().ok_or(anyhow!(
+                "{} has no file for Minecraft {} with {}",
+                project.name,
+                minecraft_version,
+                if loaders.is_empty() { "any loader".to_string() } else { loaders.join(" or ") }
+            ))?
+        }
+    };
+
+    Ok(ResolvedModFile {
+        url: file.resolved_download_url(),
+        filename: file.file_name.clone(),
+        version: file.display_name.clone(),
+        modrinth_id: None,
+        curseforge_id: Some(project.id.to_string()),
+        icon: project.logo.as_ref().and_then(|logo| logo.thumbnail_url.clone().or(logo.url.clone())),
+        size: file.file_length,
+        sha1: file.sha1().map(String::from),
+    })
+}
+
+/// Download `file` into `directory`, returning the path it was saved to.
+///
+/// Progress is reported to the action with `tracker_id`, if any. The file is removed again if
+/// the download fails or its hash does not match.
+pub async fn download_mod_file(file: &ResolvedModFile, directory: &Path, tracker_id: Option<&str>) -> Result<PathBuf> {
+    // Only the file name is used, so a malicious name cannot write outside of the directory
+    let filename = Path::new(&file.filename).file_name().ok_or(anyhow!("Invalid mod filename: {}", file.filename))?;
+    tokio::fs::create_dir_all(directory).await?;
+    let path = directory.join(filename);
+
+    let result = async {
+        let response = reqwest::get(&file.url).await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to download mod: HTTP {}", response.status()));
+        }
+        let total = response.content_length().filter(|length| *length > 0).unwrap_or(file.size);
+
+        let mut output = tokio::fs::File::create(&path).await?;
+        let mut hasher = Sha1::new();
+        let mut downloaded = 0u64;
+        let mut reported = -1i64;
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            hasher.update(&chunk);
+            output.write_all(&chunk).await?;
+            downloaded += chunk.len() as u64;
+
+            // Only report whole percent changes
+            if let Some(tracker_id) = tracker_id
+                && total > 0
+            {
+                let progress = (downloaded * 100 / total).min(100) as i64;
+                if progress > reported {
+                    reported = progress;
+                    if let Ok(Some(action)) = ActionData::get_by_tracker_id(tracker_id).await {
+                        let _ = action.update_progress(progress).await;
+                    }
+                }
+            }
+        }
+        output.flush().await?;
+
+        let hash: String = hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect();
+        if let Some(expected) = &file.sha1
+            && !expected.eq_ignore_ascii_case(&hash)
+        {
+            return Err(anyhow!("Downloaded file {} is corrupt: expected SHA-1 {}, got {}", file.filename, expected, hash));
+        }
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = result {
+        let _ = tokio::fs::remove_file(&path).await;
+        return Err(e);
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::actions_data::ActionType;
+    use crate::test_util::temp_dir;
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const JAR: &[u8] = b"PK\x03\x04 not really a jar";
+
+    fn sha1_hex(bytes: &[u8]) -> String {
+        Sha1::digest(bytes).iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    fn version(id: &str, game_version: &str, loader: &str, channel: &str, published: &str, file_url: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "project_id": "AANobbMI",
+            "author_id": "author",
+            "name": id,
+            "version_number": id,
+            "game_versions": [game_version],
+            "loaders": [loader],
+            "version_type": channel,
+            "date_published": published,
+            "files": [{
+                "hashes": { "sha1": sha1_hex(JAR), "sha512": "" },
+                "url": file_url,
+                "filename": format!("sodium-{}.jar", id),
+                "primary": true,
+                "size": JAR.len(),
+            }],
+        })
+    }
+
+    /// A Modrinth API serving one project with versions for several game versions and loaders
+    async fn mock_modrinth() -> MockServer {
+        let server = MockServer::start().await;
+        let file_url = format!("{}/cdn/sodium.jar", server.uri());
+        Mock::given(method("GET"))
+            .and(path("/project/sodium"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "AANobbMI",
+                "slug": "sodium",
+                "title": "Sodium",
+                "description": "Rendering engine",
+                "icon_url": "https://cdn.modrinth.com/sodium.png",
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/project/AANobbMI/version"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                version("0.6.4-beta", "1.21.1", "fabric", "beta", "2024-12-01T00:00:00Z", &file_url),
+                version("0.6.3", "1.21.1", "fabric", "release", "2024-11-01T00:00:00Z", &file_url),
+                version("0.6.3-neoforge", "1.21.1", "neoforge", "release", "2024-11-02T00:00:00Z", &file_url),
+                version("0.5.11", "1.20.1", "fabric", "release", "2024-06-01T00:00:00Z", &file_url),
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET")).and(path("/cdn/sodium.jar")).respond_with(ResponseTemplate::new(200).set_body_bytes(JAR)).mount(&server).await;
+        server
+    }
+
+    fn curseforge_file(id: u64, game_versions: &[&str], release_type: u32, date: &str, download_url: Option<String>) -> serde_json::Value {
+        json!({
+            "id": id,
+            "gameId": 432,
+            "modId": 394468,
+            "displayName": format!("sodium-{}", id),
+            "fileName": format!("sodium-{}.jar", id),
+            "releaseType": release_type,
+            "hashes": [{ "value": sha1_hex(JAR), "algo": 1 }],
+            "fileDate": date,
+            "fileLength": JAR.len(),
+            "downloadUrl": download_url,
+            "gameVersions": game_versions,
+        })
+    }
+
+    /// A CurseForge API serving one mod, whose newest release disallows third-party downloads
+    async fn mock_curseforge() -> MockServer {
+        let server = MockServer::start().await;
+        let file_url = Some(format!("{}/cdn/sodium.jar", server.uri()));
+        Mock::given(method("GET"))
+            .and(path("/v1/mods/394468"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(include_str!("../../../crates/curseforge/test_fixtures/mod.json")))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/mods/394468/files"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "data": [
+                curseforge_file(5000003, &["1.21.1", "Fabric"], 3, "2024-12-01T00:00:00.000Z", file_url.clone()),
+                curseforge_file(5000002, &["1.21.1", "Fabric", "Quilt"], 1, "2024-11-01T00:00:00.000Z", None),
+                curseforge_file(5000001, &["1.21.1", "NeoForge"], 1, "2024-11-02T00:00:00.000Z", file_url.clone()),
+                curseforge_file(5000000, &["1.20.1", "Forge"], 1, "2024-06-01T00:00:00.000Z", file_url.clone()),
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/mods/394468/files/5000001"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": curseforge_file(5000001, &["1.21.1", "NeoForge"], 1, "2024-11-02T00:00:00.000Z", file_url)
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET")).and(path("/cdn/sodium.jar")).respond_with(ResponseTemplate::new(200).set_body_bytes(JAR)).mount(&server).await;
+        server
+    }
+
+    #[test]
+    fn test_loader_names() {
+        assert_eq!(loader_names(&ServerType::Fabric), &["fabric"]);
+        assert_eq!(loader_names(&ServerType::Quilt), &["quilt", "fabric"]);
+        assert!(loader_names(&ServerType::Custom).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_modrinth_version_matches_server() {
+        let server = mock_modrinth().await;
+        let client = modrinth::ModrinthClient::with_base_url(server.uri());
+
+        // The stable release wins over the newer beta, and the NeoForge build is skipped
+        let file = resolve_modrinth_file(&client, "sodium", None, "1.21.1", loader_names(&ServerType::Fabric)).await.unwrap();
+        assert_eq!(file.version, "0.6.3");
+        assert_eq!(file.filename, "sodium-0.6.3.jar");
+        assert_eq!(file.modrinth_id.as_deref(), Some("AANobbMI"));
+        assert_eq!(file.icon.as_deref(), Some("https://cdn.modrinth.com/sodium.png"));
+
+        let file = resolve_modrinth_file(&client, "sodium", None, "1.21.1", loader_names(&ServerType::NeoForge)).await.unwrap();
+        assert_eq!(file.version, "0.6.3-neoforge");
+
+        let file = resolve_modrinth_file(&client, "sodium", None, "1.20.1", loader_names(&ServerType::Quilt)).await.unwrap();
+        assert_eq!(file.version, "0.5.11");
+
+        assert!(resolve_modrinth_file(&client, "sodium", None, "1.19.2", loader_names(&ServerType::Fabric)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_curseforge_file_matches_server() {
+        let server = mock_curseforge().await;
+        let client = curseforge::CurseForgeClient::with_base_url("test-key", format!("{}/v1", server.uri()));
+
+        // The release wins over the newer alpha; it has no download URL, so the CDN is used
+        let file = resolve_curseforge_file(&client, 394468, None, "1.21.1", loader_names(&ServerType::Quilt)).await.unwrap();
+        assert_eq!(file.version, "sodium-5000002");
+        assert_eq!(file.url, "https://edge.forgecdn.net/files/5000/002/sodium-5000002.jar");
+        assert_eq!(file.curseforge_id.as_deref(), Some("394468"));
+        assert!(file.icon.as_deref().is_some_and(|icon| icon.starts_with("https://media.forgecdn.net/")));
+        assert_eq!(file.sha1, Some(sha1_hex(JAR)));
+
+        assert!(resolve_curseforge_file(&client, 394468, None, "1.20.1", loader_names(&ServerType::Fabric)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_curseforge_file_by_id_lands_in_mods_directory() {
+        let server = mock_curseforge().await;
+        let client = curseforge::CurseForgeClient::with_base_url("test-key", format!("{}/v1", server.uri()));
+        let dir = temp_dir("mod-install-curseforge");
+        let mods = dir.join("server/mods");
+
+        // An explicit file is installed as is, even though the server runs Fabric
+        let file = resolve_curseforge_file(&client, 394468, Some(5000001), "1.21.1", &["fabric"]).await.unwrap();
+        let path = download_mod_file(&file, &mods, None).await.unwrap();
+        assert_eq!(path, mods.join("sodium-5000001.jar"));
+        assert_eq!(std::fs::read(&path).unwrap(), JAR);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_modrinth_file_lands_in_mods_directory() {
+        let server = mock_modrinth().await;
+        let client = modrinth::ModrinthClient::with_base_url(server.uri());
+        let dir = temp_dir("mod-install-modrinth");
+        let mods = dir.join("server/mods");
+
+        let tracker_id = "test-modrinth-install";
+        ActionData::create(1, tracker_id.to_string(), ActionType::ModDownload, None).await.unwrap();
+
+        let file = resolve_modrinth_file(&client, "sodium", None, "1.21.1", &["fabric"]).await.unwrap();
+        let path = download_mod_file(&file, &mods, Some(tracker_id)).await.unwrap();
+        assert_eq!(path, mods.join("sodium-0.6.3.jar"));
+        assert_eq!(std::fs::read(&path).unwrap(), JAR);
+        assert_eq!(ActionData::get_by_tracker_id(tracker_id).await.unwrap().unwrap().progress, 100);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_download_is_removed() {
+        let server = mock_modrinth().await;
+        let dir = temp_dir("mod-install-corrupt");
+
+        let file = ResolvedModFile {
+            url: format!("{}/cdn/sodium.jar", server.uri()),
+            filename: "../../sodium.jar".to_string(),
+            version: "0.6.3".to_string(),
+            modrinth_id: None,
+            curseforge_id: None,
+            icon: None,
+            size: 0,
+            sha1: Some(sha1_hex(b"something else")),
+        };
+        assert!(download_mod_file(&file, &dir, None).await.is_err());
+        // The name was reduced to its last component and the bad file deleted
+        assert!(!dir.join("sodium.jar").exists());
+        assert!(!dir.parent().unwrap().join("sodium.jar").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,370 @@
+use crate::server::installed_mods::mod_data::ModData;
+use crate::server::installed_mods::mod_install;
+use anyhow::Result;
+use log::warn;
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+use std::path::{Path, PathBuf};
+
+/// Where an update was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModPlatform {
+    Modrinth,
+    CurseForge,
+}
+
+/// A newer file for an installed mod
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModUpdate {
+    pub mod_id: String,
+    pub name: String,
+    /// The installed file, e.g. `sodium-fabric-0.5.3+mc1.20.1.jar`
+    pub filename: String,
+    pub current_version: String,
+    pub platform: ModPlatform,
+    /// Modrinth project ID or CurseForge mod ID, as the install endpoints take it
+    pub project_id: String,
+    /// Modrinth version ID or CurseForge file ID of the update
+    pub version_id: String,
+    pub latest_version: String,
+    pub latest_filename: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ModUpdates {
+    pub updates: Vec<ModUpdate>,
+    /// Files of mods neither Modrinth nor CurseForge know, which can't be checked
+    pub unknown: Vec<String>,
+}
+
+/// An installed mod's file and its hashes
+#[derive(Clone)]
+struct InstalledFile<'a> {
+    data: &'a ModData,
+    sha1: String,
+    fingerprint: u32,
+}
+
+/// Check `mods`, installed in `directory`, for newer files that support `minecraft_version`
+/// and one of `loaders`.
+///
+/// Files are looked up on Modrinth by hash first, in a single request, and the ones Modrinth
+/// doesn't know on CurseForge by fingerprint. A platform that can't be reached is skipped, so
+/// its mods show up as unknown.
+pub async fn check_for_updates(
+    directory: &Path,
+    mods: &[ModData],
+    minecraft_version: &str,
+    loaders: &[&str],
+    modrinth: &modrinth::ModrinthClient,
+    curseforge: &curseforge::CurseForgeClient,
+) -> Result<ModUpdates> {
+    let paths: Vec<PathBuf> = mods.iter().map(|mod_data| directory.join(&mod_data.filename)).collect();
+    let hashes = tokio::task::spawn_blocking(move || paths.iter().map(|path| std::fs::read(path).ok().map(|bytes| hash(&bytes))).collect::<Vec<_>>()).await?;
+    let files: Vec<InstalledFile> = mods
+        .iter()
+        .zip(hashes)
+        .filter_map(|(data, hashes)| hashes.map(|(sha1, fingerprint)| InstalledFile { data, sha1, fingerprint }))
+        .collect();
+
+    let mut result = ModUpdates::default();
+    let unmatched = check_modrinth(&files, minecraft_version, loaders, modrinth, &mut result.updates).await;
+    let unmatched = check_curseforge(&unmatched, minecraft_version, loaders, curseforge, &mut result.updates).await;
+    result.unknown = unmatched.iter().map(|file| file.data.filename.clone()).collect();
+    Ok(result)
+}
+
+/// The SHA-1 Modrinth knows the file by, and its CurseForge fingerprint
+fn hash(bytes: &[u8]) -> (String, u32) {
+    let sha1 = Sha1::digest(bytes).iter().map(|byte| format!("{:02x}", byte)).collect();
+    (sha1, curseforge::fingerprint(bytes))
+}
+
+/// Add the updates Modrinth has for `files`, returning the files it doesn't know
+async fn check_modrinth<'a>(
+    files: &[InstalledFile<'a>],
+    minecraft_version: &str,
+    loaders: &[&str],
+    client: &modrinth::ModrinthClient,
+    updates: &mut Vec<ModUpdate>,
+) -> Vec<InstalledFile<'a>> {
+    if files.is_empty() {
+        return Vec::new();
+    }
+    let hashes: Vec<&str> = files.iter().map(|file| file.sha1.as_str()).collect();
+    let latest = match client.get_latest_versions_from_hashes(&hashes, loaders, &[minecraft_version]).await {
+        Ok(latest) => latest,
+        Err(e) => {
+            warn!("Failed to check Modrinth for mod updates: {}", e);
+            Default::default()
+        }
+    };
+
+    let mut unmatched = Vec::new();
+    for file in files {
+        let Some(version) = latest.get(&file.sha1) else {
+            unmatched.push(file.clone());
+            continue;
+        };
+        // The installed file is part of the latest version, so it is up to date
+        if version.files.iter().any(|latest_file| latest_file.hashes.sha1.eq_ignore_ascii_case(&file.sha1)) {
+            continue;
+        }
+        let Some(latest_file) = version.files.iter().find(|file| file.primary).or(version.files.first()) else {
+            continue;
+        };
+        updates.push(ModUpdate {
+            mod_id: file.data.mod_id.clone(),
+            name: file.data.name.clone(),
+            filename: file.data.filename.clone(),
+            current_version: file.data.version.clone(),
+            platform: ModPlatform::Modrinth,
+            project_id: version.project_id.clone(),
+            version_id: version.id.clone(),
+            latest_version: version.version_number.clone(),
+            latest_filename: latest_file.filename.clone(),
+        });
+    }
+    unmatched
+}
+
+/// Add the updates CurseForge has for `files`, returning the files it doesn't know
+async fn check_curseforge<'a>(
+    files: &[InstalledFile<'a>],
+    minecraft_version: &str,
+    loaders: &[&str],
+    client: &curseforge::CurseForgeClient,
+    updates: &mut Vec<ModUpdate>,
+) -> Vec<InstalledFile<'a>> {
+    if files.is_empty() {
+        return Vec::new();
+    }
+    let fingerprints: Vec<u32> = files.iter().map(|file| file.fingerprint).collect();
+    let matches = match client.get_fingerprint_matches(&fingerprints).await {
+        Ok(matches) => matches.exact_matches,
+        Err(e) => {
+            warn!("Failed to check CurseForge for mod updates: {}", e);
+            Vec::new()
+        }
+    };
+
+    let mut unmatched = Vec::new();
+    for file in files {
+        let Some(found) = matches.iter().find(|found| found.file.file_fingerprint == Some(file.fingerprint as u64)) else {
+            unmatched.push(file.clone());
+            continue;
+        };
+        let mod_files = match client.get_mod_files(found.id).await {
+            Ok(mod_files) => mod_files,
+            Err(e) => {
+                warn!("Failed to list CurseForge files of {}: {}", file.data.name, e);
+                continue;
+            }
+        };
+        // Only a newer file counts, so a beta installed on purpose isn't "updated" to an older release
+        let Some(latest) = mod_install::pick_curseforge_file(&mod_files, minecraft_version, loaders)
+            .filter(|latest| latest.id != found.file.id && latest.file_date > found.file.file_date)
+        else {
+            continue;
+        };
+        updates.push(ModUpdate {
+            mod_id: file.data.mod_id.clone(),
+            name: file.data.name.clone(),
+            filename: file.data.filename.clone(),
+            current_version: file.data.version.clone(),
+            platform: ModPlatform::CurseForge,
+            project_id: found.id.to_string(),
+            version_id: latest.id.to_string(),
+            latest_version: latest.display_name.clone(),
+            latest_filename: latest.file_name.clone(),
+        });
+    }
+    unmatched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Installed jars: an up to date and an outdated Modrinth mod, an up to date and an outdated
+    /// CurseForge mod, and a mod neither platform knows
+    const SODIUM: &[u8] = b"sodium 0.6.3";
+    const LITHIUM: &[u8] = b"lithium 0.12.0";
+    const JEI: &[u8] = b"jei 19.21.0";
+    const CREATE: &[u8] = b"create 0.5.1";
+    const PRIVATE: &[u8] = b"our own mod";
+
+    fn sha1_hex(bytes: &[u8]) -> String {
+        hash(bytes).0
+    }
+
+    fn installed(name: &str, contents: &[u8], directory: &Path) -> ModData {
+        let filename = format!("{}.jar", name);
+        std::fs::write(directory.join(&filename), contents).unwrap();
+        ModData {
+            mod_id: name.to_string(),
+            name: name.to_string(),
+            description: String::new(),
+            version: "installed".to_string(),
+            authors: Vec::new(),
+            icon: None,
+            modrinth_id: None,
+            curseforge_id: None,
+            filename,
+        }
+    }
+
+    fn modrinth_version(project_id: &str, id: &str, file_hash: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "project_id": project_id,
+            "author_id": "author",
+            "name": id,
+            "version_number": id,
+            "game_versions": ["1.21.1"],
+            "loaders": ["fabric"],
+            "version_type": "release",
+            "date_published": "2024-11-01T00:00:00Z",
+            "files": [{
+                "hashes": { "sha1": file_hash, "sha512": "" },
+                "url": format!("https://cdn.modrinth.com/{}.jar", id),
+                "filename": format!("{}.jar", id),
+                "primary": true,
+                "size": 1,
+            }],
+        })
+    }
+
+    fn curseforge_file(mod_id: u32, id: u64, date: &str, fingerprint: Option<u32>) -> serde_json::Value {
+        json!({
+            "id": id,
+            "gameId": 432,
+            "modId": mod_id,
+            "displayName": format!("file-{}", id),
+            "fileName": format!("file-{}.jar", id),
+            "releaseType": 1,
+            "fileDate": date,
+            "fileLength": 1,
+            "gameVersions": ["1.21.1", "Fabric"],
+            "fileFingerprint": fingerprint,
+        })
+    }
+
+    /// Modrinth knows Sodium (up to date) and Lithium (outdated); CurseForge knows JEI (up to
+    /// date) and Create (outdated)
+    async fn mock_platforms() -> MockServer {
+        let server = MockServer::start().await;
+        let hashes = [SODIUM, LITHIUM, JEI, CREATE, PRIVATE].map(sha1_hex);
+        Mock::given(method("POST"))
+            .and(path("/modrinth/version_files/update"))
+            .and(body_json(json!({
+                "hashes": hashes,
+                "algorithm": "sha1",
+                "loaders": ["fabric"],
+                "game_versions": ["1.21.1"],
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(HashMap::from([
+                (sha1_hex(SODIUM), modrinth_version("AANobbMI", "sodium-0.6.3", &sha1_hex(SODIUM))),
+                (sha1_hex(LITHIUM), modrinth_version("gvQqBUqZ", "lithium-0.14.0", "0000000000000000000000000000000000000000")),
+            ])))
+            .mount(&server)
+            .await;
+
+        let (jei, create) = (curseforge::fingerprint(JEI), curseforge::fingerprint(CREATE));
+        Mock::given(method("POST"))
+            .and(path("/v1/fingerprints/432"))
+            .and(body_json(json!({ "fingerprints": [jei, create, curseforge::fingerprint(PRIVATE)] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "data": {
+                "isCacheBuilt": true,
+                "exactMatches": [
+                    { "id": 238222, "file": curseforge_file(238222, 6000001, "2024-11-01T00:00:00.000Z", Some(jei)) },
+                    { "id": 328085, "file": curseforge_file(328085, 5000001, "2024-06-01T00:00:00.000Z", Some(create)) },
+                ],
+                "exactFingerprints": [jei, create],
+            }})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/mods/238222/files"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "data": [
+                curseforge_file(238222, 6000001, "2024-11-01T00:00:00.000Z", Some(jei)),
+                curseforge_file(238222, 5900000, "2024-10-01T00:00:00.000Z", None),
+            ]})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/mods/328085/files"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "data": [
+                curseforge_file(328085, 5000002, "2024-12-01T00:00:00.000Z", None),
+                curseforge_file(328085, 5000001, "2024-06-01T00:00:00.000Z", Some(create)),
+            ]})))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_outdated_mods_are_found_on_both_platforms() {
+        let server = mock_platforms().await;
+        let modrinth = modrinth::ModrinthClient::with_base_url(format!("{}/modrinth", server.uri()));
+        let curseforge = curseforge::CurseForgeClient::with_base_url("test-key", format!("{}/v1", server.uri()));
+        let dir = temp_dir("mod-updates-mixed");
+        let mods = [("sodium", SODIUM), ("lithium", LITHIUM), ("jei", JEI), ("create", CREATE), ("private", PRIVATE)].map(|(name, contents)| installed(name, contents, &dir));
+
+        let result = check_for_updates(&dir, &mods, "1.21.1", &["fabric"], &modrinth, &curseforge).await.unwrap();
+        assert_eq!(
+            result.updates,
+            vec![
+                ModUpdate {
+                    mod_id: "lithium".to_string(),
+                    name: "lithium".to_string(),
+                    filename: "lithium.jar".to_string(),
+                    current_version: "installed".to_string(),
+                    platform: ModPlatform::Modrinth,
+                    project_id: "gvQqBUqZ".to_string(),
+                    version_id: "lithium-0.14.0".to_string(),
+                    latest_version: "lithium-0.14.0".to_string(),
+                    latest_filename: "lithium-0.14.0.jar".to_string(),
+                },
+                ModUpdate {
+                    mod_id: "create".to_string(),
+                    name: "create".to_string(),
+                    filename: "create.jar".to_string(),
+                    current_version: "installed".to_string(),
+                    platform: ModPlatform::CurseForge,
+                    project_id: "328085".to_string(),
+                    version_id: "5000002".to_string(),
+                    latest_version: "file-5000002".to_string(),
+                    latest_filename: "file-5000002.jar".to_string(),
+                },
+            ]
+        );
+        assert_eq!(result.unknown, vec!["private.jar"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_platforms_leave_mods_unknown() {
+        // Nothing is mocked, so both platforms answer 404
+        let server = MockServer::start().await;
+        let modrinth = modrinth::ModrinthClient::with_base_url(format!("{}/modrinth", server.uri()));
+        let curseforge = curseforge::CurseForgeClient::with_base_url("test-key", format!("{}/v1", server.uri()));
+        let dir = temp_dir("mod-updates-unreachable");
+        let mut mods = vec![installed("sodium", SODIUM, &dir), installed("create", CREATE, &dir)];
+        // A mod whose file was removed since it was recorded is skipped
+        mods.push(ModData { filename: "removed.jar".to_string(), ..mods[0].clone() });
+
+        let result = check_for_updates(&dir, &mods, "1.21.1", &["fabric"], &modrinth, &curseforge).await.unwrap();
+        assert!(result.updates.is_empty());
+        assert_eq!(result.unknown, vec!["sodium.jar", "create.jar"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
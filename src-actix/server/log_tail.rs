@@ -0,0 +1,242 @@
+use anyhow::Result;
+use futures::Stream;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+/// The log the server is currently writing to, relative to its logs directory
+pub const LATEST_LOG: &str = "latest.log";
+pub const DEFAULT_TAIL_LINES: usize = 100;
+pub const MAX_TAIL_LINES: usize = 5000;
+
+/// How often a followed log is checked for new lines
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn parse(level: &str) -> Option<Self> {
+        match level.to_ascii_uppercase().as_str() {
+            "DEBUG" | "TRACE" => Some(Self::Debug),
+            "INFO" => Some(Self::Info),
+            "WARN" | "WARNING" => Some(Self::Warn),
+            "ERROR" | "FATAL" | "SEVERE" => Some(Self::Error),
+            _ => None,
+        }
+    }
+
+    /// The level in the leading `[...]` groups of a log line, as written by vanilla and modded
+    /// servers (`[09:14:04] [Server thread/WARN]: ...`) and by Paper (`[09:14:04 WARN]: ...`)
+    pub fn detect(line: &str) -> Option<Self> {
+        let mut rest = line.trim_start();
+        for _ in 0..3 {
+            let Some(group) = rest.strip_prefix('[') else { break };
+            let Some(end) = group.find(']') else { break };
+            if let Some(level) = group[..end].rsplit(['/', ' ']).next().and_then(Self::parse) {
+                return Some(level);
+            }
+            rest = group[end + 1..].trim_start();
+        }
+        None
+    }
+}
+
+/// Keeps lines at or above a level. Lines without a level of their own, like stack traces,
+/// belong to the entry before them.
+pub struct LevelFilter {
+    min: Option<LogLevel>,
+    current: LogLevel,
+}
+
+impl LevelFilter {
+    pub fn new(min: Option<LogLevel>) -> Self {
+        Self { min, current: LogLevel::Info }
+    }
+
+    pub fn matches(&mut self, line: &str) -> bool {
+        if let Some(level) = LogLevel::detect(line) {
+            self.current = level;
+        }
+        self.min.is_none_or(|min| self.current >= min)
+    }
+}
+
+/// The last `count` lines of `path` at or above `level`, and the offset they were read up to.
+/// An unfinished last line is left for `follow`.
+pub fn tail(path: &Path, count: usize, level: Option<LogLevel>) -> Result<(Vec<String>, u64)> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut filter = LevelFilter::new(level);
+    let mut lines = VecDeque::with_capacity(count);
+    let mut offset = 0;
+    let mut buffer = Vec::new();
+
+    loop {
+        buffer.clear();
+        let read = reader.read_until(b'\n', &mut buffer)?;
+        if read == 0 || buffer.last() != Some(&b'\n') {
+            break;
+        }
+        offset += read as u64;
+
+        let line = decode_line(&buffer);
+        if count > 0 && filter.matches(&line) {
+            if lines.len() == count {
+                lines.pop_front();
+            }
+            lines.push_back(line);
+        }
+    }
+
+    Ok((lines.into(), offset))
+}
+
+/// Lines appended to `path` after `offset` at or above `level`, as they are written.
+/// Starts over from the top when the file shrinks, as it does when the server rolls its log.
+pub fn follow(path: PathBuf, offset: u64, level: Option<LogLevel>) -> impl Stream<Item = String> {
+    let follower = LogFollower { path, offset, partial: Vec::new(), pending: VecDeque::new(), filter: LevelFilter::new(level) };
+    futures::stream::unfold(follower, |mut follower| async move {
+        loop {
+            if let Some(line) = follower.pending.pop_front() {
+                return Some((line, follower));
+            }
+            // The file can be missing for a moment while the server rolls its log, so errors are retried
+            if !matches!(follower.read_appended().await, Ok(true)) {
+                tokio::time::sleep(FOLLOW_POLL_INTERVAL).await;
+            }
+        }
+    })
+}
+
+struct LogFollower {
+    path: PathBuf,
+    offset: u64,
+    partial: Vec<u8>,
+    pending: VecDeque<String>,
+    filter: LevelFilter,
+}
+
+impl LogFollower {
+    /// Read anything written since the last call, returning whether there was any
+    async fn read_appended(&mut self) -> Result<bool> {
+        let mut file = tokio::fs::File::open(&self.path).await?;
+        let len = file.metadata().await?.len();
+        if len < self.offset {
+            self.offset = 0;
+            self.partial.clear();
+        }
+        if len == self.offset {
+            return Ok(false);
+        }
+
+        file.seek(SeekFrom::Start(self.offset)).await?;
+        let mut appended = Vec::new();
+        file.take(len - self.offset).read_to_end(&mut appended).await?;
+        self.offset += appended.len() as u64;
+        self.partial.extend_from_slice(&appended);
+
+        while let Some(end) = self.partial.iter().position(|b| *b == b'\n') {
+            let line = decode_line(&self.partial.drain(..=end).collect::<Vec<u8>>());
+            if self.filter.matches(&line) {
+                self.pending.push_back(line);
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn decode_line(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).trim_end_matches(['\r', '\n']).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use futures::StreamExt;
+    use std::io::Write;
+
+    const FIXTURE: &str = include_str!("test_fixtures/latest.log");
+
+    fn fixture_log(name: &str) -> PathBuf {
+        let dir = temp_dir(&format!("log-tail-{}", name));
+        let path = dir.join(LATEST_LOG);
+        std::fs::write(&path, FIXTURE).unwrap();
+        path
+    }
+
+    async fn next_line(lines: &mut (impl Stream<Item = String> + Unpin)) -> String {
+        tokio::time::timeout(Duration::from_secs(5), lines.next()).await.unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_detect_level() {
+        assert_eq!(LogLevel::detect("[09:14:04] [Server thread/WARN]: Failed to load eula.txt"), Some(LogLevel::Warn));
+        assert_eq!(LogLevel::detect("[09:14:04 ERROR]: Could not pass event"), Some(LogLevel::Error));
+        assert_eq!(LogLevel::detect("[16Jan2025 09:14:04.120] [main/INFO] [cpw.mods.modlauncher.Launcher/MODLAUNCHER]: Loading"), Some(LogLevel::Info));
+        assert_eq!(LogLevel::detect("\tat net.minecraft.server.Main.main(Main.java:12)"), None);
+        assert_eq!(LogLevel::parse("warning"), Some(LogLevel::Warn));
+        assert_eq!(LogLevel::parse("loud"), None);
+    }
+
+    #[test]
+    fn test_tail_line_count() {
+        let path = fixture_log("count");
+
+        let (lines, offset) = tail(&path, 3, None).unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                r#"[09:14:08] [Server thread/INFO]: Done (3.214s)! For help, type "help""#,
+                "[09:15:10] [Server thread/WARN]: Can't keep up! Is the server overloaded? Running 2041ms or 40 ticks behind",
+                "[09:16:00] [Server thread/INFO]: Steve joined the game",
+            ]
+        );
+        assert_eq!(offset, FIXTURE.len() as u64);
+
+        assert_eq!(tail(&path, MAX_TAIL_LINES, None).unwrap().0.len(), FIXTURE.lines().count());
+        assert!(tail(&path, 0, None).unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn test_tail_level_filter() {
+        let path = fixture_log("level");
+
+        let (errors, _) = tail(&path, MAX_TAIL_LINES, Some(LogLevel::Error)).unwrap();
+        assert_eq!(errors.len(), 4);
+        assert_eq!(errors[0], "[09:14:07] [Server thread/ERROR]: Encountered an unexpected exception");
+        assert!(errors[1..].iter().all(|line| !line.starts_with('[')), "stack trace follows its entry");
+
+        let (warnings, _) = tail(&path, 2, Some(LogLevel::Warn)).unwrap();
+        assert_eq!(warnings[0], "\tat net.minecraft.server.MinecraftServer.runServer(MinecraftServer.java:687)");
+        assert!(warnings[1].contains("Can't keep up!"));
+    }
+
+    #[tokio::test]
+    async fn test_follow_filters_appended_lines() {
+        let path = fixture_log("follow");
+        let (_, offset) = tail(&path, 0, None).unwrap();
+        let mut lines = Box::pin(follow(path.clone(), offset, Some(LogLevel::Warn)));
+
+        let mut log = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        write!(log, "[09:17:00] [Server thread/INFO]: Saving chunks\n[09:17:01] [Server thread/WARN]: Ambiguity between arguments").unwrap();
+        log.flush().unwrap();
+        tokio::time::sleep(FOLLOW_POLL_INTERVAL).await;
+        writeln!(log, " [teleport, targets]\n[09:17:02] [Server thread/ERROR]: Failed to save player data").unwrap();
+
+        assert_eq!(next_line(&mut lines).await, "[09:17:01] [Server thread/WARN]: Ambiguity between arguments [teleport, targets]");
+        assert_eq!(next_line(&mut lines).await, "[09:17:02] [Server thread/ERROR]: Failed to save player data");
+
+        // Rolled over to a fresh log
+        std::fs::write(&path, "[09:20:00] [Server thread/ERROR]: Exception stopping the server\n").unwrap();
+        assert_eq!(next_line(&mut lines).await, "[09:20:00] [Server thread/ERROR]: Exception stopping the server");
+    }
+}
@@ -1,17 +1,30 @@
 pub mod backups;
+mod bulk_actions;
+mod console_socket;
+mod crash_alert;
+mod disk_usage;
 mod filesystem;
 pub mod installed_mods;
+mod log_tail;
+mod online_players;
+mod port_forwarding;
+mod properties_editor;
 mod server_actions;
+mod server_clone;
 pub mod server_data;
+mod server_console;
 mod server_db;
 mod server_endpoint;
+pub mod server_metrics;
 mod server_ping;
 mod server_properties;
 pub mod server_status;
 mod server_type;
 mod forge_server;
+pub mod templates;
 pub mod updates;
 pub mod web_event_handler;
+mod whitelist_editor;
 
 pub use server_db::initialize;
 pub use server_endpoint::configure;
@@ -0,0 +1,213 @@
+use anyhow::{anyhow, Result};
+use log::debug;
+use minecraft_server::{RconClient, ServerProperties, ServerStatusResponse};
+use regex::Regex;
+use serde::Serialize;
+use std::sync::LazyLock;
+
+/// Player counts at the start of `list` output, as vanilla (`There are 2 of a max of 20 players
+/// online`), older Spigot (`There are 2/20 players online`) and Bukkit (`There are 2 out of
+/// maximum 20 players online`) write them
+static LIST_COUNTS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"There are (\d+)\D+?(\d+) players online").unwrap());
+
+/// A name in `list uuids` output, followed by its UUID in parentheses
+static NAME_WITH_UUID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\S+) \(([0-9a-fA-F-]{36})\)$").unwrap());
+
+/// Shown in place of players who hide from the server list
+const ANONYMOUS_UUID: &str = "00000000-0000-0000-0000-000000000000";
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct OnlinePlayer {
+    pub name: String,
+    pub uuid: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct OnlinePlayers {
+    pub online: u32,
+    pub max: u32,
+    /// Everyone online when read over RCON; the server list ping only shares a sample of up to 12
+    pub players: Vec<OnlinePlayer>,
+}
+
+impl OnlinePlayers {
+    /// Read the output of `list` or `list uuids`. Returns `None` if it is not a player list,
+    /// as when the server doesn't know the command.
+    pub fn parse_list_output(output: &str) -> Option<Self> {
+        let output = strip_formatting(output);
+        let counts = LIST_COUNTS.captures(&output)?;
+        let online = counts[1].parse().ok()?;
+        let max = counts[2].parse().ok()?;
+
+        // Names follow the counts, on the same line after a colon or on the lines below,
+        // where Bukkit prefixes them with the player's group (`default: Alex, Steve`)
+        let names = output[counts.get(0)?.end()..].trim_start_matches(['.', ':']);
+        let players = names
+            .lines()
+            .map(str::trim)
+            .map(|line| match line.split_once(": ") {
+                Some((group, names)) if !group.contains(',') => names,
+                _ => line,
+            })
+            .flat_map(|line| line.split(','))
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| match NAME_WITH_UUID.captures(name) {
+                Some(captures) => OnlinePlayer { name: captures[1].to_string(), uuid: Some(captures[2].to_lowercase()) },
+                None => OnlinePlayer { name: name.to_string(), uuid: None },
+            })
+            .collect();
+
+        Some(Self { online, max, players })
+    }
+}
+
+impl From<ServerStatusResponse> for OnlinePlayers {
+    fn from(status: ServerStatusResponse) -> Self {
+        let players = status
+            .players
+            .sample
+            .into_iter()
+            .filter(|player| player.id != ANONYMOUS_UUID)
+            .map(|player| OnlinePlayer { name: player.name, uuid: Some(player.id) })
+            .collect();
+        Self { online: status.players.online, max: status.players.max, players }
+    }
+}
+
+/// The players on the server at `host`, over RCON when server.properties enables it and
+/// otherwise, or if RCON fails, from the server list ping
+pub async fn query(host: &str, properties: &ServerProperties) -> Result<OnlinePlayers> {
+    if let Some(password) = properties.rcon_password().filter(|_| properties.rcon_enabled()) {
+        match query_rcon(host, properties.rcon_port(), &password).await {
+            Ok(players) => return Ok(players),
+            Err(e) => debug!("Falling back to the server list ping for online players: {}", e),
+        }
+    }
+
+    let status = minecraft_server::ping::ping(host, properties.server_port()).await?;
+    Ok(status.into())
+}
+
+async fn query_rcon(host: &str, port: u16, password: &str) -> Result<OnlinePlayers> {
+    let mut client = RconClient::connect(host, port, password).await?;
+    // `list uuids` arrived in 1.13; older servers answer it with an error, so plain `list` is the fallback
+    for command in ["list uuids", "list"] {
+        if let Some(players) = OnlinePlayers::parse_list_output(&client.command(command).await?) {
+            return Ok(players);
+        }
+    }
+    Err(anyhow!("Server did not answer the list command with a player list"))
+}
+
+/// Remove `§` color and style codes
+fn strip_formatting(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            chars.next();
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minecraft_server::ping::{StatusPlayer, StatusPlayers, StatusVersion};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    fn player(name: &str, uuid: Option<&str>) -> OnlinePlayer {
+        OnlinePlayer { name: name.to_string(), uuid: uuid.map(String::from) }
+    }
+
+    async fn read_packet(stream: &mut TcpStream) -> Option<(i32, i32, String)> {
+        let length = stream.read_i32_le().await.ok()?;
+        let mut packet = vec![0u8; length as usize];
+        stream.read_exact(&mut packet).await.ok()?;
+        let id = i32::from_le_bytes(packet[0..4].try_into().unwrap());
+        let kind = i32::from_le_bytes(packet[4..8].try_into().unwrap());
+        Some((id, kind, String::from_utf8_lossy(&packet[8..packet.len() - 2]).to_string()))
+    }
+
+    async fn write_packet(stream: &mut TcpStream, id: i32, kind: i32, body: &str) {
+        let mut packet = Vec::new();
+        packet.extend_from_slice(&(body.len() as i32 + 10).to_le_bytes());
+        packet.extend_from_slice(&id.to_le_bytes());
+        packet.extend_from_slice(&kind.to_le_bytes());
+        packet.extend_from_slice(body.as_bytes());
+        packet.extend_from_slice(&[0, 0]);
+        stream.write_all(&packet).await.unwrap();
+    }
+
+    /// A remote console that accepts any password and answers commands from `responses`
+    async fn mock_rcon(responses: Vec<(&'static str, &'static str)>) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            while let Some((id, kind, body)) = read_packet(&mut stream).await {
+                let response = match kind {
+                    3 => (id, 2, ""),
+                    _ => (id, 0, responses.iter().find(|(command, _)| *command == body).map_or("Unknown or incomplete command", |(_, response)| response)),
+                };
+                write_packet(&mut stream, response.0, response.1, response.2).await;
+            }
+        });
+        port
+    }
+
+    #[test]
+    fn test_parse_list_output() {
+        let vanilla = OnlinePlayers::parse_list_output("There are 2 of a max of 20 players online: Alex, Steve").unwrap();
+        assert_eq!(vanilla, OnlinePlayers { online: 2, max: 20, players: vec![player("Alex", None), player("Steve", None)] });
+
+        let uuids = OnlinePlayers::parse_list_output("There are 1 of a max of 20 players online: Steve (8667BA71-B85A-4004-AF54-457A9734EED7)").unwrap();
+        assert_eq!(uuids.players, vec![player("Steve", Some("8667ba71-b85a-4004-af54-457a9734eed7"))]);
+
+        let bukkit = OnlinePlayers::parse_list_output("There are §c3§6 out of maximum §c50§6 players online.\ndefault: §aAlex, .BedrockSteve\nadmin: Notch\n").unwrap();
+        assert_eq!((bukkit.online, bukkit.max), (3, 50));
+        assert_eq!(bukkit.players, vec![player("Alex", None), player(".BedrockSteve", None), player("Notch", None)]);
+
+        let empty = OnlinePlayers::parse_list_output("There are 0 of a max of 20 players online: ").unwrap();
+        assert_eq!(empty, OnlinePlayers { online: 0, max: 20, players: vec![] });
+
+        assert_eq!(OnlinePlayers::parse_list_output("Unknown or incomplete command, see below for error"), None);
+    }
+
+    #[test]
+    fn test_players_from_ping_sample() {
+        let status = ServerStatusResponse {
+            motd: "A Minecraft Server".to_string(),
+            version: StatusVersion { name: "1.21.4".to_string(), protocol: 769 },
+            players: StatusPlayers {
+                online: 14,
+                max: 20,
+                sample: vec![
+                    StatusPlayer { name: "Steve".to_string(), id: "8667ba71-b85a-4004-af54-457a9734eed7".to_string() },
+                    StatusPlayer { name: "Anonymous Player".to_string(), id: ANONYMOUS_UUID.to_string() },
+                ],
+            },
+        };
+        let players = OnlinePlayers::from(status);
+        assert_eq!((players.online, players.max), (14, 20));
+        assert_eq!(players.players, vec![player("Steve", Some("8667ba71-b85a-4004-af54-457a9734eed7"))]);
+    }
+
+    #[tokio::test]
+    async fn test_query_over_rcon() {
+        let port = mock_rcon(vec![("list uuids", "There are 1 of a max of 20 players online: Alex (ec561538-f3fd-461d-aff5-086b22154bce)")]).await;
+        let properties = ServerProperties::parse(&format!("enable-rcon=true\nrcon.port={}\nrcon.password=hunter2\n", port));
+        let players = query("127.0.0.1", &properties).await.unwrap();
+        assert_eq!(players.players, vec![player("Alex", Some("ec561538-f3fd-461d-aff5-086b22154bce"))]);
+
+        // Servers from before `list uuids` fall back to `list`
+        let port = mock_rcon(vec![("list", "There are 2/10 players online:\nAlex, Steve")]).await;
+        let players = query_rcon("127.0.0.1", port, "hunter2").await.unwrap();
+        assert_eq!(players, OnlinePlayers { online: 2, max: 10, players: vec![player("Alex", None), player("Steve", None)] });
+    }
+}
@@ -0,0 +1,262 @@
+use anyhow::Result;
+use log::{debug, error, warn};
+use minecraft_server::ServerProperties;
+use obsidian_upnp::{PortMapping, PortMappingProtocol, UpnpError, UpnpManager};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::LazyLock;
+use tokio::sync::Mutex;
+
+/// Port Geyser listens on for Bedrock players unless its config says otherwise
+const DEFAULT_BEDROCK_PORT: u16 = 19132;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ForwardedPort {
+    pub port: u16,
+    /// `TCP` or `UDP`
+    pub protocol: String,
+    /// `game`, `query` or `bedrock`
+    pub purpose: &'static str,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct ForwardedPorts {
+    /// The router's public address, if it reported one players can reach
+    pub external_ip: Option<IpAddr>,
+    pub ports: Vec<ForwardedPort>,
+}
+
+/// Ports opened for servers on demand, tracked so they are closed again when the server stops.
+/// Whatever is still open when the panel exits goes with [`UpnpManager::close`].
+pub struct ServerPortForwards {
+    manager: &'static UpnpManager,
+    open: Mutex<HashMap<u64, Vec<PortMapping>>>,
+}
+
+static INSTANCE: LazyLock<ServerPortForwards> = LazyLock::new(|| ServerPortForwards::new(UpnpManager::global()));
+
+impl ServerPortForwards {
+    pub fn global() -> &'static Self {
+        &INSTANCE
+    }
+
+    pub fn new(manager: &'static UpnpManager) -> Self {
+        Self { manager, open: Mutex::new(HashMap::new()) }
+    }
+
+    /// Forward the game port of the server in `directory`, and its query and Geyser ports if
+    /// they are enabled. Ports that are already forwarded, as the game port is for servers with
+    /// UPnP turned on, are reported but left to whoever opened them. If any port can't be
+    /// forwarded, the ones opened by this call are closed again.
+    pub async fn open(&self, server_id: u64, directory: &Path) -> Result<ForwardedPorts> {
+        let properties = ServerProperties::load_from_dir(directory).unwrap_or_default();
+        let mut ports = Vec::new();
+        let mut opened = Vec::new();
+        for (purpose, port, protocol) in server_ports(directory, &properties) {
+            ports.push(ForwardedPort { port, protocol: protocol.to_string(), purpose });
+            if self.manager.has_mapping(port, protocol).await {
+                continue;
+            }
+
+            let description = match purpose {
+                "game" => format!("Minecraft Server {}", server_id),
+                _ => format!("Minecraft Server {} ({})", server_id, purpose),
+            };
+            let mapping = PortMapping::new(port, description, protocol);
+            if let Err(e) = self.manager.add_mapping(mapping.clone()).await {
+                for mapping in opened {
+                    self.remove(&mapping).await;
+                }
+                return Err(anyhow::anyhow!("Failed to forward {} port {}: {}", purpose, port, e));
+            }
+            opened.push(mapping);
+        }
+        debug!("Forwarded {} ports for server {}", opened.len(), server_id);
+        self.open.lock().await.entry(server_id).or_default().extend(opened);
+
+        let external_ip = match self.manager.external_ip().await {
+            Ok(ip) => Some(ip),
+            Err(e) => {
+                warn!("Could not get the external IP for server {}: {}", server_id, e);
+                None
+            }
+        };
+        Ok(ForwardedPorts { external_ip, ports })
+    }
+
+    /// Close the ports opened for a server by [`open`](Self::open)
+    pub async fn close(&self, server_id: u64) {
+        let Some(mappings) = self.open.lock().await.remove(&server_id) else {
+            return;
+        };
+        for mapping in mappings {
+            self.remove(&mapping).await;
+        }
+    }
+
+    async fn remove(&self, mapping: &PortMapping) {
+        match self.manager.remove_mapping(mapping.port, mapping.protocol).await {
+            // Stopping a server with UPnP turned on closes its game port for every protocol
+            Ok(()) | Err(UpnpError::PortNotFound(_)) => {}
+            Err(e) => error!("Failed to remove UPnP port {} ({}): {}", mapping.port, mapping.protocol, e),
+        }
+    }
+}
+
+/// The ports players connect to: the game port, the query port when `enable-query` is set,
+/// and Geyser's Bedrock port when the server has Geyser installed
+fn server_ports(directory: &Path, properties: &ServerProperties) -> Vec<(&'static str, u16, PortMappingProtocol)> {
+    let mut ports = vec![("game", properties.server_port(), PortMappingProtocol::TCP)];
+    if properties.get("enable-query").is_some_and(|enabled| enabled == "true") {
+        let query_port = properties.get("query.port").and_then(|port| port.parse().ok()).unwrap_or(properties.server_port());
+        ports.push(("query", query_port, PortMappingProtocol::UDP));
+    }
+    if let Some(bedrock_port) = geyser_port(directory) {
+        ports.push(("bedrock", bedrock_port, PortMappingProtocol::UDP));
+    }
+    ports
+}
+
+/// The Bedrock port from Geyser's config.yml, which lives in `plugins/Geyser-Spigot` (or
+/// `-Velocity`, ...) on plugin servers and `config/Geyser-Fabric` (or `-NeoForge`) on mod loaders
+fn geyser_port(directory: &Path) -> Option<u16> {
+    let config = ["plugins", "config"]
+        .into_iter()
+        .filter_map(|folder| std::fs::read_dir(directory.join(folder)).ok())
+        .flatten()
+        .flatten()
+        .find(|entry| entry.file_name().to_string_lossy().starts_with("Geyser-") && entry.path().join("config.yml").is_file())?
+        .path()
+        .join("config.yml");
+
+    // The port is the first `port:` nested under the top level `bedrock:` section
+    let contents = std::fs::read_to_string(config).ok()?;
+    let port = contents
+        .lines()
+        .skip_while(|line| line.trim_end() != "bedrock:")
+        .skip(1)
+        .take_while(|line| line.is_empty() || line.starts_with([' ', '\t', '#']))
+        .find_map(|line| line.trim().strip_prefix("port:"))
+        .and_then(|port| port.trim().parse().ok());
+    Some(port.unwrap_or(DEFAULT_BEDROCK_PORT))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use obsidian_upnp::{Discovery, Gateway};
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+
+    /// An in-memory router's port-mapping table
+    #[derive(Default)]
+    struct MockGateway {
+        table: std::sync::Mutex<Vec<(u16, PortMappingProtocol)>>,
+        /// External port the router refuses to forward
+        refused_port: Option<u16>,
+    }
+
+    impl Gateway for MockGateway {
+        fn add_mapping(&self, mapping: &PortMapping) -> Result<(), UpnpError> {
+            if self.refused_port == Some(mapping.port) {
+                return Err(UpnpError::UpnpOperationFailed("ConflictInMappingEntry".to_string()));
+            }
+            self.table.lock().unwrap().push((mapping.port, mapping.protocol));
+            Ok(())
+        }
+
+        fn remove_mapping(&self, port: u16, protocol: PortMappingProtocol) -> Result<(), UpnpError> {
+            self.table.lock().unwrap().retain(|mapping| *mapping != (port, protocol));
+            Ok(())
+        }
+
+        fn mapping_entry(&self, _index: u32) -> Result<Option<PortMapping>, UpnpError> {
+            Ok(None)
+        }
+
+        fn external_ip(&self) -> Result<IpAddr, UpnpError> {
+            Ok(IpAddr::V4(Ipv4Addr::new(81, 2, 69, 142)))
+        }
+
+        fn local_address(&self) -> Result<IpAddr, UpnpError> {
+            Ok(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10)))
+        }
+    }
+
+    struct MockDiscovery(Arc<MockGateway>);
+
+    impl Discovery for MockDiscovery {
+        fn discover(&self) -> Result<Arc<dyn Gateway>, UpnpError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn forwards(gateway: MockGateway) -> (ServerPortForwards, Arc<MockGateway>) {
+        let gateway = Arc::new(gateway);
+        let manager = Box::leak(Box::new(UpnpManager::new(MockDiscovery(gateway.clone()))));
+        (ServerPortForwards::new(manager), gateway)
+    }
+
+    fn table(gateway: &MockGateway) -> Vec<(u16, PortMappingProtocol)> {
+        let mut table = gateway.table.lock().unwrap().clone();
+        table.sort_by_key(|(port, protocol)| (*port, protocol.to_string()));
+        table
+    }
+
+    #[tokio::test]
+    async fn test_open_and_close_server_ports() {
+        let dir = temp_dir("port-forwarding-open");
+        std::fs::write(dir.join("server.properties"), "server-port=25570\nenable-query=true\nquery.port=25571\n").unwrap();
+        std::fs::create_dir_all(dir.join("plugins/Geyser-Spigot")).unwrap();
+        std::fs::write(dir.join("plugins/Geyser-Spigot/config.yml"), "bedrock:\n  address: 0.0.0.0\n  # Bedrock players connect here\n  port: 19133\nremote:\n  port: 25570\n")
+            .unwrap();
+        let (forwards, gateway) = forwards(MockGateway::default());
+
+        let forwarded = forwards.open(7, &dir).await.unwrap();
+        assert_eq!(forwarded.external_ip, Some(IpAddr::V4(Ipv4Addr::new(81, 2, 69, 142))));
+        let ports: Vec<_> = forwarded.ports.iter().map(|port| (port.purpose, port.port, port.protocol.as_str())).collect();
+        assert_eq!(ports, vec![("game", 25570, "TCP"), ("query", 25571, "UDP"), ("bedrock", 19133, "UDP")]);
+        assert_eq!(table(&gateway), vec![(19133, PortMappingProtocol::UDP), (25570, PortMappingProtocol::TCP), (25571, PortMappingProtocol::UDP)]);
+
+        forwards.close(7).await;
+        assert_eq!(table(&gateway), vec![]);
+        assert!(forwards.open.lock().await.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_ports_opened_elsewhere_are_left_open() {
+        let dir = temp_dir("port-forwarding-existing");
+        std::fs::create_dir_all(dir.join("config/Geyser-Fabric")).unwrap();
+        std::fs::write(dir.join("config/Geyser-Fabric/config.yml"), "bedrock:\n  address: 0.0.0.0\n").unwrap();
+        let (forwards, gateway) = forwards(MockGateway::default());
+
+        // As when the server started with UPnP turned on
+        forwards.manager.add_port(25565, "Minecraft Server 3".to_string(), PortMappingProtocol::TCP).await.unwrap();
+        let forwarded = forwards.open(3, &dir).await.unwrap();
+        assert_eq!(forwarded.ports.iter().map(|port| port.port).collect::<Vec<_>>(), vec![25565, DEFAULT_BEDROCK_PORT]);
+
+        forwards.close(3).await;
+        assert_eq!(table(&gateway), vec![(25565, PortMappingProtocol::TCP)]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_open_closes_what_it_opened() {
+        let dir = temp_dir("port-forwarding-refused");
+        std::fs::write(dir.join("server.properties"), "enable-query=true\nquery.port=25575\n").unwrap();
+        let (forwards, gateway) = forwards(MockGateway { refused_port: Some(25575), ..Default::default() });
+
+        let error = forwards.open(5, &dir).await.unwrap_err();
+        assert!(error.to_string().contains("query port 25575"));
+        assert_eq!(table(&gateway), vec![]);
+        assert!(!forwards.manager.has_port(25565).await);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,149 @@
+use crate::settings::settings_data::FieldError;
+use minecraft_server::{Difficulty, Gamemode, ServerProperties};
+use serde::Serialize;
+use serde_json::Value;
+use std::str::FromStr;
+
+/// The server.properties fields the panel edits, read through the typed accessors
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PropertiesView {
+    pub motd: String,
+    pub server_port: u16,
+    pub gamemode: String,
+    pub difficulty: String,
+    pub max_players: u32,
+    pub online_mode: bool,
+    pub level_name: String,
+}
+
+impl From<&ServerProperties> for PropertiesView {
+    fn from(properties: &ServerProperties) -> Self {
+        Self {
+            motd: properties.get("motd").unwrap_or_default(),
+            server_port: properties.server_port(),
+            gamemode: properties.gamemode().to_string(),
+            difficulty: properties.difficulty().to_string(),
+            max_players: properties.max_players(),
+            online_mode: properties.online_mode(),
+            level_name: properties.level_name(),
+        }
+    }
+}
+
+/// Apply the fields present in `changes` to `properties`. Nothing is changed unless every
+/// field is valid; other keys and comments in the file are kept as they are.
+pub fn apply_changes(properties: &mut ServerProperties, changes: &Value) -> Result<(), Vec<FieldError>> {
+    let Some(changes) = changes.as_object() else {
+        return Err(vec![FieldError::new("properties", "Must be an object")]);
+    };
+
+    let mut updated = properties.clone();
+    let mut errors = Vec::new();
+    for (field, value) in changes {
+        if let Err(message) = apply_field(&mut updated, field, value) {
+            errors.push(FieldError::new(field, message));
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    *properties = updated;
+    Ok(())
+}
+
+fn apply_field(properties: &mut ServerProperties, field: &str, value: &Value) -> Result<(), String> {
+    match field {
+        "motd" => properties.set("motd", value.as_str().ok_or("Must be a string")?),
+        "server_port" => {
+            let port = value.as_u64().and_then(|port| u16::try_from(port).ok()).ok_or("Must be a port number between 1 and 65535")?;
+            properties.set_server_port(port).map_err(|e| e.to_string())?;
+        }
+        "gamemode" => properties.set_gamemode(Gamemode::from_str(value.as_str().ok_or("Must be a string")?)?),
+        "difficulty" => properties.set_difficulty(Difficulty::from_str(value.as_str().ok_or("Must be a string")?)?),
+        "max_players" => {
+            let max_players = value.as_u64().and_then(|max| u32::try_from(max).ok()).ok_or("Must be a positive number")?;
+            properties.set_max_players(max_players).map_err(|e| e.to_string())?;
+        }
+        "online_mode" => properties.set_online_mode(value.as_bool().ok_or("Must be true or false")?),
+        "level_name" => {
+            let level_name = value.as_str().map(str::trim).ok_or("Must be a string")?;
+            if level_name.is_empty() {
+                return Err("World name cannot be empty".to_string());
+            }
+            properties.set("level-name", level_name);
+        }
+        _ => return Err("Unknown property".to_string()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const PROPERTIES: &str = "#Minecraft server properties
+#Sat Jun 01 12:00:00 UTC 2025
+difficulty=normal
+gamemode=creative
+motd=A Minecraft Server
+max-players=42
+server-port=25570
+custom-plugin-key=keep me
+";
+
+    #[test]
+    fn test_properties_round_trip() {
+        let mut properties = ServerProperties::parse(PROPERTIES);
+        let view = PropertiesView::from(&properties);
+        assert_eq!(
+            serde_json::to_value(&view).unwrap(),
+            json!({
+                "motd": "A Minecraft Server",
+                "server_port": 25570,
+                "gamemode": "creative",
+                "difficulty": "normal",
+                "max_players": 42,
+                "online_mode": true,
+                "level_name": "world",
+            })
+        );
+
+        // Writing back what was read keeps every value
+        apply_changes(&mut properties, &serde_json::to_value(&view).unwrap()).unwrap();
+        assert_eq!(PropertiesView::from(&properties), view);
+        assert_eq!(properties.get("custom-plugin-key").as_deref(), Some("keep me"));
+
+        apply_changes(&mut properties, &json!({ "difficulty": "hard", "max_players": 10, "motd": "Welcome back" })).unwrap();
+        let saved = properties.to_string();
+        assert!(saved.starts_with("#Minecraft server properties\n#Sat Jun 01 12:00:00 UTC 2025\n"));
+        assert!(saved.contains("difficulty=hard\n"));
+        assert!(saved.contains("max-players=10\n"));
+        assert!(saved.contains("custom-plugin-key=keep me\n"));
+
+        let reloaded = PropertiesView::from(&ServerProperties::parse(&saved));
+        assert_eq!(reloaded.difficulty, "hard");
+        assert_eq!(reloaded.motd, "Welcome back");
+        assert_eq!(reloaded.gamemode, "creative");
+    }
+
+    #[test]
+    fn test_invalid_fields_are_rejected() {
+        let mut properties = ServerProperties::parse(PROPERTIES);
+        let errors = apply_changes(
+            &mut properties,
+            &json!({ "difficulty": "nightmare", "server_port": 70000, "max_players": 0, "level_name": " ", "motd": "Still valid", "pvp": true }),
+        )
+        .unwrap_err();
+
+        let mut fields: Vec<&str> = errors.iter().map(|error| error.field.as_str()).collect();
+        fields.sort();
+        assert_eq!(fields, vec!["difficulty", "level_name", "max_players", "pvp", "server_port"]);
+        assert_eq!(errors.iter().find(|error| error.field == "difficulty").unwrap().message, "Unknown difficulty: nightmare");
+
+        // Valid fields in a rejected update are not applied either
+        assert_eq!(properties, ServerProperties::parse(PROPERTIES));
+        assert!(apply_changes(&mut properties, &json!(["difficulty"])).is_err());
+    }
+}
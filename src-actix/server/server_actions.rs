@@ -1,5 +1,8 @@
 use crate::broadcast;
 use crate::broadcast::broadcast_data::BroadcastMessage;
+use crate::server::crash_alert;
+use crate::server::port_forwarding::ServerPortForwards;
+use crate::server::server_console::ServerConsole;
 use crate::server::server_data::ServerData;
 use crate::server::server_status::ServerStatus;
 use anyhow::Result;
@@ -8,6 +11,7 @@ use obsidian_upnp::{UpnpManager, PortMappingProtocol};
 use std::collections::HashMap;
 use std::sync::{Arc, OnceLock};
 use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::Mutex;
 use tokio_interactive::AsynchronousInteractiveProcess;
 
@@ -141,10 +145,13 @@ impl ServerData {
             None => return Err(anyhow::anyhow!("Server process not found after starting")),
         };
         let mut process = process;
+        let console = ServerConsole::get(self.id);
+        console.clear();
 
         loop {
             let line = process.receive_output().await?;
             if let Some(line) = line {
+                console.push(line.as_str());
                 if line.contains("Done (") && line.contains(r#")! For help, type "help""#) {
                     self.status = ServerStatus::Running;
                     self.save().await?;
@@ -175,6 +182,21 @@ impl ServerData {
             }
         }
 
+        // Keep reading the output for console clients until the process exits
+        tokio::spawn(async move {
+            loop {
+                match process.receive_output().await {
+                    Ok(Some(line)) => console.push(line),
+                    Ok(None) if !process.is_process_running().await => break,
+                    Ok(None) => tokio::time::sleep(Duration::from_millis(50)).await,
+                    Err(e) => {
+                        debug!("Stopped reading console output: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+
         Ok(())
     }
 
@@ -224,6 +246,8 @@ impl ServerData {
                 }
             }
         }
+        // And any ports opened on demand while it ran
+        ServerPortForwards::global().close(self.id).await;
 
         // Broadcast server status change
         broadcast::broadcast(BroadcastMessage::ServerUpdate {
@@ -256,6 +280,8 @@ impl ServerData {
                 }
             }
         }
+        // And any ports opened on demand while it ran
+        ServerPortForwards::global().close(self.id).await;
 
         // Broadcast server status change
         broadcast::broadcast(BroadcastMessage::ServerUpdate {
@@ -289,27 +315,26 @@ impl ServerData {
     }
 
     pub async fn attach_to_stdout(&self, sender: tokio::sync::mpsc::Sender<actix_web_lab::sse::Event>) -> Result<()> {
-        let servers = ACTIVE_SERVERS.get_or_init(|| Arc::new(Mutex::new(HashMap::new())));
-        let pid = {
-            let servers = servers.lock().await;
-            match servers.get(&self.id) {
-                Some(pid) => *pid,
-                None => return Err(anyhow::anyhow!("Server not running")),
-            }
-        };
-        let mut process = match AsynchronousInteractiveProcess::get_process_by_pid(pid).await {
-            Some(process) => process,
-            None => return Err(anyhow::anyhow!("Server process not found")),
-        };
+        if !self.has_server_process().await {
+            return Err(anyhow::anyhow!("Server not running"));
+        }
+        let (_, mut lines) = ServerConsole::get(self.id).subscribe();
 
         loop {
             // Add timeout to detect stale connections
-            let output_future = process.receive_output();
+            let output_future = lines.recv();
             let timeout_future = tokio::time::sleep(Duration::from_secs(30));
 
             tokio::select! {
                 line_result = output_future => {
-                    let line = line_result?;
+                    let line = match line_result {
+                        Ok(line) => Some(line),
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("Console client fell behind, skipped {} lines", skipped);
+                            None
+                        }
+                        Err(RecvError::Closed) => break,
+                    };
                     if let Some(line) = line {
                         debug!("Sending message to client: {}", line);
                         let message = actix_web_lab::sse::Data::new(line).event("console");
@@ -427,35 +452,11 @@ impl ServerData {
     }
 
     async fn send_crash_notification(&self) -> Result<()> {
-        use crate::notifications::{NotificationActionType, NotificationData, NotificationType};
-
-        let pool = crate::database::get_pool();
-        let server_id_hash = serde_hash::hashids::encode_single(self.id);
-
-        let notification = NotificationData::create(
-            format!("{} Crashed", self.name),
-            format!("Server \"{}\" has crashed unexpectedly.", self.name),
-            NotificationType::System,
-            NotificationActionType::RestartServer.to_bits() | NotificationActionType::ViewDetails.to_bits(),
-            Some(server_id_hash.clone()),
-            pool,
-        )
-        .await?;
-
-        // Broadcast to all connected users
-        let notification_item = crate::notifications::NotificationItem {
-            id: notification.id.clone(),
-            title: notification.title.clone(),
-            message: notification.message.clone(),
-            is_read: false,
-            timestamp: notification.timestamp,
-            notification_type: notification.notification_type,
-            action: notification.action,
-            referenced_server: Some(server_id_hash),
-        };
-
-        crate::notifications::broadcast_notification(notification_item).await;
+        // Only a report written since this run started belongs to this crash
+        let started = std::time::UNIX_EPOCH + Duration::from_secs(self.last_started.unwrap_or_default());
+        let summary = minecraft_server::crash::find_crash_report(&self.get_directory_path(), started).and_then(|report| report.summary);
 
+        crash_alert::alert_crash(self.id, &self.name, summary, crate::database::get_pool()).await?;
         Ok(())
     }
 }
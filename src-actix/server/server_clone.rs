@@ -0,0 +1,152 @@
+use crate::server::server_data::ServerData;
+use anyhow::{anyhow, Result};
+use log::debug;
+use minecraft_server::ServerProperties;
+use std::collections::HashSet;
+use std::net::TcpListener;
+use std::path::Path;
+
+/// Folders the server writes while it runs, which a copy starts without
+const SKIPPED_DIRECTORIES: [&str; 2] = ["logs", "crash-reports"];
+
+/// Copy the server in `source` to `destination` and give the copy a game port that isn't in
+/// `taken` or already in use, returning that port. A partial copy is removed if anything fails.
+pub fn clone_files(source: &Path, destination: &Path, taken: &HashSet<u16>) -> Result<u16> {
+    if destination.exists() {
+        return Err(anyhow!("{} already exists", destination.display()));
+    }
+    let result = copy_dir(source, destination, true).map_err(anyhow::Error::from).and_then(|_| assign_free_port(destination, taken));
+    if result.is_err() {
+        let _ = std::fs::remove_dir_all(destination);
+    }
+    result
+}
+
+/// The game ports set in each server's server.properties
+pub fn used_ports(servers: &[ServerData]) -> HashSet<u16> {
+    servers
+        .iter()
+        .map(|server| ServerProperties::load_from_dir(&server.get_directory_path()).unwrap_or_default().server_port())
+        .collect()
+}
+
+/// Copy a directory like a backup does: session.lock is held by a running server and
+/// recreated on start, so it is left behind, as are the server's logs. Symlinks are skipped so
+/// a link to a folder outside the server can't pull its files into the copy.
+fn copy_dir(source: &Path, destination: &Path, top_level: bool) -> std::io::Result<()> {
+    std::fs::create_dir_all(destination)?;
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            debug!("Skipping symlink in server clone: {}", entry.path().display());
+            continue;
+        }
+        if file_type.is_dir() {
+            if top_level && SKIPPED_DIRECTORIES.iter().any(|skipped| name == *skipped) {
+                continue;
+            }
+            copy_dir(&entry.path(), &destination.join(&name), false)?;
+        } else if name != "session.lock" {
+            std::fs::copy(entry.path(), destination.join(&name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Move the server in `directory` to the first free port after its current one
+fn assign_free_port(directory: &Path, taken: &HashSet<u16>) -> Result<u16> {
+    let mut properties = ServerProperties::load_from_dir(directory).unwrap_or_default();
+    let current = properties.server_port();
+    let port = (current.saturating_add(1)..=u16::MAX)
+        .chain(1024..current)
+        .find(|port| !taken.contains(port) && TcpListener::bind(("0.0.0.0", *port)).is_ok())
+        .ok_or(anyhow!("No free port left for the server"))?;
+    properties.set_server_port(port)?;
+    properties.save_to_dir(directory)?;
+    Ok(port)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::path::PathBuf;
+
+    /// A server directory with a world still holding its session.lock, a mod, a logs folder
+    /// and server.properties on port 25565
+    fn fixture(name: &str) -> PathBuf {
+        let dir = temp_dir(&format!("clone-{}", name));
+        std::fs::create_dir_all(dir.join("server/world/region")).unwrap();
+        std::fs::create_dir_all(dir.join("server/mods")).unwrap();
+        std::fs::create_dir_all(dir.join("server/logs")).unwrap();
+        std::fs::write(dir.join("server/world/level.dat"), "level").unwrap();
+        std::fs::write(dir.join("server/world/session.lock"), "").unwrap();
+        std::fs::write(dir.join("server/world/region/r.0.0.mca"), "region").unwrap();
+        std::fs::write(dir.join("server/mods/lithium.jar"), "mod").unwrap();
+        std::fs::write(dir.join("server/logs/latest.log"), "[Server thread/INFO]: Done").unwrap();
+        std::fs::write(dir.join("server/server.properties"), "motd=Survival\nserver-port=25565\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_clone_has_independent_files() {
+        let dir = fixture("files");
+        let (source, destination) = (dir.join("server"), dir.join("copy"));
+        clone_files(&source, &destination, &HashSet::new()).unwrap();
+
+        assert_eq!(std::fs::read_to_string(destination.join("world/level.dat")).unwrap(), "level");
+        assert_eq!(std::fs::read_to_string(destination.join("world/region/r.0.0.mca")).unwrap(), "region");
+        assert!(destination.join("mods/lithium.jar").is_file());
+        assert!(!destination.join("world/session.lock").exists());
+        assert!(!destination.join("logs").exists());
+
+        // Changing either server leaves the other alone
+        std::fs::write(destination.join("world/level.dat"), "changed").unwrap();
+        std::fs::remove_file(source.join("mods/lithium.jar")).unwrap();
+        assert_eq!(std::fs::read_to_string(source.join("world/level.dat")).unwrap(), "level");
+        assert!(destination.join("mods/lithium.jar").is_file());
+        assert!(clone_files(&source, &destination, &HashSet::new()).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_clone_skips_symlinks() {
+        let dir = fixture("symlinks");
+        let (source, destination) = (dir.join("server"), dir.join("copy"));
+        std::fs::create_dir_all(dir.join("outside")).unwrap();
+        std::fs::write(dir.join("outside/secret.txt"), "secret").unwrap();
+        std::os::unix::fs::symlink(dir.join("outside"), source.join("linked")).unwrap();
+        std::os::unix::fs::symlink(dir.join("outside/secret.txt"), source.join("world/secret.txt")).unwrap();
+
+        clone_files(&source, &destination, &HashSet::new()).unwrap();
+        assert!(destination.join("world/level.dat").is_file());
+        assert!(!destination.join("linked").exists());
+        assert!(!destination.join("world/secret.txt").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_clone_gets_a_distinct_port() {
+        let dir = fixture("port");
+        let (source, destination) = (dir.join("server"), dir.join("copy"));
+        // Another server already took the next port, and something is listening on the one after
+        let listener = TcpListener::bind(("0.0.0.0", 0)).unwrap();
+        let listening = listener.local_addr().unwrap().port();
+        std::fs::write(source.join("server.properties"), format!("motd=Survival\nserver-port={}\n", listening - 2)).unwrap();
+        let taken = HashSet::from([listening - 2, listening - 1]);
+
+        let port = clone_files(&source, &destination, &taken).unwrap();
+        assert!(port > listening);
+        let properties = ServerProperties::load_from_dir(&destination).unwrap();
+        assert_eq!(properties.server_port(), port);
+        assert_eq!(properties.get("motd").as_deref(), Some("Survival"));
+        assert_eq!(ServerProperties::load_from_dir(&source).unwrap().server_port(), listening - 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
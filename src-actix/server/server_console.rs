@@ -0,0 +1,76 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, LazyLock, Mutex};
+use tokio::sync::broadcast;
+
+/// Console lines kept per server to prime new console clients
+pub const CONSOLE_HISTORY_LINES: usize = 500;
+
+/// Lines a slow console client may fall behind by before it skips ahead
+const CONSOLE_CHANNEL_CAPACITY: usize = 256;
+
+static CONSOLES: LazyLock<Mutex<HashMap<u64, Arc<ServerConsole>>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Console output of a server: a ring buffer of recent lines, and a channel for lines as they
+/// are written. The output is read once, when the server starts, and shared by every client.
+pub struct ServerConsole {
+    history: Mutex<VecDeque<String>>,
+    capacity: usize,
+    sender: broadcast::Sender<String>,
+}
+
+impl ServerConsole {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(CONSOLE_CHANNEL_CAPACITY);
+        Self { history: Mutex::new(VecDeque::with_capacity(capacity)), capacity, sender }
+    }
+
+    /// The console of `server_id`, created on first use
+    pub fn get(server_id: u64) -> Arc<Self> {
+        CONSOLES.lock().unwrap().entry(server_id).or_insert_with(|| Arc::new(Self::new(CONSOLE_HISTORY_LINES))).clone()
+    }
+
+    pub fn push(&self, line: impl Into<String>) {
+        let line = line.into();
+        let mut history = self.history.lock().unwrap();
+        if history.len() == self.capacity {
+            history.pop_front();
+        }
+        history.push_back(line.clone());
+        // Sent while holding the history so `subscribe` never sees a line twice or misses one
+        let _ = self.sender.send(line);
+    }
+
+    /// Forget the output of a previous run
+    pub fn clear(&self) {
+        self.history.lock().unwrap().clear();
+    }
+
+    /// The buffered lines, and a receiver for every line written after them
+    pub fn subscribe(&self) -> (Vec<String>, broadcast::Receiver<String>) {
+        let history = self.history.lock().unwrap();
+        (history.iter().cloned().collect(), self.sender.subscribe())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_history_is_a_ring_buffer() {
+        let console = ServerConsole::new(3);
+        for i in 1..=4 {
+            console.push(format!("line {}", i));
+        }
+
+        let (history, mut lines) = console.subscribe();
+        assert_eq!(history, vec!["line 2", "line 3", "line 4"]);
+
+        console.push("line 5");
+        assert_eq!(lines.recv().await.unwrap(), "line 5");
+        assert_eq!(console.subscribe().0, vec!["line 3", "line 4", "line 5"]);
+
+        console.clear();
+        assert!(console.subscribe().0.is_empty());
+    }
+}
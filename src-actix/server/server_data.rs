@@ -1,4 +1,6 @@
 use crate::server::installed_mods::mod_data::ModData;
+use crate::server::installed_mods::mod_install;
+use crate::server::installed_mods::mod_install::ResolvedModFile;
 use crate::server::server_properties::ServerProperties;
 use crate::server::server_status::ServerStatus;
 use crate::server::server_status::ServerStatus::Idle;
@@ -208,6 +210,22 @@ impl ServerData {
         }
     }
 
+    /// A copy of this server's configuration under a new name and directory, owned by
+    /// `owner_id`. Call `create` to save it.
+    pub fn duplicate(&self, name: String, owner_id: u64) -> Self {
+        Self {
+            id: 0,
+            directory: Self::generate_directory_name(name.as_str()),
+            name,
+            status: Idle,
+            owner_id,
+            created_at: 0,
+            updated_at: 0,
+            last_started: None,
+            ..self.clone()
+        }
+    }
+
     pub fn get_directory_path(&self) -> PathBuf {
         let path = get_servers_directory().join(&self.directory);
         // Convert to absolute path to avoid issues with relative paths
@@ -305,13 +323,14 @@ impl ServerData {
             directory: self.get_directory_path(),
             java_executable: self.java_executable.clone(),
             java_args: self.java_args.clone(),
-            max_memory_gb: self.max_memory,
-            min_memory_gb: self.min_memory,
+            max_memory_mb: self.max_memory as u32 * 1024,
+            min_memory_mb: self.min_memory as u32 * 1024,
             minecraft_args: self.minecraft_args.clone(),
             server_jar: self.server_jar.clone(),
             minecraft_version: self.minecraft_version.clone().unwrap_or_default(),
             server_type,
             loader_version: self.loader_version.clone(),
+            ..Default::default()
         }
     }
 
@@ -358,10 +377,6 @@ impl ServerData {
         curseforge_id: Option<String>,
         icon: Option<String>,
     ) -> Result<ModData> {
-        // Create mods directory if it doesn't exist
-        let mods_dir = self.get_directory_path().join("mods");
-        std::fs::create_dir_all(&mods_dir)?;
-
         // Download the mod file
         let response = reqwest::get(download_url).await?;
         if !response.status().is_success() {
@@ -378,6 +393,33 @@ impl ServerData {
         let bytes = response.bytes().await?;
         tokio::fs::write(&temp_file_path, bytes).await?;
 
+        self.install_downloaded_mod(temp_file_path, filename, version, modrinth_id, curseforge_id, icon).await
+    }
+
+    /// Download a mod picked from a mod platform and install it, reporting download progress to
+    /// the action with `tracker_id`, if any
+    pub async fn install_mod_file(&self, file: &ResolvedModFile, tracker_id: Option<&str>) -> Result<ModData> {
+        let temp_dir = get_temp_directory().join(format!("{}/mods/", self.id));
+        let temp_file_path = mod_install::download_mod_file(file, &temp_dir, tracker_id).await?;
+        let filename = temp_file_path.file_name().map(|name| name.to_string_lossy().to_string()).ok_or(anyhow!("Invalid mod filename"))?;
+        self.install_downloaded_mod(temp_file_path, filename, Some(file.version.clone()), file.modrinth_id.clone(), file.curseforge_id.clone(), file.icon.clone())
+            .await
+    }
+
+    /// Record a mod downloaded to the temp directory in the database, then move it into the mods folder
+    async fn install_downloaded_mod(
+        &self,
+        temp_file_path: PathBuf,
+        filename: String,
+        version: Option<String>,
+        modrinth_id: Option<String>,
+        curseforge_id: Option<String>,
+        icon: Option<String>,
+    ) -> Result<ModData> {
+        // Create mods directory if it doesn't exist
+        let mods_dir = self.get_directory_path().join("mods");
+        std::fs::create_dir_all(&mods_dir)?;
+
         // Parse mod data from the downloaded file
         let mod_data = ModData::from_path(&temp_file_path).await?.ok_or_else(|| anyhow::anyhow!("Failed to parse mod data from downloaded file"))?;
 
@@ -20,6 +20,7 @@ static CREATE_BACKUPS_TABLE_SQL: &str = include_str!("../../resources/sql/postgr
 pub async fn initialize(pool: &Pool) -> Result<()> {
     pool.execute(CREATE_SERVER_TABLE_SQL).await?;
     pool.execute(CREATE_BACKUPS_TABLE_SQL).await?;
+    crate::server::templates::initialize(pool).await?;
     pool.execute(r#"UPDATE servers SET status = 0;"#).await?;
     Ok(())
 }
@@ -1,14 +1,31 @@
+use crate::actions::actions_data::{ActionData, ActionStatus, ActionType};
 use crate::actix_util::http_error::Result;
+use crate::audit;
+use crate::audit::AuditAction;
 use crate::authentication::auth_data::UserRequestExt;
+use crate::authentication::permission_guard;
+use crate::authentication::permission_guard::ServerRight;
 use crate::broadcast;
 use crate::broadcast::broadcast_data::BroadcastMessage;
+use crate::server::installed_mods::{mod_install, mod_updates};
+use crate::server::installed_mods::mod_install::ResolvedModFile;
 use crate::server::server_data::ServerData;
+use crate::server::server_metrics::METRICS_INTERVAL;
 use crate::server::server_status::ServerStatus;
-use crate::server::{backups, filesystem, updates};
+use crate::server::server_type::ServerType;
+use crate::server::console_socket::CommandSender;
+use crate::server::server_console::ServerConsole;
+use crate::server::log_tail::{LogLevel, DEFAULT_TAIL_LINES, LATEST_LOG, MAX_TAIL_LINES};
+use crate::server::properties_editor::{self, PropertiesView};
+use crate::server::online_players;
+use crate::server::port_forwarding::{ForwardedPorts, ServerPortForwards};
+use crate::server::whitelist_editor;
+use crate::server::{backups, bulk_actions, console_socket, disk_usage, filesystem, log_tail, server_clone, templates, updates};
 use crate::ICON;
-use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{delete, get, post, put, web, Either, HttpRequest, HttpResponse, Responder};
 use anyhow::anyhow;
 use base64::Engine as _;
+use futures::StreamExt;
 use flate2::read::GzDecoder;
 use log::error;
 use serde_hash::hashids::{decode_single, encode_single};
@@ -17,6 +34,7 @@ use sqlx::Row as _;
 use crate::database::sql;
 use std::collections::HashMap;
 use std::io::Read;
+use std::sync::Arc;
 use std::time::Duration;
 
 #[get("")]
@@ -122,12 +140,7 @@ pub async fn update_server(server_id: web::Path<String>, body: web::Json<ServerD
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
 
-    // Check if user has permission to operate servers (update configuration)
-    if !user.can_operate_server() {
-        return Ok(HttpResponse::Forbidden().json(json!({
-            "error": "You don't have permission to modify server configuration"
-        })));
-    }
+    permission_guard::require(&user, ServerRight::EditSettings)?;
 
     let server = ServerData::get(server_id, user_id).await?;
 
@@ -154,12 +167,7 @@ pub async fn start_server(server_id: web::Path<String>, req: HttpRequest) -> Res
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
 
-    // Check if user has permission to operate servers
-    if !user.can_operate_server() {
-        return Ok(HttpResponse::Forbidden().json(json!({
-            "error": "You don't have permission to start servers"
-        })));
-    }
+    permission_guard::require(&user, ServerRight::ControlServer)?;
 
     let mut server = ServerData::get(server_id, user_id).await?.expect("Server not found");
     if server.has_server_process().await {
@@ -167,6 +175,7 @@ pub async fn start_server(server_id: web::Path<String>, req: HttpRequest) -> Res
             "error": "Server is already running".to_string(),
         })));
     }
+    audit::record(user_id, AuditAction::ServerStart, encode_single(server.id)).await;
     tokio::spawn(async move {
         if let Err(e) = server.start_server().await {
             error!("Failed to start server {}: {}", server.name, e);
@@ -187,15 +196,11 @@ pub async fn stop_server(server_id: web::Path<String>, req: HttpRequest) -> Resu
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
 
-    // Check if user has permission to operate servers
-    if !user.can_operate_server() {
-        return Ok(HttpResponse::Forbidden().json(json!({
-            "error": "You don't have permission to stop servers"
-        })));
-    }
+    permission_guard::require(&user, ServerRight::ControlServer)?;
 
     let mut server = ServerData::get(server_id, user_id).await?.expect("Server not found");
     server.stop_server().await?;
+    audit::record(user_id, AuditAction::ServerStop, encode_single(server.id)).await;
     Ok(HttpResponse::Ok().finish())
 }
 
@@ -205,15 +210,11 @@ pub async fn restart_server(server_id: web::Path<String>, req: HttpRequest) -> R
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
 
-    // Check if user has permission to operate servers
-    if !user.can_operate_server() {
-        return Ok(HttpResponse::Forbidden().json(json!({
-            "error": "You don't have permission to restart servers"
-        })));
-    }
+    permission_guard::require(&user, ServerRight::ControlServer)?;
 
     let mut server = ServerData::get(server_id, user_id).await?.expect("Server not found");
     server.restart_server().await?;
+    audit::record(user_id, AuditAction::ServerRestart, encode_single(server.id)).await;
     Ok(HttpResponse::Ok().finish())
 }
 #[post("{server_id}/kill")]
@@ -222,15 +223,11 @@ pub async fn kill_server(server_id: web::Path<String>, req: HttpRequest) -> Resu
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
 
-    // Check if user has permission to operate servers
-    if !user.can_operate_server() {
-        return Ok(HttpResponse::Forbidden().json(json!({
-            "error": "You don't have permission to force kill servers"
-        })));
-    }
+    permission_guard::require(&user, ServerRight::ControlServer)?;
 
     let mut server = ServerData::get(server_id, user_id).await?.expect("Server not found");
     server.kill_server().await?;
+    audit::record(user_id, AuditAction::ServerKill, encode_single(server.id)).await;
     Ok(HttpResponse::Ok().finish())
 }
 
@@ -241,12 +238,7 @@ pub async fn send_command(server_id: web::Path<String>, body: web::Bytes, req: H
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
 
-    // Check if user has permission to operate servers
-    if !user.can_operate_server() {
-        return Ok(HttpResponse::Forbidden().json(json!({
-            "error": "You don't have permission to send commands to servers"
-        })));
-    }
+    permission_guard::require(&user, ServerRight::ControlServer)?;
 
     let server = ServerData::get(server_id, user_id).await?.expect("Server not found");
     server.send_command(body).await?;
@@ -254,6 +246,371 @@ pub async fn send_command(server_id: web::Path<String>, body: web::Bytes, req: H
     Ok(HttpResponse::Ok().finish())
 }
 
+#[post("{server_id}/rcon")]
+pub async fn send_rcon_command(server_id: web::Path<String>, body: web::Json<serde_json::Value>, req: HttpRequest) -> Result<impl Responder> {
+    use minecraft_server::{McServerError, RconClient, ServerProperties};
+
+    let server_id = decode_single(server_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    permission_guard::require(&user, ServerRight::ControlServer)?;
+
+    let command = body.get("command").and_then(|v| v.as_str()).ok_or(anyhow!("command is required"))?;
+
+    let server = match ServerData::get(server_id, user_id).await? {
+        Some(server) => server,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "Server not found".to_string()
+            })));
+        }
+    };
+
+    let properties = ServerProperties::load_from_dir(&server.get_directory_path()).unwrap_or_default();
+    let password = match properties.rcon_password() {
+        Some(password) if properties.rcon_enabled() => password,
+        _ => {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "error": "RCON is not enabled for this server, set enable-rcon and rcon.password in server.properties".to_string()
+            })));
+        }
+    };
+
+    let response = match RconClient::connect("127.0.0.1", properties.rcon_port(), &password).await {
+        Ok(mut client) => client.command(command).await,
+        Err(e) => Err(e),
+    };
+    match response {
+        Ok(response) => Ok(HttpResponse::Ok().json(json!({
+            "response": response
+        }))),
+        Err(McServerError::ServerNotReady) => Ok(HttpResponse::ServiceUnavailable().json(json!({
+            "error": "Server is not accepting RCON connections".to_string()
+        }))),
+        Err(McServerError::InvalidConfig(message)) => Ok(HttpResponse::BadRequest().json(json!({
+            "error": message
+        }))),
+        Err(e) => {
+            error!("RCON command failed for server {}: {}", server.name, e);
+            Ok(HttpResponse::BadGateway().json(json!({
+                "error": format!("RCON command failed: {}", e)
+            })))
+        }
+    }
+}
+
+/// GET /api/server/:id/players - The players online on a running server
+#[get("{server_id}/players")]
+pub async fn get_online_players(server_id: web::Path<String>, req: HttpRequest) -> Result<impl Responder> {
+    use minecraft_server::ServerProperties;
+
+    let server_id = decode_single(server_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    let server = match ServerData::get(server_id, user_id).await? {
+        Some(server) => server,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "Server not found".to_string()
+            })));
+        }
+    };
+
+    if !server.has_server_process().await {
+        return Ok(HttpResponse::Conflict().json(json!({
+            "error": "Server is offline".to_string(),
+            "offline": true
+        })));
+    }
+
+    let properties = ServerProperties::load_from_dir(&server.get_directory_path()).unwrap_or_default();
+    match online_players::query("127.0.0.1", &properties).await {
+        Ok(players) => Ok(HttpResponse::Ok().json(players)),
+        Err(e) => {
+            error!("Failed to list online players for server {}: {}", server.name, e);
+            Ok(HttpResponse::BadGateway().json(json!({
+                "error": format!("Failed to query online players: {}", e)
+            })))
+        }
+    }
+}
+
+/// GET /api/server/:id/disk-usage - Space taken by the server and left on its volume
+#[get("{server_id}/disk-usage")]
+pub async fn get_disk_usage(server_id: web::Path<String>, req: HttpRequest) -> Result<impl Responder> {
+    let server_id = decode_single(server_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    let server = match ServerData::get(server_id, user_id).await? {
+        Some(server) => server,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "Server not found".to_string()
+            })));
+        }
+    };
+
+    let usage = disk_usage::disk_usage(server.id, &server.get_directory_path()).await?;
+    Ok(HttpResponse::Ok().json(usage))
+}
+
+/// POST /api/server/:id/clone - Create a new server as a copy of this one.
+///
+/// The copy gets the server's configuration, worlds, mods and properties, a directory of its
+/// own and a free game port. Logs and session.lock files are left behind.
+#[post("{server_id}/clone")]
+pub async fn clone_server(server_id: web::Path<String>, body: web::Json<serde_json::Value>, req: HttpRequest) -> Result<impl Responder> {
+    let server_id = decode_single(server_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    if !user.can_create_server() {
+        return Ok(HttpResponse::Forbidden().json(json!({
+            "error": "You don't have permission to create servers"
+        })));
+    }
+    let name = body.get("name").and_then(|v| v.as_str()).map(str::trim).filter(|name| !name.is_empty()).ok_or(anyhow!("name is required"))?;
+
+    let source = match ServerData::get(server_id, user_id).await? {
+        Some(server) => server,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "Server not found".to_string()
+            })));
+        }
+    };
+
+    let pool = crate::database::get_pool();
+    let taken = server_clone::used_ports(&ServerData::list_all_with_pool(pool).await?);
+    let mut server = source.duplicate(name.to_string(), user_id);
+    let (from, to) = (source.get_directory_path(), server.get_directory_path());
+    let server_port = tokio::task::spawn_blocking(move || server_clone::clone_files(&from, &to, &taken)).await.map_err(|e| anyhow!("Clone failed: {}", e))??;
+    if let Err(e) = server.create(pool).await {
+        let _ = tokio::fs::remove_dir_all(server.get_directory_path()).await;
+        return Err(e.into());
+    }
+
+    broadcast::broadcast(BroadcastMessage::ServerUpdate {
+        server: server.clone(),
+    });
+
+    Ok(HttpResponse::Created().json(json!({
+        "message": "Server cloned successfully",
+        "server_id": encode_single(server.id),
+        "server_port": server_port,
+    })))
+}
+
+/// POST /api/server/:id/upnp - Open or close the server's ports on the router
+#[post("{server_id}/upnp")]
+pub async fn set_server_upnp(server_id: web::Path<String>, body: web::Json<serde_json::Value>, req: HttpRequest) -> Result<impl Responder> {
+    let server_id = decode_single(server_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    permission_guard::require(&user, ServerRight::ControlServer)?;
+
+    let enabled = body.get("enabled").and_then(|v| v.as_bool()).ok_or(anyhow!("enabled must be true or false"))?;
+
+    let server = match ServerData::get(server_id, user_id).await? {
+        Some(server) => server,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "Server not found".to_string()
+            })));
+        }
+    };
+
+    if !enabled {
+        ServerPortForwards::global().close(server.id).await;
+        return Ok(HttpResponse::Ok().json(ForwardedPorts::default()));
+    }
+
+    // Ports opened here are closed when the server stops, so it has to be running
+    if !server.has_server_process().await {
+        return Ok(HttpResponse::Conflict().json(json!({
+            "error": "Server is offline".to_string(),
+            "offline": true
+        })));
+    }
+
+    match ServerPortForwards::global().open(server.id, &server.get_directory_path()).await {
+        Ok(ports) => Ok(HttpResponse::Ok().json(ports)),
+        Err(e) => {
+            error!("Failed to forward ports for server {}: {}", server.name, e);
+            Ok(HttpResponse::BadGateway().json(json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+/// GET /api/server/:id/whitelist - The players in the server's whitelist.json
+#[get("{server_id}/whitelist")]
+pub async fn get_whitelist(server_id: web::Path<String>, req: HttpRequest) -> Result<impl Responder> {
+    use minecraft_server::Whitelist;
+
+    let server_id = decode_single(server_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    let server = match ServerData::get(server_id, user_id).await? {
+        Some(server) => server,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "Server not found".to_string()
+            })));
+        }
+    };
+
+    let whitelist = Whitelist::load(&server.get_directory_path()).map_err(|e| anyhow!("Failed to read whitelist.json: {}", e))?;
+    Ok(HttpResponse::Ok().json(whitelist.list()))
+}
+
+/// POST /api/server/:id/whitelist - Whitelist a player by name or UUID
+#[post("{server_id}/whitelist")]
+pub async fn add_to_whitelist(server_id: web::Path<String>, body: web::Json<serde_json::Value>, req: HttpRequest) -> Result<impl Responder> {
+    use minecraft_server::mojang::MojangClient;
+    use minecraft_server::McServerError;
+
+    let server_id = decode_single(server_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    permission_guard::require(&user, ServerRight::ControlServer)?;
+
+    let player = body.get("player").and_then(|v| v.as_str()).map(str::trim).filter(|p| !p.is_empty()).ok_or(anyhow!("player is required"))?;
+
+    let server = match ServerData::get(server_id, user_id).await? {
+        Some(server) => server,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "Server not found".to_string()
+            })));
+        }
+    };
+
+    let console = whitelist_console(&server).await;
+    match whitelist_editor::add(&server.get_directory_path(), player, MojangClient::default(), console.as_ref()).await {
+        Ok(entry) => Ok(HttpResponse::Ok().json(entry)),
+        Err(e) if matches!(e.downcast_ref::<McServerError>(), Some(McServerError::PlayerNotFound(_))) => {
+            Ok(HttpResponse::NotFound().json(json!({
+                "error": format!("No Minecraft player named {}", player)
+            })))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// DELETE /api/server/:id/whitelist - Remove a player from the whitelist by name or UUID
+#[delete("{server_id}/whitelist")]
+pub async fn remove_from_whitelist(server_id: web::Path<String>, body: web::Json<serde_json::Value>, req: HttpRequest) -> Result<impl Responder> {
+    let server_id = decode_single(server_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    permission_guard::require(&user, ServerRight::ControlServer)?;
+
+    let player = body.get("player").and_then(|v| v.as_str()).map(str::trim).filter(|p| !p.is_empty()).ok_or(anyhow!("player is required"))?;
+
+    let server = match ServerData::get(server_id, user_id).await? {
+        Some(server) => server,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "Server not found".to_string()
+            })));
+        }
+    };
+
+    let console = whitelist_console(&server).await;
+    match whitelist_editor::remove(&server.get_directory_path(), player, console.as_ref()).await? {
+        Some(entry) => Ok(HttpResponse::Ok().json(entry)),
+        None => Ok(HttpResponse::NotFound().json(json!({
+            "error": format!("{} is not whitelisted", player)
+        }))),
+    }
+}
+
+/// The console of the server while it is running, to apply whitelist changes live
+async fn whitelist_console(server: &ServerData) -> Option<CommandSender> {
+    if !server.has_server_process().await {
+        return None;
+    }
+    let server = server.clone();
+    Some(Arc::new(move |command| {
+        let server = server.clone();
+        Box::pin(async move { server.send_command(command).await })
+    }))
+}
+
+/// GET /api/server/:id/properties - The typed fields of the server's server.properties
+#[get("{server_id}/properties")]
+pub async fn get_server_properties(server_id: web::Path<String>, req: HttpRequest) -> Result<impl Responder> {
+    use minecraft_server::ServerProperties;
+
+    let server_id = decode_single(server_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    let server = match ServerData::get(server_id, user_id).await? {
+        Some(server) => server,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "Server not found".to_string()
+            })));
+        }
+    };
+
+    // Servers that have never run have no server.properties yet, which reads as Minecraft's defaults
+    let properties = ServerProperties::load_from_dir(&server.get_directory_path()).unwrap_or_default();
+    Ok(HttpResponse::Ok().json(PropertiesView::from(&properties)))
+}
+
+/// PUT /api/server/:id/properties - Change typed fields of server.properties, keeping comments
+/// and every other key. Takes effect the next time the server starts.
+#[put("{server_id}/properties")]
+pub async fn update_server_properties(server_id: web::Path<String>, body: web::Json<serde_json::Value>, req: HttpRequest) -> Result<impl Responder> {
+    use minecraft_server::ServerProperties;
+
+    let server_id = decode_single(server_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    permission_guard::require(&user, ServerRight::EditSettings)?;
+
+    let server = match ServerData::get(server_id, user_id).await? {
+        Some(server) => server,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "Server not found".to_string()
+            })));
+        }
+    };
+
+    let directory = server.get_directory_path();
+    let mut properties = if directory.join("server.properties").exists() {
+        ServerProperties::load_from_dir(&directory).map_err(|e| anyhow!("Failed to read server.properties: {}", e))?
+    } else {
+        ServerProperties::default()
+    };
+
+    if let Err(errors) = properties_editor::apply_changes(&mut properties, &body) {
+        let error = errors.iter().map(|error| format!("{}: {}", error.field, error.message)).collect::<Vec<_>>().join("; ");
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "message": "Invalid properties",
+            "error": error,
+            "errors": errors,
+        })));
+    }
+    std::fs::create_dir_all(&directory)?;
+    properties.save_to_dir(&directory).map_err(|e| anyhow!("Failed to write server.properties: {}", e))?;
+
+    Ok(HttpResponse::Ok().json(PropertiesView::from(&properties)))
+}
+
 #[get("{server_id}/console")]
 pub async fn get_console_out(server_id: web::Path<String>, req: HttpRequest) -> Result<impl Responder> {
     let (sender, receiver) = tokio::sync::mpsc::channel(100);
@@ -277,6 +634,72 @@ pub async fn get_console_out(server_id: web::Path<String>, req: HttpRequest) ->
         .with_retry_duration(Duration::from_secs(3))) // Add retry duration
 }
 
+/// GET /api/server/:id/console/ws - Live console over a WebSocket, primed with recent output;
+/// text frames sent by the client are run as console commands
+#[get("{server_id}/console/ws")]
+pub async fn console_ws(server_id: web::Path<String>, req: HttpRequest, stream: web::Payload) -> Result<impl Responder> {
+    let server_id = decode_single(server_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    permission_guard::require(&user, ServerRight::ControlServer)?;
+
+    let server = match ServerData::get(server_id, user_id).await? {
+        Some(server) => server,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "Server not found".to_string()
+            })));
+        }
+    };
+
+    let commands: CommandSender = Arc::new(move |command| {
+        let server = server.clone();
+        Box::pin(async move { server.send_command(command).await })
+    });
+    Ok(console_socket::start(ServerConsole::get(server_id), commands, &req, stream))
+}
+
+#[get("{server_id}/metrics")]
+pub async fn get_server_metrics(server_id: web::Path<String>, req: HttpRequest) -> Result<impl Responder> {
+    let server_id = decode_single(server_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    let server = match ServerData::get(server_id, user_id).await? {
+        Some(server) => server,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "Server not found".to_string()
+            })));
+        }
+    };
+
+    let (sender, receiver) = tokio::sync::mpsc::channel(16);
+    let mut metrics = server.watch_metrics(METRICS_INTERVAL);
+    tokio::spawn(async move {
+        while let Some(metrics) = metrics.recv().await {
+            let event = match actix_web_lab::sse::Data::new_json(&metrics) {
+                Ok(data) => data.event("metrics"),
+                Err(e) => {
+                    error!("Failed to serialize metrics for server {}: {}", server.name, e);
+                    continue;
+                }
+            };
+            // Dropping the metrics receiver when the client disconnects stops the sampler
+            if sender.send(event.into()).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(actix_web_lab::sse::Sse::from_infallible_receiver(receiver)
+        .with_keep_alive(Duration::from_secs(5))
+        .with_retry_duration(Duration::from_secs(3))
+        .respond_to(&req)
+        .map_into_boxed_body())
+}
+
 #[get("{server_id}/icon")]
 pub async fn get_server_icon(server_id: web::Path<String>, req: HttpRequest) -> impl Responder {
     let server_id = match decode_single(server_id.into_inner()) {
@@ -342,6 +765,81 @@ pub async fn get_log_files(server_id: web::Path<String>, req: HttpRequest) -> Re
     Ok(HttpResponse::Ok().json(files))
 }
 
+/// GET /api/server/:id/logs/tail?lines=&level=&follow= - The last lines of latest.log, optionally only
+/// those at or above a level. With `follow=true` the lines are sent over SSE, followed by new ones.
+#[get("{server_id}/logs/tail")]
+pub async fn tail_latest_log(server_id: web::Path<String>, query: web::Query<HashMap<String, String>>, req: HttpRequest) -> Result<Either<HttpResponse, impl Responder>> {
+    let server_id = decode_single(server_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    let count = match query.get("lines").map(|lines| lines.parse::<usize>()).transpose() {
+        Ok(count) => count.unwrap_or(DEFAULT_TAIL_LINES).min(MAX_TAIL_LINES),
+        Err(_) => {
+            return Ok(Either::Left(HttpResponse::BadRequest().json(json!({
+                "error": "lines must be a positive number"
+            }))));
+        }
+    };
+    let level = match query.get("level").filter(|level| !level.is_empty()) {
+        Some(level) => match LogLevel::parse(level) {
+            Some(level) => Some(level),
+            None => {
+                return Ok(Either::Left(HttpResponse::BadRequest().json(json!({
+                    "error": "level must be one of INFO, WARN or ERROR"
+                }))));
+            }
+        },
+        None => None,
+    };
+    let follow = query.get("follow").is_some_and(|follow| follow == "true");
+
+    let server = match ServerData::get(server_id, user_id).await? {
+        Some(server) => server,
+        None => {
+            return Ok(Either::Left(HttpResponse::NotFound().json(json!({
+                "error": "Server not found".to_string()
+            }))));
+        }
+    };
+
+    let log_directory = server.get_directory_path().join("logs");
+    let log_file_path = crate::actix_util::path_sanitize::ensure_path_within(&log_directory, LATEST_LOG)?;
+    if !log_file_path.is_file() {
+        return Ok(Either::Left(HttpResponse::NotFound().json(json!({
+            "error": "Log file not found".to_string(),
+        }))));
+    }
+
+    let (lines, offset) = log_tail::tail(&log_file_path, count, level)?;
+    if !follow {
+        return Ok(Either::Left(HttpResponse::Ok().json(json!({
+            "lines": lines
+        }))));
+    }
+
+    let (sender, receiver) = tokio::sync::mpsc::channel(100);
+    tokio::spawn(async move {
+        let mut lines = Box::pin(futures::stream::iter(lines).chain(log_tail::follow(log_file_path, offset, level)));
+        loop {
+            tokio::select! {
+                line = lines.next() => {
+                    let Some(line) = line else { break };
+                    if sender.send(actix_web_lab::sse::Data::new(line).event("log").into()).await.is_err() {
+                        break;
+                    }
+                }
+                // Stop polling the log once the client has gone
+                _ = sender.closed() => break,
+            }
+        }
+    });
+
+    Ok(Either::Right(
+        actix_web_lab::sse::Sse::from_infallible_receiver(receiver).with_keep_alive(Duration::from_secs(5)).with_retry_duration(Duration::from_secs(3)),
+    ))
+}
+
 #[get("{server_id}/logs/{log_file}")]
 pub async fn get_log_file_contents(path: web::Path<(String, String)>, req: HttpRequest) -> Result<impl Responder> {
     let (server_id, log_file) = path.into_inner();
@@ -426,11 +924,46 @@ pub async fn get_installed_mods(
     Ok(HttpResponse::Ok().json(mods))
 }
 
+/// GET /api/server/:id/mods/updates - Newer files for the installed mods, from Modrinth or
+/// CurseForge, that match the server's Minecraft version and loader
+#[get("{server_id}/mods/updates")]
+pub async fn get_mod_updates(server_id: web::Path<String>, req: HttpRequest) -> Result<impl Responder> {
+    let server_id = decode_single(server_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    let server = match ServerData::get(server_id, user_id).await? {
+        Some(server) => server,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "Server not found".to_string()
+            })));
+        }
+    };
+    let (minecraft_version, loaders) = match mod_install_target(&server) {
+        Ok(target) => target,
+        Err(response) => return Ok(response),
+    };
+
+    let mods = server.get_installed_mods().await?;
+    let updates = mod_updates::check_for_updates(
+        &server.get_directory_path().join("mods"),
+        &mods,
+        minecraft_version,
+        loaders,
+        crate::platforms::modrinth::get_client(),
+        crate::platforms::curseforge::get_client(),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(updates))
+}
+
 #[post("{server_id}/download-mod")]
 pub async fn download_mod(server_id: web::Path<String>, body: web::Json<serde_json::Value>, req: HttpRequest) -> Result<impl Responder> {
     let server_id = decode_single(server_id.into_inner())?;
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+    permission_guard::require(&user, ServerRight::CreateFiles)?;
 
     let download_url = body.get("download_url").and_then(|v| v.as_str()).ok_or(anyhow!("download_url is required"))?;
     let filename = body.get("filename").and_then(|v| v.as_str()).map(String::from).expect("filename is required");
@@ -454,6 +987,113 @@ pub async fn download_mod(server_id: web::Path<String>, body: web::Json<serde_js
     }
 }
 
+/// The Minecraft version and loaders to match mods against, or the response to send when the
+/// server cannot load mods
+fn mod_install_target(server: &ServerData) -> std::result::Result<(&str, &'static [&'static str]), HttpResponse> {
+    let (Some(minecraft_version), Some(server_type)) = (&server.minecraft_version, &server.server_type) else {
+        return Err(HttpResponse::BadRequest().json(json!({
+            "error": "The server's Minecraft version and type must be set to install mods"
+        })));
+    };
+    if *server_type == ServerType::Vanilla {
+        return Err(HttpResponse::BadRequest().json(json!({
+            "error": "Vanilla servers cannot load mods"
+        })));
+    }
+    Ok((minecraft_version.as_str(), mod_install::loader_names(server_type)))
+}
+
+/// Install a mod file resolved from a mod platform, completing the action tracking the install
+async fn install_resolved_mod(server: &ServerData, action: ActionData, file: anyhow::Result<ResolvedModFile>) -> HttpResponse {
+    let file = match file {
+        Ok(file) => file,
+        Err(e) => {
+            let _ = action.update_status(ActionStatus::Failed, Some(e.to_string())).await;
+            return HttpResponse::UnprocessableEntity().json(json!({
+                "error": format!("No compatible version found: {}", e)
+            }));
+        }
+    };
+
+    match server.install_mod_file(&file, Some(&action.tracker_id)).await {
+        Ok(mod_data) => {
+            let _ = action.update_status(ActionStatus::Completed, Some(file.filename.clone())).await;
+            HttpResponse::Ok().json(json!({
+                "status": "success",
+                "message": "Mod downloaded and installed successfully",
+                "tracker_id": action.tracker_id,
+                "file": file,
+                "mod": mod_data
+            }))
+        }
+        Err(e) => {
+            let _ = action.update_status(ActionStatus::Failed, Some(e.to_string())).await;
+            HttpResponse::InternalServerError().json(json!({
+                "status": "error",
+                "message": format!("Failed to install mod: {}", e)
+            }))
+        }
+    }
+}
+
+#[post("{server_id}/mods/install/modrinth")]
+pub async fn install_modrinth_mod(server_id: web::Path<String>, body: web::Json<serde_json::Value>, req: HttpRequest) -> Result<impl Responder> {
+    let server_id = decode_single(server_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+    permission_guard::require(&user, ServerRight::CreateFiles)?;
+
+    let project_id = body.get("project_id").and_then(|v| v.as_str()).ok_or(anyhow!("project_id is required"))?;
+    let version_id = body.get("version_id").and_then(|v| v.as_str());
+    let tracker_id = body.get("tracker_id").and_then(|v| v.as_str()).map(String::from).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    let server = match ServerData::get(server_id, user_id).await? {
+        Some(server) => server,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "Server not found".to_string()
+            })));
+        }
+    };
+    let (minecraft_version, loaders) = match mod_install_target(&server) {
+        Ok(target) => target,
+        Err(response) => return Ok(response),
+    };
+
+    let action = ActionData::create(user_id as i64, tracker_id, ActionType::ModDownload, Some(project_id.to_string())).await?;
+    let file = mod_install::resolve_modrinth_file(crate::platforms::modrinth::get_client(), project_id, version_id, minecraft_version, loaders).await;
+    Ok(install_resolved_mod(&server, action, file).await)
+}
+
+#[post("{server_id}/mods/install/curseforge")]
+pub async fn install_curseforge_mod(server_id: web::Path<String>, body: web::Json<serde_json::Value>, req: HttpRequest) -> Result<impl Responder> {
+    let server_id = decode_single(server_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+    permission_guard::require(&user, ServerRight::CreateFiles)?;
+
+    let mod_id = body.get("mod_id").and_then(|v| v.as_u64()).and_then(|v| u32::try_from(v).ok()).ok_or(anyhow!("mod_id is required"))?;
+    let file_id = body.get("file_id").and_then(|v| v.as_u64());
+    let tracker_id = body.get("tracker_id").and_then(|v| v.as_str()).map(String::from).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    let server = match ServerData::get(server_id, user_id).await? {
+        Some(server) => server,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "Server not found".to_string()
+            })));
+        }
+    };
+    let (minecraft_version, loaders) = match mod_install_target(&server) {
+        Ok(target) => target,
+        Err(response) => return Ok(response),
+    };
+
+    let action = ActionData::create(user_id as i64, tracker_id, ActionType::ModDownload, Some(mod_id.to_string())).await?;
+    let file = mod_install::resolve_curseforge_file(crate::platforms::curseforge::get_client(), mod_id, file_id, minecraft_version, loaders).await;
+    Ok(install_resolved_mod(&server, action, file).await)
+}
+
 #[post("{server_id}/sync-mods")]
 pub async fn sync_mods(server_id: web::Path<String>, req: HttpRequest) -> Result<impl Responder> {
     let server_id = decode_single(server_id.into_inner())?;
@@ -484,6 +1124,7 @@ pub async fn delete_mod(path: web::Path<(String, String)>, req: HttpRequest) ->
     let server_id = decode_single(server_id)?;
     let user = req.get_user()?;
     let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+    permission_guard::require(&user, ServerRight::DeleteFiles)?;
 
     let server = ServerData::get(server_id, user_id).await?.ok_or(anyhow!("Server not found"))?;
 
@@ -537,6 +1178,8 @@ pub async fn get_mod_icon(path: web::Path<(String, String)>, req: HttpRequest) -
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/server")
+            // Registered before the per-server routes so "templates" is not taken for a server ID
+            .configure(templates::configure)
             .service(
                 web::scope("/{server_id}")
                     .configure(filesystem::configure)
@@ -544,7 +1187,10 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
                     .configure(updates::configure)
             )
             .service(get_installed_mods)
+            .service(get_mod_updates)
             .service(download_mod)
+            .service(install_modrinth_mod)
+            .service(install_curseforge_mod)
             .service(sync_mods)
             .service(delete_mod)
             .service(get_mod_icon)
@@ -559,14 +1205,28 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .service(restart_server)
             .service(kill_server)
             .service(send_command)
+            .service(send_rcon_command)
+            .service(get_online_players)
+            .service(set_server_upnp)
+            .service(get_disk_usage)
+            .service(clone_server)
+            .service(get_whitelist)
+            .service(add_to_whitelist)
+            .service(remove_from_whitelist)
+            .service(get_server_properties)
+            .service(update_server_properties)
             .service(get_console_out)
+            .service(console_ws)
+            .service(get_server_metrics)
             .service(ping_server)
             .service(get_log_files)
+            .service(tail_latest_log)
             .service(get_log_file_contents)
             .default_service(web::to(|| async {
                 HttpResponse::NotFound().json(json!({
                     "error": "API endpoint not found".to_string(),
                 }))
             })),
-    );
+    )
+    .configure(bulk_actions::configure);
 }
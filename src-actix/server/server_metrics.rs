@@ -0,0 +1,182 @@
+use crate::broadcast;
+use crate::broadcast::broadcast_data::BroadcastMessage;
+use crate::server::server_actions::ACTIVE_SERVERS;
+use crate::server::server_data::ServerData;
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+use minecraft_server::{RconClient, ServerProperties};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{mpsc, Mutex};
+
+/// How often metrics are sampled for servers that are being watched
+pub const METRICS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Number of open metric streams per server ID; a server is only sampled while it has one
+static METRICS_WATCHERS: LazyLock<std::sync::Mutex<HashMap<u64, usize>>> = LazyLock::new(|| std::sync::Mutex::new(HashMap::new()));
+
+/// A snapshot of a running server's performance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerMetrics {
+    /// CPU usage of the server process tree, in percent of a single core
+    pub cpu_percent: f32,
+    /// Resident memory of the server process tree in bytes
+    pub memory_bytes: u64,
+    /// Ticks per second, only available when RCON is enabled
+    pub tps: Option<f64>,
+    /// Milliseconds per tick, only reported by some server types
+    pub mspt: Option<f64>,
+    /// Players currently online, if the server answered the status ping
+    pub online_players: Option<u32>,
+    /// Player limit, if the server answered the status ping
+    pub max_players: Option<u32>,
+    /// Time the sample was taken (seconds since epoch)
+    pub timestamp: u64,
+}
+
+impl ServerData {
+    /// Sample the resource usage, TPS, and player count of the running server
+    pub async fn get_metrics(&self) -> Result<ServerMetrics> {
+        let pid = {
+            let servers = ACTIVE_SERVERS.get_or_init(|| Arc::new(Mutex::new(HashMap::new())));
+            let servers = servers.lock().await;
+            *servers.get(&self.id).ok_or(anyhow!("Server not running"))?
+        };
+
+        let usage = minecraft_server::usage::process_tree_usage(pid).await?;
+        let ping = self.get_ping().await.ok();
+        let tps = self.query_tps().await;
+
+        Ok(ServerMetrics {
+            cpu_percent: usage.cpu_percent,
+            memory_bytes: usage.memory_bytes,
+            tps: tps.as_ref().map(|report| report.tps),
+            mspt: tps.and_then(|report| report.mspt),
+            online_players: ping.as_ref().map(|ping| ping.online_players as u32),
+            max_players: ping.map(|ping| ping.max_players as u32),
+            timestamp: chrono::Utc::now().timestamp() as u64,
+        })
+    }
+
+    /// Ask the server for its tick rate over RCON, which returns only the command's own output
+    async fn query_tps(&self) -> Option<minecraft_server::TpsReport> {
+        let config = self.to_server_config();
+        let command = minecraft_server::tps::tps_command(&config.server_type)?;
+        let properties = ServerProperties::load_from_dir(&config.directory).ok()?;
+        let password = properties.rcon_password().filter(|_| properties.rcon_enabled())?;
+
+        let mut client = RconClient::connect("127.0.0.1", properties.rcon_port(), &password).await.ok()?;
+        let output = client.command(command).await.ok()?;
+        let lines: Vec<String> = output.lines().map(str::to_string).collect();
+        minecraft_server::tps::parse_tps_output(&config.server_type, &lines)
+    }
+
+    /// Stream metrics for this server, sampled every `interval` and delivered through the
+    /// broadcast channel.
+    ///
+    /// All streams for the same server share one sampler, which stops when the last
+    /// receiver is dropped.
+    pub fn watch_metrics(&self, interval: Duration) -> mpsc::Receiver<ServerMetrics> {
+        let (sender, receiver) = mpsc::channel(16);
+        // Subscribe before the sampler starts so the first sample is not missed
+        let mut updates = broadcast::subscribe();
+        let server_id = serde_hash::hashids::encode_single(self.id);
+
+        let first_watcher = {
+            let mut watchers = METRICS_WATCHERS.lock().unwrap();
+            let count = watchers.entry(self.id).or_insert(0);
+            *count += 1;
+            *count == 1
+        };
+        if first_watcher {
+            tokio::spawn(sample_metrics(self.clone(), interval));
+        }
+
+        let id = self.id;
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    message = updates.recv() => match message {
+                        Ok(BroadcastMessage::ServerMetrics { server_id: message_server_id, metrics }) if message_server_id == server_id => {
+                            if sender.send(metrics).await.is_err() {
+                                break;
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("Metrics stream fell behind, skipped {} messages", skipped);
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                    _ = sender.closed() => break,
+                }
+            }
+
+            let mut watchers = METRICS_WATCHERS.lock().unwrap();
+            if let Some(count) = watchers.get_mut(&id) {
+                *count -= 1;
+                if *count == 0 {
+                    watchers.remove(&id);
+                }
+            }
+        });
+
+        receiver
+    }
+}
+
+/// Broadcast a metrics sample for `server` every `interval` while it has watchers
+async fn sample_metrics(server: ServerData, interval: Duration) {
+    let server_id = serde_hash::hashids::encode_single(server.id);
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        if !METRICS_WATCHERS.lock().unwrap().contains_key(&server.id) {
+            debug!("No metrics watchers left for server {}, stopping sampler", server.name);
+            break;
+        }
+        match server.get_metrics().await {
+            Ok(metrics) => {
+                broadcast::broadcast(BroadcastMessage::ServerMetrics { server_id: server_id.clone(), metrics });
+            }
+            Err(e) => debug!("Skipping metrics sample for server {}: {}", server.name, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_watch_metrics_emits_periodic_frames() {
+        // Track the test process itself as the "server" process so usage can be sampled
+        let server = ServerData { id: 4242, name: "Metrics Test".to_string(), ..ServerData::default() };
+        let other = ServerData { id: 4243, ..ServerData::default() };
+        ACTIVE_SERVERS.get_or_init(|| Arc::new(Mutex::new(HashMap::new()))).lock().await.insert(server.id, std::process::id());
+
+        let mut receiver = server.watch_metrics(Duration::from_millis(300));
+        // Samples for other servers must not reach this stream
+        broadcast::broadcast(BroadcastMessage::ServerMetrics {
+            server_id: serde_hash::hashids::encode_single(other.id),
+            metrics: ServerMetrics { cpu_percent: 0.0, memory_bytes: 0, tps: Some(20.0), mspt: None, online_players: None, max_players: None, timestamp: 0 },
+        });
+
+        let mut timestamps = Vec::new();
+        for _ in 0..2 {
+            let metrics = tokio::time::timeout(Duration::from_secs(10), receiver.recv()).await.expect("no metrics frame within 10s").expect("metrics stream closed");
+            assert!(metrics.memory_bytes > 0);
+            assert_eq!(metrics.tps, None);
+            assert_eq!(metrics.online_players, None);
+            timestamps.push(metrics.timestamp);
+        }
+        assert!(timestamps[0] > 0 && timestamps[1] >= timestamps[0]);
+
+        drop(receiver);
+        ACTIVE_SERVERS.get().unwrap().lock().await.remove(&server.id);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod template_data;
+pub mod template_db;
+pub mod template_endpoint;
+
+pub use template_db::initialize;
+pub use template_endpoint::configure;
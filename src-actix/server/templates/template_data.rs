@@ -0,0 +1,273 @@
+use crate::database::Row;
+use crate::server::installed_mods::mod_data::ModData;
+use crate::server::server_data::ServerData;
+use crate::server::server_type::ServerType;
+use serde::{Deserialize, Serialize};
+use serde_hash::serde_hash;
+use sqlx::{Error, FromRow, Row as _};
+use std::collections::BTreeMap;
+
+/// Properties that belong to one server rather than its configuration, so they are not copied
+/// into templates. Copying ports would stop two servers from the same template running at once.
+const INSTANCE_PROPERTIES: &[&str] = &["server-port", "query.port", "rcon.port", "rcon.password", "server-ip", "level-seed"];
+
+/// A mod to install on servers created from a template
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateMod {
+    pub name: String,
+    pub version: String,
+    pub filename: String,
+    pub modrinth_id: Option<String>,
+    pub curseforge_id: Option<String>,
+}
+
+impl From<&ModData> for TemplateMod {
+    fn from(mod_data: &ModData) -> Self {
+        Self {
+            name: mod_data.name.clone(),
+            version: mod_data.version.clone(),
+            filename: mod_data.filename.clone(),
+            modrinth_id: mod_data.modrinth_id.clone(),
+            curseforge_id: mod_data.curseforge_id.clone(),
+        }
+    }
+}
+
+impl TemplateMod {
+    /// Whether the mod can be downloaded again from a mod platform
+    pub fn is_installable(&self) -> bool {
+        self.modrinth_id.is_some() || self.curseforge_id.is_some()
+    }
+}
+
+/// A saved server configuration that new servers can be created from
+#[serde_hash]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerTemplate {
+    #[serde(hash)]
+    pub id: u64,
+    /// Name of the template, e.g. 'Fabric 1.21 survival'
+    pub name: String,
+    pub description: Option<String>,
+    pub server_type: Option<ServerType>,
+    pub minecraft_version: Option<String>,
+    pub loader_version: Option<String>,
+    pub java_executable: String,
+    pub java_args: String,
+    pub max_memory: u8,
+    pub min_memory: u8,
+    pub minecraft_args: String,
+    pub auto_restart: bool,
+    pub backup_enabled: bool,
+    pub backup_cron: String,
+    pub backup_retention: u32,
+    /// Contents of server.properties, without the per-server properties
+    pub properties: BTreeMap<String, String>,
+    pub mods: Vec<TemplateMod>,
+    #[serde(hash)]
+    pub owner_id: u64,
+    /// Timestamp of when the template was created (seconds since epoch)
+    pub created_at: u64,
+}
+
+impl<'a> FromRow<'a, Row> for ServerTemplate {
+    fn from_row(row: &'a Row) -> Result<Self, Error> {
+        let server_type: Option<i32> = row.try_get("server_type")?;
+        let max_memory: i16 = row.try_get("max_memory")?;
+        let min_memory: i16 = row.try_get("min_memory")?;
+        let auto_restart: i16 = row.try_get("auto_restart")?;
+        let backup_enabled: i16 = row.try_get("backup_enabled")?;
+        let backup_retention: i32 = row.try_get("backup_retention")?;
+        let properties: String = row.try_get("properties")?;
+        let mods: String = row.try_get("mods")?;
+        let created_at: i64 = row.try_get("created_at")?;
+
+        Ok(ServerTemplate {
+            id: row.try_get::<i64, _>("id")? as u64,
+            name: row.try_get("name")?,
+            description: row.try_get("description")?,
+            server_type: server_type.map(|t| ServerType::from(t as u8)),
+            minecraft_version: row.try_get("minecraft_version")?,
+            loader_version: row.try_get("loader_version")?,
+            java_executable: row.try_get("java_executable")?,
+            java_args: row.try_get("java_args")?,
+            max_memory: max_memory as u8,
+            min_memory: min_memory as u8,
+            minecraft_args: row.try_get("minecraft_args")?,
+            auto_restart: auto_restart != 0,
+            backup_enabled: backup_enabled != 0,
+            backup_cron: row.try_get("backup_cron")?,
+            backup_retention: backup_retention as u32,
+            properties: serde_json::from_str(&properties).unwrap_or_default(),
+            mods: serde_json::from_str(&mods).unwrap_or_default(),
+            owner_id: row.try_get::<i64, _>("owner_id")? as u64,
+            created_at: created_at as u64,
+        })
+    }
+}
+
+impl ServerTemplate {
+    /// Capture the configuration of `server`, along with the contents of its server.properties
+    /// and its installed mods
+    pub fn from_server(name: String, description: Option<String>, server: &ServerData, properties: &str, mods: &[ModData], owner_id: u64) -> Self {
+        Self {
+            id: 0,
+            name,
+            description,
+            server_type: server.server_type.clone(),
+            minecraft_version: server.minecraft_version.clone(),
+            loader_version: server.loader_version.clone(),
+            java_executable: server.java_executable.clone(),
+            java_args: server.java_args.clone(),
+            max_memory: server.max_memory,
+            min_memory: server.min_memory,
+            minecraft_args: server.minecraft_args.clone(),
+            auto_restart: server.auto_restart,
+            backup_enabled: server.backup_enabled,
+            backup_cron: server.backup_cron.clone(),
+            backup_retention: server.backup_retention,
+            properties: parse_properties(properties),
+            mods: mods.iter().map(TemplateMod::from).collect(),
+            owner_id,
+            created_at: chrono::Utc::now().timestamp() as u64,
+        }
+    }
+
+    /// A new server named `name` with this template's configuration.
+    ///
+    /// The server still has to be created in the database, and its properties and mods written
+    /// to its directory.
+    pub fn instantiate(&self, name: String, owner_id: u64) -> ServerData {
+        let mut server = ServerData::new(
+            name,
+            self.server_type.clone().unwrap_or(ServerType::Vanilla),
+            self.minecraft_version.clone().unwrap_or_default(),
+            self.loader_version.clone(),
+            self.java_executable.clone(),
+            owner_id,
+        );
+        server.server_type = self.server_type.clone();
+        server.minecraft_version = self.minecraft_version.clone();
+        server.java_args = self.java_args.clone();
+        server.max_memory = self.max_memory;
+        server.min_memory = self.min_memory;
+        server.minecraft_args = self.minecraft_args.clone();
+        server.auto_restart = self.auto_restart;
+        server.backup_enabled = self.backup_enabled;
+        server.backup_cron = self.backup_cron.clone();
+        server.backup_retention = self.backup_retention;
+        server
+    }
+
+    /// The server.properties file for servers created from this template
+    pub fn properties_file(&self) -> String {
+        let mut contents = format!("# Created from template '{}'\n", self.name);
+        for (key, value) in &self.properties {
+            contents.push_str(&format!("{}={}\n", key, value));
+        }
+        contents
+    }
+}
+
+/// Read the shareable properties from the contents of a server.properties file
+pub fn parse_properties(contents: &str) -> BTreeMap<String, String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .filter(|(key, _)| !INSTANCE_PROPERTIES.contains(&key.as_str()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROPERTIES: &str = "#Minecraft server properties\n#Mon Jan 01 00:00:00 UTC 2024\nmotd=A Fabric Server\nserver-port=25566\ndifficulty=hard\nmax-players=8\nrcon.password=hunter2\nlevel-seed=12345\nwhite-list=true\n";
+
+    fn mod_data(name: &str, modrinth_id: Option<&str>) -> ModData {
+        ModData {
+            mod_id: name.to_lowercase(),
+            name: name.to_string(),
+            description: String::new(),
+            version: "1.0.0".to_string(),
+            authors: vec![],
+            icon: None,
+            modrinth_id: modrinth_id.map(String::from),
+            curseforge_id: None,
+            filename: format!("{}.jar", name.to_lowercase()),
+        }
+    }
+
+    fn source_server() -> ServerData {
+        ServerData {
+            id: 3,
+            name: "Survival".to_string(),
+            directory: "survival".to_string(),
+            java_executable: "/usr/lib/jvm/java-21/bin/java".to_string(),
+            java_args: "-XX:+UseG1GC".to_string(),
+            max_memory: 8,
+            min_memory: 4,
+            minecraft_args: "nogui".to_string(),
+            server_jar: "fabric-server-launch.jar".to_string(),
+            auto_start: true,
+            auto_restart: true,
+            backup_enabled: true,
+            backup_cron: "0 0 */6 * * *".to_string(),
+            backup_retention: 14,
+            minecraft_version: Some("1.21.1".to_string()),
+            server_type: Some(ServerType::Fabric),
+            loader_version: Some("0.16.5".to_string()),
+            owner_id: 1,
+            ..ServerData::default()
+        }
+    }
+
+    #[test]
+    fn test_properties_skip_instance_values() {
+        let properties = parse_properties(PROPERTIES);
+        assert_eq!(properties.get("motd").map(String::as_str), Some("A Fabric Server"));
+        assert_eq!(properties.get("difficulty").map(String::as_str), Some("hard"));
+        assert_eq!(properties.len(), 4);
+        for key in INSTANCE_PROPERTIES {
+            assert!(!properties.contains_key(*key));
+        }
+    }
+
+    #[test]
+    fn test_instantiated_server_matches_template() {
+        let mods = [mod_data("Sodium", Some("AANobbMI")), mod_data("Handmade", None)];
+        let template = ServerTemplate::from_server("Fabric survival".to_string(), None, &source_server(), PROPERTIES, &mods, 1);
+        assert_eq!(template.mods.len(), 2);
+        assert!(template.mods[0].is_installable());
+        assert!(!template.mods[1].is_installable());
+
+        // Templates are stored as JSON in the database
+        let stored: BTreeMap<String, String> = serde_json::from_str(&serde_json::to_string(&template.properties).unwrap()).unwrap();
+        assert_eq!(stored, template.properties);
+
+        let server = template.instantiate("Survival 2".to_string(), 2);
+        let source = source_server();
+        assert_eq!(server.id, 0);
+        assert_eq!(server.name, "Survival 2");
+        assert_eq!(server.owner_id, 2);
+        assert_eq!(server.server_type, source.server_type);
+        assert_eq!(server.minecraft_version, source.minecraft_version);
+        assert_eq!(server.loader_version, source.loader_version);
+        assert_eq!(server.java_executable, source.java_executable);
+        assert_eq!(server.java_args, source.java_args);
+        assert_eq!((server.max_memory, server.min_memory), (8, 4));
+        assert_eq!(server.backup_cron, source.backup_cron);
+        assert_eq!(server.backup_retention, 14);
+        // The jar is installed separately, like for any new server
+        assert_eq!(server.server_jar, "");
+        assert!(!server.auto_start);
+
+        let properties = template.properties_file();
+        assert_eq!(parse_properties(&properties), template.properties);
+        assert!(properties.contains("white-list=true\n"));
+        assert!(!properties.contains("server-port"));
+    }
+}
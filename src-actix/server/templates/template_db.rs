@@ -0,0 +1,85 @@
+use super::template_data::ServerTemplate;
+use crate::database::{Pool, sql};
+use anyhow::Result;
+use sqlx::Executor;
+
+#[cfg(feature = "sqlite")]
+static CREATE_TEMPLATES_TABLE_SQL: &str = include_str!("../../../resources/sql/sqlite/server_templates.sql");
+#[cfg(feature = "mysql")]
+static CREATE_TEMPLATES_TABLE_SQL: &str = include_str!("../../../resources/sql/mysql/server_templates.sql");
+#[cfg(feature = "postgres")]
+static CREATE_TEMPLATES_TABLE_SQL: &str = include_str!("../../../resources/sql/postgres/server_templates.sql");
+
+pub async fn initialize(pool: &Pool) -> Result<()> {
+    pool.execute(CREATE_TEMPLATES_TABLE_SQL).await?;
+    Ok(())
+}
+
+impl ServerTemplate {
+    pub async fn create(&mut self, pool: &Pool) -> Result<()> {
+        let result = sqlx::query(
+            &*sql(r#"INSERT INTO server_templates (name, description, server_type, minecraft_version, loader_version, java_executable, java_args, max_memory, min_memory, minecraft_args, auto_restart, backup_enabled, backup_cron, backup_retention, properties, mods, owner_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#),
+        )
+        .bind(&self.name)
+        .bind(self.description.as_deref())
+        .bind(&self.server_type)
+        .bind(self.minecraft_version.as_deref())
+        .bind(self.loader_version.as_deref())
+        .bind(&self.java_executable)
+        .bind(&self.java_args)
+        .bind(self.max_memory as i16)
+        .bind(self.min_memory as i16)
+        .bind(&self.minecraft_args)
+        .bind(self.auto_restart)
+        .bind(self.backup_enabled)
+        .bind(&self.backup_cron)
+        .bind(self.backup_retention as i32)
+        .bind(serde_json::to_string(&self.properties)?)
+        .bind(serde_json::to_string(&self.mods)?)
+        .bind(self.owner_id as i64)
+        .execute(pool)
+        .await?;
+
+        #[cfg(feature = "sqlite")]
+        {
+            self.id = result.last_insert_rowid() as u64;
+        }
+        #[cfg(feature = "mysql")]
+        {
+            self.id = result.last_insert_id();
+        }
+        #[cfg(feature = "postgres")]
+        {
+            let id: (i64,) = sqlx::query_as(&*sql("SELECT currval(pg_get_serial_sequence('server_templates', 'id'))")).fetch_one(pool).await?;
+            self.id = id.0 as u64;
+        }
+        Ok(())
+    }
+
+    /// Templates created by `owner_id`, newest first
+    pub async fn list(owner_id: u64, pool: &Pool) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as(&*sql(r#"SELECT * FROM server_templates WHERE owner_id = ? ORDER BY created_at DESC"#))
+            .bind(owner_id as i64)
+            .fetch_all(pool)
+            .await?)
+    }
+
+    pub async fn get(id: u64, owner_id: u64, pool: &Pool) -> Result<Option<Self>> {
+        Ok(sqlx::query_as(&*sql(r#"SELECT * FROM server_templates WHERE id = ? AND owner_id = ?"#))
+            .bind(id as i64)
+            .bind(owner_id as i64)
+            .fetch_optional(pool)
+            .await?)
+    }
+
+    /// Delete one of `owner_id`'s templates, returning whether it existed
+    pub async fn delete(id: u64, owner_id: u64, pool: &Pool) -> Result<bool> {
+        let result = sqlx::query(&*sql(r#"DELETE FROM server_templates WHERE id = ? AND owner_id = ?"#))
+            .bind(id as i64)
+            .bind(owner_id as i64)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,175 @@
+use super::template_data::{ServerTemplate, TemplateMod};
+use crate::actions::actions_data::{ActionData, ActionStatus, ActionType};
+use crate::actix_util::http_error::Result;
+use crate::authentication::auth_data::UserRequestExt;
+use crate::broadcast;
+use crate::broadcast::broadcast_data::BroadcastMessage;
+use crate::server::installed_mods::mod_install;
+use crate::server::server_data::ServerData;
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
+use anyhow::anyhow;
+use log::error;
+use serde_hash::hashids::{decode_single, encode_single};
+use serde_json::json;
+use std::collections::HashMap;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::scope("/templates").service(list_templates).service(create_template).service(delete_template))
+        .service(create_server_from_template);
+}
+
+/// GET /api/server/templates - List the user's server templates
+#[get("")]
+async fn list_templates(req: HttpRequest) -> Result<impl Responder> {
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    let templates = ServerTemplate::list(user_id, crate::database::get_pool()).await?;
+    Ok(HttpResponse::Ok().json(templates))
+}
+
+/// POST /api/server/templates - Save a server's configuration, properties and mods as a template
+#[post("")]
+async fn create_template(body: web::Json<serde_json::Value>, req: HttpRequest) -> Result<impl Responder> {
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    if !user.can_create_server() {
+        return Ok(HttpResponse::Forbidden().json(json!({
+            "error": "You don't have permission to create servers"
+        })));
+    }
+
+    let server_id = body.get("server_id").and_then(|v| v.as_str()).ok_or(anyhow!("server_id is required"))?;
+    let server_id = decode_single(server_id)?;
+    let name = body.get("name").and_then(|v| v.as_str()).map(str::trim).filter(|name| !name.is_empty()).ok_or(anyhow!("name is required"))?;
+    let description = body.get("description").and_then(|v| v.as_str()).map(String::from);
+
+    let server = match ServerData::get(server_id, user_id).await? {
+        Some(server) => server,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "Server not found".to_string()
+            })));
+        }
+    };
+
+    let properties = tokio::fs::read_to_string(server.get_directory_path().join("server.properties")).await.unwrap_or_default();
+    let mods = server.get_installed_mods().await?;
+
+    let mut template = ServerTemplate::from_server(name.to_string(), description, &server, &properties, &mods, user_id);
+    template.create(crate::database::get_pool()).await?;
+
+    Ok(HttpResponse::Created().json(template))
+}
+
+/// DELETE /api/server/templates/:id - Delete one of the user's templates
+#[delete("{template_id}")]
+async fn delete_template(template_id: web::Path<String>, req: HttpRequest) -> Result<impl Responder> {
+    let template_id = decode_single(template_id.into_inner())?;
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    if ServerTemplate::delete(template_id, user_id, crate::database::get_pool()).await? {
+        Ok(HttpResponse::Ok().finish())
+    } else {
+        Ok(HttpResponse::NotFound().json(json!({
+            "error": "Template not found"
+        })))
+    }
+}
+
+/// POST /api/server?from_template=:id - Create a server from a template.
+///
+/// The server is created right away; mods from Modrinth or CurseForge are then installed in the
+/// background, with progress reported through the returned tracker ID.
+#[post("")]
+async fn create_server_from_template(
+    query: web::Query<HashMap<String, String>>,
+    body: web::Json<serde_json::Value>,
+    req: HttpRequest,
+) -> Result<impl Responder> {
+    let user = req.get_user()?;
+    let user_id = user.id.ok_or(anyhow!("User ID not found"))?;
+
+    if !user.can_create_server() {
+        return Ok(HttpResponse::Forbidden().json(json!({
+            "error": "You don't have permission to create servers"
+        })));
+    }
+
+    let template_id = query.get("from_template").ok_or(anyhow!("from_template is required"))?;
+    let template_id = decode_single(template_id)?;
+    let name = body.get("name").and_then(|v| v.as_str()).map(str::trim).filter(|name| !name.is_empty()).ok_or(anyhow!("name is required"))?;
+
+    let pool = crate::database::get_pool();
+    let Some(template) = ServerTemplate::get(template_id, user_id, pool).await? else {
+        return Ok(HttpResponse::NotFound().json(json!({
+            "error": "Template not found"
+        })));
+    };
+
+    let mut server = template.instantiate(name.to_string(), user_id);
+    server.create(pool).await?;
+
+    std::fs::create_dir_all(server.get_directory_path())?;
+    if !template.properties.is_empty() {
+        tokio::fs::write(server.get_directory_path().join("server.properties"), template.properties_file()).await?;
+    }
+
+    broadcast::broadcast(BroadcastMessage::ServerUpdate {
+        server: server.clone(),
+    });
+
+    let (mods, skipped): (Vec<TemplateMod>, Vec<TemplateMod>) = template.mods.iter().cloned().partition(TemplateMod::is_installable);
+    let tracker_id = if mods.is_empty() {
+        None
+    } else {
+        let tracker_id = uuid::Uuid::new_v4().to_string();
+        let action = ActionData::create(user_id as i64, tracker_id.clone(), ActionType::ModDownload, Some(template.name.clone())).await?;
+        let (server, minecraft_version) = (server.clone(), template.minecraft_version.clone().unwrap_or_default());
+        tokio::spawn(async move {
+            install_template_mods(&server, &minecraft_version, &mods, action).await;
+        });
+        Some(tracker_id)
+    };
+
+    Ok(HttpResponse::Created().json(json!({
+        "message": "Server created successfully",
+        "server_id": encode_single(server.id),
+        "tracker_id": tracker_id,
+        "skipped_mods": skipped.iter().map(|mod_data| &mod_data.name).collect::<Vec<_>>(),
+    })))
+}
+
+/// Install a template's mods on a new server, picking the versions that match the server
+async fn install_template_mods(server: &ServerData, minecraft_version: &str, mods: &[TemplateMod], action: ActionData) {
+    let loaders = server.server_type.as_ref().map(mod_install::loader_names).unwrap_or_default();
+    let mut failed = Vec::new();
+
+    for (index, template_mod) in mods.iter().enumerate() {
+        let file = if let Some(modrinth_id) = &template_mod.modrinth_id {
+            mod_install::resolve_modrinth_file(crate::platforms::modrinth::get_client(), modrinth_id, None, minecraft_version, loaders).await
+        } else if let Some(curseforge_id) = template_mod.curseforge_id.as_ref().and_then(|id| id.parse().ok()) {
+            mod_install::resolve_curseforge_file(crate::platforms::curseforge::get_client(), curseforge_id, None, minecraft_version, loaders).await
+        } else {
+            Err(anyhow!("No valid mod platform ID"))
+        };
+
+        let result = match file {
+            Ok(file) => server.install_mod_file(&file, None).await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            error!("Failed to install {} on server {} from template: {}", template_mod.name, server.id, e);
+            failed.push(template_mod.name.clone());
+        }
+        let _ = action.update_progress(((index + 1) * 100 / mods.len()) as i64).await;
+    }
+
+    if failed.is_empty() {
+        let _ = action.update_status(ActionStatus::Completed, None).await;
+    } else {
+        let _ = action.update_status(ActionStatus::Failed, Some(format!("Failed to install: {}", failed.join(", ")))).await;
+    }
+}
@@ -1,5 +1,7 @@
 use crate::broadcast;
 use crate::broadcast::broadcast_data::BroadcastMessage;
+use crate::server::crash_alert;
+use crate::server::port_forwarding::ServerPortForwards;
 use crate::server::server_data::ServerData;
 use crate::server::server_status::ServerStatus;
 use log::{debug, error};
@@ -56,6 +58,7 @@ impl WebEventHandler {
                 self.server_port, self.server_id, e
             );
         }
+        ServerPortForwards::global().close(self.server_id).await;
     }
 
     async fn send_notification(
@@ -125,6 +128,9 @@ impl ServerEventHandler for WebEventHandler {
                 )
                 .await;
             }
+            ServerEvent::Ready { startup_time } => {
+                debug!("Server {} ready after {:?}", self.server_id, startup_time);
+            }
             ServerEvent::Stopped => {
                 debug!("Server {} stopped", self.server_id);
                 self.cleanup_upnp().await;
@@ -136,27 +142,84 @@ impl ServerEventHandler for WebEventHandler {
                 )
                 .await;
             }
-            ServerEvent::Crashed { exit_code } => {
+            ServerEvent::Crashed {
+                exit_code,
+                report_path,
+                summary,
+            } => {
                 error!(
                     "Server {} crashed with exit code {}",
                     self.server_id, exit_code
                 );
+                if let Some(summary) = &summary {
+                    error!("Server {} crash summary: {}", self.server_id, summary);
+                }
+                if let Some(path) = &report_path {
+                    debug!("Server {} crash report: {}", self.server_id, path.display());
+                }
                 self.cleanup_upnp().await;
+                let pool = crate::database::get_pool();
+                if let Err(e) = crash_alert::alert_crash(self.server_id, &self.server_name, summary, pool).await {
+                    error!("Failed to send crash notification: {}", e);
+                }
+            }
+            ServerEvent::Restarting { attempt, delay_secs } => {
+                debug!(
+                    "Server {} restarting in {}s (attempt {})",
+                    self.server_id, delay_secs, attempt
+                );
+            }
+            ServerEvent::CrashLoopDetected { attempts } => {
+                error!(
+                    "Server {} crashed {} times in a row, automatic restarts stopped",
+                    self.server_id, attempts
+                );
                 use crate::notifications::NotificationActionType;
                 self.send_notification(
-                    format!("{} Crashed", self.server_name),
-                    format!("Server \"{}\" has crashed unexpectedly.", self.server_name),
-                    NotificationActionType::RestartServer.to_bits()
+                    format!("{} Keeps Crashing", self.server_name),
+                    format!(
+                        "Server \"{}\" crashed {} times in a row and will not be restarted automatically.",
+                        self.server_name, attempts
+                    ),
+                    NotificationActionType::StartServer.to_bits()
                         | NotificationActionType::ViewDetails.to_bits(),
                 )
                 .await;
             }
+            ServerEvent::RestartWarning { seconds_remaining } => {
+                debug!(
+                    "Server {} scheduled restart in {}s",
+                    self.server_id, seconds_remaining
+                );
+            }
+            ServerEvent::ScheduledRestart => {
+                debug!("Server {} performing scheduled restart", self.server_id);
+            }
+            ServerEvent::PlayerJoined { player } => {
+                debug!("Player {} joined server {}", player, self.server_id);
+            }
+            ServerEvent::PlayerLeft { player } => {
+                debug!("Player {} left server {}", player, self.server_id);
+            }
+            ServerEvent::Chat { .. } => {
+                // Chat is part of the console stream
+            }
             ServerEvent::ConsoleOutput { .. } => {
                 // Console output is handled separately via subscribe_output()
             }
             ServerEvent::InstallProgress { .. } => {
                 // Installation progress can be forwarded via broadcast if needed
             }
+            ServerEvent::UpgradeProgress {
+                from_version,
+                to_version,
+                stage,
+            } => {
+                debug!(
+                    "Server {} upgrade {} -> {}: {:?}",
+                    self.server_id, from_version, to_version, stage
+                );
+            }
             ServerEvent::JavaVersionError => {
                 error!(
                     "Java version mismatch detected for server {}",
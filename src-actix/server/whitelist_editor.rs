@@ -0,0 +1,97 @@
+use crate::server::console_socket::CommandSender;
+use anyhow::Result;
+use minecraft_server::mojang::MojangClient;
+use minecraft_server::{Whitelist, WhitelistEntry};
+use std::path::Path;
+
+/// Whitelist a player by name or UUID, resolving the other through Mojang. `whitelist.json` is
+/// always updated; `console` is set while the server is running, so the live whitelist matches.
+pub async fn add(directory: &Path, name_or_uuid: &str, client: MojangClient, console: Option<&CommandSender>) -> Result<WhitelistEntry> {
+    let mut whitelist = Whitelist::load(directory)?.with_client(client);
+    let entry = whitelist.add(name_or_uuid).await?;
+    whitelist.save()?;
+    if let Some(console) = console {
+        console(format!("whitelist add {}", entry.name)).await?;
+    }
+    Ok(entry)
+}
+
+/// Remove a player by name or UUID, returning `None` if they weren't whitelisted
+pub async fn remove(directory: &Path, name_or_uuid: &str, console: Option<&CommandSender>) -> Result<Option<WhitelistEntry>> {
+    let mut whitelist = Whitelist::load(directory)?;
+    let Some(entry) = whitelist.remove(name_or_uuid) else {
+        return Ok(None);
+    };
+    whitelist.save()?;
+    if let Some(console) = console {
+        console(format!("whitelist remove {}", entry.name)).await?;
+    }
+    Ok(Some(entry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::sync::{Arc, Mutex};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const NOTCH_UUID: &str = "069a79f4-44e9-4726-a5be-fca90e38aaf5";
+
+    async fn mock_mojang() -> (MockServer, MojangClient) {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/users/profiles/minecraft/notch"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "id": NOTCH_UUID.replace('-', ""), "name": "Notch" })))
+            .mount(&server)
+            .await;
+        let client = MojangClient::with_base_urls(server.uri(), server.uri());
+        (server, client)
+    }
+
+    /// A console that records the commands sent to it
+    fn recording_console() -> (CommandSender, Arc<Mutex<Vec<String>>>) {
+        let commands = Arc::new(Mutex::new(Vec::new()));
+        let sent = commands.clone();
+        let console: CommandSender = Arc::new(move |command| {
+            sent.lock().unwrap().push(command);
+            Box::pin(async { Ok(()) })
+        });
+        (console, commands)
+    }
+
+    fn whitelist_json(dir: &Path) -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(dir.join("whitelist.json")).unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_whitelist_while_stopped_only_writes_file() {
+        let dir = temp_dir("whitelist-stopped");
+        let (_server, client) = mock_mojang().await;
+
+        let entry = add(&dir, "notch", client, None).await.unwrap();
+        assert_eq!(entry, WhitelistEntry { uuid: NOTCH_UUID.to_string(), name: "Notch".to_string() });
+        assert_eq!(whitelist_json(&dir), serde_json::json!([{ "uuid": NOTCH_UUID, "name": "Notch" }]));
+
+        assert_eq!(remove(&dir, NOTCH_UUID, None).await.unwrap(), Some(entry));
+        assert_eq!(whitelist_json(&dir), serde_json::json!([]));
+        assert_eq!(remove(&dir, "Notch", None).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_whitelist_while_running_updates_the_live_whitelist() {
+        let dir = temp_dir("whitelist-running");
+        let (_server, client) = mock_mojang().await;
+        let (console, commands) = recording_console();
+
+        add(&dir, "notch", client, Some(&console)).await.unwrap();
+        assert_eq!(whitelist_json(&dir)[0]["name"], "Notch");
+        remove(&dir, "NOTCH", Some(&console)).await.unwrap();
+        assert_eq!(whitelist_json(&dir), serde_json::json!([]));
+
+        // Players that weren't whitelisted don't reach the server
+        remove(&dir, "Notch", Some(&console)).await.unwrap();
+        assert_eq!(*commands.lock().unwrap(), vec!["whitelist add Notch", "whitelist remove Notch"]);
+    }
+}
@@ -4,3 +4,4 @@ pub mod settings_endpoint;
 pub use settings_endpoint::configure;
 pub use settings_endpoint::initialize_settings_path;
 pub use settings_endpoint::load_settings;
+pub use settings_endpoint::save_settings;
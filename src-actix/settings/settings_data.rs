@@ -1,5 +1,7 @@
+use oim::ReleaseChannel;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Settings {
@@ -7,6 +9,8 @@ pub struct Settings {
     pub network: NetworkSettings,
     pub storage: StorageSettings,
     pub java: JavaSettings,
+    #[serde(default)]
+    pub updates: UpdateSettings,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +39,12 @@ pub struct JavaSettings {
     pub default_runtime: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateSettings {
+    /// Which releases the self-updater offers: stable only, or betas and alphas too
+    pub channel: ReleaseChannel,
+}
+
 
 impl Default for GeneralSettings {
     fn default() -> Self {
@@ -65,31 +75,220 @@ impl Default for StorageSettings {
     }
 }
 
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self {
+            channel: ReleaseChannel::Release,
+        }
+    }
+}
+
+
+/// A setting that was rejected, identified by its dotted path (e.g. `general.port`)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: impl Into<String>) -> Self {
+        Self { field: field.to_string(), message: message.into() }
+    }
+}
 
 impl Settings {
+    /// Read settings from a posted JSON body, with an error for each field that is missing or
+    /// has the wrong type
+    pub fn from_json(value: &Value) -> Result<Self, Vec<FieldError>> {
+        let mut reader = FieldReader { root: value, errors: Vec::new() };
+        let settings = Self {
+            general: GeneralSettings { port: reader.port("general.port"), auto_start: reader.bool("general.auto_start") },
+            network: NetworkSettings { auto_port_forward: reader.bool("network.auto_port_forward"), upnp_enabled: reader.bool("network.upnp_enabled") },
+            storage: StorageSettings {
+                servers_directory: reader.path("storage.servers_directory"),
+                java_directory: reader.path("storage.java_directory"),
+                backups_directory: reader.path("storage.backups_directory"),
+                temp_directory: reader.path("storage.temp_directory"),
+            },
+            java: JavaSettings { default_runtime: reader.optional_string("java.default_runtime") },
+            updates: UpdateSettings { channel: reader.optional_channel("updates.channel").unwrap_or(ReleaseChannel::Release) },
+        };
+
+        if reader.errors.is_empty() { Ok(settings) } else { Err(reader.errors) }
+    }
+
     /// Validate settings to ensure paths exist or can be created
-    pub fn validate(&self) -> Result<(), String> {
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        if self.general.port == 0 {
+            errors.push(FieldError::new("general.port", "Port must be greater than 0"));
+        } else if self.general.port < 1024 {
+            errors.push(FieldError::new("general.port", "Port must be 1024 or higher (privileged ports restricted)"));
+        }
+
         let paths = [
-            &self.storage.servers_directory,
-            &self.storage.java_directory,
-            &self.storage.backups_directory,
-            &self.storage.temp_directory,
+            ("storage.servers_directory", &self.storage.servers_directory),
+            ("storage.java_directory", &self.storage.java_directory),
+            ("storage.backups_directory", &self.storage.backups_directory),
+            ("storage.temp_directory", &self.storage.temp_directory),
         ];
-
-        for path in paths {
-            if path.to_string_lossy().is_empty() {
-                return Err("Path cannot be empty".to_string());
+        for (field, path) in paths {
+            if let Err(message) = check_directory(path) {
+                errors.push(FieldError::new(field, message));
             }
         }
 
-        if self.general.port == 0 {
-            return Err("Port must be greater than 0".to_string());
+        if self.java.default_runtime.as_deref().is_some_and(|runtime| runtime.trim().is_empty()) {
+            errors.push(FieldError::new("java.default_runtime", "Runtime cannot be empty"));
         }
 
-        if self.general.port < 1024 {
-            return Err("Port must be 1024 or higher (privileged ports restricted)".to_string());
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+/// A directory setting is valid if it is a directory, or if it can be created because the
+/// closest path above it that exists is a directory
+fn check_directory(path: &Path) -> Result<(), String> {
+    if path.as_os_str().is_empty() {
+        return Err("Path cannot be empty".to_string());
+    }
+    if path.components().any(|component| component == Component::ParentDir) {
+        return Err("Path cannot contain '..'".to_string());
+    }
+    if path.exists() {
+        return if path.is_dir() { Ok(()) } else { Err("Path is not a directory".to_string()) };
+    }
+
+    let existing = path.ancestors().skip(1).map(|ancestor| if ancestor.as_os_str().is_empty() { Path::new(".") } else { ancestor }).find(|ancestor| ancestor.exists());
+    match existing {
+        Some(ancestor) if ancestor.is_dir() => Ok(()),
+        Some(ancestor) => Err(format!("Path cannot be created because {} is a file", ancestor.display())),
+        None => Err("Path does not exist and cannot be created".to_string()),
+    }
+}
+
+/// Reads typed fields out of a JSON object, collecting an error for each one that doesn't fit
+struct FieldReader<'a> {
+    root: &'a Value,
+    errors: Vec<FieldError>,
+}
+
+impl FieldReader<'_> {
+    fn get(&self, field: &str) -> Option<&Value> {
+        field.split('.').try_fold(self.root, |value, key| value.get(key)).filter(|value| !value.is_null())
+    }
+
+    fn read<T>(&mut self, field: &str, expected: &str, convert: impl FnOnce(&Value) -> Option<T>) -> Option<T> {
+        let Some(value) = self.get(field) else {
+            self.errors.push(FieldError::new(field, "This setting is required"));
+            return None;
+        };
+        let converted = convert(value);
+        if converted.is_none() {
+            self.errors.push(FieldError::new(field, format!("Must be {}", expected)));
         }
+        converted
+    }
+
+    fn port(&mut self, field: &str) -> u16 {
+        self.read(field, "a port number between 1 and 65535", |value| value.as_u64().and_then(|port| u16::try_from(port).ok())).unwrap_or_default()
+    }
+
+    fn bool(&mut self, field: &str) -> bool {
+        self.read(field, "true or false", Value::as_bool).unwrap_or_default()
+    }
+
+    fn path(&mut self, field: &str) -> PathBuf {
+        self.read(field, "a path", |value| value.as_str().map(PathBuf::from)).unwrap_or_default()
+    }
+
+    fn optional_string(&mut self, field: &str) -> Option<String> {
+        self.get(field)?;
+        self.read(field, "a string", |value| value.as_str().map(String::from))
+    }
+
+    fn optional_channel(&mut self, field: &str) -> Option<ReleaseChannel> {
+        self.get(field)?;
+        self.read(field, "Release, Beta or Alpha", |value| serde_json::from_value(value.clone()).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use serde_json::json;
+
+    fn settings_json(dir: &Path) -> Value {
+        json!({
+            "general": { "port": 8080, "auto_start": false },
+            "network": { "auto_port_forward": false, "upnp_enabled": true },
+            "storage": {
+                "servers_directory": dir.join("servers"),
+                "java_directory": dir.join("java"),
+                "backups_directory": dir,
+                "temp_directory": dir.join("temp"),
+            },
+            "java": { "default_runtime": "gamma" },
+            "updates": { "channel": "Beta" }
+        })
+    }
+
+    fn fields(errors: Vec<FieldError>) -> Vec<String> {
+        errors.into_iter().map(|error| error.field).collect()
+    }
+
+    #[test]
+    fn test_valid_settings() {
+        let settings = Settings::from_json(&settings_json(&std::env::temp_dir())).unwrap();
+        assert_eq!(settings.general.port, 8080);
+        assert_eq!(settings.java.default_runtime.as_deref(), Some("gamma"));
+        assert_eq!(settings.updates.channel, ReleaseChannel::Beta);
+        assert!(settings.validate().is_ok());
+
+        let mut without_optional = settings_json(&std::env::temp_dir());
+        without_optional["java"] = json!({});
+        without_optional.as_object_mut().unwrap().remove("updates");
+        let settings = Settings::from_json(&without_optional).unwrap();
+        assert_eq!(settings.java.default_runtime, None);
+        assert_eq!(settings.updates.channel, ReleaseChannel::Release);
+    }
+
+    #[test]
+    fn test_type_errors_name_each_field() {
+        let mut value = settings_json(&std::env::temp_dir());
+        value["general"]["port"] = json!("eighty");
+        value["network"]["upnp_enabled"] = json!("yes");
+        value["storage"].as_object_mut().unwrap().remove("temp_directory");
+        value["updates"]["channel"] = json!("nightly");
+
+        let errors = Settings::from_json(&value).unwrap_err();
+        assert_eq!(fields(errors.clone()), vec!["general.port", "network.upnp_enabled", "storage.temp_directory", "updates.channel"]);
+        assert_eq!(errors[0].message, "Must be a port number between 1 and 65535");
+
+        value["general"]["port"] = json!(70000);
+        assert!(fields(Settings::from_json(&value).unwrap_err()).contains(&"general.port".to_string()));
+    }
+
+    #[test]
+    fn test_invalid_values_are_rejected() {
+        let dir = temp_dir("settings");
+        let file = dir.join("not-a-directory");
+        std::fs::write(&file, "").unwrap();
+
+        let mut value = settings_json(&dir);
+        value["general"]["port"] = json!(80);
+        value["storage"]["servers_directory"] = json!(file.join("servers"));
+        value["storage"]["java_directory"] = json!("../java");
+        value["storage"]["backups_directory"] = json!(file);
+        value["storage"]["temp_directory"] = json!("");
 
-        Ok(())
+        let errors = Settings::from_json(&value).unwrap().validate().unwrap_err();
+        assert_eq!(
+            fields(errors),
+            vec!["general.port", "storage.servers_directory", "storage.java_directory", "storage.backups_directory", "storage.temp_directory"]
+        );
     }
 }
@@ -1,9 +1,11 @@
 use crate::actix_util::http_error::Result;
+use crate::audit;
+use crate::audit::AuditAction;
 use crate::authentication::auth_data::UserRequestExt;
 use crate::authentication::user_permissions::PermissionFlag;
 use crate::server::server_data::ServerData;
 use crate::server::server_status::ServerStatus;
-use crate::settings::settings_data::Settings;
+use crate::settings::settings_data::{FieldError, Settings};
 use actix_web::{HttpRequest, HttpResponse, Responder, get, post, put, web};
 use anyhow::anyhow;
 use log::{info, error};
@@ -42,7 +44,7 @@ pub fn load_settings() -> Result<Settings> {
 }
 
 /// Save settings to JSON file
-fn save_settings(settings: &Settings) -> Result<()> {
+pub fn save_settings(settings: &Settings) -> Result<()> {
     let path = get_settings_path();
 
     // Ensure parent directory exists
@@ -271,6 +273,17 @@ fn migrate_java_installations(old_dir: &PathBuf, new_dir: &PathBuf) -> (bool, Op
     }
 }
 
+/// Read and validate posted settings, returning a field-level error response if they are invalid
+fn parse_settings(body: &serde_json::Value) -> std::result::Result<Settings, Vec<FieldError>> {
+    let settings = Settings::from_json(body)?;
+    settings.validate()?;
+    Ok(settings)
+}
+
+fn invalid_settings_error(errors: &[FieldError]) -> String {
+    errors.iter().map(|error| format!("{}: {}", error.field, error.message)).collect::<Vec<_>>().join("; ")
+}
+
 /// GET /api/settings - Get current settings
 #[get("")]
 pub async fn get_settings(req: HttpRequest) -> Result<impl Responder> {
@@ -301,7 +314,7 @@ pub async fn get_settings(req: HttpRequest) -> Result<impl Responder> {
 
 /// PUT /api/settings - Update settings
 #[put("")]
-pub async fn update_settings(req: HttpRequest, body: web::Json<Settings>) -> Result<impl Responder> {
+pub async fn update_settings(req: HttpRequest, body: web::Json<serde_json::Value>) -> Result<impl Responder> {
     let user = req.get_user()?;
 
     // Check permissions
@@ -323,18 +336,17 @@ pub async fn update_settings(req: HttpRequest, body: web::Json<Settings>) -> Res
         })));
     }
 
-    let new_settings = body.into_inner();
-
-    // Validate settings
-    if let Err(e) = new_settings.validate() {
-        return Ok(HttpResponse::BadRequest().json(json!({
-            "message": "Invalid settings",
-            "error": e,
-        })));
-    }
-
-    // Validate that the java_directory doesn't contain path traversal sequences
-    crate::actix_util::path_sanitize::reject_path_traversal(&new_settings.storage.java_directory)?;
+    // Validate settings before anything is moved or written
+    let new_settings = match parse_settings(&body) {
+        Ok(settings) => settings,
+        Err(errors) => {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "message": "Invalid settings",
+                "error": invalid_settings_error(&errors),
+                "errors": errors,
+            })));
+        }
+    };
 
     // Load old settings to check for directory changes
     let old_settings = load_settings().ok();
@@ -370,6 +382,8 @@ pub async fn update_settings(req: HttpRequest, body: web::Json<Settings>) -> Res
         }
 
     save_settings(&new_settings)?;
+    crate::updater::set_channel(new_settings.updates.channel).await;
+    audit::record(user.id.ok_or(anyhow!("User ID not found"))?, AuditAction::SettingsChange, "settings").await;
 
     Ok(HttpResponse::Ok().json(json!({
         "message": "Settings updated successfully",
@@ -379,7 +393,7 @@ pub async fn update_settings(req: HttpRequest, body: web::Json<Settings>) -> Res
 
 /// POST /api/settings/validate - Validate settings without saving
 #[post("/validate")]
-pub async fn validate_settings(req: HttpRequest, body: web::Json<Settings>) -> Result<impl Responder> {
+pub async fn validate_settings(req: HttpRequest, body: web::Json<serde_json::Value>) -> Result<impl Responder> {
     let user = req.get_user()?;
 
     // Check permissions
@@ -389,17 +403,16 @@ pub async fn validate_settings(req: HttpRequest, body: web::Json<Settings>) -> R
         })));
     }
 
-    let settings = body.into_inner();
-
-    match settings.validate() {
+    match parse_settings(&body) {
         Ok(_) => Ok(HttpResponse::Ok().json(json!({
             "valid": true,
             "message": "Settings are valid",
         }))),
-        Err(e) => Ok(HttpResponse::BadRequest().json(json!({
+        Err(errors) => Ok(HttpResponse::BadRequest().json(json!({
             "valid": false,
             "message": "Invalid settings",
-            "error": e,
+            "error": invalid_settings_error(&errors),
+            "errors": errors,
         }))),
     }
 }
@@ -407,3 +420,58 @@ pub async fn validate_settings(req: HttpRequest, body: web::Json<Settings>) -> R
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(web::scope("/settings").service(get_settings).service(update_settings).service(validate_settings));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authentication::auth_data::UserData;
+    use crate::test_util::temp_dir;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+    use actix_web::{App, HttpMessage};
+
+    #[actix_web::test]
+    async fn test_settings_are_only_written_when_valid() {
+        let dir = temp_dir("settings-endpoint");
+        let path = SETTINGS_FILE_PATH.get_or_init(|| dir.join("settings.json"));
+
+        let mut settings = Settings::default();
+        settings.storage.servers_directory = dir.join("servers");
+        settings.storage.java_directory = dir.join("java");
+        settings.storage.backups_directory = dir.join("backups");
+        settings.storage.temp_directory = dir.join("temp");
+        save_settings(&settings).unwrap();
+        let saved = fs::read_to_string(path).unwrap();
+
+        let app = init_service(App::new().configure(configure)).await;
+        let put = |body: serde_json::Value| {
+            let request = TestRequest::put().uri("/settings").set_json(body).to_request();
+            request.extensions_mut().insert(UserData { id: Some(1), permissions: PermissionFlag::ManageSettings.into(), ..Default::default() });
+            request
+        };
+
+        let mut invalid = serde_json::to_value(&settings).unwrap();
+        invalid["general"]["port"] = json!("8080a");
+        invalid["storage"]["backups_directory"] = json!(path);
+        let response = call_service(&app, put(invalid)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = read_body_json(response).await;
+        assert_eq!(body["errors"][0]["field"], "general.port");
+        assert_eq!(fs::read_to_string(path).unwrap(), saved);
+
+        // Both errors are reported once the port parses
+        let mut invalid = serde_json::to_value(&settings).unwrap();
+        invalid["general"]["port"] = json!(443);
+        invalid["storage"]["backups_directory"] = json!(path);
+        let body: serde_json::Value = read_body_json(call_service(&app, put(invalid)).await).await;
+        let fields: Vec<&str> = body["errors"].as_array().unwrap().iter().map(|error| error["field"].as_str().unwrap()).collect();
+        assert_eq!(fields, vec!["general.port", "storage.backups_directory"]);
+        assert_eq!(fs::read_to_string(path).unwrap(), saved);
+
+        let mut valid = serde_json::to_value(&settings).unwrap();
+        valid["general"]["port"] = json!(25580);
+        let response = call_service(&app, put(valid)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(load_settings().unwrap().general.port, 25580);
+    }
+}
@@ -1,4 +1,5 @@
 mod updater_data;
 mod updater_service;
 mod updater_endpoint;
-pub use updater_endpoint::configure;
\ No newline at end of file
+pub use updater_endpoint::configure;
+pub use updater_endpoint::set_channel;
\ No newline at end of file
@@ -1,31 +1,18 @@
 use anyhow::{anyhow, Result};
+use oim::{GitHubAsset, InstallationConfig, InstallationManager, ReleaseChannel};
 use reqwest::Client;
-use serde::Deserialize;
+use semver::Version;
+use serde::Serialize;
+use std::path::Path;
 use tokio::fs;
 use tokio::process::Command;
 
 const GIT_ORGNAME: &str = "Obsidian-Minecraft-Server-Portal";
 const GIT_REPO: &str = "obsidian-server-panel";
+const GITHUB_API_URL: &str = "https://api.github.com";
 
-/// A constant array `CURRENT_VERSION` containing the major, minor, and patch version numbers of the crate/package.
-const CURRENT_VERSION: [&str; 3] = [
-    env!("CARGO_PKG_VERSION_MAJOR"), 
-    env!("CARGO_PKG_VERSION_MINOR"), 
-    env!("CARGO_PKG_VERSION_PATCH")
-];
-
-#[derive(Debug, Deserialize)]
-struct GitHubRelease {
-    tag_name: String,
-    assets: Vec<GitHubAsset>,
-    prerelease: bool,
-}
-
-#[derive(Debug, Deserialize)]
-struct GitHubAsset {
-    name: String,
-    browser_download_url: String,
-}
+/// The version of the running panel, including any pre-release tag such as `-alpha`
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[derive(Debug, Clone)]
 pub enum UpdateStatus {
@@ -36,52 +23,50 @@ pub enum UpdateStatus {
     UpdateFailed(String),
 }
 
+/// The newest version published on each release channel
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct ChannelVersions {
+    pub release: Option<String>,
+    pub beta: Option<String>,
+    pub alpha: Option<String>,
+}
+
 pub struct Updater {
     client: Client,
     current_version: String,
+    api_url: String,
 }
 
 impl Updater {
     pub fn new() -> Self {
-        let current_version = format!("{}.{}.{}", 
-            CURRENT_VERSION[0], 
-            CURRENT_VERSION[1], 
-            CURRENT_VERSION[2]
-        );
-        
+        Self::with_api_url(GITHUB_API_URL)
+    }
+
+    /// An updater that looks for releases at `api_url` instead of GitHub's API
+    pub fn with_api_url(api_url: impl Into<String>) -> Self {
         Self {
             client: Client::new(),
-            current_version,
+            current_version: CURRENT_VERSION.to_string(),
+            api_url: api_url.into(),
         }
     }
 
-    /// Check if a new version is available on GitHub
-    pub async fn check_for_updates(&self) -> Result<UpdateStatus> {
-        let url = format!(
-            "https://api.github.com/repos/{}/{}/releases/latest",
-            GIT_ORGNAME, GIT_REPO
-        );
-
-        let response = self.client
-            .get(&url)
-            .header("User-Agent", format!("{}-updater", GIT_REPO))
-            .send()
-            .await?;
+    /// The installation manager is only used to look up releases, so the install path is
+    /// wherever the panel is running from
+    fn installation_manager(&self) -> InstallationManager {
+        let install_path = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)).unwrap_or_default();
+        let config = InstallationConfig::new(install_path, format!("{}/{}", GIT_ORGNAME, GIT_REPO), GIT_REPO.to_string())
+            .github_api_url(self.api_url.clone());
+        InstallationManager::new(config)
+    }
 
-        if !response.status().is_success() {
-            return Err(anyhow!("Failed to fetch release info: {}", response.status()));
-        }
+    /// Check if a version newer than the running one is available on `channel`
+    pub async fn check_for_updates(&self, channel: ReleaseChannel) -> Result<UpdateStatus> {
+        let mut manager = self.installation_manager();
+        let release = manager.get_latest_release(channel).await?;
+        let latest_version = manager.latest_version().ok_or(anyhow!("Release {} has no version", release.tag_name))?;
 
-        let release: GitHubRelease = response.json().await?;
-        
-        // Skip prereleases unless explicitly wanted
-        if release.prerelease {
-            return Ok(UpdateStatus::NoUpdateAvailable);
-        }
-
-        // Compare versions
-        let latest_version = release.tag_name.trim_start_matches('v');
-        if self.is_newer_version(latest_version)? {
+        if *latest_version > Version::parse(&self.current_version)? {
             // Find the appropriate asset for the current platform
             if let Some(download_url) = self.get_platform_asset_url(&release.assets)? {
                 return Ok(UpdateStatus::UpdateAvailable {
@@ -94,6 +79,16 @@ impl Updater {
         Ok(UpdateStatus::NoUpdateAvailable)
     }
 
+    /// Get the newest version on every channel from a single fetch of the release list
+    pub async fn channel_versions(&self) -> Result<ChannelVersions> {
+        let (release, beta, alpha) = self.installation_manager().get_channel_versions().await?;
+        Ok(ChannelVersions {
+            release: release.map(|version| version.to_string()),
+            beta: beta.map(|version| version.to_string()),
+            alpha: alpha.map(|version| version.to_string()),
+        })
+    }
+
     /// Download and apply the update
     pub async fn perform_update(&self, download_url: &str) -> Result<UpdateStatus> {
         log::info!("Starting update download from: {}", download_url);
@@ -153,28 +148,6 @@ impl Updater {
         std::process::exit(0);
     }
 
-    /// Compare version strings using semantic versioning
-    fn is_newer_version(&self, other: &str) -> Result<bool> {
-        let current = self.parse_version(&self.current_version)?;
-        let other = self.parse_version(other)?;
-        
-        Ok(other > current)
-    }
-
-    /// Parse version string into comparable tuple
-    fn parse_version(&self, version: &str) -> Result<(u32, u32, u32)> {
-        let parts: Vec<&str> = version.split('.').collect();
-        if parts.len() != 3 {
-            return Err(anyhow!("Invalid version format: {}", version));
-        }
-
-        let major = parts[0].parse::<u32>()?;
-        let minor = parts[1].parse::<u32>()?;
-        let patch = parts[2].parse::<u32>()?;
-
-        Ok((major, minor, patch))
-    }
-
     /// Get the appropriate download URL for the current platform
     fn get_platform_asset_url(&self, assets: &[GitHubAsset]) -> Result<Option<String>> {
         let os = std::env::consts::OS;
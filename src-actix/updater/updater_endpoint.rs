@@ -1,7 +1,10 @@
-use crate::actix_util::http_error::Result;
+use crate::actix_util::http_error::{Error, Result};
 use crate::authentication::auth_data::UserRequestExt;
+use crate::settings::{load_settings, save_settings};
 use crate::updater::updater_service::UpdateService;
-use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{get, post, put, web, HttpRequest, HttpResponse, Responder, Result as ActixResult};
+use anyhow::anyhow;
+use oim::ReleaseChannel;
 use serde_json::json;
 use std::sync::{Arc, OnceLock};
 
@@ -12,6 +15,11 @@ fn get_update_service() -> &'static Arc<UpdateService> {
     UPDATE_SERVICE.get_or_init(|| Arc::new(UpdateService::new()))
 }
 
+/// Point the next update check at `channel`, after it has been saved to the settings
+pub async fn set_channel(channel: ReleaseChannel) {
+    get_update_service().set_channel(channel).await;
+}
+
 #[get("")]
 pub async fn get_current_version(req: HttpRequest) -> Result<impl Responder> {
     let user = req.get_user()?;
@@ -104,6 +112,65 @@ pub async fn get_update_status(req: HttpRequest) -> Result<impl Responder> {
     }
 }
 
+#[get("channel")]
+pub async fn get_update_channel(req: HttpRequest) -> Result<impl Responder> {
+    let user = req.get_user()?;
+
+    // Only admins can check for updates
+    if !user.is_admin() {
+        return Ok(HttpResponse::Forbidden().json(json!({
+            "error": "You don't have permission to check for updates"
+        })));
+    }
+
+    let channel = get_update_service().channel().await;
+    Ok(HttpResponse::Ok().json(json!({
+        "channel": channel,
+        "display_name": channel.display_name()
+    })))
+}
+
+#[put("channel")]
+pub async fn set_update_channel(req: HttpRequest, body: web::Json<serde_json::Value>) -> Result<impl Responder> {
+    let user = req.get_user()?;
+
+    // Only admins can change where updates come from
+    if !user.is_admin() {
+        return Ok(HttpResponse::Forbidden().json(json!({
+            "error": "You don't have permission to change the update channel"
+        })));
+    }
+
+    let channel: ReleaseChannel = body
+        .get("channel")
+        .and_then(|channel| serde_json::from_value(channel.clone()).ok())
+        .ok_or(anyhow!("channel must be one of Release, Beta or Alpha"))?;
+
+    let mut settings = load_settings()?;
+    settings.updates.channel = channel;
+    save_settings(&settings)?;
+    set_channel(channel).await;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "channel": channel,
+        "display_name": channel.display_name()
+    })))
+}
+
+#[get("versions")]
+pub async fn get_channel_versions(req: HttpRequest) -> ActixResult<impl Responder> {
+    let user = req.get_user().map_err(Error::from)?;
+
+    // Only admins can check for updates
+    if !user.is_admin() {
+        return Ok(HttpResponse::Forbidden().json(json!({
+            "error": "You don't have permission to check for updates"
+        })));
+    }
+
+    get_update_service().channel_versions().await
+}
+
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/updater")
@@ -111,6 +178,9 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .service(check_for_updates)
             .service(perform_update)
             .service(get_update_status)
+            .service(get_update_channel)
+            .service(set_update_channel)
+            .service(get_channel_versions)
             .default_service(web::to(|| async {
                 HttpResponse::NotFound().json(json!({
                     "error": "API endpoint not found".to_string(),
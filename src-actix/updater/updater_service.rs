@@ -1,6 +1,8 @@
 // update_service.rs
+use crate::settings::load_settings;
 use crate::updater::updater_data::{UpdateStatus, Updater};
 use actix_web::{HttpResponse, Result as ActixResult};
+use oim::ReleaseChannel;
 use serde_json::json;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -8,16 +10,37 @@ use tokio::sync::RwLock;
 pub struct UpdateService {
     pub(crate) updater: Arc<Updater>,
     status: Arc<RwLock<UpdateStatus>>,
+    channel: RwLock<ReleaseChannel>,
 }
 
 impl UpdateService {
     pub fn new() -> Self {
-        Self { updater: Arc::new(Updater::new()), status: Arc::new(RwLock::new(UpdateStatus::NoUpdateAvailable)) }
+        let channel = load_settings().map(|settings| settings.updates.channel).unwrap_or(ReleaseChannel::Release);
+        Self::with_updater(Updater::new(), channel)
+    }
+
+    pub fn with_updater(updater: Updater, channel: ReleaseChannel) -> Self {
+        Self { updater: Arc::new(updater), status: Arc::new(RwLock::new(UpdateStatus::NoUpdateAvailable)), channel: RwLock::new(channel) }
+    }
+
+    /// The channel the next update check looks at
+    pub async fn channel(&self) -> ReleaseChannel {
+        *self.channel.read().await
+    }
+
+    /// Switch channels, forgetting an update found on the old channel so it can't be installed
+    pub async fn set_channel(&self, channel: ReleaseChannel) {
+        *self.channel.write().await = channel;
+        let mut status = self.status.write().await;
+        if matches!(*status, UpdateStatus::UpdateAvailable { .. }) {
+            *status = UpdateStatus::NoUpdateAvailable;
+        }
     }
 
     /// Check for updates endpoint
     pub async fn check_updates(&self) -> ActixResult<HttpResponse> {
-        match self.updater.check_for_updates().await {
+        let channel = self.channel().await;
+        match self.updater.check_for_updates(channel).await {
             Ok(status) => {
                 *self.status.write().await = status.clone();
 
@@ -26,16 +49,19 @@ impl UpdateService {
                         "update_available": true,
                         "current_version": self.updater.current_version(),
                         "latest_version": version,
+                        "channel": channel,
                         "status": "available"
                     }),
                     UpdateStatus::NoUpdateAvailable => json!({
                         "update_available": false,
                         "current_version": self.updater.current_version(),
+                        "channel": channel,
                         "status": "up_to_date"
                     }),
                     _ => json!({
                         "update_available": false,
                         "current_version": self.updater.current_version(),
+                        "channel": channel,
                         "status": "checking"
                     }),
                 };
@@ -101,6 +127,24 @@ impl UpdateService {
         }
     }
 
+    /// Latest version on every channel endpoint
+    pub async fn channel_versions(&self) -> ActixResult<HttpResponse> {
+        match self.updater.channel_versions().await {
+            Ok(versions) => Ok(HttpResponse::Ok().json(json!({
+                "current_version": self.updater.current_version(),
+                "channel": self.channel().await,
+                "versions": versions
+            }))),
+            Err(e) => {
+                log::error!("Failed to fetch channel versions: {}", e);
+                Ok(HttpResponse::InternalServerError().json(json!({
+                    "error": "Failed to fetch channel versions",
+                    "message": e.to_string()
+                })))
+            }
+        }
+    }
+
     /// Get current update status
     pub async fn get_status(&self) -> ActixResult<HttpResponse> {
         let status = self.status.read().await.clone();
@@ -133,3 +177,75 @@ impl UpdateService {
         Ok(HttpResponse::Ok().json(response))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::to_bytes;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn release(tag: &str, prerelease: bool) -> serde_json::Value {
+        json!({
+            "tag_name": tag,
+            "name": tag,
+            "prerelease": prerelease,
+            "assets": [{ "name": "obsidian-server-panel", "browser_download_url": format!("https://example.com/{}/obsidian-server-panel", tag), "size": 1024 }]
+        })
+    }
+
+    /// GitHub's release list, newest first, with a release on each channel
+    async fn mock_github() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/Obsidian-Minecraft-Server-Portal/obsidian-server-panel/releases"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                release("v99.1.0-alpha.2", true),
+                release("v99.0.0-beta.1", true),
+                release("v98.0.0", false)
+            ])))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    async fn body(response: HttpResponse) -> serde_json::Value {
+        serde_json::from_slice(&to_bytes(response.into_body()).await.unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_update_check_uses_the_stored_channel() {
+        let server = mock_github().await;
+        let service = UpdateService::with_updater(Updater::with_api_url(server.uri()), ReleaseChannel::Release);
+
+        let response = body(service.check_updates().await.unwrap()).await;
+        assert_eq!(response["channel"], "Release");
+        assert_eq!(response["latest_version"], "98.0.0");
+
+        service.set_channel(ReleaseChannel::Beta).await;
+        let response = body(service.check_updates().await.unwrap()).await;
+        assert_eq!(response["channel"], "Beta");
+        assert_eq!(response["latest_version"], "99.0.0-beta.1");
+        assert!(matches!(&*service.status.read().await, UpdateStatus::UpdateAvailable { download_url, .. } if download_url.contains("v99.0.0-beta.1")));
+
+        // The beta found above can't be installed once the channel is back on stable releases
+        service.set_channel(ReleaseChannel::Release).await;
+        assert!(matches!(*service.status.read().await, UpdateStatus::NoUpdateAvailable));
+    }
+
+    #[tokio::test]
+    async fn test_channel_versions() {
+        let server = mock_github().await;
+        let service = UpdateService::with_updater(Updater::with_api_url(server.uri()), ReleaseChannel::Alpha);
+
+        let response = body(service.channel_versions().await.unwrap()).await;
+        assert_eq!(
+            response,
+            json!({
+                "current_version": env!("CARGO_PKG_VERSION"),
+                "channel": "Alpha",
+                "versions": { "release": "98.0.0", "beta": "99.0.0-beta.1", "alpha": "99.1.0-alpha.2" }
+            })
+        );
+    }
+}
@@ -273,8 +273,17 @@ export function ServerBackups()
 
             if (response.ok)
             {
-                onRestoreClose();
-                setRestoreBackupId(null);
+                // Progress is streamed as server-sent events, ending with "completed" or "failed"
+                const events = await response.text();
+                const failed = events.split("\n\n").find(event => event.includes("event: failed"));
+                if (failed)
+                {
+                    console.error("Failed to restore backup:", failed.split("data: ")[1]);
+                } else
+                {
+                    onRestoreClose();
+                    setRestoreBackupId(null);
+                }
             } else
             {
                 const error = await response.json();
@@ -900,9 +909,8 @@ export function ServerBackups()
                                 Are you sure you want to restore from this backup? This will:
                             </p>
                             <ul className="list-disc list-inside text-sm text-default-600 ml-4">
-                                <li>Stop the server if it's currently running</li>
                                 <li>Replace all server files with the backup data</li>
-                                <li>You may need to restart the server manually</li>
+                                <li>Stop a running server during the restore and start it again afterwards</li>
                             </ul>
                         </div>
                     </ModalBody>
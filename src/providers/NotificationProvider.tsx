@@ -208,6 +208,11 @@ export function NotificationProvider({children}: { children: ReactNode })
                             window.dispatchEvent(new CustomEvent('server-ping', {detail: {serverId: message.server_id, ping: message.ping}}));
                             break;
 
+                        case 'server_crashed':
+                            console.warn('[NotificationProvider] Server crashed:', message.server_name, message.summary);
+                            window.dispatchEvent(new CustomEvent('server-crashed', {detail: {serverId: message.server_id, serverName: message.server_name, summary: message.summary}}));
+                            break;
+
                         // Action update messages
                         case 'action_update':
                             console.log('[NotificationProvider] Action update:', message.action);
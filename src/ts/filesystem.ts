@@ -19,6 +19,7 @@ type FilesystemSearchResult = {
     filename: string;
     path: string;
     size: number;
+    is_dir: boolean;
     ctime: number;
     mtime: number;
 }
@@ -32,6 +33,11 @@ export interface FilesystemData
     entries: FilesystemEntry[];
 }
 
+/**
+ * What to do when a copied or moved entry's name is already taken in the destination
+ */
+export type ConflictStrategy = "overwrite" | "skip" | "rename";
+
 /**
  * FileSystem class for handling filesystem operations
  * Provides methods to browse directories and download files
@@ -132,11 +138,11 @@ export class FileSystem
     }
 
 
-    static async copyEntry(sourcePaths: string[], destinationPath: string, serverId: string): Promise<void>
+    static async copyEntry(sourcePaths: string[], destinationPath: string, serverId: string, conflict: ConflictStrategy = "overwrite"): Promise<void>
     {
         const response = await fetch(`/api/server/${serverId}/fs/copy`, {
             method: "POST",
-            body: JSON.stringify({entries: sourcePaths, path: destinationPath}),
+            body: JSON.stringify({entries: sourcePaths, path: destinationPath, conflict}),
             headers: {"Content-Type": "application/json"}
         });
 
@@ -147,11 +153,11 @@ export class FileSystem
         }
     }
 
-    static async moveEntry(sourcePaths: string[], destinationPath: string, serverId: string): Promise<void>
+    static async moveEntry(sourcePaths: string[], destinationPath: string, serverId: string, conflict: ConflictStrategy = "overwrite"): Promise<void>
     {
         const response = await fetch(`/api/server/${serverId}/fs/move`, {
             method: "POST",
-            body: JSON.stringify({entries: sourcePaths, path: destinationPath}),
+            body: JSON.stringify({entries: sourcePaths, path: destinationPath, conflict}),
             headers: {"Content-Type": "application/json"}
         });
 
@@ -407,7 +413,7 @@ export class FileSystem
                 size: result.size,
                 last_modified: new Date(result.mtime * 1000),
                 creation_date: new Date(result.ctime * 1000),
-                is_dir: false
+                is_dir: result.is_dir
             };
 
             if (entry.is_dir)
@@ -3,6 +3,7 @@ export interface Settings {
     network: NetworkSettings;
     storage: StorageSettings;
     java: JavaSettings;
+    updates: UpdateSettings;
 }
 
 export interface GeneralSettings {
@@ -26,6 +27,12 @@ export interface JavaSettings {
     default_runtime?: string | null;
 }
 
+export type ReleaseChannel = "Release" | "Beta" | "Alpha";
+
+export interface UpdateSettings {
+    channel: ReleaseChannel;
+}
+
 export interface ValidationResult {
     valid: boolean;
     message: string;